@@ -0,0 +1,235 @@
+//! Attribute usage statistics for a subtree: for every attribute, how many
+//! entries have it, how many values and distinct values there are, and
+//! the shortest and longest value. Useful to judge data quality before a
+//! migration.
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+
+/// Statistics of one attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeStats {
+    pub name: String,
+    /// Entries having the attribute.
+    pub entries: usize,
+    pub values: usize,
+    pub distinct: usize,
+    /// Shortest and longest value, in characters.
+    pub min_len: usize,
+    pub max_len: usize,
+}
+
+impl AttributeStats {
+    /// The share of `scanned` entries having the attribute, in percent.
+    pub fn coverage(&self, scanned: usize) -> f64 {
+        if scanned == 0 {
+            0.0
+        } else {
+            self.entries as f64 * 100.0 / scanned as f64
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SubtreeStats {
+    pub base_dn: String,
+    pub entries_scanned: usize,
+    /// Whether only a sample of the subtree was read.
+    pub sampled: bool,
+    /// Most widely populated first.
+    pub attributes: Vec<AttributeStats>,
+}
+
+impl SubtreeStats {
+    /// Write one row per attribute.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<usize, CoreError> {
+        let err = |e: csv::Error| CoreError::ExportError(e.to_string());
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record([
+            "attribute",
+            "entries",
+            "coverage",
+            "values",
+            "distinct",
+            "min_length",
+            "max_length",
+        ])
+        .map_err(err)?;
+        for a in &self.attributes {
+            csv.write_record([
+                a.name.clone(),
+                a.entries.to_string(),
+                format!("{:.1}", a.coverage(self.entries_scanned)),
+                a.values.to_string(),
+                a.distinct.to_string(),
+                a.min_len.to_string(),
+                a.max_len.to_string(),
+            ])
+            .map_err(err)?;
+        }
+        csv.flush()
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        Ok(self.attributes.len())
+    }
+}
+
+/// Running totals of one attribute. Distinct values are kept as hashes so
+/// a large scan doesn't hold every value.
+struct Totals {
+    name: String,
+    entries: usize,
+    values: usize,
+    distinct: HashSet<u64>,
+    min_len: usize,
+    max_len: usize,
+}
+
+/// Accumulates statistics a page of entries at a time.
+#[derive(Default)]
+pub struct StatsCollector {
+    entries: usize,
+    attributes: BTreeMap<String, Totals>,
+}
+
+impl StatsCollector {
+    pub fn entries(&self) -> usize {
+        self.entries
+    }
+
+    pub fn add(&mut self, entries: &[LdapEntry]) {
+        for entry in entries {
+            self.entries += 1;
+            for (name, values) in &entry.attributes {
+                let totals = self
+                    .attributes
+                    .entry(name.to_lowercase())
+                    .or_insert_with(|| Totals {
+                        name: name.clone(),
+                        entries: 0,
+                        values: 0,
+                        distinct: HashSet::new(),
+                        min_len: usize::MAX,
+                        max_len: 0,
+                    });
+                totals.entries += 1;
+                for value in values {
+                    let len = value.chars().count();
+                    totals.values += 1;
+                    totals.min_len = totals.min_len.min(len);
+                    totals.max_len = totals.max_len.max(len);
+                    let mut hasher = DefaultHasher::new();
+                    value.hash(&mut hasher);
+                    totals.distinct.insert(hasher.finish());
+                }
+            }
+        }
+    }
+
+    pub fn finish(self, base_dn: &str, sampled: bool) -> SubtreeStats {
+        let mut attributes: Vec<AttributeStats> = self
+            .attributes
+            .into_values()
+            .map(|t| AttributeStats {
+                name: t.name,
+                entries: t.entries,
+                values: t.values,
+                distinct: t.distinct.len(),
+                min_len: if t.values == 0 { 0 } else { t.min_len },
+                max_len: t.max_len,
+            })
+            .collect();
+        attributes.sort_by_key(|a| std::cmp::Reverse(a.entries));
+        SubtreeStats {
+            base_dn: base_dn.to_string(),
+            entries_scanned: self.entries,
+            sampled,
+            attributes,
+        }
+    }
+}
+
+impl LdapConnection {
+    /// Gather attribute statistics of the entries under `base_dn`: the
+    /// first `sample` entries, or all of them a page at a time, calling
+    /// `progress` with the number read so far.
+    pub async fn attribute_stats(
+        &mut self,
+        base_dn: &str,
+        sample: Option<usize>,
+        mut progress: impl FnMut(usize),
+    ) -> Result<SubtreeStats, CoreError> {
+        let mut collector = StatsCollector::default();
+        match sample {
+            Some(limit) => {
+                let entries = self
+                    .search_limited(base_dn, "(objectClass=*)", &["*"], limit)
+                    .await?;
+                collector.add(&entries);
+                progress(collector.entries());
+            }
+            None => {
+                self.search_subtree_pages(base_dn, "(objectClass=*)", &["*"], |page| {
+                    collector.add(&page);
+                    progress(collector.entries());
+                    Ok(())
+                })
+                .await?;
+            }
+        }
+        Ok(collector.finish(base_dn, sample.is_some()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_collect_stats() {
+        let mut collector = StatsCollector::default();
+        collector.add(&[
+            test_entry("cn=a", &[("cn", &["a"]), ("mail", &["a@x.com"])]),
+            test_entry(
+                "cn=b",
+                &[("cn", &["b"]), ("mail", &["a@x.com", "bee@x.com"])],
+            ),
+        ]);
+        collector.add(&[test_entry(
+            "cn=c",
+            &[("CN", &["ccc"]), ("description", &["é"])],
+        )]);
+        let stats = collector.finish("dc=x", true);
+
+        assert_eq!(stats.entries_scanned, 3);
+        assert!(stats.sampled);
+        let names: Vec<&str> = stats.attributes.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["cn", "mail", "description"]);
+
+        let mail = &stats.attributes[1];
+        assert_eq!(
+            (
+                mail.entries,
+                mail.values,
+                mail.distinct,
+                mail.min_len,
+                mail.max_len
+            ),
+            (2, 3, 2, 7, 9)
+        );
+        assert_eq!(stats.attributes[0].max_len, 3);
+        assert_eq!(stats.attributes[2].max_len, 1);
+        assert!((mail.coverage(3) - 66.7).abs() < 0.1);
+
+        let mut csv = Vec::new();
+        stats.write_csv(&mut csv).unwrap();
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.contains("\nmail,2,66.7,3,2,7,9\n"));
+    }
+}
@@ -0,0 +1,341 @@
+//! An append-only audit file of the writes sent to the server: one JSON
+//! object per line, with the values before and after each change, rotated
+//! by size. Password attributes are never written.
+
+use std::collections::{BTreeMap, HashSet};
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use chrono::Local;
+use ldap3::{LdapError, LdapResult};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::connection::LdapConnection;
+use crate::trace::TracedResult;
+
+/// Attributes whose values are replaced by [`REDACTED`].
+const SECRET_ATTRIBUTES: [&str; 5] = [
+    "userPassword",
+    "unicodePwd",
+    "sambaNTPassword",
+    "sambaLMPassword",
+    "clearTextPassword",
+];
+
+const REDACTED: &str = "(redacted)";
+
+/// `[audit]`: where writes are logged and how the file is rotated.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AuditSettings {
+    /// File the records are appended to; unset turns the audit log off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    /// Rotate the file once it reaches this many megabytes (0 never rotates).
+    pub max_size_mb: u64,
+    /// Rotated files kept, `<file>.1` being the newest.
+    pub keep: usize,
+}
+
+impl Default for AuditSettings {
+    fn default() -> Self {
+        Self {
+            file: None,
+            max_size_mb: 10,
+            keep: 5,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AuditOp {
+    Add,
+    Modify,
+    Rename,
+    Move,
+    Delete,
+}
+
+/// One line of the audit file.
+#[derive(Debug, Clone, Serialize)]
+pub struct AuditRecord {
+    /// Local time with offset, RFC 3339.
+    pub timestamp: String,
+    pub profile: String,
+    /// The DN bound as; empty for anonymous and SASL EXTERNAL binds.
+    pub bind_dn: String,
+    /// The identity the request was proxied as, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authz_id: Option<String>,
+    pub operation: AuditOp,
+    pub dn: String,
+    /// The DN after a rename or move.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_dn: Option<String>,
+    /// The changed attributes' values before the change.
+    pub before: BTreeMap<String, Vec<String>>,
+    /// The changed attributes' values after it; unchanged on failure.
+    pub after: BTreeMap<String, Vec<String>>,
+    /// The LDAP result code, or `None` when no result arrived.
+    pub result: Option<u32>,
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub message: String,
+}
+
+#[derive(Debug)]
+struct AuditFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+}
+
+impl AuditFile {
+    /// Append `line`, first rotating the file if it would grow past the
+    /// size limit.
+    fn append(&self, line: &str) -> std::io::Result<()> {
+        let size = fs::metadata(&self.path).map(|m| m.len()).unwrap_or(0);
+        if self.max_bytes > 0 && size > 0 && size + line.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        file.write_all(line.as_bytes())
+    }
+
+    /// Shift `<file>.N` to `<file>.N+1`, dropping the oldest, and the
+    /// file itself to `<file>.1`.
+    fn rotate(&self) -> std::io::Result<()> {
+        if self.keep == 0 {
+            return fs::remove_file(&self.path);
+        }
+        let _ = fs::remove_file(self.rotated(self.keep));
+        for n in (1..self.keep).rev() {
+            let from = self.rotated(n);
+            if from.exists() {
+                fs::rename(&from, self.rotated(n + 1))?;
+            }
+        }
+        fs::rename(&self.path, self.rotated(1))
+    }
+
+    fn rotated(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+}
+
+/// The audit file, shared by every connection; each clone carries the
+/// profile its records are written for.
+#[derive(Debug, Clone)]
+pub struct AuditLog {
+    file: Arc<Mutex<AuditFile>>,
+    profile: String,
+}
+
+impl AuditLog {
+    pub fn new(path: &Path, settings: &AuditSettings) -> Self {
+        Self {
+            file: Arc::new(Mutex::new(AuditFile {
+                path: path.to_path_buf(),
+                max_bytes: settings.max_size_mb.saturating_mul(1024 * 1024),
+                keep: settings.keep,
+            })),
+            profile: String::new(),
+        }
+    }
+
+    /// The same file, with records written for `profile`.
+    pub fn for_profile(&self, profile: &str) -> Self {
+        Self {
+            file: self.file.clone(),
+            profile: profile.to_string(),
+        }
+    }
+
+    pub fn write(&self, record: &AuditRecord) -> std::io::Result<()> {
+        let mut line = serde_json::to_string(record)?;
+        line.push('\n');
+        let file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+        file.append(&line)
+    }
+}
+
+/// Values of the given attributes, with password values redacted.
+pub fn redact(values: BTreeMap<String, Vec<String>>) -> BTreeMap<String, Vec<String>> {
+    values
+        .into_iter()
+        .map(|(attr, vals)| {
+            if SECRET_ATTRIBUTES
+                .iter()
+                .any(|s| s.eq_ignore_ascii_case(&attr))
+            {
+                let redacted = vals.iter().map(|_| REDACTED.to_string()).collect();
+                (attr, redacted)
+            } else {
+                (attr, vals)
+            }
+        })
+        .collect()
+}
+
+/// Attribute values given to an add, in the audit file's form.
+pub fn values_of(attrs: &[(String, HashSet<String>)]) -> BTreeMap<String, Vec<String>> {
+    attrs
+        .iter()
+        .map(|(attr, vals)| {
+            let mut vals: Vec<String> = vals.iter().cloned().collect();
+            vals.sort();
+            (attr.clone(), vals)
+        })
+        .collect()
+}
+
+impl LdapConnection {
+    /// Log this connection's writes to `log`.
+    pub fn set_audit_log(&mut self, log: Option<AuditLog>) {
+        self.audit = log;
+    }
+
+    pub fn is_audited(&self) -> bool {
+        self.audit.is_some()
+    }
+
+    /// The entry's current values of `attrs` (every user attribute when
+    /// empty), read for the audit log. Nothing is read when not auditing.
+    pub(crate) async fn audit_values(
+        &mut self,
+        dn: &str,
+        attrs: &[String],
+    ) -> BTreeMap<String, Vec<String>> {
+        if self.audit.is_none() {
+            return BTreeMap::new();
+        }
+        let mut attrs: Vec<&str> = attrs.iter().map(String::as_str).collect();
+        if attrs.is_empty() {
+            attrs.push("*");
+        }
+        match self.search_entry_attrs(dn, &attrs).await {
+            Ok(Some(entry)) => entry.attributes,
+            Ok(None) => BTreeMap::new(),
+            Err(e) => {
+                warn!("Audit: failed to read {}: {}", dn, e);
+                BTreeMap::new()
+            }
+        }
+    }
+
+    /// Append a record of a write and its result to the audit log.
+    pub(crate) fn audit(
+        &self,
+        operation: AuditOp,
+        dn: &str,
+        new_dn: Option<&str>,
+        before: BTreeMap<String, Vec<String>>,
+        after: BTreeMap<String, Vec<String>>,
+        result: &Result<LdapResult, LdapError>,
+    ) {
+        let Some(log) = &self.audit else {
+            return;
+        };
+        let (result, message) = match result {
+            Ok(result) => {
+                let (rc, text, _) = result.outcome();
+                (Some(rc), text)
+            }
+            Err(e) => (None, e.to_string()),
+        };
+        let record = AuditRecord {
+            timestamp: Local::now().to_rfc3339(),
+            profile: log.profile.clone(),
+            bind_dn: self
+                .bind_credentials
+                .as_ref()
+                .map(|(bind_dn, _)| bind_dn.clone())
+                .unwrap_or_default(),
+            authz_id: self.settings.authz_id.clone(),
+            operation,
+            dn: dn.to_string(),
+            new_dn: new_dn.map(str::to_string),
+            before: redact(before),
+            after: redact(after),
+            result,
+            message,
+        };
+        if let Err(e) = log.write(&record) {
+            warn!("Audit: failed to write the record of {}: {}", dn, e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(dn: &str) -> AuditRecord {
+        AuditRecord {
+            timestamp: "2026-01-02T03:04:05+00:00".to_string(),
+            profile: "Production".to_string(),
+            bind_dn: "cn=admin,dc=x".to_string(),
+            authz_id: None,
+            operation: AuditOp::Modify,
+            dn: dn.to_string(),
+            new_dn: None,
+            before: redact(BTreeMap::from([(
+                "userPassword".to_string(),
+                vec!["secret".to_string()],
+            )])),
+            after: BTreeMap::from([("mail".to_string(), vec!["a@x.com".to_string()])]),
+            result: Some(0),
+            message: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_audit_file_rotation() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        let line_len = serde_json::to_string(&record("cn=a,dc=x")).unwrap().len() as u64 + 1;
+        let log = AuditLog::new(
+            &path,
+            &AuditSettings {
+                file: None,
+                max_size_mb: 0,
+                keep: 2,
+            },
+        )
+        .for_profile("Production");
+        // Room for two records per file
+        log.file.lock().unwrap().max_bytes = line_len * 2;
+
+        for dn in [
+            "cn=a,dc=x",
+            "cn=b,dc=x",
+            "cn=c,dc=x",
+            "cn=d,dc=x",
+            "cn=e,dc=x",
+        ] {
+            log.write(&record(dn)).unwrap();
+        }
+        let read = |p: &Path| fs::read_to_string(p).unwrap();
+        let current = read(&path);
+        assert_eq!(current.lines().count(), 1);
+        assert!(current.contains("\"dn\":\"cn=e,dc=x\""));
+        assert!(read(&dir.path().join("audit.jsonl.1")).contains("cn=c,dc=x"));
+        assert!(read(&dir.path().join("audit.jsonl.2")).contains("cn=a,dc=x"));
+        assert!(!dir.path().join("audit.jsonl.3").exists());
+
+        let line: serde_json::Value =
+            serde_json::from_str(current.lines().next().unwrap()).unwrap();
+        assert_eq!(line["operation"], "modify");
+        assert_eq!(line["before"]["userPassword"][0], REDACTED);
+        assert_eq!(line["after"]["mail"][0], "a@x.com");
+        assert!(line.get("new_dn").is_none());
+    }
+}
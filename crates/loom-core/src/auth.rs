@@ -1,15 +1,113 @@
+use std::time::Instant;
+
+use ldap3::exop::WhoAmI;
+use ldap3::{Scope, SearchEntry};
+use serde::{Deserialize, Serialize};
+
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::trace::{TraceOp, TraceRecord};
+use crate::util::get_values;
 use tracing::{error, info};
 
+/// SASL mechanisms a profile can bind with instead of a simple bind.
+/// Only EXTERNAL is supported: the LDAP library can't carry out the
+/// challenge-response exchange DIGEST-MD5 or SCRAM need. Any other name
+/// is refused when the profile is read, saying so.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum SaslMechanism {
+    #[serde(rename = "external")]
+    External,
+}
+
+impl std::str::FromStr for SaslMechanism {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        if name.eq_ignore_ascii_case("external") {
+            return Ok(SaslMechanism::External);
+        }
+        Err(format!(
+            "SASL mechanism \"{}\" isn't supported; \"external\" is the only one. \
+             For password authentication, use a simple bind over TLS",
+            name
+        ))
+    }
+}
+
+impl<'de> Deserialize<'de> for SaslMechanism {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        name.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+impl SaslMechanism {
+    pub const ALL: [SaslMechanism; 1] = [SaslMechanism::External];
+
+    /// The name servers list in `supportedSASLMechanisms`.
+    pub fn name(self) -> &'static str {
+        match self {
+            SaslMechanism::External => "EXTERNAL",
+        }
+    }
+
+    /// Cycle from a simple bind (None) through each mechanism (for UI toggling).
+    pub fn cycle(current: Option<Self>) -> Option<Self> {
+        match current {
+            None => Some(SaslMechanism::External),
+            Some(mechanism) => Self::ALL
+                .iter()
+                .skip_while(|m| **m != mechanism)
+                .nth(1)
+                .copied(),
+        }
+    }
+}
+
+/// Check `mechanism` against the server's `supportedSASLMechanisms`.
+pub fn check_offered(mechanism: SaslMechanism, offered: &[String]) -> Result<(), CoreError> {
+    if offered
+        .iter()
+        .any(|m| m.eq_ignore_ascii_case(mechanism.name()))
+    {
+        return Ok(());
+    }
+    let offers = if offered.is_empty() {
+        "no SASL mechanisms".to_string()
+    } else {
+        offered.join(", ")
+    };
+    Err(CoreError::BindFailed(format!(
+        "The server doesn't offer SASL {}; it offers {}",
+        mechanism.name(),
+        offers
+    )))
+}
+
 impl LdapConnection {
+    /// Bind the way the settings ask: with their SASL mechanism when one
+    /// is set, otherwise a simple bind as `bind_dn`, or anonymously.
+    pub async fn bind(&mut self, bind_dn: Option<&str>, password: &str) -> Result<(), CoreError> {
+        match (self.settings.sasl_mechanism, bind_dn) {
+            (Some(mechanism), _) => self.sasl_bind(mechanism).await,
+            (None, Some(bind_dn)) => self.simple_bind(bind_dn, password).await,
+            (None, None) => self.anonymous_bind().await,
+        }
+    }
+
     /// Perform a simple bind with the given DN and password.
     pub async fn simple_bind(&mut self, bind_dn: &str, password: &str) -> Result<(), CoreError> {
+        let trace = TraceRecord::new(TraceOp::Bind, bind_dn);
+        let started = Instant::now();
+        let timeout = self.op_timeout();
         let result = self
             .ldap
+            .with_timeout(timeout)
             .simple_bind(bind_dn, password)
-            .await
-            .map_err(CoreError::Ldap)?;
+            .await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
 
         if result.rc != 0 {
             error!(
@@ -29,11 +127,12 @@ impl LdapConnection {
 
     /// Perform an anonymous bind.
     pub async fn anonymous_bind(&mut self) -> Result<(), CoreError> {
-        let result = self
-            .ldap
-            .simple_bind("", "")
-            .await
-            .map_err(CoreError::Ldap)?;
+        let trace = TraceRecord::new(TraceOp::Bind, "");
+        let started = Instant::now();
+        let timeout = self.op_timeout();
+        let result = self.ldap.with_timeout(timeout).simple_bind("", "").await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
 
         if result.rc != 0 {
             error!("Anonymous bind failed: rc={}, {}", result.rc, result.text);
@@ -46,4 +145,120 @@ impl LdapConnection {
         info!("Bound anonymously");
         Ok(())
     }
+
+    /// The SASL mechanisms the RootDSE advertises, read before binding.
+    pub async fn supported_sasl_mechanisms(&mut self) -> Result<Vec<String>, CoreError> {
+        let attrs = ["supportedSASLMechanisms"];
+        let trace = TraceRecord::new(TraceOp::Search, "")
+            .scope(Scope::Base)
+            .filter("(objectClass=*)")
+            .attributes(attrs);
+        let started = Instant::now();
+        let timeout = self.op_timeout();
+        let result = self
+            .ldap
+            .with_timeout(timeout)
+            .search("", Scope::Base, "(objectClass=*)", attrs.to_vec())
+            .await;
+        self.trace.record(trace, started, &result);
+        let (entries, _res) = result
+            .map_err(CoreError::from)?
+            .success()
+            .map_err(|e| CoreError::SearchFailed(format!("RootDSE: {}", e)))?;
+
+        Ok(entries
+            .into_iter()
+            .next()
+            .map(|e| {
+                let attrs = SearchEntry::construct(e).attrs.into_iter().collect();
+                get_values(&attrs, "supportedsaslmechanisms")
+            })
+            .unwrap_or_default())
+    }
+
+    /// The identity the server applies to this connection's requests,
+    /// from the Who Am I extended operation (RFC 4532). With an `authz_id`
+    /// the request carries the Proxied Authorization control, so this is
+    /// the proxied identity; empty when anonymous.
+    pub async fn who_am_i(&mut self) -> Result<String, CoreError> {
+        let trace = self.trace_proxy(TraceRecord::new(TraceOp::Extended, ""));
+        let started = Instant::now();
+        let result = self.request(Vec::new()).extended(WhoAmI).await;
+        self.trace.record(trace, started, &result);
+        let (exop, _res) = result
+            .map_err(CoreError::from)?
+            .success()
+            .map_err(CoreError::from)?;
+        Ok(String::from_utf8_lossy(&exop.val.unwrap_or_default()).into_owned())
+    }
+
+    /// Bind with a SASL mechanism, after checking the server offers it.
+    /// With EXTERNAL the identity comes from the TLS client certificate
+    /// or the local socket.
+    pub async fn sasl_bind(&mut self, mechanism: SaslMechanism) -> Result<(), CoreError> {
+        let offered = self.supported_sasl_mechanisms().await?;
+        check_offered(mechanism, &offered)?;
+
+        let trace = TraceRecord::new(TraceOp::Bind, "");
+        let started = Instant::now();
+        let timeout = self.op_timeout();
+        let result = self.ldap.with_timeout(timeout).sasl_external_bind().await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
+
+        if result.rc != 0 {
+            error!(
+                "SASL EXTERNAL bind failed: rc={}, {}",
+                result.rc, result.text
+            );
+            return Err(CoreError::BindFailed(format!(
+                "SASL EXTERNAL bind returned rc={}: {}",
+                result.rc, result.text
+            )));
+        }
+
+        info!("Bound with SASL EXTERNAL");
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_offered() {
+        let offered = vec!["GSSAPI".to_string(), "external".to_string()];
+        assert!(check_offered(SaslMechanism::External, &offered).is_ok());
+        let offered = vec!["GSSAPI".to_string(), "SCRAM-SHA-256".to_string()];
+        let err = check_offered(SaslMechanism::External, &offered).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "bind failed: The server doesn't offer SASL EXTERNAL; it offers GSSAPI, SCRAM-SHA-256"
+        );
+        let err = check_offered(SaslMechanism::External, &[]).unwrap_err();
+        assert!(err.to_string().ends_with("it offers no SASL mechanisms"));
+
+        // The form cycles simple bind -> each mechanism -> simple bind
+        let mut mechanism = None;
+        for expected in SaslMechanism::ALL {
+            mechanism = SaslMechanism::cycle(mechanism);
+            assert_eq!(mechanism, Some(expected));
+        }
+        assert_eq!(SaslMechanism::cycle(mechanism), None);
+    }
+
+    #[test]
+    fn test_unsupported_mechanism_is_refused() {
+        assert_eq!("EXTERNAL".parse(), Ok(SaslMechanism::External));
+        assert_eq!(
+            serde_json::from_str::<SaslMechanism>("\"external\"").unwrap(),
+            SaslMechanism::External
+        );
+        let err = serde_json::from_str::<SaslMechanism>("\"DIGEST-MD5\"").unwrap_err();
+        assert!(err.to_string().starts_with(
+            "SASL mechanism \"DIGEST-MD5\" isn't supported; \"external\" is the only one"
+        ));
+        assert!("scram-sha-256".parse::<SaslMechanism>().is_err());
+    }
 }
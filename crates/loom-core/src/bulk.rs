@@ -1,10 +1,18 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::time::Duration;
 
 use ldap3::Mod;
-use tracing::{debug, info};
+use serde::{Deserialize, Serialize};
+use tokio::time::Instant;
+use tracing::{debug, info, warn};
 
 use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::filter::escape_filter_value;
+use crate::import::membership::MembershipRow;
+use crate::import::plan::{ConflictResolution, ImportItem};
+use crate::transform::Assignment;
 
 /// A single bulk modification operation.
 #[derive(Debug, Clone)]
@@ -17,6 +25,116 @@ pub enum BulkMod {
     DeleteAttribute { attr: String },
     /// Delete a specific value from an attribute.
     DeleteValue { attr: String, value: String },
+    /// Replace an attribute with a value computed from each entry by a
+    /// transform script line.
+    Compute(Assignment),
+}
+
+impl BulkMod {
+    /// The attribute this operation changes.
+    pub fn attr(&self) -> &str {
+        match self {
+            BulkMod::ReplaceAttribute { attr, .. }
+            | BulkMod::AddValue { attr, .. }
+            | BulkMod::DeleteAttribute { attr }
+            | BulkMod::DeleteValue { attr, .. } => attr,
+            BulkMod::Compute(assignment) => &assignment.attr,
+        }
+    }
+}
+
+/// Attributes the entries must be read with to compute `modifications`:
+/// those the scripts read, and those they assign so unchanged values can
+/// be skipped.
+fn computed_attributes(modifications: &[BulkMod]) -> Vec<String> {
+    let mut attrs: Vec<String> = Vec::new();
+    for m in modifications {
+        if let BulkMod::Compute(assignment) = m {
+            for attr in assignment.attributes().into_iter().chain([m.attr()]) {
+                if !attrs.iter().any(|a| a.eq_ignore_ascii_case(attr)) {
+                    attrs.push(attr.to_string());
+                }
+            }
+        }
+    }
+    attrs
+}
+
+/// A value computed for an entry, shown before a scripted bulk update runs.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComputedValue {
+    pub dn: String,
+    pub attr: String,
+    /// The entry's current first value, if it has one.
+    pub old: Option<String>,
+    /// The computed value; empty leaves the attribute as it is.
+    pub new: String,
+}
+
+/// LDAP result codes after which a modify is retried with backoff.
+const RC_BUSY: u32 = 51;
+const RC_UNAVAILABLE: u32 = 52;
+
+/// Longest wait between retries of a busy or unavailable entry.
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Pacing for bulk updates, so large changes stay within server limits
+/// such as Active Directory's LDAP policies. The default sends as fast as
+/// the server answers but still backs off when it reports being busy.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Throttle {
+    /// Maximum modify operations per second (0 for no limit).
+    pub ops_per_second: u32,
+    /// Entries modified between pauses (0 for no batching).
+    pub batch_size: usize,
+    /// Pause after each batch, in milliseconds.
+    pub batch_delay_ms: u64,
+    /// Times an entry is retried when the server answers busy or unavailable.
+    pub max_retries: u32,
+    /// Wait before the first retry, in milliseconds; doubled on each retry.
+    pub backoff_ms: u64,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self {
+            ops_per_second: 0,
+            batch_size: 0,
+            batch_delay_ms: 0,
+            max_retries: 5,
+            backoff_ms: 500,
+        }
+    }
+}
+
+impl Throttle {
+    /// The shortest time between two modify operations, if rate limited.
+    pub fn interval(&self) -> Option<Duration> {
+        (self.ops_per_second > 0).then(|| Duration::from_secs(1) / self.ops_per_second)
+    }
+
+    /// The pause due once `done` entries have been modified: after each
+    /// full batch, but not after the last entry.
+    pub fn batch_pause(&self, done: usize, total: usize) -> Option<Duration> {
+        (self.batch_size > 0
+            && self.batch_delay_ms > 0
+            && done < total
+            && done % self.batch_size == 0)
+            .then(|| Duration::from_millis(self.batch_delay_ms))
+    }
+
+    /// The wait before retry number `attempt` (starting at 0).
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        Duration::from_millis(self.backoff_ms)
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(MAX_BACKOFF)
+    }
+}
+
+/// Whether a modify that failed with `rc` is worth retrying later.
+fn is_retryable(rc: u32) -> bool {
+    matches!(rc, RC_BUSY | RC_UNAVAILABLE)
 }
 
 /// Result of a bulk update operation.
@@ -25,32 +143,61 @@ pub struct BulkResult {
     pub total: usize,
     pub succeeded: usize,
     pub failed: usize,
+    /// Entries not modified because every computed value was empty or
+    /// already set.
+    pub unchanged: usize,
+    /// Modifies retried after the server reported busy or unavailable.
+    pub retries: usize,
     pub errors: Vec<(String, String)>, // (dn, error_message)
 }
 
 impl LdapConnection {
     /// Execute a bulk update: search for entries matching the filter,
-    /// then apply the modifications to each.
+    /// then apply the modifications to each, paced by `throttle`.
+    /// `progress` is called with the entries done and the total after each.
     pub async fn bulk_update(
         &mut self,
         filter: &str,
         modifications: &[BulkMod],
+        throttle: &Throttle,
+        mut progress: impl FnMut(usize, usize),
     ) -> Result<BulkResult, CoreError> {
         // First, find all matching entries
         let base_dn = self.base_dn.clone();
-        let entries = self.search_subtree(&base_dn, filter, &["dn"]).await?;
+        let computed = computed_attributes(modifications);
+        let mut attrs: Vec<&str> = computed.iter().map(String::as_str).collect();
+        if attrs.is_empty() {
+            attrs.push("dn");
+        }
+        let entries = self.search_subtree(&base_dn, filter, &attrs).await?;
 
         let total = entries.len();
         info!("Bulk update: {} entries match filter '{}'", total, filter);
 
         let mut succeeded = 0;
         let mut failed = 0;
+        let mut unchanged = 0;
+        let mut retries = 0;
         let mut errors = Vec::new();
+        let mut next_at = Instant::now();
 
-        for entry in &entries {
-            let mods = build_ldap_mods(modifications);
+        for (i, entry) in entries.iter().enumerate() {
+            let mods = build_ldap_mods(modifications, entry);
+            if mods.is_empty() {
+                unchanged += 1;
+                debug!("Bulk update: nothing to change in {}", entry.dn);
+                progress(i + 1, total);
+                continue;
+            }
+            if let Some(interval) = throttle.interval() {
+                tokio::time::sleep_until(next_at).await;
+                next_at = Instant::now() + interval;
+            }
 
-            match self.modify_entry(&entry.dn, mods).await {
+            match self
+                .modify_with_backoff(&entry.dn, mods, throttle, &mut retries)
+                .await
+            {
                 Ok(()) => {
                     succeeded += 1;
                     debug!("Bulk modified: {}", entry.dn);
@@ -61,24 +208,441 @@ impl LdapConnection {
                     debug!("Bulk modify failed for {}: {}", entry.dn, e);
                 }
             }
+            progress(i + 1, total);
+
+            if let Some(pause) = throttle.batch_pause(i + 1, total) {
+                debug!("Bulk update: pausing {:?} after {} entries", pause, i + 1);
+                tokio::time::sleep(pause).await;
+            }
         }
 
         info!(
-            "Bulk update complete: {} succeeded, {} failed out of {}",
-            succeeded, failed, total
+            "Bulk update complete: {} succeeded, {} failed, {} unchanged out of {} ({} retries)",
+            succeeded, failed, unchanged, total, retries
         );
 
         Ok(BulkResult {
             total,
             succeeded,
             failed,
+            unchanged,
+            retries,
             errors,
         })
     }
+
+    /// The values a scripted bulk update would compute, without changing
+    /// anything: the number of matching entries, and the computed values
+    /// of the first `limit` of them.
+    pub async fn preview_bulk(
+        &mut self,
+        filter: &str,
+        modifications: &[BulkMod],
+        limit: usize,
+    ) -> Result<(usize, Vec<ComputedValue>), CoreError> {
+        let base_dn = self.base_dn.clone();
+        let computed = computed_attributes(modifications);
+        let mut attrs: Vec<&str> = computed.iter().map(String::as_str).collect();
+        if attrs.is_empty() {
+            attrs.push("dn");
+        }
+        let entries = self.search_subtree(&base_dn, filter, &attrs).await?;
+
+        let mut values = Vec::new();
+        for entry in entries.iter().take(limit) {
+            for m in modifications {
+                if let BulkMod::Compute(assignment) = m {
+                    values.push(ComputedValue {
+                        dn: entry.dn.clone(),
+                        attr: assignment.attr.clone(),
+                        old: crate::util::find_values_ci(&entry.attributes, &assignment.attr)
+                            .and_then(|vals| vals.first())
+                            .cloned(),
+                        new: assignment.evaluate(entry),
+                    });
+                }
+            }
+        }
+        Ok((entries.len(), values))
+    }
+
+    /// Modify one entry, waiting and retrying while the server reports
+    /// that it is busy or unavailable.
+    async fn modify_with_backoff(
+        &mut self,
+        dn: &str,
+        mods: Vec<Mod<String>>,
+        throttle: &Throttle,
+        retries: &mut usize,
+    ) -> Result<(), CoreError> {
+        let mut attempt = 0;
+        loop {
+            let (rc, text) = self.send_modify(dn, mods.clone()).await?;
+            if rc == 0 {
+                return Ok(());
+            }
+            if !is_retryable(rc) || attempt >= throttle.max_retries {
+                return Err(CoreError::ModifyFailed(format!(
+                    "Modify {} failed rc={}: {}",
+                    dn, rc, text
+                )));
+            }
+            let wait = throttle.backoff(attempt);
+            warn!(
+                "Server busy (rc={}) modifying {}, retrying in {:?}",
+                rc, dn, wait
+            );
+            tokio::time::sleep(wait).await;
+            attempt += 1;
+            *retries += 1;
+        }
+    }
+}
+
+/// One entry's row of a batch edit: the attribute's values as read, and
+/// the single value to set instead.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueEdit {
+    pub dn: String,
+    pub old: Vec<String>,
+    /// The new value; empty removes the attribute.
+    pub new: String,
+}
+
+impl ValueEdit {
+    /// The modify setting the new value, or `None` when it is already set.
+    fn modification(&self, attr: &str) -> Option<Mod<String>> {
+        if self.new.is_empty() {
+            return (!self.old.is_empty()).then(|| Mod::Replace(attr.to_string(), HashSet::new()));
+        }
+        (self.old != [self.new.as_str()])
+            .then(|| Mod::Replace(attr.to_string(), HashSet::from([self.new.clone()])))
+    }
+}
+
+/// What became of one entry in a batch edit.
+#[derive(Debug, Clone, PartialEq)]
+pub enum EditOutcome {
+    Applied,
+    /// The entry already held the new value.
+    Unchanged,
+    Failed(String),
+}
+
+impl LdapConnection {
+    /// Each entry's values of `attr`, in the order given. An entry that
+    /// can't be read is left out and reported in the errors.
+    pub async fn read_values(
+        &mut self,
+        dns: &[String],
+        attr: &str,
+    ) -> (Vec<(String, Vec<String>)>, Vec<(String, String)>) {
+        let mut values = Vec::new();
+        let mut errors = Vec::new();
+        for dn in dns {
+            match self.search_entry_attrs(dn, &[attr]).await {
+                Ok(Some(entry)) => {
+                    let vals = crate::util::find_values_ci(&entry.attributes, attr)
+                        .cloned()
+                        .unwrap_or_default();
+                    values.push((entry.dn, vals));
+                }
+                Ok(None) => errors.push((dn.clone(), "No such entry".to_string())),
+                Err(e) => errors.push((dn.clone(), e.to_string())),
+            }
+        }
+        (values, errors)
+    }
+
+    /// Set `attr` on each entry to its edit's value, one modify per entry,
+    /// retrying while the server is busy as `throttle` allows. Returns the
+    /// outcome of each edit in order; `progress` is called after each.
+    pub async fn apply_value_edits(
+        &mut self,
+        attr: &str,
+        edits: &[ValueEdit],
+        throttle: &Throttle,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Vec<EditOutcome> {
+        let mut outcomes = Vec::with_capacity(edits.len());
+        let mut retries = 0;
+        for (i, edit) in edits.iter().enumerate() {
+            let outcome = match edit.modification(attr) {
+                None => EditOutcome::Unchanged,
+                Some(m) => match self
+                    .modify_with_backoff(&edit.dn, vec![m], throttle, &mut retries)
+                    .await
+                {
+                    Ok(()) => EditOutcome::Applied,
+                    Err(e) => EditOutcome::Failed(e.to_string()),
+                },
+            };
+            outcomes.push(outcome);
+            progress(i + 1, edits.len());
+        }
+        info!(
+            "Batch edit of {}: {} entries ({} retries)",
+            attr,
+            edits.len(),
+            retries
+        );
+        outcomes
+    }
+}
+
+/// Result of applying group memberships.
+#[derive(Debug, Default)]
+pub struct MembershipResult {
+    /// Number of distinct groups that had at least one row.
+    pub groups: usize,
+    /// Member values added across all groups.
+    pub added: usize,
+    /// Rows skipped because the member was already in the group.
+    pub already_members: usize,
+    /// Identifiers that could not be resolved to a single DN: (identifier, reason).
+    pub unresolved: Vec<(String, String)>,
+    /// Failed modify batches: (group_dn, error_message).
+    pub errors: Vec<(String, String)>,
 }
 
-/// Convert BulkMod operations to ldap3 Mod operations.
-fn build_ldap_mods(modifications: &[BulkMod]) -> Vec<Mod<String>> {
+/// Attributes tried, in order, when resolving a bare identifier to a DN.
+const IDENTIFIER_ATTRS: &[&str] = &["sAMAccountName", "uid", "cn", "userPrincipalName", "mail"];
+
+impl LdapConnection {
+    /// Add members to groups from parsed CSV rows.
+    ///
+    /// Groups and members given as bare names are resolved under the base DN.
+    /// Members already present (compared case-insensitively) are skipped, and
+    /// new values are added in modify operations of at most `batch_size` values.
+    pub async fn apply_memberships(
+        &mut self,
+        rows: &[MembershipRow],
+        member_attr: &str,
+        batch_size: usize,
+    ) -> Result<MembershipResult, CoreError> {
+        let batch_size = batch_size.max(1);
+        let mut result = MembershipResult::default();
+        let mut resolved: HashMap<String, Option<String>> = HashMap::new();
+
+        // group DN -> member DNs; groups are applied in DN order, each
+        // group's members in the order the file lists them
+        let mut pending: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for row in rows {
+            let Some(group_dn) = self
+                .resolve_identifier(&row.group, &mut resolved, &mut result)
+                .await?
+            else {
+                continue;
+            };
+            let Some(member_dn) = self
+                .resolve_identifier(&row.member, &mut resolved, &mut result)
+                .await?
+            else {
+                continue;
+            };
+            pending.entry(group_dn).or_default().push(member_dn);
+        }
+
+        result.groups = pending.len();
+        info!(
+            "Membership import: {} rows across {} groups",
+            rows.len(),
+            result.groups
+        );
+
+        for (group_dn, members) in pending {
+            let existing: HashSet<String> = match self.search_entry(&group_dn).await {
+                Ok(Some(entry)) => crate::util::find_values_ci(&entry.attributes, member_attr)
+                    .map(|vals| vals.iter().map(|v| v.to_lowercase()).collect())
+                    .unwrap_or_default(),
+                Ok(None) => {
+                    result
+                        .errors
+                        .push((group_dn, "Group entry not found".to_string()));
+                    continue;
+                }
+                Err(e) => {
+                    result.errors.push((group_dn, e.to_string()));
+                    continue;
+                }
+            };
+
+            let mut to_add = Vec::new();
+            let mut seen = HashSet::new();
+            for member in members {
+                let key = member.to_lowercase();
+                if existing.contains(&key) || !seen.insert(key) {
+                    result.already_members += 1;
+                } else {
+                    to_add.push(member);
+                }
+            }
+
+            for batch in to_add.chunks(batch_size) {
+                match self
+                    .add_attribute_values(&group_dn, member_attr, batch.to_vec())
+                    .await
+                {
+                    Ok(()) => {
+                        result.added += batch.len();
+                        debug!("Added {} members to {}", batch.len(), group_dn);
+                    }
+                    Err(e) => {
+                        debug!("Membership batch failed for {}: {}", group_dn, e);
+                        result.errors.push((group_dn.clone(), e.to_string()));
+                    }
+                }
+            }
+        }
+
+        info!(
+            "Membership import complete: {} added, {} already members, {} unresolved, {} errors",
+            result.added,
+            result.already_members,
+            result.unresolved.len(),
+            result.errors.len()
+        );
+
+        Ok(result)
+    }
+
+    /// Resolve a group or member identifier to a DN, caching the outcome.
+    /// Unresolvable identifiers are recorded once in `result.unresolved`.
+    async fn resolve_identifier(
+        &mut self,
+        ident: &str,
+        cache: &mut HashMap<String, Option<String>>,
+        result: &mut MembershipResult,
+    ) -> Result<Option<String>, CoreError> {
+        let key = ident.to_lowercase();
+        if let Some(hit) = cache.get(&key) {
+            return Ok(hit.clone());
+        }
+
+        let outcome = if crate::dn::looks_like_dn(ident) {
+            match self.search_entry(ident).await {
+                Ok(Some(entry)) => Ok(entry.dn),
+                Ok(None) => Err("DN not found".to_string()),
+                Err(CoreError::SearchFailed(msg)) => Err(msg),
+                Err(e) => return Err(e),
+            }
+        } else {
+            let escaped = escape_filter_value(ident);
+            let filter = format!(
+                "(|{})",
+                IDENTIFIER_ATTRS
+                    .iter()
+                    .map(|attr| format!("({}={})", attr, escaped))
+                    .collect::<String>()
+            );
+            let base_dn = self.base_dn.clone();
+            let entries = self.search_subtree(&base_dn, &filter, &["1.1"]).await?;
+            match entries.len() {
+                1 => Ok(entries[0].dn.clone()),
+                0 => Err("No matching entry".to_string()),
+                n => Err(format!("Ambiguous: {} matching entries", n)),
+            }
+        };
+
+        let dn = match outcome {
+            Ok(dn) => Some(dn),
+            Err(reason) => {
+                result.unresolved.push((ident.to_string(), reason));
+                None
+            }
+        };
+        cache.insert(key, dn.clone());
+        Ok(dn)
+    }
+}
+
+/// Result of applying an entry import.
+#[derive(Debug, Default)]
+pub struct ImportResult {
+    pub created: usize,
+    pub overwritten: usize,
+    pub merged: usize,
+    /// Conflicts resolved as skip.
+    pub skipped: usize,
+    /// Conflicts whose resolution changed nothing.
+    pub unchanged: usize,
+    pub errors: Vec<(String, String)>, // (dn, error_message)
+}
+
+impl LdapConnection {
+    /// Pair each entry from an import file with the server's copy, if its
+    /// DN already exists, ready for conflicts to be resolved.
+    pub async fn check_import(
+        &mut self,
+        entries: Vec<LdapEntry>,
+    ) -> Result<Vec<ImportItem>, CoreError> {
+        let mut items = Vec::with_capacity(entries.len());
+        for entry in entries {
+            let existing = if self.entry_exists(&entry.dn).await? {
+                self.search_entry(&entry.dn).await?
+            } else {
+                None
+            };
+            items.push(ImportItem::new(entry, existing));
+        }
+        Ok(items)
+    }
+
+    /// Create new entries and apply each conflict's resolution, in order
+    /// (parents should come first; see [`tree_order`](crate::import::plan::tree_order)).
+    /// Failures are recorded per entry and don't stop the import.
+    pub async fn apply_import(&mut self, items: &[ImportItem]) -> ImportResult {
+        let mut result = ImportResult::default();
+        for item in items {
+            let dn = &item.entry.dn;
+            if item.existing.is_none() {
+                let attrs = item
+                    .entry
+                    .attributes
+                    .iter()
+                    .map(|(attr, vals)| (attr.clone(), vals.iter().cloned().collect()))
+                    .collect();
+                match self.add_entry(dn, attrs).await {
+                    Ok(()) => result.created += 1,
+                    Err(e) => result.errors.push((dn.clone(), e.to_string())),
+                }
+                continue;
+            }
+            if item.resolution == ConflictResolution::Skip {
+                result.skipped += 1;
+                continue;
+            }
+            let mods = item.mods();
+            if mods.is_empty() {
+                result.unchanged += 1;
+                continue;
+            }
+            match self.modify_entry(dn, mods).await {
+                Ok(()) if item.resolution == ConflictResolution::Overwrite => {
+                    result.overwritten += 1
+                }
+                Ok(()) => result.merged += 1,
+                Err(e) => {
+                    debug!("Import failed for {}: {}", dn, e);
+                    result.errors.push((dn.clone(), e.to_string()));
+                }
+            }
+        }
+        info!(
+            "Import complete: {} created, {} overwritten, {} merged, {} skipped, {} errors",
+            result.created,
+            result.overwritten,
+            result.merged,
+            result.skipped,
+            result.errors.len()
+        );
+        result
+    }
+}
+
+/// Convert BulkMod operations to ldap3 Mod operations for `entry`. Computed
+/// values that are empty or already the attribute's only value are left out.
+fn build_ldap_mods(modifications: &[BulkMod], entry: &LdapEntry) -> Vec<Mod<String>> {
     let mut mods = Vec::new();
 
     for m in modifications {
@@ -95,8 +659,117 @@ fn build_ldap_mods(modifications: &[BulkMod]) -> Vec<Mod<String>> {
             BulkMod::DeleteValue { attr, value } => {
                 mods.push(Mod::Delete(attr.clone(), HashSet::from([value.clone()])));
             }
+            BulkMod::Compute(assignment) => {
+                let value = assignment.evaluate(entry);
+                let current = crate::util::find_values_ci(&entry.attributes, &assignment.attr);
+                if value.is_empty() || current.is_some_and(|vals| *vals == [value.as_str()]) {
+                    continue;
+                }
+                mods.push(Mod::Replace(
+                    assignment.attr.clone(),
+                    HashSet::from([value]),
+                ));
+            }
         }
     }
 
     mods
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_throttle_interval_and_batches() {
+        let throttle = Throttle {
+            ops_per_second: 4,
+            batch_size: 100,
+            batch_delay_ms: 2000,
+            ..Default::default()
+        };
+        assert_eq!(throttle.interval(), Some(Duration::from_millis(250)));
+        assert_eq!(throttle.batch_pause(99, 250), None);
+        assert_eq!(throttle.batch_pause(100, 250), Some(Duration::from_secs(2)));
+        // No pause once the last entry is done
+        assert_eq!(throttle.batch_pause(200, 200), None);
+
+        let unlimited = Throttle::default();
+        assert_eq!(unlimited.interval(), None);
+        assert_eq!(unlimited.batch_pause(100, 250), None);
+    }
+
+    #[test]
+    fn test_backoff_doubles_up_to_cap() {
+        let throttle = Throttle::default();
+        assert_eq!(throttle.backoff(0), Duration::from_millis(500));
+        assert_eq!(throttle.backoff(2), Duration::from_secs(2));
+        assert_eq!(throttle.backoff(40), MAX_BACKOFF);
+        assert!(is_retryable(RC_BUSY));
+        assert!(is_retryable(RC_UNAVAILABLE));
+        // insufficientAccessRights won't change by waiting
+        assert!(!is_retryable(50));
+    }
+
+    #[test]
+    fn test_value_edit_modification() {
+        let edit = |old: &[&str], new: &str| ValueEdit {
+            dn: "cn=a,dc=x".to_string(),
+            old: old.iter().map(|v| v.to_string()).collect(),
+            new: new.to_string(),
+        };
+        assert_eq!(edit(&["Sales"], "Sales").modification("department"), None);
+        assert_eq!(edit(&[], "").modification("department"), None);
+        assert_eq!(
+            edit(&["a", "b"], "a").modification("department"),
+            Some(Mod::Replace(
+                "department".to_string(),
+                HashSet::from(["a".to_string()])
+            ))
+        );
+        assert_eq!(
+            edit(&["Sales"], "").modification("department"),
+            Some(Mod::Replace("department".to_string(), HashSet::new()))
+        );
+    }
+
+    #[test]
+    fn test_computed_mods_skip_empty_and_unchanged_values() {
+        let script = crate::transform::Script::parse(
+            "mail = lower(givenName) .. '@corp.com'\ndescription = title\ncn = givenName",
+        )
+        .unwrap();
+        let mut modifications: Vec<BulkMod> = script
+            .assignments
+            .into_iter()
+            .map(BulkMod::Compute)
+            .collect();
+        modifications.push(BulkMod::DeleteAttribute {
+            attr: "pager".to_string(),
+        });
+        assert_eq!(
+            computed_attributes(&modifications),
+            ["givenName", "mail", "title", "description", "cn"]
+        );
+
+        let entry = LdapEntry::new(
+            "cn=Ann,dc=x".to_string(),
+            BTreeMap::from([
+                ("givenName".to_string(), vec!["Ann".to_string()]),
+                ("CN".to_string(), vec!["Ann".to_string()]),
+                ("mail".to_string(), vec!["old@corp.com".to_string()]),
+            ]),
+        );
+        // No title to copy, and cn already holds the value
+        assert_eq!(
+            build_ldap_mods(&modifications, &entry),
+            [
+                Mod::Replace(
+                    "mail".to_string(),
+                    HashSet::from(["ann@corp.com".to_string()])
+                ),
+                Mod::Delete("pager".to_string(), HashSet::new()),
+            ]
+        );
+    }
+}
@@ -0,0 +1,184 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::{Duration, Instant};
+
+use crate::entry::LdapEntry;
+
+/// Recently loaded entries, so selecting the same entry again doesn't
+/// query the server. Entries expire after `ttl`; when full, the least
+/// recently used entry is dropped. DNs are compared case-insensitively.
+#[derive(Debug)]
+pub struct EntryCache {
+    entries: HashMap<String, (Instant, LdapEntry)>,
+    /// Lowercased DNs, least recently used first.
+    order: VecDeque<String>,
+    capacity: usize,
+    ttl: Duration,
+}
+
+impl EntryCache {
+    /// A cache of up to `capacity` entries. A zero capacity or TTL turns
+    /// caching off.
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            entries: HashMap::new(),
+            order: VecDeque::new(),
+            capacity,
+            ttl,
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.capacity > 0 && !self.ttl.is_zero()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The cached copy of `dn`, if it hasn't expired.
+    pub fn get(&mut self, dn: &str) -> Option<LdapEntry> {
+        self.get_at(dn, Instant::now())
+    }
+
+    fn get_at(&mut self, dn: &str, now: Instant) -> Option<LdapEntry> {
+        let key = dn.to_lowercase();
+        let (loaded, entry) = self.entries.get(&key)?;
+        if now.duration_since(*loaded) >= self.ttl {
+            self.remove(&key);
+            return None;
+        }
+        let entry = entry.clone();
+        self.touch(&key);
+        Some(entry)
+    }
+
+    /// Remember an entry as just loaded from the server.
+    pub fn insert(&mut self, entry: LdapEntry) {
+        self.insert_at(entry, Instant::now());
+    }
+
+    fn insert_at(&mut self, entry: LdapEntry, now: Instant) {
+        if !self.enabled() {
+            return;
+        }
+        let key = entry.dn.to_lowercase();
+        if self.entries.insert(key.clone(), (now, entry)).is_some() {
+            self.touch(&key);
+        } else {
+            self.order.push_back(key);
+        }
+        while self.entries.len() > self.capacity {
+            match self.order.pop_front() {
+                Some(oldest) => {
+                    self.entries.remove(&oldest);
+                }
+                None => break,
+            }
+        }
+    }
+
+    /// Forget `dn` after it was changed.
+    pub fn invalidate(&mut self, dn: &str) {
+        self.remove(&dn.to_lowercase());
+    }
+
+    /// Forget `dn` and every entry below it, after a move or delete.
+    pub fn invalidate_subtree(&mut self, dn: &str) {
+        let key = dn.to_lowercase();
+        let suffix = format!(",{}", key);
+        let stale: Vec<String> = self
+            .entries
+            .keys()
+            .filter(|k| **k == key || k.ends_with(&suffix))
+            .cloned()
+            .collect();
+        for k in stale {
+            self.remove(&k);
+        }
+    }
+
+    /// Forget everything, e.g. after a bulk change.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+
+    fn remove(&mut self, key: &str) {
+        if self.entries.remove(key).is_some() {
+            self.order.retain(|k| k != key);
+        }
+    }
+
+    fn touch(&mut self, key: &str) {
+        if let Some(pos) = self.order.iter().position(|k| k == key) {
+            if let Some(k) = self.order.remove(pos) {
+                self.order.push_back(k);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_entries_expire() {
+        let mut cache = EntryCache::new(10, Duration::from_secs(60));
+        let start = Instant::now();
+        cache.insert_at(test_entry("cn=Alice,dc=example,dc=com", &[]), start);
+
+        let hit = cache.get_at(
+            "CN=alice,DC=example,DC=com",
+            start + Duration::from_secs(59),
+        );
+        assert_eq!(hit.unwrap().dn, "cn=Alice,dc=example,dc=com");
+        assert!(cache
+            .get_at(
+                "cn=Alice,dc=example,dc=com",
+                start + Duration::from_secs(60)
+            )
+            .is_none());
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_least_recently_used_is_dropped() {
+        let mut cache = EntryCache::new(2, Duration::from_secs(60));
+        cache.insert(test_entry("cn=a,dc=x", &[]));
+        cache.insert(test_entry("cn=b,dc=x", &[]));
+        // Reading a makes b the least recently used
+        assert!(cache.get("cn=a,dc=x").is_some());
+        cache.insert(test_entry("cn=c,dc=x", &[]));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("cn=b,dc=x").is_none());
+        assert!(cache.get("cn=a,dc=x").is_some());
+        assert!(cache.get("cn=c,dc=x").is_some());
+    }
+
+    #[test]
+    fn test_invalidate_subtree() {
+        let mut cache = EntryCache::new(10, Duration::from_secs(60));
+        cache.insert(test_entry("ou=People,dc=x", &[]));
+        cache.insert(test_entry("cn=a,ou=People,dc=x", &[]));
+        cache.insert(test_entry("cn=a,ou=OtherPeople,dc=x", &[]));
+        cache.invalidate_subtree("OU=People,dc=x");
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get("cn=a,ou=OtherPeople,dc=x").is_some());
+
+        cache.invalidate("cn=a,ou=OtherPeople,dc=x");
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn test_zero_ttl_disables_caching() {
+        let mut cache = EntryCache::new(10, Duration::ZERO);
+        cache.insert(test_entry("cn=a,dc=x", &[]));
+        assert!(cache.is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+//! Equivalent OpenLDAP command lines for a search or a change script, to
+//! hand to someone without loom.
+//!
+//! ```text
+//! ldapsearch -H 'ldaps://ldap.example.com:636' -x -D 'cn=admin,dc=example,dc=com' -W \
+//!     -LLL -E pr=500/noprompt -b 'dc=example,dc=com' -s sub '(uid=jdoe)' cn mail
+//! ```
+//!
+//! Proxies and SSH tunnels can't be expressed and are left out.
+
+use crate::connection::{ConnectionSettings, TlsMode};
+
+/// `ldapsearch` for a subtree search under `base_dn`, paged like loom's
+/// own searches. No attributes means all of them.
+pub fn ldapsearch(
+    settings: &ConnectionSettings,
+    base_dn: &str,
+    filter: &str,
+    attrs: &[String],
+) -> String {
+    let mut args = connection_args(settings);
+    args.extend([
+        "-LLL".to_string(),
+        "-E".to_string(),
+        format!("pr={}/noprompt", settings.page_size),
+        "-b".to_string(),
+        shell_quote(base_dn),
+        "-s".to_string(),
+        "sub".to_string(),
+        shell_quote(filter),
+    ]);
+    args.extend(attrs.iter().map(|a| shell_quote(a)));
+    format!("ldapsearch {}", args.join(" "))
+}
+
+/// `ldapmodify` applying an LDIF change script, given inline as a
+/// here-document so nothing in it needs escaping.
+pub fn ldapmodify(settings: &ConnectionSettings, ldif: &str) -> String {
+    let mut args = connection_args(settings);
+    if settings.relax_rules {
+        args.extend(["-e".to_string(), "relax".to_string()]);
+    }
+    let mut delimiter = "LDIF".to_string();
+    while ldif.lines().any(|line| line == delimiter) {
+        delimiter.push('_');
+    }
+    format!(
+        "ldapmodify {} <<'{}'\n{}\n{}\n",
+        args.join(" "),
+        delimiter,
+        ldif.trim_end(),
+        delimiter
+    )
+}
+
+/// Server URIs, TLS and bind options shared by both commands. `Auto` is
+/// written as LDAPS, which loom tries first; the password is prompted for.
+fn connection_args(settings: &ConnectionSettings) -> Vec<String> {
+    let (scheme, port) = match settings.tls_mode {
+        TlsMode::Auto if settings.port == 389 => ("ldaps", 636),
+        TlsMode::Auto | TlsMode::Ldaps => ("ldaps", settings.port),
+        TlsMode::StartTls | TlsMode::None => ("ldap", settings.port),
+    };
+    let uris: Vec<String> = std::iter::once(&settings.host)
+        .chain(&settings.failover_hosts)
+        .map(|host| format!("{}://{}:{}", scheme, host, port))
+        .collect();
+    let mut args = vec!["-H".to_string(), shell_quote(&uris.join(" "))];
+    if settings.tls_mode == TlsMode::StartTls {
+        args.push("-ZZ".to_string());
+    }
+    match (&settings.sasl_mechanism, &settings.bind_dn) {
+        (Some(mechanism), _) => args.extend(["-Y".to_string(), mechanism.name().to_string()]),
+        (None, Some(bind_dn)) => args.extend([
+            "-x".to_string(),
+            "-D".to_string(),
+            shell_quote(bind_dn),
+            "-W".to_string(),
+        ]),
+        (None, None) => args.push("-x".to_string()),
+    }
+    if let Some(authz_id) = &settings.authz_id {
+        args.extend([
+            "-e".to_string(),
+            shell_quote(&format!("!authzid={}", authz_id)),
+        ]);
+    }
+    args
+}
+
+/// Quote `arg` for a POSIX shell, unless it's plainly safe as it is.
+pub fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "@%+=:,./_-".contains(c));
+    if safe {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', r"'\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings() -> ConnectionSettings {
+        ConnectionSettings {
+            host: "ldap1.example.com".to_string(),
+            port: 389,
+            tls_mode: TlsMode::StartTls,
+            bind_dn: Some("cn=O'Brien,dc=example,dc=com".to_string()),
+            base_dn: None,
+            page_size: 200,
+            timeout_secs: 30,
+            relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            dns_srv: false,
+            failover_hosts: vec!["ldap2.example.com".to_string()],
+            shuffle_hosts: false,
+        }
+    }
+
+    #[test]
+    fn test_commands() {
+        let search = ldapsearch(
+            &settings(),
+            "ou=People,dc=example,dc=com",
+            "(&(cn=O'*)(mail=*))",
+            &["cn".to_string(), "mail".to_string()],
+        );
+        assert_eq!(
+            search,
+            "ldapsearch -H 'ldap://ldap1.example.com:389 ldap://ldap2.example.com:389' -ZZ \
+             -x -D 'cn=O'\\''Brien,dc=example,dc=com' -W -LLL -E pr=200/noprompt \
+             -b ou=People,dc=example,dc=com -s sub '(&(cn=O'\\''*)(mail=*))' cn mail"
+        );
+
+        let anonymous = ConnectionSettings {
+            tls_mode: TlsMode::Auto,
+            bind_dn: None,
+            failover_hosts: Vec::new(),
+            relax_rules: true,
+            ..settings()
+        };
+        let ldif = "dn: cn=x,dc=example,dc=com\nchangetype: delete\n\nLDIF\n";
+        assert_eq!(
+            ldapmodify(&anonymous, ldif),
+            "ldapmodify -H ldaps://ldap1.example.com:636 -x -e relax <<'LDIF_'\n\
+             dn: cn=x,dc=example,dc=com\nchangetype: delete\n\nLDIF\nLDIF_\n"
+        );
+    }
+}
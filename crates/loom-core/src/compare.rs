@@ -0,0 +1,445 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+
+/// How one attribute differs between two snapshots of an entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AttributeChange {
+    pub attr: String,
+    /// Values only in the old snapshot.
+    pub removed: Vec<String>,
+    /// Values only in the new snapshot.
+    pub added: Vec<String>,
+}
+
+/// An entry present in both snapshots with differing attributes.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EntryChange {
+    pub dn: String,
+    pub changes: Vec<AttributeChange>,
+}
+
+/// Differences between two snapshots (exports) of the same subtree.
+/// Entries are matched by DN and listed parents first.
+#[derive(Debug, Clone, Default)]
+pub struct SnapshotDiff {
+    pub added: Vec<LdapEntry>,
+    pub removed: Vec<LdapEntry>,
+    pub changed: Vec<EntryChange>,
+}
+
+/// Output format for a comparison report.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html,
+}
+
+impl ReportFormat {
+    /// Infer format from file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "html" | "htm" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+impl SnapshotDiff {
+    /// Compare two snapshots. DNs and attribute names match case-insensitively;
+    /// values are compared exactly, ignoring order. Attributes named in
+    /// `ignored` (e.g. `modifyTimestamp`) are left out entirely.
+    pub fn compare(old: &[LdapEntry], new: &[LdapEntry], ignored: &[String]) -> Self {
+        let ignored: BTreeSet<String> = ignored.iter().map(|a| a.to_lowercase()).collect();
+        let old_by_dn = index_by_dn(old);
+        let new_by_dn = index_by_dn(new);
+
+        let mut diff = SnapshotDiff::default();
+        for (key, new_entry) in &new_by_dn {
+            match old_by_dn.get(key) {
+                None => diff.added.push(without_ignored(new_entry, &ignored)),
+                Some(old_entry) => {
+                    let changes = attribute_changes(old_entry, new_entry, &ignored);
+                    if !changes.is_empty() {
+                        diff.changed.push(EntryChange {
+                            dn: new_entry.dn.clone(),
+                            changes,
+                        });
+                    }
+                }
+            }
+        }
+        for (key, old_entry) in &old_by_dn {
+            if !new_by_dn.contains_key(key) {
+                diff.removed.push(without_ignored(old_entry, &ignored));
+            }
+        }
+        diff
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+
+    /// One-line count of each kind of change.
+    pub fn summary(&self) -> String {
+        format!(
+            "{} added, {} removed, {} changed",
+            self.added.len(),
+            self.removed.len(),
+            self.changed.len()
+        )
+    }
+
+    /// Render the report as Markdown. `old_label`/`new_label` name the
+    /// snapshots (usually their file names).
+    pub fn to_markdown(&self, old_label: &str, new_label: &str) -> String {
+        let mut out = String::from("# Snapshot Comparison\n\n");
+        let _ = writeln!(out, "- **Old:** {}", md_cell(old_label));
+        let _ = writeln!(out, "- **New:** {}", md_cell(new_label));
+        let _ = writeln!(out, "- **Changes:** {}", self.summary());
+
+        if self.is_empty() {
+            out.push_str("\nNo differences.\n");
+            return out;
+        }
+
+        for (title, entries) in [
+            ("Added Entries", &self.added),
+            ("Removed Entries", &self.removed),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "\n## {} ({})", title, entries.len());
+            for entry in entries {
+                let _ = writeln!(out, "\n### {}\n", md_cell(&entry.dn));
+                out.push_str("| Attribute | Values |\n|-----------|--------|\n");
+                for (attr, vals) in &entry.attributes {
+                    let _ = writeln!(out, "| {} | {} |", md_cell(attr), md_values(vals));
+                }
+            }
+        }
+
+        if !self.changed.is_empty() {
+            let _ = writeln!(out, "\n## Changed Entries ({})", self.changed.len());
+            for entry in &self.changed {
+                let _ = writeln!(out, "\n### {}\n", md_cell(&entry.dn));
+                out.push_str("| Attribute | Old | New |\n|-----------|-----|-----|\n");
+                for change in &entry.changes {
+                    let _ = writeln!(
+                        out,
+                        "| {} | {} | {} |",
+                        md_cell(&change.attr),
+                        md_values(&change.removed),
+                        md_values(&change.added)
+                    );
+                }
+            }
+        }
+        out
+    }
+
+    /// Render the report as a standalone HTML page.
+    pub fn to_html(&self, old_label: &str, new_label: &str) -> String {
+        let mut out = String::from(
+            "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>Snapshot Comparison</title>\n<style>\n\
+             body { font-family: sans-serif; margin: 2em; }\n\
+             table { border-collapse: collapse; margin-bottom: 1em; }\n\
+             th, td { border: 1px solid #ccc; padding: 4px 8px; text-align: left; vertical-align: top; }\n\
+             .old { background: #fdecea; }\n\
+             .new { background: #e9f7ef; }\n\
+             </style>\n</head>\n<body>\n<h1>Snapshot Comparison</h1>\n",
+        );
+        let _ = writeln!(
+            out,
+            "<ul>\n<li><b>Old:</b> {}</li>\n<li><b>New:</b> {}</li>\n<li><b>Changes:</b> {}</li>\n</ul>",
+            html_escape(old_label),
+            html_escape(new_label),
+            self.summary()
+        );
+
+        if self.is_empty() {
+            out.push_str("<p>No differences.</p>\n");
+        }
+
+        for (title, class, entries) in [
+            ("Added Entries", "new", &self.added),
+            ("Removed Entries", "old", &self.removed),
+        ] {
+            if entries.is_empty() {
+                continue;
+            }
+            let _ = writeln!(out, "<h2>{} ({})</h2>", title, entries.len());
+            for entry in entries {
+                let _ = writeln!(out, "<h3>{}</h3>", html_escape(&entry.dn));
+                out.push_str("<table>\n<tr><th>Attribute</th><th>Values</th></tr>\n");
+                for (attr, vals) in &entry.attributes {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td class=\"{}\">{}</td></tr>",
+                        html_escape(attr),
+                        class,
+                        html_values(vals)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+        }
+
+        if !self.changed.is_empty() {
+            let _ = writeln!(out, "<h2>Changed Entries ({})</h2>", self.changed.len());
+            for entry in &self.changed {
+                let _ = writeln!(out, "<h3>{}</h3>", html_escape(&entry.dn));
+                out.push_str("<table>\n<tr><th>Attribute</th><th>Old</th><th>New</th></tr>\n");
+                for change in &entry.changes {
+                    let _ = writeln!(
+                        out,
+                        "<tr><td>{}</td><td class=\"old\">{}</td><td class=\"new\">{}</td></tr>",
+                        html_escape(&change.attr),
+                        html_values(&change.removed),
+                        html_values(&change.added)
+                    );
+                }
+                out.push_str("</table>\n");
+            }
+        }
+
+        out.push_str("</body>\n</html>\n");
+        out
+    }
+
+    /// Write the report to a file, choosing Markdown or HTML from its extension.
+    pub fn write_report(
+        &self,
+        path: &Path,
+        old_label: &str,
+        new_label: &str,
+    ) -> Result<(), CoreError> {
+        let content = match ReportFormat::from_path(path) {
+            Some(ReportFormat::Markdown) => self.to_markdown(old_label, new_label),
+            Some(ReportFormat::Html) => self.to_html(old_label, new_label),
+            None => {
+                return Err(CoreError::ExportError(
+                    "Report file must end in .md or .html".to_string(),
+                ))
+            }
+        };
+        std::fs::write(path, content)
+            .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))
+    }
+}
+
+/// Sort key for a DN: lowercased components, root first, so parents sort
+/// before their children.
+fn dn_key(dn: &str) -> Vec<String> {
+    dn.split(',')
+        .rev()
+        .map(|part| part.trim().to_lowercase())
+        .collect()
+}
+
+fn index_by_dn(entries: &[LdapEntry]) -> BTreeMap<Vec<String>, &LdapEntry> {
+    entries.iter().map(|e| (dn_key(&e.dn), e)).collect()
+}
+
+fn without_ignored(entry: &LdapEntry, ignored: &BTreeSet<String>) -> LdapEntry {
+    let mut entry = entry.clone();
+    entry
+        .attributes
+        .retain(|attr, _| !ignored.contains(&attr.to_lowercase()));
+    entry
+}
+
+fn attribute_changes(
+    old: &LdapEntry,
+    new: &LdapEntry,
+    ignored: &BTreeSet<String>,
+) -> Vec<AttributeChange> {
+    // Lowercased name -> (display name, old values, new values)
+    let mut attrs: BTreeMap<String, (String, BTreeSet<&str>, BTreeSet<&str>)> = BTreeMap::new();
+    for (attr, vals) in &old.attributes {
+        let slot = attrs
+            .entry(attr.to_lowercase())
+            .or_insert_with(|| (attr.clone(), BTreeSet::new(), BTreeSet::new()));
+        slot.1.extend(vals.iter().map(|v| v.as_str()));
+    }
+    for (attr, vals) in &new.attributes {
+        let slot = attrs
+            .entry(attr.to_lowercase())
+            .or_insert_with(|| (attr.clone(), BTreeSet::new(), BTreeSet::new()));
+        slot.0 = attr.clone();
+        slot.2.extend(vals.iter().map(|v| v.as_str()));
+    }
+
+    attrs
+        .into_iter()
+        .filter(|(key, _)| !ignored.contains(key))
+        .filter_map(|(_, (attr, old_vals, new_vals))| {
+            let removed: Vec<String> = old_vals
+                .difference(&new_vals)
+                .map(|v| v.to_string())
+                .collect();
+            let added: Vec<String> = new_vals
+                .difference(&old_vals)
+                .map(|v| v.to_string())
+                .collect();
+            if removed.is_empty() && added.is_empty() {
+                None
+            } else {
+                Some(AttributeChange {
+                    attr,
+                    removed,
+                    added,
+                })
+            }
+        })
+        .collect()
+}
+
+/// Escape text for a Markdown table cell.
+fn md_cell(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('\r', "")
+        .replace('\n', "<br>")
+}
+
+fn md_values(vals: &[String]) -> String {
+    vals.iter()
+        .map(|v| md_cell(v))
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+fn html_values(vals: &[String]) -> String {
+    vals.iter()
+        .map(|v| html_escape(v))
+        .collect::<Vec<_>>()
+        .join("<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    fn snapshots() -> (Vec<LdapEntry>, Vec<LdapEntry>) {
+        let old = vec![
+            test_entry(
+                "cn=Alice,ou=People,dc=example,dc=com",
+                &[("mail", &["alice@old.com"]), ("cn", &["Alice"])],
+            ),
+            test_entry("cn=Bob,ou=People,dc=example,dc=com", &[("cn", &["Bob"])]),
+            test_entry(
+                "cn=Admins,ou=Groups,dc=example,dc=com",
+                &[("member", &["cn=Alice", "cn=Bob"])],
+            ),
+        ];
+        let new = vec![
+            test_entry(
+                "CN=Alice, ou=People,dc=example,dc=com",
+                &[
+                    ("Mail", &["alice@new.com"]),
+                    ("cn", &["Alice"]),
+                    ("modifyTimestamp", &["20260101000000Z"]),
+                ],
+            ),
+            test_entry(
+                "cn=Admins,ou=Groups,dc=example,dc=com",
+                &[("member", &["cn=Bob", "cn=Carol"])],
+            ),
+            test_entry(
+                "cn=Carol,ou=People,dc=example,dc=com",
+                &[("cn", &["Carol"])],
+            ),
+        ];
+        (old, new)
+    }
+
+    #[test]
+    fn test_compare_snapshots() {
+        let (old, new) = snapshots();
+        let diff = SnapshotDiff::compare(&old, &new, &["modifyTimestamp".to_string()]);
+
+        assert_eq!(diff.added.len(), 1);
+        assert_eq!(diff.added[0].dn, "cn=Carol,ou=People,dc=example,dc=com");
+        assert_eq!(diff.removed.len(), 1);
+        assert_eq!(diff.removed[0].dn, "cn=Bob,ou=People,dc=example,dc=com");
+
+        // Parents-first order: ou=Groups sorts before ou=People
+        assert_eq!(diff.changed.len(), 2);
+        assert_eq!(diff.changed[0].dn, "cn=Admins,ou=Groups,dc=example,dc=com");
+        assert_eq!(
+            diff.changed[0].changes,
+            vec![AttributeChange {
+                attr: "member".to_string(),
+                removed: vec!["cn=Alice".to_string()],
+                added: vec!["cn=Carol".to_string()],
+            }]
+        );
+        assert_eq!(
+            diff.changed[1].changes,
+            vec![AttributeChange {
+                attr: "Mail".to_string(),
+                removed: vec!["alice@old.com".to_string()],
+                added: vec!["alice@new.com".to_string()],
+            }]
+        );
+        assert_eq!(diff.summary(), "1 added, 1 removed, 2 changed");
+    }
+
+    #[test]
+    fn test_value_order_is_ignored() {
+        let old = vec![test_entry("cn=G,dc=x", &[("member", &["a", "b"])])];
+        let new = vec![test_entry("cn=G,dc=x", &[("member", &["b", "a"])])];
+        assert!(SnapshotDiff::compare(&old, &new, &[]).is_empty());
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let (old, new) = snapshots();
+        let diff = SnapshotDiff::compare(&old, &new, &["modifyTimestamp".to_string()]);
+        let md = diff.to_markdown("q1.ldif", "q2.ldif");
+        assert!(md.starts_with("# Snapshot Comparison\n"));
+        assert!(md.contains("- **Old:** q1.ldif\n"));
+        assert!(md.contains("## Added Entries (1)"));
+        assert!(md.contains("| Mail | alice@old.com | alice@new.com |"));
+        assert!(!md.contains("modifyTimestamp"));
+    }
+
+    #[test]
+    fn test_html_report_escapes_values() {
+        let old = vec![test_entry("cn=A,dc=x", &[("description", &["a < b"])])];
+        let new = vec![test_entry("cn=A,dc=x", &[("description", &["<script>"])])];
+        let html = SnapshotDiff::compare(&old, &new, &[]).to_html("old", "new");
+        assert!(html.contains("<td class=\"old\">a &lt; b</td>"));
+        assert!(html.contains("<td class=\"new\">&lt;script&gt;</td>"));
+        assert!(!html.contains("<script>"));
+    }
+
+    #[test]
+    fn test_report_format_from_path() {
+        assert_eq!(
+            ReportFormat::from_path(Path::new("audit.md")),
+            Some(ReportFormat::Markdown)
+        );
+        assert_eq!(
+            ReportFormat::from_path(Path::new("audit.HTML")),
+            Some(ReportFormat::Html)
+        );
+        assert_eq!(ReportFormat::from_path(Path::new("audit.txt")), None);
+    }
+}
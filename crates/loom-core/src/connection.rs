@@ -1,11 +1,21 @@
 use std::sync::{Arc, Mutex};
 use std::time::Duration;
 
-use ldap3::{Ldap, LdapConnAsync, LdapConnSettings};
+use ldap3::controls::{ProxyAuth, RawControl};
+use ldap3::{Ldap, LdapConnAsync, LdapConnSettings, LdapError, StdStream};
+use rand::seq::SliceRandom;
 use tracing::{error, info, warn};
+use zeroize::Zeroize;
 
+use crate::audit::AuditLog;
+use crate::auth::SaslMechanism;
+use crate::dry_run::ChangeScript;
 use crate::error::CoreError;
+use crate::srv::{self, SrvRecord};
+use crate::staging::ChangeQueue;
 use crate::tls::{self, CertificateInfo, TrustStore};
+use crate::trace::{OperationTrace, TraceRecord};
+use crate::tunnel::{self, ProxySettings, SshTunnel, SshTunnelSettings};
 
 /// TLS mode for LDAP connections.
 #[derive(Debug, Clone, Default, PartialEq, serde::Serialize, serde::Deserialize)]
@@ -58,6 +68,30 @@ pub struct ConnectionSettings {
     /// to bypass server-side schema violations from directory plugins.
     #[serde(default)]
     pub relax_rules: bool,
+    /// Bind with this SASL mechanism instead of a simple bind.
+    #[serde(default)]
+    pub sasl_mechanism: Option<SaslMechanism>,
+    /// Identity to act as (`dn:...` or `u:...`), sent with every request
+    /// after the bind in the Proxied Authorization control (RFC 4370).
+    #[serde(default)]
+    pub authz_id: Option<String>,
+    /// Proxy the server is reached through.
+    #[serde(default)]
+    pub proxy: Option<ProxySettings>,
+    /// Jump host the server is reached through; used instead of `proxy`.
+    #[serde(default)]
+    pub ssh_tunnel: Option<SshTunnelSettings>,
+    /// Treat `host` as a domain and connect to the servers its
+    /// `_ldap._tcp` SRV records name, failing over between them.
+    #[serde(default)]
+    pub dns_srv: bool,
+    /// Further hosts to fail over to, on the same port, when `host`
+    /// doesn't answer.
+    #[serde(default)]
+    pub failover_hosts: Vec<String>,
+    /// Try `host` and `failover_hosts` in random order rather than as listed.
+    #[serde(default)]
+    pub shuffle_hosts: bool,
 }
 
 fn default_port() -> u16 {
@@ -75,10 +109,27 @@ pub struct LdapConnection {
     pub ldap: Ldap,
     pub settings: ConnectionSettings,
     pub base_dn: String,
+    /// The server in use when it was discovered from DNS or picked from
+    /// the failover hosts.
+    pub server: Option<SrvRecord>,
     /// Credentials stored for reconnection.
-    bind_credentials: Option<(String, String)>, // (bind_dn, password)
+    pub(crate) bind_credentials: Option<(String, String)>, // (bind_dn, password)
+    /// Set once the stored password has been wiped, until it's given again.
+    password_cleared: bool,
     /// Optional trust store for custom certificate verification.
-    trust_store: Option<Arc<TrustStore>>,
+    pub(crate) trust_store: Option<Arc<TrustStore>>,
+    /// When set, write operations are recorded here instead of sent.
+    pub(crate) dry_run: Option<ChangeScript>,
+    /// Writes queued instead of sent while staging is on.
+    pub(crate) changes: ChangeQueue,
+    /// Where writes are logged, when auditing.
+    pub(crate) audit: Option<AuditLog>,
+    /// Requests sent and their results, shared with the pool's other
+    /// connections.
+    pub(crate) trace: OperationTrace,
+    /// The SSH tunnel the connection runs through, shared with the pool's
+    /// other connections and reused on reconnect.
+    pub(crate) tunnel: Option<Arc<Mutex<SshTunnel>>>,
 }
 
 impl LdapConnection {
@@ -89,6 +140,16 @@ impl LdapConnection {
     pub async fn connect(
         settings: ConnectionSettings,
         trust_store: Option<Arc<TrustStore>>,
+    ) -> Result<Self, CoreError> {
+        Self::connect_via(settings, trust_store, None).await
+    }
+
+    /// Connect like [`connect`](Self::connect), going through `tunnel`
+    /// when it's still running rather than starting another one.
+    pub(crate) async fn connect_via(
+        settings: ConnectionSettings,
+        trust_store: Option<Arc<TrustStore>>,
+        tunnel: Option<Arc<Mutex<SshTunnel>>>,
     ) -> Result<Self, CoreError> {
         info!(
             "Connecting to {}:{} (TLS mode: {})",
@@ -97,15 +158,9 @@ impl LdapConnection {
             settings.tls_mode.label()
         );
         let timeout = Duration::from_secs(settings.timeout_secs);
-
-        let ldap = match settings.tls_mode {
-            TlsMode::Auto => Self::auto_connect(&settings, timeout, trust_store.as_ref()).await?,
-            TlsMode::Ldaps => Self::connect_ldaps(&settings, timeout, trust_store.as_ref()).await?,
-            TlsMode::StartTls => {
-                Self::connect_starttls(&settings, timeout, trust_store.as_ref()).await?
-            }
-            TlsMode::None => Self::connect_plain(&settings, timeout).await?,
-        };
+        let tunnel = Self::ensure_tunnel(&settings, tunnel, timeout).await?;
+        let (ldap, server) =
+            Self::open_any(&settings, timeout, trust_store.as_ref(), &tunnel, None).await?;
 
         let base_dn = settings.base_dn.clone().unwrap_or_default();
 
@@ -113,8 +168,161 @@ impl LdapConnection {
             ldap,
             settings,
             base_dn,
+            server,
             bind_credentials: None,
+            password_cleared: false,
             trust_store,
+            dry_run: None,
+            changes: ChangeQueue::default(),
+            audit: None,
+            trace: OperationTrace::default(),
+            tunnel,
+        })
+    }
+
+    /// The running SSH tunnel the settings ask for: `tunnel` when its
+    /// `ssh` is still alive, otherwise a new one.
+    async fn ensure_tunnel(
+        settings: &ConnectionSettings,
+        tunnel: Option<Arc<Mutex<SshTunnel>>>,
+        timeout: Duration,
+    ) -> Result<Option<Arc<Mutex<SshTunnel>>>, CoreError> {
+        let Some(ssh) = &settings.ssh_tunnel else {
+            return Ok(None);
+        };
+        if let Some(tunnel) = tunnel {
+            if tunnel.lock().map(|mut t| t.is_alive()).unwrap_or(false) {
+                return Ok(Some(tunnel));
+            }
+            warn!("SSH tunnel through {} exited, opening it again", ssh.host);
+        }
+        let tunnel = SshTunnel::open(ssh, timeout).await?;
+        Ok(Some(Arc::new(Mutex::new(tunnel))))
+    }
+
+    /// The servers to try in turn: those the domain publishes when the
+    /// settings ask for discovery, or `host` and the failover hosts. None
+    /// for a single host.
+    async fn candidates(
+        settings: &ConnectionSettings,
+    ) -> Result<Option<Vec<SrvRecord>>, CoreError> {
+        if settings.dns_srv {
+            return srv::discover(&settings.host).await.map(Some);
+        }
+        if settings.failover_hosts.is_empty() {
+            return Ok(None);
+        }
+        let mut hosts: Vec<SrvRecord> = std::iter::once(&settings.host)
+            .chain(&settings.failover_hosts)
+            .map(|host| SrvRecord {
+                priority: 0,
+                weight: 0,
+                port: settings.port,
+                target: host.clone(),
+            })
+            .collect();
+        if settings.shuffle_hosts {
+            hosts.shuffle(&mut rand::thread_rng());
+        }
+        Ok(Some(hosts))
+    }
+
+    /// Open the LDAP connection, to the first server that answers of
+    /// those the domain publishes or the failover hosts. `failed` is the
+    /// server a reconnect is replacing; it's tried last.
+    async fn open_any(
+        settings: &ConnectionSettings,
+        timeout: Duration,
+        trust_store: Option<&Arc<TrustStore>>,
+        tunnel: &Option<Arc<Mutex<SshTunnel>>>,
+        failed: Option<&SrvRecord>,
+    ) -> Result<(Ldap, Option<SrvRecord>), CoreError> {
+        let Some(candidates) = Self::candidates(settings).await? else {
+            let ldap = Self::open(settings, timeout, trust_store, tunnel).await?;
+            return Ok((ldap, None));
+        };
+        let mut last_error = None;
+        for record in srv::try_last(candidates, failed) {
+            let server = ConnectionSettings {
+                host: record.target.clone(),
+                port: record.port,
+                dns_srv: false,
+                failover_hosts: Vec::new(),
+                ..settings.clone()
+            };
+            match Self::open(&server, timeout, trust_store, tunnel).await {
+                Ok(ldap) => {
+                    info!("Using {} for {}", record.address(), settings.host);
+                    return Ok((ldap, Some(record)));
+                }
+                Err(CoreError::CertificateNotTrusted(info)) => {
+                    return Err(CoreError::CertificateNotTrusted(info));
+                }
+                Err(e) => {
+                    warn!("{} failed, trying the next server: {}", record.address(), e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            CoreError::ConnectionFailed(format!("No servers found for {}", settings.host))
+        }))
+    }
+
+    /// The settings with the server in use in place of a DNS domain, or
+    /// first among the failover hosts, so further connections go to the
+    /// same server.
+    pub(crate) fn pinned_settings(&self) -> ConnectionSettings {
+        match &self.server {
+            Some(server) => ConnectionSettings {
+                host: server.target.clone(),
+                port: server.port,
+                dns_srv: false,
+                failover_hosts: if self.settings.dns_srv {
+                    Vec::new()
+                } else {
+                    std::iter::once(&self.settings.host)
+                        .chain(&self.settings.failover_hosts)
+                        .filter(|host| **host != server.target)
+                        .cloned()
+                        .collect()
+                },
+                shuffle_hosts: false,
+                ..self.settings.clone()
+            },
+            None => self.settings.clone(),
+        }
+    }
+
+    /// Open the LDAP connection in the settings' TLS mode.
+    async fn open(
+        settings: &ConnectionSettings,
+        timeout: Duration,
+        trust_store: Option<&Arc<TrustStore>>,
+        tunnel: &Option<Arc<Mutex<SshTunnel>>>,
+    ) -> Result<Ldap, CoreError> {
+        let via = tunnel
+            .as_ref()
+            .and_then(|t| t.lock().ok().map(|t| t.port()));
+        match settings.tls_mode {
+            TlsMode::Auto => Self::auto_connect(settings, timeout, trust_store, via).await,
+            TlsMode::Ldaps => Self::connect_ldaps(settings, timeout, trust_store, via).await,
+            TlsMode::StartTls => Self::connect_starttls(settings, timeout, trust_store, via).await,
+            TlsMode::None => Self::connect_plain(settings, timeout, via).await,
+        }
+    }
+
+    /// Have ldap3 use a stream opened through the settings' SSH tunnel
+    /// (listening on the `via` port) or proxy, when there is one.
+    async fn route(
+        conn_settings: LdapConnSettings,
+        settings: &ConnectionSettings,
+        timeout: Duration,
+        via: Option<u16>,
+    ) -> Result<LdapConnSettings, CoreError> {
+        Ok(match tunnel::open_stream(settings, via, timeout).await? {
+            Some(stream) => conn_settings.set_std_stream(StdStream::Tcp(stream)),
+            None => conn_settings,
         })
     }
 
@@ -122,6 +330,7 @@ impl LdapConnection {
         settings: &ConnectionSettings,
         timeout: Duration,
         trust_store: Option<&Arc<TrustStore>>,
+        via: Option<u16>,
     ) -> Result<Ldap, CoreError> {
         // Try LDAPS first (port 636 or user-specified)
         let ldaps_port = if settings.port == 389 {
@@ -134,7 +343,7 @@ impl LdapConnection {
             ..settings.clone()
         };
 
-        match Self::connect_ldaps(&ldaps_settings, timeout, trust_store).await {
+        match Self::connect_ldaps(&ldaps_settings, timeout, trust_store, via).await {
             Ok(ldap) => {
                 info!("Connected via LDAPS on port {}", ldaps_port);
                 return Ok(ldap);
@@ -153,7 +362,7 @@ impl LdapConnection {
         }
 
         // Try StartTLS on port 389
-        match Self::connect_starttls(settings, timeout, trust_store).await {
+        match Self::connect_starttls(settings, timeout, trust_store, via).await {
             Ok(ldap) => {
                 info!("Connected via StartTLS on port {}", settings.port);
                 return Ok(ldap);
@@ -171,7 +380,7 @@ impl LdapConnection {
         }
 
         // Fall back to plain
-        let ldap = Self::connect_plain(settings, timeout).await?;
+        let ldap = Self::connect_plain(settings, timeout, via).await?;
         info!("Connected via plain LDAP on port {}", settings.port);
         Ok(ldap)
     }
@@ -209,10 +418,24 @@ impl LdapConnection {
         (conn_settings, captured)
     }
 
+    /// Connect to `url`, giving up after `timeout`. ldap3's connect
+    /// timeout covers only the TCP connect; this also bounds the TLS
+    /// handshake and StartTLS exchange, which can hang on a server that
+    /// accepts the connection but never answers.
+    async fn open_url(
+        conn_settings: LdapConnSettings,
+        url: &str,
+        timeout: Duration,
+    ) -> Result<(LdapConnAsync, Ldap), LdapError> {
+        tokio::time::timeout(timeout, LdapConnAsync::with_settings(conn_settings, url))
+            .await
+            .unwrap_or_else(|elapsed| Err(LdapError::Timeout { elapsed }))
+    }
+
     /// Check if a captured certificate slot has a value and return the appropriate error.
     fn check_captured_cert(
         captured: Option<&Arc<Mutex<Option<CertificateInfo>>>>,
-        err: impl std::fmt::Display,
+        err: LdapError,
         protocol: &str,
     ) -> CoreError {
         if let Some(slot) = captured {
@@ -224,13 +447,17 @@ impl LdapConnection {
             }
         }
         error!("{} connection failed: {}", protocol, err);
-        CoreError::ConnectionFailed(format!("{}: {}", protocol, err))
+        match err {
+            LdapError::Timeout { .. } => CoreError::Timeout,
+            err => CoreError::ConnectionFailed(format!("{}: {}", protocol, err)),
+        }
     }
 
     async fn connect_ldaps(
         settings: &ConnectionSettings,
         timeout: Duration,
         trust_store: Option<&Arc<TrustStore>>,
+        via: Option<u16>,
     ) -> Result<Ldap, CoreError> {
         info!(
             "Attempting LDAPS connection to {}:{}",
@@ -239,7 +466,8 @@ impl LdapConnection {
         let url = format!("ldaps://{}:{}", settings.host, settings.port);
         let (conn_settings, captured) =
             Self::build_conn_settings(settings, timeout, trust_store, false);
-        let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, &url)
+        let conn_settings = Self::route(conn_settings, settings, timeout, via).await?;
+        let (conn, ldap) = Self::open_url(conn_settings, &url, timeout)
             .await
             .map_err(|e| Self::check_captured_cert(captured.as_ref(), e, "LDAPS"))?;
         ldap3::drive!(conn);
@@ -250,6 +478,7 @@ impl LdapConnection {
         settings: &ConnectionSettings,
         timeout: Duration,
         trust_store: Option<&Arc<TrustStore>>,
+        via: Option<u16>,
     ) -> Result<Ldap, CoreError> {
         info!(
             "Attempting StartTLS connection to {}:{}",
@@ -258,7 +487,8 @@ impl LdapConnection {
         let url = format!("ldap://{}:{}", settings.host, settings.port);
         let (conn_settings, captured) =
             Self::build_conn_settings(settings, timeout, trust_store, true);
-        let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, &url)
+        let conn_settings = Self::route(conn_settings, settings, timeout, via).await?;
+        let (conn, ldap) = Self::open_url(conn_settings, &url, timeout)
             .await
             .map_err(|e| Self::check_captured_cert(captured.as_ref(), e, "StartTLS"))?;
         ldap3::drive!(conn);
@@ -268,6 +498,7 @@ impl LdapConnection {
     async fn connect_plain(
         settings: &ConnectionSettings,
         timeout: Duration,
+        via: Option<u16>,
     ) -> Result<Ldap, CoreError> {
         info!(
             "Attempting plain LDAP connection to {}:{}",
@@ -275,14 +506,18 @@ impl LdapConnection {
         );
         let url = format!("ldap://{}:{}", settings.host, settings.port);
         let conn_settings = LdapConnSettings::new().set_conn_timeout(timeout);
-        let (conn, ldap) = LdapConnAsync::with_settings(conn_settings, &url)
+        let conn_settings = Self::route(conn_settings, settings, timeout, via).await?;
+        let (conn, ldap) = Self::open_url(conn_settings, &url, timeout)
             .await
             .map_err(|e| {
                 error!(
                     "Plain LDAP connection to {}:{} failed: {}",
                     settings.host, settings.port, e
                 );
-                CoreError::ConnectionFailed(format!("plain LDAP: {e}"))
+                match e {
+                    LdapError::Timeout { .. } => CoreError::Timeout,
+                    e => CoreError::ConnectionFailed(format!("plain LDAP: {e}")),
+                }
             })?;
         ldap3::drive!(conn);
         Ok(ldap)
@@ -291,6 +526,25 @@ impl LdapConnection {
     /// Store bind credentials for reconnection.
     pub fn store_credentials(&mut self, bind_dn: String, password: String) {
         self.bind_credentials = Some((bind_dn, password));
+        self.password_cleared = false;
+    }
+
+    /// Wipe the stored bind password, keeping the bind DN for the audit
+    /// log. Reconnecting fails until the password is stored again.
+    pub fn clear_password(&mut self) {
+        if let Some((_, password)) = self.bind_credentials.as_mut() {
+            password.zeroize();
+            self.password_cleared = true;
+        }
+    }
+
+    /// The bind DN whose password was wiped by
+    /// [`clear_password`](Self::clear_password), if it hasn't been given again.
+    pub fn cleared_bind_dn(&self) -> Option<&str> {
+        match &self.bind_credentials {
+            Some((bind_dn, _)) if self.password_cleared => Some(bind_dn),
+            _ => None,
+        }
     }
 
     /// Attempt to reconnect using stored settings and credentials.
@@ -301,32 +555,46 @@ impl LdapConnection {
             self.settings.host, self.settings.port
         );
 
-        let timeout = Duration::from_secs(self.settings.timeout_secs);
-        let ts = self.trust_store.as_ref();
-
-        let ldap = match self.settings.tls_mode {
-            TlsMode::Auto => Self::auto_connect(&self.settings, timeout, ts).await?,
-            TlsMode::Ldaps => Self::connect_ldaps(&self.settings, timeout, ts).await?,
-            TlsMode::StartTls => Self::connect_starttls(&self.settings, timeout, ts).await?,
-            TlsMode::None => Self::connect_plain(&self.settings, timeout).await?,
-        };
+        if let Some(bind_dn) = self.cleared_bind_dn() {
+            return Err(CoreError::CredentialError(format!(
+                "the password for {} was cleared when the session locked",
+                bind_dn
+            )));
+        }
 
+        let timeout = Duration::from_secs(self.settings.timeout_secs);
+        self.tunnel = Self::ensure_tunnel(&self.settings, self.tunnel.take(), timeout).await?;
+        let (ldap, server) = Self::open_any(
+            &self.settings,
+            timeout,
+            self.trust_store.as_ref(),
+            &self.tunnel,
+            self.server.as_ref(),
+        )
+        .await?;
         self.ldap = ldap;
+        self.server = server;
 
         // Re-bind with stored credentials
-        if let Some((ref bind_dn, ref password)) = self.bind_credentials {
-            let bind_dn = bind_dn.clone();
-            let password = password.clone();
-            self.simple_bind(&bind_dn, &password).await?;
-        } else {
-            self.anonymous_bind().await?;
+        match self.bind_credentials.clone() {
+            Some((bind_dn, password)) => self.bind(Some(&bind_dn), &password).await?,
+            None => self.bind(None, "").await?,
         }
 
+        self.trace.count_reconnect();
         info!("Reconnected successfully");
         Ok(())
     }
 
+    /// How long an operation waits on the server before giving up with
+    /// [`CoreError::Timeout`], from the settings' `timeout_secs`.
+    pub fn op_timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.timeout_secs)
+    }
+
     /// Check if an error indicates a lost connection that may be recoverable.
+    /// A timeout isn't one: the server is slow, and the connection stays
+    /// usable.
     pub fn is_connection_error(err: &CoreError) -> bool {
         match err {
             CoreError::Ldap(ldap_err) => {
@@ -338,14 +606,99 @@ impl LdapConnection {
                     || msg.contains("eof")
                     || msg.contains("timed out")
             }
-            CoreError::Timeout => true,
             CoreError::ConnectionFailed(_) => true,
             _ => false,
         }
     }
 
+    /// The requests this connection has sent.
+    pub fn trace(&self) -> &OperationTrace {
+        &self.trace
+    }
+
+    /// The handle for the next request, sending `controls` plus the
+    /// Proxied Authorization control when the settings name an
+    /// `authz_id`, so the server applies that identity's access rights.
+    /// The request gives up after [`op_timeout`](Self::op_timeout).
+    pub(crate) fn request(&mut self, controls: Vec<RawControl>) -> &mut Ldap {
+        let timeout = self.op_timeout();
+        self.request_within(controls, Some(timeout))
+    }
+
+    /// Like [`request`](Self::request), waiting at most `timeout` for the
+    /// server, or however long it takes with none.
+    pub(crate) fn request_within(
+        &mut self,
+        mut controls: Vec<RawControl>,
+        timeout: Option<Duration>,
+    ) -> &mut Ldap {
+        if let Some(authzid) = &self.settings.authz_id {
+            controls.push(
+                ProxyAuth {
+                    authzid: authzid.clone(),
+                }
+                .into(),
+            );
+        }
+        let ldap = if controls.is_empty() {
+            &mut self.ldap
+        } else {
+            self.ldap.with_controls(controls)
+        };
+        ldap.timeout = timeout;
+        ldap
+    }
+
+    /// Name the Proxied Authorization control in `record` when
+    /// [`request`](Self::request) sends it.
+    pub(crate) fn trace_proxy(&self, record: TraceRecord) -> TraceRecord {
+        if self.settings.authz_id.is_some() {
+            record.control("proxiedAuthz")
+        } else {
+            record
+        }
+    }
+
     /// Unbind and close the connection.
     pub async fn disconnect(&mut self) -> Result<(), CoreError> {
-        self.ldap.unbind().await.map_err(CoreError::Ldap)
+        self.ldap.unbind().await.map_err(CoreError::from)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_unanswered_tls_handshake_times_out() {
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        // Accepts the connection, then never answers the TLS handshake
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        tokio::spawn(async move {
+            let (_socket, _) = listener.accept().await.unwrap();
+            std::future::pending::<()>().await
+        });
+        let settings = ConnectionSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls_mode: TlsMode::Ldaps,
+            bind_dn: None,
+            base_dn: None,
+            page_size: 500,
+            timeout_secs: 1,
+            relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            dns_srv: false,
+            failover_hosts: Vec::new(),
+            shuffle_hosts: false,
+        };
+        let result =
+            LdapConnection::connect_ldaps(&settings, Duration::from_millis(200), None, None).await;
+        assert!(matches!(result, Err(CoreError::Timeout)));
+        assert!(!LdapConnection::is_connection_error(&CoreError::Timeout));
     }
 }
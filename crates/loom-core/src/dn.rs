@@ -8,6 +8,15 @@ pub fn rdn(dn: &str) -> &str {
     dn.split(',').next().unwrap_or(dn)
 }
 
+/// The attribute names of an RDN, one per `+`-joined part:
+/// `cn=a+uid=b` gives `cn` and `uid`.
+pub fn rdn_attributes(rdn: &str) -> Vec<String> {
+    rdn.split('+')
+        .filter_map(|part| part.split_once('='))
+        .map(|(attr, _)| attr.trim().to_string())
+        .collect()
+}
+
 /// Get the depth of a DN (number of components).
 pub fn depth(dn: &str) -> usize {
     if dn.is_empty() {
@@ -27,12 +36,133 @@ pub fn is_ancestor(dn: &str, ancestor: &str) -> bool {
     dn_lower.ends_with(&ancestor_lower) && dn_lower.len() > ancestor_lower.len()
 }
 
+/// Heuristic: treat a value containing `attr=` before its first comma as a DN.
+pub fn looks_like_dn(value: &str) -> bool {
+    let first = value.split(',').next().unwrap_or("");
+    match first.find('=') {
+        Some(pos) => {
+            pos > 0
+                && first[..pos]
+                    .bytes()
+                    .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.')
+        }
+        None => false,
+    }
+}
+
+/// Escape a value for use in an RDN (RFC 4514): special characters are
+/// backslash-escaped, as are a leading `#` or space and a trailing space.
+pub fn escape_rdn_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let last = value.chars().count().saturating_sub(1);
+    for (i, c) in value.chars().enumerate() {
+        let edge = (i == 0 && (c == '#' || c == ' ')) || (i == last && c == ' ');
+        if edge || matches!(c, ',' | '+' | '"' | '\\' | '<' | '>' | ';' | '=') {
+            out.push('\\');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Check that `dn` has RFC 4514 string syntax: comma-separated RDNs of
+/// `type=value` pairs (joined by `+` when multi-valued) whose special
+/// characters are escaped.
+pub fn validate_dn(dn: &str) -> Result<(), String> {
+    if dn.trim().is_empty() {
+        return Err("DN is empty".to_string());
+    }
+    let chars: Vec<char> = dn.chars().collect();
+    let mut i = 0;
+    loop {
+        // Attribute type: a name or a numeric OID
+        let start = i;
+        while i < chars.len() && chars[i] != '=' {
+            i += 1;
+        }
+        let attr: String = chars[start..i].iter().collect();
+        let attr = attr.trim();
+        let is_name = attr.starts_with(|c: char| c.is_ascii_alphabetic())
+            && attr.chars().all(|c| c.is_ascii_alphanumeric() || c == '-');
+        let is_oid = !attr.is_empty()
+            && attr
+                .split('.')
+                .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_digit()));
+        if !is_name && !is_oid {
+            return Err(format!(
+                "Invalid attribute type '{}' at position {}",
+                attr, start
+            ));
+        }
+        if i == chars.len() {
+            return Err(format!("Missing '=' after '{}'", attr));
+        }
+        i += 1;
+
+        // Value, up to the next unescaped separator
+        while i < chars.len() && chars[i] != ',' && chars[i] != '+' {
+            match chars[i] {
+                '\\' => {
+                    let next = chars.get(i + 1).copied();
+                    match next {
+                        Some(c) if c.is_ascii_hexdigit() => {
+                            if !chars.get(i + 2).is_some_and(|c| c.is_ascii_hexdigit()) {
+                                return Err(format!("Incomplete hex escape at position {}", i));
+                            }
+                            i += 3;
+                        }
+                        Some(_) => i += 2,
+                        None => return Err("DN ends with a lone backslash".to_string()),
+                    }
+                }
+                c @ ('"' | ';' | '<' | '>') => {
+                    return Err(format!("Unescaped '{}' at position {}", c, i));
+                }
+                _ => i += 1,
+            }
+        }
+        if i == chars.len() {
+            return Ok(());
+        }
+        // Skip the separator; another RDN or AVA must follow
+        i += 1;
+        if i == chars.len() {
+            return Err("DN ends with a separator".to_string());
+        }
+    }
+}
+
 /// Get the display name from an RDN (the value part after '=').
 pub fn rdn_display_name(dn: &str) -> &str {
     let r = rdn(dn);
     r.find('=').map(|i| &r[i + 1..]).unwrap_or(r)
 }
 
+/// Move `dn` from under `from_base` to the same position under `to_base`
+/// (e.g. to find the matching entry on another server). DNs outside
+/// `from_base` are returned unchanged.
+pub fn rebase(dn: &str, from_base: &str, to_base: &str) -> String {
+    if from_base.is_empty() {
+        return dn.to_string();
+    }
+    if dn.eq_ignore_ascii_case(from_base) {
+        return to_base.to_string();
+    }
+    let split = dn.len().saturating_sub(from_base.len());
+    match (dn.get(..split), dn.get(split..)) {
+        (Some(prefix), Some(suffix))
+            if prefix.ends_with(',') && suffix.eq_ignore_ascii_case(from_base) =>
+        {
+            if to_base.is_empty() {
+                prefix.trim_end_matches(',').to_string()
+            } else {
+                format!("{}{}", prefix, to_base)
+            }
+        }
+        _ => dn.to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -50,6 +180,8 @@ mod tests {
     fn test_rdn() {
         assert_eq!(rdn("cn=admin,dc=example,dc=com"), "cn=admin");
         assert_eq!(rdn("dc=com"), "dc=com");
+        assert_eq!(rdn_attributes("cn=a+uid=b"), ["cn", "uid"]);
+        assert!(rdn_attributes("").is_empty());
     }
 
     #[test]
@@ -106,4 +238,61 @@ mod tests {
     fn test_rdn_display_name_no_equals() {
         assert_eq!(rdn_display_name("nodots"), "nodots");
     }
+
+    #[test]
+    fn test_rebase() {
+        assert_eq!(
+            rebase(
+                "cn=admin,ou=People,DC=Test,dc=com",
+                "dc=test,dc=com",
+                "dc=prod,dc=com"
+            ),
+            "cn=admin,ou=People,dc=prod,dc=com"
+        );
+        assert_eq!(
+            rebase("dc=test,dc=com", "dc=test,dc=com", "dc=prod,dc=com"),
+            "dc=prod,dc=com"
+        );
+        // Outside the base, or only a partial RDN match: unchanged
+        assert_eq!(
+            rebase("cn=x,dc=other,dc=com", "dc=test,dc=com", "dc=prod,dc=com"),
+            "cn=x,dc=other,dc=com"
+        );
+        assert_eq!(
+            rebase("cn=x,sdc=test,dc=com", "dc=test,dc=com", "dc=prod,dc=com"),
+            "cn=x,sdc=test,dc=com"
+        );
+    }
+
+    #[test]
+    fn test_looks_like_dn() {
+        assert!(looks_like_dn("cn=Admins,ou=Groups,dc=example,dc=com"));
+        assert!(looks_like_dn("uid=alice"));
+        assert!(!looks_like_dn("alice"));
+        assert!(!looks_like_dn("alice@example.com"));
+        assert!(!looks_like_dn("=oops,dc=example"));
+        assert!(!looks_like_dn("Doe, John"));
+        assert!(!looks_like_dn(""));
+    }
+
+    #[test]
+    fn test_validate_dn() {
+        assert!(validate_dn("cn=Admins,ou=Groups,dc=example,dc=com").is_ok());
+        assert!(validate_dn("cn=Doe\\, John+uid=jdoe,2.5.4.11=People").is_ok());
+        assert!(validate_dn("cn=caf\\c3\\a9,dc=example").is_ok());
+        assert!(validate_dn("").is_err());
+        assert!(validate_dn("alice").is_err());
+        assert!(validate_dn("cn=a,,dc=example").is_err());
+        assert!(validate_dn("cn=a,dc=example,").is_err());
+        assert!(validate_dn("cn=a;b,dc=example").is_err());
+        assert!(validate_dn("cn=a\\4,dc=example").is_err());
+    }
+
+    #[test]
+    fn test_escape_rdn_value() {
+        assert_eq!(escape_rdn_value("John Smith"), "John Smith");
+        assert_eq!(escape_rdn_value("Smith, John"), "Smith\\, John");
+        assert_eq!(escape_rdn_value("#1 fan "), "\\#1 fan\\ ");
+        assert_eq!(escape_rdn_value("a+b=c"), "a\\+b\\=c");
+    }
 }
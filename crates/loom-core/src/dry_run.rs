@@ -0,0 +1,235 @@
+use std::collections::HashSet;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use ldap3::Mod;
+use tracing::info;
+
+use crate::connection::LdapConnection;
+use crate::error::CoreError;
+use crate::export::ldif::{base64_encode, needs_base64};
+
+/// Write operations captured while a connection is in dry-run mode,
+/// kept as LDIF change records (RFC 2849) that can be applied later.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeScript {
+    records: Vec<String>,
+}
+
+impl ChangeScript {
+    /// Number of recorded change records.
+    pub fn len(&self) -> usize {
+        self.records.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.records.is_empty()
+    }
+
+    /// Record a modify operation.
+    pub fn record_modify(&mut self, dn: &str, mods: &[Mod<String>]) {
+        let mut record = header(dn, "modify");
+        for m in mods {
+            let (op, attr, vals) = match m {
+                Mod::Add(attr, vals) => ("add", attr, vals),
+                Mod::Delete(attr, vals) => ("delete", attr, vals),
+                Mod::Replace(attr, vals) => ("replace", attr, vals),
+                Mod::Increment(attr, val) => {
+                    let _ = writeln!(record, "increment: {}", attr);
+                    push_value(&mut record, attr, val);
+                    record.push_str("-\n");
+                    continue;
+                }
+            };
+            let _ = writeln!(record, "{}: {}", op, attr);
+            for value in sorted(vals) {
+                push_value(&mut record, attr, value);
+            }
+            record.push_str("-\n");
+        }
+        self.records.push(record);
+    }
+
+    /// Record an add operation.
+    pub fn record_add(&mut self, dn: &str, attrs: &[(String, HashSet<String>)]) {
+        let mut record = header(dn, "add");
+        for (attr, vals) in attrs {
+            for value in sorted(vals) {
+                push_value(&mut record, attr, value);
+            }
+        }
+        self.records.push(record);
+    }
+
+    /// Record a move of `dn` to `new_rdn` under `new_superior`.
+    pub fn record_moddn(&mut self, dn: &str, new_rdn: &str, new_superior: &str) {
+        let mut record = header(dn, "moddn");
+        push_value(&mut record, "newrdn", new_rdn);
+        record.push_str("deleteoldrdn: 1\n");
+        push_value(&mut record, "newsuperior", new_superior);
+        self.records.push(record);
+    }
+
+    /// Record a delete operation.
+    pub fn record_delete(&mut self, dn: &str) {
+        self.records.push(header(dn, "delete"));
+    }
+
+    /// Render the full script as LDIF text.
+    pub fn to_ldif(&self) -> String {
+        let mut out = String::from("version: 1\n");
+        for record in &self.records {
+            out.push('\n');
+            out.push_str(record);
+        }
+        out
+    }
+
+    /// Write the script to a file, returning the number of change records.
+    pub fn write_to(&self, path: &Path) -> Result<usize, CoreError> {
+        std::fs::write(path, self.to_ldif())
+            .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+        Ok(self.records.len())
+    }
+}
+
+fn header(dn: &str, changetype: &str) -> String {
+    let mut record = String::new();
+    push_value(&mut record, "dn", dn);
+    let _ = writeln!(record, "changetype: {}", changetype);
+    record
+}
+
+fn push_value(record: &mut String, attr: &str, value: &str) {
+    if needs_base64(value) {
+        let _ = writeln!(record, "{}:: {}", attr, base64_encode(value));
+    } else {
+        let _ = writeln!(record, "{}: {}", attr, value);
+    }
+}
+
+/// HashSet iteration order is random; sort so scripts are reproducible.
+fn sorted(vals: &HashSet<String>) -> Vec<&String> {
+    let mut vals: Vec<&String> = vals.iter().collect();
+    vals.sort();
+    vals
+}
+
+impl LdapConnection {
+    /// Start intercepting writes. Has no effect if dry run is already on.
+    pub fn start_dry_run(&mut self) {
+        if self.dry_run.is_none() {
+            info!("Dry run enabled for {}", self.settings.host);
+            self.dry_run = Some(ChangeScript::default());
+        }
+    }
+
+    /// Stop intercepting writes, returning the recorded script.
+    pub fn stop_dry_run(&mut self) -> Option<ChangeScript> {
+        let script = self.dry_run.take();
+        if let Some(ref s) = script {
+            info!(
+                "Dry run disabled for {} ({} recorded changes)",
+                self.settings.host,
+                s.len()
+            );
+        }
+        script
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run.is_some()
+    }
+
+    /// The script recorded so far, if dry run is on.
+    pub fn change_script(&self) -> Option<&ChangeScript> {
+        self.dry_run.as_ref()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_record_modify() {
+        let mut script = ChangeScript::default();
+        script.record_modify(
+            "cn=Alice,dc=example,dc=com",
+            &[
+                Mod::Replace("mail".to_string(), HashSet::from(["a@x.com".to_string()])),
+                Mod::Delete("description".to_string(), HashSet::new()),
+            ],
+        );
+        assert_eq!(
+            script.to_ldif(),
+            "version: 1\n\n\
+             dn: cn=Alice,dc=example,dc=com\n\
+             changetype: modify\n\
+             replace: mail\n\
+             mail: a@x.com\n\
+             -\n\
+             delete: description\n\
+             -\n"
+        );
+    }
+
+    #[test]
+    fn test_record_add_sorts_values() {
+        let mut script = ChangeScript::default();
+        script.record_add(
+            "cn=Bob,dc=example,dc=com",
+            &[(
+                "objectClass".to_string(),
+                HashSet::from(["top".to_string(), "person".to_string()]),
+            )],
+        );
+        let ldif = script.to_ldif();
+        assert!(ldif.contains("changetype: add\nobjectClass: person\nobjectClass: top\n"));
+    }
+
+    #[test]
+    fn test_record_delete_and_count() {
+        let mut script = ChangeScript::default();
+        assert!(script.is_empty());
+        script.record_delete("cn=Old,dc=example,dc=com");
+        script.record_delete("cn=Older,dc=example,dc=com");
+        assert_eq!(script.len(), 2);
+        let ldif = script.to_ldif();
+        assert!(ldif.contains("dn: cn=Old,dc=example,dc=com\nchangetype: delete\n\ndn: cn=Older"));
+    }
+
+    #[test]
+    fn test_record_moddn() {
+        let mut script = ChangeScript::default();
+        script.record_moddn(
+            "cn=Alice,ou=Old,dc=example,dc=com",
+            "cn=Alice",
+            "ou=New,dc=example,dc=com",
+        );
+        assert_eq!(
+            script.to_ldif(),
+            "version: 1\n\n\
+             dn: cn=Alice,ou=Old,dc=example,dc=com\n\
+             changetype: moddn\n\
+             newrdn: cn=Alice\n\
+             deleteoldrdn: 1\n\
+             newsuperior: ou=New,dc=example,dc=com\n"
+        );
+    }
+
+    #[test]
+    fn test_non_ascii_values_are_base64() {
+        let mut script = ChangeScript::default();
+        script.record_modify(
+            "cn=Zoë,dc=example,dc=com",
+            &[Mod::Add(
+                "cn".to_string(),
+                HashSet::from(["Zoë".to_string()]),
+            )],
+        );
+        let ldif = script.to_ldif();
+        assert!(ldif.contains("dn:: "));
+        assert!(ldif.contains("cn:: "));
+    }
+}
@@ -0,0 +1,157 @@
+//! Duplicate values: entries under a subtree sharing a value of one
+//! attribute, such as two accounts with the same `mail`. Worth clearing up
+//! before enabling a uniqueness constraint, which refuses to start or
+//! rejects updates while duplicates exist.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::util::find_values_ci;
+
+/// Attributes offered for the report; any attribute can be scanned.
+pub const COMMON_ATTRIBUTES: &[&str] = &["mail", "uid", "sAMAccountName", "proxyAddresses"];
+
+/// One value held by more than one entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DuplicateGroup {
+    /// The value as the first entry holds it.
+    pub value: String,
+    pub dns: Vec<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DuplicateReport {
+    pub base_dn: String,
+    pub attribute: String,
+    /// Entries holding the attribute.
+    pub entries_scanned: usize,
+    /// Most widely shared values first.
+    pub groups: Vec<DuplicateGroup>,
+}
+
+impl DuplicateReport {
+    /// The entries involved in any duplicate.
+    pub fn conflicting_entries(&self) -> usize {
+        self.groups.iter().map(|g| g.dns.len()).sum()
+    }
+
+    /// Write one row per conflicting entry: value, dn.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<usize, CoreError> {
+        let err = |e: csv::Error| CoreError::ExportError(e.to_string());
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record([self.attribute.as_str(), "dn"])
+            .map_err(err)?;
+        for group in &self.groups {
+            for dn in &group.dns {
+                csv.write_record([&group.value, dn]).map_err(err)?;
+            }
+        }
+        csv.flush()
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        Ok(self.conflicting_entries())
+    }
+}
+
+/// Group `entries` by their values of `attribute` and keep the values
+/// shared by several entries. Values compare ignoring case and
+/// surrounding spaces, as the usual matching rules of these attributes do.
+pub fn find_duplicates(base_dn: &str, entries: &[LdapEntry], attribute: &str) -> DuplicateReport {
+    let mut by_value: BTreeMap<String, DuplicateGroup> = BTreeMap::new();
+    let mut entries_scanned = 0;
+    for entry in entries {
+        let Some(values) = find_values_ci(&entry.attributes, attribute) else {
+            continue;
+        };
+        entries_scanned += 1;
+        for value in values {
+            let group = by_value
+                .entry(value.trim().to_lowercase())
+                .or_insert_with(|| DuplicateGroup {
+                    value: value.clone(),
+                    dns: Vec::new(),
+                });
+            // An entry holding the value twice in different case isn't a duplicate
+            if group.dns.last() != Some(&entry.dn) {
+                group.dns.push(entry.dn.clone());
+            }
+        }
+    }
+    let mut groups: Vec<DuplicateGroup> =
+        by_value.into_values().filter(|g| g.dns.len() > 1).collect();
+    groups.sort_by_key(|g| std::cmp::Reverse(g.dns.len()));
+    DuplicateReport {
+        base_dn: base_dn.to_string(),
+        attribute: attribute.to_string(),
+        entries_scanned,
+        groups,
+    }
+}
+
+impl LdapConnection {
+    /// Find values of `attribute` shared by several entries under `base_dn`.
+    pub async fn find_duplicate_values(
+        &mut self,
+        base_dn: &str,
+        attribute: &str,
+    ) -> Result<DuplicateReport, CoreError> {
+        let filter = format!("({}=*)", attribute);
+        let entries = self.search_subtree(base_dn, &filter, &[attribute]).await?;
+        Ok(find_duplicates(base_dn, &entries, attribute))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_find_duplicates() {
+        let entries = vec![
+            test_entry(
+                "cn=a",
+                &[("proxyAddresses", &["SMTP:amy@x.com", "smtp:sales@x.com"])],
+            ),
+            test_entry(
+                "cn=b",
+                &[("proxyAddresses", &["SMTP:bob@x.com", "smtp:Sales@x.com "])],
+            ),
+            test_entry(
+                "cn=c",
+                &[(
+                    "proxyAddresses",
+                    &["SMTP:carl@x.com", "smtp:sales@x.com", "smtp:bob@x.com"],
+                )],
+            ),
+            test_entry(
+                "cn=d",
+                &[("proxyAddresses", &["SMTP:dee@x.com", "smtp:DEE@x.com"])],
+            ),
+            LdapEntry::new("cn=e".to_string(), BTreeMap::new()),
+        ];
+        let report = find_duplicates("dc=x", &entries, "proxyaddresses");
+        assert_eq!(report.entries_scanned, 4);
+        assert_eq!(
+            report.groups,
+            [
+                DuplicateGroup {
+                    value: "smtp:sales@x.com".to_string(),
+                    dns: vec!["cn=a".into(), "cn=b".into(), "cn=c".into()],
+                },
+                DuplicateGroup {
+                    value: "SMTP:bob@x.com".to_string(),
+                    dns: vec!["cn=b".into(), "cn=c".into()],
+                },
+            ]
+        );
+        assert_eq!(report.conflicting_entries(), 5);
+
+        let mut csv = Vec::new();
+        assert_eq!(report.write_csv(&mut csv).unwrap(), 5);
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("proxyaddresses,dn\nsmtp:sales@x.com,cn=a\n"));
+    }
+}
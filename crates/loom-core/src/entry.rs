@@ -43,6 +43,19 @@ impl LdapEntry {
     }
 }
 
+/// An entry for tests; each pair adds its values to the named attribute.
+#[cfg(test)]
+pub(crate) fn test_entry(dn: &str, attrs: &[(&str, &[&str])]) -> LdapEntry {
+    let mut attributes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (name, values) in attrs {
+        attributes
+            .entry(name.to_string())
+            .or_default()
+            .extend(values.iter().map(|v| v.to_string()));
+    }
+    LdapEntry::new(dn.to_string(), attributes)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,5 +1,6 @@
 use thiserror::Error;
 
+use crate::modify::ModifyConflict;
 use crate::tls::CertificateInfo;
 
 #[derive(Error, Debug)]
@@ -19,6 +20,14 @@ pub enum CoreError {
     #[error("modify failed: {0}")]
     ModifyFailed(String),
 
+    #[error("{} of {} was changed on the server", .0.attr, .0.dn)]
+    ModifyConflict(Box<ModifyConflict>),
+
+    /// The server can't move a subtree in place; copying it and deleting
+    /// the originals is left to the caller to opt into.
+    #[error("the server can't move this subtree in place: {0}")]
+    SubtreeMoveUnsupported(String),
+
     #[error("add failed: {0}")]
     AddFailed(String),
 
@@ -43,9 +52,23 @@ pub enum CoreError {
     #[error("vault error: {0}")]
     VaultError(String),
 
-    #[error("timeout")]
+    #[error("edit policy error: {0}")]
+    PolicyError(String),
+
+    /// The server didn't answer within the connection's timeout. The
+    /// connection itself may well be fine.
+    #[error("timed out waiting for the server")]
     Timeout,
 
     #[error("ldap error: {0}")]
-    Ldap(#[from] ldap3::LdapError),
+    Ldap(#[source] ldap3::LdapError),
+}
+
+impl From<ldap3::LdapError> for CoreError {
+    fn from(e: ldap3::LdapError) -> Self {
+        match e {
+            ldap3::LdapError::Timeout { .. } => CoreError::Timeout,
+            e => CoreError::Ldap(e),
+        }
+    }
 }
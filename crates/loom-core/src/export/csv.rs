@@ -1,85 +1,190 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::schema::SchemaCache;
+use crate::util::find_values_ci;
 
-use super::requested_attrs;
+use super::writer::{write_all, TableSink, TableWriter};
 
 /// Export entries to CSV format.
 ///
-/// Columns: dn, then all unique attribute names sorted alphabetically.
+/// Columns: dn, then the requested attributes. For `["*"]` the columns come
+/// from the schema when one is given (see [`schema_columns`]), otherwise all
+/// unique attribute names sorted alphabetically.
 /// Multi-valued attributes are joined with "; ".
 pub fn export(
     entries: &[LdapEntry],
     path: &Path,
     attributes: &[String],
+    schema: Option<&SchemaCache>,
 ) -> Result<usize, CoreError> {
-    let file = std::fs::File::create(path)
-        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
-    let writer = std::io::BufWriter::new(file);
+    write_all(&mut writer(path, attributes, schema)?, entries)
+}
 
-    write_csv(writer, entries, attributes)
+/// A streaming writer for a CSV file at `path`; see [`export`].
+pub(super) fn writer<'a>(
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&'a SchemaCache>,
+) -> Result<TableWriter<'a, CsvSink<BufWriter<File>>>, CoreError> {
+    let file = File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    Ok(TableWriter::new(
+        CsvSink::new(BufWriter::new(file)),
+        attributes,
+        schema,
+    ))
 }
 
 /// Write entries in CSV format to any writer.
-pub fn write_csv<W: std::io::Write>(
+pub fn write_csv<W: Write>(
     writer: W,
     entries: &[LdapEntry],
     attributes: &[String],
 ) -> Result<usize, CoreError> {
-    if entries.is_empty() {
-        return Ok(0);
+    write_csv_with_schema(writer, entries, attributes, None)
+}
+
+/// Write entries in CSV format, using `schema` to build the header when all
+/// attributes are requested.
+pub fn write_csv_with_schema<W: Write>(
+    writer: W,
+    entries: &[LdapEntry],
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+) -> Result<usize, CoreError> {
+    write_all(
+        &mut TableWriter::new(CsvSink::new(writer), attributes, schema),
+        entries,
+    )
+}
+
+pub(super) struct CsvSink<W: Write> {
+    writer: csv::Writer<W>,
+}
+
+impl<W: Write> CsvSink<W> {
+    fn new(writer: W) -> Self {
+        Self {
+            writer: csv::Writer::from_writer(writer),
+        }
+    }
+}
+
+impl<W: Write> TableSink for CsvSink<W> {
+    fn header(&mut self, header: &[String]) -> Result<(), CoreError> {
+        self.writer
+            .write_record(header)
+            .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))
     }
 
-    let attr_names: Vec<String> = if let Some(attrs) = requested_attrs(attributes) {
-        attrs.to_vec()
-    } else {
-        let mut all_attrs: BTreeSet<String> = BTreeSet::new();
-        for entry in entries {
-            for key in entry.attributes.keys() {
-                all_attrs.insert(key.clone());
+    fn row(&mut self, cells: &[Vec<String>]) -> Result<(), CoreError> {
+        self.writer
+            .write_record(cells.iter().map(|vals| vals.join("; ")))
+            .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))
+    }
+
+    fn finish(&mut self, _rows: usize) -> Result<(), CoreError> {
+        self.writer
+            .flush()
+            .map_err(|e| CoreError::ExportError(format!("CSV flush failed: {}", e)))
+    }
+}
+
+/// What an all-attributes export needs to know to pick its columns, gathered
+/// an entry at a time: the objectClasses and attribute names in use.
+#[derive(Debug, Default)]
+pub(super) struct ColumnSet {
+    classes: BTreeSet<String>,
+    keys: BTreeSet<String>,
+}
+
+impl ColumnSet {
+    pub(super) fn add(&mut self, entry: &LdapEntry) {
+        for oc in find_values_ci(&entry.attributes, "objectClass")
+            .into_iter()
+            .flatten()
+        {
+            self.classes.insert(oc.to_lowercase());
+        }
+        for key in entry.attributes.keys() {
+            if !self.keys.contains(key) {
+                self.keys.insert(key.clone());
             }
         }
-        all_attrs.into_iter().collect()
-    };
+    }
 
-    let mut csv_writer = csv::Writer::from_writer(writer);
+    /// The schema's columns (see [`schema_columns`]) when a schema is given,
+    /// otherwise every attribute name, sorted.
+    pub(super) fn columns(&self, schema: Option<&SchemaCache>) -> Vec<String> {
+        let Some(schema) = schema else {
+            return self.keys.iter().cloned().collect();
+        };
+        let class_refs: Vec<&str> = self.classes.iter().map(|s| s.as_str()).collect();
+        let (must, may) = schema.must_may_attributes(&class_refs);
 
-    // Header: dn + attribute names
-    let mut header = vec!["dn".to_string()];
-    header.extend(attr_names.iter().cloned());
-    csv_writer
-        .write_record(&header)
-        .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))?;
+        let object_class = schema.canonical_attribute_name("objectClass");
+        let mut seen: BTreeSet<String> = BTreeSet::new();
+        let mut columns = Vec::new();
+        for name in std::iter::once(object_class).chain(must).chain(may) {
+            if seen.insert(name.to_lowercase()) {
+                columns.push(name);
+            }
+        }
 
-    // Data rows
-    for entry in entries {
-        let mut record = vec![entry.dn.clone()];
-        for attr in &attr_names {
-            let value = entry
-                .attributes
-                .get(attr)
-                .map(|vals| vals.join("; "))
-                .unwrap_or_default();
-            record.push(value);
+        let mut extra: BTreeMap<String, String> = BTreeMap::new();
+        for key in &self.keys {
+            let name = schema.canonical_attribute_name(key);
+            let lower = name.to_lowercase();
+            if !seen.contains(&lower) {
+                extra.entry(lower).or_insert(name);
+            }
         }
-        csv_writer
-            .write_record(&record)
-            .map_err(|e| CoreError::ExportError(format!("CSV write failed: {}", e)))?;
+        columns.extend(extra.into_values());
+        columns
     }
+}
 
-    csv_writer
-        .flush()
-        .map_err(|e| CoreError::ExportError(format!("CSV flush failed: {}", e)))?;
+/// Build a stable column list for an all-attributes export: `objectClass`,
+/// then the MUST and MAY attributes of every objectClass used by the entries
+/// (each group sorted), then any remaining attributes the schema doesn't
+/// account for, sorted. Columns are included even when no entry has a value,
+/// so repeated exports of the same classes share a header.
+pub fn schema_columns(schema: &SchemaCache, entries: &[LdapEntry]) -> Vec<String> {
+    let mut set = ColumnSet::default();
+    for entry in entries {
+        set.add(entry);
+    }
+    set.columns(Some(schema))
+}
 
-    Ok(entries.len())
+/// An entry's attributes keyed by canonical name, so that values stored
+/// under an alias (e.g. `commonName`) land in the schema's column (`cn`).
+pub(super) fn entry_values(
+    entry: &LdapEntry,
+    schema: Option<&SchemaCache>,
+) -> BTreeMap<String, Vec<String>> {
+    let Some(schema) = schema else {
+        return entry.attributes.clone();
+    };
+    let mut values: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for (key, vals) in &entry.attributes {
+        values
+            .entry(schema.canonical_attribute_name(key))
+            .or_default()
+            .extend(vals.iter().cloned());
+    }
+    values
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::collections::BTreeMap;
+    use crate::schema::{AttributeSyntax, AttributeTypeInfo, ObjectClassInfo, ObjectClassKind};
 
     #[test]
     fn test_export_csv() {
@@ -167,4 +272,95 @@ mod tests {
         // Bob has no mail → empty cell
         assert_eq!(lines[2], "\"cn=Bob,dc=example,dc=com\",,Bob");
     }
+
+    fn person_schema() -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        for (name, sup, must, may) in [
+            ("top", None, vec!["objectClass"], vec![]),
+            (
+                "person",
+                Some("top"),
+                vec!["sn", "cn"],
+                vec!["telephoneNumber"],
+            ),
+            ("mailUser", None, vec![], vec!["mail"]),
+        ] {
+            schema.object_classes.insert(
+                name.to_lowercase(),
+                ObjectClassInfo {
+                    oid: String::new(),
+                    names: vec![name.to_string()],
+                    description: None,
+                    superior: sup.map(|s: &str| s.to_string()),
+                    kind: ObjectClassKind::Structural,
+                    must: must.into_iter().map(String::from).collect(),
+                    may: may.into_iter().map(String::from).collect(),
+                },
+            );
+        }
+        let cn = AttributeTypeInfo {
+            oid: "2.5.4.3".to_string(),
+            names: vec!["cn".to_string(), "commonName".to_string()],
+            description: None,
+            syntax: AttributeSyntax::DirectoryString,
+            syntax_oid: None,
+            syntax_length: None,
+            superior: None,
+            equality: None,
+            ordering: None,
+            substr: None,
+            single_value: false,
+            no_user_modification: false,
+        };
+        schema.attribute_types.insert("cn".to_string(), cn.clone());
+        schema.attribute_types.insert("commonname".to_string(), cn);
+        schema
+    }
+
+    #[test]
+    fn test_schema_header_is_stable() {
+        let alice = LdapEntry::new(
+            "cn=Alice,dc=example,dc=com".to_string(),
+            BTreeMap::from([
+                (
+                    "objectClass".to_string(),
+                    vec!["person".to_string(), "mailUser".to_string()],
+                ),
+                ("commonName".to_string(), vec!["Alice".to_string()]),
+                ("sn".to_string(), vec!["Smith".to_string()]),
+                ("description".to_string(), vec!["Extra".to_string()]),
+            ]),
+        );
+        let bob = LdapEntry::new(
+            "cn=Bob,dc=example,dc=com".to_string(),
+            BTreeMap::from([
+                (
+                    "objectclass".to_string(),
+                    vec!["mailuser".to_string(), "person".to_string()],
+                ),
+                ("cn".to_string(), vec!["Bob".to_string()]),
+                ("mail".to_string(), vec!["bob@example.com".to_string()]),
+            ]),
+        );
+        let schema = person_schema();
+        let star = vec!["*".to_string()];
+
+        let header = "dn,objectClass,cn,sn,mail,telephoneNumber";
+        let mut buf = Vec::new();
+        write_csv_with_schema(&mut buf, std::slice::from_ref(&bob), &star, Some(&schema)).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        assert_eq!(output.lines().next(), Some(header));
+
+        // Values stored under an alias fill the canonical column; attributes
+        // outside the schema are appended after it
+        let mut buf = Vec::new();
+        write_csv_with_schema(&mut buf, &[alice, bob], &star, Some(&schema)).unwrap();
+        let output = String::from_utf8(buf).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[0], format!("{},description", header));
+        assert_eq!(
+            lines[1],
+            "\"cn=Alice,dc=example,dc=com\",person; mailUser,Alice,Smith,,,Extra"
+        );
+    }
 }
@@ -1,28 +1,13 @@
 use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
 
 use super::requested_attrs;
-
-/// Filter entries to include only the requested attributes.
-fn filter_entries(entries: &[LdapEntry], attributes: &[String]) -> Vec<LdapEntry> {
-    if let Some(attrs) = requested_attrs(attributes) {
-        entries
-            .iter()
-            .map(|entry| {
-                let filtered: BTreeMap<String, Vec<String>> = attrs
-                    .iter()
-                    .filter_map(|a| entry.attributes.get(a).map(|v| (a.clone(), v.clone())))
-                    .collect();
-                LdapEntry::new(entry.dn.clone(), filtered)
-            })
-            .collect()
-    } else {
-        entries.to_vec()
-    }
-}
+use super::writer::{write_all, EntryWriter};
 
 /// Export entries to JSON format (array of entry objects).
 pub fn export(
@@ -30,23 +15,89 @@ pub fn export(
     path: &Path,
     attributes: &[String],
 ) -> Result<usize, CoreError> {
-    let filtered = filter_entries(entries, attributes);
-    let json = serde_json::to_string_pretty(&filtered)
-        .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))?;
-
-    std::fs::write(path, json)
-        .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+    write_all(&mut writer(path, attributes)?, entries)
+}
 
-    Ok(entries.len())
+/// An [`EntryWriter`] for a JSON file at `path`.
+pub fn writer(
+    path: &Path,
+    attributes: &[String],
+) -> Result<JsonWriter<BufWriter<File>>, CoreError> {
+    let file = File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    Ok(JsonWriter::new(BufWriter::new(file), attributes))
 }
 
 /// Serialize entries to a JSON string.
 pub fn to_string(entries: &[LdapEntry], attributes: &[String]) -> Result<String, CoreError> {
-    let filtered = filter_entries(entries, attributes);
-    serde_json::to_string_pretty(&filtered)
+    let mut buf = Vec::new();
+    write_all(&mut JsonWriter::new(&mut buf, attributes), entries)?;
+    String::from_utf8(buf)
         .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))
 }
 
+/// Writes a pretty-printed JSON array one entry at a time; the output is
+/// the same as serializing the whole array at once.
+pub struct JsonWriter<W> {
+    writer: W,
+    attributes: Option<Vec<String>>,
+    count: usize,
+}
+
+impl<W: Write> JsonWriter<W> {
+    pub fn new(writer: W, attributes: &[String]) -> Self {
+        Self {
+            writer,
+            attributes: requested_attrs(attributes).map(<[String]>::to_vec),
+            count: 0,
+        }
+    }
+
+    fn write(&mut self, text: &str) -> Result<(), CoreError> {
+        self.writer
+            .write_all(text.as_bytes())
+            .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))
+    }
+}
+
+impl<W: Write> EntryWriter for JsonWriter<W> {
+    fn begin(&mut self) -> Result<(), CoreError> {
+        self.write("[")
+    }
+
+    fn write_entry(&mut self, entry: &LdapEntry) -> Result<(), CoreError> {
+        let json = match &self.attributes {
+            Some(attrs) => serde_json::to_string_pretty(&filter_entry(entry, attrs)),
+            None => serde_json::to_string_pretty(entry),
+        }
+        .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))?;
+        let separator = if self.count > 0 { ",\n  " } else { "\n  " };
+        self.write(separator)?;
+        // Indent the entry one level, as an array element. Newlines inside
+        // values are escaped, so every line break here is structural.
+        self.write(&json.replace('\n', "\n  "))?;
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<usize, CoreError> {
+        self.write(if self.count > 0 { "\n]" } else { "]" })?;
+        self.writer
+            .flush()
+            .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+        Ok(self.count)
+    }
+}
+
+/// `entry` with only the requested attributes.
+fn filter_entry(entry: &LdapEntry, attrs: &[String]) -> LdapEntry {
+    let filtered: BTreeMap<String, Vec<String>> = attrs
+        .iter()
+        .filter_map(|a| entry.attributes.get(a).map(|v| (a.clone(), v.clone())))
+        .collect();
+    LdapEntry::new(entry.dn.clone(), filtered)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -70,4 +121,26 @@ mod tests {
         assert_eq!(parsed.len(), 1);
         assert_eq!(parsed[0].dn, "cn=Test,dc=example,dc=com");
     }
+
+    #[test]
+    fn test_streamed_matches_whole_array() {
+        let entries: Vec<LdapEntry> = ["Alice", "Bob"]
+            .iter()
+            .map(|cn| {
+                LdapEntry::new(
+                    format!("cn={},dc=example,dc=com", cn),
+                    BTreeMap::from([
+                        ("cn".to_string(), vec![cn.to_string()]),
+                        ("description".to_string(), vec!["line\nbreak".to_string()]),
+                    ]),
+                )
+            })
+            .collect();
+        let star = vec!["*".to_string()];
+        assert_eq!(
+            to_string(&entries, &star).unwrap(),
+            serde_json::to_string_pretty(&entries).unwrap()
+        );
+        assert_eq!(to_string(&[], &star).unwrap(), "[]");
+    }
 }
@@ -1,10 +1,12 @@
-use std::io::Write;
+use std::fs::File;
+use std::io::{BufWriter, Write};
 use std::path::Path;
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
 
 use super::requested_attrs;
+use super::writer::{write_all, EntryWriter};
 
 /// Export entries to LDIF format (RFC 2849).
 pub fn export(
@@ -12,11 +14,17 @@ pub fn export(
     path: &Path,
     attributes: &[String],
 ) -> Result<usize, CoreError> {
-    let file = std::fs::File::create(path)
-        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
-    let mut writer = std::io::BufWriter::new(file);
+    write_all(&mut writer(path, attributes)?, entries)
+}
 
-    write_ldif(&mut writer, entries, attributes)
+/// An [`EntryWriter`] for an LDIF file at `path`.
+pub fn writer(
+    path: &Path,
+    attributes: &[String],
+) -> Result<LdifWriter<BufWriter<File>>, CoreError> {
+    let file = File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    Ok(LdifWriter::new(BufWriter::new(file), attributes))
 }
 
 /// Write entries in LDIF format to any writer.
@@ -25,61 +33,78 @@ pub fn write_ldif<W: Write>(
     entries: &[LdapEntry],
     attributes: &[String],
 ) -> Result<usize, CoreError> {
-    let filtered = requested_attrs(attributes);
-    let mut count = 0;
+    write_all(&mut LdifWriter::new(writer, attributes), entries)
+}
 
-    for entry in entries {
-        if count > 0 {
-            writeln!(writer).map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+/// Writes LDIF records one entry at a time.
+pub struct LdifWriter<W> {
+    writer: W,
+    attributes: Option<Vec<String>>,
+    count: usize,
+}
+
+impl<W: Write> LdifWriter<W> {
+    pub fn new(writer: W, attributes: &[String]) -> Self {
+        Self {
+            writer,
+            attributes: requested_attrs(attributes).map(<[String]>::to_vec),
+            count: 0,
         }
+    }
+}
 
-        // DN line
-        if needs_base64(&entry.dn) {
-            writeln!(writer, "dn:: {}", base64_encode(&entry.dn))
-        } else {
-            writeln!(writer, "dn: {}", entry.dn)
+fn write_value<W: Write>(writer: &mut W, attr: &str, value: &str) -> Result<(), CoreError> {
+    if needs_base64(value) {
+        writeln!(writer, "{}:: {}", attr, base64_encode(value))
+    } else {
+        writeln!(writer, "{}: {}", attr, value)
+    }
+    .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))
+}
+
+impl<W: Write> EntryWriter for LdifWriter<W> {
+    fn begin(&mut self) -> Result<(), CoreError> {
+        Ok(())
+    }
+
+    fn write_entry(&mut self, entry: &LdapEntry) -> Result<(), CoreError> {
+        if self.count > 0 {
+            writeln!(self.writer)
+                .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
         }
-        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+
+        // DN line
+        write_value(&mut self.writer, "dn", &entry.dn)?;
 
         // Attributes
-        if let Some(attrs) = filtered {
+        if let Some(attrs) = &self.attributes {
             for attr in attrs {
-                if let Some(values) = entry.attributes.get(attr) {
-                    for value in values {
-                        if needs_base64(value) {
-                            writeln!(writer, "{}:: {}", attr, base64_encode(value))
-                        } else {
-                            writeln!(writer, "{}: {}", attr, value)
-                        }
-                        .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
-                    }
+                for value in entry.attributes.get(attr).into_iter().flatten() {
+                    write_value(&mut self.writer, attr, value)?;
                 }
             }
         } else {
             for (attr, values) in &entry.attributes {
                 for value in values {
-                    if needs_base64(value) {
-                        writeln!(writer, "{}:: {}", attr, base64_encode(value))
-                    } else {
-                        writeln!(writer, "{}: {}", attr, value)
-                    }
-                    .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))?;
+                    write_value(&mut self.writer, attr, value)?;
                 }
             }
         }
 
-        count += 1;
+        self.count += 1;
+        Ok(())
     }
 
-    writer
-        .flush()
-        .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
-
-    Ok(count)
+    fn finish(&mut self) -> Result<usize, CoreError> {
+        self.writer
+            .flush()
+            .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))?;
+        Ok(self.count)
+    }
 }
 
 /// Check if a value needs base64 encoding for LDIF.
-fn needs_base64(s: &str) -> bool {
+pub(crate) fn needs_base64(s: &str) -> bool {
     if s.is_empty() {
         return false;
     }
@@ -91,7 +116,7 @@ fn needs_base64(s: &str) -> bool {
         .any(|b| b > 127 || (b < 32 && b != b'\n' && b != b'\r'))
 }
 
-fn base64_encode(s: &str) -> String {
+pub(crate) fn base64_encode(s: &str) -> String {
     use base64::Engine;
     base64::engine::general_purpose::STANDARD.encode(s.as_bytes())
 }
@@ -0,0 +1,136 @@
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::schema::SchemaCache;
+
+use super::writer::{write_all, TableSink, TableWriter};
+
+/// Export entries as a GitHub-flavored Markdown table, for pasting query
+/// results into wikis and pull requests.
+pub fn export(
+    entries: &[LdapEntry],
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+) -> Result<usize, CoreError> {
+    write_all(&mut writer(path, attributes, schema)?, entries)
+}
+
+/// A streaming writer for a Markdown file at `path`; see [`export`].
+pub(super) fn writer<'a>(
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&'a SchemaCache>,
+) -> Result<TableWriter<'a, MarkdownSink<BufWriter<File>>>, CoreError> {
+    let file = File::create(path)
+        .map_err(|e| CoreError::ExportError(format!("Failed to create file: {}", e)))?;
+    Ok(TableWriter::new(
+        MarkdownSink(BufWriter::new(file)),
+        attributes,
+        schema,
+    ))
+}
+
+/// The table: a `dn` column, then the same columns as a CSV export.
+/// Multi-valued attributes are shown one value per line within the cell.
+pub fn to_string(
+    entries: &[LdapEntry],
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+) -> Result<String, CoreError> {
+    let mut buf = Vec::new();
+    write_all(
+        &mut TableWriter::new(MarkdownSink(&mut buf), attributes, schema),
+        entries,
+    )?;
+    Ok(String::from_utf8_lossy(&buf).into_owned())
+}
+
+pub(super) struct MarkdownSink<W>(W);
+
+impl<W: Write> MarkdownSink<W> {
+    fn line(&mut self, cells: impl Iterator<Item = String>) -> Result<(), CoreError> {
+        let cells: Vec<String> = cells.collect();
+        writeln!(self.0, "| {} |", cells.join(" | "))
+            .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))
+    }
+}
+
+impl<W: Write> TableSink for MarkdownSink<W> {
+    fn header(&mut self, header: &[String]) -> Result<(), CoreError> {
+        self.line(header.iter().map(|h| cell(h)))?;
+        writeln!(self.0, "|{}", "---|".repeat(header.len()))
+            .map_err(|e| CoreError::ExportError(format!("Write failed: {}", e)))
+    }
+
+    fn row(&mut self, cells: &[Vec<String>]) -> Result<(), CoreError> {
+        self.line(cells.iter().map(|vals| {
+            vals.iter()
+                .map(|v| cell(v))
+                .collect::<Vec<_>>()
+                .join("<br>")
+        }))
+    }
+
+    fn finish(&mut self, _rows: usize) -> Result<(), CoreError> {
+        self.0
+            .flush()
+            .map_err(|e| CoreError::ExportError(format!("Flush failed: {}", e)))
+    }
+}
+
+/// Escape a value for a table cell: pipes would end the cell, line breaks
+/// the row, and `<` could open an HTML tag.
+fn cell(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('|', "\\|")
+        .replace('<', "&lt;")
+        .replace("\r\n", "\n")
+        .replace(['\n', '\r'], "<br>")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    #[test]
+    fn test_markdown_table() {
+        let entries = vec![
+            LdapEntry::new(
+                "cn=Alice,dc=example,dc=com".to_string(),
+                BTreeMap::from([
+                    ("cn".to_string(), vec!["Alice".to_string()]),
+                    (
+                        "mail".to_string(),
+                        vec!["alice@example.com".to_string(), "a@example.com".to_string()],
+                    ),
+                    (
+                        "description".to_string(),
+                        vec!["Ops | on call\nweekends".to_string()],
+                    ),
+                ]),
+            ),
+            LdapEntry::new(
+                "cn=Bob,dc=example,dc=com".to_string(),
+                BTreeMap::from([("cn".to_string(), vec!["Bob".to_string()])]),
+            ),
+        ];
+        let attrs = ["mail".to_string(), "description".to_string()];
+        let md = to_string(&entries, &attrs, None).unwrap();
+        let lines: Vec<&str> = md.lines().collect();
+        assert_eq!(lines[0], "| dn | mail | description |");
+        assert_eq!(lines[1], "|---|---|---|");
+        assert_eq!(
+            lines[2],
+            "| cn=Alice,dc=example,dc=com | alice@example.com<br>a@example.com \
+             | Ops \\| on call<br>weekends |"
+        );
+        assert_eq!(lines[3], "| cn=Bob,dc=example,dc=com |  |  |");
+        assert!(to_string(&[], &attrs, None).unwrap().is_empty());
+    }
+}
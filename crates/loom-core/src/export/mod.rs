@@ -1,12 +1,22 @@
 pub mod csv;
 pub mod json;
 pub mod ldif;
+pub mod markdown;
+pub mod report;
+pub mod schema;
+pub mod writer;
 pub mod xlsx;
 
+use std::collections::HashSet;
 use std::path::Path;
 
+use crate::dn;
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::schema::SchemaCache;
+use crate::util::find_values_ci;
+
+use writer::EntryWriter;
 
 /// Supported export formats.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -15,6 +25,8 @@ pub enum ExportFormat {
     Json,
     Csv,
     Xlsx,
+    /// A GitHub-flavored table; export only.
+    Markdown,
 }
 
 impl ExportFormat {
@@ -25,11 +37,21 @@ impl ExportFormat {
             "json" => Some(Self::Json),
             "csv" => Some(Self::Csv),
             "xlsx" | "xls" => Some(Self::Xlsx),
+            "md" | "markdown" => Some(Self::Markdown),
             _ => None,
         }
     }
 }
 
+/// Where exported entries came from, recorded by formats with room for it
+/// (the XLSX "Export Info" sheet).
+#[derive(Debug, Clone, Default)]
+pub struct ExportMetadata {
+    pub server: Option<String>,
+    pub base_dn: Option<String>,
+    pub filter: Option<String>,
+}
+
 /// If `attributes` contains only `"*"`, return `None` (meaning all attributes,
 /// alphabetical order). Otherwise return the explicit list.
 pub fn requested_attrs(attributes: &[String]) -> Option<&[String]> {
@@ -50,20 +72,197 @@ pub fn export_entries(
     path: &Path,
     attributes: &[String],
 ) -> Result<usize, CoreError> {
+    export_entries_with_schema(entries, path, attributes, None)
+}
+
+/// Export entries to a file like [`export_entries`], using `schema` to give
+/// `["*"]` CSV exports a complete, consistently ordered header.
+pub fn export_entries_with_schema(
+    entries: &[LdapEntry],
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+) -> Result<usize, CoreError> {
+    export_entries_with_metadata(
+        entries,
+        path,
+        attributes,
+        schema,
+        &ExportMetadata::default(),
+    )
+}
+
+/// Export entries to a file like [`export_entries_with_schema`], recording
+/// `metadata` in formats that support it.
+pub fn export_entries_with_metadata(
+    entries: &[LdapEntry],
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+    metadata: &ExportMetadata,
+) -> Result<usize, CoreError> {
+    let mut writer = entry_writer(path, attributes, schema, metadata)?;
+    writer::write_all(writer.as_mut(), entries)
+}
+
+/// Serialize entries in a text format instead of to a file, e.g. for the
+/// clipboard. XLSX isn't text and is refused.
+pub fn entries_to_string(
+    entries: &[LdapEntry],
+    attributes: &[String],
+    format: ExportFormat,
+) -> Result<String, CoreError> {
+    let mut buf = Vec::new();
+    match format {
+        ExportFormat::Ldif => {
+            ldif::write_ldif(&mut buf, entries, attributes)?;
+        }
+        ExportFormat::Csv => {
+            csv::write_csv(&mut buf, entries, attributes)?;
+        }
+        ExportFormat::Json => return json::to_string(entries, attributes),
+        ExportFormat::Markdown => return markdown::to_string(entries, attributes, None),
+        ExportFormat::Xlsx => {
+            return Err(CoreError::ExportError(
+                "XLSX can't be written as text".to_string(),
+            ))
+        }
+    }
+    String::from_utf8(buf).map_err(|e| CoreError::ExportError(e.to_string()))
+}
+
+/// A streaming writer for an export to `path`, in the format its extension
+/// names, so entries can be written as they arrive (e.g. page by page from
+/// [`LdapConnection::search_subtree_pages`](crate::connection::LdapConnection::search_subtree_pages))
+/// rather than collected first. Call [`EntryWriter::begin`], then
+/// [`EntryWriter::write_entry`] for each entry, then [`EntryWriter::finish`].
+pub fn entry_writer<'a>(
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&'a SchemaCache>,
+    metadata: &ExportMetadata,
+) -> Result<Box<dyn EntryWriter + Send + 'a>, CoreError> {
     let format = ExportFormat::from_path(path)
         .ok_or_else(|| CoreError::ExportError("Unknown file extension".to_string()))?;
 
-    match format {
-        ExportFormat::Ldif => ldif::export(entries, path, attributes),
-        ExportFormat::Json => json::export(entries, path, attributes),
-        ExportFormat::Csv => csv::export(entries, path, attributes),
-        ExportFormat::Xlsx => xlsx::export(entries, path, attributes),
+    Ok(match format {
+        ExportFormat::Ldif => Box::new(ldif::writer(path, attributes)?),
+        ExportFormat::Json => Box::new(json::writer(path, attributes)?),
+        ExportFormat::Csv => Box::new(csv::writer(path, attributes, schema)?),
+        ExportFormat::Xlsx => Box::new(xlsx::writer(path, attributes, schema, metadata)),
+        ExportFormat::Markdown => Box::new(markdown::writer(path, attributes, schema)?),
+    })
+}
+
+/// Whether `path` names one file per entry, i.e. its file name is a
+/// template with `{placeholders}` such as `users/{uid}.ldif`.
+pub fn is_per_entry_template(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.contains('{') && n.contains('}'))
+}
+
+/// Export each entry to its own file, named by filling in the template's
+/// file name from the entry: `{rdn}` (`cn=Alice`), `{rdn_value}` (`Alice`),
+/// `{dn}`, or any attribute (`{uid}`, first value). Files go in the
+/// template's directory and the format comes from its extension. Names are
+/// made filesystem-safe, and clashes get `-2`, `-3`... in DN order so the
+/// same directory exports to the same file names every time.
+pub fn export_entries_per_file(
+    entries: &[LdapEntry],
+    template: &Path,
+    attributes: &[String],
+    schema: Option<&SchemaCache>,
+    metadata: &ExportMetadata,
+) -> Result<usize, CoreError> {
+    let name_template = template
+        .file_name()
+        .and_then(|n| n.to_str())
+        .ok_or_else(|| CoreError::ExportError("File name template is required".to_string()))?;
+    ExportFormat::from_path(Path::new(name_template))
+        .ok_or_else(|| CoreError::ExportError("Unknown file extension".to_string()))?;
+    let dir = template.parent().unwrap_or(Path::new(""));
+
+    let mut sorted: Vec<&LdapEntry> = entries.iter().collect();
+    sorted.sort_by_key(|e| e.dn.to_lowercase());
+
+    let mut used = HashSet::new();
+    for entry in &sorted {
+        let name = unique_name(&entry_file_name(name_template, entry), &mut used);
+        export_entries_with_metadata(
+            std::slice::from_ref(*entry),
+            &dir.join(name),
+            attributes,
+            schema,
+            metadata,
+        )?;
+    }
+    Ok(sorted.len())
+}
+
+/// Fill in a file name template for one entry; see [`export_entries_per_file`].
+pub fn entry_file_name(template: &str, entry: &LdapEntry) -> String {
+    let mut out = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        out.push_str(&rest[..start]);
+        let key = &rest[start + 1..start + len];
+        let value = match key {
+            "rdn" => dn::rdn(&entry.dn).trim().to_string(),
+            "rdn_value" => dn::rdn_display_name(&entry.dn).trim().to_string(),
+            "dn" => entry.dn.clone(),
+            attr => find_values_ci(&entry.attributes, attr)
+                .and_then(|vals| vals.first().cloned())
+                .unwrap_or_default(),
+        };
+        out.push_str(&sanitize_file_name(&value));
+        rest = &rest[start + len + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Replace characters that aren't allowed (or are awkward) in file names.
+fn sanitize_file_name(value: &str) -> String {
+    let cleaned: String = value
+        .chars()
+        .map(|c| match c {
+            '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+    let trimmed = cleaned.trim().trim_end_matches('.');
+    if trimmed.is_empty() {
+        "_".to_string()
+    } else {
+        trimmed.to_string()
     }
 }
 
+/// Add `-2`, `-3`... before the extension until the name is unused
+/// (compared case-insensitively, for case-insensitive filesystems).
+fn unique_name(name: &str, used: &mut HashSet<String>) -> String {
+    let (stem, ext) = match name.rfind('.') {
+        Some(pos) if pos > 0 => name.split_at(pos),
+        _ => (name, ""),
+    };
+    let mut candidate = name.to_string();
+    let mut n = 2;
+    while !used.insert(candidate.to_lowercase()) {
+        candidate = format!("{}-{}{}", stem, n, ext);
+        n += 1;
+    }
+    candidate
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::entry::test_entry;
 
     #[test]
     fn test_format_from_path() {
@@ -91,10 +290,87 @@ mod tests {
             ExportFormat::from_path(Path::new("test.xls")),
             Some(ExportFormat::Xlsx)
         );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("test.md")),
+            Some(ExportFormat::Markdown)
+        );
         assert_eq!(ExportFormat::from_path(Path::new("test.txt")), None);
         assert_eq!(ExportFormat::from_path(Path::new("noext")), None);
     }
 
+    #[test]
+    fn test_entries_to_string() {
+        let alice = test_entry(
+            "cn=Alice,dc=example,dc=com",
+            &[("cn", &["Alice"]), ("mail", &["alice@example.com"])],
+        );
+        let all = ["*".to_string()];
+        let ldif = entries_to_string(std::slice::from_ref(&alice), &all, ExportFormat::Ldif);
+        assert_eq!(
+            ldif.unwrap(),
+            "dn: cn=Alice,dc=example,dc=com\ncn: Alice\nmail: alice@example.com\n"
+        );
+        let csv = entries_to_string(
+            std::slice::from_ref(&alice),
+            &["mail".to_string()],
+            ExportFormat::Csv,
+        );
+        assert_eq!(
+            csv.unwrap(),
+            "dn,mail\n\"cn=Alice,dc=example,dc=com\",alice@example.com\n"
+        );
+        let json =
+            entries_to_string(std::slice::from_ref(&alice), &all, ExportFormat::Json).unwrap();
+        assert!(json.contains("\"mail\""), "{}", json);
+        assert!(entries_to_string(&[alice], &all, ExportFormat::Xlsx).is_err());
+    }
+
+    #[test]
+    fn test_entry_file_name() {
+        let alice = test_entry(
+            "cn=Alice Smith,ou=People,dc=example,dc=com",
+            &[("uid", &["asmith"]), ("title", &["R&D/Ops"])],
+        );
+        assert_eq!(entry_file_name("{rdn}.ldif", &alice), "cn=Alice Smith.ldif");
+        assert_eq!(
+            entry_file_name("{rdn_value}.ldif", &alice),
+            "Alice Smith.ldif"
+        );
+        assert_eq!(entry_file_name("{UID}.ldif", &alice), "asmith.ldif");
+        assert_eq!(entry_file_name("{title}.ldif", &alice), "R&D_Ops.ldif");
+        assert_eq!(entry_file_name("{mail}.ldif", &alice), "_.ldif");
+    }
+
+    #[test]
+    fn test_export_entries_per_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let entries = vec![
+            test_entry("cn=Bob,ou=B,dc=example,dc=com", &[("cn", &["Bob"])]),
+            test_entry("cn=Alice,dc=example,dc=com", &[("cn", &["Alice"])]),
+            test_entry("cn=bob,ou=A,dc=example,dc=com", &[("cn", &["bob"])]),
+        ];
+        let template = dir.path().join("{rdn_value}.ldif");
+        assert!(is_per_entry_template(&template));
+        assert!(!is_per_entry_template(&dir.path().join("export.ldif")));
+
+        let count = export_entries_per_file(
+            &entries,
+            &template,
+            &["*".to_string()],
+            None,
+            &ExportMetadata::default(),
+        )
+        .unwrap();
+        assert_eq!(count, 3);
+
+        // Clashes are numbered in DN order: ou=A's bob before ou=B's Bob
+        let first = std::fs::read_to_string(dir.path().join("bob.ldif")).unwrap();
+        assert!(first.starts_with("dn: cn=bob,ou=A,dc=example,dc=com\n"));
+        let second = std::fs::read_to_string(dir.path().join("Bob-2.ldif")).unwrap();
+        assert!(second.starts_with("dn: cn=Bob,ou=B,dc=example,dc=com\n"));
+        assert!(dir.path().join("Alice.ldif").exists());
+    }
+
     #[test]
     fn test_format_from_path_case_insensitive() {
         assert_eq!(
@@ -0,0 +1,411 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use chrono::{DateTime, NaiveDateTime};
+
+use crate::dn;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::reports::FILETIME_ATTRIBUTES;
+use crate::util::find_values_ci;
+
+/// Output formats for entry reports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Text,
+    /// Typst source; compile to PDF with `typst compile report.typ`.
+    Typst,
+}
+
+impl ReportFormat {
+    /// Infer format from file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "md" | "markdown" => Some(Self::Markdown),
+            "txt" => Some(Self::Text),
+            "typ" => Some(Self::Typst),
+            _ => None,
+        }
+    }
+}
+
+/// A group of related attributes shown together in a report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportSection {
+    Identity,
+    Contact,
+    Groups,
+    Audit,
+}
+
+impl ReportSection {
+    pub const ALL: [ReportSection; 4] = [
+        ReportSection::Identity,
+        ReportSection::Contact,
+        ReportSection::Groups,
+        ReportSection::Audit,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ReportSection::Identity => "Identity",
+            ReportSection::Contact => "Contact",
+            ReportSection::Groups => "Group memberships",
+            ReportSection::Audit => "Audit metadata",
+        }
+    }
+
+    /// Attributes listed in this section, in report order.
+    pub fn attributes(self) -> &'static [&'static str] {
+        match self {
+            ReportSection::Identity => &[
+                "cn",
+                "displayName",
+                "givenName",
+                "sn",
+                "uid",
+                "sAMAccountName",
+                "userPrincipalName",
+                "employeeID",
+                "employeeNumber",
+                "employeeType",
+                "title",
+                "department",
+                "company",
+                "manager",
+                "description",
+                "objectClass",
+            ],
+            ReportSection::Contact => &[
+                "mail",
+                "telephoneNumber",
+                "mobile",
+                "physicalDeliveryOfficeName",
+                "streetAddress",
+                "l",
+                "st",
+                "postalCode",
+                "c",
+            ],
+            ReportSection::Groups => &["memberOf"],
+            ReportSection::Audit => &[
+                "whenCreated",
+                "whenChanged",
+                "createTimestamp",
+                "modifyTimestamp",
+                "creatorsName",
+                "modifiersName",
+                "pwdLastSet",
+                "lastLogonTimestamp",
+                "accountExpires",
+                "lockoutTime",
+                "badPwdCount",
+                "userAccountControl",
+                "pwdChangedTime",
+                "pwdAccountLockedTime",
+            ],
+        }
+    }
+}
+
+/// One section of one entry, with values ready to print.
+struct SectionRows {
+    label: &'static str,
+    rows: Vec<(String, Vec<String>)>,
+}
+
+fn collect_sections(entry: &LdapEntry, sections: &[ReportSection]) -> Vec<SectionRows> {
+    sections
+        .iter()
+        .map(|&section| {
+            let rows = section
+                .attributes()
+                .iter()
+                .filter_map(|attr| {
+                    let values = find_values_ci(&entry.attributes, attr)?;
+                    let mut shown: Vec<String> =
+                        values.iter().map(|v| display_value(attr, v)).collect();
+                    if section == ReportSection::Groups {
+                        shown.sort_by_key(|v| v.to_lowercase());
+                    }
+                    Some((attr.to_string(), shown))
+                })
+                .collect();
+            SectionRows {
+                label: section.label(),
+                rows,
+            }
+        })
+        .filter(|s| !s.rows.is_empty())
+        .collect()
+}
+
+/// Make timestamps readable, keeping the raw value alongside. Group DNs
+/// are prefixed with the group's name.
+fn display_value(attr: &str, value: &str) -> String {
+    if attr.eq_ignore_ascii_case("memberOf") {
+        return format!("{} ({})", dn::rdn_display_name(value), value);
+    }
+    if FILETIME_ATTRIBUTES
+        .iter()
+        .any(|a| a.eq_ignore_ascii_case(attr))
+    {
+        return match value.parse::<i64>() {
+            Ok(0) | Ok(i64::MAX) => format!("never ({})", value),
+            Ok(ticks) => {
+                // 100ns intervals since 1601-01-01
+                let secs = ticks / 10_000_000 - 11_644_473_600;
+                match DateTime::from_timestamp(secs, 0) {
+                    Some(t) => format!("{} ({})", t.format("%Y-%m-%d %H:%M:%S UTC"), value),
+                    None => value.to_string(),
+                }
+            }
+            Err(_) => value.to_string(),
+        };
+    }
+    // GeneralizedTime: 20240102103000.0Z
+    let digits = value.as_bytes().get(..14);
+    if value.ends_with('Z') && digits.is_some_and(|d| d.iter().all(u8::is_ascii_digit)) {
+        if let Ok(t) = NaiveDateTime::parse_from_str(&value[..14], "%Y%m%d%H%M%S") {
+            return format!("{} UTC ({})", t.format("%Y-%m-%d %H:%M:%S"), value);
+        }
+    }
+    value.to_string()
+}
+
+fn entry_title(entry: &LdapEntry) -> String {
+    find_values_ci(&entry.attributes, "displayName")
+        .and_then(|v| v.first().cloned())
+        .unwrap_or_else(|| dn::rdn_display_name(&entry.dn).to_string())
+}
+
+/// Render a report of `entries` with the chosen sections. `generated` is
+/// the timestamp printed in the header.
+pub fn render(
+    entries: &[LdapEntry],
+    sections: &[ReportSection],
+    format: ReportFormat,
+    generated: &str,
+) -> String {
+    match format {
+        ReportFormat::Markdown => render_markdown(entries, sections, generated),
+        ReportFormat::Text => render_text(entries, sections, generated),
+        ReportFormat::Typst => render_typst(entries, sections, generated),
+    }
+}
+
+const EMPTY_NOTE: &str = "No values in the selected sections.";
+
+fn render_markdown(entries: &[LdapEntry], sections: &[ReportSection], generated: &str) -> String {
+    let cell = |s: &str| s.replace('|', "\\|").replace('\n', "<br>");
+    let mut out = format!(
+        "# Entry report\n\nGenerated {} -- {} entries\n",
+        generated,
+        entries.len()
+    );
+    for entry in entries {
+        let _ = write!(out, "\n## {}\n\n`{}`\n", entry_title(entry), entry.dn);
+        let rows = collect_sections(entry, sections);
+        if rows.is_empty() {
+            let _ = writeln!(out, "\n{}", EMPTY_NOTE);
+        }
+        for section in rows {
+            let _ = write!(
+                out,
+                "\n### {}\n\n| Attribute | Value |\n|---|---|\n",
+                section.label
+            );
+            for (attr, values) in section.rows {
+                for (i, value) in values.iter().enumerate() {
+                    let name = if i == 0 { attr.as_str() } else { "" };
+                    let _ = writeln!(out, "| {} | {} |", name, cell(value));
+                }
+            }
+        }
+    }
+    out
+}
+
+fn render_text(entries: &[LdapEntry], sections: &[ReportSection], generated: &str) -> String {
+    let mut out = format!(
+        "ENTRY REPORT\nGenerated {} -- {} entries\n",
+        generated,
+        entries.len()
+    );
+    for entry in entries {
+        let title = entry_title(entry);
+        let _ = write!(
+            out,
+            "\n{}\n{}\nDN: {}\n",
+            title,
+            "=".repeat(title.chars().count()),
+            entry.dn
+        );
+        let rows = collect_sections(entry, sections);
+        if rows.is_empty() {
+            let _ = writeln!(out, "\n  {}", EMPTY_NOTE);
+        }
+        for section in rows {
+            let _ = writeln!(out, "\n  {}", section.label);
+            let width = section.rows.iter().map(|(a, _)| a.len()).max().unwrap_or(0);
+            for (attr, values) in section.rows {
+                for (i, value) in values.iter().enumerate() {
+                    let name = if i == 0 { attr.as_str() } else { "" };
+                    // Continuation lines of multi-line values line up too
+                    let value = value.replace('\n', &format!("\n    {:width$}  ", ""));
+                    let _ = writeln!(out, "    {:width$}  {}", name, value);
+                }
+            }
+        }
+    }
+    out
+}
+
+fn render_typst(entries: &[LdapEntry], sections: &[ReportSection], generated: &str) -> String {
+    // Values go in as string literals, so no markup in them is interpreted
+    let lit = |s: &str| {
+        let escaped = s
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n");
+        format!("\"{}\"", escaped)
+    };
+    let mut out = format!(
+        "#set page(paper: \"a4\", margin: 2cm)\n#set text(size: 10pt)\n\n\
+         #heading(level: 1, \"Entry report\")\n#text(fill: gray, {})\n",
+        lit(&format!(
+            "Generated {} -- {} entries",
+            generated,
+            entries.len()
+        ))
+    );
+    for entry in entries {
+        let _ = write!(
+            out,
+            "\n#heading(level: 2, {})\n#raw({})\n",
+            lit(&entry_title(entry)),
+            lit(&entry.dn)
+        );
+        let rows = collect_sections(entry, sections);
+        if rows.is_empty() {
+            let _ = writeln!(out, "\n{}", lit(EMPTY_NOTE));
+        }
+        for section in rows {
+            let _ = write!(
+                out,
+                "\n#heading(level: 3, {})\n#table(\n  columns: (auto, 1fr),\n  [*Attribute*], [*Value*],\n",
+                lit(section.label)
+            );
+            for (attr, values) in section.rows {
+                for (i, value) in values.iter().enumerate() {
+                    let name = if i == 0 { attr.as_str() } else { "" };
+                    let _ = writeln!(out, "  {}, {},", lit(name), lit(value));
+                }
+            }
+            out.push_str(")\n");
+        }
+    }
+    out
+}
+
+/// Write a report of `entries` to `path`, picking the format from its
+/// extension (`.md`, `.txt`, `.typ`).
+pub fn write_report(
+    entries: &[LdapEntry],
+    path: &Path,
+    sections: &[ReportSection],
+) -> Result<usize, CoreError> {
+    let format = ReportFormat::from_path(path).ok_or_else(|| {
+        CoreError::ExportError("Report file must end in .md, .txt or .typ".to_string())
+    })?;
+    let generated = chrono::Utc::now().format("%Y-%m-%d %H:%M UTC").to_string();
+    let report = render(entries, sections, format, &generated);
+    std::fs::write(path, report)
+        .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn user() -> LdapEntry {
+        LdapEntry::new(
+            "CN=John Doe,OU=Users,DC=corp,DC=com".to_string(),
+            BTreeMap::from([
+                ("cn".to_string(), vec!["John Doe".to_string()]),
+                ("mail".to_string(), vec!["jdoe@corp.com".to_string()]),
+                (
+                    "memberOf".to_string(),
+                    vec![
+                        "CN=VPN Users,OU=Groups,DC=corp,DC=com".to_string(),
+                        "CN=Accounting,OU=Groups,DC=corp,DC=com".to_string(),
+                    ],
+                ),
+                (
+                    "whenCreated".to_string(),
+                    vec!["20240102103000.0Z".to_string()],
+                ),
+                ("pwdLastSet".to_string(), vec!["0".to_string()]),
+                ("description".to_string(), vec!["a | b".to_string()]),
+            ]),
+        )
+    }
+
+    #[test]
+    fn test_markdown_sections() {
+        let md = render(
+            &[user()],
+            &[
+                ReportSection::Identity,
+                ReportSection::Groups,
+                ReportSection::Audit,
+            ],
+            ReportFormat::Markdown,
+            "now",
+        );
+        assert!(md.contains("## John Doe\n\n`CN=John Doe,OU=Users,DC=corp,DC=com`"));
+        assert!(md.contains("| description | a \\| b |"));
+        // Groups sorted by name, each with its DN
+        let accounting = md.find("| memberOf | Accounting (CN=Accounting").unwrap();
+        let vpn = md.find("|  | VPN Users (CN=VPN Users").unwrap();
+        assert!(accounting < vpn);
+        assert!(md.contains("| whenCreated | 2024-01-02 10:30:00 UTC (20240102103000.0Z) |"));
+        assert!(md.contains("| pwdLastSet | never (0) |"));
+        // Contact was not chosen
+        assert!(!md.contains("jdoe@corp.com"));
+    }
+
+    #[test]
+    fn test_text_and_typst() {
+        let text = render(
+            &[user()],
+            &[ReportSection::Contact],
+            ReportFormat::Text,
+            "now",
+        );
+        assert!(text.contains("John Doe\n========\nDN: CN=John Doe"));
+        assert!(text.contains("\n  Contact\n    mail  jdoe@corp.com\n"));
+
+        let entry = LdapEntry::new(
+            "cn=x,dc=example".to_string(),
+            BTreeMap::from([("cn".to_string(), vec!["say \"#hi\"".to_string()])]),
+        );
+        let typ = render(
+            &[entry],
+            &[ReportSection::Identity],
+            ReportFormat::Typst,
+            "now",
+        );
+        assert!(typ.contains("  \"cn\", \"say \\\"#hi\\\"\",\n"));
+
+        let empty = render(&[user()], &[], ReportFormat::Text, "now");
+        assert!(empty.contains(EMPTY_NOTE));
+        assert_eq!(
+            ReportFormat::from_path(Path::new("r.typ")),
+            Some(ReportFormat::Typst)
+        );
+    }
+}
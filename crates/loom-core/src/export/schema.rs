@@ -0,0 +1,293 @@
+use std::fmt::Write as _;
+use std::path::Path;
+
+use serde::Serialize;
+
+use crate::error::CoreError;
+use crate::schema::{AttributeTypeInfo, ObjectClassInfo, SchemaCache};
+
+use super::ldif::{base64_encode, needs_base64};
+
+/// Output formats for schema exports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SchemaFormat {
+    /// A subschema entry with RFC 4512 definitions, as servers publish it.
+    Ldif,
+    Json,
+    /// Documentation for readers without directory access.
+    Markdown,
+}
+
+impl SchemaFormat {
+    /// Infer format from file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "ldif" | "ldf" => Some(Self::Ldif),
+            "json" => Some(Self::Json),
+            "md" | "markdown" => Some(Self::Markdown),
+            _ => None,
+        }
+    }
+}
+
+/// Write the schema to `path`, returning the number of definitions written.
+pub fn export(schema: &SchemaCache, path: &Path, format: SchemaFormat) -> Result<usize, CoreError> {
+    let text = match format {
+        SchemaFormat::Ldif => to_ldif(schema),
+        SchemaFormat::Json => to_json(schema)?,
+        SchemaFormat::Markdown => to_markdown(schema),
+    };
+    std::fs::write(path, text)
+        .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+    Ok(schema.unique_object_classes().len() + schema.unique_attribute_types().len())
+}
+
+/// The schema as a `cn=schema` subschema entry in LDIF.
+pub fn to_ldif(schema: &SchemaCache) -> String {
+    let mut out =
+        String::from("dn: cn=schema\nobjectClass: top\nobjectClass: subschema\ncn: schema\n");
+    let mut line = |attr: &str, value: String| {
+        if needs_base64(&value) {
+            let _ = writeln!(out, "{}:: {}", attr, base64_encode(&value));
+        } else {
+            let _ = writeln!(out, "{}: {}", attr, value);
+        }
+    };
+    for at in schema.unique_attribute_types() {
+        line("attributeTypes", attribute_type_definition(at));
+    }
+    for oc in schema.unique_object_classes() {
+        line("objectClasses", object_class_definition(oc));
+    }
+    out
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct SchemaDocument<'a> {
+    attribute_types: Vec<&'a AttributeTypeInfo>,
+    object_classes: Vec<&'a ObjectClassInfo>,
+}
+
+/// The schema as a JSON object with `attributeTypes` and `objectClasses`.
+pub fn to_json(schema: &SchemaCache) -> Result<String, CoreError> {
+    let doc = SchemaDocument {
+        attribute_types: schema.unique_attribute_types(),
+        object_classes: schema.unique_object_classes(),
+    };
+    serde_json::to_string_pretty(&doc)
+        .map_err(|e| CoreError::ExportError(format!("JSON serialization failed: {}", e)))
+}
+
+/// The schema as Markdown: a section per object class, then a table of
+/// attribute types.
+pub fn to_markdown(schema: &SchemaCache) -> String {
+    let classes = schema.unique_object_classes();
+    let types = schema.unique_attribute_types();
+    let mut out = String::from("# LDAP Schema\n\n");
+    let _ = writeln!(
+        out,
+        "{} object classes, {} attribute types.\n",
+        classes.len(),
+        types.len()
+    );
+
+    out.push_str("## Object Classes\n\n");
+    for oc in classes {
+        let _ = writeln!(out, "### {}\n", display_names(&oc.names, &oc.oid));
+        if let Some(desc) = &oc.description {
+            let _ = writeln!(out, "{}\n", desc);
+        }
+        let _ = writeln!(out, "- **OID:** `{}`", oc.oid);
+        let _ = writeln!(out, "- **Kind:** {}", oc.kind.keyword());
+        if let Some(sup) = &oc.superior {
+            let _ = writeln!(out, "- **Superior:** {}", sup);
+        }
+        if !oc.must.is_empty() {
+            let _ = writeln!(out, "- **MUST:** {}", oc.must.join(", "));
+        }
+        if !oc.may.is_empty() {
+            let _ = writeln!(out, "- **MAY:** {}", oc.may.join(", "));
+        }
+        out.push('\n');
+    }
+
+    out.push_str("## Attribute Types\n\n");
+    out.push_str("| Name | OID | Syntax | Equality | Single-valued | Description |\n");
+    out.push_str("|------|-----|--------|----------|---------------|-------------|\n");
+    for at in types {
+        let syntax = match &at.syntax_oid {
+            Some(oid) => format!("{:?} (`{}`)", at.syntax, oid),
+            None => format!("{:?}", at.syntax),
+        };
+        let _ = writeln!(
+            out,
+            "| {} | `{}` | {} | {} | {} | {} |",
+            cell(&display_names(&at.names, &at.oid)),
+            at.oid,
+            cell(&syntax),
+            cell(at.equality.as_deref().unwrap_or("")),
+            if at.single_value { "yes" } else { "no" },
+            cell(at.description.as_deref().unwrap_or("")),
+        );
+    }
+    out
+}
+
+/// An RFC 4512 AttributeTypeDescription.
+fn attribute_type_definition(at: &AttributeTypeInfo) -> String {
+    let mut def = format!("( {}", at.oid);
+    push_names(&mut def, &at.names);
+    push_desc(&mut def, at.description.as_deref());
+    for (keyword, value) in [
+        ("SUP", &at.superior),
+        ("EQUALITY", &at.equality),
+        ("ORDERING", &at.ordering),
+        ("SUBSTR", &at.substr),
+    ] {
+        if let Some(value) = value {
+            let _ = write!(def, " {} {}", keyword, value);
+        }
+    }
+    if let Some(oid) = &at.syntax_oid {
+        let _ = write!(def, " SYNTAX {}", oid);
+        if let Some(len) = at.syntax_length {
+            let _ = write!(def, "{{{}}}", len);
+        }
+    }
+    if at.single_value {
+        def.push_str(" SINGLE-VALUE");
+    }
+    if at.no_user_modification {
+        def.push_str(" NO-USER-MODIFICATION");
+    }
+    def.push_str(" )");
+    def
+}
+
+/// An RFC 4512 ObjectClassDescription.
+fn object_class_definition(oc: &ObjectClassInfo) -> String {
+    let mut def = format!("( {}", oc.oid);
+    push_names(&mut def, &oc.names);
+    push_desc(&mut def, oc.description.as_deref());
+    if let Some(sup) = &oc.superior {
+        let _ = write!(def, " SUP {}", sup);
+    }
+    let _ = write!(def, " {}", oc.kind.keyword());
+    for (keyword, attrs) in [("MUST", &oc.must), ("MAY", &oc.may)] {
+        match attrs.as_slice() {
+            [] => {}
+            [one] => {
+                let _ = write!(def, " {} {}", keyword, one);
+            }
+            many => {
+                let _ = write!(def, " {} ( {} )", keyword, many.join(" $ "));
+            }
+        }
+    }
+    def.push_str(" )");
+    def
+}
+
+fn push_names(def: &mut String, names: &[String]) {
+    match names {
+        [] => {}
+        [one] => {
+            let _ = write!(def, " NAME '{}'", one);
+        }
+        many => {
+            let quoted: Vec<String> = many.iter().map(|n| format!("'{}'", n)).collect();
+            let _ = write!(def, " NAME ( {} )", quoted.join(" "));
+        }
+    }
+}
+
+fn push_desc(def: &mut String, desc: Option<&str>) {
+    if let Some(desc) = desc {
+        // qdstring escapes (RFC 4512 section 4.1)
+        let escaped = desc.replace('\\', "\\5C").replace('\'', "\\27");
+        let _ = write!(def, " DESC '{}'", escaped);
+    }
+}
+
+/// "cn (commonName)": the first name with any aliases, or the OID.
+fn display_names(names: &[String], oid: &str) -> String {
+    match names {
+        [] => oid.to_string(),
+        [one] => one.clone(),
+        [first, rest @ ..] => format!("{} ({})", first, rest.join(", ")),
+    }
+}
+
+/// Escape a value for a Markdown table cell.
+fn cell(s: &str) -> String {
+    s.replace('|', "\\|").replace('\n', " ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{AttributeSyntax, ObjectClassKind};
+
+    fn schema() -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        let cn = AttributeTypeInfo {
+            oid: "2.5.4.3".to_string(),
+            names: vec!["cn".to_string(), "commonName".to_string()],
+            description: Some("Common name".to_string()),
+            syntax: AttributeSyntax::DirectoryString,
+            syntax_oid: Some("1.3.6.1.4.1.1466.115.121.1.15".to_string()),
+            syntax_length: Some(64),
+            superior: Some("name".to_string()),
+            equality: Some("caseIgnoreMatch".to_string()),
+            ordering: None,
+            substr: Some("caseIgnoreSubstringsMatch".to_string()),
+            single_value: false,
+            no_user_modification: false,
+        };
+        schema.attribute_types.insert("cn".to_string(), cn.clone());
+        schema.attribute_types.insert("commonname".to_string(), cn);
+        schema.object_classes.insert(
+            "person".to_string(),
+            ObjectClassInfo {
+                oid: "2.5.6.6".to_string(),
+                names: vec!["person".to_string()],
+                description: None,
+                superior: Some("top".to_string()),
+                kind: ObjectClassKind::Structural,
+                must: vec!["sn".to_string(), "cn".to_string()],
+                may: vec!["description".to_string()],
+            },
+        );
+        schema
+    }
+
+    #[test]
+    fn test_ldif_definitions() {
+        let ldif = to_ldif(&schema());
+        assert!(ldif.starts_with("dn: cn=schema\n"));
+        assert!(ldif.contains(
+            "attributeTypes: ( 2.5.4.3 NAME ( 'cn' 'commonName' ) DESC 'Common name' \
+             SUP name EQUALITY caseIgnoreMatch SUBSTR caseIgnoreSubstringsMatch \
+             SYNTAX 1.3.6.1.4.1.1466.115.121.1.15{64} )\n"
+        ));
+        assert!(ldif.contains(
+            "objectClasses: ( 2.5.6.6 NAME 'person' SUP top STRUCTURAL \
+             MUST ( sn $ cn ) MAY description )\n"
+        ));
+        // Aliases don't produce duplicate definitions
+        assert_eq!(ldif.matches("attributeTypes:").count(), 1);
+    }
+
+    #[test]
+    fn test_json_and_markdown() {
+        let json: serde_json::Value = serde_json::from_str(&to_json(&schema()).unwrap()).unwrap();
+        assert_eq!(json["attributeTypes"][0]["equality"], "caseIgnoreMatch");
+        assert_eq!(json["objectClasses"][0]["kind"], "Structural");
+
+        let md = to_markdown(&schema());
+        assert!(md.contains("### person\n"));
+        assert!(md.contains("- **MUST:** sn, cn\n"));
+        assert!(md.contains("| cn (commonName) | `2.5.4.3` |"));
+    }
+}
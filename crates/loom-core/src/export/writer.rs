@@ -0,0 +1,237 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Seek, SeekFrom, Write};
+
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::schema::SchemaCache;
+use crate::util::find_values_ci;
+
+use super::csv::{entry_values, ColumnSet};
+use super::requested_attrs;
+
+/// A destination that takes exported entries one at a time, so an export
+/// can be fed page by page from a search and run in bounded memory.
+pub trait EntryWriter {
+    /// Write anything that comes before the entries.
+    fn begin(&mut self) -> Result<(), CoreError>;
+    fn write_entry(&mut self, entry: &LdapEntry) -> Result<(), CoreError>;
+    /// Complete the output, returning the number of entries written.
+    fn finish(&mut self) -> Result<usize, CoreError>;
+}
+
+/// Write all of `entries` to `writer`, start to finish.
+pub fn write_all(writer: &mut dyn EntryWriter, entries: &[LdapEntry]) -> Result<usize, CoreError> {
+    writer.begin()?;
+    for entry in entries {
+        writer.write_entry(entry)?;
+    }
+    writer.finish()
+}
+
+/// A tabular format (CSV, XLSX, Markdown) that [`TableWriter`] fills in a
+/// row at a time.
+pub(super) trait TableSink {
+    /// The column names, `dn` included, before the first row.
+    fn header(&mut self, header: &[String]) -> Result<(), CoreError>;
+    /// One entry's values, in header order.
+    fn row(&mut self, cells: &[Vec<String>]) -> Result<(), CoreError>;
+    /// Called once with the number of rows; `header` was never called if
+    /// that is zero.
+    fn finish(&mut self, rows: usize) -> Result<(), CoreError>;
+}
+
+/// Feeds entries to a [`TableSink`]. With an explicit attribute list the
+/// rows go straight through; for `["*"]` the columns depend on every entry,
+/// so entries are spooled to a temporary file and written at the end, which
+/// keeps memory bounded by the number of distinct attributes.
+pub(super) struct TableWriter<'a, S> {
+    sink: S,
+    attributes: Option<Vec<String>>,
+    schema: Option<&'a SchemaCache>,
+    header: Vec<String>,
+    spool: Option<Spool>,
+    columns: ColumnSet,
+    count: usize,
+}
+
+impl<'a, S: TableSink> TableWriter<'a, S> {
+    pub(super) fn new(sink: S, attributes: &[String], schema: Option<&'a SchemaCache>) -> Self {
+        Self {
+            sink,
+            attributes: requested_attrs(attributes).map(<[String]>::to_vec),
+            schema,
+            header: Vec::new(),
+            spool: None,
+            columns: ColumnSet::default(),
+            count: 0,
+        }
+    }
+
+    fn write_row(&mut self, entry: &LdapEntry) -> Result<(), CoreError> {
+        let values = entry_values(entry, self.schema);
+        let cells: Vec<Vec<String>> = self
+            .header
+            .iter()
+            .map(|column| {
+                if column.eq_ignore_ascii_case("dn") {
+                    vec![entry.dn.clone()]
+                } else {
+                    find_values_ci(&values, column).cloned().unwrap_or_default()
+                }
+            })
+            .collect();
+        self.sink.row(&cells)
+    }
+
+    fn set_header(&mut self, mut columns: Vec<String>) -> Result<(), CoreError> {
+        if !columns.iter().any(|c| c.eq_ignore_ascii_case("dn")) {
+            columns.insert(0, "dn".to_string());
+        }
+        self.header = columns;
+        self.sink.header(&self.header)
+    }
+}
+
+impl<S: TableSink> EntryWriter for TableWriter<'_, S> {
+    fn begin(&mut self) -> Result<(), CoreError> {
+        if self.attributes.is_none() {
+            self.spool = Some(Spool::new()?);
+        }
+        Ok(())
+    }
+
+    fn write_entry(&mut self, entry: &LdapEntry) -> Result<(), CoreError> {
+        if let Some(spool) = &mut self.spool {
+            self.columns.add(entry);
+            spool.push(entry)?;
+        } else {
+            if self.count == 0 {
+                let columns = self.attributes.clone().unwrap_or_default();
+                self.set_header(columns)?;
+            }
+            self.write_row(entry)?;
+        }
+        self.count += 1;
+        Ok(())
+    }
+
+    fn finish(&mut self) -> Result<usize, CoreError> {
+        if let Some(spool) = self.spool.take() {
+            if self.count > 0 {
+                self.set_header(self.columns.columns(self.schema))?;
+                for entry in spool.replay()? {
+                    self.write_row(&entry?)?;
+                }
+            }
+        }
+        self.sink.finish(self.count)?;
+        Ok(self.count)
+    }
+}
+
+/// Entries parked in an anonymous temporary file as JSON lines.
+struct Spool {
+    file: BufWriter<File>,
+}
+
+impl Spool {
+    fn new() -> Result<Self, CoreError> {
+        let file = tempfile::tempfile()
+            .map_err(|e| CoreError::ExportError(format!("Failed to create spool file: {}", e)))?;
+        Ok(Self {
+            file: BufWriter::new(file),
+        })
+    }
+
+    fn push(&mut self, entry: &LdapEntry) -> Result<(), CoreError> {
+        serde_json::to_writer(&mut self.file, entry)
+            .map_err(|e| CoreError::ExportError(format!("Spool write failed: {}", e)))?;
+        writeln!(self.file)
+            .map_err(|e| CoreError::ExportError(format!("Spool write failed: {}", e)))
+    }
+
+    fn replay(self) -> Result<impl Iterator<Item = Result<LdapEntry, CoreError>>, CoreError> {
+        let err = |e: std::io::Error| CoreError::ExportError(format!("Spool read failed: {}", e));
+        let mut file = self.file.into_inner().map_err(|e| err(e.into_error()))?;
+        file.seek(SeekFrom::Start(0)).map_err(err)?;
+        Ok(BufReader::new(file).lines().map(move |line| {
+            let line = line.map_err(err)?;
+            serde_json::from_str(&line)
+                .map_err(|e| CoreError::ExportError(format!("Spool read failed: {}", e)))
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    /// Records what a sink is given.
+    #[derive(Default)]
+    struct Recorder {
+        header: Vec<String>,
+        rows: Vec<Vec<Vec<String>>>,
+        finished: Option<usize>,
+    }
+
+    impl TableSink for &mut Recorder {
+        fn header(&mut self, header: &[String]) -> Result<(), CoreError> {
+            self.header = header.to_vec();
+            Ok(())
+        }
+
+        fn row(&mut self, cells: &[Vec<String>]) -> Result<(), CoreError> {
+            self.rows.push(cells.to_vec());
+            Ok(())
+        }
+
+        fn finish(&mut self, rows: usize) -> Result<(), CoreError> {
+            self.finished = Some(rows);
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_spooled_columns() {
+        let entries = vec![
+            test_entry("cn=a,dc=x", &[("cn", &["a"])]),
+            test_entry("cn=b,dc=x", &[("cn", &["b"]), ("mail", &["b@x"])]),
+        ];
+        let mut recorder = Recorder::default();
+        let mut writer = TableWriter::new(&mut recorder, &["*".to_string()], None);
+        assert_eq!(write_all(&mut writer, &entries).unwrap(), 2);
+        drop(writer);
+
+        // Columns seen in later entries are known for the first row too
+        assert_eq!(recorder.header, vec!["dn", "cn", "mail"]);
+        assert_eq!(recorder.rows[0][2], Vec::<String>::new());
+        assert_eq!(recorder.rows[1][2], vec!["b@x"]);
+        assert_eq!(recorder.finished, Some(2));
+    }
+
+    #[test]
+    fn test_requested_columns_stream() {
+        let mut recorder = Recorder::default();
+        let attrs = ["mail".to_string(), "dn".to_string()];
+        let mut writer = TableWriter::new(&mut recorder, &attrs, None);
+        writer.begin().unwrap();
+        writer
+            .write_entry(&test_entry("cn=b,dc=x", &[("Mail", &["b@x"])]))
+            .unwrap();
+        // Written as soon as it arrives
+        assert_eq!(writer.sink.rows.len(), 1);
+        assert_eq!(writer.finish().unwrap(), 1);
+        drop(writer);
+        assert_eq!(recorder.header, vec!["mail", "dn"]);
+        assert_eq!(recorder.rows[0], vec![vec!["b@x"], vec!["cn=b,dc=x"]]);
+
+        // Nothing to write: no header either
+        let mut empty = Recorder::default();
+        let mut writer = TableWriter::new(&mut empty, &attrs, None);
+        assert_eq!(write_all(&mut writer, &[]).unwrap(), 0);
+        drop(writer);
+        assert!(empty.header.is_empty());
+        assert_eq!(empty.finished, Some(0));
+    }
+}
@@ -1,85 +1,273 @@
-use std::collections::BTreeSet;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 
-use rust_xlsxwriter::{Format, Workbook};
+use rust_xlsxwriter::{Color, Format, FormatBorder, Workbook, XlsxError};
 
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::schema::SchemaCache;
 
-use super::requested_attrs;
+use super::writer::{write_all, TableSink, TableWriter};
+use super::ExportMetadata;
+
+/// Data rows per sheet: Excel's 1,048,576-row limit less the header.
+const MAX_ROWS_PER_SHEET: usize = 1_048_575;
+
+/// Column widths are fitted to the data, within these bounds (in characters).
+const MIN_COLUMN_WIDTH: usize = 8;
+const MAX_COLUMN_WIDTH: usize = 80;
 
 /// Export entries to Excel (.xlsx) format.
+///
+/// Columns follow the requested attribute order, with `dn` first unless it
+/// is requested elsewhere; `["*"]` uses the same columns as CSV. The header
+/// row is styled and frozen, columns are sized to their contents, entries
+/// beyond one sheet's row limit continue on further sheets, and a final
+/// "Export Info" sheet records where the entries came from.
 pub fn export(
     entries: &[LdapEntry],
     path: &Path,
     attributes: &[String],
+    schema: Option<&SchemaCache>,
+    metadata: &ExportMetadata,
 ) -> Result<usize, CoreError> {
-    if entries.is_empty() {
-        return Ok(0);
+    write_all(&mut writer(path, attributes, schema, metadata), entries)
+}
+
+/// A streaming writer for an Excel file at `path`; see [`export`]. Sheets
+/// are written in constant-memory mode, and nothing is saved if there are
+/// no entries.
+pub(super) fn writer<'a>(
+    path: &Path,
+    attributes: &[String],
+    schema: Option<&'a SchemaCache>,
+    metadata: &ExportMetadata,
+) -> TableWriter<'a, XlsxSink> {
+    let sink = XlsxSink::new(path, attributes, metadata, MAX_ROWS_PER_SHEET);
+    TableWriter::new(sink, attributes, schema)
+}
+
+pub(super) struct XlsxSink {
+    workbook: Workbook,
+    path: PathBuf,
+    attributes: Vec<String>,
+    metadata: ExportMetadata,
+    rows_per_sheet: usize,
+    header: Vec<String>,
+    header_format: Format,
+    /// Sheets of entries started so far; the last is being written.
+    sheets: usize,
+    rows_in_sheet: usize,
+    widths: Vec<usize>,
+}
+
+impl XlsxSink {
+    fn new(
+        path: &Path,
+        attributes: &[String],
+        metadata: &ExportMetadata,
+        rows_per_sheet: usize,
+    ) -> Self {
+        Self {
+            workbook: Workbook::new(),
+            path: path.to_path_buf(),
+            attributes: attributes.to_vec(),
+            metadata: metadata.clone(),
+            rows_per_sheet,
+            header: Vec::new(),
+            header_format: Format::new()
+                .set_bold()
+                .set_background_color(Color::RGB(0xD9E1F2))
+                .set_border_bottom(FormatBorder::Thin),
+            sheets: 0,
+            rows_in_sheet: 0,
+            widths: Vec::new(),
+        }
     }
 
-    let mut workbook = Workbook::new();
-    let worksheet = workbook.add_worksheet();
+    /// Start the next sheet of entries with its own header row.
+    fn start_sheet(&mut self) -> Result<(), XlsxError> {
+        self.sheets += 1;
+        let name = if self.sheets == 1 {
+            "LDAP Entries".to_string()
+        } else {
+            format!("LDAP Entries ({})", self.sheets)
+        };
+        let worksheet = self.workbook.add_worksheet_with_constant_memory();
+        worksheet.set_name(name)?;
+        for (col, name) in self.header.iter().enumerate() {
+            worksheet.write_string_with_format(0, col as u16, name, &self.header_format)?;
+        }
+        self.widths = self.header.iter().map(|h| h.chars().count()).collect();
+        self.rows_in_sheet = 0;
+        Ok(())
+    }
 
-    worksheet
-        .set_name("LDAP Entries")
-        .map_err(|e| CoreError::ExportError(format!("Excel error: {}", e)))?;
+    /// Size, freeze and filter the sheet being written.
+    fn close_sheet(&mut self) -> Result<(), XlsxError> {
+        let worksheet = self.workbook.worksheet_from_index(self.sheets - 1)?;
+        for (col, width) in self.widths.iter().enumerate() {
+            let width = (*width).clamp(MIN_COLUMN_WIDTH, MAX_COLUMN_WIDTH) + 2;
+            worksheet.set_column_width(col as u16, width as f64)?;
+        }
+        worksheet.set_freeze_panes(1, 0)?;
+        let last_col = self.header.len().saturating_sub(1) as u16;
+        worksheet.autofilter(0, 0, self.rows_in_sheet as u32, last_col)?;
+        Ok(())
+    }
 
-    let attr_names: Vec<String> = if let Some(attrs) = requested_attrs(attributes) {
-        attrs.to_vec()
-    } else {
-        let mut all_attrs: BTreeSet<String> = BTreeSet::new();
-        for entry in entries {
-            for key in entry.attributes.keys() {
-                all_attrs.insert(key.clone());
+    fn write_row(&mut self, cells: &[Vec<String>]) -> Result<(), XlsxError> {
+        if self.sheets == 0 || self.rows_in_sheet == self.rows_per_sheet {
+            if self.sheets > 0 {
+                self.close_sheet()?;
             }
+            self.start_sheet()?;
         }
-        all_attrs.into_iter().collect()
-    };
+        self.rows_in_sheet += 1;
+        let row = self.rows_in_sheet as u32;
+        let worksheet = self.workbook.worksheet_from_index(self.sheets - 1)?;
+        for (col, vals) in cells.iter().enumerate() {
+            let value = vals.join("; ");
+            if !value.is_empty() {
+                self.widths[col] = self.widths[col].max(value.chars().count());
+                worksheet.write_string(row, col as u16, &value)?;
+            }
+        }
+        Ok(())
+    }
 
-    let header_format = Format::new().set_bold();
+    /// Finish the last sheet of entries, add the "Export Info" sheet and save.
+    fn save(&mut self, rows: usize) -> Result<(), XlsxError> {
+        self.close_sheet()?;
+        let info = self.workbook.add_worksheet();
+        info.set_name("Export Info")?;
+        let exported = chrono::Utc::now()
+            .format("%Y-%m-%d %H:%M:%S UTC")
+            .to_string();
+        let info_rows = [
+            ("Exported", exported),
+            ("Server", self.metadata.server.clone().unwrap_or_default()),
+            ("Base DN", self.metadata.base_dn.clone().unwrap_or_default()),
+            ("Filter", self.metadata.filter.clone().unwrap_or_default()),
+            ("Attributes", self.attributes.join(", ")),
+            ("Entries", rows.to_string()),
+            ("Sheets", self.sheets.to_string()),
+        ];
+        let mut value_width = MIN_COLUMN_WIDTH;
+        for (row, (label, value)) in info_rows.iter().enumerate() {
+            info.write_string_with_format(row as u32, 0, *label, &self.header_format)?;
+            info.write_string(row as u32, 1, value)?;
+            value_width = value_width.max(value.chars().count());
+        }
+        info.set_column_width(0, 12)?;
+        info.set_column_width(1, (value_width.min(MAX_COLUMN_WIDTH) + 2) as f64)?;
+        self.workbook.save(&self.path)
+    }
+}
 
-    // Header row
-    worksheet
-        .write_string_with_format(0, 0, "dn", &header_format)
-        .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
+impl TableSink for XlsxSink {
+    fn header(&mut self, header: &[String]) -> Result<(), CoreError> {
+        self.header = header.to_vec();
+        Ok(())
+    }
 
-    for (col, attr) in attr_names.iter().enumerate() {
-        worksheet
-            .write_string_with_format(0, (col + 1) as u16, attr, &header_format)
-            .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
+    fn row(&mut self, cells: &[Vec<String>]) -> Result<(), CoreError> {
+        self.write_row(cells)
+            .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))
     }
 
-    // Data rows
-    for (row_idx, entry) in entries.iter().enumerate() {
-        let row = (row_idx + 1) as u32;
-
-        worksheet
-            .write_string(row, 0, &entry.dn)
-            .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
-
-        for (col_idx, attr) in attr_names.iter().enumerate() {
-            let col = (col_idx + 1) as u16;
-            let value = entry
-                .attributes
-                .get(attr)
-                .map(|vals| vals.join("; "))
-                .unwrap_or_default();
-            if !value.is_empty() {
-                worksheet
-                    .write_string(row, col, &value)
-                    .map_err(|e| CoreError::ExportError(format!("Excel write error: {}", e)))?;
-            }
+    fn finish(&mut self, rows: usize) -> Result<(), CoreError> {
+        if rows == 0 {
+            return Ok(());
         }
+        self.save(rows)
+            .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use calamine::{open_workbook, Reader, Xlsx};
+
+    use super::*;
+
+    fn entries(count: usize) -> Vec<LdapEntry> {
+        (0..count)
+            .map(|i| {
+                LdapEntry::new(
+                    format!("cn=user{},dc=example,dc=com", i),
+                    BTreeMap::from([
+                        ("cn".to_string(), vec![format!("user{}", i)]),
+                        ("Mail".to_string(), vec![format!("user{}@example.com", i)]),
+                        ("sn".to_string(), vec!["Doe".to_string()]),
+                    ]),
+                )
+            })
+            .collect()
+    }
+
+    fn cell(sheet: &calamine::Range<calamine::Data>, row: usize, col: usize) -> String {
+        sheet
+            .get((row, col))
+            .map(|c| c.to_string())
+            .unwrap_or_default()
     }
 
-    worksheet
-        .set_column_width(0, 50)
-        .map_err(|e| CoreError::ExportError(format!("Excel error: {}", e)))?;
+    #[test]
+    fn test_requested_column_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("out.xlsx");
+        let attrs = ["sn".to_string(), "dn".to_string(), "mail".to_string()];
+        let metadata = ExportMetadata {
+            server: Some("ldap.example.com".to_string()),
+            base_dn: Some("dc=example,dc=com".to_string()),
+            filter: Some("(objectClass=person)".to_string()),
+        };
+        assert_eq!(
+            export(&entries(2), &path, &attrs, None, &metadata).unwrap(),
+            2
+        );
 
-    workbook
-        .save(path)
-        .map_err(|e| CoreError::ExportError(format!("Excel save failed: {}", e)))?;
+        let mut book: Xlsx<_> = open_workbook(&path).unwrap();
+        assert_eq!(book.sheet_names(), vec!["LDAP Entries", "Export Info"]);
+        let sheet = book.worksheet_range("LDAP Entries").unwrap();
+        // Requested order, with dn where it was asked for, matched
+        // case-insensitively
+        assert_eq!(cell(&sheet, 0, 0), "sn");
+        assert_eq!(cell(&sheet, 0, 1), "dn");
+        assert_eq!(cell(&sheet, 0, 2), "mail");
+        assert_eq!(cell(&sheet, 1, 1), "cn=user0,dc=example,dc=com");
+        assert_eq!(cell(&sheet, 2, 2), "user1@example.com");
 
-    Ok(entries.len())
+        let info = book.worksheet_range("Export Info").unwrap();
+        assert_eq!(cell(&info, 1, 1), "ldap.example.com");
+        assert_eq!(cell(&info, 3, 1), "(objectClass=person)");
+        assert_eq!(cell(&info, 5, 1), "2");
+    }
+
+    #[test]
+    fn test_split_across_sheets() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("split.xlsx");
+        let sink = XlsxSink::new(&path, &["cn".to_string()], &ExportMetadata::default(), 2);
+        let mut writer = TableWriter::new(sink, &["cn".to_string()], None);
+        assert_eq!(write_all(&mut writer, &entries(5)).unwrap(), 5);
+
+        let mut book: Xlsx<_> = open_workbook(&path).unwrap();
+        assert_eq!(
+            book.sheet_names(),
+            vec![
+                "LDAP Entries",
+                "LDAP Entries (2)",
+                "LDAP Entries (3)",
+                "Export Info"
+            ]
+        );
+        let last = book.worksheet_range("LDAP Entries (3)").unwrap();
+        // Each sheet has its own header
+        assert_eq!(cell(&last, 0, 0), "dn");
+        assert_eq!(cell(&last, 1, 1), "user4");
+        assert_eq!(last.height(), 2);
+    }
 }
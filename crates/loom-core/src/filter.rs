@@ -1,3 +1,10 @@
+use std::ops::Range;
+
+use chrono::{DateTime, Duration, Utc};
+
+use crate::reports::{day_start, to_filetime, to_generalized_time, FILETIME_ATTRIBUTES};
+use crate::schema::{AttributeSyntax, SchemaCache};
+
 /// The kind of filter context the cursor is in.
 #[derive(Debug, Clone, PartialEq)]
 pub enum FilterContext {
@@ -95,6 +102,200 @@ pub fn detect_attribute_context(input: &str) -> Option<String> {
     Some(partial.to_string())
 }
 
+/// Comparison operator of a simple filter item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CompareOp {
+    Equal,
+    Approx,
+    GreaterOrEqual,
+    LessOrEqual,
+}
+
+impl CompareOp {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            CompareOp::Equal => "=",
+            CompareOp::Approx => "~=",
+            CompareOp::GreaterOrEqual => ">=",
+            CompareOp::LessOrEqual => "<=",
+        }
+    }
+}
+
+/// A parsed LDAP search filter.
+///
+/// Assertion values are kept in their escaped RFC 4515 form, so binary
+/// values like `\01\ab` survive a parse/serialize round trip unchanged.
+/// The builders that take plain values escape them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Filter {
+    And(Vec<Filter>),
+    Or(Vec<Filter>),
+    Not(Box<Filter>),
+    /// `(attr=*)`
+    Present(String),
+    /// `(attr=initial*any*...*last)`; any part may be absent.
+    Substring {
+        attr: String,
+        initial: Option<String>,
+        any: Vec<String>,
+        last: Option<String>,
+    },
+    Compare {
+        attr: String,
+        op: CompareOp,
+        value: String,
+    },
+}
+
+impl Filter {
+    /// Parse a filter string; errors match [`validate_filter`].
+    pub fn parse(filter: &str) -> Result<Filter, String> {
+        parse_trimmed(filter.trim()).map_err(|e| e.message)
+    }
+
+    /// `(&...)` of the given clauses.
+    pub fn all(clauses: impl IntoIterator<Item = Filter>) -> Filter {
+        Filter::And(clauses.into_iter().collect())
+    }
+
+    /// `(|...)` of the given clauses.
+    pub fn any(clauses: impl IntoIterator<Item = Filter>) -> Filter {
+        Filter::Or(clauses.into_iter().collect())
+    }
+
+    pub fn negate(filter: Filter) -> Filter {
+        Filter::Not(Box::new(filter))
+    }
+
+    pub fn present(attr: &str) -> Filter {
+        Filter::Present(attr.to_string())
+    }
+
+    /// `(attr=value)` with `value` escaped.
+    pub fn equals(attr: &str, value: &str) -> Filter {
+        Filter::compare(attr, CompareOp::Equal, value)
+    }
+
+    /// `(attr<op>value)` with `value` escaped.
+    pub fn compare(attr: &str, op: CompareOp, value: &str) -> Filter {
+        Filter::Compare {
+            attr: attr.to_string(),
+            op,
+            value: escape_filter_value(value),
+        }
+    }
+
+    /// AND `clause` onto this filter. A top-level `(&...)` gains another
+    /// clause instead of being wrapped again.
+    pub fn with_clause(self, clause: Filter) -> Filter {
+        match self {
+            Filter::And(mut clauses) => {
+                clauses.push(clause);
+                Filter::And(clauses)
+            }
+            other => Filter::And(vec![other, clause]),
+        }
+    }
+
+    /// Rewrite attribute names throughout the filter. `f` returns the new
+    /// name, or `None` to leave the attribute as it is.
+    pub fn rewrite_attributes(&mut self, f: &mut impl FnMut(&str) -> Option<String>) {
+        match self {
+            Filter::And(clauses) | Filter::Or(clauses) => {
+                for clause in clauses {
+                    clause.rewrite_attributes(f);
+                }
+            }
+            Filter::Not(inner) => inner.rewrite_attributes(f),
+            Filter::Present(attr)
+            | Filter::Substring { attr, .. }
+            | Filter::Compare { attr, .. } => {
+                if let Some(new) = f(attr) {
+                    *attr = new;
+                }
+            }
+        }
+    }
+
+    /// Rename every use of `from` (case-insensitive) to `to`.
+    pub fn rename_attribute(&mut self, from: &str, to: &str) {
+        self.rewrite_attributes(&mut |attr| {
+            attr.eq_ignore_ascii_case(from).then(|| to.to_string())
+        });
+    }
+
+    /// Attribute names the filter refers to, in order of first use, without
+    /// case-insensitive duplicates.
+    pub fn attributes(&self) -> Vec<&str> {
+        let mut out: Vec<&str> = Vec::new();
+        self.collect_attributes(&mut out);
+        out
+    }
+
+    fn collect_attributes<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Filter::And(clauses) | Filter::Or(clauses) => {
+                for clause in clauses {
+                    clause.collect_attributes(out);
+                }
+            }
+            Filter::Not(inner) => inner.collect_attributes(out),
+            Filter::Present(attr)
+            | Filter::Substring { attr, .. }
+            | Filter::Compare { attr, .. } => {
+                if !out.iter().any(|a| a.eq_ignore_ascii_case(attr)) {
+                    out.push(attr);
+                }
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for Filter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Filter::And(clauses) | Filter::Or(clauses) => {
+                let op = if matches!(self, Filter::And(_)) {
+                    '&'
+                } else {
+                    '|'
+                };
+                write!(f, "({}", op)?;
+                for clause in clauses {
+                    write!(f, "{}", clause)?;
+                }
+                write!(f, ")")
+            }
+            Filter::Not(inner) => write!(f, "(!{})", inner),
+            Filter::Present(attr) => write!(f, "({}=*)", attr),
+            Filter::Substring {
+                attr,
+                initial,
+                any,
+                last,
+            } => {
+                write!(f, "({}={}", attr, initial.as_deref().unwrap_or(""))?;
+                for part in any {
+                    write!(f, "*{}", part)?;
+                }
+                write!(f, "*{})", last.as_deref().unwrap_or(""))
+            }
+            Filter::Compare { attr, op, value } => {
+                write!(f, "({}{}{})", attr, op.as_str(), value)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Filter::parse(s)
+    }
+}
+
 /// Validate that a string is a valid LDAP search filter per RFC 4515.
 ///
 /// ```text
@@ -108,148 +309,702 @@ pub fn detect_attribute_context(input: &str) -> Option<String> {
 /// filtertype = "=" / "~=" / ">=" / "<="
 /// ```
 pub fn validate_filter(filter: &str) -> Result<(), String> {
-    let filter = filter.trim();
+    Filter::parse(filter).map(|_| ())
+}
+
+/// A parse error and the byte offset it was found at.
+#[derive(Debug)]
+struct SyntaxError {
+    pos: usize,
+    message: String,
+}
+
+impl SyntaxError {
+    fn at(pos: usize, message: String) -> Self {
+        Self { pos, message }
+    }
+}
+
+/// Parse an already trimmed filter string.
+fn parse_trimmed(filter: &str) -> Result<Filter, SyntaxError> {
     if filter.is_empty() {
-        return Err("Filter cannot be empty".to_string());
+        return Err(SyntaxError::at(0, "Filter cannot be empty".to_string()));
     }
 
-    let bytes = filter.as_bytes();
-    let end = parse_filter(bytes, 0)?;
-    if end != bytes.len() {
-        Err(format!(
-            "Unexpected characters after filter at position {}",
-            end + 1
+    let (parsed, end) = parse_filter(filter, 0)?;
+    if end != filter.len() {
+        Err(SyntaxError::at(
+            end,
+            format!("Unexpected characters after filter at position {}", end + 1),
         ))
     } else {
-        Ok(())
+        Ok(parsed)
     }
 }
 
 /// Parse a single filter: "(" filtercomp ")"
-/// Returns the position after the closing ')'.
-fn parse_filter(input: &[u8], pos: usize) -> Result<usize, String> {
-    if pos >= input.len() {
-        return Err(format!("Expected '(' at position {}", pos + 1));
-    }
-    if input[pos] != b'(' {
-        return Err(format!("Expected '(' at position {}", pos + 1));
+/// Returns the filter and the position after the closing ')'.
+fn parse_filter(input: &str, pos: usize) -> Result<(Filter, usize), SyntaxError> {
+    let bytes = input.as_bytes();
+    if pos >= bytes.len() || bytes[pos] != b'(' {
+        return Err(SyntaxError::at(
+            pos,
+            format!("Expected '(' at position {}", pos + 1),
+        ));
     }
 
     let inner = pos + 1;
-    if inner >= input.len() {
-        return Err(format!(
-            "Unexpected end of filter after '(' at position {}",
-            pos + 1
+    if inner >= bytes.len() {
+        return Err(SyntaxError::at(
+            pos,
+            format!("Unexpected end of filter after '(' at position {}", pos + 1),
         ));
     }
 
-    let end = match input[inner] {
-        b'&' => parse_filter_list(input, inner + 1, '&')?,
-        b'|' => parse_filter_list(input, inner + 1, '|')?,
-        b'!' => parse_filter(input, inner + 1)?,
+    let (filter, end) = match bytes[inner] {
+        b'&' => {
+            let (clauses, end) = parse_filter_list(input, inner + 1, '&')?;
+            (Filter::And(clauses), end)
+        }
+        b'|' => {
+            let (clauses, end) = parse_filter_list(input, inner + 1, '|')?;
+            (Filter::Or(clauses), end)
+        }
+        b'!' => {
+            let (negated, end) = parse_filter(input, inner + 1)?;
+            (Filter::negate(negated), end)
+        }
         _ => parse_item(input, inner)?,
     };
 
-    if end >= input.len() {
-        return Err(format!("Expected ')' at position {}", end + 1));
-    }
-    if input[end] != b')' {
-        return Err(format!("Expected ')' at position {}", end + 1));
+    if end >= bytes.len() || bytes[end] != b')' {
+        return Err(SyntaxError::at(
+            end,
+            format!("Expected ')' at position {}", end + 1),
+        ));
     }
-    Ok(end + 1)
+    Ok((filter, end + 1))
 }
 
 /// Parse a filterlist: 1*filter
 /// The operator char is only used for error messages.
-fn parse_filter_list(input: &[u8], pos: usize, op: char) -> Result<usize, String> {
-    if pos >= input.len() || input[pos] != b'(' {
-        return Err(format!(
-            "Empty filter list in '{}' operator at position {}",
-            op,
-            pos + 1
-        ));
-    }
+fn parse_filter_list(
+    input: &str,
+    pos: usize,
+    op: char,
+) -> Result<(Vec<Filter>, usize), SyntaxError> {
+    let bytes = input.as_bytes();
     let mut cur = pos;
-    let mut count = 0;
-    while cur < input.len() && input[cur] == b'(' {
-        cur = parse_filter(input, cur)?;
-        count += 1;
-    }
-    if count == 0 {
-        return Err(format!(
-            "Empty filter list in '{}' operator at position {}",
-            op,
-            pos + 1
+    let mut clauses = Vec::new();
+    while cur < bytes.len() && bytes[cur] == b'(' {
+        let (clause, end) = parse_filter(input, cur)?;
+        clauses.push(clause);
+        cur = end;
+    }
+    if clauses.is_empty() {
+        return Err(SyntaxError::at(
+            pos,
+            format!(
+                "Empty filter list in '{}' operator at position {}",
+                op,
+                pos + 1
+            ),
         ));
     }
-    Ok(cur)
+    Ok((clauses, cur))
 }
 
 /// Parse a simple filter item: attr filtertype value
-/// Returns position after the value (just before the closing ')').
-fn parse_item(input: &[u8], pos: usize) -> Result<usize, String> {
+/// Returns the item and the position after the value (just before the closing ')').
+fn parse_item(input: &str, pos: usize) -> Result<(Filter, usize), SyntaxError> {
+    let bytes = input.as_bytes();
     // Parse attribute name: alphanumeric, hyphen, period, semicolon (for options like ;binary)
     let attr_start = pos;
     let mut cur = pos;
-    while cur < input.len()
-        && (input[cur].is_ascii_alphanumeric()
-            || input[cur] == b'-'
-            || input[cur] == b'.'
-            || input[cur] == b';')
+    while cur < bytes.len()
+        && (bytes[cur].is_ascii_alphanumeric()
+            || bytes[cur] == b'-'
+            || bytes[cur] == b'.'
+            || bytes[cur] == b';')
     {
         cur += 1;
     }
 
     if cur == attr_start {
-        return Err(format!(
-            "Expected attribute name after '(' at position {}",
-            pos + 1
+        return Err(SyntaxError::at(
+            pos,
+            format!("Expected attribute name after '(' at position {}", pos + 1),
         ));
     }
+    let attr = input[attr_start..cur].to_string();
 
-    if cur >= input.len() {
-        return Err(
+    let missing_op = |cur| {
+        SyntaxError::at(
+            cur,
             "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
-        );
+        )
+    };
+    if cur >= bytes.len() {
+        return Err(missing_op(cur));
     }
 
     // Parse filtertype: =, ~=, >=, <=
-    if cur + 1 < input.len() && input[cur + 1] == b'=' {
-        match input[cur] {
-            b'~' | b'>' | b'<' => {
-                cur += 2;
+    let op = match (bytes[cur], bytes.get(cur + 1)) {
+        (b'~', Some(b'=')) => CompareOp::Approx,
+        (b'>', Some(b'=')) => CompareOp::GreaterOrEqual,
+        (b'<', Some(b'=')) => CompareOp::LessOrEqual,
+        (b'=', _) => CompareOp::Equal,
+        _ => return Err(missing_op(cur)),
+    };
+    cur += op.as_str().len();
+
+    // Parse value: everything until the matching ')'
+    // Values can contain any character except unescaped ')' at our nesting level.
+    // Unescaped '*' in an equality value separates substring parts.
+    let value_start = cur;
+    let mut parts = Vec::new();
+    let mut part_start = cur;
+    while cur < bytes.len() && bytes[cur] != b')' {
+        if bytes[cur] == b'\\' && cur + 1 < bytes.len() {
+            // Skip escaped character
+            cur += 2;
+        } else if let Some(len) = macro_call_len(&bytes[cur..]) {
+            // A date macro's parentheses aren't the item's
+            cur += len;
+        } else {
+            if bytes[cur] == b'*' {
+                parts.push(&input[part_start..cur]);
+                part_start = cur + 1;
             }
-            b'=' => {
-                // Just '=' — single char operator
+            cur += 1;
+        }
+    }
+    let value = &input[value_start..cur];
+
+    let filter = if op != CompareOp::Equal || parts.is_empty() {
+        Filter::Compare {
+            attr,
+            op,
+            value: value.to_string(),
+        }
+    } else if value == "*" {
+        Filter::Present(attr)
+    } else {
+        let last = &input[part_start..cur];
+        let non_empty = |s: &str| (!s.is_empty()).then(|| s.to_string());
+        Filter::Substring {
+            attr,
+            initial: non_empty(parts[0]),
+            any: parts[1..]
+                .iter()
+                .filter(|p| !p.is_empty())
+                .map(|p| p.to_string())
+                .collect(),
+            last: non_empty(last),
+        }
+    };
+    Ok((filter, cur))
+}
+
+/// Escape special characters in an assertion value per RFC 4515.
+pub fn escape_filter_value(input: &str) -> String {
+    let mut out = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c {
+            '*' => out.push_str("\\2a"),
+            '(' => out.push_str("\\28"),
+            ')' => out.push_str("\\29"),
+            '\\' => out.push_str("\\5c"),
+            '\0' => out.push_str("\\00"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// How serious a [`FilterDiagnostic`] is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    /// The filter won't parse.
+    Error,
+    /// The filter parses but likely doesn't do what was meant.
+    Warning,
+}
+
+/// An edit that resolves a diagnostic: `span` replaced by `replacement`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterFix {
+    pub span: Range<usize>,
+    pub replacement: String,
+    /// What the fix does, e.g. "Insert ')'".
+    pub label: String,
+}
+
+impl FilterFix {
+    pub fn apply(&self, filter: &str) -> String {
+        let mut fixed = filter.to_string();
+        fixed.replace_range(self.span.clone(), &self.replacement);
+        fixed
+    }
+}
+
+/// A problem found in a filter, with the byte range it covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FilterDiagnostic {
+    pub span: Range<usize>,
+    pub severity: Severity,
+    pub message: String,
+    pub fix: Option<FilterFix>,
+}
+
+impl FilterDiagnostic {
+    fn new(severity: Severity, span: Range<usize>, message: String) -> Self {
+        Self {
+            span,
+            severity,
+            message,
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, span: Range<usize>, replacement: &str, label: String) -> Self {
+        self.fix = Some(FilterFix {
+            span,
+            replacement: replacement.to_string(),
+            label,
+        });
+        self
+    }
+}
+
+/// The byte ranges of a simple item's parts, as found by [`scan_filter`].
+struct ItemSpans {
+    attr: Range<usize>,
+    op: Option<CompareOp>,
+    value: Range<usize>,
+}
+
+/// Parentheses and items of a filter, found without requiring it to parse.
+#[derive(Default)]
+struct FilterScan {
+    items: Vec<ItemSpans>,
+    /// Offsets of '(' never closed.
+    unclosed: Vec<usize>,
+    /// Offsets of ')' closing nothing.
+    stray: Vec<usize>,
+}
+
+/// Walk a filter the way the parser does, but keep going past errors:
+/// a '(' not followed by `&`, `|`, `!` or another '(' starts an item whose
+/// value runs to the next unescaped ')'.
+fn scan_filter(input: &str) -> FilterScan {
+    let bytes = input.as_bytes();
+    let mut scan = FilterScan::default();
+    let mut open = Vec::new();
+    let mut cur = 0;
+    while cur < bytes.len() {
+        match bytes[cur] {
+            b'(' => {
+                open.push(cur);
                 cur += 1;
+                if matches!(
+                    bytes.get(cur),
+                    None | Some(b'&' | b'|' | b'!' | b'(' | b')')
+                ) {
+                    continue;
+                }
+                let attr_start = cur;
+                while cur < bytes.len() && !b"=~<>()".contains(&bytes[cur]) {
+                    cur += 1;
+                }
+                let attr = attr_start..cur;
+                let op = match (bytes.get(cur), bytes.get(cur + 1)) {
+                    (Some(b'~'), Some(b'=')) => Some(CompareOp::Approx),
+                    (Some(b'>'), Some(b'=')) => Some(CompareOp::GreaterOrEqual),
+                    (Some(b'<'), Some(b'=')) => Some(CompareOp::LessOrEqual),
+                    (Some(b'='), _) => Some(CompareOp::Equal),
+                    _ => None,
+                };
+                if let Some(op) = op {
+                    cur += op.as_str().len();
+                }
+                let value_start = cur;
+                while cur < bytes.len() && bytes[cur] != b')' {
+                    cur += match bytes[cur] {
+                        b'\\' => 2,
+                        _ => macro_call_len(&bytes[cur..]).unwrap_or(1),
+                    };
+                }
+                cur = cur.min(bytes.len());
+                scan.items.push(ItemSpans {
+                    attr,
+                    op,
+                    value: value_start..cur,
+                });
             }
-            _ => {
-                return Err(
-                    "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
+            b')' => {
+                if open.pop().is_none() {
+                    scan.stray.push(cur);
+                }
+                cur += 1;
+            }
+            _ => cur += 1,
+        }
+    }
+    scan.unclosed = open;
+    scan
+}
+
+/// Check a filter for syntax errors and likely mistakes. Unlike
+/// [`validate_filter`], every problem found is reported, each with the
+/// byte range of `filter` it covers and, where one is obvious, a fix.
+/// With a schema, attribute names are checked against it.
+///
+/// Diagnostics are ordered errors first, then by position.
+pub fn lint_filter(filter: &str, schema: Option<&SchemaCache>) -> Vec<FilterDiagnostic> {
+    let trimmed = filter.trim();
+    if trimmed.is_empty() {
+        return Vec::new();
+    }
+    let offset = filter.len() - filter.trim_start().len();
+    let end = offset + trimmed.len();
+    let scan = scan_filter(filter);
+    let mut diagnostics = Vec::new();
+
+    for &pos in &scan.stray {
+        diagnostics.push(
+            FilterDiagnostic::new(
+                Severity::Error,
+                pos..pos + 1,
+                format!("Unbalanced ')' at position {}", pos + 1),
+            )
+            .with_fix(pos..pos + 1, "", "Remove the ')'".to_string()),
+        );
+    }
+    if let Some(&pos) = scan.unclosed.first() {
+        let missing = scan.unclosed.len();
+        diagnostics.push(
+            FilterDiagnostic::new(
+                Severity::Error,
+                pos..pos + 1,
+                format!("Unbalanced '(' at position {} is never closed", pos + 1),
+            )
+            .with_fix(
+                end..end,
+                &")".repeat(missing),
+                format!("Insert {} ')' at the end", missing),
+            ),
+        );
+    }
+
+    // The parser's error is only news when the parentheses balance
+    if diagnostics.is_empty() {
+        if let Err(e) = parse_trimmed(trimmed) {
+            let pos = (offset + e.pos).min(end.saturating_sub(1));
+            let mut diagnostic = FilterDiagnostic::new(Severity::Error, pos..pos + 1, e.message);
+            if !trimmed.starts_with('(') && scan.items.is_empty() {
+                diagnostic = diagnostic.with_fix(
+                    offset..end,
+                    &format!("({})", trimmed),
+                    "Wrap in parentheses".to_string(),
                 );
             }
+            diagnostics.push(diagnostic);
         }
-    } else if cur < input.len() && input[cur] == b'=' {
-        cur += 1;
-    } else {
-        return Err(
-            "Expected comparison operator (=, ~=, >=, <=) after attribute name".to_string(),
+    }
+
+    for item in &scan.items {
+        lint_item(filter, item, schema, &mut diagnostics);
+    }
+
+    diagnostics.sort_by_key(|d| (d.severity, d.span.start));
+    diagnostics
+}
+
+/// Warnings about a single item: unknown attributes and stray wildcards.
+fn lint_item(
+    filter: &str,
+    item: &ItemSpans,
+    schema: Option<&SchemaCache>,
+    diagnostics: &mut Vec<FilterDiagnostic>,
+) {
+    let attr = &filter[item.attr.clone()];
+    // Options like ";binary" don't change which attribute is meant
+    let name = attr.split(';').next().unwrap_or_default();
+    let is_oid = !name.is_empty() && name.bytes().all(|b| b.is_ascii_digit() || b == b'.');
+    let known = schema
+        .filter(|s| !s.attribute_types.is_empty())
+        .map(|s| is_oid || s.get_attribute_type(name).is_some());
+    // Without an operator the name may still be being typed
+    if item.op.is_some() && !name.is_empty() && known == Some(false) {
+        let mut diagnostic = FilterDiagnostic::new(
+            Severity::Warning,
+            item.attr.start..item.attr.start + name.len(),
+            format!("'{}' is not an attribute in the schema", name),
         );
+        if let Some(suggestion) = schema.and_then(|s| closest_attribute(s, name)) {
+            diagnostic = diagnostic.with_fix(
+                item.attr.start..item.attr.start + name.len(),
+                &suggestion,
+                format!("Replace with '{}'", suggestion),
+            );
+        }
+        diagnostics.push(diagnostic);
     }
 
-    // Parse value: everything until the matching ')'
-    // Values can contain any character except unescaped ')' at our nesting level.
-    // We just scan until we find ')'.
-    while cur < input.len() && input[cur] != b')' {
-        if input[cur] == b'\\' && cur + 1 < input.len() {
-            // Skip escaped character
-            cur += 2;
-        } else {
-            cur += 1;
+    let Some(op) = item.op else {
+        return;
+    };
+    let value = &filter[item.value.clone()];
+    let stars = unescaped_stars(value);
+    if stars.is_empty() {
+        return;
+    }
+    let star_span = |i: usize| item.value.start + i..item.value.start + i + 1;
+
+    if op != CompareOp::Equal {
+        // Only '=' has wildcards; elsewhere a '*' must be escaped
+        for &i in &stars {
+            diagnostics.push(
+                FilterDiagnostic::new(
+                    Severity::Warning,
+                    star_span(i),
+                    format!(
+                        "'*' is not a wildcard with '{}' and should be escaped",
+                        op.as_str()
+                    ),
+                )
+                .with_fix(
+                    star_span(i),
+                    "\\2a",
+                    "Escape the '*' as \\2a".to_string(),
+                ),
+            );
+        }
+        return;
+    }
+
+    for pair in stars.windows(2) {
+        if pair[1] == pair[0] + 1 {
+            let span = item.value.start + pair[0]..item.value.start + pair[1] + 1;
+            diagnostics.push(
+                FilterDiagnostic::new(
+                    Severity::Warning,
+                    span.clone(),
+                    "Repeated '*' matches the same as a single one".to_string(),
+                )
+                .with_fix(span, "*", "Use a single '*'".to_string()),
+            );
         }
     }
 
-    Ok(cur)
+    // A wildcard against a DN-valued attribute finds nothing on most servers
+    let is_dn = schema.is_some_and(|s| s.attribute_syntax(name) == AttributeSyntax::Dn);
+    if is_dn && value != "*" {
+        diagnostics.push(FilterDiagnostic::new(
+            Severity::Warning,
+            star_span(stars[0]),
+            format!(
+                "'{}' holds DNs, which most servers can't match with '*'",
+                name
+            ),
+        ));
+    }
+}
+
+/// Byte offsets of the unescaped '*'s in an assertion value.
+fn unescaped_stars(value: &str) -> Vec<usize> {
+    let bytes = value.as_bytes();
+    let mut stars = Vec::new();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'\\' => i += 2,
+            b'*' => {
+                stars.push(i);
+                i += 1;
+            }
+            _ => i += 1,
+        }
+    }
+    stars
+}
+
+/// The schema attribute name nearest `name`, if one is within two edits.
+fn closest_attribute(schema: &SchemaCache, name: &str) -> Option<String> {
+    let lower = name.to_lowercase();
+    schema
+        .all_attribute_names()
+        .into_iter()
+        .map(|candidate| (edit_distance(&lower, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= 2)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, candidate)| candidate)
+}
+
+/// Levenshtein distance between two strings, by character.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+/// Date macros taking an argument in parentheses.
+const MACRO_CALLS: [&str; 2] = ["@filetime(", "@generalized("];
+
+/// The length of the `@filetime(...)` or `@generalized(...)` call at the
+/// start of `rest`, if there is one.
+fn macro_call_len(rest: &[u8]) -> Option<usize> {
+    let call = MACRO_CALLS
+        .iter()
+        .find(|c| rest.starts_with(c.as_bytes()))?;
+    let close = rest[call.len()..].iter().position(|&b| b == b')')?;
+    Some(call.len() + close + 1)
+}
+
+/// What decides how a date macro is written: the time it's relative to,
+/// and the directory whose attributes it is compared with.
+#[derive(Debug, Clone, Copy)]
+pub struct MacroContext<'a> {
+    pub now: DateTime<Utc>,
+    /// Active Directory keeps account times as FILETIMEs.
+    pub active_directory: bool,
+    pub schema: Option<&'a SchemaCache>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TimeFormat {
+    Filetime,
+    Generalized,
+}
+
+/// Expand the date macros in a filter. A value that is a macro is
+/// replaced by a timestamp:
+///
+/// - `@now`, or `@today` for midnight UTC, optionally offset by hours,
+///   days or weeks: `@today-30d`, `@now+12h`, `@now-2w`
+/// - `@filetime(...)` and `@generalized(...)` around the same times force
+///   the format; the `@now` may be left out, so `@filetime(-90d)` is 90
+///   days ago
+///
+/// Otherwise the format follows the attribute: a FILETIME for Active
+/// Directory's account times (`pwdLastSet`, `lastLogonTimestamp`, ...),
+/// GeneralizedTime for everything else. Other values, including ones
+/// that merely start like a macro such as `@nowhere`, are left alone.
+pub fn expand_macros(filter: &str, context: &MacroContext) -> Result<String, String> {
+    let mut expanded = String::with_capacity(filter.len());
+    let mut copied = 0;
+    for item in scan_filter(filter).items {
+        let attr = &filter[item.attr.clone()];
+        let value = &filter[item.value.clone()];
+        if let Some(timestamp) = expand_value(attr, value, context)? {
+            expanded.push_str(&filter[copied..item.value.start]);
+            expanded.push_str(&timestamp);
+            copied = item.value.end;
+        }
+    }
+    expanded.push_str(&filter[copied..]);
+    Ok(expanded)
+}
+
+/// The timestamp a macro value stands for, or `None` when `value` isn't
+/// a macro. Only an explicit `@filetime(...)` or `@generalized(...)` with
+/// an unreadable time is an error.
+fn expand_value(attr: &str, value: &str, context: &MacroContext) -> Result<Option<String>, String> {
+    let call = |name: &str| {
+        value
+            .strip_prefix(name)
+            .and_then(|arg| arg.strip_suffix(')'))
+    };
+    let explicit = |arg: &str| {
+        macro_time(arg, context.now).ok_or_else(|| format!("Can't read the time in '{}'", value))
+    };
+    let (format, at) = if let Some(arg) = call(MACRO_CALLS[0]) {
+        (Some(TimeFormat::Filetime), explicit(arg)?)
+    } else if let Some(arg) = call(MACRO_CALLS[1]) {
+        (Some(TimeFormat::Generalized), explicit(arg)?)
+    } else if value.starts_with("@now") || value.starts_with("@today") {
+        match macro_time(value, context.now) {
+            Some(at) => (None, at),
+            None => return Ok(None),
+        }
+    } else {
+        return Ok(None);
+    };
+    let format = match format {
+        Some(format) => format,
+        None => attribute_time_format(attr, context)?,
+    };
+    Ok(Some(match format {
+        TimeFormat::Filetime => to_filetime(at).to_string(),
+        TimeFormat::Generalized => to_generalized_time(at),
+    }))
+}
+
+/// The time of a macro: `now` or `today` (with or without the `@`), or
+/// neither meaning now, followed by an optional `+`/`-` offset in `h`,
+/// `d` or `w`.
+fn macro_time(spec: &str, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let spec = spec.strip_prefix('@').unwrap_or(spec);
+    let (base, offset) = if let Some(rest) = spec.strip_prefix("now") {
+        (now, rest)
+    } else if let Some(rest) = spec.strip_prefix("today") {
+        (day_start(now), rest)
+    } else {
+        (now, spec)
+    };
+    if offset.is_empty() {
+        return Some(base);
+    }
+    let sign = match offset.as_bytes()[0] {
+        b'+' => 1,
+        b'-' => -1,
+        _ => return None,
+    };
+    let unit = offset.chars().last()?;
+    let count: i64 = offset
+        .get(1..offset.len() - unit.len_utf8())?
+        .parse()
+        .ok()?;
+    let span = match unit {
+        'h' => Duration::try_hours(count)?,
+        'd' => Duration::try_days(count)?,
+        'w' => Duration::try_weeks(count)?,
+        _ => return None,
+    };
+    base.checked_add_signed(span * sign)
+}
+
+/// How a time compared with `attr` has to be written.
+fn attribute_time_format(attr: &str, context: &MacroContext) -> Result<TimeFormat, String> {
+    let name = attr.split(';').next().unwrap_or_default();
+    if context.active_directory
+        && FILETIME_ATTRIBUTES
+            .iter()
+            .any(|a| a.eq_ignore_ascii_case(name))
+    {
+        return Ok(TimeFormat::Filetime);
+    }
+    match context.schema.map(|s| s.attribute_syntax(name)) {
+        Some(AttributeSyntax::Integer) => Err(format!(
+            "'{}' is not a time attribute; use @filetime(...) or @generalized(...)",
+            name
+        )),
+        _ => Ok(TimeFormat::Generalized),
+    }
 }
 
 #[cfg(test)]
@@ -367,6 +1122,123 @@ mod tests {
         assert!(err.contains("Unexpected characters"), "got: {}", err);
     }
 
+    #[test]
+    fn test_lint_filter() {
+        let directory = crate::offline::OfflineDirectory::load_embedded();
+        let schema = Some(directory.schema());
+        let messages = |filter: &str| -> Vec<String> {
+            lint_filter(filter, schema)
+                .into_iter()
+                .map(|d| d.message)
+                .collect()
+        };
+        let fixed = |filter: &str| {
+            let diagnostics = lint_filter(filter, schema);
+            diagnostics[0].fix.as_ref().unwrap().apply(filter)
+        };
+
+        assert!(lint_filter("(&(mail=*@x.com)(cn>=b))", schema).is_empty());
+        assert!(lint_filter("(member=*)", schema).is_empty());
+
+        // Unbalanced parentheses are positioned and fixable
+        let d = &lint_filter("(&(cn=a)(sn=b)", schema)[0];
+        assert_eq!((d.severity, d.span.clone()), (Severity::Error, 0..1));
+        assert_eq!(fixed("(&(cn=a)(sn=b)"), "(&(cn=a)(sn=b))");
+        assert_eq!(
+            messages("(cn=a))"),
+            ["Unbalanced ')' at position 7".to_string()]
+        );
+        assert_eq!(fixed("(cn=a))"), "(cn=a)");
+        assert_eq!(fixed("cn=a"), "(cn=a)");
+
+        // Parser errors keep their position, offset by leading whitespace
+        let d = &lint_filter("  (&)", schema)[0];
+        assert_eq!(d.span, 4..5);
+        assert!(d.message.starts_with("Empty filter list"), "{}", d.message);
+
+        // Unknown attributes, with the nearest name as a fix
+        let d = &lint_filter("(mial=a)", schema)[0];
+        assert_eq!((d.severity, d.span.clone()), (Severity::Warning, 1..5));
+        assert_eq!(fixed("(mial=a)"), "(mail=a)");
+        assert!(lint_filter("(mial=a)", None).is_empty());
+        assert!(lint_filter("(2.5.4.3=a)", schema).is_empty());
+
+        // Suspicious wildcards
+        assert_eq!(fixed("(cn>=a*)"), "(cn>=a\\2a)");
+        assert_eq!(fixed("(cn=a**b)"), "(cn=a*b)");
+        assert!(lint_filter("(cn>=a\\2a)", schema).is_empty());
+        let d = &lint_filter("(member=*admin*)", schema)[0];
+        assert!(d.message.contains("holds DNs"), "{}", d.message);
+        assert!(d.fix.is_none());
+    }
+
+    #[test]
+    fn test_expand_macros() {
+        let mut schema = crate::offline::OfflineDirectory::load_embedded()
+            .schema()
+            .clone();
+        let mut uid_number = schema.get_attribute_type("cn").unwrap().clone();
+        uid_number.syntax = AttributeSyntax::Integer;
+        schema
+            .attribute_types
+            .insert("uidnumber".to_string(), uid_number);
+        let now = DateTime::parse_from_rfc3339("2026-03-31T15:30:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let context = MacroContext {
+            now,
+            active_directory: true,
+            schema: Some(&schema),
+        };
+        let expand = |filter: &str| expand_macros(filter, &context);
+        let filetime = |at: &str| {
+            let at = DateTime::parse_from_rfc3339(at)
+                .unwrap()
+                .with_timezone(&Utc);
+            to_filetime(at).to_string()
+        };
+
+        assert_eq!(
+            expand("(&(whenChanged>=@today-30d)(modifyTimestamp<=@now))").unwrap(),
+            "(&(whenChanged>=20260301000000Z)(modifyTimestamp<=20260331153000Z))"
+        );
+        assert_eq!(
+            expand("(lastLogonTimestamp<=@now-2w)").unwrap(),
+            format!("(lastLogonTimestamp<={})", filetime("2026-03-17T15:30:00Z"))
+        );
+        // The call's parentheses don't close the item
+        assert!(validate_filter("(pwdLastSet<=@filetime(-90d))").is_ok());
+        assert_eq!(
+            expand("(!(pwdLastSet<=@filetime(-90d)))").unwrap(),
+            format!("(!(pwdLastSet<={}))", filetime("2025-12-31T15:30:00Z"))
+        );
+        assert_eq!(
+            expand("(cn=@generalized(today+1d))").unwrap(),
+            "(cn=20260401000000Z)"
+        );
+        // Outside Active Directory the same attribute is GeneralizedTime
+        let other = MacroContext {
+            active_directory: false,
+            ..context
+        };
+        assert_eq!(
+            expand_macros("(pwdLastSet>=@today)", &other).unwrap(),
+            "(pwdLastSet>=20260331000000Z)"
+        );
+
+        // Values that aren't macros are untouched
+        let plain = "(&(mail=*@nowhere.com)(cn=@team))";
+        assert_eq!(expand(plain).unwrap(), plain);
+        for literal in ["(cn=@nowhere)", "(mail=@today.example)", "(cn=@now-)"] {
+            assert_eq!(expand(literal).unwrap(), literal);
+        }
+        // An explicit call must hold a readable time
+        assert!(expand("(whenChanged>=@generalized(today-3x))").is_err());
+        assert!(expand("(pwdLastSet>=@filetime(now-))").is_err());
+        let err = expand("(uidNumber>=@now)").unwrap_err();
+        assert!(err.contains("not a time attribute"), "{}", err);
+    }
+
     // ---- detect_attribute_context tests ----
 
     #[test]
@@ -527,4 +1399,77 @@ mod tests {
             })
         );
     }
+
+    // ---- Filter AST tests ----
+
+    #[test]
+    fn test_parse_round_trip() {
+        for input in [
+            "(objectClass=*)",
+            "(&(objectClass=person)(|(cn=Alice)(cn=Bob))(!(sn=Smith)))",
+            "(cn=*smi*th*)",
+            "(cn=J*)",
+            "(uidNumber>=100)",
+            "(objectGUID=\\01\\ab)",
+        ] {
+            assert_eq!(Filter::parse(input).unwrap().to_string(), input);
+        }
+        assert_eq!(
+            Filter::parse("(cn=a*b*c)").unwrap(),
+            Filter::Substring {
+                attr: "cn".to_string(),
+                initial: Some("a".to_string()),
+                any: vec!["b".to_string()],
+                last: Some("c".to_string()),
+            }
+        );
+        assert_eq!(
+            Filter::parse("(cn=a\\2a)").unwrap(),
+            Filter::Compare {
+                attr: "cn".to_string(),
+                op: CompareOp::Equal,
+                value: "a\\2a".to_string(),
+            }
+        );
+        assert_eq!(
+            Filter::parse("(cn)").unwrap_err(),
+            validate_filter("(cn)").unwrap_err()
+        );
+    }
+
+    #[test]
+    fn test_build_and_transform() {
+        let built = Filter::all([
+            Filter::equals("objectClass", "user"),
+            Filter::negate(Filter::present("mail")),
+        ]);
+        assert_eq!(built.to_string(), "(&(objectClass=user)(!(mail=*)))");
+        assert_eq!(Filter::equals("cn", "a(b)").to_string(), "(cn=a\\28b\\29)");
+
+        // with_clause extends a top-level AND and wraps anything else
+        let extended = built.with_clause(Filter::equals("sn", "Doe"));
+        assert_eq!(
+            extended.to_string(),
+            "(&(objectClass=user)(!(mail=*))(sn=Doe))"
+        );
+        let wrapped = Filter::parse("(|(cn=a)(cn=b))")
+            .unwrap()
+            .with_clause(Filter::present("uid"));
+        assert_eq!(wrapped.to_string(), "(&(|(cn=a)(cn=b))(uid=*))");
+
+        let mut renamed = Filter::parse("(|(UID=jdoe)(uid=j*)(cn=x))").unwrap();
+        renamed.rename_attribute("uid", "sAMAccountName");
+        assert_eq!(
+            renamed.to_string(),
+            "(|(sAMAccountName=jdoe)(sAMAccountName=j*)(cn=x))"
+        );
+        assert_eq!(renamed.attributes(), ["sAMAccountName", "cn"]);
+    }
+
+    #[test]
+    fn test_escape_filter_value() {
+        assert_eq!(escape_filter_value("plain"), "plain");
+        assert_eq!(escape_filter_value("a*(b)\\c"), "a\\2a\\28b\\29\\5cc");
+        assert!(validate_filter(&format!("(cn={})", escape_filter_value("x)(y"))).is_ok());
+    }
 }
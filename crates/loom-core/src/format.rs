@@ -0,0 +1,280 @@
+use std::collections::{BTreeMap, HashMap};
+
+use base64::Engine;
+use chrono::{DateTime, NaiveDateTime};
+use regex_automata::meta::Regex;
+use serde::{Deserialize, Serialize};
+
+/// A built-in renderer for attribute values.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ValueFormat {
+    /// GeneralizedTime (`20240102103000.0Z`) as a readable UTC timestamp.
+    Date,
+    /// AD FILETIME: 100ns intervals since 1601 (`pwdLastSet`, `accountExpires`).
+    Filetime,
+    /// Seconds, or AD's negative 100ns intervals (`maxPwdAge`), as `42d 3h`.
+    Duration,
+    /// A byte count as `1.5 MiB`.
+    ByteSize,
+    /// A binary SID as `S-1-5-21-...`.
+    Sid,
+    /// A binary GUID in AD byte order as `xxxxxxxx-xxxx-...`.
+    Guid,
+    /// The value's bytes in hex.
+    Hex,
+}
+
+/// How one attribute is displayed: a built-in renderer, or a regex whose
+/// captures fill a template (`$1`, `${name}`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum FormatRule {
+    Builtin(ValueFormat),
+    Template { regex: String, template: String },
+}
+
+#[derive(Debug, Clone)]
+enum Renderer {
+    Builtin(ValueFormat),
+    Template { regex: Regex, template: String },
+}
+
+/// Display formatting rules keyed by attribute name, ready to apply.
+#[derive(Debug, Clone, Default)]
+pub struct DisplayFormats {
+    rules: HashMap<String, Renderer>,
+}
+
+impl DisplayFormats {
+    /// Compile the configured rules. A rule with an invalid regex is left
+    /// out and reported in the returned errors.
+    pub fn new(rules: &BTreeMap<String, FormatRule>) -> (Self, Vec<String>) {
+        let mut compiled = HashMap::new();
+        let mut errors = Vec::new();
+        for (attr, rule) in rules {
+            let renderer = match rule {
+                FormatRule::Builtin(format) => Renderer::Builtin(*format),
+                FormatRule::Template { regex, template } => match Regex::new(regex) {
+                    Ok(regex) => Renderer::Template {
+                        regex,
+                        template: template.clone(),
+                    },
+                    Err(e) => {
+                        errors.push(format!("display format for {}: {}", attr, e));
+                        continue;
+                    }
+                },
+            };
+            compiled.insert(attr.to_lowercase(), renderer);
+        }
+        (Self { rules: compiled }, errors)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// `value` rendered by the rule for `attr`, or `None` when there is no
+    /// rule or the value isn't in the form the rule expects.
+    pub fn format(&self, attr: &str, value: &str) -> Option<String> {
+        match self.rules.get(&attr.to_lowercase())? {
+            Renderer::Builtin(format) => format.apply(value),
+            Renderer::Template { regex, template } => {
+                let mut caps = regex.create_captures();
+                regex.captures(value, &mut caps);
+                caps.is_match()
+                    .then(|| caps.interpolate_string(value, template))
+            }
+        }
+    }
+
+    /// `value` rendered for display, falling back to the value itself.
+    pub fn display(&self, attr: &str, value: &str) -> String {
+        self.format(attr, value)
+            .unwrap_or_else(|| value.to_string())
+    }
+}
+
+impl ValueFormat {
+    pub fn apply(self, value: &str) -> Option<String> {
+        match self {
+            ValueFormat::Date => format_generalized_time(value),
+            ValueFormat::Filetime => format_filetime(value),
+            ValueFormat::Duration => format_duration(value),
+            ValueFormat::ByteSize => format_byte_size(value),
+            ValueFormat::Sid => format_sid(&binary_value(value)),
+            ValueFormat::Guid => format_guid(&binary_value(value)),
+            ValueFormat::Hex => Some(
+                value
+                    .bytes()
+                    .map(|b| format!("{:02x}", b))
+                    .collect::<Vec<_>>()
+                    .join(" "),
+            ),
+        }
+    }
+}
+
+fn format_generalized_time(value: &str) -> Option<String> {
+    let digits = value.get(..14)?;
+    if !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let t = NaiveDateTime::parse_from_str(digits, "%Y%m%d%H%M%S").ok()?;
+    Some(format!("{} UTC", t.format("%Y-%m-%d %H:%M:%S")))
+}
+
+fn format_filetime(value: &str) -> Option<String> {
+    match value.trim().parse::<i64>().ok()? {
+        0 | i64::MAX => Some("never".to_string()),
+        ticks => {
+            let secs = ticks / 10_000_000 - 11_644_473_600;
+            let t = DateTime::from_timestamp(secs, 0)?;
+            Some(t.format("%Y-%m-%d %H:%M:%S UTC").to_string())
+        }
+    }
+}
+
+fn format_duration(value: &str) -> Option<String> {
+    let n = value.trim().parse::<i64>().ok()?;
+    let secs = match n {
+        i64::MIN => return Some("never".to_string()),
+        // AD stores intervals as negative counts of 100ns
+        n if n < 0 => n.unsigned_abs() / 10_000_000,
+        n => n as u64,
+    };
+    if secs == 0 {
+        return Some("0s".to_string());
+    }
+    let parts: Vec<String> = [(86_400, "d"), (3_600, "h"), (60, "m"), (1, "s")]
+        .iter()
+        .scan(secs, |rest, &(unit, suffix)| {
+            let count = *rest / unit;
+            *rest %= unit;
+            Some((count, suffix))
+        })
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, suffix)| format!("{}{}", count, suffix))
+        .collect();
+    Some(parts.join(" "))
+}
+
+fn format_byte_size(value: &str) -> Option<String> {
+    value.trim().parse::<u64>().ok().map(crate::humanize::bytes)
+}
+
+/// The bytes of a binary value, which arrive as text: hex (optionally
+/// `\`-escaped or spaced, as in filters), base64, or else the raw bytes.
+fn binary_value(value: &str) -> Vec<u8> {
+    let hex: String = value
+        .chars()
+        .filter(|c| !matches!(c, '\\' | ' ' | ':'))
+        .collect();
+    if !hex.is_empty() && hex.len() % 2 == 0 && hex.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return (0..hex.len())
+            .step_by(2)
+            .filter_map(|i| u8::from_str_radix(&hex[i..i + 2], 16).ok())
+            .collect();
+    }
+    base64::engine::general_purpose::STANDARD
+        .decode(value.trim())
+        .unwrap_or_else(|_| value.as_bytes().to_vec())
+}
+
+/// MS-DTYP 2.4.2.2: revision, sub-authority count, 48-bit big-endian
+/// authority, then little-endian 32-bit sub-authorities.
+fn format_sid(bytes: &[u8]) -> Option<String> {
+    let (&revision, rest) = bytes.split_first()?;
+    let (&count, rest) = rest.split_first()?;
+    let authority = rest.get(..6)?;
+    let subs = rest.get(6..)?;
+    if subs.len() != count as usize * 4 {
+        return None;
+    }
+    let authority = authority.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64);
+    let mut sid = format!("S-{}-{}", revision, authority);
+    for chunk in subs.chunks_exact(4) {
+        let sub = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        sid.push_str(&format!("-{}", sub));
+    }
+    Some(sid)
+}
+
+/// The first three GUID fields are little-endian on the wire.
+fn format_guid(bytes: &[u8]) -> Option<String> {
+    let b: &[u8; 16] = bytes.try_into().ok()?;
+    Some(format!(
+        "{:02x}{:02x}{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}-{:02x}{:02x}{:02x}{:02x}{:02x}{:02x}",
+        b[3], b[2], b[1], b[0], b[5], b[4], b[7], b[6], b[8], b[9], b[10], b[11], b[12], b[13],
+        b[14], b[15]
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_formats() {
+        assert_eq!(
+            ValueFormat::Date.apply("20240102103000.0Z").as_deref(),
+            Some("2024-01-02 10:30:00 UTC")
+        );
+        assert_eq!(
+            ValueFormat::Filetime.apply("133500000000000000").as_deref(),
+            Some("2024-01-17 21:20:00 UTC")
+        );
+        assert_eq!(ValueFormat::Filetime.apply("0").as_deref(), Some("never"));
+        assert_eq!(
+            ValueFormat::Duration.apply("-36288000000000").as_deref(),
+            Some("42d")
+        );
+        assert_eq!(
+            ValueFormat::Duration.apply("3725").as_deref(),
+            Some("1h 2m 5s")
+        );
+        assert_eq!(
+            ValueFormat::ByteSize.apply("1572864").as_deref(),
+            Some("1.5 MiB")
+        );
+        assert_eq!(ValueFormat::ByteSize.apply("512").as_deref(), Some("512 B"));
+        assert_eq!(
+            ValueFormat::Sid
+                .apply("010500000000000515000000a065cf7e784b9b5fe77c8770f4010000")
+                .as_deref(),
+            Some("S-1-5-21-2127521184-1604012920-1887927527-500")
+        );
+        assert_eq!(
+            ValueFormat::Guid
+                .apply("\\33\\22\\11\\00\\55\\44\\77\\66\\88\\99\\aa\\bb\\cc\\dd\\ee\\ff")
+                .as_deref(),
+            Some("00112233-4455-6677-8899-aabbccddeeff")
+        );
+        assert_eq!(ValueFormat::Hex.apply("Ab").as_deref(), Some("41 62"));
+        assert_eq!(ValueFormat::Date.apply("yesterday"), None);
+    }
+
+    #[test]
+    fn test_rules_from_config() {
+        let rules: BTreeMap<String, FormatRule> = toml::from_str(
+            r#"
+            pwdLastSet = "filetime"
+            telephoneNumber = { regex = '^\+(\d)(\d{3})(\d+)$', template = "+$1 ($2) $3" }
+            broken = { regex = "(", template = "$1" }
+            "#,
+        )
+        .unwrap();
+        let (formats, errors) = DisplayFormats::new(&rules);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("broken"));
+        assert_eq!(formats.display("PWDLASTSET", "0"), "never");
+        assert_eq!(
+            formats.display("telephoneNumber", "+15551234567"),
+            "+1 (555) 1234567"
+        );
+        // Values the rule doesn't match, and attributes without one, are unchanged
+        assert_eq!(formats.display("telephoneNumber", "ext. 12"), "ext. 12");
+        assert_eq!(formats.display("cn", "John"), "John");
+    }
+}
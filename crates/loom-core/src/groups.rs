@@ -0,0 +1,408 @@
+//! Nested group membership: the groups an entry belongs to directly or
+//! through other groups, or every member of a group including those of
+//! its nested groups.
+//!
+//! Active Directory resolves the chain itself with the
+//! LDAP_MATCHING_RULE_IN_CHAIN rule. Elsewhere every group under the base
+//! DN is read once and the membership graph is walked here, breadth-first
+//! for each member's shortest path and depth-first to find cycles.
+
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::filter::escape_filter_value;
+use crate::util::find_values_ci;
+
+/// LDAP_MATCHING_RULE_IN_CHAIN, which walks `member` / `memberOf` links
+/// transitively on Active Directory.
+const IN_CHAIN: &str = "1.2.840.113556.1.4.1941";
+const GROUP_CLASSES: &[&str] = &["group", "groupOfNames", "groupOfUniqueNames"];
+const GROUP_FILTER: &str =
+    "(|(objectClass=group)(objectClass=groupOfNames)(objectClass=groupOfUniqueNames))";
+
+/// Which way to follow membership.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expansion {
+    /// The groups the entry is in, directly or through other groups.
+    Groups,
+    /// The members of the group, including those of nested groups.
+    Members,
+}
+
+impl Expansion {
+    pub fn label(self) -> &'static str {
+        match self {
+            Expansion::Groups => "Groups of",
+            Expansion::Members => "Members of",
+        }
+    }
+
+    pub fn other(self) -> Self {
+        match self {
+            Expansion::Groups => Expansion::Members,
+            Expansion::Members => Expansion::Groups,
+        }
+    }
+}
+
+/// One entry of the flattened membership.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MemberRow {
+    pub dn: String,
+    /// 1 for direct membership; `None` when the server resolved the chain
+    /// and the entry isn't a direct one.
+    pub depth: Option<usize>,
+    /// The group (or entry) it was reached through on the shortest path.
+    pub via: Option<String>,
+    pub is_group: bool,
+    /// Whether the entry is part of a membership cycle.
+    pub in_cycle: bool,
+}
+
+/// The flattened membership of one entry.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MembershipReport {
+    pub dn: String,
+    pub expansion: Expansion,
+    /// Whether the server resolved the chain (Active Directory).
+    pub server_resolved: bool,
+    pub rows: Vec<MemberRow>,
+    /// Each cycle as the DNs along it, ending where it started.
+    pub cycles: Vec<Vec<String>>,
+}
+
+impl MembershipReport {
+    pub fn direct_count(&self) -> usize {
+        self.rows.iter().filter(|r| r.depth == Some(1)).count()
+    }
+}
+
+/// Whether `entry` is a group by its object classes.
+pub fn is_group(entry: &LdapEntry) -> bool {
+    find_values_ci(&entry.attributes, "objectClass").is_some_and(|classes| {
+        classes
+            .iter()
+            .any(|c| GROUP_CLASSES.iter().any(|g| g.eq_ignore_ascii_case(c)))
+    })
+}
+
+/// Groups are expanded to their members, other entries to their groups.
+pub fn default_expansion(entry: &LdapEntry) -> Expansion {
+    if is_group(entry) {
+        Expansion::Members
+    } else {
+        Expansion::Groups
+    }
+}
+
+/// The member DNs of a group entry, from `member` and `uniqueMember`.
+pub fn group_members(entry: &LdapEntry) -> Vec<String> {
+    let mut members = Vec::new();
+    for attr in ["member", "uniqueMember"] {
+        for value in find_values_ci(&entry.attributes, attr)
+            .into_iter()
+            .flatten()
+        {
+            members.push(strip_unique_id(value).to_string());
+        }
+    }
+    members
+}
+
+/// A `uniqueMember` value without its optional `#'...'B` unique
+/// identifier.
+pub fn strip_unique_id(value: &str) -> &str {
+    match value.rfind("#'") {
+        Some(i) if value.ends_with("'B") => &value[..i],
+        _ => value,
+    }
+}
+
+/// Walk the membership graph `groups` (each group with its members) from
+/// `root`.
+pub fn expand(
+    groups: &[(String, Vec<String>)],
+    root: &str,
+    expansion: Expansion,
+) -> MembershipReport {
+    let key = |dn: &str| dn.to_lowercase();
+    let mut display: HashMap<String, String> = HashMap::new();
+    let mut group_keys = HashSet::new();
+    let mut edges: HashMap<String, Vec<String>> = HashMap::new();
+    for (group, members) in groups {
+        group_keys.insert(key(group));
+        display.entry(key(group)).or_insert_with(|| group.clone());
+        for member in members {
+            display.entry(key(member)).or_insert_with(|| member.clone());
+            let (from, to) = match expansion {
+                Expansion::Members => (key(group), key(member)),
+                Expansion::Groups => (key(member), key(group)),
+            };
+            edges.entry(from).or_default().push(to);
+        }
+    }
+    let root_key = key(root);
+    let name = |k: &str| display.get(k).cloned().unwrap_or_else(|| k.to_string());
+
+    // Breadth-first: each entry's depth and the path it's first reached by
+    let mut rows = Vec::new();
+    let mut seen = HashSet::from([root_key.clone()]);
+    let mut queue = VecDeque::from([(root_key.clone(), 0)]);
+    while let Some((node, depth)) = queue.pop_front() {
+        for next in edges.get(&node).into_iter().flatten() {
+            if seen.insert(next.clone()) {
+                rows.push(MemberRow {
+                    dn: name(next),
+                    depth: Some(depth + 1),
+                    via: (node != root_key).then(|| name(&node)),
+                    is_group: group_keys.contains(next),
+                    in_cycle: false,
+                });
+                queue.push_back((next.clone(), depth + 1));
+            }
+        }
+    }
+
+    // Depth-first: an edge back to an entry on the current path closes a cycle
+    let mut cycles: Vec<Vec<String>> = Vec::new();
+    let mut done = HashSet::new();
+    let mut path: Vec<String> = Vec::new();
+    let mut stack: Vec<(String, usize)> = vec![(root_key.clone(), 0)];
+    while let Some((node, child)) = stack.pop() {
+        if child == 0 {
+            path.push(node.clone());
+        }
+        let next = edges.get(&node).and_then(|e| e.get(child)).cloned();
+        match next {
+            Some(next) => {
+                stack.push((node, child + 1));
+                if let Some(start) = path.iter().position(|p| *p == next) {
+                    let mut cycle: Vec<String> = path[start..].iter().map(|k| name(k)).collect();
+                    cycle.push(name(&next));
+                    cycles.push(cycle);
+                } else if !done.contains(&next) {
+                    stack.push((next, 0));
+                }
+            }
+            None => {
+                path.pop();
+                done.insert(node);
+            }
+        }
+    }
+
+    let in_cycle: HashSet<String> = cycles.iter().flatten().map(|dn| key(dn)).collect();
+    for row in &mut rows {
+        row.in_cycle = in_cycle.contains(&key(&row.dn));
+    }
+    MembershipReport {
+        dn: root.to_string(),
+        expansion,
+        server_resolved: false,
+        rows,
+        cycles,
+    }
+}
+
+/// The groups among `entries`, with their members, for [`expand`].
+pub fn group_graph(entries: &[LdapEntry]) -> Vec<(String, Vec<String>)> {
+    entries
+        .iter()
+        .filter(|e| is_group(e))
+        .map(|e| (e.dn.clone(), group_members(e)))
+        .collect()
+}
+
+impl LdapConnection {
+    /// Resolve the nested membership of `dn`, searching under `base_dn`.
+    /// Without an `expansion`, the [`default_expansion`] for the entry.
+    pub async fn nested_membership(
+        &mut self,
+        dn: &str,
+        base_dn: &str,
+        expansion: Option<Expansion>,
+        active_directory: bool,
+    ) -> Result<MembershipReport, CoreError> {
+        let entry = self
+            .search_entry_attrs(dn, &["objectClass", "member", "uniqueMember", "memberOf"])
+            .await?
+            .ok_or_else(|| CoreError::SearchFailed(format!("{} was not found", dn)))?;
+        let expansion = expansion.unwrap_or_else(|| default_expansion(&entry));
+
+        if active_directory {
+            match self.chain_membership(&entry, base_dn, expansion).await {
+                Ok(report) => return Ok(report),
+                Err(e) => tracing::debug!("in-chain search failed, expanding here: {}", e),
+            }
+        }
+        let groups = self
+            .search_subtree(
+                base_dn,
+                GROUP_FILTER,
+                &["objectClass", "member", "uniqueMember"],
+            )
+            .await?;
+        Ok(expand(&group_graph(&groups), dn, expansion))
+    }
+
+    /// Let Active Directory walk the chain; only direct membership gets a
+    /// depth. A group that turns up in its own chain is a cycle.
+    async fn chain_membership(
+        &mut self,
+        entry: &LdapEntry,
+        base_dn: &str,
+        expansion: Expansion,
+    ) -> Result<MembershipReport, CoreError> {
+        let (link, direct_attr) = match expansion {
+            Expansion::Groups => ("member", "memberOf"),
+            Expansion::Members => ("memberOf", "member"),
+        };
+        let filter = format!(
+            "({}:{}:={})",
+            link,
+            IN_CHAIN,
+            escape_filter_value(&entry.dn)
+        );
+        let found = self
+            .search_subtree(base_dn, &filter, &["objectClass"])
+            .await?;
+        let direct: HashSet<String> = find_values_ci(&entry.attributes, direct_attr)
+            .into_iter()
+            .flatten()
+            .map(|dn| dn.to_lowercase())
+            .collect();
+
+        let root = entry.dn.to_lowercase();
+        let mut cycles = Vec::new();
+        let mut rows = Vec::new();
+        for e in found {
+            if e.dn.to_lowercase() == root {
+                cycles.push(vec![entry.dn.clone(), "...".to_string(), entry.dn.clone()]);
+                continue;
+            }
+            rows.push(MemberRow {
+                depth: direct.contains(&e.dn.to_lowercase()).then_some(1),
+                via: None,
+                is_group: is_group(&e),
+                in_cycle: false,
+                dn: e.dn,
+            });
+        }
+        rows.sort_by_key(|r| (r.depth.is_none(), r.dn.to_lowercase()));
+        Ok(MembershipReport {
+            dn: entry.dn.clone(),
+            expansion,
+            server_resolved: true,
+            rows,
+            cycles,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(pairs: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+        pairs
+            .iter()
+            .map(|(g, members)| {
+                (
+                    g.to_string(),
+                    members.iter().map(|m| m.to_string()).collect(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_expand_groups_and_members() {
+        let groups = graph(&[
+            ("cn=staff", &["cn=it", "cn=alice"]),
+            ("cn=it", &["cn=admins", "cn=bob"]),
+            ("cn=admins", &["cn=Alice"]),
+            ("cn=vpn", &["cn=it"]),
+        ]);
+
+        let report = expand(&groups, "cn=alice", Expansion::Groups);
+        let found: Vec<(&str, Option<usize>, Option<&str>)> = report
+            .rows
+            .iter()
+            .map(|r| (r.dn.as_str(), r.depth, r.via.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("cn=staff", Some(1), None),
+                ("cn=admins", Some(1), None),
+                ("cn=it", Some(2), Some("cn=admins")),
+                ("cn=vpn", Some(3), Some("cn=it")),
+            ]
+        );
+        assert!(report.cycles.is_empty());
+        assert_eq!(report.direct_count(), 2);
+
+        let report = expand(&groups, "cn=staff", Expansion::Members);
+        let members: Vec<(&str, bool)> = report
+            .rows
+            .iter()
+            .map(|r| (r.dn.as_str(), r.is_group))
+            .collect();
+        assert_eq!(
+            members,
+            [
+                ("cn=it", true),
+                ("cn=alice", false),
+                ("cn=admins", true),
+                ("cn=bob", false),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expand_detects_cycles() {
+        // a -> b -> c -> a, reached from the user through d -> a
+        let groups = graph(&[
+            ("cn=a", &["cn=c"]),
+            ("cn=b", &["cn=a"]),
+            ("cn=c", &["cn=b"]),
+            ("cn=a", &["cn=user"]),
+        ]);
+        let report = expand(&groups, "cn=user", Expansion::Groups);
+        assert_eq!(report.rows.len(), 3);
+        assert!(report.rows.iter().all(|r| r.in_cycle));
+        assert_eq!(
+            report.cycles,
+            [vec!["cn=a", "cn=b", "cn=c", "cn=a"]
+                .into_iter()
+                .map(String::from)
+                .collect::<Vec<_>>()]
+        );
+
+        // A group nested in itself
+        let report = expand(&graph(&[("cn=x", &["cn=x"])]), "cn=x", Expansion::Members);
+        assert!(report.rows.is_empty());
+        assert_eq!(report.cycles.len(), 1);
+    }
+
+    #[test]
+    fn test_group_members() {
+        let mut attrs = std::collections::BTreeMap::new();
+        attrs.insert(
+            "objectClass".to_string(),
+            vec!["groupOfUniqueNames".to_string()],
+        );
+        attrs.insert(
+            "uniqueMember".to_string(),
+            vec![
+                "uid=a,dc=x#'0101'B".to_string(),
+                "cn=#hash,dc=x".to_string(),
+            ],
+        );
+        let entry = LdapEntry::new("cn=g,dc=x".to_string(), attrs);
+        assert!(is_group(&entry));
+        assert_eq!(group_members(&entry), ["uid=a,dc=x", "cn=#hash,dc=x"]);
+    }
+}
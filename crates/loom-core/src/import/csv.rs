@@ -69,6 +69,32 @@ pub fn parse_csv(content: &str) -> Result<Vec<LdapEntry>, CoreError> {
     Ok(entries)
 }
 
+/// A CSV file's header and rows, before its columns are mapped to
+/// attributes (see [`crate::import::mapping`]).
+#[derive(Debug, Clone, Default)]
+pub struct CsvTable {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+/// Read CSV content as a table, without requiring a `dn` column.
+pub fn read_table(content: &str) -> Result<CsvTable, CoreError> {
+    let mut reader = csv::Reader::from_reader(content.as_bytes());
+    let headers = reader
+        .headers()
+        .map_err(|e| CoreError::ImportError(format!("CSV header error: {}", e)))?
+        .iter()
+        .map(|s| s.trim().to_string())
+        .collect();
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let record =
+            result.map_err(|e| CoreError::ImportError(format!("CSV record error: {}", e)))?;
+        rows.push(record.iter().map(|s| s.to_string()).collect());
+    }
+    Ok(CsvTable { headers, rows })
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
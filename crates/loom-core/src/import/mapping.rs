@@ -0,0 +1,332 @@
+use std::collections::{BTreeMap, HashSet};
+
+use crate::dn;
+use crate::entry::LdapEntry;
+use crate::schema::SchemaCache;
+use crate::util::find_values_ci;
+
+use super::csv::CsvTable;
+
+/// How the columns of a CSV file become entries: the attribute each column
+/// fills, and a template that builds each row's DN from its columns, such as
+/// `uid={username},ou=people,dc=example,dc=com`.
+///
+/// Placeholder values are RDN-escaped, except when the template is a single
+/// placeholder, which then names a column that already holds whole DNs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ColumnMapping {
+    /// The attribute for each column, in header order; `None` leaves the
+    /// column out.
+    pub attributes: Vec<Option<String>>,
+    pub dn_template: String,
+}
+
+/// The entries a mapping produces and the rows it couldn't turn into one.
+#[derive(Debug, Clone, Default)]
+pub struct MappingResult {
+    pub entries: Vec<LdapEntry>,
+    /// `row N: reason`, counting the first data row as row 1.
+    pub problems: Vec<String>,
+}
+
+impl ColumnMapping {
+    /// A first guess: columns named after an attribute (any of its names,
+    /// when the schema is loaded) map to it, and a `dn` column becomes the
+    /// DN template.
+    pub fn guess(headers: &[String], schema: Option<&SchemaCache>) -> Self {
+        let mut dn_template = String::new();
+        let attributes = headers
+            .iter()
+            .map(|header| {
+                if header.eq_ignore_ascii_case("dn") {
+                    dn_template = format!("{{{}}}", header);
+                    return None;
+                }
+                match schema {
+                    Some(schema) => schema
+                        .get_attribute_type(header)
+                        .map(|_| schema.canonical_attribute_name(header)),
+                    None => Some(header.clone()),
+                }
+            })
+            .collect();
+        Self {
+            attributes,
+            dn_template,
+        }
+    }
+
+    /// Build entries from `table`, checking the DN template, each row's DN,
+    /// duplicate DNs and, when a schema is given, that mapped attributes
+    /// exist and required attributes are present. This is the whole import
+    /// short of writing, so it also serves as a dry run. Returns an error
+    /// when the mapping itself is unusable.
+    pub fn apply(
+        &self,
+        table: &CsvTable,
+        schema: Option<&SchemaCache>,
+    ) -> Result<MappingResult, String> {
+        let template = Template::parse(&self.dn_template, &table.headers)?;
+        if let Some(schema) = schema {
+            for attr in self.attributes.iter().flatten() {
+                if schema.get_attribute_type(attr).is_none() {
+                    return Err(format!("{} is not in the schema", attr));
+                }
+            }
+        }
+
+        let mut result = MappingResult::default();
+        let mut seen = HashSet::new();
+        for (i, row) in table.rows.iter().enumerate() {
+            let n = i + 1;
+            if row.iter().all(|v| v.trim().is_empty()) {
+                continue;
+            }
+            let dn = match template.fill(row) {
+                Ok(dn) => dn,
+                Err(e) => {
+                    result.problems.push(format!("row {}: {}", n, e));
+                    continue;
+                }
+            };
+            if let Err(e) = dn::validate_dn(&dn) {
+                result
+                    .problems
+                    .push(format!("row {}: invalid DN {}: {}", n, dn, e));
+                continue;
+            }
+            if !seen.insert(dn.to_lowercase()) {
+                result
+                    .problems
+                    .push(format!("row {}: duplicate DN {}", n, dn));
+                continue;
+            }
+
+            let mut attributes: BTreeMap<String, Vec<String>> = BTreeMap::new();
+            for (attr, value) in self.attributes.iter().zip(row) {
+                let (Some(attr), value) = (attr, value.trim()) else {
+                    continue;
+                };
+                if !value.is_empty() {
+                    attributes
+                        .entry(attr.clone())
+                        .or_default()
+                        .extend(value.split("; ").map(|s| s.to_string()));
+                }
+            }
+
+            if let Some(schema) = schema {
+                let classes: Vec<&str> = find_values_ci(&attributes, "objectClass")
+                    .into_iter()
+                    .flatten()
+                    .map(|s| s.as_str())
+                    .collect();
+                let present: Vec<&str> = attributes.keys().map(|k| k.as_str()).collect();
+                let missing = schema.missing_must_attributes(&classes, &present);
+                if !missing.is_empty() {
+                    result.problems.push(format!(
+                        "row {}: {} is missing required {}",
+                        n,
+                        dn,
+                        missing.join(", ")
+                    ));
+                    continue;
+                }
+            }
+            result.entries.push(LdapEntry::new(dn, attributes));
+        }
+        Ok(result)
+    }
+}
+
+/// A parsed DN template: literal text and column placeholders.
+struct Template {
+    parts: Vec<Part>,
+}
+
+enum Part {
+    Text(String),
+    Column(usize, String),
+}
+
+impl Template {
+    fn parse(template: &str, headers: &[String]) -> Result<Self, String> {
+        if template.trim().is_empty() {
+            return Err("A DN template is required".to_string());
+        }
+        let mut parts = Vec::new();
+        let mut rest = template;
+        while let Some(start) = rest.find('{') {
+            let len = rest[start..]
+                .find('}')
+                .ok_or_else(|| "Unclosed '{' in DN template".to_string())?;
+            let name = &rest[start + 1..start + len];
+            let col = headers
+                .iter()
+                .position(|h| h.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("DN template names unknown column '{}'", name))?;
+            if start > 0 {
+                parts.push(Part::Text(rest[..start].to_string()));
+            }
+            parts.push(Part::Column(col, name.to_string()));
+            rest = &rest[start + len + 1..];
+        }
+        if !rest.is_empty() {
+            parts.push(Part::Text(rest.to_string()));
+        }
+        if !parts.iter().any(|p| matches!(p, Part::Column(..))) {
+            return Err("The DN template must use at least one {column}".to_string());
+        }
+        Ok(Self { parts })
+    }
+
+    fn fill(&self, row: &[String]) -> Result<String, String> {
+        let whole = self.parts.len() == 1;
+        let mut dn = String::new();
+        for part in &self.parts {
+            match part {
+                Part::Text(text) => dn.push_str(text),
+                Part::Column(col, name) => {
+                    let value = row.get(*col).map(|v| v.trim()).unwrap_or("");
+                    if value.is_empty() {
+                        return Err(format!("{} is empty", name));
+                    }
+                    if whole {
+                        dn.push_str(value);
+                    } else {
+                        dn.push_str(&dn::escape_rdn_value(value));
+                    }
+                }
+            }
+        }
+        Ok(dn)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::import::csv::read_table;
+    use crate::schema::{AttributeSyntax, AttributeTypeInfo, ObjectClassInfo, ObjectClassKind};
+
+    fn schema() -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        for names in [
+            vec!["uid", "userid"],
+            vec!["cn", "commonName"],
+            vec!["sn"],
+            vec!["mail"],
+            vec!["objectClass"],
+        ] {
+            let info = AttributeTypeInfo {
+                oid: String::new(),
+                names: names.iter().map(|n| n.to_string()).collect(),
+                description: None,
+                syntax: AttributeSyntax::DirectoryString,
+                syntax_oid: None,
+                syntax_length: None,
+                superior: None,
+                equality: None,
+                ordering: None,
+                substr: None,
+                single_value: false,
+                no_user_modification: false,
+            };
+            for name in names {
+                schema
+                    .attribute_types
+                    .insert(name.to_lowercase(), info.clone());
+            }
+        }
+        schema.object_classes.insert(
+            "person".to_string(),
+            ObjectClassInfo {
+                oid: String::new(),
+                names: vec!["person".to_string()],
+                description: None,
+                superior: None,
+                kind: ObjectClassKind::Structural,
+                must: vec!["cn".to_string(), "sn".to_string()],
+                may: vec![],
+            },
+        );
+        schema
+    }
+
+    #[test]
+    fn test_guess_and_apply() {
+        let table = read_table(
+            "username,userid,full name,sn,objectClass\n\
+             alice,alice,\"Smith, Alice\",Smith,person\n\
+             bob,bob,Bob,,person\n\
+             ,carol,Carol,Jones,person\n\
+             alice,alice2,Alice Two,Smith,person\n",
+        )
+        .unwrap();
+        let schema = schema();
+
+        let mut mapping = ColumnMapping::guess(&table.headers, Some(&schema));
+        assert_eq!(
+            mapping.attributes,
+            vec![
+                None,
+                Some("uid".to_string()),
+                None,
+                Some("sn".to_string()),
+                Some("objectClass".to_string()),
+            ]
+        );
+        assert!(mapping.apply(&table, Some(&schema)).is_err());
+
+        mapping.attributes[2] = Some("cn".to_string());
+        mapping.dn_template = "uid={Username},ou=people,dc=example,dc=com".to_string();
+        let result = mapping.apply(&table, Some(&schema)).unwrap();
+        assert_eq!(result.entries.len(), 1);
+        assert_eq!(
+            result.entries[0].dn,
+            "uid=alice,ou=people,dc=example,dc=com"
+        );
+        assert_eq!(result.entries[0].first_value("cn"), Some("Smith, Alice"));
+        assert_eq!(
+            result.problems,
+            vec![
+                "row 2: uid=bob,ou=people,dc=example,dc=com is missing required sn",
+                "row 3: Username is empty",
+                "row 4: duplicate DN uid=alice,ou=people,dc=example,dc=com",
+            ]
+        );
+
+        mapping.attributes[0] = Some("nickname".to_string());
+        assert_eq!(
+            mapping.apply(&table, Some(&schema)).unwrap_err(),
+            "nickname is not in the schema"
+        );
+    }
+
+    #[test]
+    fn test_dn_template() {
+        let table = read_table("dn,cn\n\"cn=Smith\\, A,dc=x\",A\n").unwrap();
+        let mapping = ColumnMapping::guess(&table.headers, None);
+        assert_eq!(mapping.dn_template, "{dn}");
+        // A lone placeholder is used as the whole DN, unescaped
+        let result = mapping.apply(&table, None).unwrap();
+        assert_eq!(result.entries[0].dn, "cn=Smith\\, A,dc=x");
+
+        let mapping = ColumnMapping {
+            attributes: vec![None, Some("cn".to_string())],
+            dn_template: "cn={cn},dc=x".to_string(),
+        };
+        let table = read_table("dn,cn\nignored,\"A+B\"\n").unwrap();
+        assert_eq!(
+            mapping.apply(&table, None).unwrap().entries[0].dn,
+            "cn=A\\+B,dc=x"
+        );
+        for bad in ["", "cn=fixed,dc=x", "cn={nope},dc=x", "cn={cn"] {
+            let mapping = ColumnMapping {
+                dn_template: bad.to_string(),
+                ..mapping.clone()
+            };
+            assert!(mapping.apply(&table, None).is_err(), "{}", bad);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use crate::error::CoreError;
+
+/// One row of a group membership CSV: a group and a member to add to it.
+/// Either side may be a full DN or a bare identifier (cn, uid, sAMAccountName, mail).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MembershipRow {
+    pub group: String,
+    pub member: String,
+}
+
+/// Parsed membership rows plus the number of repeated rows that were dropped.
+#[derive(Debug, Default)]
+pub struct MembershipCsv {
+    pub rows: Vec<MembershipRow>,
+    pub duplicates: usize,
+}
+
+/// Import membership rows from a two-column CSV file.
+pub fn import(path: &Path) -> Result<MembershipCsv, CoreError> {
+    let content = std::fs::read_to_string(path)
+        .map_err(|e| CoreError::ImportError(format!("Failed to read file: {}", e)))?;
+    parse_membership_csv(&content)
+}
+
+/// Parse two-column CSV content (group, member).
+///
+/// A header row is optional and is skipped when its first column is "group".
+/// Blank lines and rows with an empty column are ignored; repeated
+/// (group, member) pairs are counted once, compared case-insensitively.
+pub fn parse_membership_csv(content: &str) -> Result<MembershipCsv, CoreError> {
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(content.as_bytes());
+
+    let mut parsed = MembershipCsv::default();
+    let mut seen = HashSet::new();
+
+    for (idx, result) in reader.records().enumerate() {
+        let record =
+            result.map_err(|e| CoreError::ImportError(format!("CSV record error: {}", e)))?;
+
+        let group = record.get(0).unwrap_or("");
+        let member = record.get(1).unwrap_or("");
+
+        if idx == 0 && group.eq_ignore_ascii_case("group") {
+            continue;
+        }
+        if group.is_empty() || member.is_empty() {
+            continue;
+        }
+        if record.len() > 2 && record.iter().skip(2).any(|f| !f.is_empty()) {
+            return Err(CoreError::ImportError(format!(
+                "Line {}: expected 2 columns (group, member), found {}",
+                idx + 1,
+                record.len()
+            )));
+        }
+
+        if !seen.insert((group.to_lowercase(), member.to_lowercase())) {
+            parsed.duplicates += 1;
+            continue;
+        }
+
+        parsed.rows.push(MembershipRow {
+            group: group.to_string(),
+            member: member.to_string(),
+        });
+    }
+
+    Ok(parsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_with_header() {
+        let data = "group,member\nAdmins,alice\n\"cn=Ops,ou=Groups,dc=example,dc=com\",bob\n";
+        let parsed = parse_membership_csv(data).unwrap();
+        assert_eq!(parsed.rows.len(), 2);
+        assert_eq!(parsed.rows[0].group, "Admins");
+        assert_eq!(parsed.rows[0].member, "alice");
+        assert_eq!(parsed.rows[1].group, "cn=Ops,ou=Groups,dc=example,dc=com");
+    }
+
+    #[test]
+    fn test_parse_without_header() {
+        let data = "Admins,alice\nAdmins,bob\n";
+        let parsed = parse_membership_csv(data).unwrap();
+        assert_eq!(parsed.rows.len(), 2);
+    }
+
+    #[test]
+    fn test_duplicates_counted_case_insensitively() {
+        let data = "Admins,alice\nadmins, ALICE \nAdmins,alice\n";
+        let parsed = parse_membership_csv(data).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+        assert_eq!(parsed.duplicates, 2);
+    }
+
+    #[test]
+    fn test_blank_and_incomplete_rows_skipped() {
+        let data = "Admins,alice\n\nAdmins,\n,bob\n";
+        let parsed = parse_membership_csv(data).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+    }
+
+    #[test]
+    fn test_extra_columns_rejected() {
+        let data = "Admins,alice,extra\n";
+        assert!(parse_membership_csv(data).is_err());
+    }
+
+    #[test]
+    fn test_trailing_empty_column_allowed() {
+        let data = "Admins,alice,\n";
+        let parsed = parse_membership_csv(data).unwrap();
+        assert_eq!(parsed.rows.len(), 1);
+    }
+}
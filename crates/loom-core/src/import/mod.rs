@@ -1,6 +1,9 @@
 pub mod csv;
 pub mod json;
 pub mod ldif;
+pub mod mapping;
+pub mod membership;
+pub mod plan;
 pub mod xlsx;
 
 use std::path::Path;
@@ -19,5 +22,8 @@ pub fn import_entries(path: &Path) -> Result<Vec<LdapEntry>, CoreError> {
         ExportFormat::Json => json::import(path),
         ExportFormat::Csv => csv::import(path),
         ExportFormat::Xlsx => xlsx::import(path),
+        ExportFormat::Markdown => Err(CoreError::ImportError(
+            "Markdown tables can't be imported".to_string(),
+        )),
     }
 }
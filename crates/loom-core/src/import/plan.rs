@@ -0,0 +1,194 @@
+use std::collections::HashSet;
+
+use ldap3::Mod;
+
+use crate::dn;
+use crate::entry::LdapEntry;
+use crate::util::find_values_ci;
+
+/// What to do with an imported entry whose DN already exists.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum ConflictResolution {
+    /// Leave the server's entry alone.
+    #[default]
+    Skip,
+    /// Make the server's entry match the file: the file's attributes are
+    /// replaced and attributes missing from the file removed.
+    Overwrite,
+    /// Add the file's values that the server's entry lacks.
+    Merge,
+}
+
+impl ConflictResolution {
+    pub fn next(self) -> Self {
+        match self {
+            Self::Skip => Self::Overwrite,
+            Self::Overwrite => Self::Merge,
+            Self::Merge => Self::Skip,
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Self::Skip => "skip",
+            Self::Overwrite => "overwrite",
+            Self::Merge => "merge",
+        }
+    }
+}
+
+/// One entry from an import file and, if its DN is already on the server,
+/// the server's copy.
+#[derive(Debug, Clone)]
+pub struct ImportItem {
+    pub entry: LdapEntry,
+    pub existing: Option<LdapEntry>,
+    pub resolution: ConflictResolution,
+}
+
+impl ImportItem {
+    pub fn new(entry: LdapEntry, existing: Option<LdapEntry>) -> Self {
+        Self {
+            entry,
+            existing,
+            resolution: ConflictResolution::default(),
+        }
+    }
+
+    pub fn is_conflict(&self) -> bool {
+        self.existing.is_some()
+    }
+
+    /// The modifications that apply this item's resolution to the existing
+    /// entry; empty when there is nothing to change.
+    pub fn mods(&self) -> Vec<Mod<String>> {
+        match (&self.existing, self.resolution) {
+            (None, _) | (Some(_), ConflictResolution::Skip) => Vec::new(),
+            (Some(existing), ConflictResolution::Overwrite) => {
+                overwrite_mods(existing, &self.entry)
+            }
+            (Some(existing), ConflictResolution::Merge) => merge_mods(existing, &self.entry),
+        }
+    }
+}
+
+/// Sort entries so each parent comes before its children and siblings sit
+/// together, in the order they would appear in the directory tree.
+pub fn tree_order(entries: &mut [LdapEntry]) {
+    entries.sort_by_cached_key(|e| {
+        let mut parts: Vec<String> = e.dn.split(',').map(|p| p.trim().to_lowercase()).collect();
+        parts.reverse();
+        parts
+    });
+}
+
+/// Replace each attribute in `incoming` whose values differ, and delete
+/// attributes `existing` has that `incoming` doesn't, except objectClass
+/// and the RDN's attribute.
+pub fn overwrite_mods(existing: &LdapEntry, incoming: &LdapEntry) -> Vec<Mod<String>> {
+    let mut mods = Vec::new();
+    for (attr, values) in &incoming.attributes {
+        let unchanged = find_values_ci(&existing.attributes, attr).is_some_and(|current| {
+            current.iter().collect::<HashSet<_>>() == values.iter().collect::<HashSet<_>>()
+        });
+        if !unchanged {
+            mods.push(Mod::Replace(attr.clone(), values.iter().cloned().collect()));
+        }
+    }
+    let rdn_attr = dn::rdn(&existing.dn).split('=').next().unwrap_or("").trim();
+    for attr in existing.attributes.keys() {
+        let keep = attr.eq_ignore_ascii_case(rdn_attr) || attr.eq_ignore_ascii_case("objectClass");
+        if !keep && find_values_ci(&incoming.attributes, attr).is_none() {
+            mods.push(Mod::Replace(attr.clone(), HashSet::new()));
+        }
+    }
+    mods
+}
+
+/// Add the values of `incoming` that `existing` doesn't have, compared
+/// case-insensitively.
+pub fn merge_mods(existing: &LdapEntry, incoming: &LdapEntry) -> Vec<Mod<String>> {
+    let mut mods = Vec::new();
+    for (attr, values) in &incoming.attributes {
+        let current: HashSet<String> = find_values_ci(&existing.attributes, attr)
+            .into_iter()
+            .flatten()
+            .map(|v| v.to_lowercase())
+            .collect();
+        let missing: HashSet<String> = values
+            .iter()
+            .filter(|v| !current.contains(&v.to_lowercase()))
+            .cloned()
+            .collect();
+        if !missing.is_empty() {
+            mods.push(Mod::Add(attr.clone(), missing));
+        }
+    }
+    mods
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_overwrite_and_merge_mods() {
+        let existing = test_entry(
+            "uid=alice,ou=people,dc=example,dc=com",
+            &[
+                ("uid", &["alice"]),
+                ("mail", &["alice@example.com"]),
+                ("telephoneNumber", &["555-0100"]),
+                ("title", &["Engineer"]),
+            ],
+        );
+        let incoming = test_entry(
+            "uid=alice,ou=people,dc=example,dc=com",
+            &[
+                ("Mail", &["ALICE@example.com", "a@example.com"]),
+                ("title", &["Engineer"]),
+            ],
+        );
+
+        let mods = overwrite_mods(&existing, &incoming);
+        assert_eq!(mods.len(), 2);
+        // Changed values are replaced; the unchanged title is left alone
+        assert!(matches!(&mods[0], Mod::Replace(a, v) if a == "Mail" && v.len() == 2));
+        // Missing attributes are removed, except the RDN's
+        assert!(matches!(&mods[1], Mod::Replace(a, v) if a == "telephoneNumber" && v.is_empty()));
+
+        let mods = merge_mods(&existing, &incoming);
+        assert_eq!(mods.len(), 1);
+        assert!(matches!(&mods[0], Mod::Add(a, v)
+            if a == "Mail" && v == &HashSet::from(["a@example.com".to_string()])));
+
+        let mut item = ImportItem::new(incoming, Some(existing));
+        assert!(item.is_conflict());
+        assert!(item.mods().is_empty());
+        item.resolution = item.resolution.next();
+        assert_eq!(item.resolution, ConflictResolution::Overwrite);
+        assert_eq!(item.mods().len(), 2);
+    }
+
+    #[test]
+    fn test_tree_order() {
+        let mut entries = vec![
+            test_entry("uid=bob,ou=people,dc=example,dc=com", &[]),
+            test_entry("ou=groups,dc=example,dc=com", &[]),
+            test_entry("ou=People,dc=example,dc=com", &[]),
+            test_entry("uid=alice,ou=people,dc=example,dc=com", &[]),
+        ];
+        tree_order(&mut entries);
+        let dns: Vec<&str> = entries.iter().map(|e| e.dn.as_str()).collect();
+        assert_eq!(
+            dns,
+            vec![
+                "ou=groups,dc=example,dc=com",
+                "ou=People,dc=example,dc=com",
+                "uid=alice,ou=people,dc=example,dc=com",
+                "uid=bob,ou=people,dc=example,dc=com",
+            ]
+        );
+    }
+}
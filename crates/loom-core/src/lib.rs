@@ -1,19 +1,43 @@
+pub mod attribute_stats;
+pub mod audit;
 pub mod auth;
 pub mod bulk;
+pub mod cache;
+pub mod cli_command;
+pub mod compare;
 pub mod connection;
 pub mod credentials;
 pub mod dn;
+pub mod dry_run;
+pub mod duplicates;
 pub mod entry;
 pub mod error;
 pub mod export;
 pub mod filter;
+pub mod format;
+pub mod groups;
+pub mod humanize;
 pub mod import;
 pub mod modify;
+pub mod monitor;
 pub mod offline;
+pub mod orphans;
+pub mod password_policy;
+pub mod policy;
+pub mod pool;
+pub mod rename;
+pub mod replication;
+pub mod reports;
 pub mod schema;
+pub mod schema_store;
 pub mod search;
 pub mod server_detect;
+pub mod srv;
+pub mod staging;
 pub mod tls;
+pub mod trace;
+pub mod transform;
 pub mod tree;
+pub mod tunnel;
 pub mod util;
 pub mod vault;
@@ -1,11 +1,103 @@
-use std::collections::HashSet;
+use std::collections::{BTreeMap, HashSet};
+use std::time::Instant;
 
-use ldap3::controls::RelaxRules;
+use ldap3::controls::{RawControl, RelaxRules};
 use ldap3::Mod;
-use tracing::{debug, info};
+use tracing::{debug, info, warn};
 
+use crate::audit::{self, AuditOp};
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::staging::ChangeOp;
+use crate::trace::{TraceOp, TraceRecord};
+use crate::util::find_values_ci;
+
+/// LDAP result code returned when a value to delete is not present.
+const RC_NO_SUCH_ATTRIBUTE: u32 = 16;
+/// LDAP result code when the attribute has no equality rule to find a
+/// value to delete with.
+const RC_INAPPROPRIATE_MATCHING: u32 = 18;
+
+/// Result codes servers use to say they can't move an entry that has
+/// children. unwillingToPerform (53) is left out: it is as often a policy
+/// refusal, which a copy would get around.
+const RC_NOT_ALLOWED_ON_NON_LEAF: u32 = 66;
+const RC_AFFECTS_MULTIPLE_DSAS: u32 = 71;
+
+/// How [`LdapConnection::move_entry`] carried out a move.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MoveMethod {
+    /// A single ModifyDN request with newSuperior.
+    Renamed,
+    /// The server couldn't move the subtree, so this many entries were
+    /// re-added under the new parent and the originals deleted. The
+    /// copies have new server-assigned identities (objectGUID, objectSid)
+    /// and lack attributes the server doesn't return, like password hashes.
+    Copied(usize),
+}
+
+/// A value edit rejected because the entry changed on the server since it
+/// was read: the value being replaced is gone.
+#[derive(Debug, Clone)]
+pub struct ModifyConflict {
+    pub dn: String,
+    pub attr: String,
+    /// The value the edit started from.
+    pub base_value: String,
+    /// The value the user wanted to write.
+    pub new_value: String,
+    /// The attribute's values on the server now.
+    pub current_values: Vec<String>,
+}
+
+/// What the result of a Delete+Add value replacement means.
+#[derive(Debug)]
+enum ReplaceOutcome {
+    Saved,
+    /// The old value is gone: someone else changed it.
+    Conflict,
+    /// The attribute has no equality rule, so the old value can't be found.
+    Unmatchable,
+    Failed(CoreError),
+}
+
+fn replace_outcome(dn: &str, rc: u32, text: &str) -> ReplaceOutcome {
+    match rc {
+        0 => ReplaceOutcome::Saved,
+        RC_NO_SUCH_ATTRIBUTE => ReplaceOutcome::Conflict,
+        RC_INAPPROPRIATE_MATCHING => ReplaceOutcome::Unmatchable,
+        _ => ReplaceOutcome::Failed(CoreError::ModifyFailed(format!(
+            "Modify {} failed rc={}: {}",
+            dn, rc, text
+        ))),
+    }
+}
+
+fn conflict(
+    dn: &str,
+    attr: &str,
+    old_value: &str,
+    new_value: &str,
+    current_values: Vec<String>,
+) -> CoreError {
+    CoreError::ModifyConflict(Box::new(ModifyConflict {
+        dn: dn.to_string(),
+        attr: attr.to_string(),
+        base_value: old_value.to_string(),
+        new_value: new_value.to_string(),
+        current_values,
+    }))
+}
+
+/// `current` with `old_value` swapped for `new_value`, or `None` when
+/// `old_value` is no longer among them. Values are compared exactly, as
+/// there's no matching rule to say otherwise.
+fn swap_value(current: &[String], old_value: &str, new_value: &str) -> Option<Vec<String>> {
+    let i = current.iter().position(|v| v == old_value)?;
+    let mut values = current.to_vec();
+    values[i] = new_value.to_string();
+    Some(values)
+}
 
 impl LdapConnection {
     /// Modify an entry's attributes.
@@ -17,6 +109,46 @@ impl LdapConnection {
         dn: &str,
         mods: Vec<Mod<String>>,
     ) -> Result<(), CoreError> {
+        let (rc, text) = self.send_modify(dn, mods).await?;
+        if rc != 0 {
+            return Err(CoreError::ModifyFailed(format!(
+                "Modify {} failed rc={}: {}",
+                dn, rc, text
+            )));
+        }
+
+        info!("Modified entry: {}", dn);
+        Ok(())
+    }
+
+    /// A write request for the trace, noting the Relax Rules control when
+    /// it is sent.
+    fn trace_request(&self, record: TraceRecord) -> TraceRecord {
+        let record = self.trace_proxy(record);
+        if self.settings.relax_rules {
+            record.control("relaxRules")
+        } else {
+            record
+        }
+    }
+
+    /// Controls sent with writes: Relax Rules when enabled.
+    fn write_controls(&self) -> Vec<RawControl> {
+        if self.settings.relax_rules {
+            vec![RelaxRules.into()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    /// Send a modify request and return the server's result code and text.
+    /// While staging, or in dry-run mode, the change is queued or recorded
+    /// and reported as successful.
+    pub(crate) async fn send_modify(
+        &mut self,
+        dn: &str,
+        mods: Vec<Mod<String>>,
+    ) -> Result<(u32, String), CoreError> {
         debug!(
             "modify_entry dn={} relax_rules={}",
             dn, self.settings.relax_rules
@@ -32,46 +164,113 @@ impl LdapConnection {
             }
         }
 
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .modify(dn, mods)
-                .await
-                .map_err(CoreError::Ldap)?
-        } else {
-            self.ldap.modify(dn, mods).await.map_err(CoreError::Ldap)?
-        };
-
-        debug!("modify_entry result rc={} text={}", result.rc, result.text);
+        if self.changes.is_staging() {
+            info!("Staged modify of {}", dn);
+            self.changes.push(ChangeOp::Modify {
+                dn: dn.to_string(),
+                mods,
+            });
+            return Ok((0, String::new()));
+        }
+        if let Some(ref mut script) = self.dry_run {
+            script.record_modify(dn, &mods);
+            info!("Dry run: recorded modify of {}", dn);
+            return Ok((0, String::new()));
+        }
 
-        if result.rc != 0 {
-            return Err(CoreError::ModifyFailed(format!(
-                "Modify {} failed rc={}: {}",
-                dn, result.rc, result.text
-            )));
+        let changed: Vec<String> = mods.iter().map(|m| mod_attr(m).to_string()).collect();
+        let before = self.audit_values(dn, &changed).await;
+        let trace = self.trace_request(TraceRecord::new(TraceOp::Modify, dn).attributes(&changed));
+        let started = Instant::now();
+        let controls = self.write_controls();
+        let result = self.request(controls).modify(dn, mods).await;
+        self.trace.record(trace, started, &result);
+        if self.is_audited() {
+            let after = match &result {
+                Ok(r) if r.rc == 0 => self.audit_values(dn, &changed).await,
+                _ => before.clone(),
+            };
+            self.audit(AuditOp::Modify, dn, None, before, after, &result);
         }
+        let result = result.map_err(CoreError::from)?;
 
-        info!("Modified entry: {}", dn);
-        Ok(())
+        debug!("modify_entry result rc={} text={}", result.rc, result.text);
+        Ok((result.rc, result.text))
     }
 
     /// Replace a single attribute value.
+    ///
+    /// The old value is deleted and the new one added in one modify request,
+    /// so the server rejects the change if `old_value` is no longer present.
+    /// That rejection is returned as `CoreError::ModifyConflict` carrying
+    /// the attribute's current values. An empty `old_value` replaces the
+    /// attribute unconditionally.
+    ///
+    /// Deleting a value needs the attribute's equality rule. When
+    /// `matchable` is false (the schema gives it none) or the server says
+    /// so with inappropriateMatching, the attribute's values are read and
+    /// replaced as a whole instead, `old_value` swapped for the new one;
+    /// a missing `old_value` is still reported as a conflict.
     pub async fn replace_attribute_value(
         &mut self,
         dn: &str,
         attr: &str,
-        _old_value: &str,
+        old_value: &str,
         new_value: &str,
+        matchable: bool,
     ) -> Result<(), CoreError> {
         debug!(
             "replace_attribute_value dn={} attr={} new_value={}",
             dn, attr, new_value
         );
-        let mods = vec![Mod::Replace(
-            attr.to_string(),
-            HashSet::from([new_value.to_string()]),
-        )];
-        self.modify_entry(dn, mods).await
+        if old_value.is_empty() {
+            let mods = vec![Mod::Replace(
+                attr.to_string(),
+                HashSet::from([new_value.to_string()]),
+            )];
+            return self.modify_entry(dn, mods).await;
+        }
+
+        if matchable {
+            let mods = vec![
+                Mod::Delete(attr.to_string(), HashSet::from([old_value.to_string()])),
+                Mod::Add(attr.to_string(), HashSet::from([new_value.to_string()])),
+            ];
+            let (rc, text) = self.send_modify(dn, mods).await?;
+            match replace_outcome(dn, rc, &text) {
+                ReplaceOutcome::Saved => {
+                    info!("Modified entry: {}", dn);
+                    return Ok(());
+                }
+                ReplaceOutcome::Conflict => {
+                    let current_values = self.current_values(dn, attr).await?;
+                    return Err(conflict(dn, attr, old_value, new_value, current_values));
+                }
+                ReplaceOutcome::Unmatchable => {
+                    debug!("{} has no equality rule; replacing its values", attr);
+                }
+                ReplaceOutcome::Failed(e) => return Err(e),
+            }
+        }
+
+        let current_values = self.current_values(dn, attr).await?;
+        match swap_value(&current_values, old_value, new_value) {
+            Some(values) => {
+                let mods = vec![Mod::Replace(attr.to_string(), values.into_iter().collect())];
+                self.modify_entry(dn, mods).await
+            }
+            None => Err(conflict(dn, attr, old_value, new_value, current_values)),
+        }
+    }
+
+    /// The attribute's values on the server now.
+    async fn current_values(&mut self, dn: &str, attr: &str) -> Result<Vec<String>, CoreError> {
+        Ok(match self.search_entry(dn).await? {
+            Some(entry) => find_values_ci(&entry.attributes, attr)
+                .cloned()
+                .unwrap_or_default(),
+            None => Vec::new(),
+        })
     }
 
     /// Add a value to an attribute.
@@ -142,15 +341,38 @@ impl LdapConnection {
             debug!("  attr={} vals={:?}", attr, vals);
         }
 
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .add(dn, attrs)
-                .await
-                .map_err(CoreError::Ldap)?
+        if self.changes.is_staging() {
+            info!("Staged add of {}", dn);
+            self.changes.push(ChangeOp::Add {
+                dn: dn.to_string(),
+                attrs,
+            });
+            return Ok(());
+        }
+        if let Some(ref mut script) = self.dry_run {
+            script.record_add(dn, &attrs);
+            info!("Dry run: recorded add of {}", dn);
+            return Ok(());
+        }
+
+        let trace = self.trace_request(
+            TraceRecord::new(TraceOp::Add, dn).attributes(attrs.iter().map(|(attr, _)| attr)),
+        );
+        let added = if self.is_audited() {
+            audit::values_of(&attrs)
         } else {
-            self.ldap.add(dn, attrs).await.map_err(CoreError::Ldap)?
+            BTreeMap::new()
         };
+        let started = Instant::now();
+        let controls = self.write_controls();
+        let result = self.request(controls).add(dn, attrs).await;
+        self.trace.record(trace, started, &result);
+        let after = match &result {
+            Ok(r) if r.rc == 0 => added,
+            _ => BTreeMap::new(),
+        };
+        self.audit(AuditOp::Add, dn, None, BTreeMap::new(), after, &result);
+        let result = result.map_err(CoreError::from)?;
 
         debug!("add_entry result rc={} text={}", result.rc, result.text);
 
@@ -165,6 +387,204 @@ impl LdapConnection {
         Ok(())
     }
 
+    /// Move an entry under `new_parent`, keeping its RDN, and return its new
+    /// DN. Uses ModifyDN with newSuperior. When `subtree` is set and the
+    /// server says it can't move an entry with children, this fails with
+    /// [`CoreError::SubtreeMoveUnsupported`], unless `copy_fallback` is set:
+    /// then the subtree is copied under the new parent (parents first) and
+    /// the originals deleted (children first). If a copy fails, the copies
+    /// made so far are deleted again and the originals left alone.
+    pub async fn move_entry(
+        &mut self,
+        dn: &str,
+        new_parent: &str,
+        subtree: bool,
+        copy_fallback: bool,
+    ) -> Result<(String, MoveMethod), CoreError> {
+        let rdn = crate::dn::rdn(dn).to_string();
+        let new_dn = format!("{},{}", rdn, new_parent);
+        debug!(
+            "move_entry dn={} new_parent={} subtree={}",
+            dn, new_parent, subtree
+        );
+
+        if self.changes.is_staging() {
+            info!("Staged move of {} to {}", dn, new_dn);
+            self.changes.push(ChangeOp::Move {
+                dn: dn.to_string(),
+                new_parent: new_parent.to_string(),
+                subtree,
+            });
+            return Ok((new_dn, MoveMethod::Renamed));
+        }
+        if let Some(ref mut script) = self.dry_run {
+            script.record_moddn(dn, &rdn, new_parent);
+            info!("Dry run: recorded move of {} to {}", dn, new_dn);
+            return Ok((new_dn, MoveMethod::Renamed));
+        }
+
+        let attrs = rdn_change_attributes(&rdn, &rdn);
+        let before = self.audit_values(dn, &attrs).await;
+        let trace = self.trace_request(TraceRecord::new(TraceOp::ModifyDn, dn));
+        let started = Instant::now();
+        let controls = self.write_controls();
+        let result = self
+            .request(controls)
+            .modifydn(dn, &rdn, true, Some(new_parent))
+            .await;
+        self.trace.record(trace, started, &result);
+        self.audit_moddn(AuditOp::Move, dn, &new_dn, &attrs, before, &result)
+            .await;
+        let result = result.map_err(CoreError::from)?;
+
+        debug!("move_entry result rc={} text={}", result.rc, result.text);
+
+        match result.rc {
+            0 => {
+                info!("Moved entry: {} -> {}", dn, new_dn);
+                Ok((new_dn, MoveMethod::Renamed))
+            }
+            RC_NOT_ALLOWED_ON_NON_LEAF | RC_AFFECTS_MULTIPLE_DSAS if subtree && !copy_fallback => {
+                Err(CoreError::SubtreeMoveUnsupported(format!(
+                    "{} (rc={}: {})",
+                    dn, result.rc, result.text
+                )))
+            }
+            RC_NOT_ALLOWED_ON_NON_LEAF | RC_AFFECTS_MULTIPLE_DSAS if subtree => {
+                info!(
+                    "Server refused to move {} (rc={}), copying the subtree instead",
+                    dn, result.rc
+                );
+                let count = self.copy_subtree_then_delete(dn, &new_dn).await?;
+                Ok((new_dn, MoveMethod::Copied(count)))
+            }
+            rc => Err(CoreError::ModifyFailed(format!(
+                "Move {} failed rc={}: {}",
+                dn, rc, result.text
+            ))),
+        }
+    }
+
+    /// Rename an entry in place to `new_rdn` (ModifyDN, deleting the old
+    /// RDN value) and return its new DN.
+    pub async fn rename_entry(&mut self, dn: &str, new_rdn: &str) -> Result<String, CoreError> {
+        let parent = crate::dn::parent_dn(dn).unwrap_or_default().to_string();
+        let new_dn = if parent.is_empty() {
+            new_rdn.to_string()
+        } else {
+            format!("{},{}", new_rdn, parent)
+        };
+        debug!("rename_entry dn={} new_rdn={}", dn, new_rdn);
+
+        if self.changes.is_staging() {
+            info!("Staged rename of {} to {}", dn, new_dn);
+            self.changes.push(ChangeOp::Rename {
+                dn: dn.to_string(),
+                new_rdn: new_rdn.to_string(),
+            });
+            return Ok(new_dn);
+        }
+        if let Some(ref mut script) = self.dry_run {
+            script.record_moddn(dn, new_rdn, &parent);
+            info!("Dry run: recorded rename of {} to {}", dn, new_dn);
+            return Ok(new_dn);
+        }
+
+        let attrs = rdn_change_attributes(crate::dn::rdn(dn), new_rdn);
+        let before = self.audit_values(dn, &attrs).await;
+        let trace = self.trace_request(TraceRecord::new(TraceOp::ModifyDn, dn));
+        let started = Instant::now();
+        let controls = self.write_controls();
+        let result = self
+            .request(controls)
+            .modifydn(dn, new_rdn, true, None)
+            .await;
+        self.trace.record(trace, started, &result);
+        self.audit_moddn(AuditOp::Rename, dn, &new_dn, &attrs, before, &result)
+            .await;
+        let result = result.map_err(CoreError::from)?;
+
+        debug!("rename_entry result rc={} text={}", result.rc, result.text);
+        if result.rc != 0 {
+            return Err(CoreError::ModifyFailed(format!(
+                "Rename {} failed rc={}: {}",
+                dn, result.rc, result.text
+            )));
+        }
+
+        info!("Renamed entry: {} -> {}", dn, new_dn);
+        Ok(new_dn)
+    }
+
+    /// Re-add every entry under `dn` beneath `new_dn`, then delete the
+    /// originals. Returns the number of entries moved. A failed copy is
+    /// rolled back; a failed delete leaves both trees, and says so.
+    async fn copy_subtree_then_delete(
+        &mut self,
+        dn: &str,
+        new_dn: &str,
+    ) -> Result<usize, CoreError> {
+        let mut entries = self.search_subtree(dn, "(objectClass=*)", &["*"]).await?;
+        entries.sort_by_key(|e| crate::dn::depth(&e.dn));
+
+        let mut copies: Vec<String> = Vec::new();
+        for entry in &entries {
+            let target = crate::dn::rebase(&entry.dn, dn, new_dn);
+            let attrs = entry
+                .attributes
+                .iter()
+                .map(|(attr, vals)| (attr.clone(), vals.iter().cloned().collect()))
+                .collect();
+            if let Err(e) = self.add_entry(&target, attrs).await {
+                let left = self.delete_copies(&copies).await;
+                let cleanup = if left.is_empty() {
+                    "removed the copies again".to_string()
+                } else {
+                    format!(
+                        "couldn't remove {} of the copies ({})",
+                        left.len(),
+                        left.join("; ")
+                    )
+                };
+                return Err(CoreError::AddFailed(format!(
+                    "copied {} of {} entries, then {}; originals left in place: {}",
+                    copies.len(),
+                    entries.len(),
+                    cleanup,
+                    e
+                )));
+            }
+            copies.push(target);
+        }
+
+        for (deleted, entry) in entries.iter().rev().enumerate() {
+            if let Err(e) = self.delete_entry(&entry.dn).await {
+                return Err(CoreError::DeleteFailed(format!(
+                    "copied all {} entries to {} but deleted only {} of the originals, \
+                     so both trees are left: {}",
+                    entries.len(),
+                    new_dn,
+                    deleted,
+                    e
+                )));
+            }
+        }
+        Ok(entries.len())
+    }
+
+    /// Delete the copies made by an unfinished subtree copy, children
+    /// first, and return the DNs that couldn't be deleted.
+    async fn delete_copies(&mut self, copies: &[String]) -> Vec<String> {
+        let mut left = Vec::new();
+        for dn in copies.iter().rev() {
+            if let Err(e) = self.delete_entry(dn).await {
+                warn!("Couldn't remove the copy {}: {}", dn, e);
+                left.push(dn.clone());
+            }
+        }
+        left
+    }
+
     /// Delete an entry by DN.
     pub async fn delete_entry(&mut self, dn: &str) -> Result<(), CoreError> {
         debug!(
@@ -172,15 +592,29 @@ impl LdapConnection {
             dn, self.settings.relax_rules
         );
 
-        let result = if self.settings.relax_rules {
-            self.ldap
-                .with_controls(vec![RelaxRules.into()])
-                .delete(dn)
-                .await
-                .map_err(CoreError::Ldap)?
-        } else {
-            self.ldap.delete(dn).await.map_err(CoreError::Ldap)?
+        if self.changes.is_staging() {
+            info!("Staged delete of {}", dn);
+            self.changes.push(ChangeOp::Delete { dn: dn.to_string() });
+            return Ok(());
+        }
+        if let Some(ref mut script) = self.dry_run {
+            script.record_delete(dn);
+            info!("Dry run: recorded delete of {}", dn);
+            return Ok(());
+        }
+
+        let before = self.audit_values(dn, &[]).await;
+        let trace = self.trace_request(TraceRecord::new(TraceOp::Delete, dn));
+        let started = Instant::now();
+        let controls = self.write_controls();
+        let result = self.request(controls).delete(dn).await;
+        self.trace.record(trace, started, &result);
+        let after = match &result {
+            Ok(r) if r.rc == 0 => BTreeMap::new(),
+            _ => before.clone(),
         };
+        self.audit(AuditOp::Delete, dn, None, before, after, &result);
+        let result = result.map_err(CoreError::from)?;
 
         debug!("delete_entry result rc={} text={}", result.rc, result.text);
 
@@ -194,4 +628,100 @@ impl LdapConnection {
         info!("Deleted entry: {}", dn);
         Ok(())
     }
+
+    /// Audit a ModifyDN: the old and new DN, with the RDN attributes'
+    /// values before and, read from the new DN, after it, since
+    /// deleteoldrdn removes the old RDN value and adds the new one.
+    async fn audit_moddn(
+        &mut self,
+        operation: AuditOp,
+        dn: &str,
+        new_dn: &str,
+        attrs: &[String],
+        before: BTreeMap<String, Vec<String>>,
+        result: &Result<ldap3::LdapResult, ldap3::LdapError>,
+    ) {
+        if !self.is_audited() {
+            return;
+        }
+        let after = match result {
+            Ok(r) if r.rc == 0 => self.audit_values(new_dn, attrs).await,
+            _ => before.clone(),
+        };
+        self.audit(operation, dn, Some(new_dn), before, after, result);
+    }
+}
+
+/// The attributes of the old and new RDN, each named once.
+fn rdn_change_attributes(old_rdn: &str, new_rdn: &str) -> Vec<String> {
+    let mut attrs: Vec<String> = Vec::new();
+    for attr in crate::dn::rdn_attributes(old_rdn)
+        .into_iter()
+        .chain(crate::dn::rdn_attributes(new_rdn))
+    {
+        if !attrs.iter().any(|a| a.eq_ignore_ascii_case(&attr)) {
+            attrs.push(attr);
+        }
+    }
+    attrs
+}
+
+/// The attribute a modification changes.
+fn mod_attr(m: &Mod<String>) -> &str {
+    match m {
+        Mod::Add(attr, _)
+        | Mod::Delete(attr, _)
+        | Mod::Replace(attr, _)
+        | Mod::Increment(attr, _) => attr,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_value_replacement_outcomes() {
+        let dn = "cn=a,dc=x";
+        assert!(matches!(replace_outcome(dn, 0, ""), ReplaceOutcome::Saved));
+        assert!(matches!(
+            replace_outcome(dn, RC_NO_SUCH_ATTRIBUTE, "no such value"),
+            ReplaceOutcome::Conflict
+        ));
+        assert!(matches!(
+            replace_outcome(dn, RC_INAPPROPRIATE_MATCHING, "no equality rule"),
+            ReplaceOutcome::Unmatchable
+        ));
+        assert!(matches!(
+            replace_outcome(dn, 50, "insufficient access"),
+            ReplaceOutcome::Failed(CoreError::ModifyFailed(msg)) if msg.contains("rc=50")
+        ));
+
+        let current = vec!["one".to_string(), "Two".to_string()];
+        match conflict(dn, "description", "two", "2", current.clone()) {
+            CoreError::ModifyConflict(c) => {
+                assert_eq!(c.attr, "description");
+                assert_eq!(c.base_value, "two");
+                assert_eq!(c.new_value, "2");
+                assert_eq!(c.current_values, current);
+            }
+            other => panic!("expected a conflict, got {:?}", other),
+        }
+
+        // Without a matching rule, values compare exactly
+        assert_eq!(
+            swap_value(&current, "Two", "2"),
+            Some(vec!["one".into(), "2".into()])
+        );
+        assert_eq!(swap_value(&current, "two", "2"), None);
+    }
+
+    #[test]
+    fn test_rdn_change_attributes() {
+        assert_eq!(rdn_change_attributes("cn=alice", "cn=bob"), ["cn"]);
+        assert_eq!(
+            rdn_change_attributes("cn=alice", "UID=alice+CN=alice"),
+            ["cn", "UID"]
+        );
+    }
 }
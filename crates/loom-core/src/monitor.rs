@@ -0,0 +1,413 @@
+//! Server activity figures for the metrics view. OpenLDAP and 389
+//! Directory publish counters under `cn=Monitor`; Active Directory only
+//! offers a few RootDSE attributes, of which `highestCommittedUSN` serves
+//! as a change counter.
+
+use std::time::Duration;
+
+use tracing::debug;
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::util::find_values_ci;
+
+/// Attributes read from the `cn=Monitor` subtree. OpenLDAP's counters are
+/// operational, so they are named rather than left to `*`.
+const MONITOR_ATTRS: &[&str] = &[
+    "monitorCounter",
+    "monitorOpCompleted",
+    "monitoredInfo",
+    "currentConnections",
+    "totalConnections",
+    "opsCompleted",
+    "threads",
+    "version",
+    "startTime",
+];
+
+/// RootDSE attributes Active Directory reports its state in.
+const AD_ATTRS: &[&str] = &[
+    "highestCommittedUSN",
+    "isSynchronized",
+    "isGlobalCatalogReady",
+    "dsServiceName",
+    "currentTime",
+];
+
+/// Naming contexts checked for a replication `contextCSN`.
+const MAX_CONTEXTS: usize = 4;
+
+/// A snapshot of a server's activity counters. Counters are totals since
+/// the server started; rates come from comparing two snapshots.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ServerMetrics {
+    /// Where the figures came from: `cn=Monitor` or `RootDSE`.
+    pub source: String,
+    pub current_connections: Option<u64>,
+    pub total_connections: Option<u64>,
+    pub operations_completed: Option<u64>,
+    /// Completed count per operation type, e.g. `Search`.
+    pub operations: Vec<(String, u64)>,
+    /// A counter that grows with each committed change.
+    pub changes: Option<u64>,
+    /// Replication state, e.g. the last change time per server ID.
+    pub replication: Vec<(String, String)>,
+    /// Other figures such as the version, uptime and threads.
+    pub info: Vec<(String, String)>,
+}
+
+impl ServerMetrics {
+    /// Read the `cn=Monitor` entries of OpenLDAP or 389 Directory.
+    pub fn from_monitor(entries: &[LdapEntry]) -> Self {
+        let mut metrics = ServerMetrics {
+            source: "cn=Monitor".to_string(),
+            ..Default::default()
+        };
+        for entry in entries {
+            let dn = entry.dn.to_ascii_lowercase().replace(", ", ",");
+            let counter = |attr: &str| first(entry, attr).and_then(|v| v.parse().ok());
+            match dn.as_str() {
+                // OpenLDAP
+                "cn=current,cn=connections,cn=monitor" => {
+                    metrics.current_connections = counter("monitorCounter");
+                }
+                "cn=total,cn=connections,cn=monitor" => {
+                    metrics.total_connections = counter("monitorCounter");
+                }
+                "cn=operations,cn=monitor" => {
+                    metrics.operations_completed = counter("monitorOpCompleted");
+                }
+                "cn=uptime,cn=time,cn=monitor" => {
+                    if let Some(secs) = counter("monitoredInfo") {
+                        metrics
+                            .info
+                            .push(("Uptime".to_string(), format_uptime(secs)));
+                    }
+                }
+                "cn=active,cn=threads,cn=monitor" => {
+                    if let Some(threads) = first(entry, "monitoredInfo") {
+                        metrics
+                            .info
+                            .push(("Active threads".to_string(), threads.to_string()));
+                    }
+                }
+                "cn=monitor" => {
+                    // 389 Directory keeps its counters on the base entry
+                    metrics.current_connections = metrics
+                        .current_connections
+                        .or_else(|| counter("currentConnections"));
+                    metrics.total_connections = metrics
+                        .total_connections
+                        .or_else(|| counter("totalConnections"));
+                    metrics.operations_completed = metrics
+                        .operations_completed
+                        .or_else(|| counter("opsCompleted"));
+                    let version = first(entry, "monitoredInfo").or_else(|| first(entry, "version"));
+                    if let Some(version) = version {
+                        metrics
+                            .info
+                            .insert(0, ("Version".to_string(), version.to_string()));
+                    }
+                    if let Some(threads) = first(entry, "threads") {
+                        metrics
+                            .info
+                            .push(("Threads".to_string(), threads.to_string()));
+                    }
+                    if let Some(started) = first(entry, "startTime") {
+                        metrics
+                            .info
+                            .push(("Started".to_string(), format_generalized_time(started)));
+                    }
+                }
+                _ => {
+                    if let Some(op) = dn
+                        .strip_suffix(",cn=operations,cn=monitor")
+                        .and_then(|rdn| rdn.strip_prefix("cn="))
+                    {
+                        if let Some(completed) = counter("monitorOpCompleted") {
+                            // Keep the server's spelling, e.g. "Search"
+                            let name = entry.rdn().split_once('=').map_or(op, |(_, v)| v);
+                            metrics.operations.push((name.to_string(), completed));
+                        }
+                    }
+                }
+            }
+        }
+        metrics
+    }
+
+    /// Read Active Directory's RootDSE.
+    pub fn from_active_directory(root_dse: &LdapEntry) -> Self {
+        let mut metrics = ServerMetrics {
+            source: "RootDSE".to_string(),
+            changes: first(root_dse, "highestCommittedUSN").and_then(|v| v.parse().ok()),
+            ..Default::default()
+        };
+        if let Some(synced) = first(root_dse, "isSynchronized") {
+            metrics
+                .replication
+                .push(("Synchronized".to_string(), synced.to_string()));
+        }
+        if let Some(ready) = first(root_dse, "isGlobalCatalogReady") {
+            metrics
+                .replication
+                .push(("Global catalog ready".to_string(), ready.to_string()));
+        }
+        if let Some(service) = first(root_dse, "dsServiceName") {
+            // CN=NTDS Settings,CN=DC01,CN=Servers,... names the DC
+            let server = service.split(',').nth(1).unwrap_or(service);
+            let name = server.split_once('=').map_or(server, |(_, v)| v);
+            metrics
+                .info
+                .push(("Domain controller".to_string(), name.to_string()));
+        }
+        if let Some(time) = first(root_dse, "currentTime") {
+            metrics
+                .info
+                .push(("Server time".to_string(), format_generalized_time(time)));
+        }
+        metrics
+    }
+
+    /// Add each server's last change time from a naming context's
+    /// `contextCSN` values.
+    pub fn add_context_csns(&mut self, context: &str, csns: &[String]) {
+        for csn in csns {
+            if let Some((sid, time)) = parse_csn(csn) {
+                self.replication
+                    .push((format!("{} (SID {})", context, sid), time));
+            }
+        }
+    }
+
+    /// Whether the server reported anything worth showing.
+    pub fn is_empty(&self) -> bool {
+        self.current_connections.is_none()
+            && self.total_connections.is_none()
+            && self.operations_completed.is_none()
+            && self.operations.is_empty()
+            && self.changes.is_none()
+            && self.replication.is_empty()
+    }
+}
+
+/// The first value of `attr`, whatever case the server spelled it in.
+fn first<'a>(entry: &'a LdapEntry, attr: &str) -> Option<&'a str> {
+    find_values_ci(&entry.attributes, attr)
+        .and_then(|values| values.first())
+        .map(String::as_str)
+}
+
+/// Per-second rate of a counter between two samples `elapsed` apart.
+/// `None` when either sample is missing or the counter went backwards,
+/// as after a server restart.
+pub fn rate(previous: Option<u64>, current: Option<u64>, elapsed: Duration) -> Option<f64> {
+    let delta = current?.checked_sub(previous?)?;
+    let secs = elapsed.as_secs_f64();
+    (secs > 0.0).then(|| delta as f64 / secs)
+}
+
+/// The server ID and change time of a CSN such as
+/// `20240315103000.123456Z#000000#001#000000`.
+fn parse_csn(csn: &str) -> Option<(String, String)> {
+    let mut parts = csn.split('#');
+    let time = parts.next()?;
+    let sid = parts.nth(1)?;
+    Some((sid.to_string(), format_generalized_time(time)))
+}
+
+/// `20240315103000.123456Z` as `2024-03-15 10:30:00 UTC`; other values are
+/// returned as they are.
+fn format_generalized_time(value: &str) -> String {
+    let digits = value
+        .get(..14)
+        .filter(|d| d.bytes().all(|b| b.is_ascii_digit()));
+    match digits {
+        Some(d) => format!(
+            "{}-{}-{} {}:{}:{} UTC",
+            &d[..4],
+            &d[4..6],
+            &d[6..8],
+            &d[8..10],
+            &d[10..12],
+            &d[12..14]
+        ),
+        None => value.to_string(),
+    }
+}
+
+/// "3d 04h 05m", "4h 05m" or "5m".
+fn format_uptime(secs: u64) -> String {
+    let (days, hours, minutes) = (secs / 86400, secs % 86400 / 3600, secs % 3600 / 60);
+    match (days, hours) {
+        (0, 0) => format!("{}m", minutes),
+        (0, _) => format!("{}h {:02}m", hours, minutes),
+        _ => format!("{}d {:02}h {:02}m", days, hours, minutes),
+    }
+}
+
+impl LdapConnection {
+    /// Read the server's activity counters: `cn=Monitor` where the server
+    /// has one, otherwise Active Directory's RootDSE. Replication times are
+    /// added from the naming contexts' `contextCSN`.
+    pub async fn server_metrics(&mut self) -> Result<ServerMetrics, CoreError> {
+        let mut metrics = match self
+            .search_subtree("cn=Monitor", "(objectClass=*)", MONITOR_ATTRS)
+            .await
+        {
+            Ok(entries) => ServerMetrics::from_monitor(&entries),
+            Err(e) => {
+                debug!("server_metrics: cn=Monitor not readable: {}", e);
+                ServerMetrics::default()
+            }
+        };
+
+        let mut root_attrs = AD_ATTRS.to_vec();
+        root_attrs.push("namingContexts");
+        let root_dse = self.search_entry_attrs("", &root_attrs).await?;
+        if let Some(root_dse) = root_dse {
+            if metrics.is_empty() && first(&root_dse, "highestCommittedUSN").is_some() {
+                return Ok(ServerMetrics::from_active_directory(&root_dse));
+            }
+            let contexts = find_values_ci(&root_dse.attributes, "namingContexts")
+                .cloned()
+                .unwrap_or_default();
+            for context in contexts.iter().take(MAX_CONTEXTS) {
+                if let Ok(Some(entry)) = self.search_entry_attrs(context, &["contextCSN"]).await {
+                    let csns = find_values_ci(&entry.attributes, "contextCSN")
+                        .cloned()
+                        .unwrap_or_default();
+                    metrics.add_context_csns(context, &csns);
+                }
+            }
+        }
+
+        if metrics.is_empty() {
+            return Err(CoreError::SearchFailed(
+                "the server publishes no metrics (cn=Monitor is missing or not readable)"
+                    .to_string(),
+            ));
+        }
+        if metrics.source.is_empty() {
+            metrics.source = "contextCSN".to_string();
+        }
+        Ok(metrics)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_openldap_monitor() {
+        let entries = [
+            test_entry(
+                "cn=Monitor",
+                &[("monitoredInfo", &["OpenLDAP: slapd 2.6.7"])],
+            ),
+            test_entry(
+                "cn=Current,cn=Connections,cn=Monitor",
+                &[("monitorCounter", &["12"])],
+            ),
+            test_entry(
+                "cn=Total,cn=Connections,cn=Monitor",
+                &[("monitorCounter", &["3456"])],
+            ),
+            test_entry(
+                "cn=Operations,cn=Monitor",
+                &[("monitorOpCompleted", &["900"])],
+            ),
+            test_entry(
+                "cn=Search,cn=Operations,cn=Monitor",
+                &[("monitorOpCompleted", &["800"])],
+            ),
+            test_entry(
+                "cn=Uptime,cn=Time,cn=Monitor",
+                &[("monitoredInfo", &["273900"])],
+            ),
+        ];
+        let metrics = ServerMetrics::from_monitor(&entries);
+        assert_eq!(metrics.current_connections, Some(12));
+        assert_eq!(metrics.total_connections, Some(3456));
+        assert_eq!(metrics.operations_completed, Some(900));
+        assert_eq!(metrics.operations, [("Search".to_string(), 800)]);
+        assert_eq!(
+            metrics.info,
+            [
+                ("Version".to_string(), "OpenLDAP: slapd 2.6.7".to_string()),
+                ("Uptime".to_string(), "3d 04h 05m".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_389_monitor_and_csn() {
+        let entries = [test_entry(
+            "cn=monitor",
+            &[
+                ("currentconnections", &["3"]),
+                ("opscompleted", &["120"]),
+                ("starttime", &["20240315103000Z"]),
+            ],
+        )];
+        let mut metrics = ServerMetrics::from_monitor(&entries);
+        assert_eq!(metrics.current_connections, Some(3));
+        assert_eq!(metrics.operations_completed, Some(120));
+        assert_eq!(
+            metrics.info,
+            [("Started".to_string(), "2024-03-15 10:30:00 UTC".to_string())]
+        );
+
+        metrics.add_context_csns(
+            "dc=example,dc=com",
+            &["20240315103000.123456Z#000000#001#000000".to_string()],
+        );
+        assert_eq!(
+            metrics.replication,
+            [(
+                "dc=example,dc=com (SID 001)".to_string(),
+                "2024-03-15 10:30:00 UTC".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_active_directory() {
+        let root_dse = test_entry(
+            "",
+            &[
+                ("highestCommittedUSN", &["51234"]),
+                ("isSynchronized", &["TRUE"]),
+                (
+                    "dsServiceName",
+                    &["CN=NTDS Settings,CN=DC01,CN=Servers,CN=Site,CN=Sites,CN=Configuration,DC=corp,DC=com"],
+                ),
+            ],
+        );
+        let metrics = ServerMetrics::from_active_directory(&root_dse);
+        assert_eq!(metrics.changes, Some(51234));
+        assert_eq!(
+            metrics.replication,
+            [("Synchronized".to_string(), "TRUE".to_string())]
+        );
+        assert_eq!(
+            metrics.info,
+            [("Domain controller".to_string(), "DC01".to_string())]
+        );
+        assert!(!metrics.is_empty());
+        assert!(ServerMetrics::default().is_empty());
+    }
+
+    #[test]
+    fn test_rate() {
+        let secs = Duration::from_secs(5);
+        assert_eq!(rate(Some(100), Some(150), secs), Some(10.0));
+        assert_eq!(rate(None, Some(150), secs), None);
+        // A restarted server's counters start over
+        assert_eq!(rate(Some(150), Some(10), secs), None);
+        assert_eq!(format_uptime(300), "5m");
+    }
+}
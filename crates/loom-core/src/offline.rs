@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use crate::dn;
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
@@ -5,7 +7,7 @@ use crate::import::ldif;
 use crate::schema::{
     AttributeSyntax, AttributeTypeInfo, ObjectClassInfo, ObjectClassKind, SchemaCache,
 };
-use crate::tree::TreeNode;
+use crate::tree::{ChildCount, TreeNode};
 
 const EXAMPLE_LDIF: &str = include_str!("../../../assets/example-directory.ldif");
 
@@ -47,6 +49,12 @@ impl OfflineDirectory {
     /// Return immediate children of the given parent DN.
     pub fn children(&self, parent_dn: &str) -> Vec<TreeNode> {
         let parent_lower = parent_dn.to_lowercase();
+        let mut counts: HashMap<String, usize> = HashMap::new();
+        for e in &self.entries {
+            if let Some(p) = dn::parent_dn(&e.dn) {
+                *counts.entry(p.to_lowercase()).or_default() += 1;
+            }
+        }
         self.entries
             .iter()
             .filter(|e| {
@@ -54,7 +62,10 @@ impl OfflineDirectory {
                     .map(|p| p.to_lowercase() == parent_lower)
                     .unwrap_or(false)
             })
-            .map(|e| TreeNode::new(e.dn.clone()))
+            .map(|e| {
+                let count = counts.get(&e.dn.to_lowercase()).copied().unwrap_or(0);
+                TreeNode::new(e.dn.clone()).with_child_count(Some(ChildCount::Exact(count)))
+            })
             .collect()
     }
 
@@ -339,6 +350,12 @@ fn build_example_schema() -> SchemaCache {
             names: vec![name.to_string()],
             description: None,
             syntax,
+            syntax_oid: None,
+            syntax_length: None,
+            superior: None,
+            equality: None,
+            ordering: None,
+            substr: None,
             single_value,
             no_user_modification,
         };
@@ -491,6 +508,13 @@ mod tests {
         let it_children = dir.children("ou=IT,ou=Corporate,dc=contoso,dc=com");
         // Should have: Infrastructure, Development, Security, Service Accounts
         assert_eq!(it_children.len(), 4, "IT should have 4 sub-OUs");
+
+        let corporate = dir.children("ou=Corporate,dc=contoso,dc=com");
+        let it = corporate
+            .iter()
+            .find(|n| n.dn == "ou=IT,ou=Corporate,dc=contoso,dc=com")
+            .unwrap();
+        assert_eq!(it.child_count, Some(ChildCount::Exact(4)));
     }
 
     #[test]
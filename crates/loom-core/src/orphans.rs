@@ -0,0 +1,313 @@
+//! Orphaned references: DN-valued attributes such as `manager` or `member`
+//! that point at entries which no longer exist.
+//!
+//! Every referenced DN is looked up once; DNs of the scanned entries
+//! themselves are known to exist. A lookup that fails for another reason
+//! than noSuchObject (a referral, no access) counts as existing, so only
+//! values that are certainly dangling are reported or removed.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::Write;
+
+use ldap3::Mod;
+
+use crate::connection::LdapConnection;
+use crate::dn::looks_like_dn;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::groups::strip_unique_id;
+use crate::util::find_values_ci;
+
+/// The DN-valued attributes scanned by default.
+pub const REFERENCE_ATTRIBUTES: &[&str] = &[
+    "manager",
+    "secretary",
+    "member",
+    "uniqueMember",
+    "owner",
+    "seeAlso",
+];
+
+/// One DN value of an entry.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Reference {
+    /// The entry holding the value.
+    pub dn: String,
+    pub attribute: String,
+    /// The value as stored, which is what a cleanup removes.
+    pub value: String,
+}
+
+impl Reference {
+    /// The DN the value points at.
+    pub fn target(&self) -> &str {
+        strip_unique_id(&self.value)
+    }
+}
+
+/// The outcome of scanning a subtree.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct OrphanReport {
+    pub base_dn: String,
+    /// Entries holding at least one of the attributes.
+    pub entries_scanned: usize,
+    /// Distinct referenced DNs looked up.
+    pub targets_checked: usize,
+    pub orphans: Vec<Reference>,
+}
+
+impl OrphanReport {
+    /// Write the orphans as CSV: entry, attribute, missing DN.
+    pub fn write_csv<W: Write>(&self, writer: W) -> Result<usize, CoreError> {
+        let err = |e: csv::Error| CoreError::ExportError(e.to_string());
+        let mut csv = csv::Writer::from_writer(writer);
+        csv.write_record(["dn", "attribute", "missing"])
+            .map_err(err)?;
+        for orphan in &self.orphans {
+            csv.write_record([&orphan.dn, &orphan.attribute, orphan.target()])
+                .map_err(err)?;
+        }
+        csv.flush()
+            .map_err(|e| CoreError::ExportError(e.to_string()))?;
+        Ok(self.orphans.len())
+    }
+}
+
+/// The result of removing dangling values.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CleanupResult {
+    pub removed: usize,
+    /// Values left alone because their target exists again.
+    pub skipped: usize,
+    pub errors: Vec<(String, String)>,
+}
+
+/// Compare DNs ignoring case and the spaces allowed around `,` and `=`.
+fn dn_key(dn: &str) -> String {
+    dn.split(',')
+        .map(|rdn| rdn.split('=').map(str::trim).collect::<Vec<_>>().join("="))
+        .collect::<Vec<_>>()
+        .join(",")
+        .to_lowercase()
+}
+
+/// The filter matching entries with any of `attributes`.
+pub fn reference_filter(attributes: &[&str]) -> String {
+    let terms: String = attributes.iter().map(|a| format!("({}=*)", a)).collect();
+    format!("(|{})", terms)
+}
+
+/// Every DN value of `attributes` in `entries`.
+pub fn references(entries: &[LdapEntry], attributes: &[&str]) -> Vec<Reference> {
+    let mut refs = Vec::new();
+    for entry in entries {
+        for attr in attributes {
+            for value in find_values_ci(&entry.attributes, attr)
+                .into_iter()
+                .flatten()
+            {
+                if looks_like_dn(strip_unique_id(value)) {
+                    refs.push(Reference {
+                        dn: entry.dn.clone(),
+                        attribute: attr.to_string(),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+    }
+    refs
+}
+
+/// The distinct DNs `refs` point at, other than the scanned `entries`.
+pub fn unknown_targets(refs: &[Reference], entries: &[LdapEntry]) -> Vec<String> {
+    let mut known: HashSet<String> = entries.iter().map(|e| dn_key(&e.dn)).collect();
+    refs.iter()
+        .filter(|r| known.insert(dn_key(r.target())))
+        .map(|r| r.target().to_string())
+        .collect()
+}
+
+/// The references whose target is among `missing`, sorted by entry.
+pub fn orphans(refs: Vec<Reference>, missing: &[String]) -> Vec<Reference> {
+    let missing: HashSet<String> = missing.iter().map(|dn| dn_key(dn)).collect();
+    let mut orphans: Vec<Reference> = refs
+        .into_iter()
+        .filter(|r| missing.contains(&dn_key(r.target())))
+        .collect();
+    orphans.sort();
+    orphans
+}
+
+/// Scan `entries` for references to DNs that `exists` says are gone.
+pub fn scan(
+    base_dn: &str,
+    entries: &[LdapEntry],
+    attributes: &[&str],
+    mut exists: impl FnMut(&str) -> bool,
+) -> OrphanReport {
+    let refs = references(entries, attributes);
+    let targets = unknown_targets(&refs, entries);
+    let missing: Vec<String> = targets.iter().filter(|dn| !exists(dn)).cloned().collect();
+    report(base_dn, refs, targets.len(), &missing)
+}
+
+fn report(
+    base_dn: &str,
+    refs: Vec<Reference>,
+    targets_checked: usize,
+    missing: &[String],
+) -> OrphanReport {
+    let holders: BTreeSet<&str> = refs.iter().map(|r| r.dn.as_str()).collect();
+    OrphanReport {
+        base_dn: base_dn.to_string(),
+        entries_scanned: holders.len(),
+        targets_checked,
+        orphans: orphans(refs, missing),
+    }
+}
+
+impl LdapConnection {
+    /// Find values of `attributes` under `base_dn` that point at entries
+    /// which don't exist.
+    pub async fn find_orphaned_references(
+        &mut self,
+        base_dn: &str,
+        attributes: &[&str],
+    ) -> Result<OrphanReport, CoreError> {
+        let entries = self
+            .search_subtree(base_dn, &reference_filter(attributes), attributes)
+            .await?;
+        let refs = references(&entries, attributes);
+        let targets = unknown_targets(&refs, &entries);
+        let mut missing = Vec::new();
+        for target in &targets {
+            match self.entry_exists(target).await {
+                Ok(false) => missing.push(target.clone()),
+                Ok(true) => {}
+                Err(e) => tracing::debug!("can't tell whether {} exists: {}", target, e),
+            }
+        }
+        Ok(report(base_dn, refs, targets.len(), &missing))
+    }
+
+    /// Remove the dangling values `refs`, one modify per entry. Each target
+    /// is checked again first, and values whose target exists are kept.
+    pub async fn remove_orphaned_references(&mut self, refs: &[Reference]) -> CleanupResult {
+        let mut result = CleanupResult::default();
+        let mut by_entry: BTreeMap<&str, BTreeMap<&str, HashSet<String>>> = BTreeMap::new();
+        for r in refs {
+            if !matches!(self.entry_exists(r.target()).await, Ok(false)) {
+                result.skipped += 1;
+                continue;
+            }
+            by_entry
+                .entry(&r.dn)
+                .or_default()
+                .entry(&r.attribute)
+                .or_default()
+                .insert(r.value.clone());
+        }
+        for (dn, attrs) in by_entry {
+            let count: usize = attrs.values().map(HashSet::len).sum();
+            let mods = attrs
+                .into_iter()
+                .map(|(attr, values)| Mod::Delete(attr.to_string(), values))
+                .collect();
+            match self.modify_entry(dn, mods).await {
+                Ok(()) => result.removed += count,
+                Err(e) => result.errors.push((dn.to_string(), e.to_string())),
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_scan_finds_dangling_values() {
+        let entries = vec![
+            test_entry(
+                "uid=amy,ou=people,dc=x",
+                &[
+                    ("manager", &["uid=gone,ou=people,dc=x"]),
+                    ("seeAlso", &["not a dn"]),
+                ],
+            ),
+            test_entry(
+                "cn=staff,ou=groups,dc=x",
+                &[
+                    (
+                        "member",
+                        &["UID=Amy, ou=People,dc=x", "uid=gone,ou=people,dc=x"],
+                    ),
+                    ("owner", &["uid=boss,ou=people,dc=x"]),
+                ],
+            ),
+            test_entry(
+                "cn=vpn,ou=groups,dc=x",
+                &[("uniqueMember", &["uid=left,ou=people,dc=x#'0101'B"])],
+            ),
+        ];
+        let mut looked_up = Vec::new();
+        let report = scan("dc=x", &entries, REFERENCE_ATTRIBUTES, |dn| {
+            looked_up.push(dn.to_string());
+            dn.starts_with("uid=boss")
+        });
+
+        // Scanned entries aren't looked up, and each target only once
+        assert_eq!(
+            looked_up,
+            [
+                "uid=gone,ou=people,dc=x",
+                "uid=boss,ou=people,dc=x",
+                "uid=left,ou=people,dc=x"
+            ]
+        );
+        assert_eq!(report.entries_scanned, 3);
+        assert_eq!(report.targets_checked, 3);
+        let found: Vec<(&str, &str, &str)> = report
+            .orphans
+            .iter()
+            .map(|r| (r.dn.as_str(), r.attribute.as_str(), r.value.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                (
+                    "cn=staff,ou=groups,dc=x",
+                    "member",
+                    "uid=gone,ou=people,dc=x"
+                ),
+                (
+                    "cn=vpn,ou=groups,dc=x",
+                    "uniqueMember",
+                    "uid=left,ou=people,dc=x#'0101'B"
+                ),
+                (
+                    "uid=amy,ou=people,dc=x",
+                    "manager",
+                    "uid=gone,ou=people,dc=x"
+                ),
+            ]
+        );
+
+        let mut csv = Vec::new();
+        assert_eq!(report.write_csv(&mut csv).unwrap(), 3);
+        let csv = String::from_utf8(csv).unwrap();
+        assert!(csv.starts_with("dn,attribute,missing\n"));
+        assert!(csv.contains("\"cn=vpn,ou=groups,dc=x\",uniqueMember,\"uid=left,ou=people,dc=x\""));
+    }
+
+    #[test]
+    fn test_reference_filter() {
+        assert_eq!(
+            reference_filter(&["manager", "member"]),
+            "(|(manager=*)(member=*))"
+        );
+    }
+}
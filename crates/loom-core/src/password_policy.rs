@@ -0,0 +1,570 @@
+//! The password policy in effect for a user and the state of their
+//! password. Active Directory applies the fine-grained policy named by the
+//! constructed `msDS-ResultantPSO`, else the domain's own settings;
+//! OpenLDAP's ppolicy overlay applies the user's `pwdPolicySubentry`, else
+//! the overlay's default policy.
+
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::format::ValueFormat;
+use crate::util::find_values_ci;
+
+/// User attributes read on Active Directory; the `msDS-` ones are
+/// constructed and only returned when asked for.
+const AD_USER_ATTRS: &[&str] = &[
+    "userAccountControl",
+    "pwdLastSet",
+    "lockoutTime",
+    "badPwdCount",
+    "msDS-ResultantPSO",
+    "msDS-UserPasswordExpiryTimeComputed",
+    "msDS-User-Account-Control-Computed",
+];
+
+/// Operational user attributes kept by the ppolicy overlay.
+const PPOLICY_USER_ATTRS: &[&str] = &[
+    "pwdPolicySubentry",
+    "pwdChangedTime",
+    "pwdAccountLockedTime",
+    "pwdFailureTime",
+    "pwdReset",
+];
+
+/// Settings shown for Active Directory: label, attribute on a password
+/// settings object, attribute on the domain head, and format.
+const AD_SETTINGS: &[(&str, &str, &str, Option<ValueFormat>)] = &[
+    (
+        "Minimum length",
+        "msDS-MinimumPasswordLength",
+        "minPwdLength",
+        None,
+    ),
+    (
+        "History length",
+        "msDS-PasswordHistoryLength",
+        "pwdHistoryLength",
+        None,
+    ),
+    (
+        "Maximum age",
+        "msDS-MaximumPasswordAge",
+        "maxPwdAge",
+        Some(ValueFormat::Duration),
+    ),
+    (
+        "Minimum age",
+        "msDS-MinimumPasswordAge",
+        "minPwdAge",
+        Some(ValueFormat::Duration),
+    ),
+    (
+        "Lockout threshold",
+        "msDS-LockoutThreshold",
+        "lockoutThreshold",
+        None,
+    ),
+    (
+        "Lockout duration",
+        "msDS-LockoutDuration",
+        "lockoutDuration",
+        Some(ValueFormat::Duration),
+    ),
+    (
+        "Lockout window",
+        "msDS-LockoutObservationWindow",
+        "lockOutObservationWindow",
+        Some(ValueFormat::Duration),
+    ),
+];
+
+/// Settings shown for a ppolicy policy entry.
+const PPOLICY_SETTINGS: &[(&str, &str, Option<ValueFormat>)] = &[
+    ("Minimum length", "pwdMinLength", None),
+    ("History length", "pwdInHistory", None),
+    ("Maximum age", "pwdMaxAge", Some(ValueFormat::Duration)),
+    ("Minimum age", "pwdMinAge", Some(ValueFormat::Duration)),
+    ("Quality check", "pwdCheckQuality", None),
+    ("Lockout", "pwdLockout", None),
+    ("Lockout threshold", "pwdMaxFailure", None),
+    (
+        "Lockout duration",
+        "pwdLockoutDuration",
+        Some(ValueFormat::Duration),
+    ),
+    (
+        "Failure window",
+        "pwdFailureCountInterval",
+        Some(ValueFormat::Duration),
+    ),
+    ("Must change after reset", "pwdMustChange", None),
+    (
+        "Expiry warning",
+        "pwdExpireWarning",
+        Some(ValueFormat::Duration),
+    ),
+    ("Grace logins", "pwdGraceAuthNLimit", None),
+];
+
+/// `userAccountControl` flags.
+const UF_LOCKOUT: u32 = 0x10;
+const UF_DONT_EXPIRE_PASSWD: u32 = 0x1_0000;
+
+/// `pwdAccountLockedTime` of an account locked by an administrator.
+const PPOLICY_ADMIN_LOCK: &str = "000001010000Z";
+
+/// When the user's password expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Expiry {
+    Never,
+    /// The password was reset and must be changed at next logon.
+    MustChange,
+    At(DateTime<Utc>),
+    Unknown,
+}
+
+/// Whether the user is locked out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Lockout {
+    Unlocked,
+    Until(DateTime<Utc>),
+    /// Until an administrator unlocks the account.
+    UntilReset,
+}
+
+/// A user's effective password policy and password state.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPolicyReport {
+    pub dn: String,
+    /// Which policy applies, e.g. `Fine-grained policy` or `Domain policy`.
+    pub source: String,
+    /// The entry holding the policy, if any.
+    pub policy_dn: Option<String>,
+    pub password_changed: Option<DateTime<Utc>>,
+    pub expiry: Expiry,
+    pub lockout: Lockout,
+    pub failed_logins: Option<u32>,
+    pub history_length: Option<u32>,
+    /// The policy's settings as label and readable value.
+    pub settings: Vec<(String, String)>,
+}
+
+impl PasswordPolicyReport {
+    /// Judge an Active Directory user against `policy`: a password settings
+    /// object when `fine_grained`, otherwise the domain head.
+    pub fn from_active_directory(
+        user: &LdapEntry,
+        policy: &LdapEntry,
+        fine_grained: bool,
+        now: DateTime<Utc>,
+    ) -> Self {
+        let setting =
+            |pso: &str, domain: &str| first(policy, if fine_grained { pso } else { domain });
+        let mut settings: Vec<(String, String)> = AD_SETTINGS
+            .iter()
+            .filter_map(|(label, pso, domain, format)| {
+                let value = setting(pso, domain)?;
+                Some((label.to_string(), display(value, *format)))
+            })
+            .collect();
+        let complexity = if fine_grained {
+            first(policy, "msDS-PasswordComplexityEnabled").map(str::to_string)
+        } else {
+            // DOMAIN_PASSWORD_COMPLEX is bit 0 of pwdProperties
+            number(policy, "pwdProperties").map(|p| (p & 1 == 1).to_string().to_uppercase())
+        };
+        if let Some(complexity) = complexity {
+            settings.push(("Complexity required".to_string(), complexity));
+        }
+        if let Some(precedence) = first(policy, "msDS-PasswordSettingsPrecedence") {
+            settings.push(("Precedence".to_string(), precedence.to_string()));
+        }
+
+        let uac = number(user, "userAccountControl").unwrap_or_default() as u32;
+        let password_changed = first(user, "pwdLastSet").and_then(filetime);
+        let max_age = setting("msDS-MaximumPasswordAge", "maxPwdAge").and_then(interval);
+        let expiry = if uac & UF_DONT_EXPIRE_PASSWD != 0 {
+            Expiry::Never
+        } else if first(user, "pwdLastSet") == Some("0") {
+            Expiry::MustChange
+        } else if let Some(computed) = first(user, "msDS-UserPasswordExpiryTimeComputed") {
+            // Already accounts for the resultant policy
+            match filetime(computed) {
+                Some(at) => Expiry::At(at),
+                None => Expiry::Never,
+            }
+        } else {
+            expires(password_changed, max_age)
+        };
+
+        let locked_at = first(user, "lockoutTime").and_then(filetime);
+        let computed = number(user, "msDS-User-Account-Control-Computed");
+        let lockout = match locked_at {
+            // The computed flag is authoritative where the server sends it
+            _ if computed.is_some_and(|c| c as u32 & UF_LOCKOUT == 0) => Lockout::Unlocked,
+            Some(at) => {
+                let duration = setting("msDS-LockoutDuration", "lockoutDuration")
+                    .and_then(interval)
+                    .unwrap_or_default();
+                locked_until(at, duration, now)
+            }
+            None => Lockout::Unlocked,
+        };
+
+        Self {
+            dn: user.dn.clone(),
+            source: if fine_grained {
+                "Fine-grained policy".to_string()
+            } else {
+                "Domain policy".to_string()
+            },
+            policy_dn: Some(policy.dn.clone()),
+            password_changed,
+            expiry,
+            lockout,
+            failed_logins: number(user, "badPwdCount").map(|n| n as u32),
+            history_length: setting("msDS-PasswordHistoryLength", "pwdHistoryLength")
+                .and_then(|v| v.parse().ok()),
+            settings,
+        }
+    }
+
+    /// Judge a user of OpenLDAP's ppolicy overlay against `policy`, if any
+    /// applies.
+    pub fn from_ppolicy(user: &LdapEntry, policy: Option<&LdapEntry>, now: DateTime<Utc>) -> Self {
+        let setting = |attr: &str| policy.and_then(|p| first(p, attr));
+        let settings = PPOLICY_SETTINGS
+            .iter()
+            .filter_map(|(label, attr, format)| {
+                let value = setting(attr)?;
+                Some((label.to_string(), display(value, *format)))
+            })
+            .collect();
+
+        let password_changed = first(user, "pwdChangedTime").and_then(generalized_time);
+        let max_age = setting("pwdMaxAge")
+            .and_then(|v| v.parse().ok())
+            .map(Duration::from_secs);
+        let expiry = if first(user, "pwdReset").is_some_and(|v| v.eq_ignore_ascii_case("TRUE")) {
+            Expiry::MustChange
+        } else if policy.is_none() {
+            Expiry::Unknown
+        } else {
+            expires(password_changed, max_age)
+        };
+
+        let lockout = match first(user, "pwdAccountLockedTime") {
+            None => Lockout::Unlocked,
+            Some(PPOLICY_ADMIN_LOCK) => Lockout::UntilReset,
+            Some(value) => match generalized_time(value) {
+                Some(at) => {
+                    let duration = setting("pwdLockoutDuration")
+                        .and_then(|v| v.parse().ok())
+                        .map(Duration::from_secs)
+                        .unwrap_or_default();
+                    locked_until(at, duration, now)
+                }
+                None => Lockout::UntilReset,
+            },
+        };
+
+        Self {
+            dn: user.dn.clone(),
+            source: match policy {
+                Some(_) => "ppolicy".to_string(),
+                None => "ppolicy (no policy applies)".to_string(),
+            },
+            policy_dn: policy.map(|p| p.dn.clone()),
+            password_changed,
+            expiry,
+            lockout,
+            failed_logins: find_values_ci(&user.attributes, "pwdFailureTime")
+                .map(|v| v.len() as u32),
+            history_length: setting("pwdInHistory").and_then(|v| v.parse().ok()),
+            settings,
+        }
+    }
+}
+
+/// A password changed at `changed` with a maximum age of `max_age`; no or
+/// a zero maximum age never expires.
+fn expires(changed: Option<DateTime<Utc>>, max_age: Option<Duration>) -> Expiry {
+    match (changed, max_age) {
+        (_, None) => Expiry::Never,
+        (_, Some(age)) if age.is_zero() => Expiry::Never,
+        (Some(changed), Some(age)) => match chrono::Duration::from_std(age) {
+            Ok(age) => Expiry::At(changed + age),
+            Err(_) => Expiry::Never,
+        },
+        (None, Some(_)) => Expiry::Unknown,
+    }
+}
+
+/// A lock set at `at` lasting `duration`; a zero duration lasts until an
+/// administrator unlocks the account.
+fn locked_until(at: DateTime<Utc>, duration: Duration, now: DateTime<Utc>) -> Lockout {
+    if duration.is_zero() {
+        return Lockout::UntilReset;
+    }
+    match chrono::Duration::from_std(duration) {
+        Ok(duration) if at + duration > now => Lockout::Until(at + duration),
+        Ok(_) => Lockout::Unlocked,
+        Err(_) => Lockout::UntilReset,
+    }
+}
+
+fn first<'a>(entry: &'a LdapEntry, attr: &str) -> Option<&'a str> {
+    find_values_ci(&entry.attributes, attr)
+        .and_then(|values| values.first())
+        .map(String::as_str)
+}
+
+fn number(entry: &LdapEntry, attr: &str) -> Option<i64> {
+    first(entry, attr).and_then(|v| v.trim().parse().ok())
+}
+
+fn display(value: &str, format: Option<ValueFormat>) -> String {
+    format
+        .and_then(|f| f.apply(value))
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// An AD FILETIME (100ns since 1601); 0 and the maximum mean never.
+fn filetime(value: &str) -> Option<DateTime<Utc>> {
+    match value.trim().parse::<i64>().ok()? {
+        0 | i64::MAX => None,
+        ticks => DateTime::from_timestamp(ticks / 10_000_000 - 11_644_473_600, 0),
+    }
+}
+
+/// An AD interval, a negative count of 100ns; the minimum means forever,
+/// which like zero is returned as zero.
+fn interval(value: &str) -> Option<Duration> {
+    match value.trim().parse::<i64>().ok()? {
+        i64::MIN => Some(Duration::ZERO),
+        n => Some(Duration::from_secs(n.unsigned_abs() / 10_000_000)),
+    }
+}
+
+fn generalized_time(value: &str) -> Option<DateTime<Utc>> {
+    let t = NaiveDateTime::parse_from_str(value.get(..14)?, "%Y%m%d%H%M%S").ok()?;
+    Some(Utc.from_utc_datetime(&t))
+}
+
+impl LdapConnection {
+    /// Resolve the password policy in effect for `dn` and the state of its
+    /// password.
+    pub async fn password_policy(&mut self, dn: &str) -> Result<PasswordPolicyReport, CoreError> {
+        let attrs: Vec<&str> = AD_USER_ATTRS
+            .iter()
+            .chain(PPOLICY_USER_ATTRS)
+            .copied()
+            .collect();
+        let user = self
+            .search_entry_attrs(dn, &attrs)
+            .await?
+            .ok_or_else(|| CoreError::SearchFailed(format!("{} was not found", dn)))?;
+        let now = Utc::now();
+
+        if first(&user, "userAccountControl").is_some() || first(&user, "pwdLastSet").is_some() {
+            if let Some(pso_dn) = first(&user, "msDS-ResultantPSO").map(str::to_string) {
+                let pso_attrs: Vec<&str> = AD_SETTINGS
+                    .iter()
+                    .map(|(_, pso, _, _)| *pso)
+                    .chain([
+                        "msDS-PasswordComplexityEnabled",
+                        "msDS-PasswordSettingsPrecedence",
+                    ])
+                    .collect();
+                if let Some(pso) = self.search_entry_attrs(&pso_dn, &pso_attrs).await? {
+                    return Ok(PasswordPolicyReport::from_active_directory(
+                        &user, &pso, true, now,
+                    ));
+                }
+            }
+            let domain_dn = self
+                .search_entry_attrs("", &["defaultNamingContext"])
+                .await?
+                .and_then(|root| first(&root, "defaultNamingContext").map(str::to_string))
+                .unwrap_or_else(|| self.base_dn.clone());
+            let domain_attrs: Vec<&str> = AD_SETTINGS
+                .iter()
+                .map(|(_, _, domain, _)| *domain)
+                .chain(["pwdProperties"])
+                .collect();
+            let domain = self
+                .search_entry_attrs(&domain_dn, &domain_attrs)
+                .await?
+                .ok_or_else(|| {
+                    CoreError::SearchFailed(format!(
+                        "the domain head {} is not readable",
+                        domain_dn
+                    ))
+                })?;
+            return Ok(PasswordPolicyReport::from_active_directory(
+                &user, &domain, false, now,
+            ));
+        }
+
+        let policy_dn = match first(&user, "pwdPolicySubentry") {
+            Some(dn) => Some(dn.to_string()),
+            // The overlay's default, where cn=config is readable
+            None => self
+                .search_subtree(
+                    "cn=config",
+                    "(objectClass=olcPPolicyConfig)",
+                    &["olcPPolicyDefault"],
+                )
+                .await
+                .ok()
+                .and_then(|entries| {
+                    entries
+                        .iter()
+                        .find_map(|e| first(e, "olcPPolicyDefault").map(str::to_string))
+                }),
+        };
+        let policy_attrs: Vec<&str> = PPOLICY_SETTINGS.iter().map(|(_, attr, _)| *attr).collect();
+        let policy = match policy_dn {
+            Some(policy_dn) => self.search_entry_attrs(&policy_dn, &policy_attrs).await?,
+            None => None,
+        };
+        Ok(PasswordPolicyReport::from_ppolicy(
+            &user,
+            policy.as_ref(),
+            now,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    fn at(value: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(value).unwrap().into()
+    }
+
+    /// FILETIME of an RFC 3339 time.
+    fn ticks(value: &str) -> String {
+        ((at(value).timestamp() + 11_644_473_600) * 10_000_000).to_string()
+    }
+
+    #[test]
+    fn test_active_directory_domain_policy() {
+        let changed = ticks("2024-03-01T00:00:00Z");
+        let locked = ticks("2024-03-15T10:00:00Z");
+        let user = test_entry(
+            "CN=jdoe,CN=Users,DC=example,DC=com",
+            &[
+                ("userAccountControl", &["512"]),
+                ("pwdLastSet", &[changed.as_str()]),
+                ("lockoutTime", &[locked.as_str()]),
+                ("badPwdCount", &["5"]),
+            ],
+        );
+        let domain = test_entry(
+            "DC=example,DC=com",
+            &[
+                ("maxPwdAge", &["-36288000000000"]), // 42 days
+                ("pwdHistoryLength", &["24"]),
+                ("lockoutDuration", &["-18000000000"]), // 30 minutes
+                ("pwdProperties", &["1"]),
+            ],
+        );
+        let now = at("2024-03-15T10:10:00Z");
+        let report = PasswordPolicyReport::from_active_directory(&user, &domain, false, now);
+        assert_eq!(report.source, "Domain policy");
+        assert_eq!(report.expiry, Expiry::At(at("2024-04-12T00:00:00Z")));
+        assert_eq!(report.lockout, Lockout::Until(at("2024-03-15T10:30:00Z")));
+        assert_eq!(report.history_length, Some(24));
+        assert_eq!(report.failed_logins, Some(5));
+        assert!(report
+            .settings
+            .contains(&("Maximum age".to_string(), "42d".to_string())));
+        assert!(report
+            .settings
+            .contains(&("Complexity required".to_string(), "TRUE".to_string())));
+
+        // The lock has run out by the next day
+        let later = at("2024-03-16T00:00:00Z");
+        let report = PasswordPolicyReport::from_active_directory(&user, &domain, false, later);
+        assert_eq!(report.lockout, Lockout::Unlocked);
+    }
+
+    #[test]
+    fn test_active_directory_fine_grained_policy() {
+        let expiry = ticks("2024-06-01T00:00:00Z");
+        let user = test_entry(
+            "CN=admin,CN=Users,DC=example,DC=com",
+            &[
+                ("userAccountControl", &["512"]),
+                ("pwdLastSet", &[ticks("2024-03-01T00:00:00Z").as_str()]),
+                ("msDS-UserPasswordExpiryTimeComputed", &[expiry.as_str()]),
+                ("msDS-User-Account-Control-Computed", &["0"]),
+                ("lockoutTime", &[ticks("2024-03-15T10:00:00Z").as_str()]),
+            ],
+        );
+        let pso = test_entry(
+            "CN=Admins,CN=Password Settings Container,CN=System,DC=example,DC=com",
+            &[
+                ("msDS-PasswordHistoryLength", &["48"]),
+                ("msDS-LockoutDuration", &["-9223372036854775808"]),
+                ("msDS-PasswordSettingsPrecedence", &["10"]),
+            ],
+        );
+        let now = at("2024-03-15T10:10:00Z");
+        let report = PasswordPolicyReport::from_active_directory(&user, &pso, true, now);
+        assert_eq!(report.source, "Fine-grained policy");
+        assert_eq!(report.expiry, Expiry::At(at("2024-06-01T00:00:00Z")));
+        // Not locked according to the computed flags
+        assert_eq!(report.lockout, Lockout::Unlocked);
+        assert_eq!(report.history_length, Some(48));
+
+        let reset = test_entry(
+            "CN=new,CN=Users,DC=example,DC=com",
+            &[("userAccountControl", &["66048"]), ("pwdLastSet", &["0"])],
+        );
+        let report = PasswordPolicyReport::from_active_directory(&reset, &pso, true, now);
+        assert_eq!(report.expiry, Expiry::Never);
+    }
+
+    #[test]
+    fn test_ppolicy() {
+        let user = test_entry(
+            "uid=jdoe,ou=People,dc=example,dc=com",
+            &[
+                ("pwdChangedTime", &["20240301000000Z"]),
+                ("pwdAccountLockedTime", &["20240315100000Z"]),
+                ("pwdFailureTime", &["20240315095900Z"]),
+                ("pwdFailureTime", &["20240315100000Z"]),
+            ],
+        );
+        let policy = test_entry(
+            "cn=default,ou=Policies,dc=example,dc=com",
+            &[
+                ("pwdMaxAge", &["7776000"]), // 90 days
+                ("pwdInHistory", &["5"]),
+                ("pwdLockoutDuration", &["0"]),
+            ],
+        );
+        let now = at("2024-03-15T10:10:00Z");
+        let report = PasswordPolicyReport::from_ppolicy(&user, Some(&policy), now);
+        assert_eq!(report.expiry, Expiry::At(at("2024-05-30T00:00:00Z")));
+        assert_eq!(report.lockout, Lockout::UntilReset);
+        assert_eq!(report.failed_logins, Some(2));
+        assert_eq!(report.history_length, Some(5));
+        assert_eq!(
+            report.policy_dn.as_deref(),
+            Some("cn=default,ou=Policies,dc=example,dc=com")
+        );
+
+        let report = PasswordPolicyReport::from_ppolicy(&user, None, now);
+        assert_eq!(report.source, "ppolicy (no policy applies)");
+        assert_eq!(report.expiry, Expiry::Unknown);
+    }
+}
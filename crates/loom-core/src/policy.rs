@@ -0,0 +1,157 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+
+/// Attributes owned by external systems (e.g. an identity management
+/// platform), which must not be edited directly. Loaded from a TOML policy
+/// file:
+///
+/// ```toml
+/// [[managed]]
+/// system = "SailPoint"
+/// attributes = ["mail", "title", "department"]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct EditPolicy {
+    #[serde(default)]
+    pub managed: Vec<ManagedAttributes>,
+}
+
+/// A group of attributes and the system that owns them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ManagedAttributes {
+    pub system: String,
+    pub attributes: Vec<String>,
+}
+
+impl EditPolicy {
+    pub fn load(path: &Path) -> Result<Self, CoreError> {
+        let text = std::fs::read_to_string(path).map_err(|e| {
+            CoreError::PolicyError(format!("failed to read {}: {}", path.display(), e))
+        })?;
+        Self::parse(&text).map_err(|e| CoreError::PolicyError(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn parse(text: &str) -> Result<Self, String> {
+        toml::from_str(text).map_err(|e| e.message().to_string())
+    }
+
+    /// The system that owns `attr`, if any (case-insensitive).
+    pub fn owner(&self, attr: &str) -> Option<&str> {
+        self.managed
+            .iter()
+            .find(|m| m.attributes.iter().any(|a| a.eq_ignore_ascii_case(attr)))
+            .map(|m| m.system.as_str())
+    }
+
+    /// Refuse edits to a managed attribute, naming its owner.
+    pub fn check_editable(&self, attr: &str) -> Result<(), String> {
+        match self.owner(attr) {
+            Some(system) => Err(format!(
+                "{} is managed by {}; change it there instead",
+                attr, system
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Safeguards a profile can ask for around destructive operations.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct SafetyPolicy {
+    /// Confirm a delete by typing the entry's RDN rather than pressing y.
+    #[serde(default)]
+    pub confirm_delete_rdn: bool,
+    /// Ask a second time before a bulk update matching more entries than this.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bulk_confirm_above: Option<usize>,
+    /// Refuse writes to entries outside this subtree.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub write_subtree: Option<String>,
+}
+
+impl SafetyPolicy {
+    /// Refuse a write to `dn` when it lies outside `write_subtree`.
+    pub fn check_scope(&self, dn: &str) -> Result<(), String> {
+        let Some(subtree) = &self.write_subtree else {
+            return Ok(());
+        };
+        let normalize = |dn: &str| {
+            dn.split(',')
+                .map(|rdn| rdn.trim().to_lowercase())
+                .collect::<Vec<_>>()
+        };
+        let (dn_rdns, subtree_rdns) = (normalize(dn), normalize(subtree));
+        if dn_rdns.ends_with(&subtree_rdns) {
+            Ok(())
+        } else {
+            Err(format!(
+                "{} is outside {}, the only subtree this profile may change",
+                dn, subtree
+            ))
+        }
+    }
+
+    /// Whether a bulk update matching `matched` entries needs a second
+    /// confirmation.
+    pub fn needs_second_confirm(&self, matched: usize) -> bool {
+        self.bulk_confirm_above.is_some_and(|limit| matched > limit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_owner_lookup() {
+        let policy = EditPolicy::parse(
+            r#"
+            [[managed]]
+            system = "SailPoint"
+            attributes = ["mail", "title"]
+
+            [[managed]]
+            system = "HR feed"
+            attributes = ["employeeNumber"]
+            "#,
+        )
+        .unwrap();
+        assert_eq!(policy.owner("Mail"), Some("SailPoint"));
+        assert_eq!(policy.owner("employeenumber"), Some("HR feed"));
+        assert_eq!(policy.owner("description"), None);
+        assert!(policy
+            .check_editable("title")
+            .unwrap_err()
+            .contains("managed by SailPoint"));
+        assert!(EditPolicy::parse("[[managed]]\nsystem = 1").is_err());
+    }
+
+    #[test]
+    fn test_safety_scope() {
+        let policy = SafetyPolicy {
+            bulk_confirm_above: Some(50),
+            write_subtree: Some("ou=People, dc=example,dc=com".to_string()),
+            ..Default::default()
+        };
+        assert!(policy.check_scope("ou=people,dc=example,dc=com").is_ok());
+        assert!(policy
+            .check_scope("uid=alice,OU=People,dc=example, dc=com")
+            .is_ok());
+        // A suffix of an RDN value isn't the subtree
+        assert!(policy
+            .check_scope("uid=bob,ou=OtherPeople,dc=example,dc=com")
+            .is_err());
+        assert!(policy
+            .check_scope("dc=example,dc=com")
+            .unwrap_err()
+            .contains("outside ou=People"));
+        assert!(SafetyPolicy::default().check_scope("dc=any").is_ok());
+
+        assert!(!policy.needs_second_confirm(50));
+        assert!(policy.needs_second_confirm(51));
+        assert!(!SafetyPolicy::default().needs_second_confirm(10_000));
+    }
+}
@@ -0,0 +1,354 @@
+use std::sync::Arc;
+
+use tokio::sync::{Mutex, OwnedMutexGuard};
+use tracing::{debug, info, warn};
+
+use crate::connection::{ConnectionSettings, LdapConnection};
+use crate::error::CoreError;
+use crate::staging::ChangeQueue;
+use crate::tls::TrustStore;
+use crate::trace::OperationTrace;
+use crate::tunnel::SshTunnel;
+
+/// A connection checked out of the pool; it returns when dropped.
+pub type PooledConnection = OwnedMutexGuard<LdapConnection>;
+
+/// What's needed to open more connections like the main one without
+/// holding its lock.
+struct Template {
+    settings: ConnectionSettings,
+    base_dn: String,
+    bind_credentials: Option<(String, String)>,
+    trust_store: Option<Arc<TrustStore>>,
+    trace: OperationTrace,
+    tunnel: Option<Arc<std::sync::Mutex<SshTunnel>>>,
+}
+
+impl Template {
+    fn from_connection(conn: &LdapConnection) -> Self {
+        Self {
+            settings: conn.pinned_settings(),
+            base_dn: conn.base_dn.clone(),
+            bind_credentials: conn.bind_credentials.clone(),
+            trust_store: conn.trust_store.clone(),
+            trace: conn.trace.clone(),
+            tunnel: conn.tunnel.clone(),
+        }
+    }
+
+    /// Connect and bind the same way as the main connection.
+    async fn open(&self) -> Result<LdapConnection, CoreError> {
+        let mut conn = LdapConnection::connect_via(
+            self.settings.clone(),
+            self.trust_store.clone(),
+            self.tunnel.clone(),
+        )
+        .await?;
+        conn.trace = self.trace.clone();
+        match &self.bind_credentials {
+            Some((bind_dn, password)) => conn.bind(Some(bind_dn), password).await?,
+            None => conn.bind(None, "").await?,
+        }
+        conn.base_dn = self.base_dn.clone();
+        Ok(conn)
+    }
+}
+
+/// A tab's connections: the main one, which carries every write, the
+/// dry-run state and the staged changes, plus up to `size - 1` extra connections opened on demand
+/// for reads, so an export doesn't hold up browsing.
+pub struct ConnectionPool {
+    main: Arc<Mutex<LdapConnection>>,
+    readers: std::sync::Mutex<Vec<Arc<Mutex<LdapConnection>>>>,
+    /// Cleared while the pool is suspended, so no reader can be opened
+    /// with the bind password until it's resumed.
+    template: std::sync::Mutex<Option<Arc<Template>>>,
+    host: String,
+    trace: OperationTrace,
+    size: usize,
+    changes: ChangeQueue,
+}
+
+impl ConnectionPool {
+    /// Wrap a bound connection. A `size` of 1 or less keeps everything on
+    /// the main connection.
+    pub fn new(conn: LdapConnection, size: usize) -> Self {
+        let template = Template::from_connection(&conn);
+        let changes = conn.staged_changes();
+        Self {
+            host: template.settings.host.clone(),
+            trace: template.trace.clone(),
+            main: Arc::new(Mutex::new(conn)),
+            readers: std::sync::Mutex::new(Vec::new()),
+            template: std::sync::Mutex::new(Some(Arc::new(template))),
+            size: size.max(1),
+            changes,
+        }
+    }
+
+    /// The main connection, for writes and anything tied to dry run.
+    pub fn main(&self) -> Arc<Mutex<LdapConnection>> {
+        self.main.clone()
+    }
+
+    /// The requests sent on every connection of the pool.
+    pub fn trace(&self) -> OperationTrace {
+        self.trace.clone()
+    }
+
+    /// The changes staged on the main connection.
+    pub fn changes(&self) -> ChangeQueue {
+        self.changes.clone()
+    }
+
+    /// Extra read connections currently open.
+    pub fn open_readers(&self) -> usize {
+        self.readers.lock().map(|r| r.len()).unwrap_or(0)
+    }
+
+    /// A connection for a quick read such as loading an entry: the main
+    /// connection when it is free, otherwise an idle or new extra one.
+    pub async fn reader(&self) -> PooledConnection {
+        if let Ok(guard) = self.main.clone().try_lock_owned() {
+            return guard;
+        }
+        self.extra_reader().await
+    }
+
+    /// A connection for a long read such as an export, kept off the main
+    /// connection when the pool has room so edits aren't held up either.
+    pub async fn long_reader(&self) -> PooledConnection {
+        self.extra_reader().await
+    }
+
+    /// An idle extra connection, or a new one if the pool isn't full.
+    /// Waits for the main connection when neither is possible, or while
+    /// the pool is suspended.
+    async fn extra_reader(&self) -> PooledConnection {
+        let readers = self.readers.lock().map(|r| r.clone()).unwrap_or_default();
+        if let Some(guard) = readers.iter().find_map(|r| r.clone().try_lock_owned().ok()) {
+            return guard;
+        }
+        let template = self.template.lock().ok().and_then(|t| t.clone());
+        if let Some(template) = template.filter(|_| readers.len() + 1 < self.size) {
+            match template.open().await {
+                Ok(conn) => {
+                    let reader = Arc::new(Mutex::new(conn));
+                    let guard = reader
+                        .clone()
+                        .try_lock_owned()
+                        .expect("new connection is unshared");
+                    if let Ok(mut readers) = self.readers.lock() {
+                        // Another caller may have filled the pool meanwhile;
+                        // then this connection closes once it's returned.
+                        if readers.len() + 1 < self.size {
+                            readers.push(reader);
+                            info!(
+                                "Opened pooled connection {} of {} to {}",
+                                readers.len() + 1,
+                                self.size,
+                                self.host
+                            );
+                        }
+                    }
+                    return guard;
+                }
+                Err(e) => warn!("Could not open a pooled connection to {}: {}", self.host, e),
+            }
+        }
+        self.main.clone().lock_owned().await
+    }
+
+    /// Unbind and drop the extra connections. New ones are opened as
+    /// needed, so this is safe at any time.
+    pub async fn close_readers(&self) {
+        let readers: Vec<_> = self
+            .readers
+            .lock()
+            .map(|mut r| r.drain(..).collect())
+            .unwrap_or_default();
+        for reader in readers {
+            if let Err(e) = reader.lock().await.disconnect().await {
+                debug!("Unbind of pooled connection failed: {}", e);
+            }
+        }
+    }
+
+    /// Close the extra connections and forget how to open more, dropping
+    /// the pool's copy of the bind password. Until `resume`, every read
+    /// goes to the main connection.
+    pub async fn suspend(&self) {
+        if let Ok(mut template) = self.template.lock() {
+            *template = None;
+        }
+        self.close_readers().await;
+    }
+
+    /// Open extra connections again, bound like `conn`, the main
+    /// connection once it has been rebound.
+    pub fn resume(&self, conn: &LdapConnection) {
+        if let Ok(mut template) = self.template.lock() {
+            *template = Some(Arc::new(Template::from_connection(conn)));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::TlsMode;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    /// A server that accepts every bind and counts connections and unbinds.
+    async fn fake_server() -> (u16, Arc<AtomicUsize>, Arc<AtomicUsize>) {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let accepted = Arc::new(AtomicUsize::new(0));
+        let unbinds = Arc::new(AtomicUsize::new(0));
+        let (a, u) = (accepted.clone(), unbinds.clone());
+        tokio::spawn(async move {
+            while let Ok((mut socket, _)) = listener.accept().await {
+                a.fetch_add(1, Ordering::SeqCst);
+                let u = u.clone();
+                tokio::spawn(async move {
+                    // LDAPMessage: SEQUENCE { messageID INTEGER, protocolOp }
+                    let mut head = [0u8; 2];
+                    while socket.read_exact(&mut head).await.is_ok() {
+                        let len = if head[1] & 0x80 == 0 {
+                            head[1] as usize
+                        } else {
+                            let mut bytes = vec![0u8; (head[1] & 0x7f) as usize];
+                            socket.read_exact(&mut bytes).await.unwrap();
+                            bytes.iter().fold(0, |n, b| n << 8 | *b as usize)
+                        };
+                        let mut body = vec![0u8; len];
+                        socket.read_exact(&mut body).await.unwrap();
+                        let id = &body[..2 + body[1] as usize];
+                        match body[id.len()] {
+                            // BindRequest: answer success
+                            0x60 => {
+                                let mut reply = vec![0x30, (id.len() + 9) as u8];
+                                reply.extend_from_slice(id);
+                                reply.extend_from_slice(&[0x61, 7, 0x0a, 1, 0, 4, 0, 4, 0]);
+                                socket.write_all(&reply).await.unwrap();
+                            }
+                            // UnbindRequest
+                            0x42 => {
+                                u.fetch_add(1, Ordering::SeqCst);
+                                return;
+                            }
+                            _ => {}
+                        }
+                    }
+                });
+            }
+        });
+        (port, accepted, unbinds)
+    }
+
+    async fn connect(port: u16) -> LdapConnection {
+        let settings = ConnectionSettings {
+            host: "127.0.0.1".to_string(),
+            port,
+            tls_mode: TlsMode::None,
+            bind_dn: Some("cn=admin,dc=x".to_string()),
+            base_dn: Some("dc=x".to_string()),
+            page_size: 500,
+            timeout_secs: 5,
+            relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            dns_srv: false,
+            failover_hosts: Vec::new(),
+            shuffle_hosts: false,
+        };
+        let mut conn = LdapConnection::connect(settings, None).await.unwrap();
+        conn.bind(Some("cn=admin,dc=x"), "secret").await.unwrap();
+        conn
+    }
+
+    fn is_main(pool: &ConnectionPool, guard: &PooledConnection) -> bool {
+        Arc::ptr_eq(OwnedMutexGuard::mutex(guard), &pool.main())
+    }
+
+    #[tokio::test]
+    async fn test_reader_reuse_fallback_and_close() {
+        let (port, accepted, unbinds) = fake_server().await;
+        let pool = ConnectionPool::new(connect(port).await, 2);
+
+        // A free main connection serves quick reads
+        let main = pool.reader().await;
+        assert!(is_main(&pool, &main));
+
+        // While it's busy, an extra connection is opened, then reused
+        let reader = pool.reader().await;
+        assert!(!is_main(&pool, &reader));
+        assert_eq!(pool.open_readers(), 1);
+        drop(reader);
+        let reader = pool.long_reader().await;
+        assert!(!is_main(&pool, &reader));
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+        // With the pool full, a reader waits for the main connection
+        drop(main);
+        let fallback = pool.long_reader().await;
+        assert!(is_main(&pool, &fallback));
+        drop(fallback);
+        drop(reader);
+
+        pool.close_readers().await;
+        assert_eq!(pool.open_readers(), 0);
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(unbinds.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn test_suspended_pool_opens_no_readers() {
+        let (port, accepted, _) = fake_server().await;
+        let pool = ConnectionPool::new(connect(port).await, 3);
+        let main = pool.reader().await;
+        let reader = pool.long_reader().await;
+        drop(reader);
+        assert_eq!(pool.open_readers(), 1);
+        drop(main);
+
+        pool.suspend().await;
+        assert_eq!(pool.open_readers(), 0);
+        let main = pool.main().lock_owned().await;
+        let waiting = tokio::time::timeout(Duration::from_millis(200), pool.long_reader()).await;
+        assert!(waiting.is_err(), "a suspended pool must not open readers");
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+
+        pool.resume(&main);
+        let reader = pool.long_reader().await;
+        assert!(!is_main(&pool, &reader));
+        assert_eq!(accepted.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_cleared_password_blocks_reconnect() {
+        let (port, accepted, _) = fake_server().await;
+        let mut conn = connect(port).await;
+
+        conn.clear_password();
+        assert_eq!(conn.cleared_bind_dn(), Some("cn=admin,dc=x"));
+        assert_eq!(
+            conn.bind_credentials,
+            Some(("cn=admin,dc=x".to_string(), String::new()))
+        );
+        assert!(matches!(
+            conn.reconnect().await,
+            Err(CoreError::CredentialError(_))
+        ));
+        assert_eq!(accepted.load(Ordering::SeqCst), 1);
+
+        conn.store_credentials("cn=admin,dc=x".to_string(), "secret".to_string());
+        assert_eq!(conn.cleared_bind_dn(), None);
+        conn.reconnect().await.unwrap();
+        assert_eq!(accepted.load(Ordering::SeqCst), 2);
+    }
+}
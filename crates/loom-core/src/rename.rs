@@ -0,0 +1,510 @@
+use std::collections::HashSet;
+
+use ldap3::Mod;
+use tracing::{info, warn};
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::util::get_values;
+
+/// Characters Active Directory rejects in a sAMAccountName.
+const SAM_INVALID_CHARS: &str = "\"/\\[]:;|=,+*?<>";
+const SAM_MAX_LEN: usize = 20;
+
+/// The new naming values for an Active Directory user.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RenameTarget {
+    pub cn: String,
+    pub sam_account_name: String,
+    pub user_principal_name: String,
+    /// Primary mail address; empty leaves an entry without mail alone.
+    pub mail: String,
+}
+
+impl RenameTarget {
+    /// The entry's current values, as a starting point for editing.
+    pub fn from_entry(entry: &LdapEntry) -> Self {
+        let first = |attr: &str| get_values(&entry.attributes, attr).into_iter().next();
+        Self {
+            cn: first("cn").unwrap_or_else(|| crate::dn::rdn_display_name(&entry.dn).to_string()),
+            sam_account_name: first("sAMAccountName").unwrap_or_default(),
+            user_principal_name: first("userPrincipalName").unwrap_or_default(),
+            mail: first("mail").unwrap_or_default(),
+        }
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.cn.trim().is_empty() {
+            return Err("cn is required".to_string());
+        }
+        let sam = &self.sam_account_name;
+        if sam.is_empty() {
+            return Err("sAMAccountName is required".to_string());
+        }
+        if sam.chars().count() > SAM_MAX_LEN {
+            return Err(format!(
+                "sAMAccountName is longer than {} characters",
+                SAM_MAX_LEN
+            ));
+        }
+        if let Some(c) = sam.chars().find(|c| SAM_INVALID_CHARS.contains(*c)) {
+            return Err(format!("sAMAccountName may not contain '{}'", c));
+        }
+        if sam.ends_with('.') {
+            return Err("sAMAccountName may not end with '.'".to_string());
+        }
+        match self.user_principal_name.split_once('@') {
+            Some((name, suffix))
+                if !name.is_empty()
+                    && !suffix.is_empty()
+                    && !suffix.contains('@')
+                    && !self.user_principal_name.contains(' ') => {}
+            _ => {
+                return Err(format!(
+                    "userPrincipalName '{}' is not of the form name@suffix",
+                    self.user_principal_name
+                ))
+            }
+        }
+        if !self.mail.is_empty() && !self.mail.contains('@') {
+            return Err(format!("mail '{}' is not an address", self.mail));
+        }
+        Ok(())
+    }
+}
+
+/// Replace the name part of a UPN, keeping its `@suffix`.
+pub fn retarget_upn(upn: &str, name: &str) -> String {
+    match upn.split_once('@') {
+        Some((_, suffix)) => format!("{}@{}", name, suffix),
+        None => name.to_string(),
+    }
+}
+
+/// One attribute's values before and after the rename.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueChange {
+    pub attr: String,
+    pub old: Vec<String>,
+    pub new: Vec<String>,
+}
+
+impl ValueChange {
+    /// Values to delete then add: only those that actually differ, so
+    /// the delete fails if the entry changed since it was read.
+    fn mods(&self) -> Vec<Mod<String>> {
+        let removed: HashSet<String> = self
+            .old
+            .iter()
+            .filter(|v| !self.new.contains(v))
+            .cloned()
+            .collect();
+        let added: HashSet<String> = self
+            .new
+            .iter()
+            .filter(|v| !self.old.contains(v))
+            .cloned()
+            .collect();
+        let mut mods = Vec::new();
+        if !removed.is_empty() {
+            mods.push(Mod::Delete(self.attr.clone(), removed));
+        }
+        if !added.is_empty() {
+            mods.push(Mod::Add(self.attr.clone(), added));
+        }
+        mods
+    }
+
+    fn reversed(&self) -> Self {
+        Self {
+            attr: self.attr.clone(),
+            old: self.new.clone(),
+            new: self.old.clone(),
+        }
+    }
+}
+
+/// A step of a rename, applied in plan order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RenameStep {
+    /// Change attribute values in one modify request.
+    Modify {
+        label: String,
+        changes: Vec<ValueChange>,
+    },
+    /// ModifyDN to a new RDN under the same parent.
+    Rdn { old_rdn: String, new_rdn: String },
+}
+
+impl RenameStep {
+    pub fn label(&self) -> &str {
+        match self {
+            RenameStep::Modify { label, .. } => label,
+            RenameStep::Rdn { .. } => "Rename RDN",
+        }
+    }
+
+    fn reversed(&self) -> Self {
+        match self {
+            RenameStep::Modify { label, changes } => RenameStep::Modify {
+                label: label.clone(),
+                changes: changes.iter().map(ValueChange::reversed).collect(),
+            },
+            RenameStep::Rdn { old_rdn, new_rdn } => RenameStep::Rdn {
+                old_rdn: new_rdn.clone(),
+                new_rdn: old_rdn.clone(),
+            },
+        }
+    }
+}
+
+/// A staged rename of an AD user. Logon names go first, since the domain
+/// controller checks them for uniqueness; mail next; the RDN last, because
+/// it changes the DN the other steps address.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenamePlan {
+    pub dn: String,
+    pub new_dn: String,
+    pub steps: Vec<RenameStep>,
+}
+
+impl RenamePlan {
+    /// Work out the changes needed to give `entry` the `target` names.
+    /// The primary proxyAddresses entry (`SMTP:`) follows the new mail and
+    /// the old primary is kept as an alias (`smtp:`).
+    pub fn build(entry: &LdapEntry, target: &RenameTarget) -> Result<Self, String> {
+        target.validate()?;
+        let current = RenameTarget::from_entry(entry);
+
+        let old_rdn = entry.rdn().to_string();
+        let rdn_attr = old_rdn.split('=').next().unwrap_or_default();
+        if !rdn_attr.eq_ignore_ascii_case("cn") {
+            return Err(format!(
+                "{} is not named by cn; safe rename is for AD users",
+                entry.dn
+            ));
+        }
+
+        let mut steps = Vec::new();
+
+        let mut logon = Vec::new();
+        push_single(
+            &mut logon,
+            entry,
+            "sAMAccountName",
+            &current.sam_account_name,
+            &target.sam_account_name,
+        );
+        push_single(
+            &mut logon,
+            entry,
+            "userPrincipalName",
+            &current.user_principal_name,
+            &target.user_principal_name,
+        );
+        if !logon.is_empty() {
+            steps.push(RenameStep::Modify {
+                label: "Logon names".to_string(),
+                changes: logon,
+            });
+        }
+
+        let mut mail = Vec::new();
+        if !target.mail.is_empty() && target.mail != current.mail {
+            push_single(&mut mail, entry, "mail", &current.mail, &target.mail);
+            let proxies = get_values(&entry.attributes, "proxyAddresses");
+            if !proxies.is_empty() {
+                let new = promote_primary(&proxies, &target.mail);
+                if new != proxies {
+                    mail.push(ValueChange {
+                        attr: attr_key(entry, "proxyAddresses"),
+                        old: proxies,
+                        new,
+                    });
+                }
+            }
+        }
+        if !mail.is_empty() {
+            steps.push(RenameStep::Modify {
+                label: "Mail addresses".to_string(),
+                changes: mail,
+            });
+        }
+
+        let new_rdn = format!(
+            "{}={}",
+            rdn_attr,
+            crate::dn::escape_rdn_value(target.cn.trim())
+        );
+        let new_dn = if new_rdn == old_rdn {
+            entry.dn.clone()
+        } else {
+            let new_dn = match crate::dn::parent_dn(&entry.dn) {
+                Some(parent) => format!("{},{}", new_rdn, parent),
+                None => new_rdn.clone(),
+            };
+            steps.push(RenameStep::Rdn { old_rdn, new_rdn });
+            new_dn
+        };
+
+        Ok(Self {
+            dn: entry.dn.clone(),
+            new_dn,
+            steps,
+        })
+    }
+
+    /// Human-readable summary of the plan, one line per change.
+    pub fn preview_lines(&self) -> Vec<String> {
+        let mut lines = Vec::new();
+        for (i, step) in self.steps.iter().enumerate() {
+            lines.push(format!("{}. {}", i + 1, step.label()));
+            match step {
+                RenameStep::Modify { changes, .. } => {
+                    for change in changes {
+                        if change.old.len() <= 1 && change.new.len() <= 1 {
+                            lines.push(format!(
+                                "   {}: {} -> {}",
+                                change.attr,
+                                change.old.first().map_or("(none)", String::as_str),
+                                change.new.first().map_or("(none)", String::as_str),
+                            ));
+                            continue;
+                        }
+                        lines.push(format!("   {}:", change.attr));
+                        for v in change.old.iter().filter(|v| !change.new.contains(v)) {
+                            lines.push(format!("     - {}", v));
+                        }
+                        for v in change.new.iter().filter(|v| !change.old.contains(v)) {
+                            lines.push(format!("     + {}", v));
+                        }
+                    }
+                }
+                RenameStep::Rdn { old_rdn, new_rdn } => {
+                    lines.push(format!("   {} -> {}", old_rdn, new_rdn));
+                }
+            }
+        }
+        lines.push(format!("New DN: {}", self.new_dn));
+        lines
+    }
+}
+
+/// The entry's spelling of an attribute name, or `attr` if it is absent.
+fn attr_key(entry: &LdapEntry, attr: &str) -> String {
+    entry
+        .attributes
+        .keys()
+        .find(|k| k.eq_ignore_ascii_case(attr))
+        .cloned()
+        .unwrap_or_else(|| attr.to_string())
+}
+
+fn push_single(
+    changes: &mut Vec<ValueChange>,
+    entry: &LdapEntry,
+    attr: &str,
+    old: &str,
+    new: &str,
+) {
+    if old == new {
+        return;
+    }
+    let values = |v: &str| {
+        if v.is_empty() {
+            Vec::new()
+        } else {
+            vec![v.to_string()]
+        }
+    };
+    changes.push(ValueChange {
+        attr: attr_key(entry, attr),
+        old: values(old),
+        new: values(new),
+    });
+}
+
+/// Make `mail` the primary SMTP proxy address, demoting the old primary
+/// to an alias.
+fn promote_primary(proxies: &[String], mail: &str) -> Vec<String> {
+    let primary = format!("SMTP:{}", mail);
+    let mut out = vec![primary.clone()];
+    for p in proxies {
+        if p.eq_ignore_ascii_case(&primary) {
+            continue;
+        }
+        match p.strip_prefix("SMTP:") {
+            Some(addr) => out.push(format!("smtp:{}", addr)),
+            None => out.push(p.clone()),
+        }
+    }
+    out
+}
+
+impl LdapConnection {
+    /// Apply a rename plan step by step and return the entry's new DN. If
+    /// a step fails, the steps already applied are undone in reverse order.
+    pub async fn apply_rename_plan(&mut self, plan: &RenamePlan) -> Result<String, CoreError> {
+        let mut dn = plan.dn.clone();
+        let mut applied = Vec::new();
+
+        for step in &plan.steps {
+            match self.apply_rename_step(&dn, step).await {
+                Ok(next) => {
+                    dn = next;
+                    applied.push(step);
+                }
+                Err(e) => {
+                    warn!("Rename step '{}' failed on {}: {}", step.label(), dn, e);
+                    let mut undo_error = None;
+                    for done in applied.iter().rev() {
+                        match self.apply_rename_step(&dn, &done.reversed()).await {
+                            Ok(prev) => dn = prev,
+                            Err(undo) => {
+                                undo_error = Some(undo);
+                                break;
+                            }
+                        }
+                    }
+                    let msg = match undo_error {
+                        None => format!(
+                            "{} failed, {} earlier step(s) rolled back: {}",
+                            step.label(),
+                            applied.len(),
+                            e
+                        ),
+                        Some(undo) => format!(
+                            "{} failed ({}) and the rollback also failed, {} is partly renamed: {}",
+                            step.label(),
+                            e,
+                            dn,
+                            undo
+                        ),
+                    };
+                    return Err(CoreError::ModifyFailed(msg));
+                }
+            }
+        }
+
+        info!("Renamed user {} -> {}", plan.dn, dn);
+        Ok(dn)
+    }
+
+    /// Apply one step to the entry at `dn`, returning its DN afterwards.
+    async fn apply_rename_step(
+        &mut self,
+        dn: &str,
+        step: &RenameStep,
+    ) -> Result<String, CoreError> {
+        match step {
+            RenameStep::Modify { changes, .. } => {
+                let mods = changes.iter().flat_map(ValueChange::mods).collect();
+                self.modify_entry(dn, mods).await?;
+                Ok(dn.to_string())
+            }
+            RenameStep::Rdn { new_rdn, .. } => self.rename_entry(dn, new_rdn).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeMap;
+
+    fn user() -> LdapEntry {
+        let attrs = [
+            ("cn", vec!["John Doe"]),
+            ("sAMAccountName", vec!["jdoe"]),
+            ("userPrincipalName", vec!["jdoe@corp.example.com"]),
+            ("mail", vec!["john.doe@example.com"]),
+            (
+                "proxyAddresses",
+                vec![
+                    "SMTP:john.doe@example.com",
+                    "smtp:jd@example.com",
+                    "X500:/o=Corp",
+                ],
+            ),
+        ];
+        LdapEntry::new(
+            "CN=John Doe,OU=Users,DC=corp,DC=example,DC=com".to_string(),
+            attrs
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v.into_iter().map(String::from).collect()))
+                .collect::<BTreeMap<_, _>>(),
+        )
+    }
+
+    #[test]
+    fn test_build_orders_steps_and_promotes_mail() {
+        let entry = user();
+        let target = RenameTarget {
+            cn: "John Smith".to_string(),
+            sam_account_name: "jsmith".to_string(),
+            user_principal_name: retarget_upn("jdoe@corp.example.com", "jsmith"),
+            mail: "john.smith@example.com".to_string(),
+        };
+        let plan = RenamePlan::build(&entry, &target).unwrap();
+        assert_eq!(
+            plan.new_dn,
+            "CN=John Smith,OU=Users,DC=corp,DC=example,DC=com"
+        );
+
+        let labels: Vec<&str> = plan.steps.iter().map(RenameStep::label).collect();
+        assert_eq!(labels, vec!["Logon names", "Mail addresses", "Rename RDN"]);
+
+        let RenameStep::Modify { changes, .. } = &plan.steps[1] else {
+            panic!("expected a modify step");
+        };
+        assert_eq!(
+            changes[1].new,
+            vec![
+                "SMTP:john.smith@example.com",
+                "smtp:john.doe@example.com",
+                "smtp:jd@example.com",
+                "X500:/o=Corp",
+            ]
+        );
+        assert_eq!(
+            plan.steps[2],
+            RenameStep::Rdn {
+                old_rdn: "CN=John Doe".to_string(),
+                new_rdn: "CN=John Smith".to_string(),
+            }
+        );
+    }
+
+    #[test]
+    fn test_build_skips_unchanged_and_validates() {
+        let entry = user();
+        let mut target = RenameTarget::from_entry(&entry);
+        let plan = RenamePlan::build(&entry, &target).unwrap();
+        assert!(plan.steps.is_empty());
+        assert_eq!(plan.new_dn, entry.dn);
+
+        // Only the logon name changes
+        target.sam_account_name = "john.doe".to_string();
+        let plan = RenamePlan::build(&entry, &target).unwrap();
+        assert_eq!(plan.steps.len(), 1);
+
+        target.sam_account_name = "a:b".to_string();
+        assert!(RenamePlan::build(&entry, &target).is_err());
+        target.sam_account_name = "jdoe".to_string();
+        target.user_principal_name = "jdoe".to_string();
+        assert!(RenamePlan::build(&entry, &target).is_err());
+    }
+
+    #[test]
+    fn test_reversed_change_swaps_mods() {
+        let change = ValueChange {
+            attr: "sAMAccountName".to_string(),
+            old: vec!["jdoe".to_string()],
+            new: vec!["jsmith".to_string()],
+        };
+        let mods = change.reversed().mods();
+        assert!(matches!(&mods[0], Mod::Delete(_, vals) if vals.contains("jsmith")));
+        assert!(matches!(&mods[1], Mod::Add(_, vals) if vals.contains("jdoe")));
+    }
+}
@@ -0,0 +1,619 @@
+//! Replication health per partner. Active Directory keeps each
+//! partition's inbound partners in the naming context head's `repsFrom`
+//! and its outbound ones in `repsTo`; OpenLDAP syncrepl is judged by
+//! comparing the `contextCSN` of a suffix across the servers holding it.
+
+use std::collections::{BTreeSet, HashMap};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, NaiveDateTime, TimeZone, Utc};
+use ldap3::{Scope, SearchEntry};
+use tracing::debug;
+
+use crate::connection::LdapConnection;
+use crate::error::CoreError;
+use crate::trace::{TraceOp, TraceRecord};
+use crate::util::find_values_ci;
+
+/// A partner this far behind is shown as lagging.
+pub const WARN_LAG: Duration = Duration::from_secs(60 * 60);
+/// A partner this far behind, or failing this many syncs in a row, is
+/// shown as failing.
+pub const FAIL_LAG: Duration = Duration::from_secs(24 * 60 * 60);
+pub const FAIL_COUNT: u32 = 3;
+
+/// Seconds from 1601-01-01, the epoch of `repsFrom` times, to 1970-01-01.
+const EPOCH_1601_SECS: i64 = 11_644_473_600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Health {
+    Healthy,
+    Lagging,
+    Failing,
+}
+
+impl Health {
+    pub fn label(self) -> &'static str {
+        match self {
+            Health::Healthy => "OK",
+            Health::Lagging => "LAGGING",
+            Health::Failing => "FAILING",
+        }
+    }
+
+    /// Judge a partner by how far behind it is and its failed syncs in a
+    /// row. An unknown lag counts as none.
+    pub fn judge(lag: Option<Duration>, failures: u32) -> Self {
+        let lag = lag.unwrap_or_default();
+        if failures >= FAIL_COUNT || lag >= FAIL_LAG {
+            Health::Failing
+        } else if failures > 0 || lag >= WARN_LAG {
+            Health::Lagging
+        } else {
+            Health::Healthy
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// This server pulls changes from the partner (`repsFrom`).
+    Inbound,
+    /// The partner pulls changes from this server (`repsTo`).
+    Outbound,
+    /// Another server holding the same suffix, compared by `contextCSN`.
+    Peer,
+}
+
+impl Direction {
+    pub fn label(self) -> &'static str {
+        match self {
+            Direction::Inbound => "in",
+            Direction::Outbound => "out",
+            Direction::Peer => "peer",
+        }
+    }
+}
+
+/// One replication partner of one partition.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PartnerStatus {
+    pub partition: String,
+    pub partner: String,
+    pub direction: Direction,
+    pub last_success: Option<DateTime<Utc>>,
+    /// Failed syncs since the last success.
+    pub failures: u32,
+    /// Windows error code of the last attempt; 0 on success.
+    pub last_result: u32,
+    /// The partner's highest USN this server has applied.
+    pub usn: Option<u64>,
+    /// How far behind the partner, or this server, is.
+    pub lag: Option<Duration>,
+    pub health: Health,
+    /// Detail for the selected row, e.g. which side is behind.
+    pub note: String,
+}
+
+/// A `contextCSN` value: the time of the last change a server (by its
+/// server ID) made to a suffix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Csn {
+    pub time: DateTime<Utc>,
+    pub sid: String,
+}
+
+impl Csn {
+    /// Parse `20240315103000.123456Z#000000#001#000000`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts = value.split('#');
+        let time = parts.next()?;
+        let sid = parts.nth(1)?;
+        let seconds = NaiveDateTime::parse_from_str(time.get(..14)?, "%Y%m%d%H%M%S").ok()?;
+        let micros: u32 = time
+            .get(15..21)
+            .and_then(|m| m.parse().ok())
+            .unwrap_or_default();
+        let time = Utc.from_utc_datetime(&seconds) + chrono::Duration::microseconds(micros.into());
+        Some(Self {
+            time,
+            sid: sid.to_string(),
+        })
+    }
+}
+
+/// The `contextCSN` values of each suffix a server holds.
+pub type ContextCsns = Vec<(String, Vec<Csn>)>;
+
+/// The replication state seen from one server.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReplicationReport {
+    /// Active Directory's `highestCommittedUSN`.
+    pub highest_usn: Option<u64>,
+    /// OpenLDAP: this server's `contextCSN` per suffix.
+    pub contexts: ContextCsns,
+    /// OpenLDAP: `provider` URIs of the syncrepl consumers configured in
+    /// `cn=config`, where readable.
+    pub providers: Vec<String>,
+    pub partners: Vec<PartnerStatus>,
+}
+
+impl ReplicationReport {
+    /// Compare this server's suffixes with those of other servers, given
+    /// as `(name, contexts)`, adding a peer row for each suffix they share.
+    pub fn compare_with(&mut self, peers: &[(String, ContextCsns)]) {
+        for (context, local) in &self.contexts {
+            for (name, peer_contexts) in peers {
+                let Some((_, remote)) = peer_contexts
+                    .iter()
+                    .find(|(c, _)| c.eq_ignore_ascii_case(context))
+                else {
+                    continue;
+                };
+                self.partners
+                    .push(compare_csns(context, name, local, remote));
+            }
+        }
+    }
+
+    /// The worst health among the partners.
+    pub fn health(&self) -> Option<Health> {
+        self.partners.iter().map(|p| p.health).max()
+    }
+}
+
+/// Compare a suffix's CSNs on this server (`local`) and a peer's: for each
+/// server ID, the side with the older change is behind by the difference.
+pub fn compare_csns(context: &str, peer: &str, local: &[Csn], remote: &[Csn]) -> PartnerStatus {
+    let sids: BTreeSet<&str> = local.iter().chain(remote).map(|c| c.sid.as_str()).collect();
+    let find = |csns: &[Csn], sid: &str| csns.iter().find(|c| c.sid == sid).map(|c| c.time);
+
+    let mut notes = Vec::new();
+    let mut peer_behind = Duration::ZERO;
+    let mut local_behind = Duration::ZERO;
+    let mut missing = false;
+    for sid in sids {
+        match (find(local, sid), find(remote, sid)) {
+            (Some(l), Some(r)) => {
+                let diff = (l - r).to_std().unwrap_or_default();
+                peer_behind = peer_behind.max(diff);
+                local_behind = local_behind.max((r - l).to_std().unwrap_or_default());
+            }
+            (Some(_), None) => {
+                missing = true;
+                notes.push(format!("SID {} missing on the peer", sid));
+            }
+            (None, _) => {
+                missing = true;
+                notes.push(format!("SID {} missing here", sid));
+            }
+        }
+    }
+    let lag = peer_behind.max(local_behind);
+    if peer_behind > Duration::ZERO {
+        notes.push("the peer is behind".to_string());
+    } else if local_behind > Duration::ZERO {
+        notes.push("this server is behind".to_string());
+    }
+    let mut health = Health::judge(Some(lag), 0);
+    if missing {
+        health = health.max(Health::Lagging);
+    }
+    PartnerStatus {
+        partition: context.to_string(),
+        partner: peer.to_string(),
+        direction: Direction::Peer,
+        last_success: remote.iter().map(|c| c.time).max(),
+        failures: 0,
+        last_result: 0,
+        usn: None,
+        lag: Some(lag),
+        health,
+        note: notes.join("; "),
+    }
+}
+
+/// A decoded `repsFrom`/`repsTo` value (MS-DRSR REPS_FROM, version 1).
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReplicaLink {
+    pub failures: u32,
+    pub last_success: Option<DateTime<Utc>>,
+    pub last_attempt: Option<DateTime<Utc>>,
+    pub last_result: u32,
+    pub usn: u64,
+    /// The partner's `<guid>._msdcs.<forest>` DNS name.
+    pub dns_name: Option<String>,
+    /// `objectGUID` of the partner's NTDS Settings object.
+    pub dsa_guid: [u8; 16],
+}
+
+impl ReplicaLink {
+    /// Decode a binary value. Offsets are from the start of the value; the
+    /// partner name's offset is relative to the version 1 body at byte 8.
+    pub fn parse(bytes: &[u8]) -> Option<Self> {
+        let u32_at = |at: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(bytes.get(at..at + 4)?.try_into().ok()?))
+        };
+        let u64_at = |at: usize| -> Option<u64> {
+            Some(u64::from_le_bytes(bytes.get(at..at + 8)?.try_into().ok()?))
+        };
+        if u32_at(0)? != 1 {
+            return None;
+        }
+        let dns_name = u32_at(36).and_then(|offset| {
+            let start = 8 + offset as usize;
+            let len = u32_at(start)? as usize;
+            let name = bytes.get(start + 4..start + 4 + len)?;
+            let name = String::from_utf8_lossy(name);
+            let name = name.trim_end_matches('\0');
+            (!name.is_empty()).then(|| name.to_string())
+        });
+        Some(Self {
+            failures: u32_at(12)?,
+            last_success: filetime_secs(u64_at(16)?),
+            last_attempt: filetime_secs(u64_at(24)?),
+            last_result: u32_at(32)?,
+            usn: u64_at(152)?,
+            dns_name,
+            dsa_guid: bytes.get(160..176)?.try_into().ok()?,
+        })
+    }
+
+    fn status(
+        &self,
+        partition: &str,
+        partner: String,
+        direction: Direction,
+        now: DateTime<Utc>,
+    ) -> PartnerStatus {
+        let lag = self
+            .last_success
+            .map(|at| (now - at).to_std().unwrap_or_default());
+        let mut health = Health::judge(lag, self.failures);
+        let mut note = String::new();
+        if self.last_result != 0 {
+            note = format!("last attempt failed with error {}", self.last_result);
+        }
+        // repsTo rarely records times, so only inbound links are expected
+        // to have succeeded
+        if direction == Direction::Inbound && self.last_success.is_none() {
+            health = health.max(Health::Lagging);
+            if note.is_empty() {
+                note = "never replicated".to_string();
+            }
+        }
+        PartnerStatus {
+            partition: partition.to_string(),
+            partner,
+            direction,
+            last_success: self.last_success,
+            failures: self.failures,
+            last_result: self.last_result,
+            usn: (direction == Direction::Inbound).then_some(self.usn),
+            lag,
+            health,
+            note,
+        }
+    }
+}
+
+/// Seconds since 1601 as a time; 0 means never.
+fn filetime_secs(secs: u64) -> Option<DateTime<Utc>> {
+    if secs == 0 {
+        return None;
+    }
+    DateTime::from_timestamp(i64::try_from(secs).ok()? - EPOCH_1601_SECS, 0)
+}
+
+/// The `provider` URI of an `olcSyncrepl` value.
+pub fn syncrepl_provider(value: &str) -> Option<&str> {
+    // Skip the "{0}" ordering prefix
+    let value = value.split_once('}').map_or(value, |(_, rest)| rest);
+    value
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix("provider="))
+        .map(|p| p.trim_matches('"'))
+}
+
+/// `CN=NTDS Settings,CN=DC02,CN=Servers,...` names the server `DC02`.
+fn server_name(ntds_dn: &str) -> Option<&str> {
+    let server = ntds_dn.split(',').nth(1)?;
+    Some(server.split_once('=').map_or(server, |(_, v)| v))
+}
+
+/// A filter value matching the exact bytes.
+fn escape_bytes(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("\\{:02x}", b)).collect()
+}
+
+impl LdapConnection {
+    /// Read replication partners: `repsFrom` and `repsTo` of each naming
+    /// context on Active Directory, otherwise this server's `contextCSN`
+    /// values and configured syncrepl providers. Compare the latter with
+    /// other servers using [`ReplicationReport::compare_with`].
+    pub async fn replication_status(&mut self) -> Result<ReplicationReport, CoreError> {
+        let root_dse = self
+            .search_entry_attrs("", &["namingContexts", "highestCommittedUSN"])
+            .await?
+            .ok_or_else(|| CoreError::SearchFailed("the RootDSE is not readable".to_string()))?;
+        let contexts = find_values_ci(&root_dse.attributes, "namingContexts")
+            .cloned()
+            .unwrap_or_default();
+        let highest_usn = find_values_ci(&root_dse.attributes, "highestCommittedUSN")
+            .and_then(|v| v.first())
+            .and_then(|v| v.parse().ok());
+
+        let mut report = ReplicationReport {
+            highest_usn,
+            ..Default::default()
+        };
+        if highest_usn.is_some() {
+            let now = Utc::now();
+            let sites = format!("CN=Sites,CN=Configuration,{}", self.forest_dn().await);
+            let mut names: HashMap<[u8; 16], String> = HashMap::new();
+            for context in &contexts {
+                for (attr, direction) in [
+                    ("repsFrom", Direction::Inbound),
+                    ("repsTo", Direction::Outbound),
+                ] {
+                    let values = match self.binary_values(context, attr).await {
+                        Ok(values) => values,
+                        Err(e) => {
+                            debug!("replication_status: {} of {}: {}", attr, context, e);
+                            continue;
+                        }
+                    };
+                    for link in values.iter().filter_map(|v| ReplicaLink::parse(v)) {
+                        let partner = match names.get(&link.dsa_guid) {
+                            Some(name) => name.clone(),
+                            None => {
+                                let name = self.dsa_name(&sites, &link).await;
+                                names.insert(link.dsa_guid, name.clone());
+                                name
+                            }
+                        };
+                        report
+                            .partners
+                            .push(link.status(context, partner, direction, now));
+                    }
+                }
+            }
+        } else {
+            report.contexts = self.context_csns(&contexts).await;
+            if let Ok(entries) = self
+                .search_subtree("cn=config", "(olcSyncrepl=*)", &["olcSyncrepl"])
+                .await
+            {
+                report.providers = entries
+                    .iter()
+                    .filter_map(|e| find_values_ci(&e.attributes, "olcSyncrepl"))
+                    .flatten()
+                    .filter_map(|v| syncrepl_provider(v))
+                    .map(str::to_string)
+                    .collect();
+            }
+        }
+
+        if report.partners.is_empty() && report.contexts.iter().all(|(_, c)| c.is_empty()) {
+            return Err(CoreError::SearchFailed(
+                "no replication state found (no repsFrom, repsTo or contextCSN is readable)"
+                    .to_string(),
+            ));
+        }
+        Ok(report)
+    }
+
+    /// The `contextCSN` values of the given naming contexts, or of the
+    /// RootDSE's when `contexts` is empty.
+    pub async fn context_csns(&mut self, contexts: &[String]) -> ContextCsns {
+        let contexts = if contexts.is_empty() {
+            self.search_entry_attrs("", &["namingContexts"])
+                .await
+                .ok()
+                .flatten()
+                .and_then(|e| find_values_ci(&e.attributes, "namingContexts").cloned())
+                .unwrap_or_default()
+        } else {
+            contexts.to_vec()
+        };
+        let mut csns = Vec::new();
+        for context in contexts {
+            if let Ok(Some(entry)) = self.search_entry_attrs(&context, &["contextCSN"]).await {
+                let values: Vec<Csn> = find_values_ci(&entry.attributes, "contextCSN")
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|v| Csn::parse(v))
+                    .collect();
+                if !values.is_empty() {
+                    csns.push((context, values));
+                }
+            }
+        }
+        csns
+    }
+
+    /// Name the partner of a replica link: the server holding its NTDS
+    /// Settings object, else its DNS name.
+    async fn dsa_name(&mut self, sites: &str, link: &ReplicaLink) -> String {
+        let filter = format!(
+            "(&(objectClass=nTDSDSA)(objectGUID={}))",
+            escape_bytes(&link.dsa_guid)
+        );
+        if let Ok(entries) = self.search_subtree(sites, &filter, &["1.1"]).await {
+            if let Some(name) = entries.first().and_then(|e| server_name(&e.dn)) {
+                return name.to_string();
+            }
+        }
+        link.dns_name
+            .clone()
+            .unwrap_or_else(|| "(unknown)".to_string())
+    }
+
+    /// The forest root domain from `rootDomainNamingContext`.
+    async fn forest_dn(&mut self) -> String {
+        self.search_entry_attrs("", &["rootDomainNamingContext"])
+            .await
+            .ok()
+            .flatten()
+            .and_then(|e| {
+                find_values_ci(&e.attributes, "rootDomainNamingContext")
+                    .and_then(|v| v.first().cloned())
+            })
+            .unwrap_or_else(|| self.base_dn.clone())
+    }
+
+    /// The values of a binary attribute of `dn`, which the usual searches
+    /// drop.
+    async fn binary_values(&mut self, dn: &str, attr: &str) -> Result<Vec<Vec<u8>>, CoreError> {
+        let trace = self.trace_proxy(
+            TraceRecord::new(TraceOp::Search, dn)
+                .scope(Scope::Base)
+                .filter("(objectClass=*)")
+                .attributes([attr]),
+        );
+        let started = Instant::now();
+        let result = self
+            .request(Vec::new())
+            .search(dn, Scope::Base, "(objectClass=*)", vec![attr])
+            .await;
+        self.trace.record(trace, started, &result);
+        let (entries, _) = result
+            .map_err(CoreError::from)?
+            .success()
+            .map_err(|e| CoreError::SearchFailed(e.to_string()))?;
+        Ok(entries
+            .into_iter()
+            .flat_map(|e| {
+                let entry = SearchEntry::construct(e);
+                let bin = entry
+                    .bin_attrs
+                    .into_iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+                    .map(|(_, values)| values);
+                // Values that happen to be valid UTF-8 land in attrs
+                let text = entry
+                    .attrs
+                    .into_iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+                    .map(|(_, values)| values.into_iter().map(String::into_bytes).collect());
+                bin.into_iter().chain(text).flatten().collect::<Vec<_>>()
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn csn(value: &str) -> Csn {
+        Csn::parse(value).unwrap()
+    }
+
+    /// A version 1 link with the partner's DNS name after the body.
+    fn reps_from(failures: u32, last_success: u64, result: u32, usn: u64) -> Vec<u8> {
+        let mut bytes = vec![0u8; 208];
+        bytes[0..4].copy_from_slice(&1u32.to_le_bytes());
+        bytes[12..16].copy_from_slice(&failures.to_le_bytes());
+        bytes[16..24].copy_from_slice(&last_success.to_le_bytes());
+        bytes[24..32].copy_from_slice(&last_success.to_le_bytes());
+        bytes[32..36].copy_from_slice(&result.to_le_bytes());
+        bytes[36..40].copy_from_slice(&200u32.to_le_bytes());
+        bytes[152..160].copy_from_slice(&usn.to_le_bytes());
+        bytes[160..176].copy_from_slice(&[7u8; 16]);
+        let name = b"1234._msdcs.example.com\0";
+        bytes.extend_from_slice(&(name.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(name);
+        bytes
+    }
+
+    #[test]
+    fn test_parse_replica_link() {
+        // 2024-03-15 10:30:00 UTC
+        let secs = 1_710_498_600 + EPOCH_1601_SECS as u64;
+        let link = ReplicaLink::parse(&reps_from(0, secs, 0, 123_456)).unwrap();
+        assert_eq!(
+            link.last_success.unwrap().to_rfc3339(),
+            "2024-03-15T10:30:00+00:00"
+        );
+        assert_eq!(link.usn, 123_456);
+        assert_eq!(link.dns_name.as_deref(), Some("1234._msdcs.example.com"));
+        assert_eq!(link.dsa_guid, [7u8; 16]);
+        assert!(ReplicaLink::parse(&[2, 0, 0, 0]).is_none());
+
+        let now = link.last_success.unwrap() + chrono::Duration::minutes(5);
+        let status = link.status("dc=example,dc=com", "DC02".into(), Direction::Inbound, now);
+        assert_eq!(status.health, Health::Healthy);
+        assert_eq!(status.lag, Some(Duration::from_secs(300)));
+
+        let failing = ReplicaLink::parse(&reps_from(4, secs, 8524, 1)).unwrap();
+        let status = failing.status("dc=example,dc=com", "DC03".into(), Direction::Inbound, now);
+        assert_eq!(status.health, Health::Failing);
+        assert_eq!(status.note, "last attempt failed with error 8524");
+
+        let never = ReplicaLink::parse(&reps_from(0, 0, 0, 0)).unwrap();
+        let status = never.status("dc=example,dc=com", "DC04".into(), Direction::Inbound, now);
+        assert_eq!(status.health, Health::Lagging);
+        let status = never.status("dc=example,dc=com", "DC04".into(), Direction::Outbound, now);
+        assert_eq!(status.health, Health::Healthy);
+    }
+
+    #[test]
+    fn test_compare_csns() {
+        let mut report = ReplicationReport {
+            contexts: vec![(
+                "dc=example,dc=com".to_string(),
+                vec![
+                    csn("20240315103000.000000Z#000000#001#000000"),
+                    csn("20240315090000.000000Z#000000#002#000000"),
+                ],
+            )],
+            ..Default::default()
+        };
+        let peers = vec![
+            (
+                "ldap2".to_string(),
+                vec![(
+                    "DC=example,DC=com".to_string(),
+                    vec![
+                        csn("20240315100000.000000Z#000000#001#000000"),
+                        csn("20240315090000.000000Z#000000#002#000000"),
+                    ],
+                )],
+            ),
+            (
+                "ldap3".to_string(),
+                vec![(
+                    "dc=example,dc=com".to_string(),
+                    vec![csn("20240315103000.000000Z#000000#001#000000")],
+                )],
+            ),
+            ("other".to_string(), vec![("dc=other".to_string(), vec![])]),
+        ];
+        report.compare_with(&peers);
+        assert_eq!(report.partners.len(), 2);
+        assert_eq!(report.partners[0].lag, Some(Duration::from_secs(30 * 60)));
+        assert_eq!(report.partners[0].health, Health::Healthy);
+        assert_eq!(report.partners[0].note, "the peer is behind");
+        assert_eq!(report.partners[1].health, Health::Lagging);
+        assert_eq!(report.partners[1].note, "SID 002 missing on the peer");
+        assert_eq!(report.health(), Some(Health::Lagging));
+    }
+
+    #[test]
+    fn test_judge_and_provider() {
+        assert_eq!(Health::judge(None, 0), Health::Healthy);
+        assert_eq!(Health::judge(Some(WARN_LAG), 0), Health::Lagging);
+        assert_eq!(Health::judge(Some(FAIL_LAG), 0), Health::Failing);
+        assert_eq!(Health::judge(None, 1), Health::Lagging);
+        assert_eq!(Health::judge(None, FAIL_COUNT), Health::Failing);
+        assert_eq!(
+            syncrepl_provider("{0}rid=001 provider=ldap://ldap1.example.com:389 bindmethod=simple"),
+            Some("ldap://ldap1.example.com:389")
+        );
+        assert_eq!(
+            server_name("CN=NTDS Settings,CN=DC02,CN=Servers,CN=Default,CN=Sites"),
+            Some("DC02")
+        );
+    }
+}
@@ -0,0 +1,622 @@
+//! Canned account reports: expiring, inactive, password-never-expires and
+//! disabled accounts, for Active Directory and OpenLDAP; and reports
+//! defined in the config (`[[reports]]`).
+//!
+//! Each canned report builds a server-side filter for the directory
+//! flavour and re-checks every returned entry client-side, so attributes
+//! without an ordering rule (shadowExpire) and offline snapshots give the
+//! same answer.
+//!
+//! A defined report's filter is a template with `{parameter}` placeholders:
+//!
+//! ```toml
+//! [[reports]]
+//! name = "Contractors leaving"
+//! filter = "(&(employeeType={type})(accountExpires<={days:filetime}))"
+//! columns = ["sAMAccountName", "accountExpires"]
+//! parameters = [{ name = "type", default = "contractor" }, { name = "days", default = "30" }]
+//! decode = { accountExpires = "filetime" }
+//! ```
+
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Duration, NaiveDateTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::connection::LdapConnection;
+use crate::entry::LdapEntry;
+use crate::error::CoreError;
+use crate::filter::escape_filter_value;
+use crate::format::{DisplayFormats, FormatRule};
+use crate::util::find_values_ci;
+
+const UF_ACCOUNTDISABLE: i64 = 0x2;
+const UF_DONT_EXPIRE_PASSWD: i64 = 0x1_0000;
+/// Seconds between 1601-01-01 (the FILETIME epoch) and 1970-01-01.
+const FILETIME_EPOCH_OFFSET: i64 = 11_644_473_600;
+/// Active Directory's account times, kept as FILETIMEs.
+pub(crate) const FILETIME_ATTRIBUTES: [&str; 6] = [
+    "pwdLastSet",
+    "lastLogon",
+    "lastLogonTimestamp",
+    "accountExpires",
+    "lockoutTime",
+    "badPasswordTime",
+];
+/// shadowMax values this large mean the password never has to change.
+const SHADOW_MAX_NEVER: i64 = 99_999;
+/// pwdAccountLockedTime for an account locked until an administrator
+/// unlocks it.
+const PPOLICY_ADMIN_LOCK: &str = "000001010000Z";
+
+const AD_USERS: &str = "(objectCategory=person)(objectClass=user)";
+const AD_ENABLED: &str = "(!(userAccountControl:1.2.840.113556.1.4.803:=2))";
+
+/// A canned report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Report {
+    /// Accounts whose expiry date falls within the next N days.
+    ExpiringAccounts,
+    /// Enabled accounts with no logon in the last N days.
+    InactiveAccounts,
+    /// Accounts whose password is exempt from expiry.
+    PasswordNeverExpires,
+    /// Disabled or administratively locked accounts.
+    DisabledAccounts,
+}
+
+impl Report {
+    pub const ALL: [Report; 4] = [
+        Report::ExpiringAccounts,
+        Report::InactiveAccounts,
+        Report::PasswordNeverExpires,
+        Report::DisabledAccounts,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Report::ExpiringAccounts => "Accounts expiring in N days",
+            Report::InactiveAccounts => "Accounts not logged in for N days",
+            Report::PasswordNeverExpires => "Users with password never expires",
+            Report::DisabledAccounts => "Disabled accounts",
+        }
+    }
+
+    /// The name used on the `:report` command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Report::ExpiringAccounts => "expiring",
+            Report::InactiveAccounts => "inactive",
+            Report::PasswordNeverExpires => "neverexpires",
+            Report::DisabledAccounts => "disabled",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Report> {
+        Report::ALL
+            .into_iter()
+            .find(|r| r.name().eq_ignore_ascii_case(name))
+    }
+
+    /// The default N for reports that take a number of days.
+    pub fn default_days(self) -> Option<u32> {
+        match self {
+            Report::ExpiringAccounts => Some(30),
+            Report::InactiveAccounts => Some(90),
+            Report::PasswordNeverExpires | Report::DisabledAccounts => None,
+        }
+    }
+
+    /// A title for one run, e.g. "Accounts not logged in for 90 days".
+    pub fn title(self, days: u32) -> String {
+        self.label().replace('N', &days.to_string())
+    }
+}
+
+/// One run of a report with its parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ReportQuery {
+    pub report: Report,
+    pub days: u32,
+    /// Whether to use Active Directory attributes rather than OpenLDAP's
+    /// shadowAccount and ppolicy ones.
+    pub active_directory: bool,
+    pub now: DateTime<Utc>,
+}
+
+impl ReportQuery {
+    pub fn new(report: Report, days: u32, active_directory: bool) -> Self {
+        Self {
+            report,
+            days,
+            active_directory,
+            now: Utc::now(),
+        }
+    }
+
+    fn horizon(&self) -> DateTime<Utc> {
+        self.now + Duration::days(i64::from(self.days))
+    }
+
+    fn cutoff(&self) -> DateTime<Utc> {
+        self.now - Duration::days(i64::from(self.days))
+    }
+
+    /// The filter sent to the server.
+    pub fn filter(&self) -> String {
+        if self.active_directory {
+            match self.report {
+                Report::ExpiringAccounts => format!(
+                    "(&{}(accountExpires>={})(accountExpires<={}))",
+                    AD_USERS,
+                    to_filetime(self.now),
+                    to_filetime(self.horizon())
+                ),
+                // Accounts that never logged on count once they are older
+                // than the cutoff
+                Report::InactiveAccounts => format!(
+                    "(&{}{}(|(lastLogonTimestamp<={})(&(!(lastLogonTimestamp=*))(whenCreated<={}))))",
+                    AD_USERS,
+                    AD_ENABLED,
+                    to_filetime(self.cutoff()),
+                    to_generalized_time(self.cutoff())
+                ),
+                Report::PasswordNeverExpires => format!(
+                    "(&{}(userAccountControl:1.2.840.113556.1.4.803:=65536))",
+                    AD_USERS
+                ),
+                Report::DisabledAccounts => format!(
+                    "(&{}(userAccountControl:1.2.840.113556.1.4.803:=2))",
+                    AD_USERS
+                ),
+            }
+        } else {
+            match self.report {
+                // shadowExpire has no ordering rule in the NIS schema
+                Report::ExpiringAccounts => "(shadowExpire=*)".to_string(),
+                Report::InactiveAccounts => {
+                    let cutoff = to_generalized_time(self.cutoff());
+                    format!("(|(pwdLastSuccess<={})(authTimestamp<={}))", cutoff, cutoff)
+                }
+                Report::PasswordNeverExpires => "(shadowMax=*)".to_string(),
+                Report::DisabledAccounts => {
+                    format!("(pwdAccountLockedTime={})", PPOLICY_ADMIN_LOCK)
+                }
+            }
+        }
+    }
+
+    /// The attributes to request: all user attributes plus the operational
+    /// ones the report reads.
+    pub fn attributes(&self) -> Vec<&'static str> {
+        let mut attrs = vec!["*"];
+        if !self.active_directory {
+            match self.report {
+                Report::InactiveAccounts => attrs.extend(["pwdLastSuccess", "authTimestamp"]),
+                Report::DisabledAccounts => attrs.push("pwdAccountLockedTime"),
+                _ => {}
+            }
+        }
+        attrs
+    }
+
+    /// Whether `entry` belongs in the report.
+    pub fn matches(&self, entry: &LdapEntry) -> bool {
+        if self.active_directory {
+            let is_user = has_value(entry, "objectClass", "user")
+                && !has_value(entry, "objectClass", "computer");
+            let uac = number(entry, "userAccountControl").unwrap_or(0);
+            is_user
+                && match self.report {
+                    Report::ExpiringAccounts => first(entry, "accountExpires")
+                        .and_then(from_filetime)
+                        .is_some_and(|at| at >= self.now && at <= self.horizon()),
+                    Report::InactiveAccounts => {
+                        let cutoff = self.cutoff();
+                        uac & UF_ACCOUNTDISABLE == 0
+                            && match first(entry, "lastLogonTimestamp") {
+                                // A never-set timestamp (0) counts as before any cutoff
+                                Some(value) => !from_filetime(value).is_some_and(|at| at > cutoff),
+                                None => first(entry, "whenCreated")
+                                    .and_then(from_generalized_time)
+                                    .is_some_and(|at| at <= cutoff),
+                            }
+                    }
+                    Report::PasswordNeverExpires => uac & UF_DONT_EXPIRE_PASSWD != 0,
+                    Report::DisabledAccounts => uac & UF_ACCOUNTDISABLE != 0,
+                }
+        } else {
+            match self.report {
+                Report::ExpiringAccounts => number(entry, "shadowExpire")
+                    .filter(|days| *days >= 0)
+                    .and_then(|days| DateTime::from_timestamp(days * 86_400, 0))
+                    .is_some_and(|at| at >= day_start(self.now) && at <= self.horizon()),
+                Report::InactiveAccounts => {
+                    let cutoff = self.cutoff();
+                    let last = ["pwdLastSuccess", "authTimestamp"]
+                        .iter()
+                        .filter_map(|attr| first(entry, attr).and_then(from_generalized_time))
+                        .max();
+                    last.is_some_and(|at| at <= cutoff)
+                }
+                Report::PasswordNeverExpires => number(entry, "shadowMax")
+                    .is_some_and(|max| !(0..SHADOW_MAX_NEVER).contains(&max)),
+                Report::DisabledAccounts => {
+                    has_value(entry, "pwdAccountLockedTime", PPOLICY_ADMIN_LOCK)
+                }
+            }
+        }
+    }
+
+    /// Keep only the entries that belong in the report, e.g. from an
+    /// offline snapshot.
+    pub fn apply(&self, entries: Vec<LdapEntry>) -> Vec<LdapEntry> {
+        entries.into_iter().filter(|e| self.matches(e)).collect()
+    }
+}
+
+/// A report defined in the config.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportDefinition {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub description: String,
+    /// The search filter, with `{parameter}` placeholders.
+    pub filter: String,
+    /// Where to search; unset means the connection's base DN.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base_dn: Option<String>,
+    /// Attributes to request, show and export; empty means all.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub columns: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub parameters: Vec<ReportParameter>,
+    /// Attribute name to renderer, applied to the values of the results,
+    /// e.g. `accountExpires = "filetime"`.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub decode: BTreeMap<String, FormatRule>,
+}
+
+/// A parameter of a defined report.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ReportParameter {
+    pub name: String,
+    /// The value used unless another is given for the run; without one a
+    /// value is required.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default: Option<String>,
+}
+
+impl ReportDefinition {
+    /// The values for a run: each parameter's default, overridden by
+    /// `overrides`.
+    pub fn values(
+        &self,
+        overrides: &[(String, String)],
+    ) -> Result<BTreeMap<String, String>, String> {
+        let mut values = BTreeMap::new();
+        for (name, value) in overrides {
+            if !self.parameters.iter().any(|p| p.name == *name) {
+                return Err(format!("report {} has no parameter {}", self.name, name));
+            }
+            values.insert(name.clone(), value.clone());
+        }
+        for param in &self.parameters {
+            if !values.contains_key(&param.name) {
+                let value = param
+                    .default
+                    .clone()
+                    .ok_or_else(|| format!("parameter {} needs a value", param.name))?;
+                values.insert(param.name.clone(), value);
+            }
+        }
+        Ok(values)
+    }
+
+    /// The filter with its placeholders filled in. `{name}` inserts the
+    /// value escaped, `{name:raw}` as typed (for wildcards). For dates the
+    /// value is a number of days from `now`, negative for the past:
+    /// `{name:filetime}` inserts an AD FILETIME, `{name:generalized}` a
+    /// GeneralizedTime and `{name:epochdays}` days since 1970 (shadowExpire).
+    pub fn render_filter(
+        &self,
+        values: &BTreeMap<String, String>,
+        now: DateTime<Utc>,
+    ) -> Result<String, String> {
+        let mut filter = String::new();
+        let mut rest = self.filter.as_str();
+        while let Some(start) = rest.find('{') {
+            filter.push_str(&rest[..start]);
+            let end = rest[start..]
+                .find('}')
+                .ok_or_else(|| format!("unclosed {{ in the filter of {}", self.name))?;
+            let placeholder = &rest[start + 1..start + end];
+            let (name, kind) = placeholder.split_once(':').unwrap_or((placeholder, ""));
+            let value = values
+                .get(name)
+                .ok_or_else(|| format!("unknown parameter {{{}}}", name))?;
+            let days = || {
+                value
+                    .trim()
+                    .parse::<i64>()
+                    .map(|days| now + Duration::days(days))
+                    .map_err(|_| format!("{} must be a number of days, not {}", name, value))
+            };
+            match kind {
+                "" => filter.push_str(&escape_filter_value(value)),
+                "raw" => filter.push_str(value),
+                "filetime" => filter.push_str(&to_filetime(days()?).to_string()),
+                "generalized" => filter.push_str(&to_generalized_time(days()?)),
+                "epochdays" => filter.push_str(&days()?.timestamp().div_euclid(86_400).to_string()),
+                other => return Err(format!("unknown conversion {{{}:{}}}", name, other)),
+            }
+            rest = &rest[start + end + 1..];
+        }
+        filter.push_str(rest);
+        Ok(filter)
+    }
+
+    /// The attributes to request.
+    pub fn attributes(&self) -> Vec<String> {
+        if self.columns.is_empty() {
+            vec!["*".to_string()]
+        } else {
+            self.columns.clone()
+        }
+    }
+
+    /// `entries` with the values of decoded attributes replaced by their
+    /// rendering. Values a rule can't read are kept as they are.
+    pub fn decode(&self, mut entries: Vec<LdapEntry>) -> Vec<LdapEntry> {
+        if self.decode.is_empty() {
+            return entries;
+        }
+        let (formats, _) = DisplayFormats::new(&self.decode);
+        for entry in &mut entries {
+            for (attr, values) in entry.attributes.iter_mut() {
+                for value in values.iter_mut() {
+                    if let Some(decoded) = formats.format(attr, value) {
+                        *value = decoded;
+                    }
+                }
+            }
+        }
+        entries
+    }
+}
+
+fn first<'a>(entry: &'a LdapEntry, attr: &str) -> Option<&'a str> {
+    find_values_ci(&entry.attributes, attr)
+        .and_then(|values| values.first())
+        .map(String::as_str)
+}
+
+fn number(entry: &LdapEntry, attr: &str) -> Option<i64> {
+    first(entry, attr).and_then(|v| v.trim().parse().ok())
+}
+
+fn has_value(entry: &LdapEntry, attr: &str, value: &str) -> bool {
+    find_values_ci(&entry.attributes, attr)
+        .is_some_and(|values| values.iter().any(|v| v.eq_ignore_ascii_case(value)))
+}
+
+pub(crate) fn day_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    at - Duration::seconds(at.timestamp().rem_euclid(86_400))
+}
+
+pub(crate) fn to_filetime(at: DateTime<Utc>) -> i64 {
+    (at.timestamp() + FILETIME_EPOCH_OFFSET) * 10_000_000
+}
+
+/// An AD FILETIME; 0 and the maximum mean never.
+fn from_filetime(value: &str) -> Option<DateTime<Utc>> {
+    match value.trim().parse::<i64>().ok()? {
+        0 | i64::MAX => None,
+        ticks => DateTime::from_timestamp(ticks / 10_000_000 - FILETIME_EPOCH_OFFSET, 0),
+    }
+}
+
+pub(crate) fn to_generalized_time(at: DateTime<Utc>) -> String {
+    at.format("%Y%m%d%H%M%SZ").to_string()
+}
+
+fn from_generalized_time(value: &str) -> Option<DateTime<Utc>> {
+    let t = NaiveDateTime::parse_from_str(value.get(..14)?, "%Y%m%d%H%M%S").ok()?;
+    Some(Utc.from_utc_datetime(&t))
+}
+
+impl LdapConnection {
+    /// Run a canned report over the subtree at `base_dn`.
+    pub async fn run_report(
+        &mut self,
+        base_dn: &str,
+        query: &ReportQuery,
+    ) -> Result<Vec<LdapEntry>, CoreError> {
+        let entries = self
+            .search_subtree(base_dn, &query.filter(), &query.attributes())
+            .await?;
+        Ok(query.apply(entries))
+    }
+
+    /// Run a defined report with the parameter `values`, searching its
+    /// own base DN or else `base_dn`.
+    pub async fn run_report_definition(
+        &mut self,
+        base_dn: &str,
+        report: &ReportDefinition,
+        values: &BTreeMap<String, String>,
+    ) -> Result<Vec<LdapEntry>, CoreError> {
+        let filter = report
+            .render_filter(values, Utc::now())
+            .map_err(CoreError::SearchFailed)?;
+        let base_dn = report.base_dn.as_deref().unwrap_or(base_dn);
+        let attrs = report.attributes();
+        let attrs: Vec<&str> = attrs.iter().map(String::as_str).collect();
+        let entries = self.search_subtree(base_dn, &filter, &attrs).await?;
+        Ok(report.decode(entries))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    fn query(report: Report, days: u32, active_directory: bool) -> ReportQuery {
+        ReportQuery {
+            report,
+            days,
+            active_directory,
+            now: Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap(),
+        }
+    }
+
+    fn ad_user(extra: &[(&str, &[&str])]) -> LdapEntry {
+        let mut attrs: Vec<(&str, &[&str])> = vec![
+            ("objectClass", &["top"]),
+            ("objectClass", &["person"]),
+            ("objectClass", &["user"]),
+        ];
+        attrs.extend_from_slice(extra);
+        test_entry("cn=jdoe,cn=Users,dc=example,dc=com", &attrs)
+    }
+
+    #[test]
+    fn test_active_directory_filters() {
+        let q = query(Report::InactiveAccounts, 90, true);
+        let filter = q.filter();
+        assert!(filter.contains("(lastLogonTimestamp<=133472016000000000)"));
+        assert!(filter.contains("(whenCreated<=20231216120000Z)"));
+        assert!(filter.contains(AD_ENABLED));
+        assert_eq!(
+            query(Report::ExpiringAccounts, 30, false).filter(),
+            "(shadowExpire=*)"
+        );
+        assert_eq!(
+            query(Report::InactiveAccounts, 90, false).attributes(),
+            vec!["*", "pwdLastSuccess", "authTimestamp"]
+        );
+    }
+
+    #[test]
+    fn test_active_directory_matches() {
+        let q = query(Report::InactiveAccounts, 90, true);
+        let old = to_filetime(q.now - Duration::days(120)).to_string();
+        let recent = to_filetime(q.now - Duration::days(5)).to_string();
+        assert!(q.matches(&ad_user(&[("lastLogonTimestamp", &[old.as_str()])])));
+        assert!(!q.matches(&ad_user(&[("lastLogonTimestamp", &[recent.as_str()])])));
+        assert!(!q.matches(&ad_user(&[
+            ("lastLogonTimestamp", &[old.as_str()]),
+            ("userAccountControl", &["514"]),
+        ])));
+        // Never logged on: only once the account is older than the cutoff
+        assert!(q.matches(&ad_user(&[("whenCreated", &["20230101000000.0Z"])])));
+        assert!(!q.matches(&ad_user(&[("whenCreated", &["20240301000000.0Z"])])));
+
+        let q = query(Report::ExpiringAccounts, 30, true);
+        let soon = to_filetime(q.now + Duration::days(10)).to_string();
+        let later = to_filetime(q.now + Duration::days(60)).to_string();
+        assert!(q.matches(&ad_user(&[("accountExpires", &[soon.as_str()])])));
+        assert!(!q.matches(&ad_user(&[("accountExpires", &[later.as_str()])])));
+        assert!(!q.matches(&ad_user(&[("accountExpires", &["9223372036854775807"])])));
+
+        let q = query(Report::PasswordNeverExpires, 0, true);
+        assert!(q.matches(&ad_user(&[("userAccountControl", &["66048"])])));
+        assert!(!q.matches(&ad_user(&[("userAccountControl", &["512"])])));
+    }
+
+    #[test]
+    fn test_openldap_matches() {
+        let q = query(Report::ExpiringAccounts, 30, false);
+        // 2024-03-15 is day 19797
+        assert!(q.matches(&test_entry("uid=a", &[("shadowExpire", &["19797"])])));
+        assert!(q.matches(&test_entry("uid=a", &[("shadowExpire", &["19820"])])));
+        assert!(!q.matches(&test_entry("uid=a", &[("shadowExpire", &["19796"])])));
+        assert!(!q.matches(&test_entry("uid=a", &[("shadowExpire", &["-1"])])));
+
+        let q = query(Report::InactiveAccounts, 90, false);
+        assert!(q.matches(&test_entry(
+            "uid=a",
+            &[("pwdLastSuccess", &["20230101000000Z"])]
+        )));
+        // The most recent of the two timestamps decides
+        assert!(!q.matches(&test_entry(
+            "uid=a",
+            &[
+                ("pwdLastSuccess", &["20230101000000Z"]),
+                ("authTimestamp", &["20240310000000Z"]),
+            ]
+        )));
+
+        let q = query(Report::PasswordNeverExpires, 0, false);
+        assert!(q.matches(&test_entry("uid=a", &[("shadowMax", &["99999"])])));
+        assert!(!q.matches(&test_entry("uid=a", &[("shadowMax", &["90"])])));
+
+        let q = query(Report::DisabledAccounts, 0, false);
+        assert!(q.matches(&test_entry(
+            "uid=a",
+            &[("pwdAccountLockedTime", &[PPOLICY_ADMIN_LOCK])]
+        )));
+
+        assert_eq!(
+            Report::from_name("Inactive"),
+            Some(Report::InactiveAccounts)
+        );
+        assert_eq!(
+            Report::InactiveAccounts.title(90),
+            "Accounts not logged in for 90 days"
+        );
+    }
+
+    #[test]
+    fn test_report_definition() {
+        let report: ReportDefinition = toml::from_str(
+            r#"
+name = "Contractors leaving"
+filter = "(&(employeeType={type})(cn={prefix:raw})(accountExpires<={days:filetime})(shadowExpire<={days:epochdays}))"
+columns = ["cn", "accountExpires"]
+parameters = [{ name = "type", default = "contractor" }, { name = "days", default = "30" }, { name = "prefix" }]
+decode = { accountExpires = "filetime" }
+"#,
+        )
+        .unwrap();
+        let now = Utc.with_ymd_and_hms(2024, 3, 15, 12, 0, 0).unwrap();
+
+        assert!(report
+            .values(&[])
+            .unwrap_err()
+            .contains("prefix needs a value"));
+        assert!(report
+            .values(&[("nope".to_string(), "x".to_string())])
+            .is_err());
+        let values = report
+            .values(&[
+                ("prefix".to_string(), "a*".to_string()),
+                ("type".to_string(), "temp (agency)".to_string()),
+            ])
+            .unwrap();
+        let filter = report.render_filter(&values, now).unwrap();
+        assert_eq!(
+            filter,
+            format!(
+                "(&(employeeType=temp \\28agency\\29)(cn=a*)(accountExpires<={})(shadowExpire<=19827))",
+                to_filetime(now + Duration::days(30))
+            )
+        );
+
+        let mut bad = report.clone();
+        bad.filter = "(cn={days:hex})".to_string();
+        assert!(bad.render_filter(&values, now).is_err());
+
+        let decoded = report.decode(vec![test_entry(
+            "cn=a",
+            &[("accountExpires", &["133515360000000000"]), ("cn", &["a"])],
+        )]);
+        assert_ne!(
+            decoded[0].first_value("accountExpires"),
+            Some("133515360000000000")
+        );
+        assert_eq!(decoded[0].first_value("cn"), Some("a"));
+        assert_eq!(report.attributes(), vec!["cn", "accountExpires"]);
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::{BTreeMap, BTreeSet};
+use std::time::Instant;
 
 use ldap3::{Scope, SearchEntry};
 use serde::{Deserialize, Serialize};
@@ -6,6 +7,7 @@ use tracing::{debug, error, info, warn};
 
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::trace::{TraceOp, TraceRecord};
 use crate::util::find_values_ci;
 
 /// Known LDAP attribute syntaxes mapped to friendly types.
@@ -23,19 +25,125 @@ pub enum AttributeSyntax {
     Other(String),
 }
 
+impl AttributeSyntax {
+    /// Check a value against the syntax's string encoding (RFC 4517), so
+    /// obvious mistakes are caught before the server rejects them with
+    /// invalidAttributeSyntax. Syntaxes without a checkable form accept
+    /// anything.
+    pub fn validate(&self, value: &str) -> Result<(), String> {
+        match self {
+            AttributeSyntax::Integer => {
+                let digits = value.strip_prefix('-').unwrap_or(value);
+                if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+                    return Err(format!("'{}' is not an integer", value));
+                }
+                Ok(())
+            }
+            AttributeSyntax::Boolean => match value {
+                "TRUE" | "FALSE" => Ok(()),
+                _ => Err(format!("'{}' is not a boolean (use TRUE or FALSE)", value)),
+            },
+            AttributeSyntax::GeneralizedTime => validate_generalized_time(value).map_err(|e| {
+                format!(
+                    "'{}' is not a GeneralizedTime ({}; e.g. 20261014093000Z)",
+                    value, e
+                )
+            }),
+            AttributeSyntax::Dn => crate::dn::validate_dn(value)
+                .map_err(|e| format!("'{}' is not a valid DN: {}", value, e)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Check `YYYYMMDDHH[MM[SS]][(.|,)fraction](Z|+HH[MM]|-HH[MM])`.
+fn validate_generalized_time(value: &str) -> Result<(), String> {
+    let zone_at = value
+        .find(['Z', '+', '-'])
+        .ok_or_else(|| "missing time zone".to_string())?;
+    let (time, zone) = value.split_at(zone_at);
+    let (clock, fraction) = match time.find(['.', ',']) {
+        Some(i) => (&time[..i], Some(&time[i + 1..])),
+        None => (time, None),
+    };
+    if !clock.bytes().all(|b| b.is_ascii_digit()) || ![10, 12, 14].contains(&clock.len()) {
+        return Err("expected YYYYMMDDHH[MM[SS]]".to_string());
+    }
+    if fraction.is_some_and(|f| f.is_empty() || !f.bytes().all(|b| b.is_ascii_digit())) {
+        return Err("invalid fraction".to_string());
+    }
+    let field = |range: std::ops::Range<usize>| clock.get(range).and_then(|d| d.parse().ok());
+    let (year, month, day) = (field(0..4), field(4..6), field(6..8));
+    let valid_date = matches!((year, month, day), (Some(y), Some(m), Some(d))
+        if chrono::NaiveDate::from_ymd_opt(y as i32, m, d).is_some());
+    if !valid_date {
+        return Err("invalid date".to_string());
+    }
+    if field(8..10).unwrap_or(0) > 23
+        || field(10..12).unwrap_or(0) > 59
+        || field(12..14).unwrap_or(0) > 60
+    {
+        return Err("invalid time".to_string());
+    }
+    let offset = zone.get(1..).unwrap_or("");
+    let offset_part = |range: std::ops::Range<usize>| -> Option<u32> {
+        offset.get(range).map_or(Some(0), |d| d.parse().ok())
+    };
+    let valid_zone = zone == "Z"
+        || ((offset.len() == 2 || offset.len() == 4)
+            && offset.bytes().all(|b| b.is_ascii_digit())
+            && offset_part(0..2).is_some_and(|h| h <= 23)
+            && offset_part(2..4).is_some_and(|m| m <= 59));
+    if !valid_zone {
+        return Err("invalid time zone".to_string());
+    }
+    Ok(())
+}
+
+/// What the schema allows for one attribute's values.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueRules {
+    pub attr: String,
+    pub syntax: AttributeSyntax,
+    pub single_value: bool,
+}
+
+impl ValueRules {
+    /// Check the full set of values the attribute would hold after a
+    /// modification.
+    pub fn check(&self, values: &[&str]) -> Result<(), String> {
+        if self.single_value && values.len() > 1 {
+            return Err(format!(
+                "{} is single-valued and already has a value",
+                self.attr
+            ));
+        }
+        values.iter().try_for_each(|v| self.syntax.validate(v))
+    }
+}
+
 /// An LDAP attribute type definition from the schema.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AttributeTypeInfo {
     pub oid: String,
     pub names: Vec<String>,
     pub description: Option<String>,
     pub syntax: AttributeSyntax,
+    /// The SYNTAX OID, without any length bound.
+    pub syntax_oid: Option<String>,
+    /// The length bound on the syntax (`{64}`), if any.
+    pub syntax_length: Option<u32>,
+    /// The SUP attribute type this one derives from.
+    pub superior: Option<String>,
+    pub equality: Option<String>,
+    pub ordering: Option<String>,
+    pub substr: Option<String>,
     pub single_value: bool,
     pub no_user_modification: bool,
 }
 
 /// An LDAP object class definition.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ObjectClassInfo {
     pub oid: String,
     pub names: Vec<String>,
@@ -46,15 +154,26 @@ pub struct ObjectClassInfo {
     pub may: Vec<String>,
 }
 
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ObjectClassKind {
     Abstract,
     Structural,
     Auxiliary,
 }
 
+impl ObjectClassKind {
+    /// The schema definition keyword for this kind.
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            ObjectClassKind::Abstract => "ABSTRACT",
+            ObjectClassKind::Structural => "STRUCTURAL",
+            ObjectClassKind::Auxiliary => "AUXILIARY",
+        }
+    }
+}
+
 /// Cached schema information for a connection.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SchemaCache {
     pub attribute_types: BTreeMap<String, AttributeTypeInfo>,
     pub object_classes: BTreeMap<String, ObjectClassInfo>,
@@ -74,6 +193,11 @@ impl SchemaCache {
         self.attribute_types.get(&name_lower)
     }
 
+    /// Lookup an object class by name (case-insensitive).
+    pub fn get_object_class(&self, name: &str) -> Option<&ObjectClassInfo> {
+        self.object_classes.get(&name.to_lowercase())
+    }
+
     /// Get the syntax for an attribute name.
     pub fn attribute_syntax(&self, name: &str) -> AttributeSyntax {
         self.get_attribute_type(name)
@@ -88,6 +212,41 @@ impl SchemaCache {
             .unwrap_or(false)
     }
 
+    /// Whether values of an attribute can be matched for equality, so a
+    /// single value can be deleted: it, or a SUP it derives from, names an
+    /// EQUALITY rule. Attributes the schema doesn't define are assumed to.
+    pub fn has_equality_rule(&self, name: &str) -> bool {
+        let mut at = match self.get_attribute_type(name) {
+            Some(at) => at,
+            None => return true,
+        };
+        // Bounded, in case of a SUP cycle in a broken schema
+        for _ in 0..16 {
+            if at.equality.is_some() {
+                return true;
+            }
+            match at
+                .superior
+                .as_deref()
+                .and_then(|sup| self.get_attribute_type(sup))
+            {
+                Some(sup) => at = sup,
+                None => return false,
+            }
+        }
+        false
+    }
+
+    /// Validation rules for an attribute, or `None` when the schema
+    /// doesn't define it.
+    pub fn value_rules(&self, name: &str) -> Option<ValueRules> {
+        self.get_attribute_type(name).map(|at| ValueRules {
+            attr: name.to_string(),
+            syntax: at.syntax.clone(),
+            single_value: at.single_value,
+        })
+    }
+
     /// Return all allowed attributes for the given object classes,
     /// walking the superior chain to collect inherited MUST/MAY attrs.
     /// Filters out `no_user_modification` attributes.
@@ -123,6 +282,143 @@ impl SchemaCache {
         }
     }
 
+    /// The canonical (first) name of an attribute type, or `name` unchanged
+    /// when the schema doesn't define it.
+    pub fn canonical_attribute_name(&self, name: &str) -> String {
+        self.get_attribute_type(name)
+            .and_then(|at| at.names.first().cloned())
+            .unwrap_or_else(|| name.to_string())
+    }
+
+    /// Split the attributes of the given object classes (including inherited
+    /// ones) into MUST and MAY lists, each sorted case-insensitively and using
+    /// canonical names. An attribute required by any class only appears in MUST.
+    pub fn must_may_attributes(&self, object_classes: &[&str]) -> (Vec<String>, Vec<String>) {
+        let mut must = BTreeMap::new();
+        let mut may = BTreeMap::new();
+        let mut visited = BTreeSet::new();
+        for oc_name in object_classes {
+            let mut current = Some(oc_name.to_lowercase());
+            while let Some(oc_lower) = current.take() {
+                if !visited.insert(oc_lower.clone()) {
+                    break;
+                }
+                let Some(oc) = self.object_classes.get(&oc_lower) else {
+                    break;
+                };
+                for a in &oc.must {
+                    let name = self.canonical_attribute_name(a);
+                    must.insert(name.to_lowercase(), name);
+                }
+                for a in &oc.may {
+                    let name = self.canonical_attribute_name(a);
+                    may.insert(name.to_lowercase(), name);
+                }
+                current = oc.superior.as_ref().map(|s| s.to_lowercase());
+            }
+        }
+        may.retain(|key, _| !must.contains_key(key));
+        (must.into_values().collect(), may.into_values().collect())
+    }
+
+    /// MUST attributes of `object_classes` (including inherited ones) that
+    /// none of the `present` attribute names provides, by canonical name.
+    pub fn missing_must_attributes(
+        &self,
+        object_classes: &[&str],
+        present: &[&str],
+    ) -> Vec<String> {
+        let provided: BTreeSet<String> = present
+            .iter()
+            .map(|a| self.canonical_attribute_name(a).to_lowercase())
+            .collect();
+        let (must, _) = self.must_may_attributes(object_classes);
+        must.into_iter()
+            .filter(|a| !provided.contains(&a.to_lowercase()))
+            .collect()
+    }
+
+    /// Each object class once (they are stored under every name), sorted
+    /// by first name.
+    pub fn unique_object_classes(&self) -> Vec<&ObjectClassInfo> {
+        let mut seen = BTreeSet::new();
+        let mut classes: Vec<&ObjectClassInfo> = self
+            .object_classes
+            .values()
+            .filter(|oc| seen.insert(oc.oid.clone()))
+            .collect();
+        classes.sort_by(|a, b| {
+            a.names
+                .first()
+                .unwrap_or(&a.oid)
+                .cmp(b.names.first().unwrap_or(&b.oid))
+        });
+        classes
+    }
+
+    /// Each attribute type once, sorted by first name.
+    pub fn unique_attribute_types(&self) -> Vec<&AttributeTypeInfo> {
+        let mut seen = BTreeSet::new();
+        let mut types: Vec<&AttributeTypeInfo> = self
+            .attribute_types
+            .values()
+            .filter(|at| seen.insert(at.oid.clone()))
+            .collect();
+        types.sort_by(|a, b| {
+            a.names
+                .first()
+                .unwrap_or(&a.oid)
+                .cmp(b.names.first().unwrap_or(&b.oid))
+        });
+        types
+    }
+
+    /// The object classes that name `attr` (by any of its names) in their
+    /// own MUST and MAY lists, as sorted canonical class names. Classes that
+    /// only inherit it from a superior are not listed.
+    pub fn classes_using_attribute(&self, attr: &str) -> (Vec<String>, Vec<String>) {
+        let names: Vec<String> = match self.get_attribute_type(attr) {
+            Some(at) => at.names.iter().map(|n| n.to_lowercase()).collect(),
+            None => vec![attr.to_lowercase()],
+        };
+        let names_it = |list: &[String]| list.iter().any(|a| names.contains(&a.to_lowercase()));
+        let mut seen = BTreeSet::new();
+        let mut must = Vec::new();
+        let mut may = Vec::new();
+        for oc in self.object_classes.values() {
+            if !seen.insert(oc.oid.clone()) {
+                continue;
+            }
+            let name = oc.names.first().unwrap_or(&oc.oid).clone();
+            if names_it(&oc.must) {
+                must.push(name);
+            } else if names_it(&oc.may) {
+                may.push(name);
+            }
+        }
+        must.sort_by_key(|n| n.to_lowercase());
+        may.sort_by_key(|n| n.to_lowercase());
+        (must, may)
+    }
+
+    /// The superiors of an object class, nearest first.
+    pub fn superior_chain(&self, object_class: &str) -> Vec<String> {
+        let mut chain = Vec::new();
+        let mut current = self
+            .get_object_class(object_class)
+            .and_then(|oc| oc.superior.clone());
+        while let Some(sup) = current.take() {
+            if chain.iter().any(|c: &String| c.eq_ignore_ascii_case(&sup)) {
+                break;
+            }
+            current = self
+                .get_object_class(&sup)
+                .and_then(|oc| oc.superior.clone());
+            chain.push(sup);
+        }
+        chain
+    }
+
     /// Return all attribute names in the schema, including aliases and
     /// read-only attributes. Useful for search filter autocomplete where
     /// any attribute can appear in a filter expression.
@@ -176,17 +472,7 @@ impl LdapConnection {
         &mut self,
         subschema_dn: Option<&str>,
     ) -> Result<SchemaCache, CoreError> {
-        // Build list of DNs to try — preferred first, then fallbacks
-        let mut dns_to_try: Vec<String> = Vec::new();
-        if let Some(dn) = subschema_dn {
-            dns_to_try.push(dn.to_string());
-        }
-        // Add fallbacks if they aren't already in the list
-        for fallback in ["cn=Subschema", "cn=schema"] {
-            if !dns_to_try.iter().any(|d| d.eq_ignore_ascii_case(fallback)) {
-                dns_to_try.push(fallback.to_string());
-            }
-        }
+        let dns_to_try = schema_dns(subschema_dn);
 
         debug!(
             "load_schema: subschema_dn={:?}, will try DNs in order: {:?}",
@@ -233,28 +519,61 @@ impl LdapConnection {
         Err(last_err.unwrap_or_else(|| CoreError::SchemaError("No schema found".into())))
     }
 
+    /// When the subschema entry was last changed: its `modifyTimestamp`, or
+    /// `whenChanged` on AD. `None` when the server publishes neither, so a
+    /// cached copy can't be checked for staleness.
+    pub async fn schema_timestamp(
+        &mut self,
+        subschema_dn: Option<&str>,
+    ) -> Result<Option<String>, CoreError> {
+        let mut last_err = None;
+        for schema_dn in schema_dns(subschema_dn) {
+            match self
+                .search_entry_attrs(&schema_dn, &["modifyTimestamp", "whenChanged"])
+                .await
+            {
+                Ok(Some(entry)) => {
+                    return Ok(["modifyTimestamp", "whenChanged"]
+                        .iter()
+                        .find_map(|a| find_values_ci(&entry.attributes, a)?.first())
+                        .cloned());
+                }
+                Ok(None) => {}
+                Err(e) => last_err = Some(e),
+            }
+        }
+        match last_err {
+            Some(e) => Err(e),
+            None => Ok(None),
+        }
+    }
+
     /// Try to load schema from a specific DN.
     async fn try_load_schema_from(&mut self, schema_dn: &str) -> Result<SchemaCache, CoreError> {
         debug!(
             "try_load_schema_from: searching base={:?} scope=Base filter=(objectClass=*)",
             schema_dn
         );
+        let attrs = vec!["attributeTypes", "objectClasses"];
+        let trace = self.trace_proxy(
+            TraceRecord::new(TraceOp::Search, schema_dn)
+                .scope(Scope::Base)
+                .filter("(objectClass=*)")
+                .attributes(&attrs),
+        );
+        let started = Instant::now();
         let result = self
-            .ldap
-            .search(
-                schema_dn,
-                Scope::Base,
-                "(objectClass=*)",
-                vec!["attributeTypes", "objectClasses"],
-            )
-            .await
-            .map_err(|e| {
-                debug!(
-                    "try_load_schema_from: LDAP search error for {:?}: {}",
-                    schema_dn, e
-                );
-                CoreError::Ldap(e)
-            })?;
+            .request(Vec::new())
+            .search(schema_dn, Scope::Base, "(objectClass=*)", attrs)
+            .await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(|e| {
+            debug!(
+                "try_load_schema_from: LDAP search error for {:?}: {}",
+                schema_dn, e
+            );
+            CoreError::from(e)
+        })?;
 
         let (entries, _res) = result.success().map_err(|e| {
             debug!(
@@ -367,6 +686,17 @@ impl LdapConnection {
     }
 }
 
+/// The subschema DNs to try, the RootDSE's first, then common fallbacks.
+fn schema_dns(subschema_dn: Option<&str>) -> Vec<String> {
+    let mut dns: Vec<String> = subschema_dn.map(str::to_string).into_iter().collect();
+    for fallback in ["cn=Subschema", "cn=schema"] {
+        if !dns.iter().any(|d| d.eq_ignore_ascii_case(fallback)) {
+            dns.push(fallback.to_string());
+        }
+    }
+    dns
+}
+
 /// Parse an LDAP attributeType schema definition string.
 /// Format: ( OID NAME 'name' DESC 'desc' SYNTAX oid SINGLE-VALUE ... )
 fn parse_attribute_type(def: &str) -> Option<AttributeTypeInfo> {
@@ -380,6 +710,10 @@ fn parse_attribute_type(def: &str) -> Option<AttributeTypeInfo> {
     let names = parse_names(inner);
     let description = parse_quoted_field(inner, "DESC");
     let syntax_oid = parse_unquoted_field(inner, "SYNTAX");
+    let superior = parse_unquoted_field(inner, "SUP");
+    let equality = parse_unquoted_field(inner, "EQUALITY");
+    let ordering = parse_unquoted_field(inner, "ORDERING");
+    let substr = parse_unquoted_field(inner, "SUBSTR");
     let single_value = inner.contains("SINGLE-VALUE");
     let no_user_modification = inner.contains("NO-USER-MODIFICATION");
 
@@ -387,12 +721,23 @@ fn parse_attribute_type(def: &str) -> Option<AttributeTypeInfo> {
         .as_deref()
         .map(map_syntax_oid)
         .unwrap_or(AttributeSyntax::String);
+    let syntax_length = syntax_oid
+        .as_deref()
+        .and_then(|oid| oid.split_once('{'))
+        .and_then(|(_, n)| n.trim_end_matches('}').parse().ok());
+    let syntax_oid = syntax_oid.map(|oid| oid.split('{').next().unwrap_or(&oid).to_string());
 
     Some(AttributeTypeInfo {
         oid,
         names,
         description,
         syntax,
+        syntax_oid,
+        syntax_length,
+        superior,
+        equality,
+        ordering,
+        substr,
         single_value,
         no_user_modification,
     })
@@ -547,6 +892,39 @@ mod tests {
         assert!(!at.single_value);
     }
 
+    #[test]
+    fn test_parse_attribute_type_matching_rules() {
+        let def = "( 2.5.4.41 NAME 'name' EQUALITY caseIgnoreMatch \
+                   SUBSTR caseIgnoreSubstringsMatch SYNTAX 1.3.6.1.4.1.1466.115.121.1.15{32768} )";
+        let at = parse_attribute_type(def).unwrap();
+        assert_eq!(at.equality.as_deref(), Some("caseIgnoreMatch"));
+        assert_eq!(at.substr.as_deref(), Some("caseIgnoreSubstringsMatch"));
+        assert_eq!(at.ordering, None);
+        assert_eq!(
+            at.syntax_oid.as_deref(),
+            Some("1.3.6.1.4.1.1466.115.121.1.15")
+        );
+        assert_eq!(at.syntax_length, Some(32768));
+
+        let name = at;
+        let def = "( 2.5.4.3 NAME ( 'cn' 'commonName' ) SUP name )";
+        let at = parse_attribute_type(def).unwrap();
+        assert_eq!(at.superior.as_deref(), Some("name"));
+
+        // cn inherits its equality rule; audio has none
+        let audio = parse_attribute_type(
+            "( 0.9.2342.19200300.100.1.55 NAME 'audio' SYNTAX 1.3.6.1.4.1.1466.115.121.1.40 )",
+        )
+        .unwrap();
+        let mut schema = SchemaCache::new();
+        schema.attribute_types.insert("name".to_string(), name);
+        schema.attribute_types.insert("cn".to_string(), at);
+        schema.attribute_types.insert("audio".to_string(), audio);
+        assert!(schema.has_equality_rule("CN"));
+        assert!(!schema.has_equality_rule("audio"));
+        assert!(schema.has_equality_rule("notInSchema"));
+    }
+
     #[test]
     fn test_parse_attribute_type_multi_name() {
         let def =
@@ -575,6 +953,58 @@ mod tests {
         assert_eq!(oc.may, vec!["userPassword", "telephoneNumber"]);
     }
 
+    #[test]
+    fn test_syntax_validation() {
+        assert!(AttributeSyntax::Integer.validate("-42").is_ok());
+        assert!(AttributeSyntax::Integer.validate("4x").is_err());
+        assert!(AttributeSyntax::Integer.validate("-").is_err());
+        assert!(AttributeSyntax::Boolean.validate("TRUE").is_ok());
+        assert!(AttributeSyntax::Boolean.validate("true").is_err());
+        for ok in [
+            "20261014093000Z",
+            "2026101409Z",
+            "20261014093000.5+0200",
+            "202610140930-05",
+        ] {
+            assert!(
+                AttributeSyntax::GeneralizedTime.validate(ok).is_ok(),
+                "{}",
+                ok
+            );
+        }
+        for bad in [
+            "20261014093000",
+            "20261332093000Z",
+            "2026101425Z",
+            "20261014093000+2",
+            "x",
+        ] {
+            assert!(
+                AttributeSyntax::GeneralizedTime.validate(bad).is_err(),
+                "{}",
+                bad
+            );
+        }
+        assert!(AttributeSyntax::Dn.validate("cn=x,dc=example").is_ok());
+        assert!(AttributeSyntax::Dn.validate("not a dn").is_err());
+        assert!(AttributeSyntax::DirectoryString
+            .validate("anything")
+            .is_ok());
+    }
+
+    #[test]
+    fn test_value_rules_single_value() {
+        let rules = ValueRules {
+            attr: "uidNumber".to_string(),
+            syntax: AttributeSyntax::Integer,
+            single_value: true,
+        };
+        assert!(rules.check(&["1000"]).is_ok());
+        let err = rules.check(&["1000", "1001"]).unwrap_err();
+        assert!(err.contains("single-valued"));
+        assert!(rules.check(&["abc"]).is_err());
+    }
+
     #[test]
     fn test_map_syntax_oid() {
         assert_eq!(
@@ -644,6 +1074,12 @@ mod tests {
                 names: vec![name.to_string()],
                 description: None,
                 syntax: AttributeSyntax::String,
+                syntax_oid: None,
+                syntax_length: None,
+                superior: None,
+                equality: None,
+                ordering: None,
+                substr: None,
                 single_value: false,
                 no_user_modification: no_user_mod,
             };
@@ -684,6 +1120,41 @@ mod tests {
         assert_eq!(cn_count, 1, "cn should appear exactly once");
     }
 
+    #[test]
+    fn test_must_may_attributes() {
+        let schema = build_test_schema();
+        let (must, may) = schema.must_may_attributes(&["inetOrgPerson", "person"]);
+        assert_eq!(must, vec!["cn", "objectClass", "sn"]);
+        assert_eq!(may, vec!["mail", "telephoneNumber", "uid", "userPassword"]);
+    }
+
+    #[test]
+    fn test_missing_must_attributes() {
+        let schema = build_test_schema();
+        let missing =
+            schema.missing_must_attributes(&["inetOrgPerson"], &["objectClass", "CN", "mail"]);
+        assert_eq!(missing, vec!["sn"]);
+        assert!(schema
+            .missing_must_attributes(&["person"], &["objectClass", "cn", "sn"])
+            .is_empty());
+    }
+
+    #[test]
+    fn test_cross_references() {
+        let schema = build_test_schema();
+        let (must, may) = schema.classes_using_attribute("CN");
+        assert_eq!(must, vec!["person"]);
+        assert!(may.is_empty());
+        let (must, may) = schema.classes_using_attribute("mail");
+        assert!(must.is_empty());
+        assert_eq!(may, vec!["inetOrgPerson"]);
+        assert_eq!(
+            schema.superior_chain("inetorgperson"),
+            vec!["person", "top"]
+        );
+        assert!(schema.superior_chain("top").is_empty());
+    }
+
     #[test]
     fn test_all_user_attributes() {
         let schema = build_test_schema();
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CoreError;
+use crate::schema::SchemaCache;
+
+/// A parsed schema saved between sessions, one file per host, so it can be
+/// shown at once on connect instead of after a multi-second download.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredSchema {
+    pub host: String,
+    /// The subschema entry's change time when the schema was read; a
+    /// different value on the server means the copy is stale.
+    pub modify_timestamp: Option<String>,
+    pub schema: SchemaCache,
+}
+
+impl StoredSchema {
+    /// Default cache directory: `<config_dir>/loom-ldapbrowser/schema`.
+    pub fn default_dir() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("loom-ldapbrowser")
+            .join("schema")
+    }
+
+    /// The cache file for `host` under `dir`.
+    pub fn path(dir: &Path, host: &str) -> PathBuf {
+        let name: String = host
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        dir.join(format!("{}.json", name))
+    }
+
+    /// The saved schema for `host`, if there is a readable one.
+    pub fn load(dir: &Path, host: &str) -> Option<Self> {
+        let text = std::fs::read_to_string(Self::path(dir, host)).ok()?;
+        let stored: Self = serde_json::from_str(&text).ok()?;
+        stored.host.eq_ignore_ascii_case(host).then_some(stored)
+    }
+
+    /// Save under `dir`, replacing the file atomically.
+    pub fn save(&self, dir: &Path) -> Result<(), CoreError> {
+        let err = |e: std::io::Error| CoreError::SchemaError(format!("schema cache: {}", e));
+        std::fs::create_dir_all(dir).map_err(err)?;
+        let json = serde_json::to_string(self)
+            .map_err(|e| CoreError::SchemaError(format!("schema cache: {}", e)))?;
+        let path = Self::path(dir, &self.host);
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json).map_err(err)?;
+        std::fs::rename(&tmp, &path).map_err(err)
+    }
+
+    /// Whether the server's current `timestamp` shows this copy is still
+    /// current. Without a timestamp on either side there is no telling.
+    pub fn is_current(&self, timestamp: Option<&str>) -> bool {
+        matches!((self.modify_timestamp.as_deref(), timestamp), (Some(a), Some(b)) if a == b)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::schema::{AttributeSyntax, AttributeTypeInfo};
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut schema = SchemaCache::new();
+        schema.attribute_types.insert(
+            "cn".to_string(),
+            AttributeTypeInfo {
+                oid: "2.5.4.3".to_string(),
+                names: vec!["cn".to_string()],
+                description: None,
+                syntax: AttributeSyntax::DirectoryString,
+                syntax_oid: None,
+                syntax_length: None,
+                superior: None,
+                equality: None,
+                ordering: None,
+                substr: None,
+                single_value: false,
+                no_user_modification: false,
+            },
+        );
+        let stored = StoredSchema {
+            host: "DC01.corp.example.com".to_string(),
+            modify_timestamp: Some("20261001120000.0Z".to_string()),
+            schema,
+        };
+        stored.save(dir.path()).unwrap();
+        assert!(dir.path().join("dc01.corp.example.com.json").exists());
+
+        let loaded = StoredSchema::load(dir.path(), "dc01.corp.example.com").unwrap();
+        assert!(loaded.schema.get_attribute_type("CN").is_some());
+        assert!(loaded.is_current(Some("20261001120000.0Z")));
+        assert!(!loaded.is_current(Some("20261014090000.0Z")));
+        assert!(!loaded.is_current(None));
+        assert!(StoredSchema::load(dir.path(), "dc02.corp.example.com").is_none());
+    }
+}
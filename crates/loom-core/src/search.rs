@@ -1,15 +1,64 @@
-use ldap3::{Scope, SearchEntry};
+use std::time::{Duration, Instant};
+
+use ldap3::{Scope, SearchEntry, SearchOptions};
 use tracing::debug;
 
 use crate::connection::LdapConnection;
 use crate::entry::LdapEntry;
 use crate::error::CoreError;
+use crate::filter::Filter;
+use crate::trace::{TraceOp, TraceRecord};
+use crate::tree::{ChildCount, SUBORDINATE_ATTRS};
+
+/// LDAP result code when a search stopped at its size limit.
+const RC_SIZE_LIMIT_EXCEEDED: u32 = 4;
+/// LDAP result code for a base DN that does not exist.
+const RC_NO_SUCH_OBJECT: u32 = 32;
 
 impl LdapConnection {
-    /// Search for immediate children of the given DN.
+    /// Search for immediate children of the given DN. Only the attributes
+    /// that tell how many children each of them has are requested.
     pub async fn search_children(&mut self, parent_dn: &str) -> Result<Vec<LdapEntry>, CoreError> {
-        self.search(parent_dn, Scope::OneLevel, "(objectClass=*)", &["*"])
-            .await
+        self.search(
+            parent_dn,
+            Scope::OneLevel,
+            "(objectClass=*)",
+            SUBORDINATE_ATTRS,
+        )
+        .await
+    }
+
+    /// Count the children of `dn` by reading up to `limit` of their DNs,
+    /// for servers that don't report a subordinate count.
+    pub async fn count_children(
+        &mut self,
+        dn: &str,
+        limit: usize,
+    ) -> Result<ChildCount, CoreError> {
+        let trace = self.trace_proxy(
+            TraceRecord::new(TraceOp::Search, dn)
+                .scope(Scope::OneLevel)
+                .filter("(objectClass=*)")
+                .attributes(["1.1"]),
+        );
+        let started = Instant::now();
+        let result = self
+            .request(Vec::new())
+            .with_search_options(
+                SearchOptions::new().sizelimit(i32::try_from(limit).unwrap_or(i32::MAX)),
+            )
+            .search(dn, Scope::OneLevel, "(objectClass=*)", vec!["1.1"])
+            .await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
+        match result.1.rc {
+            0 => Ok(ChildCount::Exact(result.0.len())),
+            RC_SIZE_LIMIT_EXCEEDED => Ok(ChildCount::AtLeast(result.0.len())),
+            rc => Err(CoreError::SearchFailed(format!(
+                "Counting children of {} failed rc={}: {}",
+                dn, rc, result.1.text
+            ))),
+        }
     }
 
     /// Search for a single entry by exact DN.
@@ -22,6 +71,45 @@ impl LdapConnection {
         Ok(results.into_iter().next())
     }
 
+    /// Fetch a single entry with an explicit attribute list, e.g.
+    /// `["*", "+"]` to include operational attributes.
+    pub async fn search_entry_attrs(
+        &mut self,
+        dn: &str,
+        attrs: &[&str],
+    ) -> Result<Option<LdapEntry>, CoreError> {
+        let results = self
+            .search(dn, Scope::Base, "(objectClass=*)", attrs)
+            .await?;
+        Ok(results.into_iter().next())
+    }
+
+    /// Check whether an entry exists. A noSuchObject result is `false`
+    /// rather than an error.
+    pub async fn entry_exists(&mut self, dn: &str) -> Result<bool, CoreError> {
+        let trace = self.trace_proxy(
+            TraceRecord::new(TraceOp::Search, dn)
+                .scope(Scope::Base)
+                .filter("(objectClass=*)")
+                .attributes(["1.1"]),
+        );
+        let started = Instant::now();
+        let result = self
+            .request(Vec::new())
+            .search(dn, Scope::Base, "(objectClass=*)", vec!["1.1"])
+            .await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
+        match result.1.rc {
+            0 => Ok(!result.0.is_empty()),
+            RC_NO_SUCH_OBJECT => Ok(false),
+            rc => Err(CoreError::SearchFailed(format!(
+                "Lookup of {} failed rc={}: {}",
+                dn, rc, result.1.text
+            ))),
+        }
+    }
+
     /// Search a subtree with the given filter.
     pub async fn search_subtree(
         &mut self,
@@ -48,14 +136,22 @@ impl LdapConnection {
             val: Some(encode_paged_results_control(limit as u32, &[])),
         }];
 
+        let trace = self.trace_proxy(
+            TraceRecord::new(TraceOp::Search, base_dn)
+                .scope(Scope::Subtree)
+                .filter(filter)
+                .attributes(attrs)
+                .control("pagedResults"),
+        );
+        let started = Instant::now();
         let result = self
-            .ldap
-            .with_controls(controls)
+            .request(controls)
             .search(base_dn, Scope::Subtree, filter, attrs.to_vec())
-            .await
-            .map_err(CoreError::Ldap)?;
+            .await;
+        self.trace.record(trace, started, &result);
 
         let (entries, _res) = result
+            .map_err(CoreError::from)?
             .success()
             .map_err(|e| CoreError::SearchFailed(e.to_string()))?;
 
@@ -73,6 +169,72 @@ impl LdapConnection {
         Ok(entries)
     }
 
+    /// Collect the distinct values of `attr` from up to `sample_size` entries
+    /// under `base_dn`; see [`distinct_attribute_values`].
+    pub async fn sample_attribute_values(
+        &mut self,
+        base_dn: &str,
+        attr: &str,
+        sample_size: usize,
+        max_distinct: usize,
+    ) -> Result<Vec<String>, CoreError> {
+        let filter = Filter::present(attr).to_string();
+        let entries = self
+            .search_limited(base_dn, &filter, &[attr], sample_size)
+            .await?;
+
+        let values = distinct_attribute_values(&entries, attr, max_distinct);
+        if values.is_empty() && !entries.is_empty() {
+            debug!(
+                "sample_attribute_values: {} has over {} values, not sampling",
+                attr, max_distinct
+            );
+        }
+        Ok(values)
+    }
+
+    /// Search a subtree like [`search_subtree`](Self::search_subtree),
+    /// waiting up to `timeout` for each page instead of the connection's
+    /// timeout, or as long as the server takes with none.
+    pub async fn search_subtree_within(
+        &mut self,
+        base_dn: &str,
+        filter: &str,
+        attrs: &[&str],
+        timeout: Option<Duration>,
+    ) -> Result<Vec<LdapEntry>, CoreError> {
+        let mut all_entries = Vec::new();
+        self.search_pages(base_dn, Scope::Subtree, filter, attrs, timeout, |page| {
+            all_entries.extend(page);
+            Ok(())
+        })
+        .await?;
+        Ok(all_entries)
+    }
+
+    /// Search a subtree a page at a time, handing each page to `on_page` as
+    /// it arrives instead of collecting them, so callers such as exports can
+    /// process any number of entries in bounded memory. Returns the total
+    /// number of entries; an error from `on_page` stops the search.
+    pub async fn search_subtree_pages(
+        &mut self,
+        base_dn: &str,
+        filter: &str,
+        attrs: &[&str],
+        on_page: impl FnMut(Vec<LdapEntry>) -> Result<(), CoreError>,
+    ) -> Result<usize, CoreError> {
+        let timeout = self.op_timeout();
+        self.search_pages(
+            base_dn,
+            Scope::Subtree,
+            filter,
+            attrs,
+            Some(timeout),
+            on_page,
+        )
+        .await
+    }
+
     /// Perform a paged LDAP search.
     async fn search(
         &mut self,
@@ -81,8 +243,27 @@ impl LdapConnection {
         filter: &str,
         attrs: &[&str],
     ) -> Result<Vec<LdapEntry>, CoreError> {
-        let page_size = self.settings.page_size;
+        let timeout = self.op_timeout();
         let mut all_entries = Vec::new();
+        self.search_pages(base_dn, scope, filter, attrs, Some(timeout), |page| {
+            all_entries.extend(page);
+            Ok(())
+        })
+        .await?;
+        Ok(all_entries)
+    }
+
+    async fn search_pages(
+        &mut self,
+        base_dn: &str,
+        scope: Scope,
+        filter: &str,
+        attrs: &[&str],
+        timeout: Option<Duration>,
+        mut on_page: impl FnMut(Vec<LdapEntry>) -> Result<(), CoreError>,
+    ) -> Result<usize, CoreError> {
+        let page_size = self.settings.page_size;
+        let mut total = 0;
         let mut cookie = Vec::new();
 
         loop {
@@ -92,27 +273,36 @@ impl LdapConnection {
                 val: Some(encode_paged_results_control(page_size, &cookie)),
             }];
 
+            let trace = self.trace_proxy(
+                TraceRecord::new(TraceOp::Search, base_dn)
+                    .scope(scope)
+                    .filter(filter)
+                    .attributes(attrs)
+                    .control("pagedResults"),
+            );
+            let started = Instant::now();
             let result = self
-                .ldap
-                .with_controls(controls)
+                .request_within(controls, timeout)
                 .search(base_dn, scope, filter, attrs.to_vec())
-                .await
-                .map_err(CoreError::Ldap)?;
+                .await;
+            self.trace.record(trace, started, &result);
 
             let (entries, res) = result
+                .map_err(CoreError::from)?
                 .success()
                 .map_err(|e| CoreError::SearchFailed(e.to_string()))?;
 
-            let count = entries.len();
-            for entry in entries {
-                all_entries.push(LdapEntry::from_search_entry(SearchEntry::construct(entry)));
-            }
-
+            let page: Vec<LdapEntry> = entries
+                .into_iter()
+                .map(|e| LdapEntry::from_search_entry(SearchEntry::construct(e)))
+                .collect();
+            total += page.len();
             debug!(
                 "Paged search: got {} entries (total: {})",
-                count,
-                all_entries.len()
+                page.len(),
+                total
             );
+            on_page(page)?;
 
             // Extract the cookie from the response control
             cookie = extract_paged_results_cookie(&res);
@@ -121,11 +311,40 @@ impl LdapConnection {
             }
         }
 
-        Ok(all_entries)
+        Ok(total)
     }
 }
 
 /// Encode a Simple Paged Results control value (RFC 2696).
+/// The distinct values of `attr` across `entries`, compared and sorted
+/// case-insensitively. Returns an empty list when there are more than
+/// `max_distinct`, as the attribute is then too varied to offer as a
+/// choice list.
+pub fn distinct_attribute_values(
+    entries: &[LdapEntry],
+    attr: &str,
+    max_distinct: usize,
+) -> Vec<String> {
+    let mut values: Vec<String> = Vec::new();
+    let all = entries.iter().filter_map(|entry| {
+        entry
+            .attributes
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+            .map(|(_, vals)| vals)
+    });
+    for val in all.flatten() {
+        if !values.iter().any(|v| v.eq_ignore_ascii_case(val)) {
+            if values.len() == max_distinct {
+                return Vec::new();
+            }
+            values.push(val.clone());
+        }
+    }
+    values.sort_by_key(|v| v.to_lowercase());
+    values
+}
+
 fn encode_paged_results_control(page_size: u32, cookie: &[u8]) -> Vec<u8> {
     // BER encoding: SEQUENCE { INTEGER size, OCTET STRING cookie }
     let size_bytes = ber_encode_integer(page_size as i64);
@@ -243,3 +462,24 @@ fn ber_decode_length(data: &[u8]) -> (usize, usize) {
         (len, 1 + num_bytes)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_distinct_attribute_values() {
+        let entries = vec![
+            test_entry("cn=a", &[("department", &["Sales"])]),
+            test_entry("cn=b", &[("Department", &["engineering", "SALES"])]),
+            test_entry("cn=c", &[("title", &["Manager"])]),
+        ];
+        assert_eq!(
+            distinct_attribute_values(&entries, "department", 5),
+            ["engineering", "Sales"]
+        );
+        // Too many distinct values: not enum-like
+        assert!(distinct_attribute_values(&entries, "department", 1).is_empty());
+    }
+}
@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::time::Instant;
 
 use ldap3::{Scope, SearchEntry};
 use serde::{Deserialize, Serialize};
@@ -7,6 +8,7 @@ use tracing::{debug, info};
 
 use crate::connection::LdapConnection;
 use crate::error::CoreError;
+use crate::trace::{TraceOp, TraceRecord};
 use crate::util::{get_first, get_values, has_attr};
 
 /// Known LDAP server types.
@@ -38,40 +40,53 @@ pub struct RootDse {
     pub supported_controls: Vec<String>,
     pub supported_extensions: Vec<String>,
     pub server_type: ServerType,
+    /// The profile's extra RootDSE attributes, in the order requested.
+    /// Attributes the server didn't return have no values.
+    pub extra: Vec<(String, Vec<String>)>,
     pub raw: BTreeMap<String, Vec<String>>,
 }
 
 impl LdapConnection {
-    /// Read the Root DSE and detect server type.
-    pub async fn read_root_dse(&mut self) -> Result<RootDse, CoreError> {
+    /// Read the Root DSE and detect server type. `extra_attributes` are
+    /// requested on top of the standard set and returned in `RootDse::extra`.
+    pub async fn read_root_dse(
+        &mut self,
+        extra_attributes: &[String],
+    ) -> Result<RootDse, CoreError> {
+        let mut request: Vec<&str> = vec![
+            "*",
+            "+",
+            "namingContexts",
+            "subschemaSubentry",
+            "vendorName",
+            "vendorVersion",
+            "supportedControl",
+            "supportedExtension",
+            "supportedLDAPVersion",
+            "forestFunctionality",
+            "domainFunctionality",
+            "domainControllerFunctionality",
+            "isGlobalCatalogReady",
+            "schemaNamingContext",
+            "configurationNamingContext",
+            "rootDomainNamingContext",
+            "objectClass",
+        ];
+        request.extend(extra_attributes.iter().map(String::as_str));
+
+        let trace = TraceRecord::new(TraceOp::Search, "")
+            .scope(Scope::Base)
+            .filter("(objectClass=*)")
+            .attributes(&request);
+        let started = Instant::now();
+        let timeout = self.op_timeout();
         let result = self
             .ldap
-            .search(
-                "",
-                Scope::Base,
-                "(objectClass=*)",
-                vec![
-                    "*",
-                    "+",
-                    "namingContexts",
-                    "subschemaSubentry",
-                    "vendorName",
-                    "vendorVersion",
-                    "supportedControl",
-                    "supportedExtension",
-                    "supportedLDAPVersion",
-                    "forestFunctionality",
-                    "domainFunctionality",
-                    "domainControllerFunctionality",
-                    "isGlobalCatalogReady",
-                    "schemaNamingContext",
-                    "configurationNamingContext",
-                    "rootDomainNamingContext",
-                    "objectClass",
-                ],
-            )
-            .await
-            .map_err(CoreError::Ldap)?;
+            .with_timeout(timeout)
+            .search("", Scope::Base, "(objectClass=*)", request)
+            .await;
+        self.trace.record(trace, started, &result);
+        let result = result.map_err(CoreError::from)?;
 
         let (entries, _res) = result
             .success()
@@ -111,6 +126,8 @@ impl LdapConnection {
             }
         }
 
+        let extra = requested_values(&attrs, extra_attributes);
+
         Ok(RootDse {
             naming_contexts,
             subschema_subentry,
@@ -119,11 +136,24 @@ impl LdapConnection {
             supported_controls,
             supported_extensions,
             server_type,
+            extra,
             raw: attrs,
         })
     }
 }
 
+/// Look up each requested attribute (case-insensitive), keeping the order
+/// and spelling the profile used.
+fn requested_values(
+    attrs: &BTreeMap<String, Vec<String>>,
+    names: &[String],
+) -> Vec<(String, Vec<String>)> {
+    names
+        .iter()
+        .map(|name| (name.clone(), get_values(attrs, name)))
+        .collect()
+}
+
 /// Detect server type from RootDSE attributes.
 fn detect_server_type(
     attrs: &BTreeMap<String, Vec<String>>,
@@ -196,6 +226,23 @@ fn detect_server_type(
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_requested_values_keep_order_and_spelling() {
+        let mut attrs = BTreeMap::new();
+        attrs.insert("isglobalcatalogready".to_string(), vec!["TRUE".to_string()]);
+        let names = vec![
+            "isGlobalCatalogReady".to_string(),
+            "applianceId".to_string(),
+        ];
+        assert_eq!(
+            requested_values(&attrs, &names),
+            vec![
+                ("isGlobalCatalogReady".to_string(), vec!["TRUE".to_string()]),
+                ("applianceId".to_string(), vec![]),
+            ]
+        );
+    }
+
     #[test]
     fn test_detect_ad() {
         let mut attrs = BTreeMap::new();
@@ -0,0 +1,439 @@
+//! Discovering a domain's directory servers from DNS SRV records
+//! (`_ldap._tcp.<domain>`, RFC 2782), as Active Directory publishes for
+//! its domain controllers. A small DNS client is enough for this one
+//! query, using the system's name servers: those in `/etc/resolv.conf`,
+//! or on Windows those the network interfaces are configured with.
+
+use std::net::{IpAddr, SocketAddr};
+use std::time::Duration;
+
+use rand::Rng;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpStream, UdpSocket};
+use tracing::{debug, warn};
+
+use crate::error::CoreError;
+
+const TYPE_SRV: u16 = 33;
+const CLASS_IN: u16 = 1;
+const QUERY_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// One server from an SRV record.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SrvRecord {
+    pub priority: u16,
+    pub weight: u16,
+    pub port: u16,
+    pub target: String,
+}
+
+impl SrvRecord {
+    pub fn address(&self) -> String {
+        format!("{}:{}", self.target, self.port)
+    }
+}
+
+/// Build a recursive query for the SRV records of `name`.
+fn encode_query(id: u16, name: &str) -> Result<Vec<u8>, String> {
+    let mut msg = Vec::with_capacity(name.len() + 18);
+    msg.extend_from_slice(&id.to_be_bytes());
+    msg.extend_from_slice(&[0x01, 0x00]); // recursion desired
+    msg.extend_from_slice(&[0, 1, 0, 0, 0, 0, 0, 0]); // one question
+    for label in name.trim_end_matches('.').split('.') {
+        if label.is_empty() || label.len() > 63 {
+            return Err(format!("Invalid domain name '{}'", name));
+        }
+        msg.push(label.len() as u8);
+        msg.extend_from_slice(label.as_bytes());
+    }
+    msg.push(0);
+    msg.extend_from_slice(&TYPE_SRV.to_be_bytes());
+    msg.extend_from_slice(&CLASS_IN.to_be_bytes());
+    Ok(msg)
+}
+
+fn read_u16(msg: &[u8], pos: usize) -> Result<u16, String> {
+    msg.get(pos..pos + 2)
+        .map(|b| u16::from_be_bytes([b[0], b[1]]))
+        .ok_or_else(|| "Truncated DNS response".to_string())
+}
+
+/// Read a possibly compressed name at `pos`, returning it and the
+/// position after it.
+fn read_name(msg: &[u8], mut pos: usize) -> Result<(String, usize), String> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    for _ in 0..128 {
+        let len = *msg.get(pos).ok_or("Truncated DNS name")? as usize;
+        if len == 0 {
+            return Ok((labels.join("."), end.unwrap_or(pos + 1)));
+        }
+        if len & 0xC0 == 0xC0 {
+            let pointer = (read_u16(msg, pos)? & 0x3FFF) as usize;
+            end.get_or_insert(pos + 2);
+            pos = pointer;
+            continue;
+        }
+        let label = msg
+            .get(pos + 1..pos + 1 + len)
+            .ok_or("Truncated DNS name")?;
+        labels.push(String::from_utf8_lossy(label).into_owned());
+        pos += 1 + len;
+    }
+    Err("DNS name has too many labels".to_string())
+}
+
+/// The SRV records in the response to query `id`, and whether it was
+/// truncated. A name that doesn't exist has no records.
+fn parse_response(id: u16, msg: &[u8]) -> Result<(Vec<SrvRecord>, bool), String> {
+    if read_u16(msg, 0)? != id {
+        return Err("DNS response doesn't match the query".to_string());
+    }
+    let flags = read_u16(msg, 2)?;
+    let truncated = flags & 0x0200 != 0;
+    match flags & 0x000F {
+        0 | 3 => {}
+        rcode => return Err(format!("DNS server answered with error code {}", rcode)),
+    }
+    let questions = read_u16(msg, 4)?;
+    let answers = read_u16(msg, 6)?;
+
+    let mut pos = 12;
+    for _ in 0..questions {
+        pos = read_name(msg, pos)?.1 + 4;
+    }
+    let mut records = Vec::new();
+    for _ in 0..answers {
+        pos = read_name(msg, pos)?.1;
+        let rtype = read_u16(msg, pos)?;
+        let rdlen = read_u16(msg, pos + 8)? as usize;
+        let rdata = pos + 10;
+        if rtype == TYPE_SRV {
+            let target = read_name(msg, rdata + 6)?.0;
+            // A target of "." means the service isn't offered
+            if !target.is_empty() {
+                records.push(SrvRecord {
+                    priority: read_u16(msg, rdata)?,
+                    weight: read_u16(msg, rdata + 2)?,
+                    port: read_u16(msg, rdata + 4)?,
+                    target,
+                });
+            }
+        }
+        pos = rdata + rdlen;
+    }
+    Ok((records, truncated))
+}
+
+/// Order records to try: lowest priority first, and within a priority a
+/// random order where heavier records tend to come first (RFC 2782).
+/// `pick(total)` returns a number from 0 to `total` inclusive.
+pub fn order(mut records: Vec<SrvRecord>, mut pick: impl FnMut(u32) -> u32) -> Vec<SrvRecord> {
+    // Zero-weight records go first in their group, so they're only
+    // chosen when the draw is 0
+    records.sort_by_key(|r| (r.priority, r.weight != 0));
+    let mut ordered = Vec::with_capacity(records.len());
+    while !records.is_empty() {
+        let priority = records[0].priority;
+        let group = records
+            .iter()
+            .take_while(|r| r.priority == priority)
+            .count();
+        let total: u32 = records[..group].iter().map(|r| u32::from(r.weight)).sum();
+        let draw = pick(total);
+        let mut sum = 0;
+        let chosen = records[..group]
+            .iter()
+            .position(|r| {
+                sum += u32::from(r.weight);
+                sum >= draw
+            })
+            .unwrap_or(0);
+        ordered.push(records.remove(chosen));
+    }
+    ordered
+}
+
+/// `records` with `failed` moved to the end, so failing over tries the
+/// other servers first.
+pub fn try_last(mut records: Vec<SrvRecord>, failed: Option<&SrvRecord>) -> Vec<SrvRecord> {
+    if let Some(failed) = failed {
+        if let Some(i) = records
+            .iter()
+            .position(|r| r.target == failed.target && r.port == failed.port)
+        {
+            let record = records.remove(i);
+            records.push(record);
+        }
+    }
+    records
+}
+
+/// The name servers listed in `resolv.conf` content.
+#[cfg_attr(windows, allow(dead_code))]
+fn parse_resolv_conf(content: &str) -> Vec<IpAddr> {
+    content
+        .lines()
+        .filter_map(|line| line.trim().strip_prefix("nameserver"))
+        .filter_map(|rest| {
+            let addr = rest.trim();
+            // Drop an IPv6 zone like fe80::1%eth0
+            addr.split('%').next().unwrap_or(addr).parse().ok()
+        })
+        .collect()
+}
+
+/// The name servers in `reg query` output for the TCP/IP parameters keys:
+/// each interface's static `NameServer` and DHCP-assigned
+/// `DhcpNameServer` values, separated by commas or spaces.
+#[cfg_attr(not(windows), allow(dead_code))]
+fn parse_reg_name_servers(output: &str) -> Vec<IpAddr> {
+    let mut servers: Vec<IpAddr> = Vec::new();
+    for line in output.lines() {
+        let mut fields = line.split_whitespace();
+        let (Some("NameServer" | "DhcpNameServer"), Some("REG_SZ")) =
+            (fields.next(), fields.next())
+        else {
+            continue;
+        };
+        for addr in fields.flat_map(|f| f.split(',')) {
+            let addr = addr.split('%').next().unwrap_or(addr);
+            if let Ok(ip) = addr.parse() {
+                if !servers.contains(&ip) {
+                    servers.push(ip);
+                }
+            }
+        }
+    }
+    servers
+}
+
+/// The system's name servers.
+#[cfg(not(windows))]
+fn system_name_servers() -> Result<Vec<IpAddr>, CoreError> {
+    let servers = std::fs::read_to_string("/etc/resolv.conf")
+        .map(|content| parse_resolv_conf(&content))
+        .unwrap_or_default();
+    if servers.is_empty() {
+        return Err(CoreError::ConnectionFailed(
+            "No DNS servers found in /etc/resolv.conf".to_string(),
+        ));
+    }
+    Ok(servers)
+}
+
+/// The system's name servers, read from the network interfaces' settings
+/// in the registry.
+#[cfg(windows)]
+fn system_name_servers() -> Result<Vec<IpAddr>, CoreError> {
+    let mut servers = Vec::new();
+    for key in [
+        r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip\Parameters\Interfaces",
+        r"HKLM\SYSTEM\CurrentControlSet\Services\Tcpip6\Parameters\Interfaces",
+    ] {
+        let output = std::process::Command::new("reg")
+            .args(["query", key, "/s"])
+            .output()
+            .map_err(|e| CoreError::ConnectionFailed(format!("Couldn't run reg: {}", e)))?;
+        for server in parse_reg_name_servers(&String::from_utf8_lossy(&output.stdout)) {
+            if !servers.contains(&server) {
+                servers.push(server);
+            }
+        }
+    }
+    if servers.is_empty() {
+        return Err(CoreError::ConnectionFailed(
+            "No DNS servers found in the network settings".to_string(),
+        ));
+    }
+    Ok(servers)
+}
+
+async fn query_udp(server: SocketAddr, query: &[u8]) -> std::io::Result<Vec<u8>> {
+    let bind: SocketAddr = if server.is_ipv4() {
+        ([0, 0, 0, 0], 0).into()
+    } else {
+        ([0u16; 8], 0).into()
+    };
+    let socket = UdpSocket::bind(bind).await?;
+    socket.connect(server).await?;
+    socket.send(query).await?;
+    let mut buf = vec![0u8; 4096];
+    let n = socket.recv(&mut buf).await?;
+    buf.truncate(n);
+    Ok(buf)
+}
+
+async fn query_tcp(server: SocketAddr, query: &[u8]) -> std::io::Result<Vec<u8>> {
+    let mut stream = TcpStream::connect(server).await?;
+    stream
+        .write_all(&(query.len() as u16).to_be_bytes())
+        .await?;
+    stream.write_all(query).await?;
+    let len = stream.read_u16().await? as usize;
+    let mut buf = vec![0u8; len];
+    stream.read_exact(&mut buf).await?;
+    Ok(buf)
+}
+
+async fn query(server: SocketAddr, id: u16, msg: &[u8]) -> Result<Vec<SrvRecord>, String> {
+    let timeout = |e: tokio::time::error::Elapsed| e.to_string();
+    let response = tokio::time::timeout(QUERY_TIMEOUT, query_udp(server, msg))
+        .await
+        .map_err(timeout)?
+        .map_err(|e| e.to_string())?;
+    let (records, truncated) = parse_response(id, &response)?;
+    if !truncated {
+        return Ok(records);
+    }
+    debug!("SRV answer from {} truncated, asking over TCP", server);
+    let response = tokio::time::timeout(QUERY_TIMEOUT, query_tcp(server, msg))
+        .await
+        .map_err(timeout)?
+        .map_err(|e| e.to_string())?;
+    Ok(parse_response(id, &response)?.0)
+}
+
+/// Look up `_ldap._tcp.<domain>` and return its servers in the order to
+/// try them.
+pub async fn discover(domain: &str) -> Result<Vec<SrvRecord>, CoreError> {
+    let name = format!("_ldap._tcp.{}", domain.trim().trim_end_matches('.'));
+    let servers = system_name_servers()?;
+
+    let id: u16 = rand::thread_rng().gen();
+    let msg = encode_query(id, &name).map_err(CoreError::ConnectionFailed)?;
+    let mut last_error = String::new();
+    for server in servers {
+        match query(SocketAddr::new(server, 53), id, &msg).await {
+            Ok(records) if records.is_empty() => {
+                return Err(CoreError::ConnectionFailed(format!(
+                    "No SRV records for {}",
+                    name
+                )))
+            }
+            Ok(records) => {
+                let mut rng = rand::thread_rng();
+                return Ok(order(records, |total| rng.gen_range(0..=total)));
+            }
+            Err(e) => {
+                warn!("SRV lookup of {} at {} failed: {}", name, server, e);
+                last_error = e;
+            }
+        }
+    }
+    Err(CoreError::ConnectionFailed(format!(
+        "SRV lookup of {} failed: {}",
+        name, last_error
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(priority: u16, weight: u16, target: &str) -> SrvRecord {
+        SrvRecord {
+            priority,
+            weight,
+            port: 389,
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_parse_response() {
+        let id = 0x1234;
+        let mut msg = encode_query(id, "_ldap._tcp.corp.example.com").unwrap();
+        msg[2] = 0x81; // a response
+        msg[7] = 2; // two answers
+        for (priority, weight, target) in [(0u16, 100u16, "dc01"), (10, 0, "dc02")] {
+            msg.extend_from_slice(&[0xC0, 12]); // the question's name
+            msg.extend_from_slice(&TYPE_SRV.to_be_bytes());
+            msg.extend_from_slice(&CLASS_IN.to_be_bytes());
+            msg.extend_from_slice(&600u32.to_be_bytes());
+            msg.extend_from_slice(&(6 + 1 + 4 + 2u16).to_be_bytes());
+            msg.extend_from_slice(&priority.to_be_bytes());
+            msg.extend_from_slice(&weight.to_be_bytes());
+            msg.extend_from_slice(&389u16.to_be_bytes());
+            msg.push(4);
+            msg.extend_from_slice(target.as_bytes());
+            // The rest of the name points at "corp.example.com" in the question
+            msg.extend_from_slice(&[0xC0, 12 + 6 + 5]);
+        }
+
+        let (records, truncated) = parse_response(id, &msg).unwrap();
+        assert!(!truncated);
+        assert_eq!(
+            records,
+            [
+                SrvRecord {
+                    priority: 0,
+                    weight: 100,
+                    port: 389,
+                    target: "dc01.corp.example.com".to_string(),
+                },
+                SrvRecord {
+                    priority: 10,
+                    weight: 0,
+                    port: 389,
+                    target: "dc02.corp.example.com".to_string(),
+                },
+            ]
+        );
+        assert!(parse_response(id + 1, &msg).is_err());
+
+        let conf = "# generated\nsearch corp\nnameserver 10.0.0.2\nnameserver fe80::1%eth0\n";
+        assert_eq!(
+            parse_resolv_conf(conf),
+            [
+                "10.0.0.2".parse::<IpAddr>().unwrap(),
+                "fe80::1".parse().unwrap()
+            ]
+        );
+
+        let reg = "\r\nHKEY_LOCAL_MACHINE\\...\\Interfaces\\{0A1B}\r\n    \
+                   NameServer    REG_SZ    10.0.0.2,10.0.0.3\r\n    \
+                   DhcpNameServer    REG_SZ    192.168.1.1 10.0.0.2\r\n    \
+                   NameServer    REG_SZ    \r\n";
+        assert_eq!(
+            parse_reg_name_servers(reg),
+            [
+                "10.0.0.2".parse::<IpAddr>().unwrap(),
+                "10.0.0.3".parse().unwrap(),
+                "192.168.1.1".parse().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_order() {
+        let records = vec![
+            record(10, 0, "backup"),
+            record(0, 10, "light"),
+            record(0, 90, "heavy"),
+            record(0, 0, "spare"),
+        ];
+        let targets = |ordered: Vec<SrvRecord>| -> Vec<String> {
+            ordered.into_iter().map(|r| r.target).collect()
+        };
+        // A draw of 0 picks the zero-weight record, then the running sums
+        // decide among the rest; other priorities come after
+        let mut draws = vec![0, 50, 0, 0].into_iter();
+        assert_eq!(
+            targets(order(records.clone(), |_| draws.next().unwrap())),
+            ["spare", "heavy", "light", "backup"]
+        );
+        let mut draws = vec![5, 0, 0, 0].into_iter();
+        assert_eq!(
+            targets(order(records.clone(), |_| draws.next().unwrap())),
+            ["light", "spare", "heavy", "backup"]
+        );
+
+        // Reconnecting tries the server that dropped after the others
+        assert_eq!(
+            targets(try_last(records.clone(), Some(&record(0, 10, "light")))),
+            ["backup", "heavy", "spare", "light"]
+        );
+        assert_eq!(targets(try_last(records.clone(), None))[0], "backup");
+    }
+}
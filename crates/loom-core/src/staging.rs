@@ -0,0 +1,341 @@
+//! Staged changes: while staging is on, writes are queued in a local
+//! changeset instead of being sent, so they can be reviewed, reordered
+//! and then applied together.
+
+use std::collections::HashSet;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ldap3::Mod;
+use tracing::info;
+
+use crate::connection::LdapConnection;
+use crate::dry_run::ChangeScript;
+use crate::error::CoreError;
+
+/// A write waiting to be applied.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChangeOp {
+    Modify {
+        dn: String,
+        mods: Vec<Mod<String>>,
+    },
+    Add {
+        dn: String,
+        attrs: Vec<(String, HashSet<String>)>,
+    },
+    /// Move under `new_parent`, keeping the RDN.
+    Move {
+        dn: String,
+        new_parent: String,
+        subtree: bool,
+    },
+    /// Rename in place.
+    Rename {
+        dn: String,
+        new_rdn: String,
+    },
+    Delete {
+        dn: String,
+    },
+}
+
+impl ChangeOp {
+    pub fn dn(&self) -> &str {
+        match self {
+            ChangeOp::Modify { dn, .. }
+            | ChangeOp::Add { dn, .. }
+            | ChangeOp::Move { dn, .. }
+            | ChangeOp::Rename { dn, .. }
+            | ChangeOp::Delete { dn } => dn,
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ChangeOp::Modify { .. } => "MODIFY",
+            ChangeOp::Add { .. } => "ADD",
+            ChangeOp::Move { .. } => "MOVE",
+            ChangeOp::Rename { .. } => "RENAME",
+            ChangeOp::Delete { .. } => "DELETE",
+        }
+    }
+
+    fn record(&self, script: &mut ChangeScript) {
+        match self {
+            ChangeOp::Modify { dn, mods } => script.record_modify(dn, mods),
+            ChangeOp::Add { dn, attrs } => script.record_add(dn, attrs),
+            ChangeOp::Move { dn, new_parent, .. } => {
+                script.record_moddn(dn, crate::dn::rdn(dn), new_parent)
+            }
+            ChangeOp::Rename { dn, new_rdn } => {
+                script.record_moddn(dn, new_rdn, crate::dn::parent_dn(dn).unwrap_or_default())
+            }
+            ChangeOp::Delete { dn } => script.record_delete(dn),
+        }
+    }
+
+    /// The change as an LDIF change record.
+    pub fn to_ldif(&self) -> String {
+        let mut script = ChangeScript::default();
+        self.record(&mut script);
+        script
+            .to_ldif()
+            .trim_start_matches("version: 1\n\n")
+            .to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct StagedChange {
+    /// Identifies the change while the queue is reordered around it.
+    pub id: u64,
+    pub op: ChangeOp,
+}
+
+#[derive(Debug, Default)]
+struct Queue {
+    staging: bool,
+    applying: bool,
+    next_id: u64,
+    changes: Vec<StagedChange>,
+}
+
+/// The changes staged on a tab's connection, shared with the UI so it
+/// can list, reorder and remove them without waiting for the connection.
+#[derive(Debug, Clone, Default)]
+pub struct ChangeQueue(Arc<Mutex<Queue>>);
+
+impl ChangeQueue {
+    fn lock(&self) -> MutexGuard<'_, Queue> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Whether writes are being queued rather than sent.
+    pub fn is_staging(&self) -> bool {
+        let queue = self.lock();
+        queue.staging && !queue.applying
+    }
+
+    pub fn set_staging(&self, staging: bool) {
+        self.lock().staging = staging;
+    }
+
+    /// Whether the queue is being applied; it can't be edited meanwhile.
+    pub fn is_applying(&self) -> bool {
+        self.lock().applying
+    }
+
+    pub fn push(&self, op: ChangeOp) {
+        let mut queue = self.lock();
+        let id = queue.next_id;
+        queue.next_id += 1;
+        queue.changes.push(StagedChange { id, op });
+    }
+
+    /// The queued changes, in the order they'll be applied.
+    pub fn changes(&self) -> Vec<StagedChange> {
+        self.lock().changes.clone()
+    }
+
+    pub fn len(&self) -> usize {
+        self.lock().changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.lock().changes.is_empty()
+    }
+
+    /// Drop the change at `index`. Refused while applying.
+    pub fn remove(&self, index: usize) -> bool {
+        let mut queue = self.lock();
+        if queue.applying || index >= queue.changes.len() {
+            return false;
+        }
+        queue.changes.remove(index);
+        true
+    }
+
+    /// Move the change at `index` by `delta` places, returning its new
+    /// index. Refused while applying.
+    pub fn shift(&self, index: usize, delta: isize) -> Option<usize> {
+        let mut queue = self.lock();
+        let target = index.checked_add_signed(delta)?;
+        if queue.applying || index >= queue.changes.len() || target >= queue.changes.len() {
+            return None;
+        }
+        let change = queue.changes.remove(index);
+        queue.changes.insert(target, change);
+        Some(target)
+    }
+
+    /// Drop every change. Refused while applying.
+    pub fn clear(&self) -> bool {
+        let mut queue = self.lock();
+        if queue.applying {
+            return false;
+        }
+        queue.changes.clear();
+        true
+    }
+
+    /// Every change as one LDIF change script.
+    pub fn to_ldif(&self) -> String {
+        let mut script = ChangeScript::default();
+        for change in &self.lock().changes {
+            change.op.record(&mut script);
+        }
+        script.to_ldif()
+    }
+
+    /// Write the changes to a file for review, returning how many.
+    pub fn write_to(&self, path: &Path) -> Result<usize, CoreError> {
+        std::fs::write(path, self.to_ldif())
+            .map_err(|e| CoreError::ExportError(format!("Failed to write file: {}", e)))?;
+        Ok(self.len())
+    }
+
+    fn forget(&self, id: u64) {
+        self.lock().changes.retain(|c| c.id != id);
+    }
+}
+
+/// Result of applying the staged changes.
+#[derive(Debug, Default)]
+pub struct ApplyResult {
+    pub applied: usize,
+    pub errors: Vec<(String, String)>, // (dn, error_message)
+    /// Changes left queued: those that failed, and any after a failure
+    /// when stopping on errors.
+    pub remaining: usize,
+}
+
+impl LdapConnection {
+    /// The changes staged on this connection.
+    pub fn staged_changes(&self) -> ChangeQueue {
+        self.changes.clone()
+    }
+
+    /// Send the staged changes in order, removing each from the queue once
+    /// the server accepts it. With `stop_on_error`, the first failure ends
+    /// the run and leaves it and the changes after it queued; otherwise
+    /// every change is tried and the failed ones stay queued.
+    pub async fn apply_staged(
+        &mut self,
+        stop_on_error: bool,
+        mut progress: impl FnMut(usize, usize),
+    ) -> ApplyResult {
+        let changes = {
+            let mut queue = self.changes.lock();
+            queue.applying = true;
+            queue.changes.clone()
+        };
+        let mut result = ApplyResult::default();
+        for (i, change) in changes.iter().enumerate() {
+            match self.apply_change(&change.op).await {
+                Ok(()) => {
+                    self.changes.forget(change.id);
+                    result.applied += 1;
+                }
+                Err(e) => {
+                    result
+                        .errors
+                        .push((change.op.dn().to_string(), e.to_string()));
+                    if stop_on_error {
+                        break;
+                    }
+                }
+            }
+            progress(i + 1, changes.len());
+        }
+        self.changes.lock().applying = false;
+        result.remaining = self.changes.len();
+        info!(
+            "Applied {} staged changes, {} failed, {} still queued",
+            result.applied,
+            result.errors.len(),
+            result.remaining
+        );
+        result
+    }
+
+    async fn apply_change(&mut self, op: &ChangeOp) -> Result<(), CoreError> {
+        match op {
+            ChangeOp::Modify { dn, mods } => self.modify_entry(dn, mods.clone()).await,
+            ChangeOp::Add { dn, attrs } => self.add_entry(dn, attrs.clone()).await,
+            ChangeOp::Move {
+                dn,
+                new_parent,
+                subtree,
+            } => self
+                .move_entry(dn, new_parent, *subtree, false)
+                .await
+                .map(|_| ()),
+            ChangeOp::Rename { dn, new_rdn } => self.rename_entry(dn, new_rdn).await.map(|_| ()),
+            ChangeOp::Delete { dn } => self.delete_entry(dn).await,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_queue() {
+        let queue = ChangeQueue::default();
+        assert!(!queue.is_staging());
+        queue.set_staging(true);
+        assert!(queue.is_staging());
+
+        queue.push(ChangeOp::Delete {
+            dn: "cn=a,dc=x".to_string(),
+        });
+        queue.push(ChangeOp::Modify {
+            dn: "cn=b,dc=x".to_string(),
+            mods: vec![Mod::Replace(
+                "mail".to_string(),
+                HashSet::from(["b@x.com".to_string()]),
+            )],
+        });
+        queue.push(ChangeOp::Move {
+            dn: "cn=c,ou=old,dc=x".to_string(),
+            new_parent: "ou=new,dc=x".to_string(),
+            subtree: false,
+        });
+        let dns = |q: &ChangeQueue| -> Vec<String> {
+            q.changes().iter().map(|c| c.op.dn().to_string()).collect()
+        };
+
+        // Reorder, staying within bounds
+        assert_eq!(queue.shift(2, -2), Some(0));
+        assert_eq!(queue.shift(0, -1), None);
+        assert_eq!(queue.shift(2, 1), None);
+        assert_eq!(dns(&queue), ["cn=c,ou=old,dc=x", "cn=a,dc=x", "cn=b,dc=x"]);
+
+        let ldif = queue.to_ldif();
+        assert!(ldif
+            .starts_with("version: 1\n\ndn: cn=c,ou=old,dc=x\nchangetype: moddn\nnewrdn: cn=c\n"));
+        assert!(
+            ldif.contains("\ndn: cn=b,dc=x\nchangetype: modify\nreplace: mail\nmail: b@x.com\n-\n")
+        );
+        assert_eq!(
+            queue.changes()[1].op.to_ldif(),
+            "dn: cn=a,dc=x\nchangetype: delete\n"
+        );
+
+        // Nothing can be edited while the queue is applied
+        queue.lock().applying = true;
+        assert!(!queue.is_staging());
+        assert!(!queue.remove(0));
+        assert!(!queue.clear());
+        queue.lock().applying = false;
+
+        let first = queue.changes()[0].id;
+        queue.forget(first);
+        assert!(queue.remove(1));
+        assert_eq!(dns(&queue), ["cn=a,dc=x"]);
+        assert!(queue.clear());
+        assert!(queue.is_empty());
+    }
+}
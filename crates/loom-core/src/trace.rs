@@ -0,0 +1,327 @@
+//! A record of the LDAP requests a connection sends and the results it
+//! gets back, kept in a ring buffer for the trace viewer. Bind requests
+//! are recorded with their DN only. Running totals per operation type are
+//! kept alongside for metrics.
+
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use ldap3::result::ExopResult;
+use ldap3::{LdapError, LdapResult, Scope, SearchResult};
+
+/// Operations kept per connection; the oldest are dropped first.
+pub const TRACE_CAPACITY: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TraceOp {
+    Bind,
+    Search,
+    Add,
+    Modify,
+    ModifyDn,
+    Delete,
+    Extended,
+}
+
+impl TraceOp {
+    pub fn label(self) -> &'static str {
+        match self {
+            TraceOp::Bind => "BIND",
+            TraceOp::Search => "SEARCH",
+            TraceOp::Add => "ADD",
+            TraceOp::Modify => "MODIFY",
+            TraceOp::ModifyDn => "MODDN",
+            TraceOp::Delete => "DELETE",
+            TraceOp::Extended => "EXTENDED",
+        }
+    }
+}
+
+/// One request and its result.
+#[derive(Debug, Clone)]
+pub struct TraceRecord {
+    pub time: DateTime<Local>,
+    pub op: TraceOp,
+    pub dn: String,
+    /// Search scope: `base`, `one` or `sub`.
+    pub scope: Option<&'static str>,
+    pub filter: Option<String>,
+    /// Attributes requested by a search, or changed by an add or modify.
+    pub attributes: Vec<String>,
+    /// Names of the request controls sent.
+    pub controls: Vec<String>,
+    /// The LDAP result code, or `None` when no result arrived, e.g. because
+    /// the connection dropped.
+    pub result_code: Option<u32>,
+    /// The server's diagnostic message, or the client-side error.
+    pub message: String,
+    pub duration: Duration,
+    /// Entries returned by a search.
+    pub entries: Option<usize>,
+}
+
+impl TraceRecord {
+    /// A request about to be sent; the result fields are filled in by
+    /// [`OperationTrace::record`].
+    pub fn new(op: TraceOp, dn: &str) -> Self {
+        Self {
+            time: Local::now(),
+            op,
+            dn: dn.to_string(),
+            scope: None,
+            filter: None,
+            attributes: Vec::new(),
+            controls: Vec::new(),
+            result_code: None,
+            message: String::new(),
+            duration: Duration::ZERO,
+            entries: None,
+        }
+    }
+
+    pub fn scope(mut self, scope: Scope) -> Self {
+        self.scope = Some(match scope {
+            Scope::Base => "base",
+            Scope::OneLevel => "one",
+            Scope::Subtree => "sub",
+        });
+        self
+    }
+
+    pub fn filter(mut self, filter: &str) -> Self {
+        self.filter = Some(filter.to_string());
+        self
+    }
+
+    pub fn attributes<S: AsRef<str>>(mut self, attrs: impl IntoIterator<Item = S>) -> Self {
+        self.attributes = attrs.into_iter().map(|a| a.as_ref().to_string()).collect();
+        self
+    }
+
+    pub fn control(mut self, name: &str) -> Self {
+        self.controls.push(name.to_string());
+        self
+    }
+
+    /// Whether the request failed: no result, or a code other than
+    /// success, compare false/true or a size limit.
+    pub fn failed(&self) -> bool {
+        !matches!(self.result_code, Some(0 | 4 | 5 | 6))
+    }
+
+    /// The record as one line of an exported trace.
+    pub fn to_line(&self) -> String {
+        let mut line = format!(
+            "{} {} \"{}\"",
+            self.time.format("%Y-%m-%d %H:%M:%S%.3f"),
+            self.op.label(),
+            self.dn
+        );
+        if let Some(scope) = self.scope {
+            line.push_str(&format!(" scope={}", scope));
+        }
+        if let Some(filter) = &self.filter {
+            line.push_str(&format!(" filter={}", filter));
+        }
+        if !self.attributes.is_empty() {
+            line.push_str(&format!(" attrs={}", self.attributes.join(",")));
+        }
+        if !self.controls.is_empty() {
+            line.push_str(&format!(" controls={}", self.controls.join(",")));
+        }
+        match self.result_code {
+            Some(rc) => line.push_str(&format!(" -> rc={}", rc)),
+            None => line.push_str(" -> error"),
+        }
+        if let Some(entries) = self.entries {
+            line.push_str(&format!(" entries={}", entries));
+        }
+        line.push_str(&format!(" {}ms", self.duration.as_millis()));
+        if !self.message.is_empty() {
+            line.push_str(&format!(" \"{}\"", self.message));
+        }
+        line
+    }
+}
+
+/// Results an operation can be traced from.
+pub trait TracedResult {
+    /// Result code, diagnostic message and entry count.
+    fn outcome(&self) -> (u32, String, Option<usize>);
+}
+
+impl TracedResult for LdapResult {
+    fn outcome(&self) -> (u32, String, Option<usize>) {
+        (self.rc, self.text.clone(), None)
+    }
+}
+
+impl TracedResult for ExopResult {
+    fn outcome(&self) -> (u32, String, Option<usize>) {
+        (self.1.rc, self.1.text.clone(), None)
+    }
+}
+
+impl TracedResult for SearchResult {
+    fn outcome(&self) -> (u32, String, Option<usize>) {
+        (self.1.rc, self.1.text.clone(), Some(self.0.len()))
+    }
+}
+
+/// Running totals of one operation type since the connection opened.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct OpTotals {
+    pub count: u64,
+    pub failures: u64,
+    /// Time spent waiting for results.
+    pub duration: Duration,
+}
+
+#[derive(Debug, Default)]
+struct Trace {
+    records: VecDeque<TraceRecord>,
+    totals: BTreeMap<TraceOp, OpTotals>,
+    reconnects: u64,
+}
+
+/// The trace shared by a tab's pooled connections.
+#[derive(Debug, Clone, Default)]
+pub struct OperationTrace(Arc<Mutex<Trace>>);
+
+impl OperationTrace {
+    fn lock(&self) -> std::sync::MutexGuard<'_, Trace> {
+        self.0.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Complete `record` with the outcome of a request sent at `started`
+    /// and add it to the trace.
+    pub fn record<T: TracedResult>(
+        &self,
+        mut record: TraceRecord,
+        started: Instant,
+        result: &Result<T, LdapError>,
+    ) {
+        record.duration = started.elapsed();
+        match result {
+            Ok(result) => {
+                let (rc, text, entries) = result.outcome();
+                record.result_code = Some(rc);
+                record.message = text;
+                record.entries = entries;
+            }
+            Err(e) => record.message = e.to_string(),
+        }
+        self.push(record);
+    }
+
+    /// Add a completed record.
+    pub fn push(&self, record: TraceRecord) {
+        let mut trace = self.lock();
+        let totals = trace.totals.entry(record.op).or_default();
+        totals.count += 1;
+        totals.failures += u64::from(record.failed());
+        totals.duration += record.duration;
+        if trace.records.len() == TRACE_CAPACITY {
+            trace.records.pop_front();
+        }
+        trace.records.push_back(record);
+    }
+
+    /// The recorded operations, oldest first.
+    pub fn records(&self) -> Vec<TraceRecord> {
+        self.lock().records.iter().cloned().collect()
+    }
+
+    /// Forget the recorded operations. The totals keep counting.
+    pub fn clear(&self) {
+        self.lock().records.clear();
+    }
+
+    /// Totals per operation type, including operations no longer recorded.
+    pub fn totals(&self) -> Vec<(TraceOp, OpTotals)> {
+        self.lock().totals.iter().map(|(op, t)| (*op, *t)).collect()
+    }
+
+    pub fn count_reconnect(&self) {
+        self.lock().reconnects += 1;
+    }
+
+    /// Successful reconnects after the connection dropped.
+    pub fn reconnects(&self) -> u64 {
+        self.lock().reconnects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ldap_result(rc: u32, text: &str) -> LdapResult {
+        LdapResult {
+            rc,
+            matched: String::new(),
+            text: text.to_string(),
+            refs: Vec::new(),
+            ctrls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_record_outcome() {
+        let trace = OperationTrace::default();
+        let search = TraceRecord::new(TraceOp::Search, "ou=People,dc=example,dc=com")
+            .scope(Scope::Subtree)
+            .filter("(uid=jdoe)")
+            .attributes(["cn", "mail"])
+            .control("pagedResults");
+        trace.record(
+            search,
+            Instant::now(),
+            &Ok::<_, LdapError>(SearchResult(Vec::new(), ldap_result(0, ""))),
+        );
+        let delete = TraceRecord::new(TraceOp::Delete, "cn=x,dc=example,dc=com");
+        trace.record(
+            delete,
+            Instant::now(),
+            &Ok::<_, LdapError>(ldap_result(50, "insufficient access")),
+        );
+
+        let records = trace.records();
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].entries, Some(0));
+        assert!(!records[0].failed());
+        assert!(records[1].failed());
+        let line = records[0].to_line();
+        assert!(line.contains(
+            "SEARCH \"ou=People,dc=example,dc=com\" scope=sub filter=(uid=jdoe) \
+             attrs=cn,mail controls=pagedResults -> rc=0 entries=0"
+        ));
+        assert!(records[1].to_line().ends_with("ms \"insufficient access\""));
+
+        trace.clear();
+        assert!(trace.records().is_empty());
+        // Totals outlive the records
+        let totals = trace.totals();
+        assert_eq!(totals.len(), 2);
+        assert_eq!((totals[0].0, totals[0].1.count), (TraceOp::Search, 1));
+        assert_eq!((totals[1].0, totals[1].1.failures), (TraceOp::Delete, 1));
+    }
+
+    #[test]
+    fn test_oldest_records_are_dropped() {
+        let trace = OperationTrace::default();
+        for i in 0..TRACE_CAPACITY + 3 {
+            let record = TraceRecord::new(TraceOp::Delete, &format!("cn={}", i));
+            trace.record(
+                record,
+                Instant::now(),
+                &Ok::<_, LdapError>(ldap_result(0, "")),
+            );
+        }
+        let records = trace.records();
+        assert_eq!(records.len(), TRACE_CAPACITY);
+        assert_eq!(records[0].dn, "cn=3");
+    }
+}
@@ -0,0 +1,522 @@
+//! Transform scripts for bulk updates: one assignment per line, computing
+//! an attribute's new value from the entry being changed, as in
+//!
+//! ```text
+//! mail = lower(givenName) .. "." .. lower(sn) .. "@corp.com"
+//! ```
+//!
+//! The language only reads the entry: it has string literals, attribute
+//! values, `..` concatenation and a few string functions, but no
+//! variables, loops or I/O, so a script can't do anything but produce
+//! values.
+
+use thiserror::Error;
+
+use crate::entry::LdapEntry;
+
+/// A script that couldn't be parsed, with the 1-based position of the
+/// problem.
+#[derive(Debug, Clone, PartialEq, Error)]
+#[error("line {line}, column {column}: {message}")]
+pub struct ScriptError {
+    pub line: usize,
+    pub column: usize,
+    pub message: String,
+}
+
+/// The functions a script can call.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Func {
+    Lower,
+    Upper,
+    Trim,
+    /// The first n characters.
+    Left,
+    /// The last n characters.
+    Right,
+    /// Replace every occurrence of a string.
+    Replace,
+    /// The first argument, or the second if it's empty.
+    Default,
+}
+
+impl Func {
+    fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "lower" => Func::Lower,
+            "upper" => Func::Upper,
+            "trim" => Func::Trim,
+            "left" => Func::Left,
+            "right" => Func::Right,
+            "replace" => Func::Replace,
+            "default" => Func::Default,
+            _ => return None,
+        })
+    }
+
+    fn arity(self) -> usize {
+        match self {
+            Func::Lower | Func::Upper | Func::Trim => 1,
+            Func::Left | Func::Right | Func::Default => 2,
+            Func::Replace => 3,
+        }
+    }
+
+    fn call(self, args: &[String]) -> String {
+        let count = |s: &str| s.parse::<usize>().unwrap_or_default();
+        match self {
+            Func::Lower => args[0].to_lowercase(),
+            Func::Upper => args[0].to_uppercase(),
+            Func::Trim => args[0].trim().to_string(),
+            Func::Left => args[0].chars().take(count(&args[1])).collect(),
+            Func::Right => {
+                let len = args[0].chars().count();
+                args[0]
+                    .chars()
+                    .skip(len.saturating_sub(count(&args[1])))
+                    .collect()
+            }
+            Func::Replace if args[1].is_empty() => args[0].clone(),
+            Func::Replace => args[0].replace(&args[1], &args[2]),
+            Func::Default if args[0].is_empty() => args[1].clone(),
+            Func::Default => args[0].clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Literal(String),
+    /// The entry's first value of the attribute, or empty.
+    Attr(String),
+    Dn,
+    Concat(Vec<Expr>),
+    Call(Func, Vec<Expr>),
+}
+
+impl Expr {
+    fn evaluate(&self, entry: &LdapEntry) -> String {
+        match self {
+            Expr::Literal(s) => s.clone(),
+            Expr::Attr(attr) => crate::util::find_values_ci(&entry.attributes, attr)
+                .and_then(|vals| vals.first())
+                .cloned()
+                .unwrap_or_default(),
+            Expr::Dn => entry.dn.clone(),
+            Expr::Concat(parts) => parts.iter().map(|p| p.evaluate(entry)).collect(),
+            Expr::Call(func, args) => {
+                let args: Vec<String> = args.iter().map(|a| a.evaluate(entry)).collect();
+                func.call(&args)
+            }
+        }
+    }
+
+    fn attributes<'a>(&'a self, out: &mut Vec<&'a str>) {
+        match self {
+            Expr::Attr(attr) => out.push(attr),
+            Expr::Concat(parts) | Expr::Call(_, parts) => {
+                parts.iter().for_each(|p| p.attributes(out))
+            }
+            Expr::Literal(_) | Expr::Dn => {}
+        }
+    }
+}
+
+/// One line of a script: `attr = expression`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Assignment {
+    /// The attribute given the computed value.
+    pub attr: String,
+    expr: Expr,
+}
+
+impl Assignment {
+    /// The value computed for `entry`.
+    pub fn evaluate(&self, entry: &LdapEntry) -> String {
+        self.expr.evaluate(entry)
+    }
+
+    /// The attributes the expression reads.
+    pub fn attributes(&self) -> Vec<&str> {
+        let mut attrs = Vec::new();
+        self.expr.attributes(&mut attrs);
+        attrs
+    }
+}
+
+/// A parsed transform script.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    pub assignments: Vec<Assignment>,
+}
+
+impl Script {
+    /// Parse a script. Blank lines and `--` comments are ignored; each
+    /// attribute may be assigned once.
+    pub fn parse(source: &str) -> Result<Self, ScriptError> {
+        let mut assignments: Vec<Assignment> = Vec::new();
+        for (i, line) in source.lines().enumerate() {
+            let tokens = tokenize(line, i + 1)?;
+            if tokens.is_empty() {
+                continue;
+            }
+            let mut parser = Parser {
+                tokens,
+                pos: 0,
+                line: i + 1,
+                end: line.chars().count() + 1,
+            };
+            let assignment = parser.assignment()?;
+            if assignments
+                .iter()
+                .any(|a| a.attr.eq_ignore_ascii_case(&assignment.attr))
+            {
+                return Err(ScriptError {
+                    line: i + 1,
+                    column: 1,
+                    message: format!("{} is already assigned", assignment.attr),
+                });
+            }
+            assignments.push(assignment);
+        }
+        if assignments.is_empty() {
+            return Err(ScriptError {
+                line: 1,
+                column: 1,
+                message: "The script has no assignments".to_string(),
+            });
+        }
+        Ok(Self { assignments })
+    }
+
+    /// Every attribute the script reads or assigns, without duplicates.
+    pub fn attributes(&self) -> Vec<String> {
+        let mut attrs: Vec<String> = Vec::new();
+        for a in &self.assignments {
+            for attr in a.attributes().into_iter().chain([a.attr.as_str()]) {
+                if !attrs.iter().any(|x| x.eq_ignore_ascii_case(attr)) {
+                    attrs.push(attr.to_string());
+                }
+            }
+        }
+        attrs
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Name(String),
+    Str(String),
+    Number(String),
+    Concat,
+    Assign,
+    Open,
+    Close,
+    Comma,
+}
+
+impl Token {
+    fn describe(&self) -> String {
+        match self {
+            Token::Name(name) => format!("'{}'", name),
+            Token::Str(_) => "a string".to_string(),
+            Token::Number(n) => format!("'{}'", n),
+            Token::Concat => "'..'".to_string(),
+            Token::Assign => "'='".to_string(),
+            Token::Open => "'('".to_string(),
+            Token::Close => "')'".to_string(),
+            Token::Comma => "','".to_string(),
+        }
+    }
+}
+
+/// Split a line into tokens with their 1-based columns, stopping at `--`.
+fn tokenize(line: &str, line_no: usize) -> Result<Vec<(Token, usize)>, ScriptError> {
+    let chars: Vec<char> = line.chars().collect();
+    let error = |column: usize, message: String| ScriptError {
+        line: line_no,
+        column,
+        message,
+    };
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        let column = i + 1;
+        let next = chars.get(i + 1).copied();
+        match c {
+            _ if c.is_whitespace() => i += 1,
+            '-' if next == Some('-') => break,
+            '.' if next == Some('.') => {
+                tokens.push((Token::Concat, column));
+                i += 2;
+            }
+            '=' => {
+                tokens.push((Token::Assign, column));
+                i += 1;
+            }
+            '(' => {
+                tokens.push((Token::Open, column));
+                i += 1;
+            }
+            ')' => {
+                tokens.push((Token::Close, column));
+                i += 1;
+            }
+            ',' => {
+                tokens.push((Token::Comma, column));
+                i += 1;
+            }
+            '"' | '\'' => {
+                let mut value = String::new();
+                i += 1;
+                loop {
+                    match chars.get(i) {
+                        None => return Err(error(column, "Unterminated string".to_string())),
+                        Some(&q) if q == c => break,
+                        Some('\\') => {
+                            match chars.get(i + 1) {
+                                Some('n') => value.push('\n'),
+                                Some('t') => value.push('\t'),
+                                Some(&e) => value.push(e),
+                                None => {
+                                    return Err(error(column, "Unterminated string".to_string()))
+                                }
+                            }
+                            i += 1;
+                        }
+                        Some(&ch) => value.push(ch),
+                    }
+                    i += 1;
+                }
+                tokens.push((Token::Str(value), column));
+                i += 1;
+            }
+            _ if c.is_ascii_digit() => {
+                let start = i;
+                while i < chars.len() && chars[i].is_ascii_digit() {
+                    i += 1;
+                }
+                tokens.push((Token::Number(chars[start..i].iter().collect()), column));
+            }
+            _ if c.is_ascii_alphabetic() => {
+                let start = i;
+                while i < chars.len()
+                    && (chars[i].is_ascii_alphanumeric()
+                        || chars[i] == '_'
+                        || (chars[i] == '-' && chars.get(i + 1) != Some(&'-')))
+                {
+                    i += 1;
+                }
+                tokens.push((Token::Name(chars[start..i].iter().collect()), column));
+            }
+            _ => return Err(error(column, format!("Unexpected '{}'", c))),
+        }
+    }
+    Ok(tokens)
+}
+
+struct Parser {
+    tokens: Vec<(Token, usize)>,
+    pos: usize,
+    line: usize,
+    /// The column just past the line, for errors at its end.
+    end: usize,
+}
+
+impl Parser {
+    fn error(&self, column: usize, message: String) -> ScriptError {
+        ScriptError {
+            line: self.line,
+            column,
+            message,
+        }
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(t, _)| t)
+    }
+
+    fn column(&self) -> usize {
+        self.tokens.get(self.pos).map_or(self.end, |(_, c)| *c)
+    }
+
+    fn next(&mut self, what: &str) -> Result<(Token, usize), ScriptError> {
+        let token = self
+            .tokens
+            .get(self.pos)
+            .cloned()
+            .ok_or_else(|| self.error(self.end, format!("Expected {}", what)))?;
+        self.pos += 1;
+        Ok(token)
+    }
+
+    fn expect(&mut self, expected: Token, what: &str) -> Result<(), ScriptError> {
+        let (token, column) = self.next(what)?;
+        if token != expected {
+            return Err(self.error(
+                column,
+                format!("Expected {}, found {}", what, token.describe()),
+            ));
+        }
+        Ok(())
+    }
+
+    fn assignment(&mut self) -> Result<Assignment, ScriptError> {
+        let attr = match self.next("an attribute name")? {
+            (Token::Name(name), _) if name != "dn" => name,
+            (token, column) => {
+                return Err(self.error(
+                    column,
+                    format!("Expected an attribute name, found {}", token.describe()),
+                ))
+            }
+        };
+        self.expect(Token::Assign, "'='")?;
+        let expr = self.expr()?;
+        if let Some(token) = self.peek() {
+            let message = format!(
+                "Expected '..' or the end of the line, found {}",
+                token.describe()
+            );
+            return Err(self.error(self.column(), message));
+        }
+        Ok(Assignment { attr, expr })
+    }
+
+    fn expr(&mut self) -> Result<Expr, ScriptError> {
+        let mut parts = vec![self.term()?];
+        while self.peek() == Some(&Token::Concat) {
+            self.pos += 1;
+            parts.push(self.term()?);
+        }
+        Ok(if parts.len() == 1 {
+            parts.remove(0)
+        } else {
+            Expr::Concat(parts)
+        })
+    }
+
+    fn term(&mut self) -> Result<Expr, ScriptError> {
+        match self.next("a value")? {
+            (Token::Str(s), _) | (Token::Number(s), _) => Ok(Expr::Literal(s)),
+            (Token::Name(name), column) if self.peek() == Some(&Token::Open) => {
+                let func = Func::from_name(&name)
+                    .ok_or_else(|| self.error(column, format!("Unknown function '{}'", name)))?;
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() != Some(&Token::Close) {
+                    args.push(self.expr()?);
+                    while self.peek() == Some(&Token::Comma) {
+                        self.pos += 1;
+                        args.push(self.expr()?);
+                    }
+                }
+                self.expect(Token::Close, "')'")?;
+                if args.len() != func.arity() {
+                    return Err(self.error(
+                        column,
+                        format!(
+                            "{}() takes {} argument{}, not {}",
+                            name,
+                            func.arity(),
+                            if func.arity() == 1 { "" } else { "s" },
+                            args.len()
+                        ),
+                    ));
+                }
+                Ok(Expr::Call(func, args))
+            }
+            (Token::Name(name), _) if name == "dn" => Ok(Expr::Dn),
+            (Token::Name(name), _) => Ok(Expr::Attr(name)),
+            (token, column) => Err(self.error(
+                column,
+                format!("Expected a value, found {}", token.describe()),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entry::test_entry;
+
+    #[test]
+    fn test_script_evaluation_and_errors() {
+        let script = Script::parse(
+            "-- build the address\n\
+             mail = lower(givenName) .. \".\" .. lower(SN) .. '@corp.com'\n\
+             \n\
+             initials = upper(left(givenName, 1) .. left(sn, 1))  -- JD\n\
+             displayName = default(displayName, givenName .. ' ' .. sn)\n\
+             employeeID = replace(trim(employeeNumber), '0', '') .. right(dn, 4)",
+        )
+        .unwrap();
+        let jdoe = test_entry(
+            "uid=jdoe,ou=people,dc=x",
+            &[
+                ("givenName", &["John"]),
+                ("sn", &["Doe"]),
+                ("employeeNumber", &["  0042 "]),
+            ],
+        );
+        let values: Vec<(String, String)> = script
+            .assignments
+            .iter()
+            .map(|a| (a.attr.clone(), a.evaluate(&jdoe)))
+            .collect();
+        assert_eq!(
+            values,
+            [
+                ("mail".to_string(), "john.doe@corp.com".to_string()),
+                ("initials".to_string(), "JD".to_string()),
+                ("displayName".to_string(), "John Doe".to_string()),
+                ("employeeID".to_string(), "42dc=x".to_string()),
+            ]
+        );
+        assert_eq!(
+            script.attributes(),
+            [
+                "givenName",
+                "SN",
+                "mail",
+                "initials",
+                "displayName",
+                "employeeNumber",
+                "employeeID"
+            ]
+        );
+
+        let error = |source: &str| Script::parse(source).unwrap_err().to_string();
+        assert_eq!(error("mail = lower(sn"), "line 1, column 16: Expected ')'");
+        assert_eq!(
+            error("\nmail = shout(sn)"),
+            "line 2, column 8: Unknown function 'shout'"
+        );
+        assert_eq!(
+            error("mail = left(sn)"),
+            "line 1, column 8: left() takes 2 arguments, not 1"
+        );
+        assert_eq!(
+            error("mail = sn sn"),
+            "line 1, column 11: Expected '..' or the end of the line, found 'sn'"
+        );
+        assert_eq!(
+            error("mail = 'x\nmail = 'y'"),
+            "line 1, column 8: Unterminated string"
+        );
+        assert_eq!(
+            error("cn = sn\nCN = uid"),
+            "line 2, column 1: CN is already assigned"
+        );
+        assert_eq!(
+            error("-- nothing"),
+            "line 1, column 1: The script has no assignments"
+        );
+        assert_eq!(
+            error("dn = sn"),
+            "line 1, column 1: Expected an attribute name, found 'dn'"
+        );
+    }
+}
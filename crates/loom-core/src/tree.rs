@@ -1,4 +1,63 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
 use crate::dn;
+use crate::entry::LdapEntry;
+
+/// Operational attributes that report how many children an entry has,
+/// requested with each level of the tree.
+pub const SUBORDINATE_ATTRS: &[&str] = &[
+    "numSubordinates",
+    "msDS-Approx-Immed-Subordinates",
+    "hasSubordinates",
+];
+
+/// How many children a node has, as far as the server told us.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChildCount {
+    Exact(usize),
+    /// Active Directory's estimate, which can lag behind recent changes.
+    Approx(usize),
+    /// A sized search stopped at this many.
+    AtLeast(usize),
+}
+
+impl ChildCount {
+    /// Read the count from an entry's subordinate attributes. `None` when
+    /// the server only says that there are children, or nothing at all.
+    pub fn from_entry(entry: &LdapEntry) -> Option<Self> {
+        // Servers differ in how they case operational attribute names
+        let value = |attr: &str| {
+            entry
+                .attributes
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(attr))
+                .and_then(|(_, values)| values.first())
+                .map(|v| v.trim())
+        };
+        let number = |attr: &str| value(attr)?.parse::<usize>().ok();
+        if let Some(n) = number("numSubordinates") {
+            return Some(Self::Exact(n));
+        }
+        if let Some(n) = number("msDS-Approx-Immed-Subordinates") {
+            return Some(Self::Approx(n));
+        }
+        match value("hasSubordinates") {
+            Some(v) if v.eq_ignore_ascii_case("false") => Some(Self::Exact(0)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for ChildCount {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Exact(n) => write!(f, "({})", n),
+            Self::Approx(n) => write!(f, "(~{})", n),
+            Self::AtLeast(n) => write!(f, "({}+)", n),
+        }
+    }
+}
 
 /// A node in the directory tree.
 #[derive(Debug, Clone)]
@@ -7,6 +66,7 @@ pub struct TreeNode {
     pub display_name: String,
     pub children: Option<Vec<TreeNode>>,
     pub has_children_hint: bool,
+    pub child_count: Option<ChildCount>,
 }
 
 impl TreeNode {
@@ -17,7 +77,16 @@ impl TreeNode {
             display_name,
             children: None,
             has_children_hint: true,
+            child_count: None,
+        }
+    }
+
+    pub fn with_child_count(mut self, count: Option<ChildCount>) -> Self {
+        if let Some(ChildCount::Exact(0)) = count {
+            self.has_children_hint = false;
         }
+        self.child_count = count;
+        self
     }
 
     /// Whether this node has been loaded (children fetched).
@@ -33,6 +102,7 @@ impl TreeNode {
     /// Set the children of this node.
     pub fn set_children(&mut self, children: Vec<TreeNode>) {
         self.has_children_hint = !children.is_empty();
+        self.child_count = Some(ChildCount::Exact(children.len()));
         self.children = Some(children);
     }
 
@@ -40,23 +110,102 @@ impl TreeNode {
     pub fn collapse(&mut self) {
         self.children = None;
     }
+
+    /// Number of nodes below this one currently held in memory.
+    pub fn loaded_descendants(&self) -> usize {
+        self.children
+            .iter()
+            .flatten()
+            .map(|c| 1 + c.loaded_descendants())
+            .sum()
+    }
+}
+
+/// How a reload changed a node's children.
+#[derive(Debug, Default, PartialEq)]
+pub struct ChildrenDiff {
+    pub added: usize,
+    pub removed: usize,
+}
+
+impl ChildrenDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added == 0 && self.removed == 0
+    }
+}
+
+/// Next step towards showing a DN in the tree; see `DirectoryTree::reveal_path`.
+#[derive(Debug, PartialEq)]
+pub enum RevealStep {
+    /// Tree identifiers from the first level below the root to the target.
+    Found(Vec<String>),
+    /// Load this node's children, then try again.
+    Load(String),
+    /// The DN is not below the root, or its parent has no such child.
+    NotFound,
+}
+
+fn dn_eq(a: &str, b: &str) -> bool {
+    let mut a_parts = a.split(',').map(str::trim);
+    let mut b_parts = b.split(',').map(str::trim);
+    loop {
+        match (a_parts.next(), b_parts.next()) {
+            (None, None) => return true,
+            (Some(x), Some(y)) if x.eq_ignore_ascii_case(y) => {}
+            _ => return false,
+        }
+    }
 }
 
 /// The full directory tree, lazily loaded.
+/// Source of tree generations, shared so that no two trees ever report
+/// the same one.
+static NEXT_GENERATION: AtomicU64 = AtomicU64::new(1);
+
+fn next_generation() -> u64 {
+    NEXT_GENERATION.fetch_add(1, Ordering::Relaxed)
+}
+
 #[derive(Debug)]
 pub struct DirectoryTree {
     pub root_dn: String,
     pub root: TreeNode,
+    generation: u64,
 }
 
 impl DirectoryTree {
     pub fn new(root_dn: String) -> Self {
         let root = TreeNode::new(root_dn.clone());
-        Self { root_dn, root }
+        Self {
+            root_dn,
+            root,
+            generation: next_generation(),
+        }
+    }
+
+    /// Changes whenever the tree is changed through its methods, so a view
+    /// can keep its layout until then.
+    pub fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    /// Find a node by DN.
+    pub fn find_node(&self, target_dn: &str) -> Option<&TreeNode> {
+        fn find<'a>(node: &'a TreeNode, target_dn: &str) -> Option<&'a TreeNode> {
+            if node.dn.eq_ignore_ascii_case(target_dn) {
+                return Some(node);
+            }
+            node.children
+                .iter()
+                .flatten()
+                .find_map(|child| find(child, target_dn))
+        }
+        find(&self.root, target_dn)
     }
 
     /// Find a mutable reference to a node by DN.
     pub fn find_node_mut(&mut self, target_dn: &str) -> Option<&mut TreeNode> {
+        self.generation = next_generation();
         Self::find_in_node(&mut self.root, target_dn)
     }
 
@@ -74,11 +223,124 @@ impl DirectoryTree {
         None
     }
 
-    /// Insert children for a specific node DN.
-    pub fn insert_children(&mut self, parent_dn: &str, children: Vec<TreeNode>) {
-        if let Some(node) = self.find_node_mut(parent_dn) {
+    /// Work out how to show `target_dn` in the tree: the identifier path of
+    /// loaded nodes leading to it, or the next ancestor whose children must
+    /// be loaded first. DNs are compared per component, ignoring case and
+    /// spaces around commas.
+    pub fn reveal_path(&self, target_dn: &str) -> RevealStep {
+        let root = self.root_dn.trim();
+        let mut chain = Vec::new();
+        let mut current = target_dn.trim();
+        while !dn_eq(current, root) {
+            chain.push(current);
+            match dn::parent_dn(current) {
+                Some(parent) => current = parent.trim(),
+                None if root.is_empty() => break,
+                None => return RevealStep::NotFound,
+            }
+        }
+        chain.reverse();
+        if chain.is_empty() {
+            return RevealStep::NotFound;
+        }
+
+        let mut node = &self.root;
+        let mut path = Vec::new();
+        for dn in chain {
+            let Some(ref children) = node.children else {
+                return RevealStep::Load(node.dn.clone());
+            };
+            match children.iter().find(|c| dn_eq(&c.dn, dn)) {
+                Some(child) => {
+                    path.push(child.dn.clone());
+                    node = child;
+                }
+                None => return RevealStep::NotFound,
+            }
+        }
+        RevealStep::Found(path)
+    }
+
+    /// Set the children of a node from a fresh load. Children that were
+    /// already there keep their own loaded children, so a reload leaves
+    /// expanded nodes expanded; the new list decides order and membership.
+    /// Returns what changed when the node had been loaded before.
+    pub fn insert_children(
+        &mut self,
+        parent_dn: &str,
+        children: Vec<TreeNode>,
+    ) -> Option<ChildrenDiff> {
+        let node = self.find_node_mut(parent_dn)?;
+        let Some(mut old) = node.children.take() else {
             node.set_children(children);
+            return None;
+        };
+
+        let mut diff = ChildrenDiff::default();
+        let merged: Vec<TreeNode> = children
+            .into_iter()
+            .map(|child| {
+                match old
+                    .iter()
+                    .position(|o| o.dn.eq_ignore_ascii_case(&child.dn))
+                {
+                    Some(pos) => {
+                        let mut kept = old.swap_remove(pos);
+                        if !kept.is_loaded() {
+                            kept.child_count = child.child_count;
+                        }
+                        kept
+                    }
+                    None => {
+                        diff.added += 1;
+                        child
+                    }
+                }
+            })
+            .collect();
+        diff.removed = old.len();
+        node.set_children(merged);
+        Some(diff)
+    }
+
+    /// Record a child count found after the node was loaded into the tree.
+    pub fn set_child_count(&mut self, dn: &str, count: ChildCount) {
+        if let Some(node) = self.find_node_mut(dn) {
+            if !node.is_loaded() {
+                node.child_count = Some(count);
+            }
+        }
+    }
+
+    /// DNs of every node in the tree, including leaves and the root,
+    /// parents first.
+    pub fn known_dns(&self) -> Vec<String> {
+        fn collect(node: &TreeNode, out: &mut Vec<String>) {
+            out.push(node.dn.clone());
+            for child in node.children.iter().flatten() {
+                collect(child, out);
+            }
+        }
+        let mut out = Vec::new();
+        collect(&self.root, &mut out);
+        out
+    }
+
+    /// DNs of `dn` and every loaded node below it, parents first.
+    pub fn loaded_subtree(&self, dn: &str) -> Vec<String> {
+        fn collect(node: &TreeNode, out: &mut Vec<String>) {
+            if let Some(ref children) = node.children {
+                out.push(node.dn.clone());
+                for child in children {
+                    collect(child, out);
+                }
+            }
+        }
+        let mut out = Vec::new();
+        if let Some(node) = self.find_node(dn) {
+            collect(node, &mut out);
         }
+        out
     }
 }
 
@@ -86,6 +348,22 @@ impl DirectoryTree {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_loaded_descendants() {
+        let mut root = TreeNode::new("dc=example,dc=com".to_string());
+        assert_eq!(root.loaded_descendants(), 0);
+        let mut people = TreeNode::new("ou=People,dc=example,dc=com".to_string());
+        people.set_children(vec![
+            TreeNode::new("cn=a,ou=People,dc=example,dc=com".to_string()),
+            TreeNode::new("cn=b,ou=People,dc=example,dc=com".to_string()),
+        ]);
+        root.set_children(vec![
+            people,
+            TreeNode::new("ou=Groups,dc=example,dc=com".to_string()),
+        ]);
+        assert_eq!(root.loaded_descendants(), 4);
+    }
+
     #[test]
     fn test_tree_node_new() {
         let node = TreeNode::new("cn=Admin,dc=example,dc=com".to_string());
@@ -194,4 +472,146 @@ mod tests {
         assert!(found.is_some());
         assert_eq!(found.unwrap().display_name, "Alice");
     }
+
+    #[test]
+    fn test_reload_keeps_expanded_children() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let root = "dc=example,dc=com";
+        tree.insert_children(
+            root,
+            vec![
+                TreeNode::new("ou=People,dc=example,dc=com".to_string()),
+                TreeNode::new("ou=Old,dc=example,dc=com".to_string()),
+            ],
+        );
+        tree.insert_children(
+            "ou=People,dc=example,dc=com",
+            vec![TreeNode::new(
+                "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            )],
+        );
+
+        let before = tree.generation();
+        let diff = tree.insert_children(
+            root,
+            vec![
+                TreeNode::new("ou=New,dc=example,dc=com".to_string()),
+                TreeNode::new("OU=People,dc=example,dc=com".to_string()),
+            ],
+        );
+        assert_ne!(tree.generation(), before);
+        assert_eq!(
+            diff,
+            Some(ChildrenDiff {
+                added: 1,
+                removed: 1
+            })
+        );
+        let children = tree.root.children.as_ref().unwrap();
+        assert_eq!(children[0].dn, "ou=New,dc=example,dc=com");
+        // The kept node is the old one, with Alice still loaded
+        assert_eq!(children[1].dn, "ou=People,dc=example,dc=com");
+        assert!(tree
+            .find_node("cn=Alice,ou=People,dc=example,dc=com")
+            .is_some());
+        assert!(tree.find_node("ou=Old,dc=example,dc=com").is_none());
+        assert_eq!(
+            tree.loaded_subtree(root),
+            vec![
+                "dc=example,dc=com".to_string(),
+                "ou=People,dc=example,dc=com".to_string(),
+            ]
+        );
+        assert_eq!(
+            tree.known_dns(),
+            vec![
+                "dc=example,dc=com".to_string(),
+                "ou=New,dc=example,dc=com".to_string(),
+                "ou=People,dc=example,dc=com".to_string(),
+                "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_child_count_from_entry() {
+        use std::collections::BTreeMap;
+
+        let entry = |attr: &str, value: &str| {
+            let mut attrs = BTreeMap::new();
+            attrs.insert(attr.to_string(), vec![value.to_string()]);
+            LdapEntry::new("ou=x".to_string(), attrs)
+        };
+        assert_eq!(
+            ChildCount::from_entry(&entry("numSubordinates", "3")),
+            Some(ChildCount::Exact(3))
+        );
+        assert_eq!(
+            ChildCount::from_entry(&entry("msDS-Approx-Immed-Subordinates", "30000")),
+            Some(ChildCount::Approx(30000))
+        );
+        assert_eq!(
+            ChildCount::from_entry(&entry("hasSubordinates", "FALSE")),
+            Some(ChildCount::Exact(0))
+        );
+        assert_eq!(
+            ChildCount::from_entry(&entry("hasSubordinates", "TRUE")),
+            None
+        );
+        assert_eq!(ChildCount::AtLeast(1000).to_string(), "(1000+)");
+        assert_eq!(ChildCount::Approx(12).to_string(), "(~12)");
+
+        let node = TreeNode::new("ou=x".to_string()).with_child_count(Some(ChildCount::Exact(0)));
+        assert!(!node.has_children_hint);
+    }
+
+    #[test]
+    fn test_reveal_path_steps() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        let target = "cn=Alice, ou=People,DC=example,dc=com";
+
+        assert_eq!(
+            tree.reveal_path(target),
+            RevealStep::Load("dc=example,dc=com".to_string())
+        );
+
+        tree.insert_children(
+            "dc=example,dc=com",
+            vec![TreeNode::new("ou=People,dc=example,dc=com".to_string())],
+        );
+        assert_eq!(
+            tree.reveal_path(target),
+            RevealStep::Load("ou=People,dc=example,dc=com".to_string())
+        );
+
+        tree.insert_children(
+            "ou=People,dc=example,dc=com",
+            vec![TreeNode::new(
+                "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            )],
+        );
+        assert_eq!(
+            tree.reveal_path(target),
+            RevealStep::Found(vec![
+                "ou=People,dc=example,dc=com".to_string(),
+                "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_reveal_path_not_found() {
+        let mut tree = DirectoryTree::new("dc=example,dc=com".to_string());
+        assert_eq!(
+            tree.reveal_path("cn=x,dc=other,dc=org"),
+            RevealStep::NotFound
+        );
+        assert_eq!(tree.reveal_path("dc=example,dc=com"), RevealStep::NotFound);
+
+        tree.insert_children("dc=example,dc=com", Vec::new());
+        assert_eq!(
+            tree.reveal_path("ou=Missing,dc=example,dc=com"),
+            RevealStep::NotFound
+        );
+    }
 }
@@ -0,0 +1,459 @@
+//! Reaching servers that aren't directly routable: through a SOCKS5 or
+//! HTTP CONNECT proxy, or an SSH tunnel to a jump host. The TCP stream is
+//! opened here and handed to ldap3, which still runs TLS against the
+//! server's own name.
+
+use std::net::{IpAddr, Ipv4Addr};
+use std::process::Stdio;
+use std::time::Duration;
+
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::process::{Child, Command};
+use tracing::{debug, info};
+
+use crate::connection::ConnectionSettings;
+use crate::error::CoreError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProxyProtocol {
+    #[default]
+    Socks5,
+    Http,
+}
+
+/// A proxy the server is reached through.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProxySettings {
+    #[serde(default)]
+    pub protocol: ProxyProtocol,
+    /// `host:port` of the proxy.
+    pub address: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+    /// Kept in the vault or OS keychain under [`Self::credential_key`] and
+    /// filled in before connecting. Still read from older config files,
+    /// but never written back.
+    #[serde(default, skip_serializing)]
+    pub password: Option<String>,
+}
+
+impl ProxySettings {
+    /// The vault or keychain entry holding the proxy password of the
+    /// profile named `profile`.
+    pub fn credential_key(profile: &str) -> String {
+        format!("{} (proxy)", profile)
+    }
+
+    fn credentials(&self) -> Option<(&str, &str)> {
+        self.username
+            .as_deref()
+            .map(|user| (user, self.password.as_deref().unwrap_or("")))
+    }
+}
+
+/// A jump host the server is reached through, with the system `ssh`
+/// client forwarding connections (`ssh -D`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SshTunnelSettings {
+    pub host: String,
+    #[serde(default = "default_ssh_port")]
+    pub port: u16,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub identity_file: Option<String>,
+}
+
+/// How much of ssh's stderr is kept to explain a tunnel that didn't start.
+const STDERR_LINES: usize = 20;
+
+fn default_ssh_port() -> u16 {
+    22
+}
+
+/// A running `ssh` process offering a SOCKS5 proxy on a local port. The
+/// process ends when the tunnel is dropped.
+pub struct SshTunnel {
+    child: Child,
+    port: u16,
+}
+
+impl SshTunnel {
+    /// Start `ssh` and wait until its local proxy accepts connections.
+    /// `ssh` runs in batch mode, so the jump host must accept a key from
+    /// the agent or `identity_file`; it can't prompt for a password.
+    pub async fn open(settings: &SshTunnelSettings, timeout: Duration) -> Result<Self, CoreError> {
+        let port = free_local_port().await?;
+        let target = match &settings.user {
+            Some(user) => format!("{}@{}", user, settings.host),
+            None => settings.host.clone(),
+        };
+        info!("Opening SSH tunnel through {}", target);
+
+        let mut command = Command::new("ssh");
+        command
+            .arg("-N")
+            .arg("-D")
+            .arg(format!("127.0.0.1:{}", port))
+            .args(["-o", "BatchMode=yes", "-o", "ExitOnForwardFailure=yes"])
+            .arg("-p")
+            .arg(settings.port.to_string());
+        if let Some(identity) = &settings.identity_file {
+            command.arg("-i").arg(identity);
+        }
+        let mut child = command
+            .arg("--")
+            .arg(&target)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| CoreError::ConnectionFailed(format!("Couldn't run ssh: {}", e)))?;
+
+        // Keep reading ssh's stderr for as long as it runs, so a chatty
+        // jump host can't fill the pipe and stall the tunnel.
+        let drain = child.stderr.take().map(|pipe| {
+            tokio::spawn(async move {
+                let mut lines = BufReader::new(pipe).lines();
+                let mut output = Vec::new();
+                while let Ok(Some(line)) = lines.next_line().await {
+                    debug!("ssh: {}", line);
+                    if output.len() < STDERR_LINES {
+                        output.push(line);
+                    }
+                }
+                output.join("\n")
+            })
+        });
+
+        let started = tokio::time::Instant::now();
+        loop {
+            if let Ok(Some(status)) = child.try_wait() {
+                let stderr = match drain {
+                    Some(task) => task.await.unwrap_or_default(),
+                    None => String::new(),
+                };
+                return Err(CoreError::ConnectionFailed(format!(
+                    "SSH tunnel through {} exited ({}): {}",
+                    target,
+                    status,
+                    stderr.trim()
+                )));
+            }
+            if TcpStream::connect((Ipv4Addr::LOCALHOST, port))
+                .await
+                .is_ok()
+            {
+                debug!("SSH tunnel listening on 127.0.0.1:{}", port);
+                return Ok(Self { child, port });
+            }
+            if started.elapsed() >= timeout {
+                return Err(CoreError::ConnectionFailed(format!(
+                    "SSH tunnel through {} didn't start within {}s",
+                    target,
+                    timeout.as_secs()
+                )));
+            }
+            tokio::time::sleep(Duration::from_millis(100)).await;
+        }
+    }
+
+    /// The local port of the tunnel's SOCKS5 proxy.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Whether the `ssh` process is still running.
+    pub fn is_alive(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+}
+
+async fn free_local_port() -> Result<u16, CoreError> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, 0))
+        .await
+        .map_err(|e| CoreError::ConnectionFailed(e.to_string()))?;
+    listener
+        .local_addr()
+        .map(|addr| addr.port())
+        .map_err(|e| CoreError::ConnectionFailed(e.to_string()))
+}
+
+/// Open a TCP stream to the settings' host and port through the SSH
+/// tunnel listening on the `tunnel` port, or the proxy, if any; `None`
+/// means ldap3 connects directly.
+pub(crate) async fn open_stream(
+    settings: &ConnectionSettings,
+    tunnel: Option<u16>,
+    timeout: Duration,
+) -> Result<Option<std::net::TcpStream>, CoreError> {
+    let host = settings.host.as_str();
+    let port = settings.port;
+    let connect = async {
+        let stream = match (tunnel, &settings.proxy) {
+            (Some(local), _) => {
+                let mut stream = TcpStream::connect((Ipv4Addr::LOCALHOST, local)).await?;
+                socks5_handshake(&mut stream, host, port, None).await?;
+                stream
+            }
+            (None, Some(proxy)) => {
+                let mut stream = TcpStream::connect(proxy.address.as_str()).await?;
+                match proxy.protocol {
+                    ProxyProtocol::Socks5 => {
+                        socks5_handshake(&mut stream, host, port, proxy.credentials()).await?
+                    }
+                    ProxyProtocol::Http => {
+                        http_connect_handshake(&mut stream, host, port, proxy.credentials()).await?
+                    }
+                }
+                stream
+            }
+            (None, None) => return Ok(None),
+        };
+        stream.into_std().map(Some)
+    };
+    match tokio::time::timeout(timeout, connect).await {
+        Ok(result) => {
+            result.map_err(|e| CoreError::ConnectionFailed(format!("{}:{}: {}", host, port, e)))
+        }
+        Err(_) => Err(CoreError::ConnectionFailed(format!(
+            "{}:{}: timed out connecting through the proxy",
+            host, port
+        ))),
+    }
+}
+
+fn proxy_error(msg: impl Into<String>) -> std::io::Error {
+    std::io::Error::other(msg.into())
+}
+
+/// Ask a SOCKS5 proxy (RFC 1928) to connect to `host:port`, with
+/// username/password authentication (RFC 1929) when given. Host names
+/// are resolved by the proxy.
+pub async fn socks5_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    credentials: Option<(&str, &str)>,
+) -> std::io::Result<()> {
+    let methods: &[u8] = if credentials.is_some() { &[0, 2] } else { &[0] };
+    let mut greeting = vec![5, methods.len() as u8];
+    greeting.extend_from_slice(methods);
+    stream.write_all(&greeting).await?;
+
+    let mut reply = [0u8; 2];
+    stream.read_exact(&mut reply).await?;
+    match (reply[1], credentials) {
+        (0, _) => {}
+        (2, Some((user, password))) => {
+            if user.len() > 255 || password.len() > 255 {
+                return Err(proxy_error("SOCKS5 username or password too long"));
+            }
+            let mut auth = vec![1, user.len() as u8];
+            auth.extend_from_slice(user.as_bytes());
+            auth.push(password.len() as u8);
+            auth.extend_from_slice(password.as_bytes());
+            stream.write_all(&auth).await?;
+            let mut status = [0u8; 2];
+            stream.read_exact(&mut status).await?;
+            if status[1] != 0 {
+                return Err(proxy_error(
+                    "SOCKS5 proxy rejected the username or password",
+                ));
+            }
+        }
+        _ => {
+            return Err(proxy_error(
+                "SOCKS5 proxy accepts none of the offered authentication methods",
+            ))
+        }
+    }
+
+    let mut request = vec![5, 1, 0];
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => {
+            request.push(1);
+            request.extend_from_slice(&ip.octets());
+        }
+        Ok(IpAddr::V6(ip)) => {
+            request.push(4);
+            request.extend_from_slice(&ip.octets());
+        }
+        Err(_) => {
+            if host.len() > 255 {
+                return Err(proxy_error("Host name too long for SOCKS5"));
+            }
+            request.push(3);
+            request.push(host.len() as u8);
+            request.extend_from_slice(host.as_bytes());
+        }
+    }
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut head = [0u8; 4];
+    stream.read_exact(&mut head).await?;
+    if head[1] != 0 {
+        let reason = match head[1] {
+            1 => "general failure",
+            2 => "connection not allowed by ruleset",
+            3 => "network unreachable",
+            4 => "host unreachable",
+            5 => "connection refused",
+            6 => "TTL expired",
+            7 => "command not supported",
+            8 => "address type not supported",
+            _ => "unknown error",
+        };
+        return Err(proxy_error(format!("SOCKS5 proxy: {}", reason)));
+    }
+    // Skip the bound address and port
+    let addr_len = match head[3] {
+        1 => 4,
+        4 => 16,
+        3 => {
+            let mut len = [0u8; 1];
+            stream.read_exact(&mut len).await?;
+            len[0] as usize
+        }
+        _ => return Err(proxy_error("SOCKS5 proxy sent an unknown address type")),
+    };
+    let mut bound = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut bound).await?;
+    Ok(())
+}
+
+/// Ask an HTTP proxy to open a tunnel to `host:port` with CONNECT, with
+/// Basic authentication when given.
+pub async fn http_connect_handshake<S: AsyncRead + AsyncWrite + Unpin>(
+    stream: &mut S,
+    host: &str,
+    port: u16,
+    credentials: Option<(&str, &str)>,
+) -> std::io::Result<()> {
+    let authority = match host.parse::<IpAddr>() {
+        Ok(IpAddr::V6(_)) => format!("[{}]:{}", host, port),
+        _ => format!("{}:{}", host, port),
+    };
+    let mut request = format!("CONNECT {0} HTTP/1.1\r\nHost: {0}\r\n", authority);
+    if let Some((user, password)) = credentials {
+        let token =
+            base64::engine::general_purpose::STANDARD.encode(format!("{}:{}", user, password));
+        request.push_str(&format!("Proxy-Authorization: Basic {}\r\n", token));
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes()).await?;
+
+    // Read the response head a byte at a time so nothing after it, which
+    // belongs to the tunnelled connection, is consumed
+    let mut head = Vec::new();
+    while !head.ends_with(b"\r\n\r\n") {
+        if head.len() > 8192 {
+            return Err(proxy_error("HTTP proxy response too long"));
+        }
+        let mut byte = [0u8; 1];
+        stream.read_exact(&mut byte).await?;
+        head.push(byte[0]);
+    }
+    let head = String::from_utf8_lossy(&head);
+    let status_line = head.lines().next().unwrap_or_default();
+    let status = status_line.split_whitespace().nth(1).unwrap_or_default();
+    if !status.starts_with('2') {
+        return Err(proxy_error(format!("HTTP proxy: {}", status_line)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_socks5_handshake() {
+        let (mut client, mut server) = tokio::io::duplex(256);
+        let proxy = tokio::spawn(async move {
+            let mut greeting = [0u8; 4];
+            server.read_exact(&mut greeting).await.unwrap();
+            assert_eq!(greeting, [5, 2, 0, 2]);
+            server.write_all(&[5, 2]).await.unwrap();
+            let mut auth = [0u8; 10];
+            server.read_exact(&mut auth).await.unwrap();
+            assert_eq!(&auth, b"\x01\x03bob\x04pass");
+            server.write_all(&[1, 0]).await.unwrap();
+            let mut request = [0u8; 5 + 16 + 2];
+            server.read_exact(&mut request).await.unwrap();
+            assert_eq!(&request[..5], &[5, 1, 0, 3, 16]);
+            assert_eq!(&request[5..21], b"ldap.example.com");
+            assert_eq!(&request[21..], &636u16.to_be_bytes());
+            server
+                .write_all(&[5, 0, 0, 1, 10, 0, 0, 1, 0x02, 0x7c])
+                .await
+                .unwrap();
+        });
+        socks5_handshake(&mut client, "ldap.example.com", 636, Some(("bob", "pass")))
+            .await
+            .unwrap();
+        proxy.await.unwrap();
+
+        // A refused connection is reported with its reason
+        let (mut client, mut server) = tokio::io::duplex(256);
+        tokio::spawn(async move {
+            let mut buf = [0u8; 3];
+            server.read_exact(&mut buf).await.unwrap();
+            server.write_all(&[5, 0]).await.unwrap();
+            let mut request = [0u8; 10];
+            server.read_exact(&mut request).await.unwrap();
+            assert_eq!(request[3], 1);
+            server.write_all(&[5, 5, 0, 1]).await.unwrap();
+        });
+        let err = socks5_handshake(&mut client, "10.0.0.5", 389, None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.to_string(), "SOCKS5 proxy: connection refused");
+    }
+
+    #[tokio::test]
+    async fn test_http_connect_handshake() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            let mut request = vec![0u8; 1024];
+            let n = server.read(&mut request).await.unwrap();
+            let request = String::from_utf8_lossy(&request[..n]).to_string();
+            assert!(request.starts_with("CONNECT ldap.example.com:389 HTTP/1.1\r\n"));
+            assert!(request.contains("Proxy-Authorization: Basic Ym9iOnBhc3M=\r\n"));
+            server
+                .write_all(b"HTTP/1.1 200 Connection established\r\n\r\n\x30")
+                .await
+                .unwrap();
+        });
+        http_connect_handshake(&mut client, "ldap.example.com", 389, Some(("bob", "pass")))
+            .await
+            .unwrap();
+        // The tunnelled bytes are left for the LDAP connection
+        let mut first = [0u8; 1];
+        client.read_exact(&mut first).await.unwrap();
+        assert_eq!(first, [0x30]);
+
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        tokio::spawn(async move {
+            let mut request = vec![0u8; 1024];
+            let _ = server.read(&mut request).await.unwrap();
+            server
+                .write_all(b"HTTP/1.1 407 Proxy Authentication Required\r\n\r\n")
+                .await
+                .unwrap();
+        });
+        let err = http_connect_handshake(&mut client, "ldap.example.com", 389, None)
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "HTTP proxy: HTTP/1.1 407 Proxy Authentication Required"
+        );
+    }
+}
@@ -150,6 +150,11 @@ impl Vault {
         path.is_file()
     }
 
+    /// Path of the vault file on disk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
     /// Default vault file path: `<config_dir>/loom-ldapbrowser/vault.dat`.
     pub fn default_path() -> PathBuf {
         dirs::config_dir()
@@ -1,10 +1,19 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
 
 use anyhow::Result;
 use clap::Parser;
 use tracing::{info, warn};
-use tracing_subscriber::EnvFilter;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{EnvFilter, Layer};
 
+use loom_core::compare::SnapshotDiff;
+use loom_core::connection::LdapConnection;
+use loom_core::reports::{Report, ReportQuery};
+use loom_core::server_detect::ServerType;
+use loom_core::tls::TrustStore;
+use loom_core::util::find_values_ci;
 use loom_core::vault::Vault;
 use loom_tui::app::App;
 use loom_tui::config::AppConfig;
@@ -43,27 +52,95 @@ struct Cli {
     /// Path to vault file (default: ~/.config/loom-ldapbrowser/vault.dat)
     #[arg(long)]
     vault: Option<String>,
+
+    /// Compare two snapshot files (LDIF/JSON/CSV/XLSX exports of the same subtree), print a change report and exit
+    #[arg(long, num_args = 2, value_names = ["OLD", "NEW"])]
+    compare: Option<Vec<String>>,
+
+    /// Write the --compare report to this file (.md or .html) instead of stdout
+    #[arg(long, requires = "compare")]
+    report: Option<String>,
+
+    /// Attributes to leave out of the --compare report (comma-separated, e.g. modifyTimestamp)
+    #[arg(long, requires = "compare", value_delimiter = ',')]
+    ignore_attr: Vec<String>,
+
+    /// Open every profile of this workspace (a [[workspaces]] entry in the config) at startup
+    #[arg(short, long)]
+    workspace: Option<String>,
+
+    /// Send a command to the running instance's ipc_socket and exit (e.g. "goto <DN>", "search <FILTER>", "export <PATH> [FILTER]")
+    #[arg(long, value_name = "COMMAND")]
+    remote: Option<String>,
+
+    /// Run a report without the TUI and exit: a [[reports]] name from the config, or expiring, inactive, neverexpires or disabled
+    #[arg(long, value_name = "NAME")]
+    run_report: Option<String>,
+
+    /// Connection profile to run --run-report against (default: the first one)
+    #[arg(long, requires = "run_report")]
+    profile: Option<String>,
+
+    /// A --run-report parameter (repeatable, e.g. --param days=60)
+    #[arg(long = "param", requires = "run_report", value_name = "NAME=VALUE")]
+    params: Vec<String>,
+
+    /// Write the --run-report results to this file (the format follows the extension) instead of listing them on stdout
+    #[arg(long, requires = "run_report")]
+    output: Option<String>,
 }
 
-#[tokio::main]
-async fn main() -> Result<()> {
+fn main() -> Result<()> {
+    // Claiming a socket-activation socket edits the environment, which
+    // must happen before the runtime's worker threads start
+    loom_tui::ipc::claim_activation();
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()?
+        .block_on(run())
+}
+
+async fn run() -> Result<()> {
     rustls::crypto::ring::default_provider()
         .install_default()
         .expect("Failed to install rustls crypto provider");
 
     let cli = Cli::parse();
 
+    // Snapshot comparison runs without the TUI or a server connection
+    if let Some(paths) = &cli.compare {
+        return compare_snapshots(
+            &paths[0],
+            &paths[1],
+            cli.report.as_deref(),
+            &cli.ignore_attr,
+        );
+    }
+
+    if let Some(command) = &cli.remote {
+        return send_remote(command);
+    }
+
+    if let Some(name) = &cli.run_report {
+        return run_report(&cli, name).await;
+    }
+
     // Initialize logging to ./logs/ directory at debug level
     let log_dir = std::path::PathBuf::from("./logs");
     std::fs::create_dir_all(&log_dir)?;
     let log_file = std::fs::File::create(log_dir.join("loom-ldapbrowser.log"))?;
 
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::from_default_env().add_directive("loom_ldapbrowser=debug".parse()?),
-        )
+    // The file gets what RUST_LOG asks for; the log panel gets debug and
+    // above from the loom crates
+    let file_layer = tracing_subscriber::fmt::layer()
         .with_writer(log_file)
         .with_ansi(false)
+        .with_filter(
+            EnvFilter::from_default_env().add_directive("loom_ldapbrowser=debug".parse()?),
+        );
+    tracing_subscriber::registry()
+        .with(file_layer)
+        .with(loom_tui::log_capture::layer())
         .init();
 
     info!("loom-ldapbrowser starting");
@@ -81,11 +158,26 @@ async fn main() -> Result<()> {
             tls_mode: loom_core::connection::TlsMode::Auto,
             bind_dn: cli.bind_dn,
             base_dn: cli.base_dn,
+            start_dn: None,
+            default_filter: None,
+            root_dse_attributes: Vec::new(),
+            favorites: Vec::new(),
+            saved_searches: Vec::new(),
+            edit_policy: None,
             credential_method: loom_core::credentials::CredentialMethod::Prompt,
             password_command: None,
             page_size: 500,
             timeout_secs: 30,
+            pool_size: 3,
             relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            allow_commands: false,
+            dns_srv: false,
+            shuffle_hosts: false,
+            safety: None,
             folder: None,
             read_only: false,
             offline: false,
@@ -98,13 +190,148 @@ async fn main() -> Result<()> {
 
     // Create and run the app
     let mut app = App::new(config, vault);
-    app.connect_first_profile().await;
+    match cli.workspace {
+        Some(name) => app.open_workspace(&name).await,
+        None => app.connect_first_profile().await,
+    }
     app.run().await?;
 
     info!("loom-ldapbrowser exiting");
     Ok(())
 }
 
+/// Compare two exported snapshots and write the report to `report` (or stdout).
+fn compare_snapshots(
+    old_path: &str,
+    new_path: &str,
+    report: Option<&str>,
+    ignored: &[String],
+) -> Result<()> {
+    let old = loom_core::import::import_entries(Path::new(old_path))?;
+    let new = loom_core::import::import_entries(Path::new(new_path))?;
+    let diff = SnapshotDiff::compare(&old, &new, ignored);
+
+    match report {
+        Some(path) => {
+            diff.write_report(Path::new(path), old_path, new_path)?;
+            eprintln!("{} ({})", diff.summary(), path);
+        }
+        None => print!("{}", diff.to_markdown(old_path, new_path)),
+    }
+    Ok(())
+}
+
+/// Connect to a profile, run the report `name` and write the results to
+/// `--output`, or list their DNs and columns on stdout, tab-separated.
+async fn run_report(cli: &Cli, name: &str) -> Result<()> {
+    let config = AppConfig::load();
+    let profile = match &cli.profile {
+        Some(profile) => config
+            .connections
+            .iter()
+            .find(|p| p.name.eq_ignore_ascii_case(profile))
+            .ok_or_else(|| anyhow::anyhow!("No connection profile named {}", profile))?,
+        None => config
+            .connections
+            .first()
+            .ok_or_else(|| anyhow::anyhow!("No connection profiles configured"))?,
+    };
+    if profile.offline {
+        anyhow::bail!(
+            "{} is an offline profile; reports need a server",
+            profile.name
+        );
+    }
+    let mut overrides = Vec::new();
+    for param in &cli.params {
+        let (param, value) = param
+            .split_once('=')
+            .ok_or_else(|| anyhow::anyhow!("--param {} is not NAME=VALUE", param))?;
+        overrides.push((param.to_string(), value.to_string()));
+    }
+
+    let vault = load_vault(&cli.vault_password, &cli.vault, &config);
+    let mut password = loom_tui::app::resolve_password(profile, &vault)?;
+    if profile.bind_dn.is_some() && password.is_empty() {
+        password =
+            rpassword::read_password_from_tty(Some(&format!("Password for {}: ", profile.name)))?;
+    }
+    let trust_store = Arc::new(TrustStore::from_config(&config.trusted_certificates));
+    let mut conn = LdapConnection::connect(
+        loom_tui::app::connection_settings(profile, &vault),
+        Some(trust_store),
+    )
+    .await?;
+    conn.bind(profile.bind_dn.as_deref(), &password).await?;
+    // Also fills in the base DN when the profile has none
+    let root_dse = conn.read_root_dse(&[]).await?;
+    let base_dn = conn.base_dn.clone();
+
+    let (entries, columns) = if let Some(report) = config.report(name) {
+        let values = report.values(&overrides).map_err(anyhow::Error::msg)?;
+        let entries = conn
+            .run_report_definition(&base_dn, report, &values)
+            .await?;
+        (entries, report.columns.clone())
+    } else if let Some(report) = Report::from_name(name) {
+        let days = match overrides.as_slice() {
+            [] => report.default_days().unwrap_or(0),
+            [(param, days)] if param == "days" => days
+                .parse()
+                .map_err(|_| anyhow::anyhow!("days must be a number, not {}", days))?,
+            _ => anyhow::bail!("{} only takes --param days=N", name),
+        };
+        let active_directory = root_dse.server_type == ServerType::ActiveDirectory;
+        let query = ReportQuery::new(report, days, active_directory);
+        (conn.run_report(&base_dn, &query).await?, Vec::new())
+    } else {
+        anyhow::bail!("No report named {}", name);
+    };
+
+    match &cli.output {
+        Some(path) => {
+            let attributes = if columns.is_empty() {
+                vec!["*".to_string()]
+            } else {
+                columns
+            };
+            let count = loom_core::export::export_entries(&entries, Path::new(path), &attributes)?;
+            eprintln!("{} entries ({})", count, path);
+        }
+        None => {
+            let mut header = vec!["dn".to_string()];
+            header.extend(columns.iter().cloned());
+            println!("{}", header.join("\t"));
+            for entry in &entries {
+                let mut row = vec![entry.dn.clone()];
+                row.extend(columns.iter().map(|attr| {
+                    find_values_ci(&entry.attributes, attr)
+                        .map(|values| values.join("; "))
+                        .unwrap_or_default()
+                }));
+                println!("{}", row.join("\t"));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Pass a remote control command to the running instance and print its reply.
+fn send_remote(command: &str) -> Result<()> {
+    let config = AppConfig::load();
+    let Some(socket) = config.general.ipc_socket.as_deref() else {
+        anyhow::bail!("Set ipc_socket under [general] in the config to use --remote");
+    };
+    let path = loom_tui::config::expand_home(socket);
+    let reply = loom_tui::ipc::send_command(&path, command)
+        .map_err(|e| anyhow::anyhow!("Could not reach {}: {}", path.display(), e))?;
+    if let Some(error) = reply.strip_prefix("error: ") {
+        anyhow::bail!("{}", error);
+    }
+    println!("{}", reply);
+    Ok(())
+}
+
 /// Attempt to open the vault if vault_enabled is set in config.
 /// Returns None if vault is not enabled, doesn't exist yet, or can't be opened.
 fn load_vault(
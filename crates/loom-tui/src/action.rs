@@ -1,12 +1,28 @@
+use loom_core::attribute_stats::SubtreeStats;
+use loom_core::bulk::{ComputedValue, EditOutcome, Throttle, ValueEdit};
+use loom_core::duplicates::DuplicateReport;
 use loom_core::entry::LdapEntry;
+use loom_core::export::report::ReportSection;
+use loom_core::export::ExportFormat;
+use loom_core::groups::{Expansion, MembershipReport};
+use loom_core::import::plan::ImportItem;
+use loom_core::modify::ModifyConflict;
+use loom_core::monitor::ServerMetrics;
+use loom_core::orphans::{OrphanReport, Reference};
+use loom_core::password_policy::PasswordPolicyReport;
+use loom_core::rename::RenamePlan;
+use loom_core::replication::ReplicationReport;
+use loom_core::reports::Report;
 use loom_core::schema::SchemaCache;
 use loom_core::server_detect::ServerType;
 use loom_core::tls::CertificateInfo;
-use loom_core::tree::TreeNode;
+use loom_core::tree::{ChildCount, TreeNode};
 
 use crate::components::attribute_editor::EditResult;
 use crate::components::bulk_update_dialog::BulkOp;
 use crate::config::ConnectionProfile;
+use crate::ipc::{RemoteCommand, RpcCall};
+use crate::jobs::{JobId, JobState};
 
 /// Unique identifier for a connection tab.
 pub type ConnectionId = usize;
@@ -29,6 +45,11 @@ pub enum ContextMenuSource {
         attr_name: String,
         attr_value: String,
     },
+    /// A search result, with the columns the results table shows.
+    SearchResult {
+        dn: String,
+        columns: Vec<String>,
+    },
 }
 
 /// All actions that can flow through the application.
@@ -52,6 +73,9 @@ pub enum Action {
     CloseTab(ConnectionId),
     CloseCurrentTab,
     SwitchTab(ConnectionId),
+    ToggleSplitView,
+    SwapSplitFocus,
+    CopyToOtherSide,
 
     // Connection
     ShowConnectDialog,
@@ -60,6 +84,7 @@ pub enum Action {
     ConnectAdHoc(ConnectionProfile, String), // profile + password (never saved)
     PromptCredentials(ConnectionProfile),    // show credential prompt for profile
     ConnectWithCredentials(ConnectionProfile, String), // retry with user-provided credentials
+    RestoreSession,                          // reopen the tabs saved on the last quit
     Connected(ConnectionId, String, ServerType),
     Disconnected(ConnectionId),
     ConnectionError(String),
@@ -70,9 +95,35 @@ pub enum Action {
     TreeCollapse(String),
     TreeSelect(String),
     TreeChildrenLoaded(ConnectionId, String, Vec<TreeNode>),
+    RefreshSubtree(String), // reload loaded children below a DN
+    ChildCountLoaded(ConnectionId, String, ChildCount),
     TreeUp,
     TreeDown,
     TreeToggle,
+    RevealEntry(String), // expand the tree down to a DN and select it
+    ShowGoToDn,
+    ShowQuickOpen,
+    FollowReference(String, String), // attr, referenced DN
+    NavigateBack,
+    NavigateForward,
+    ShowHistory,
+    HistoryJump(usize),      // index into the tab's history
+    ToggleFavorite(String),  // dn
+    ToggleReference(String), // dn
+    ShowFavorites,
+    ShowWorkspaces,
+    ShowCommandPalette,
+    ShowCommandLine,
+    RunCommandLine(String),
+    OpenWorkspace(String),
+    FavoritesChecked(ConnectionId, Vec<String>), // DNs that no longer exist
+    SaveSearch {
+        name: String,
+        filter: String,
+    },
+    RunSavedSearch(String),    // name
+    DeleteSavedSearch(String), // name
+    SavedSearchResults(ConnectionId, String, Result<Vec<String>, String>), // name, matching DNs
 
     // Entry Detail
     EntryLoaded(ConnectionId, LdapEntry),
@@ -93,14 +144,27 @@ pub enum Action {
         generation: u64,
         entries: Vec<LdapEntry>,
     },
+    SampleAttributeValues(String), // attr to offer value completions for
+    AttributeValuesSampled(ConnectionId, String, Vec<String>),
 
     // Attribute Editing
     EditAttribute(String, String, String), // dn, attr_name, current_value
+    EditInExternalEditor(String, String, String), // dn, attr_name, current_value
     AddAttribute(String, String),          // dn, attr_name
     ShowAddAttribute(String),              // dn — opens attribute picker
     DeleteAttributeValue(String, String, String), // dn, attr, value
     SaveAttribute(EditResult),
     AttributeSaved(String), // dn that was updated
+    ShowModifyConflict(Box<ModifyConflict>),
+    ConflictMerge {
+        dn: String,
+        attr: String,
+        base_value: String,
+        draft: String,
+    },
+    ConflictAborted(String), // dn to reload
+    ShowRecentEntries,
+    OpenRecentEntry(String), // dn
     DnSearchRequest {
         generation: u64,
         query: String,
@@ -124,7 +188,26 @@ pub enum Action {
         filter: String,
         attributes: Vec<String>,
     },
-    ExportComplete(String), // success message
+    ExportComplete(String),               // success message
+    ShowExportEntriesDialog(Vec<String>), // dns to export
+    ExportEntries {
+        dns: Vec<String>,
+        path: String,
+        attributes: Vec<String>,
+    },
+    ShowImportDialog,
+    ImportPreview(Vec<LdapEntry>), // parsed entries to check against the server
+    ImportPreviewReady(Vec<ImportItem>), // entries in tree order, conflicts flagged
+    ImportExecute(Vec<ImportItem>), // entries with conflicts resolved
+    ImportComplete(String, Vec<String>), // summary + per-entry failures
+
+    // Entry reports
+    ShowReportDialog(Vec<String>), // dns to report on
+    ReportExecute {
+        dns: Vec<String>,
+        path: String,
+        sections: Vec<ReportSection>,
+    },
 
     // Bulk Update
     ShowBulkUpdateDialog,
@@ -133,8 +216,37 @@ pub enum Action {
         attribute: String,
         value: String,
         op: BulkOp,
+        throttle: Throttle,
     },
     BulkUpdateComplete(String), // result message
+    BulkPreview {
+        filter: String,
+        script: String,
+    },
+    BulkPreviewReady(usize, Vec<ComputedValue>), // matching entries, values of the first few
+
+    // Dry run
+    ToggleDryRun,
+    DryRunToggled(ConnectionId, bool, String), // tab, enabled, message
+    ExportDryRunScript,
+
+    // Staged changes
+    ToggleStaging,
+    ToggleChangesPanel,
+    ApplyStagedChanges {
+        stop_on_error: bool,
+    },
+    StagedChangesApplied(String, Vec<String>), // summary, problems
+    ExportStagedChanges,
+    DiscardStagedChanges,
+
+    // Group membership import
+    ShowMembershipImportDialog,
+    MembershipImportExecute {
+        path: String,
+        member_attr: String,
+    },
+    MembershipImportComplete(String, Vec<String>), // summary + per-row problems
 
     // Create / Delete Entry
     ShowCreateEntryDialog(String), // parent DN
@@ -146,8 +258,108 @@ pub enum Action {
     DeleteEntry(String),  // DN to delete
     EntryDeleted(String), // DN that was deleted
 
+    // Move Entry (cut / paste under)
+    CutEntry {
+        dn: String,
+        subtree: bool,
+    },
+    PasteEntry(String), // new parent DN
+    MoveEntry {
+        dn: String,
+        new_parent: String,
+        subtree: bool,
+        /// Copy the subtree and delete the originals if the server can't
+        /// move it in place.
+        copy_fallback: bool,
+    },
+    EntryMoved {
+        old_dn: String,
+        new_dn: String,
+        /// Entries re-added when the server couldn't move the subtree in place.
+        copied: Option<usize>,
+    },
+
+    // Safe rename of an AD user
+    ShowRenameUser(String), // dn
+    SafeRenameExecute(Box<RenamePlan>),
+    SafeRenameComplete {
+        old_dn: String,
+        new_dn: String,
+    },
+
+    // Password policy
+    ShowPasswordPolicy(String), // dn
+    PasswordPolicyLoaded(Box<PasswordPolicyReport>),
+
+    // Nested group membership; without an expansion, the entry's default
+    ShowNestedMembership {
+        dn: String,
+        expansion: Option<Expansion>,
+    },
+    NestedMembershipLoaded(Box<MembershipReport>),
+
+    // Orphaned references under a subtree
+    ShowOrphanScan(String), // base dn
+    OrphanScanLoaded(Box<OrphanReport>),
+    SaveOrphanReport,
+    CleanupOrphans(Vec<Reference>),
+    OrphanCleanupComplete(String, Vec<String>), // summary, problems
+
+    // One attribute edited across the entries marked in search results
+    ShowBatchEdit(Vec<String>), // dns
+    BatchEditLoad {
+        dns: Vec<String>,
+        attr: String,
+    },
+    BatchEditLoaded {
+        attr: String,
+        values: Vec<(String, Vec<String>)>,
+        errors: Vec<(String, String)>,
+    },
+    BatchEditExecute {
+        attr: String,
+        edits: Vec<ValueEdit>,
+    },
+    BatchEditComplete {
+        attr: String,
+        summary: String,
+        outcomes: Vec<(String, EditOutcome)>,
+    },
+
+    // Values of an attribute held by several entries; without a base DN,
+    // under the tab's
+    FindDuplicates {
+        attribute: String,
+        base_dn: Option<String>,
+    },
+    DuplicatesLoaded(Box<DuplicateReport>),
+    SaveDuplicateReport,
+
+    // Attribute statistics of a subtree; without a sample size, every entry
+    ShowAttributeStats(String), // base dn
+    RunAttributeStats {
+        base_dn: String,
+        sample: Option<usize>,
+    },
+    AttributeStatsComplete(String, Box<SubtreeStats>), // summary, stats
+    SaveAttributeStats,
+
+    // Reports
+    ShowAccountReports,
+    RunAccountReport {
+        report: Report,
+        days: u32,
+    },
+    /// Run a `[[reports]]` entry by name, with parameter values.
+    RunDefinedReport {
+        name: String,
+        values: Vec<(String, String)>,
+    },
+    ExportSearchResults,
+
     // Schema
     ShowSchemaViewer,
+    SchemaExport(String), // path; the extension picks LDIF, JSON or Markdown
 
     // Help / About
     ShowHelp,
@@ -156,9 +368,38 @@ pub enum Action {
 
     // Log Panel
     ToggleLogPanel,
+    SaveLog(String), // the shown log lines
+
+    // Operation trace
+    ToggleTracePanel,
+    SaveTrace(String), // the listed requests, one per line
+    ClearTrace,
+
+    // Server metrics
+    ToggleMetricsPanel,
+    RefreshMetrics,
+    MetricsLoaded(ConnectionId, Box<ServerMetrics>),
+    MetricsFailed(ConnectionId, String),
+
+    // Replication
+    ToggleReplicationPanel,
+    RefreshReplication,
+    ReplicationLoaded(ConnectionId, Box<ReplicationReport>),
+    ReplicationSampled(ConnectionId, Box<ReplicationReport>), // for the metrics endpoint
+    ReplicationFailed(ConnectionId, String),
+
+    // Jobs
+    ToggleJobsPanel,
+    JobProgress(JobId, usize, Option<usize>), // done so far, total if known
+    JobFinished(JobId, JobState),
+    CancelJob(JobId),
+    RetryJob(JobId),
+    ClearFinishedJobs,
 
     // Popup / Modal
     ShowConfirm(String, Box<Action>),
+    /// A write the tab's safety policy has already let through.
+    PolicyCleared(Box<Action>),
     PopupConfirm,
     PopupCancel,
     ClosePopup,
@@ -166,6 +407,9 @@ pub enum Action {
     // Status
     StatusMessage(String),
     ErrorMessage(String),
+    /// A reconnect landed on this server of the tab's failover hosts or
+    /// SRV records.
+    ActiveServer(ConnectionId, String),
 
     // Layout switching
     SwitchLayout(ActiveLayout),
@@ -183,6 +427,9 @@ pub enum Action {
     ConnMgrImportExecute(Vec<ConnectionProfile>), // commit selected imported profiles
     ConnMgrSelectFolder(String),                // folder path selected in tree
     ConnMgrSaveFolderDesc(String, String),      // (folder path, new description)
+    ConnMgrMoveProfile(usize, isize),           // reorder profile within its folder
+    ShowMoveProfileDialog(usize),               // pick a new folder for a profile
+    ConnMgrMoveToFolder(usize, Option<String>), // (profile index, folder; None = top level)
 
     // Certificate Trust
     ShowCertTrustDialog {
@@ -201,12 +448,30 @@ pub enum Action {
     // Context Menu
     ShowContextMenu(ContextMenuSource),
     CopyToClipboard(String),
+    CopyLdapSearch,
+    CopyLdapModify(String), // LDIF change script
+    CopyEntryAs {
+        dn: String,
+        format: ExportFormat,
+        attributes: Vec<String>,
+    },
 
     // Vault
     VaultStorePassword(String, String), // profile name + password
     VaultSetupPrompt,                   // trigger vault creation flow
     VaultPasswordEntered(String),       // vault master password from dialog
 
+    // Remote control (local socket)
+    RemoteCommand(RemoteCommand),
+    RpcCall(RpcCall), // JSON-RPC request, answered through the call
+
+    // Idle lock
+    LockSession,
+    UnlockSession(String), // vault master password from lock screen
+    RebindNeeded(ConnectionId, String), // tab + bind DN whose password the lock wiped
+    RebindWithCredentials(ConnectionId, String, String), // tab + bind DN + password
+    SkipRebind(ConnectionId), // unlock password prompt dismissed
+
     // No-op
     None,
 }
@@ -1,60 +1,135 @@
-use std::path::PathBuf;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
-use crossterm::event::{KeyCode, KeyModifiers, MouseEventKind};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers, MouseEventKind};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
-use tokio::sync::Mutex;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
-use loom_core::bulk::BulkMod;
-use loom_core::connection::LdapConnection;
+use loom_core::audit::AuditLog;
+use loom_core::bulk::{BulkMod, EditOutcome, Throttle, ValueEdit};
+use loom_core::cache::EntryCache;
+use loom_core::cli_command;
+use loom_core::connection::{ConnectionSettings, LdapConnection};
 use loom_core::credentials::{CredentialMethod, CredentialProvider};
+use loom_core::entry::LdapEntry;
 use loom_core::error::CoreError;
+use loom_core::export::report::{write_report, ReportSection};
+use loom_core::export::schema::SchemaFormat;
+use loom_core::export::{self, ExportMetadata};
+use loom_core::filter::{expand_macros, MacroContext};
+use loom_core::format::DisplayFormats;
+use loom_core::groups::{self, Expansion};
+use loom_core::humanize;
+use loom_core::import::plan::ImportItem;
+use loom_core::modify::MoveMethod;
 use loom_core::offline::OfflineDirectory;
+use loom_core::orphans::{self, Reference, REFERENCE_ATTRIBUTES};
+use loom_core::policy::{EditPolicy, SafetyPolicy};
+use loom_core::pool::ConnectionPool;
+use loom_core::rename::RenamePlan;
+use loom_core::replication::ReplicationReport;
+use loom_core::reports::{Report, ReportDefinition, ReportQuery};
 use loom_core::schema::{AttributeSyntax, SchemaCache};
+use loom_core::schema_store::StoredSchema;
+use loom_core::staging::ChangeQueue;
 use loom_core::tls::{TrustStore, TrustedCertEntry};
-use loom_core::tree::{DirectoryTree, TreeNode};
+use loom_core::trace::OperationTrace;
+use loom_core::tree::{ChildCount, DirectoryTree, RevealStep, TreeNode};
+use loom_core::tunnel::ProxySettings;
+use loom_core::util::find_values_ci;
 use loom_core::vault::Vault;
 
 use crate::action::{Action, ActiveLayout, ConnectionId, ContextMenuSource, FocusTarget};
+use crate::commands::{CompletionSource, ExCommand};
 use crate::component::Component;
 use crate::components::about_popup::AboutPopup;
+use crate::components::account_reports::AccountReportsPopup;
 use crate::components::attribute_editor::{AttributeEditor, EditOp, EditResult};
 use crate::components::attribute_picker::AttributePicker;
-use crate::components::bulk_update_dialog::BulkUpdateDialog;
+use crate::components::attribute_stats_panel::AttributeStatsPanel;
+use crate::components::batch_edit_dialog::BatchEditDialog;
+use crate::components::bulk_update_dialog::{BulkUpdateDialog, PREVIEW_ENTRIES};
 use crate::components::cert_trust_dialog::CertTrustDialog;
+use crate::components::changes_panel::ChangesPanel;
+use crate::components::command_line::CommandLine;
+use crate::components::command_palette::{self, CommandPalette};
 use crate::components::command_panel::CommandPanel;
 use crate::components::confirm_dialog::ConfirmDialog;
+use crate::components::conflict_dialog::ConflictDialog;
 use crate::components::connect_dialog::ConnectDialog;
 use crate::components::connection_form::ConnectionForm;
 use crate::components::connections_tree::{ActiveConnInfo, ConnectionsTree};
-use crate::components::context_menu::ContextMenu;
+use crate::components::context_menu::{ContextMenu, MenuItem};
 use crate::components::create_entry_dialog::CreateEntryDialog;
 use crate::components::credential_prompt::CredentialPromptDialog;
 use crate::components::detail_panel::DetailPanel;
+use crate::components::duplicates_panel::DuplicatesPanel;
 use crate::components::export_dialog::ExportDialog;
+use crate::components::favorites_popup::FavoritesPopup;
+use crate::components::goto_dn_dialog::GoToDnDialog;
 use crate::components::help_popup::HelpPopup;
-use crate::components::layout_bar::LayoutBar;
+use crate::components::history_popup::HistoryPopup;
+use crate::components::import_dialog::ImportDialog;
+use crate::components::jobs_panel::JobsPanel;
+use crate::components::layout_bar::{LayoutBar, LayoutBarHit};
+use crate::components::lock_screen::LockScreen;
 use crate::components::log_panel::LogPanel;
+use crate::components::membership_import_dialog::MembershipImportDialog;
+use crate::components::membership_popup::MembershipPopup;
+use crate::components::metrics_panel::MetricsPanel;
+use crate::components::move_profile_dialog::MoveProfileDialog;
 use crate::components::new_connection_dialog::NewConnectionDialog;
+use crate::components::orphans_panel::OrphansPanel;
+use crate::components::password_policy_popup::PasswordPolicyPopup;
 use crate::components::profile_export_dialog::ProfileExportDialog;
 use crate::components::profile_import_dialog::ProfileImportDialog;
+use crate::components::quick_open::QuickOpenDialog;
+use crate::components::recent_popup::RecentPopup;
+use crate::components::rename_user_dialog::RenameUserDialog;
+use crate::components::replication_panel::ReplicationPanel;
+use crate::components::report_dialog::ReportDialog;
 use crate::components::schema_viewer::SchemaViewer;
 use crate::components::search_dialog::SearchDialog;
 use crate::components::status_bar::StatusBar;
 use crate::components::tab_bar::TabBar;
+use crate::components::trace_panel::TracePanel;
 use crate::components::tree_panel::TreePanel;
+use crate::components::tree_view::{SearchFolder, TreeViewState};
 use crate::components::vault_password_dialog::VaultPasswordDialog;
-use crate::config::{AppConfig, ConnectionProfile};
-use crate::event::{self, AppEvent};
+use crate::components::workspace_popup::WorkspacePopup;
+use crate::config::{expand_home, AppConfig, ConnectionProfile, SavedSearch};
+use crate::event::{self, AppEvent, ClickTracker};
+use crate::exporter::{ConnectionSample, MetricsExporter};
+use crate::external_editor::{self, ExternalEdit};
 use crate::focus::FocusManager;
-use crate::keymap::Keymap;
-use crate::theme::Theme;
+use crate::history::NavHistory;
+use crate::hooks::{HookContext, HookEvent, Hooks};
+use crate::ipc::{self, RemoteCommand, RemoteControl, RpcCall, RpcError};
+use crate::jobs::{self, JobHandle, JobRegistry, JobState, JobTask};
+use crate::keymap::{KeyContext, Keymap};
+use crate::log_capture;
+use crate::session::{Session, SessionTab};
+use crate::stats::{ConnectionStats, SessionStats};
+use crate::theme::{self, Theme, ThemeVariant};
 use crate::tui;
 
+/// Maximum member values sent in a single modify during membership import.
+const MEMBERSHIP_BATCH_SIZE: usize = 100;
+
+/// How many recently viewed entries are kept per connection.
+const RECENT_ENTRIES_LIMIT: usize = 20;
+
+/// A sized child count search stops here and shows `(n+)`.
+const CHILD_COUNT_LIMIT: usize = 1000;
+
+/// Nodes per level counted with a sized search, so opening a level with
+/// thousands of entries doesn't send thousands of searches.
+const MAX_CHILD_COUNT_SEARCHES: usize = 100;
+
 /// Which divider the user is dragging.
 #[derive(Debug, Clone, Copy)]
 enum DragTarget {
@@ -64,7 +139,7 @@ enum DragTarget {
 
 /// Backend for a connection tab — either live LDAP or offline/example.
 enum TabBackend {
-    Live(Arc<Mutex<LdapConnection>>),
+    Live(Arc<ConnectionPool>),
     Offline(OfflineDirectory),
 }
 
@@ -76,9 +151,85 @@ struct ConnectionTab {
     server_type: String,
     subschema_dn: Option<String>,
     read_only: bool,
+    /// Writes are recorded into a change script instead of sent.
+    dry_run: bool,
+    /// Writes are queued for review instead of sent.
+    staging: bool,
+    /// Filter pre-filled in the search input for this profile.
+    default_filter: Option<String>,
+    /// Values of the profile's extra RootDSE attributes.
+    server_info: Vec<(String, Vec<String>)>,
     backend: TabBackend,
     directory_tree: DirectoryTree,
     schema: Option<SchemaCache>,
+    history: NavHistory,
+    /// DNs pinned on the profile, shown in the tree's Favorites section.
+    favorites: Vec<String>,
+    /// Lowercased favorites found to no longer exist.
+    stale_favorites: HashSet<String>,
+    /// The profile's saved searches, with their results once run.
+    search_folders: Vec<SearchFolder>,
+    /// Values sampled for filter completion, keyed by lowercased attribute.
+    value_samples: HashMap<String, Vec<String>>,
+    /// Attributes owned by other systems, from the profile's policy file.
+    edit_policy: EditPolicy,
+    /// Entries recently read from the server.
+    entry_cache: EntryCache,
+    /// Safeguards the profile puts around destructive operations.
+    safety: Option<SafetyPolicy>,
+}
+
+impl ConnectionTab {
+    /// The label shown in the tab bar, marked while writes are held back.
+    fn display_label(&self) -> String {
+        let mut label = self.label.clone();
+        if self.dry_run {
+            label.push_str(" [dry run]");
+        }
+        if self.staging {
+            label.push_str(" [staging]");
+        }
+        label
+    }
+}
+
+/// The other connection shown in the split browser view. Its panels are
+/// swapped with the App's own tree and detail panels when focus crosses
+/// sides, so the focused side is always the active tab.
+struct SplitPane {
+    tab_id: ConnectionId,
+    tree_panel: TreePanel,
+    detail_panel: DetailPanel,
+    /// Whether this (unfocused) side is drawn on the left.
+    on_left: bool,
+}
+
+/// Tree state of a restored tab, applied as the saved nodes load.
+struct RestoringTab {
+    expanded: Vec<String>,
+    selected: Option<String>,
+    /// DNs whose children were requested, so each is loaded only once.
+    requested: HashSet<String>,
+}
+
+/// An entry marked with cut, waiting to be pasted under a new parent.
+struct CutMark {
+    conn_id: ConnectionId,
+    dn: String,
+    /// Move the whole subtree.
+    subtree: bool,
+}
+
+/// A file being written by an export, removed when dropped unless cleared,
+/// so an export that fails or is cancelled leaves nothing behind.
+struct PartialFile<'a>(Option<&'a Path>);
+
+impl Drop for PartialFile<'_> {
+    fn drop(&mut self) {
+        if let Some(path) = self.0 {
+            let _ = std::fs::remove_file(path);
+        }
+    }
 }
 
 /// The main application.
@@ -90,21 +241,69 @@ pub struct App {
     // Encrypted vault for password storage
     vault: Option<Vault>,
 
+    // Idle auto-lock: last user input, and the vault path while locked
+    last_activity: Instant,
+    locked_vault_path: Option<PathBuf>,
+
+    // Recently viewed entry DNs per connection label, newest first
+    recent_entries: HashMap<String, Vec<String>>,
+
+    // Session metrics for the stats file
+    started_at: Instant,
+    stats_written_at: Option<Instant>,
+    actions_processed: u64,
+    error_count: u64,
+
+    /// Remote control socket, while one is being served.
+    remote_control: Option<RemoteControl>,
+    /// Remote commands received while the session was locked, run once
+    /// it is unlocked.
+    locked_remote_commands: Vec<RemoteCommand>,
+    /// Tabs waiting to be asked for the bind password an idle lock wiped,
+    /// prompted one at a time.
+    pending_rebinds: VecDeque<(ConnectionId, ConnectionProfile)>,
+    /// Prometheus endpoint, while one is being served.
+    exporter: Option<MetricsExporter>,
+    /// When the metrics endpoint last had every tab's replication checked.
+    replication_sampled_at: Option<Instant>,
+    /// When the search or report now running was started, for its timing.
+    search_started: Option<Instant>,
+
+    // DN being expanded to in the tree once its ancestors finish loading
+    pending_reveal: Option<(ConnectionId, String)>,
+
+    // Session from the last run, while the offer to reopen it is showing
+    saved_session: Option<Session>,
+    // Restored tabs whose saved tree state is still being loaded
+    restoring: HashMap<ConnectionId, RestoringTab>,
+
     // Certificate trust
     trust_store: Arc<TrustStore>,
 
+    /// The audit file every tab's writes are logged to, when configured.
+    audit_log: Option<AuditLog>,
+    hooks: Hooks,
+
     // Layout state
     active_layout: ActiveLayout,
 
     // Connection tabs
     tabs: Vec<ConnectionTab>,
     active_tab_id: Option<ConnectionId>,
+    /// Second connection shown beside the active one, if split view is on.
+    split: Option<SplitPane>,
+    /// Compiled `[display.attributes]` rules, for panels created later.
+    display_formats: DisplayFormats,
+    cut_mark: Option<CutMark>,
 
     // Keymap
     keymap: Keymap,
 
     // Theme (for popup rendering)
     theme: Theme,
+    /// Theme and variant shown, which `:theme reload` reads again.
+    theme_name: String,
+    theme_variant: ThemeVariant,
 
     // UI components
     layout_bar: LayoutBar,
@@ -132,19 +331,48 @@ pub struct App {
     attribute_picker: AttributePicker,
     export_dialog: ExportDialog,
     bulk_update_dialog: BulkUpdateDialog,
+    membership_import_dialog: MembershipImportDialog,
+    import_dialog: ImportDialog,
+    conflict_dialog: ConflictDialog,
+    move_profile_dialog: MoveProfileDialog,
+    recent_popup: RecentPopup,
+    history_popup: HistoryPopup,
+    favorites_popup: FavoritesPopup,
+    workspace_popup: WorkspacePopup,
+    command_palette: CommandPalette,
+    command_line: CommandLine,
+    goto_dn_dialog: GoToDnDialog,
+    batch_edit_dialog: BatchEditDialog,
+    quick_open: QuickOpenDialog,
+    rename_user_dialog: RenameUserDialog,
+    report_dialog: ReportDialog,
     create_entry_dialog: CreateEntryDialog,
     schema_viewer: SchemaViewer,
     help_popup: HelpPopup,
     about_popup: AboutPopup,
     log_panel: LogPanel,
+    jobs_panel: JobsPanel,
+    trace_panel: TracePanel,
+    changes_panel: ChangesPanel,
+    metrics_panel: MetricsPanel,
+    replication_panel: ReplicationPanel,
+    password_policy_popup: PasswordPolicyPopup,
+    account_reports: AccountReportsPopup,
+    membership_popup: MembershipPopup,
+    orphans_panel: OrphansPanel,
+    duplicates_panel: DuplicatesPanel,
+    attribute_stats_panel: AttributeStatsPanel,
     profile_export_dialog: ProfileExportDialog,
     profile_import_dialog: ProfileImportDialog,
+    lock_screen: LockScreen,
 
     // Ad-hoc connection tracking (for save-to-config)
     last_adhoc_profile: Option<ConnectionProfile>,
 
     // Track areas for mouse hit-testing
     tree_area: Option<Rect>,
+    /// Recent left clicks, to spot double-clicks.
+    clicks: ClickTracker,
     detail_area: Option<Rect>,
     tab_area: Option<Rect>,
     layout_bar_area: Option<Rect>,
@@ -158,9 +386,15 @@ pub struct App {
     // Vim-style 'g' prefix state for gt/gT tab switching
     pending_g: bool,
 
+    // Value to open in $EDITOR once the run loop releases the terminal
+    pending_external_edit: Option<ExternalEdit>,
+
     // First-launch: after help popup closes, show the connect dialog
     show_connect_after_help: bool,
 
+    // Background operations listed in the jobs panel
+    jobs: JobRegistry,
+
     // Async communication
     action_tx: tokio::sync::mpsc::UnboundedSender<Action>,
     action_rx: tokio::sync::mpsc::UnboundedReceiver<Action>,
@@ -168,29 +402,88 @@ pub struct App {
 
 impl App {
     pub fn new(config: AppConfig, vault: Option<Vault>) -> Self {
-        let theme = Theme::load(&config.general.theme);
+        let (theme, theme_error) =
+            match Theme::load_variant(&config.general.theme, config.general.theme_variant) {
+                Ok(theme) => (theme, None),
+                Err(e) => (Theme::dark(), Some(format!("{}, using dark", e))),
+            };
         let keymap = Keymap::from_config(&config.keybindings);
         let status_bar = StatusBar::new(theme.clone(), &keymap);
         let (action_tx, action_rx) = tokio::sync::mpsc::unbounded_channel();
         let autocomplete_enabled = config.general.autocomplete;
         let live_search_enabled = config.general.live_search;
         let trust_store = Arc::new(TrustStore::from_config(&config.trusted_certificates));
+        let hooks = Hooks::new(config.hooks.clone(), action_tx.clone());
+        let audit_log =
+            config
+                .audit
+                .file
+                .as_deref()
+                .and_then(|file| match Self::expand_export_path(file) {
+                    Ok(path) => Some(AuditLog::new(&path, &config.audit)),
+                    Err(e) => {
+                        let _ = action_tx.send(Action::ErrorMessage(format!(
+                            "Audit log {} can't be written: {}",
+                            file, e
+                        )));
+                        None
+                    }
+                });
+        let (display_formats, format_errors) = DisplayFormats::new(&config.display.attributes);
+        for e in format_errors {
+            let _ = action_tx.send(Action::ErrorMessage(e));
+        }
+        let theme_name = config.general.theme.clone();
+        let theme_variant = config.general.theme_variant;
+        if let Some(e) = theme_error {
+            let _ = action_tx.send(Action::ErrorMessage(e));
+        }
+        for warning in keymap.warnings() {
+            let _ = action_tx.send(Action::ErrorMessage(warning.clone()));
+        }
+        let attribute_groups = config.display.attribute_groups();
+        let report_definitions = config.reports.clone();
 
         Self {
             config,
             should_quit: false,
             next_conn_id: 0,
             vault,
+            last_activity: Instant::now(),
+            locked_vault_path: None,
+            recent_entries: HashMap::new(),
+            started_at: Instant::now(),
+            stats_written_at: None,
+            actions_processed: 0,
+            error_count: 0,
+            remote_control: None,
+            locked_remote_commands: Vec::new(),
+            pending_rebinds: VecDeque::new(),
+            exporter: None,
+            replication_sampled_at: None,
+            search_started: None,
+            pending_reveal: None,
+            saved_session: None,
+            restoring: HashMap::new(),
             trust_store,
+            audit_log,
+            hooks,
             active_layout: ActiveLayout::Profiles,
             tabs: Vec::new(),
             active_tab_id: None,
+            split: None,
+            display_formats: display_formats.clone(),
+            cut_mark: None,
             keymap,
             theme: theme.clone(),
+            theme_name,
+            theme_variant,
             layout_bar: LayoutBar::new(theme.clone()),
             tab_bar: TabBar::new(theme.clone()),
             tree_panel: TreePanel::new(theme.clone()),
-            detail_panel: DetailPanel::new(theme.clone()),
+            detail_panel: DetailPanel::new(theme.clone())
+                .with_formats(display_formats.clone())
+                .with_groups(attribute_groups),
             command_panel: CommandPanel::new(
                 theme.clone(),
                 autocomplete_enabled,
@@ -207,20 +500,49 @@ impl App {
             new_connection_dialog: NewConnectionDialog::new(theme.clone()),
             credential_prompt: CredentialPromptDialog::new(theme.clone()),
             vault_password_dialog: VaultPasswordDialog::new(theme.clone()),
-            search_dialog: SearchDialog::new(theme.clone()),
+            search_dialog: SearchDialog::new(theme.clone()).with_formats(display_formats),
             attribute_editor: AttributeEditor::new(theme.clone()),
             attribute_picker: AttributePicker::new(theme.clone()),
             export_dialog: ExportDialog::new(theme.clone()),
             bulk_update_dialog: BulkUpdateDialog::new(theme.clone()),
+            membership_import_dialog: MembershipImportDialog::new(theme.clone()),
+            import_dialog: ImportDialog::new(theme.clone()),
+            conflict_dialog: ConflictDialog::new(theme.clone()),
+            move_profile_dialog: MoveProfileDialog::new(theme.clone()),
+            recent_popup: RecentPopup::new(theme.clone()),
+            history_popup: HistoryPopup::new(theme.clone()),
+            favorites_popup: FavoritesPopup::new(theme.clone()),
+            workspace_popup: WorkspacePopup::new(theme.clone()),
+            command_palette: CommandPalette::new(theme.clone()),
+            command_line: CommandLine::new(theme.clone()),
+            goto_dn_dialog: GoToDnDialog::new(theme.clone()),
+            batch_edit_dialog: BatchEditDialog::new(theme.clone()),
+            quick_open: QuickOpenDialog::new(theme.clone()),
+            rename_user_dialog: RenameUserDialog::new(theme.clone()),
+            report_dialog: ReportDialog::new(theme.clone()),
             create_entry_dialog: CreateEntryDialog::new(theme.clone()),
             schema_viewer: SchemaViewer::new(theme.clone()),
             help_popup: HelpPopup::new(theme.clone()),
             about_popup: AboutPopup::new(theme.clone()),
             log_panel: LogPanel::new(theme.clone()),
+            jobs_panel: JobsPanel::new(theme.clone()),
+            trace_panel: TracePanel::new(theme.clone()),
+            changes_panel: ChangesPanel::new(theme.clone()),
+            metrics_panel: MetricsPanel::new(theme.clone()),
+            replication_panel: ReplicationPanel::new(theme.clone()),
+            password_policy_popup: PasswordPolicyPopup::new(theme.clone()),
+            account_reports: AccountReportsPopup::new(theme.clone())
+                .with_definitions(report_definitions),
+            membership_popup: MembershipPopup::new(theme.clone()),
+            orphans_panel: OrphansPanel::new(theme.clone()),
+            duplicates_panel: DuplicatesPanel::new(theme.clone()),
+            attribute_stats_panel: AttributeStatsPanel::new(theme.clone()),
             profile_export_dialog: ProfileExportDialog::new(theme.clone()),
-            profile_import_dialog: ProfileImportDialog::new(theme),
+            profile_import_dialog: ProfileImportDialog::new(theme.clone()),
+            lock_screen: LockScreen::new(theme),
             last_adhoc_profile: None,
             tree_area: None,
+            clicks: ClickTracker::default(),
             detail_area: None,
             tab_area: None,
             layout_bar_area: None,
@@ -229,7 +551,9 @@ impl App {
             tree_split_pct: 25,
             drag_target: None,
             pending_g: false,
+            pending_external_edit: None,
             show_connect_after_help: false,
+            jobs: JobRegistry::new(action_tx.clone()),
             action_tx,
             action_rx,
         }
@@ -252,12 +576,203 @@ impl App {
         self.tabs.iter_mut().find(|t| t.id == id)
     }
 
+    /// The active tab's operation trace; offline tabs have none.
+    fn active_trace(&self) -> Option<OperationTrace> {
+        match &self.active_tab()?.backend {
+            TabBackend::Live(pool) => Some(pool.trace()),
+            TabBackend::Offline(_) => None,
+        }
+    }
+
+    /// The context for a hook on `conn_id`'s tab, or `None` when no hook is
+    /// set for the event, or it is a write held back by dry run or staging.
+    fn hook_context(&self, conn_id: ConnectionId, event: HookEvent) -> Option<HookContext> {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id)?;
+        if !self.hooks.is_set(event) || (event.is_write() && (tab.dry_run || tab.staging)) {
+            return None;
+        }
+        Some(HookContext::new(event, &tab.label, &tab.host))
+    }
+
+    /// Run the hook for a write to `dn` on the active tab.
+    fn run_entry_hook(&self, event: HookEvent, dn: &str) {
+        let context = self
+            .active_tab_id
+            .and_then(|id| self.hook_context(id, event));
+        if let Some(context) = context {
+            self.hooks.run(context.dn(dn));
+        }
+    }
+
+    /// The active tab's staged changes; offline tabs have none.
+    fn active_changes(&self) -> Option<ChangeQueue> {
+        match &self.active_tab()?.backend {
+            TabBackend::Live(pool) => Some(pool.changes()),
+            TabBackend::Offline(_) => None,
+        }
+    }
+
+    fn metrics_interval(&self) -> Duration {
+        Duration::from_secs(self.config.general.metrics_interval_secs.max(1))
+    }
+
+    /// Read the active tab's server metrics for the metrics view.
+    fn refresh_metrics(&mut self) {
+        let Some(conn_id) = self.active_tab_id else {
+            return;
+        };
+        self.metrics_panel.start_refresh(conn_id);
+        let tx = self.action_tx.clone();
+        match self.active_tab().map(|t| &t.backend) {
+            Some(TabBackend::Live(pool)) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    let action = match conn.server_metrics().await {
+                        Ok(metrics) => Action::MetricsLoaded(conn_id, Box::new(metrics)),
+                        Err(e) => Action::MetricsFailed(conn_id, e.to_string()),
+                    };
+                    let _ = tx.send(action);
+                });
+            }
+            _ => {
+                let _ = tx.send(Action::MetricsFailed(
+                    conn_id,
+                    "Server metrics need a live connection".to_string(),
+                ));
+            }
+        }
+    }
+
+    /// Check the active tab's replication partners. On servers other than
+    /// Active Directory, the other open live tabs are compared with it as
+    /// peers by `contextCSN`.
+    fn check_replication(&mut self) {
+        let Some(conn_id) = self.active_tab_id else {
+            return;
+        };
+        self.replication_panel.start_loading(conn_id);
+        let tx = self.action_tx.clone();
+        let Some(TabBackend::Live(pool)) = self.active_tab().map(|t| &t.backend) else {
+            let _ = tx.send(Action::ReplicationFailed(
+                conn_id,
+                "Replication status needs a live connection".to_string(),
+            ));
+            return;
+        };
+        let pool = pool.clone();
+        let peers = self.replication_peers(conn_id);
+        let activity = self.jobs.track(conn_id);
+        tokio::spawn(async move {
+            let _activity = activity;
+            let action = match load_replication(&pool, peers).await {
+                Ok(report) => Action::ReplicationLoaded(conn_id, Box::new(report)),
+                Err(e) => Action::ReplicationFailed(conn_id, e.to_string()),
+            };
+            let _ = tx.send(action);
+        });
+    }
+
+    /// The other live tabs, whose servers are compared with a tab's by
+    /// `contextCSN` when it isn't Active Directory.
+    fn replication_peers(&self, conn_id: ConnectionId) -> Vec<(String, Arc<ConnectionPool>)> {
+        self.tabs
+            .iter()
+            .filter(|t| t.id != conn_id)
+            .filter_map(|t| match &t.backend {
+                TabBackend::Live(pool) => Some((t.label.clone(), pool.clone())),
+                TabBackend::Offline(_) => None,
+            })
+            .collect()
+    }
+
+    /// Serve the Prometheus endpoint, if a port is configured.
+    fn start_exporter(&mut self) {
+        let Some(port) = self.config.prometheus.port else {
+            return;
+        };
+        match MetricsExporter::start(port) {
+            Ok(exporter) => {
+                self.log_panel.push_info(format!(
+                    "Serving Prometheus metrics on http://127.0.0.1:{}/metrics",
+                    exporter.port()
+                ));
+                self.exporter = Some(exporter);
+            }
+            Err(e) => self.push_error(format!("Metrics endpoint disabled: {}", e)),
+        }
+    }
+
+    /// Bring the metrics endpoint's figures up to date, and check every live
+    /// tab's replication when the configured interval has passed.
+    fn update_exporter(&mut self) {
+        let Some(exporter) = &self.exporter else {
+            return;
+        };
+        let connections = self
+            .tabs
+            .iter()
+            .map(|tab| ConnectionSample {
+                label: tab.label.clone(),
+                host: tab.host.clone(),
+                trace: match &tab.backend {
+                    TabBackend::Live(pool) => Some(pool.trace()),
+                    TabBackend::Offline(_) => None,
+                },
+            })
+            .collect();
+        exporter.set_session(
+            self.started_at.elapsed().as_secs(),
+            self.error_count,
+            connections,
+        );
+
+        let interval = self.config.prometheus.replication_interval_secs;
+        if interval == 0
+            || self
+                .replication_sampled_at
+                .is_some_and(|at| at.elapsed() < Duration::from_secs(interval))
+        {
+            return;
+        }
+        self.replication_sampled_at = Some(Instant::now());
+        for tab in &self.tabs {
+            let TabBackend::Live(pool) = &tab.backend else {
+                continue;
+            };
+            let pool = pool.clone();
+            let peers = self.replication_peers(tab.id);
+            let conn_id = tab.id;
+            let tx = self.action_tx.clone();
+            tokio::spawn(async move {
+                match load_replication(&pool, peers).await {
+                    Ok(report) => {
+                        let _ = tx.send(Action::ReplicationSampled(conn_id, Box::new(report)));
+                    }
+                    Err(e) => debug!("Replication check for metrics failed: {}", e),
+                }
+            });
+        }
+    }
+
+    /// Record a replication check in the metrics endpoint's gauges.
+    fn sample_replication(&self, conn_id: ConnectionId, report: &ReplicationReport) {
+        if let (Some(exporter), Some(tab)) =
+            (&self.exporter, self.tabs.iter().find(|t| t.id == conn_id))
+        {
+            exporter.set_replication(&tab.label, report);
+        }
+    }
+
     fn push_message(&mut self, msg: String) {
         self.command_panel.push_message(msg.clone());
         self.log_panel.push_info(msg);
     }
 
     fn push_error(&mut self, msg: String) {
+        self.error_count += 1;
         self.command_panel.push_error(msg.clone());
         self.log_panel.push_error(msg);
     }
@@ -271,6 +786,17 @@ impl App {
             self.show_connect_after_help = true;
             return;
         }
+        if self.config.general.restore_session {
+            if let Some(session) = Session::load(&Session::default_path()) {
+                let names: Vec<&str> = session.tabs.iter().map(|t| t.profile.as_str()).collect();
+                self.confirm_dialog.show(
+                    format!("Reopen the tabs from last time? ({})", names.join(", ")),
+                    Action::RestoreSession,
+                );
+                self.saved_session = Some(session);
+                return;
+            }
+        }
         if !self.config.connections.is_empty() {
             let profile = self.config.connections[0].clone();
             match self.connect_profile(&profile).await {
@@ -337,19 +863,33 @@ impl App {
             server_type: "Active Directory (Example)".to_string(),
             subschema_dn: None,
             read_only: true,
+            dry_run: false,
+            staging: false,
+            default_filter: None,
+            server_info: Vec::new(),
             backend: TabBackend::Offline(offline),
             directory_tree: DirectoryTree::new(base_dn.clone()),
             schema: Some(schema),
+            history: NavHistory::default(),
+            favorites: Vec::new(),
+            stale_favorites: HashSet::new(),
+            search_folders: Vec::new(),
+            value_samples: HashMap::new(),
+            edit_policy: EditPolicy::default(),
+            entry_cache: self.new_entry_cache(),
+            safety: None,
         };
 
         self.tabs.push(tab);
         self.tab_bar
             .add_tab(conn_id, "Example Directory".to_string());
         self.active_tab_id = Some(conn_id);
+        self.command_panel.set_value_samples(HashMap::new());
         self.active_layout = ActiveLayout::Browser;
         self.layout_bar.active = ActiveLayout::Browser;
         self.focus.set_layout(ActiveLayout::Browser);
         self.spawn_load_children(conn_id, base_dn);
+        self.reopen_last_entry(conn_id);
         self.push_message("Connected to example directory (read-only)".to_string());
         self.status_bar
             .set_connected("contoso.example", "Active Directory (Example)");
@@ -362,18 +902,17 @@ impl App {
     ) -> anyhow::Result<()> {
         self.push_message(format!("Connecting to {}...", profile.host));
 
-        let settings = profile.to_connection_settings();
+        let settings = connection_settings(profile, &self.vault);
         let mut conn = LdapConnection::connect(settings, Some(self.trust_store.clone())).await?;
 
         // Bind with credential resolution
-        if let Some(ref bind_dn) = profile.bind_dn {
-            conn.simple_bind(bind_dn, password).await?;
-        } else {
-            conn.anonymous_bind().await?;
-        }
+        conn.bind(profile.bind_dn.as_deref(), password).await?;
 
         // Read RootDSE to detect server type and auto-discover base DN
-        let (server_type_str, subschema_dn) = match conn.read_root_dse().await {
+        let (server_type_str, subschema_dn, mut server_info) = match conn
+            .read_root_dse(&profile.root_dse_attributes)
+            .await
+        {
             Ok(root_dse) => {
                 let st = root_dse.server_type.to_string();
                 debug!(
@@ -387,28 +926,72 @@ impl App {
                 let raw_keys: Vec<&String> = root_dse.raw.keys().collect();
                 debug!("RootDSE raw attribute keys: {:?}", raw_keys);
                 self.push_message(format!("Server type: {}", st));
-                (st, root_dse.subschema_subentry)
+                for (attr, vals) in &root_dse.extra {
+                    if vals.is_empty() {
+                        self.log_panel
+                            .push_info(format!("RootDSE {}: (not returned)", attr));
+                    } else {
+                        self.log_panel
+                            .push_info(format!("RootDSE {}: {}", attr, vals.join(", ")));
+                    }
+                }
+                (st, root_dse.subschema_subentry, root_dse.extra)
             }
             Err(e) => {
                 debug!("RootDSE read failed (non-fatal): {}", e);
-                ("LDAP".to_string(), None)
+                ("LDAP".to_string(), None, Vec::new())
             }
         };
         debug!("connect_with_password: subschema_dn={:?}", subschema_dn);
 
+        // Every request after the bind carries the Proxied Authorization
+        // control; confirm the server accepts it and show who we act as
+        if let Some(authz_id) = &profile.authz_id {
+            let identity = conn.who_am_i().await.map_err(|e| {
+                anyhow::anyhow!("Proxied authorization as {} was refused: {}", authz_id, e)
+            })?;
+            let identity = if identity.is_empty() {
+                "anonymous".to_string()
+            } else {
+                identity
+            };
+            self.log_panel.push_info(format!("Acting as {}", identity));
+            server_info.push(("authz".to_string(), vec![identity]));
+        }
+        if let Some(server) = &conn.server {
+            if profile.dns_srv {
+                self.log_panel.push_info(format!(
+                    "Using {} from the SRV records of {}",
+                    server.address(),
+                    profile.host
+                ));
+            } else {
+                self.log_panel
+                    .push_info(format!("Using {} of {}", server.target, profile.host));
+            }
+            server_info.insert(0, ("server".to_string(), vec![server.target.clone()]));
+        }
+
         let conn_id = self.allocate_conn_id();
         let base_dn = conn.base_dn.clone();
         let label = profile.name.clone();
         let host = profile.host.clone();
 
-        let read_only = profile.read_only;
+        let (edit_policy, policy_failed) = self.load_edit_policy(profile);
+        let read_only = profile.read_only || policy_failed;
         let ro_suffix = if read_only { " (read-only)" } else { "" };
         let conn_msg = format!("Connected to {} (base: {}){}", host, base_dn, ro_suffix);
         self.status_bar.set_message(conn_msg.clone());
         self.log_panel.push_info(conn_msg);
         self.status_bar.set_connected(&host, &server_type_str);
+        self.status_bar.set_server_info(&server_info);
 
-        let connection = Arc::new(Mutex::new(conn));
+        conn.set_audit_log(
+            self.audit_log
+                .as_ref()
+                .map(|log| log.for_profile(&profile.name)),
+        );
+        let pool = Arc::new(ConnectionPool::new(conn, profile.pool_size));
         let directory_tree = DirectoryTree::new(base_dn.clone());
 
         let tab = ConnectionTab {
@@ -418,27 +1001,96 @@ impl App {
             server_type: server_type_str,
             subschema_dn,
             read_only,
-            backend: TabBackend::Live(connection),
+            dry_run: false,
+            staging: false,
+            default_filter: profile.default_filter.clone(),
+            server_info,
+            backend: TabBackend::Live(pool),
             directory_tree,
             schema: None,
+            history: NavHistory::default(),
+            favorites: profile.favorites.clone(),
+            stale_favorites: HashSet::new(),
+            search_folders: profile
+                .saved_searches
+                .iter()
+                .map(|s| SearchFolder::new(s.name.clone(), s.filter.clone()))
+                .collect(),
+            value_samples: HashMap::new(),
+            edit_policy,
+            entry_cache: self.new_entry_cache(),
+            safety: profile.safety.clone(),
         };
 
         self.tabs.push(tab);
         self.tab_bar.add_tab(conn_id, label);
         self.active_tab_id = Some(conn_id);
+        self.command_panel.set_value_samples(HashMap::new());
         self.active_layout = ActiveLayout::Browser;
         self.layout_bar.active = ActiveLayout::Browser;
         self.focus.set_layout(ActiveLayout::Browser);
 
         // Load root children
         self.spawn_load_children(conn_id, base_dn);
+        match profile.start_dn.as_deref().map(str::trim) {
+            // Root children are already loading; the reveal continues from there
+            Some(start_dn) if !start_dn.is_empty() => {
+                self.pending_reveal = Some((conn_id, start_dn.to_string()));
+            }
+            _ => self.reopen_last_entry(conn_id),
+        }
 
         // Auto-load schema so attribute picker is ready
         self.spawn_load_schema(conn_id);
+        self.spawn_check_favorites(conn_id);
 
         Ok(())
     }
 
+    /// Load the profile's edit policy. A policy that can't be read is
+    /// reported and the connection opened read-only (second value true),
+    /// rather than letting edits through unprotected.
+    fn load_edit_policy(&mut self, profile: &ConnectionProfile) -> (EditPolicy, bool) {
+        let Some(path) = profile.edit_policy.as_deref() else {
+            return (EditPolicy::default(), false);
+        };
+        match EditPolicy::load(&expand_home(path)) {
+            Ok(policy) => {
+                let count: usize = policy.managed.iter().map(|m| m.attributes.len()).sum();
+                self.log_panel
+                    .push_info(format!("Edit policy: {} managed attributes", count));
+                (policy, false)
+            }
+            Err(e) => {
+                self.push_error(format!("{}; opening read-only", e));
+                (EditPolicy::default(), true)
+            }
+        }
+    }
+
+    /// The policy message when `attr` is owned by another system on a tab.
+    fn policy_error(&self, conn_id: ConnectionId, attr: &str) -> Option<String> {
+        self.tabs
+            .iter()
+            .find(|t| t.id == conn_id)?
+            .edit_policy
+            .check_editable(attr)
+            .err()
+    }
+
+    /// Refuse to start editing an attribute the active tab's policy locks,
+    /// naming the system that owns it.
+    fn edit_locked(&mut self, attr: &str) -> bool {
+        let Some(msg) = self
+            .active_tab_id
+            .and_then(|id| self.policy_error(id, attr))
+        else {
+            return false;
+        };
+        self.push_error(msg);
+        true
+    }
+
     fn spawn_load_children(&self, conn_id: ConnectionId, dn: String) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
@@ -449,16 +1101,20 @@ impl App {
                     let nodes = dir.children(&dn);
                     let _ = tx.send(Action::TreeChildrenLoaded(conn_id, dn, nodes));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let count_search = self.config.general.child_count_search;
+                    let activity = self.jobs.track(conn_id);
                     tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
+                        let _activity = activity;
+                        let mut conn = pool.reader().await;
                         let result = match conn.search_children(&dn).await {
                             Ok(entries) => Ok(entries),
                             Err(e) if LdapConnection::is_connection_error(&e) => {
                                 let _ =
                                     tx.send(Action::StatusMessage("Reconnecting...".to_string()));
                                 if conn.reconnect().await.is_ok() {
+                                    report_server(&tx, conn_id, &conn);
                                     conn.search_children(&dn).await
                                 } else {
                                     Err(e)
@@ -472,9 +1128,42 @@ impl App {
                                 info!("Loaded {} child objects under '{}'", entries.len(), dn);
                                 let nodes: Vec<TreeNode> = entries
                                     .iter()
-                                    .map(|e| TreeNode::new(e.dn.clone()))
+                                    .map(|e| {
+                                        TreeNode::new(e.dn.clone())
+                                            .with_child_count(ChildCount::from_entry(e))
+                                    })
+                                    .collect();
+                                let uncounted: Vec<String> = nodes
+                                    .iter()
+                                    .filter(|n| n.child_count.is_none())
+                                    .take(MAX_CHILD_COUNT_SEARCHES)
+                                    .map(|n| n.dn.clone())
                                     .collect();
                                 let _ = tx.send(Action::TreeChildrenLoaded(conn_id, dn, nodes));
+                                drop(conn);
+
+                                // The server didn't say how many children these
+                                // have; count each with a sized search.
+                                if count_search {
+                                    for child in uncounted {
+                                        let result = pool
+                                            .reader()
+                                            .await
+                                            .count_children(&child, CHILD_COUNT_LIMIT)
+                                            .await;
+                                        match result {
+                                            Ok(count) => {
+                                                let _ = tx.send(Action::ChildCountLoaded(
+                                                    conn_id, child, count,
+                                                ));
+                                            }
+                                            Err(e) => {
+                                                debug!("Child count of '{}' failed: {}", child, e);
+                                                break;
+                                            }
+                                        }
+                                    }
+                                }
                             }
                             Err(e) => {
                                 error!("Failed to load children of '{}': {}", dn, e);
@@ -490,7 +1179,42 @@ impl App {
         }
     }
 
-    fn spawn_load_entry(&self, conn_id: ConnectionId, dn: String) {
+    /// An empty entry cache sized from the config.
+    fn new_entry_cache(&self) -> EntryCache {
+        EntryCache::new(
+            self.config.general.entry_cache_size,
+            Duration::from_secs(self.config.general.entry_cache_ttl_secs),
+        )
+    }
+
+    /// Forget cached copies of `dn` and its subtree on a tab, after a change.
+    fn invalidate_cached(&mut self, conn_id: ConnectionId, dn: &str) {
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+            tab.entry_cache.invalidate_subtree(dn);
+        }
+    }
+
+    /// Forget every cached entry on the active tab, after a change that
+    /// may have touched many entries.
+    fn clear_entry_cache(&mut self) {
+        if let Some(tab) = self.active_tab_mut() {
+            tab.entry_cache.clear();
+        }
+    }
+
+    /// Show an entry, from the tab's cache when a fresh copy is there.
+    fn spawn_load_entry(&mut self, conn_id: ConnectionId, dn: String) {
+        let cached = self
+            .tabs
+            .iter_mut()
+            .find(|t| t.id == conn_id)
+            .and_then(|t| t.entry_cache.get(&dn));
+        if let Some(entry) = cached {
+            debug!("Entry cache hit: {}", dn);
+            self.show_entry(conn_id, entry);
+            return;
+        }
+
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
             let tx = self.action_tx.clone();
@@ -504,16 +1228,19 @@ impl App {
                         let _ = tx.send(Action::ErrorMessage(format!("Entry not found: {}", dn)));
                     }
                 },
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let activity = self.jobs.track(conn_id);
                     tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
+                        let _activity = activity;
+                        let mut conn = pool.reader().await;
                         let result = match conn.search_entry(&dn).await {
                             Ok(entry) => Ok(entry),
                             Err(e) if LdapConnection::is_connection_error(&e) => {
                                 let _ =
                                     tx.send(Action::StatusMessage("Reconnecting...".to_string()));
                                 if conn.reconnect().await.is_ok() {
+                                    report_server(&tx, conn_id, &conn);
                                     conn.search_entry(&dn).await
                                 } else {
                                     Err(e)
@@ -543,7 +1270,42 @@ impl App {
         }
     }
 
-    fn spawn_search(&self, conn_id: ConnectionId, filter: String) {
+    /// Show a loaded entry in the detail panel of the side its tab is on.
+    fn show_entry(&mut self, conn_id: ConnectionId, entry: LdapEntry) {
+        self.record_recent_entry(conn_id, &entry.dn);
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+            tab.history.visit(&entry.dn);
+        }
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        let schema = tab.and_then(|t| t.schema.clone());
+        let policy = tab.map(|t| t.edit_policy.clone());
+        match self.split.as_mut() {
+            Some(pane) if pane.tab_id == conn_id => {
+                pane.detail_panel
+                    .set_entry(entry, schema.as_ref(), policy.as_ref());
+            }
+            // A late load for a tab that's no longer on screen
+            _ if self.active_tab_id != Some(conn_id) => {}
+            _ => self
+                .detail_panel
+                .set_entry(entry, schema.as_ref(), policy.as_ref()),
+        }
+    }
+
+    /// The filter with its date macros expanded for the tab's directory.
+    fn expand_filter_macros(&self, conn_id: ConnectionId, filter: &str) -> Result<String, String> {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        let context = MacroContext {
+            now: chrono::Utc::now(),
+            active_directory: tab.is_some_and(|t| t.server_type.starts_with("Active Directory")),
+            schema: tab.and_then(|t| t.schema.as_ref()),
+        };
+        expand_macros(filter, &context)
+    }
+
+    /// Search the tab's tree for `filter`. `timeout_secs` overrides the
+    /// profile's timeout, `0` for none.
+    fn spawn_search(&self, conn_id: ConnectionId, filter: String, timeout_secs: Option<u64>) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
             let base_dn = tab.directory_tree.root_dn.clone();
@@ -554,17 +1316,29 @@ impl App {
                     let entries = dir.search(&base_dn, &filter);
                     let _ = tx.send(Action::SearchResults(conn_id, entries));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let activity = self.jobs.track(conn_id);
                     tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let result = match conn.search_subtree(&base_dn, &filter, &["*"]).await {
+                        let _activity = activity;
+                        let mut conn = pool.reader().await;
+                        let timeout = match timeout_secs {
+                            None => Some(conn.op_timeout()),
+                            Some(0) => None,
+                            Some(secs) => Some(Duration::from_secs(secs)),
+                        };
+                        let result = match conn
+                            .search_subtree_within(&base_dn, &filter, &["*"], timeout)
+                            .await
+                        {
                             Ok(entries) => Ok(entries),
                             Err(e) if LdapConnection::is_connection_error(&e) => {
                                 let _ =
                                     tx.send(Action::StatusMessage("Reconnecting...".to_string()));
                                 if conn.reconnect().await.is_ok() {
-                                    conn.search_subtree(&base_dn, &filter, &["*"]).await
+                                    report_server(&tx, conn_id, &conn);
+                                    conn.search_subtree_within(&base_dn, &filter, &["*"], timeout)
+                                        .await
                                 } else {
                                     Err(e)
                                 }
@@ -572,15 +1346,16 @@ impl App {
                             Err(e) => Err(e),
                         };
 
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::SearchResults(conn_id, entries));
-                            }
-                            Err(e) => {
-                                let _ =
-                                    tx.send(Action::ErrorMessage(format!("Search failed: {}", e)));
-                            }
-                        }
+                        let action = match result {
+                            Ok(entries) => Action::SearchResults(conn_id, entries),
+                            Err(CoreError::Timeout) => Action::ErrorMessage(format!(
+                                "Search timed out after {}; press t in the search \
+                                 results to allow longer",
+                                humanize::duration(timeout.unwrap_or_default())
+                            )),
+                            Err(e) => Action::ErrorMessage(format!("Search failed: {}", e)),
+                        };
+                        let _ = tx.send(action);
                     });
                 }
             }
@@ -590,12 +1365,16 @@ impl App {
     fn spawn_save_attribute(&self, conn_id: ConnectionId, result: EditResult) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            if tab.read_only {
+            if tab.read_only && !tab.dry_run {
                 let _ = self
                     .action_tx
                     .send(Action::ErrorMessage("Connection is read-only".to_string()));
                 return;
             }
+            if let Some(msg) = self.policy_error(conn_id, result.op.attr()) {
+                let _ = self.action_tx.send(Action::ErrorMessage(msg));
+                return;
+            }
             let tx = self.action_tx.clone();
 
             match &tab.backend {
@@ -604,8 +1383,14 @@ impl App {
                         "Example directory is read-only".to_string(),
                     ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    let matchable = match (&result.op, &tab.schema) {
+                        (EditOp::Replace { attr, .. }, Some(schema)) => {
+                            schema.has_equality_rule(attr)
+                        }
+                        _ => true,
+                    };
                     tokio::spawn(async move {
                         debug!(
                             "spawn_save_attribute: dn={} op={:?} new_value={}",
@@ -619,6 +1404,7 @@ impl App {
                                     attr,
                                     old_value,
                                     &result.new_value,
+                                    matchable,
                                 )
                                 .await
                             }
@@ -635,6 +1421,9 @@ impl App {
                             Ok(()) => {
                                 let _ = tx.send(Action::AttributeSaved(result.dn));
                             }
+                            Err(CoreError::ModifyConflict(conflict)) => {
+                                let _ = tx.send(Action::ShowModifyConflict(conflict));
+                            }
                             Err(e) => {
                                 let _ =
                                     tx.send(Action::ErrorMessage(format!("Failed to save: {}", e)));
@@ -661,15 +1450,47 @@ impl App {
                     );
                     let _ = tx.send(Action::SchemaLoaded(conn_id, Box::new(schema)));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
                     let subschema_dn = tab.subschema_dn.clone();
+                    let host = tab.host.clone();
                     debug!(
                         "spawn_load_schema: conn_id={}, subschema_dn={:?}",
                         conn_id, subschema_dn
                     );
+                    let activity = self.jobs.track(conn_id);
                     tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
+                        let _activity = activity;
+                        // Show the copy from the last session at once, then
+                        // check it against the server
+                        let cache_dir = StoredSchema::default_dir();
+                        let cached = StoredSchema::load(&cache_dir, &host);
+                        if let Some(stored) = &cached {
+                            debug!(
+                                "spawn_load_schema: using cached schema for {} ({:?})",
+                                host, stored.modify_timestamp
+                            );
+                            let _ = tx.send(Action::SchemaLoaded(
+                                conn_id,
+                                Box::new(stored.schema.clone()),
+                            ));
+                        }
+
+                        let mut conn = pool.reader().await;
+                        let timestamp = match conn.schema_timestamp(subschema_dn.as_deref()).await {
+                            Ok(timestamp) => timestamp,
+                            Err(e) => {
+                                debug!("spawn_load_schema: no schema timestamp: {}", e);
+                                None
+                            }
+                        };
+                        if cached
+                            .as_ref()
+                            .is_some_and(|stored| stored.is_current(timestamp.as_deref()))
+                        {
+                            debug!("spawn_load_schema: cached schema for {} is current", host);
+                            return;
+                        }
                         match conn.load_schema(subschema_dn.as_deref()).await {
                             Ok(schema) => {
                                 info!(
@@ -677,7 +1498,19 @@ impl App {
                                     schema.attribute_types.len(),
                                     schema.object_classes.len()
                                 );
-                                let _ = tx.send(Action::SchemaLoaded(conn_id, Box::new(schema)));
+                                let stored = StoredSchema {
+                                    host,
+                                    modify_timestamp: timestamp,
+                                    schema,
+                                };
+                                if let Err(e) = stored.save(&cache_dir) {
+                                    warn!("Failed to save schema cache: {}", e);
+                                }
+                                let _ =
+                                    tx.send(Action::SchemaLoaded(conn_id, Box::new(stored.schema)));
+                            }
+                            Err(e) if cached.is_some() => {
+                                warn!("Schema refresh failed for conn_id={}: {}", conn_id, e);
                             }
                             Err(e) => {
                                 error!("Schema load failed for conn_id={}: {}", conn_id, e);
@@ -699,6 +1532,20 @@ impl App {
         }
     }
 
+    /// "Exported 1,234 entries to out.ldif (1.2 MiB)". The size is left out
+    /// when `path` isn't a single file, as with per-entry templates.
+    fn export_summary(count: usize, path: &Path) -> String {
+        let mut msg = format!(
+            "Exported {} entries to {}",
+            humanize::count(count),
+            path.display()
+        );
+        if let Some(meta) = std::fs::metadata(path).ok().filter(|m| m.is_file()) {
+            msg.push_str(&format!(" ({})", humanize::bytes(meta.len())));
+        }
+        msg
+    }
+
     /// Expand a user-provided file path:
     /// - Replace leading `~` with the user's home directory
     /// - Create parent directories if they don't exist
@@ -726,8 +1573,60 @@ impl App {
         Ok(expanded)
     }
 
+    /// Write an export file, or one file per entry when the file name is a
+    /// `{placeholder}` template.
+    fn write_export(
+        entries: &[LdapEntry],
+        path: &Path,
+        attributes: &[String],
+        schema: Option<&SchemaCache>,
+        metadata: &ExportMetadata,
+    ) -> Result<usize, CoreError> {
+        if loom_core::export::is_per_entry_template(path) {
+            loom_core::export::export_entries_per_file(entries, path, attributes, schema, metadata)
+        } else {
+            loom_core::export::export_entries_with_metadata(
+                entries, path, attributes, schema, metadata,
+            )
+        }
+    }
+
+    /// Start `task` on the connection as a background job.
+    fn run_job(&mut self, conn_id: ConnectionId, task: JobTask) {
+        match task {
+            JobTask::Export {
+                path,
+                base_dn,
+                filter,
+                attributes,
+            } => self.spawn_export(conn_id, path, base_dn, filter, attributes),
+            JobTask::ExportEntries {
+                dns,
+                path,
+                attributes,
+            } => self.spawn_export_entries(conn_id, dns, path, attributes),
+            JobTask::BulkUpdate {
+                filter,
+                modifications,
+                throttle,
+            } => self.spawn_bulk_update(conn_id, filter, modifications, throttle),
+            JobTask::Import(items) => self.spawn_import(conn_id, items),
+            JobTask::MembershipImport { path, member_attr } => {
+                self.spawn_membership_import(conn_id, path, member_attr)
+            }
+            JobTask::OrphanCleanup(refs) => self.spawn_orphan_cleanup(conn_id, refs),
+            JobTask::BatchEdit { attr, edits } => self.spawn_batch_edit(conn_id, attr, edits),
+            JobTask::AttributeStats { base_dn, sample } => {
+                self.spawn_attribute_stats(conn_id, base_dn, sample)
+            }
+            JobTask::ApplyStaged { stop_on_error } => {
+                self.spawn_apply_staged(conn_id, stop_on_error)
+            }
+        }
+    }
+
     fn spawn_export(
-        &self,
+        &mut self,
         conn_id: ConnectionId,
         path: String,
         base_dn: String,
@@ -746,122 +1645,322 @@ impl App {
                 }
             };
             let display_path = filepath.display().to_string();
+            let metadata = ExportMetadata {
+                server: Some(tab.host.clone()),
+                base_dn: Some(base_dn.clone()),
+                filter: Some(filter.clone()),
+            };
+            let hook = self
+                .hook_context(conn_id, HookEvent::PostExport)
+                .map(|context| context.file(&display_path));
 
             match &tab.backend {
                 TabBackend::Offline(dir) => {
+                    let started = Instant::now();
                     let entries = dir.search(&base_dn, &filter);
-                    match loom_core::export::export_entries(&entries, &filepath, &attributes) {
+                    match Self::write_export(
+                        &entries,
+                        &filepath,
+                        &attributes,
+                        tab.schema.as_ref(),
+                        &metadata,
+                    ) {
                         Ok(count) => {
-                            let _ = tx.send(Action::ExportComplete(format!(
-                                "Exported {} entries to {}",
-                                count, display_path
-                            )));
+                            let mut msg = Self::export_summary(count, &filepath);
+                            if let Some(context) = hook {
+                                self.hooks.run(context.message(&msg));
+                            }
+                            jobs::append_elapsed(&mut msg, started);
+                            let _ = tx.send(Action::ExportComplete(msg));
                         }
                         Err(e) => {
                             let _ = tx.send(Action::ErrorMessage(format!("Export failed: {}", e)));
                         }
                     }
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let attr_refs: Vec<&str> = attributes.iter().map(|s| s.as_str()).collect();
-                        match conn.search_subtree(&base_dn, &filter, &attr_refs).await {
-                            Ok(entries) => {
-                                match loom_core::export::export_entries(
-                                    &entries,
-                                    &filepath,
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let schema = tab.schema.clone();
+                    let hooks = self.hooks.clone();
+                    let title = format!("Export to {}", display_path);
+                    let task = JobTask::Export {
+                        path,
+                        base_dn: base_dn.clone(),
+                        filter: filter.clone(),
+                        attributes: attributes.clone(),
+                    };
+                    self.jobs
+                        .spawn(conn_id, title, task, move |job| async move {
+                            let mut conn = pool.long_reader().await;
+                            let result = if loom_core::export::is_per_entry_template(&filepath) {
+                                let attr_refs: Vec<&str> =
+                                    attributes.iter().map(|s| s.as_str()).collect();
+                                match conn.search_subtree(&base_dn, &filter, &attr_refs).await {
+                                    Ok(entries) => Self::write_export(
+                                        &entries,
+                                        &filepath,
+                                        &attributes,
+                                        schema.as_ref(),
+                                        &metadata,
+                                    ),
+                                    Err(e) => Err(e),
+                                }
+                            } else {
+                                Self::stream_export(
+                                    &mut conn,
+                                    &base_dn,
+                                    &filter,
                                     &attributes,
-                                ) {
-                                    Ok(count) => {
-                                        let _ = tx.send(Action::ExportComplete(format!(
-                                            "Exported {} entries to {}",
-                                            count, display_path
-                                        )));
-                                    }
-                                    Err(e) => {
-                                        let _ = tx.send(Action::ErrorMessage(format!(
-                                            "Export failed: {}",
-                                            e
-                                        )));
+                                    &filepath,
+                                    schema.as_ref(),
+                                    &metadata,
+                                    &job,
+                                )
+                                .await
+                            };
+                            match result {
+                                Ok(count) => {
+                                    let msg = Self::export_summary(count, &filepath);
+                                    if let Some(context) = hook {
+                                        hooks.run(context.message(&msg));
                                     }
+                                    Action::ExportComplete(msg)
                                 }
+                                Err(e) => Action::ErrorMessage(format!("Export failed: {}", e)),
                             }
-                            Err(e) => {
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Export search failed: {}",
-                                    e
-                                )));
-                            }
-                        }
-                    });
+                        });
                 }
             }
         }
     }
 
-    fn spawn_bulk_update(
-        &self,
+    /// Write a subtree search to `path` a page at a time as it arrives, so
+    /// exports of any size run in bounded memory. A partly written file is
+    /// removed if the export fails or its job is cancelled.
+    #[allow(clippy::too_many_arguments)]
+    async fn stream_export(
+        conn: &mut LdapConnection,
+        base_dn: &str,
+        filter: &str,
+        attributes: &[String],
+        path: &Path,
+        schema: Option<&SchemaCache>,
+        metadata: &ExportMetadata,
+        job: &JobHandle,
+    ) -> Result<usize, CoreError> {
+        // Declared before the writer so the file is closed before removal
+        let mut partial = PartialFile(Some(path));
+        let mut writer = loom_core::export::entry_writer(path, attributes, schema, metadata)?;
+        let attr_refs: Vec<&str> = attributes.iter().map(|s| s.as_str()).collect();
+        writer.begin()?;
+        let mut written = 0;
+        conn.search_subtree_pages(base_dn, filter, &attr_refs, |page| {
+            page.iter()
+                .try_for_each(|entry| writer.write_entry(entry))?;
+            written += page.len();
+            job.progress(written, None);
+            Ok(())
+        })
+        .await?;
+        let count = writer.finish()?;
+        partial.0 = None;
+        Ok(count)
+    }
+
+    /// Export the entries named by `dns`, re-reading each with the chosen
+    /// attributes, through the same writers as a filter export.
+    fn spawn_export_entries(
+        &mut self,
         conn_id: ConnectionId,
-        filter: String,
-        modifications: Vec<BulkMod>,
+        dns: Vec<String>,
+        path: String,
+        attributes: Vec<String>,
     ) {
-        let tab = self.tabs.iter().find(|t| t.id == conn_id);
-        if let Some(tab) = tab {
-            if tab.read_only {
-                let _ = self
-                    .action_tx
-                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let filepath = match Self::expand_export_path(&path) {
+            Ok(p) => p,
+            Err(e) => {
+                let _ = tx.send(Action::ErrorMessage(format!("Export failed: {}", e)));
                 return;
             }
-            let tx = self.action_tx.clone();
-
-            match &tab.backend {
-                TabBackend::Offline(_) => {
-                    let _ = tx.send(Action::ErrorMessage(
-                        "Example directory is read-only".to_string(),
-                    ));
+        };
+        let schema = tab.schema.clone();
+        let requested = attributes.clone();
+        let task = JobTask::ExportEntries {
+            dns: dns.clone(),
+            path,
+            attributes: attributes.clone(),
+        };
+        let title = format!("Export {} entries to {}", dns.len(), filepath.display());
+        let metadata = ExportMetadata {
+            server: Some(tab.host.clone()),
+            ..Default::default()
+        };
+        let hook = self
+            .hook_context(conn_id, HookEvent::PostExport)
+            .map(|context| context.file(&filepath.display().to_string()));
+        let hooks = self.hooks.clone();
+        let summary_path = filepath.clone();
+        let finish = move |result: Result<(usize, Vec<String>), CoreError>| match result {
+            Ok((count, unread)) => {
+                let mut msg = Self::export_summary(count, &summary_path);
+                if !unread.is_empty() {
+                    msg.push_str(&unread_summary(&unread));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        match conn.bulk_update(&filter, &modifications).await {
-                            Ok(result) => {
-                                let msg = format!(
-                                    "Bulk update: {} succeeded, {} failed out of {}",
-                                    result.succeeded, result.failed, result.total
-                                );
-                                let _ = tx.send(Action::BulkUpdateComplete(msg));
-                            }
-                            Err(e) => {
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Bulk update failed: {}",
-                                    e
-                                )));
-                            }
+                if let Some(context) = hook {
+                    hooks.run(context.message(&msg));
+                }
+                Action::ExportComplete(msg)
+            }
+            Err(e) => Action::ErrorMessage(format!("Export failed: {}", e)),
+        };
+
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let mut unread = Vec::new();
+                let entries: Vec<LdapEntry> = dns
+                    .iter()
+                    .filter_map(|dn| {
+                        let entry = dir.entry(dn);
+                        if entry.is_none() {
+                            unread.push(dn.clone());
                         }
+                        entry
+                    })
+                    .collect();
+                let result = Self::write_export(
+                    &entries,
+                    &filepath,
+                    &attributes,
+                    schema.as_ref(),
+                    &metadata,
+                );
+                let _ = tx.send(finish(result.map(|count| (count, unread))));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                self.jobs
+                    .spawn(conn_id, title, task, move |job| async move {
+                        let mut conn = pool.long_reader().await;
+                        let result = if loom_core::export::is_per_entry_template(&filepath) {
+                            let mut entries = Vec::new();
+                            let read = Self::read_export_entries(
+                                &mut conn,
+                                &dns,
+                                &requested,
+                                &job,
+                                |entry| {
+                                    entries.push(entry);
+                                    Ok(())
+                                },
+                            )
+                            .await;
+                            read.and_then(|unread| {
+                                Self::write_export(
+                                    &entries,
+                                    &filepath,
+                                    &attributes,
+                                    schema.as_ref(),
+                                    &metadata,
+                                )
+                                .map(|count| (count, unread))
+                            })
+                        } else {
+                            Self::stream_export_entries(
+                                &mut conn,
+                                &dns,
+                                &attributes,
+                                &filepath,
+                                schema.as_ref(),
+                                &metadata,
+                                &job,
+                            )
+                            .await
+                        };
+                        finish(result)
                     });
+            }
+        }
+    }
+
+    /// Write the entries named by `dns` to `path` as each is read. Returns
+    /// the number written and the DNs that couldn't be read. A partly
+    /// written file is removed if the export fails or is cancelled.
+    async fn stream_export_entries(
+        conn: &mut LdapConnection,
+        dns: &[String],
+        attributes: &[String],
+        path: &Path,
+        schema: Option<&SchemaCache>,
+        metadata: &ExportMetadata,
+        job: &JobHandle,
+    ) -> Result<(usize, Vec<String>), CoreError> {
+        // Declared before the writer so the file is closed before removal
+        let mut partial = PartialFile(Some(path));
+        let mut writer = loom_core::export::entry_writer(path, attributes, schema, metadata)?;
+        writer.begin()?;
+        let unread = Self::read_export_entries(conn, dns, attributes, job, |entry| {
+            writer.write_entry(&entry)
+        })
+        .await?;
+        let count = writer.finish()?;
+        partial.0 = None;
+        Ok((count, unread))
+    }
+
+    /// Read each of `dns` with `attributes` and hand it to `each`. Returns
+    /// the DNs that were gone or failed to read, which are logged.
+    async fn read_export_entries(
+        conn: &mut LdapConnection,
+        dns: &[String],
+        attributes: &[String],
+        job: &JobHandle,
+        mut each: impl FnMut(LdapEntry) -> Result<(), CoreError>,
+    ) -> Result<Vec<String>, CoreError> {
+        let attr_refs: Vec<&str> = attributes.iter().map(|s| s.as_str()).collect();
+        let mut unread = Vec::new();
+        for (i, dn) in dns.iter().enumerate() {
+            match conn.search_entry_attrs(dn, &attr_refs).await {
+                Ok(Some(entry)) => each(entry)?,
+                Ok(None) => {
+                    warn!("Export: {} not found", dn);
+                    unread.push(dn.clone());
+                }
+                Err(e) => {
+                    warn!("Export: failed to read {}: {}", dn, e);
+                    unread.push(dn.clone());
                 }
             }
+            job.progress(i + 1, Some(dns.len()));
         }
+        Ok(unread)
     }
 
-    fn spawn_create_entry(
-        &self,
+    fn spawn_bulk_update(
+        &mut self,
         conn_id: ConnectionId,
-        dn: String,
-        attributes: Vec<(String, Vec<String>)>,
+        filter: String,
+        modifications: Vec<BulkMod>,
+        throttle: Throttle,
     ) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            if tab.read_only {
+            if tab.read_only && !tab.dry_run {
                 let _ = self
                     .action_tx
                     .send(Action::ErrorMessage("Connection is read-only".to_string()));
                 return;
             }
+            if let Some(msg) = modifications
+                .iter()
+                .find_map(|m| self.policy_error(conn_id, m.attr()))
+            {
+                let _ = self.action_tx.send(Action::ErrorMessage(msg));
+                return;
+            }
             let tx = self.action_tx.clone();
 
             match &tab.backend {
@@ -870,37 +1969,400 @@ impl App {
                         "Example directory is read-only".to_string(),
                     ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    let hook = self.hook_context(conn_id, HookEvent::PostBulkUpdate);
+                    let hooks = self.hooks.clone();
+                    let title = format!("Bulk update of {}", filter);
+                    let task = JobTask::BulkUpdate {
+                        filter: filter.clone(),
+                        modifications: modifications.clone(),
+                        throttle: throttle.clone(),
+                    };
+                    self.jobs.spawn(conn_id, title, task, |job| async move {
                         let mut conn = connection.lock().await;
-                        // Convert Vec<String> -> HashSet<String> for ldap3
-                        let attrs: Vec<(String, std::collections::HashSet<String>)> = attributes
-                            .into_iter()
-                            .map(|(k, v)| (k, v.into_iter().collect()))
-                            .collect();
-
-                        match conn.add_entry(&dn, attrs).await {
-                            Ok(()) => {
-                                let _ = tx.send(Action::EntryCreated(dn));
+                        let progress = |done, total| job.progress(done, Some(total));
+                        let result = match conn
+                            .bulk_update(&filter, &modifications, &throttle, progress)
+                            .await
+                        {
+                            Ok(result) => result,
+                            Err(e) => {
+                                return Action::ErrorMessage(format!("Bulk update failed: {}", e))
                             }
+                        };
+                        let mut msg = format!(
+                            "Bulk update: {} succeeded, {} failed",
+                            humanize::count(result.succeeded),
+                            humanize::count(result.failed)
+                        );
+                        if result.unchanged > 0 {
+                            msg.push_str(&format!(
+                                ", {} unchanged",
+                                humanize::count(result.unchanged)
+                            ));
+                        }
+                        msg.push_str(&format!(" out of {}", humanize::count(result.total)));
+                        if result.retries > 0 {
+                            msg.push_str(&format!(
+                                " ({} retries while the server was busy)",
+                                result.retries
+                            ));
+                        }
+                        if let Some(context) = hook {
+                            hooks.run(context.message(&msg));
+                        }
+                        Action::BulkUpdateComplete(msg)
+                    });
+                }
+            }
+        }
+    }
+
+    /// Compute the values of a scripted bulk update for the first matching
+    /// entries, for the bulk update dialog's preview.
+    fn spawn_bulk_preview(
+        &self,
+        conn_id: ConnectionId,
+        filter: String,
+        modifications: Vec<BulkMod>,
+    ) {
+        let tx = self.action_tx.clone();
+        match self
+            .tabs
+            .iter()
+            .find(|t| t.id == conn_id)
+            .map(|t| &t.backend)
+        {
+            Some(TabBackend::Live(pool)) => {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let mut conn = pool.reader().await;
+                    let action = match conn
+                        .preview_bulk(&filter, &modifications, PREVIEW_ENTRIES)
+                        .await
+                    {
+                        Ok((total, values)) => Action::BulkPreviewReady(total, values),
+                        Err(e) => Action::ErrorMessage(format!("Preview failed: {}", e)),
+                    };
+                    let _ = tx.send(action);
+                });
+            }
+            Some(TabBackend::Offline(_)) => {
+                let _ = tx.send(Action::ErrorMessage(
+                    "Example directory is read-only".to_string(),
+                ));
+            }
+            None => {}
+        }
+    }
+
+    /// Turn dry run on or off for the active tab. Turning it off saves any
+    /// recorded changes so they are never silently discarded.
+    fn spawn_toggle_dry_run(&self) {
+        let Some(tab) = self.active_tab() else {
+            let _ = self
+                .action_tx
+                .send(Action::ErrorMessage("No active connection".to_string()));
+            return;
+        };
+        let tx = self.action_tx.clone();
+
+        match &tab.backend {
+            TabBackend::Offline(_) => {
+                let _ = tx.send(Action::ErrorMessage(
+                    "Dry run requires a live connection".to_string(),
+                ));
+            }
+            TabBackend::Live(pool) => {
+                let connection = pool.main();
+                let conn_id = tab.id;
+                let path = dry_run_script_path(&tab.label);
+                tokio::spawn(async move {
+                    let mut conn = connection.lock().await;
+                    if !conn.is_dry_run() {
+                        conn.start_dry_run();
+                        let _ = tx.send(Action::DryRunToggled(
+                            conn_id,
+                            true,
+                            "Dry run on: writes are recorded, not sent".to_string(),
+                        ));
+                        return;
+                    }
+
+                    let script = conn.stop_dry_run().unwrap_or_default();
+                    drop(conn);
+                    let msg = if script.is_empty() {
+                        "Dry run off: no changes recorded".to_string()
+                    } else {
+                        match script.write_to(&path) {
+                            Ok(count) => format!(
+                                "Dry run off: {} changes saved to {}",
+                                count,
+                                path.display()
+                            ),
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to create entry: {}",
+                                    "Failed to save dry-run script: {}",
                                     e
                                 )));
+                                "Dry run off".to_string()
                             }
                         }
-                    });
+                    };
+                    let _ = tx.send(Action::DryRunToggled(conn_id, false, msg));
+                });
+            }
+        }
+    }
+
+    /// Write a report on `dns`, re-reading each entry with its operational
+    /// attributes so the audit section is complete.
+    fn spawn_report(
+        &self,
+        conn_id: ConnectionId,
+        dns: Vec<String>,
+        path: String,
+        sections: Vec<ReportSection>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let filepath = match Self::expand_export_path(&path) {
+            Ok(p) => p,
+            Err(e) => {
+                let _ = tx.send(Action::ErrorMessage(format!("Report failed: {}", e)));
+                return;
+            }
+        };
+        let finish = move |entries: Vec<LdapEntry>| {
+            let action = match write_report(&entries, &filepath, &sections) {
+                Ok(count) => Action::ExportComplete(format!(
+                    "Wrote report on {} entries to {}",
+                    count,
+                    filepath.display()
+                )),
+                Err(e) => Action::ErrorMessage(format!("Report failed: {}", e)),
+            };
+            let _ = tx.send(action);
+        };
+
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                finish(dns.iter().filter_map(|dn| dir.entry(dn)).collect());
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let mut conn = pool.long_reader().await;
+                    let mut entries = Vec::new();
+                    for dn in &dns {
+                        match conn.search_entry_attrs(dn, &["*", "+"]).await {
+                            Ok(Some(entry)) => entries.push(entry),
+                            Ok(None) => debug!("Report: {} not found", dn),
+                            Err(e) => debug!("Report: failed to read {}: {}", dn, e),
+                        }
+                    }
+                    finish(entries);
+                });
+            }
+        }
+    }
+
+    /// Write the active connection's loaded schema to a file, in the format
+    /// named by its extension.
+    fn export_schema(&self, path: &str) {
+        let Some(schema) = self.active_tab().and_then(|tab| tab.schema.as_ref()) else {
+            let _ = self
+                .action_tx
+                .send(Action::ErrorMessage("Schema not loaded".to_string()));
+            return;
+        };
+        let result = Self::expand_export_path(path).and_then(|filepath| {
+            let format = SchemaFormat::from_path(&filepath)
+                .ok_or_else(|| format!("{} is not .ldif, .json or .md", filepath.display()))?;
+            loom_core::export::schema::export(schema, &filepath, format)
+                .map(|count| (count, filepath))
+                .map_err(|e| e.to_string())
+        });
+        let action = match result {
+            Ok((count, filepath)) => Action::ExportComplete(format!(
+                "Exported {} schema definitions to {}",
+                count,
+                filepath.display()
+            )),
+            Err(e) => Action::ErrorMessage(format!("Schema export failed: {}", e)),
+        };
+        let _ = self.action_tx.send(action);
+    }
+
+    /// Save the active tab's dry-run script so far without leaving dry run.
+    fn spawn_export_dry_run_script(&self) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        if !tab.dry_run {
+            let _ = self.action_tx.send(Action::ErrorMessage(format!(
+                "Dry run is off (press {} to start)",
+                self.keymap.hint("toggle_dry_run")
+            )));
+            return;
+        }
+        if let TabBackend::Live(pool) = &tab.backend {
+            let connection = pool.main();
+            let path = dry_run_script_path(&tab.label);
+            let tx = self.action_tx.clone();
+            tokio::spawn(async move {
+                let script = connection.lock().await.change_script().cloned();
+                match script {
+                    Some(script) if !script.is_empty() => match script.write_to(&path) {
+                        Ok(count) => {
+                            let _ = tx.send(Action::StatusMessage(format!(
+                                "Saved {} dry-run changes to {}",
+                                count,
+                                path.display()
+                            )));
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Failed to save dry-run script: {}",
+                                e
+                            )));
+                        }
+                    },
+                    _ => {
+                        let _ = tx.send(Action::StatusMessage(
+                            "No dry-run changes recorded yet".to_string(),
+                        ));
+                    }
                 }
+            });
+        }
+    }
+
+    /// Turn staging on or off for the active tab. Staging can't be turned
+    /// off while changes are queued, so they are never silently dropped.
+    fn toggle_staging(&mut self) {
+        let Some(tab) = self.active_tab() else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let Some(changes) = self.active_changes() else {
+            self.push_error("Staging requires a live connection".to_string());
+            return;
+        };
+        if tab.read_only {
+            self.push_error("Connection is read-only".to_string());
+            return;
+        }
+        if tab.staging && !changes.is_empty() {
+            let msg = format!(
+                "{} changes are staged; apply or discard them first ({})",
+                changes.len(),
+                self.keymap.hint("toggle_changes_panel")
+            );
+            self.push_error(msg);
+            return;
+        }
+        let Some(tab) = self.active_tab_mut() else {
+            return;
+        };
+        tab.staging = !tab.staging;
+        changes.set_staging(tab.staging);
+        let (conn_id, label) = (tab.id, tab.display_label());
+        self.tab_bar.set_label(conn_id, label);
+        self.push_message(if changes.is_staging() {
+            "Staging on: writes are queued for review, not sent".to_string()
+        } else {
+            "Staging off".to_string()
+        });
+    }
+
+    /// Save the active tab's staged changes as an LDIF change script.
+    fn export_staged_changes(&mut self) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let Some(changes) = self.active_changes().filter(|c| !c.is_empty()) else {
+            self.push_message("No changes staged".to_string());
+            return;
+        };
+        let path = staged_changes_path(&tab.label);
+        match changes.write_to(&path) {
+            Ok(count) => self.push_message(format!(
+                "Saved {} staged changes to {}",
+                count,
+                path.display()
+            )),
+            Err(e) => self.push_error(format!("Failed to save staged changes: {}", e)),
+        }
+    }
+
+    /// An entry already read, for copying: the one shown in the detail
+    /// panel, a search result, or the active tab's cached copy.
+    fn loaded_entry(&mut self, dn: &str) -> Option<LdapEntry> {
+        let matches = |e: &&LdapEntry| e.dn.eq_ignore_ascii_case(dn);
+        if let Some(entry) = self.detail_panel.entry.as_ref().filter(matches) {
+            return Some(entry.clone());
+        }
+        if let Some(entry) = self.search_dialog.results.iter().find(matches) {
+            return Some(entry.clone());
+        }
+        self.active_tab_mut()?.entry_cache.get(dn)
+    }
+
+    /// The active tab's connection settings, for a command line reaching
+    /// the same server.
+    fn active_cli_settings(&self) -> Result<ConnectionSettings, String> {
+        let tab = self.active_tab().ok_or("No active connection")?;
+        if let TabBackend::Offline(_) = tab.backend {
+            return Err("The example directory has no server".to_string());
+        }
+        self.config
+            .connections
+            .iter()
+            .find(|p| p.name == tab.label)
+            .map(ConnectionProfile::to_connection_settings)
+            .ok_or_else(|| format!("No saved profile named '{}'", tab.label))
+    }
+
+    /// Copy the last search as an `ldapsearch` command, with its date
+    /// macros expanded and the shown columns as the attributes.
+    fn copy_ldapsearch(&mut self) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let (id, base_dn) = (tab.id, tab.directory_tree.root_dn.clone());
+        if self.search_dialog.filter.is_empty() {
+            self.push_error("No search to copy -- run one first".to_string());
+            return;
+        }
+        let command = self
+            .expand_filter_macros(id, &self.search_dialog.filter)
+            .and_then(|filter| {
+                let settings = self.active_cli_settings()?;
+                Ok(cli_command::ldapsearch(
+                    &settings,
+                    &base_dn,
+                    &filter,
+                    &self.search_dialog.columns,
+                ))
+            });
+        match command {
+            Ok(command) => {
+                let _ = self.action_tx.send(Action::CopyToClipboard(command));
             }
+            Err(e) => self.push_error(e),
         }
     }
 
-    fn spawn_delete_entry(&self, conn_id: ConnectionId, dn: String) {
+    /// Parse an import file and look up which of its DNs already exist, for
+    /// the import dialog's preview.
+    fn spawn_import_preview(&self, conn_id: ConnectionId, mut entries: Vec<LdapEntry>) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            if tab.read_only {
+            if tab.read_only && !tab.dry_run {
                 let _ = self
                     .action_tx
                     .send(Action::ErrorMessage("Connection is read-only".to_string()));
@@ -914,19 +2376,19 @@ impl App {
                         "Example directory is read-only".to_string(),
                     ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
                     tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        match conn.delete_entry(&dn).await {
-                            Ok(()) => {
-                                let _ = tx.send(Action::EntryDeleted(dn));
+                        loom_core::import::plan::tree_order(&mut entries);
+
+                        let mut conn = pool.reader().await;
+                        match conn.check_import(entries).await {
+                            Ok(items) => {
+                                let _ = tx.send(Action::ImportPreviewReady(items));
                             }
                             Err(e) => {
-                                let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to delete entry: {}",
-                                    e
-                                )));
+                                let _ =
+                                    tx.send(Action::ErrorMessage(format!("Import failed: {}", e)));
                             }
                         }
                     });
@@ -935,55 +2397,111 @@ impl App {
         }
     }
 
-    fn spawn_dn_search(
-        &self,
+    /// Apply a previewed import: create new entries and resolve conflicts.
+    fn spawn_import(&mut self, conn_id: ConnectionId, items: Vec<ImportItem>) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(TabBackend::Live(pool)) = tab.map(|t| &t.backend) {
+            let connection = pool.main();
+            let hook = self.hook_context(conn_id, HookEvent::PostImport);
+            let hooks = self.hooks.clone();
+            let title = format!("Import {} entries", items.len());
+            let task = JobTask::Import(items.clone());
+            self.jobs.spawn(conn_id, title, task, |_| async move {
+                let mut conn = connection.lock().await;
+                let result = conn.apply_import(&items).await;
+                let summary = format!(
+                    "Import: {} created, {} overwritten, {} merged, {} skipped, {} unchanged, {} failed",
+                    result.created,
+                    result.overwritten,
+                    result.merged,
+                    result.skipped,
+                    result.unchanged,
+                    result.errors.len()
+                );
+                let problems = result
+                    .errors
+                    .iter()
+                    .map(|(dn, err)| format!("Import failed for {}: {}", dn, err))
+                    .collect();
+                if let Some(context) = hook {
+                    hooks.run(context.message(&summary));
+                }
+                Action::ImportComplete(summary, problems)
+            });
+        }
+    }
+
+    fn spawn_membership_import(
+        &mut self,
         conn_id: ConnectionId,
-        generation: u64,
-        query: String,
-        base_dn: String,
+        path: String,
+        member_attr: String,
     ) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
+            if tab.read_only && !tab.dry_run {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
             let tx = self.action_tx.clone();
 
             match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let entries = dir.search_limited(&base_dn, &query, 50);
-                    let _ = tx.send(Action::DnSearchResults {
-                        generation,
-                        entries,
-                    });
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
-                    tokio::spawn(async move {
-                        let mut conn = connection.lock().await;
-                        let result = match conn
-                            .search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
-                            .await
-                        {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                if conn.reconnect().await.is_ok() {
-                                    conn.search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
-                                        .await
-                                } else {
-                                    Err(e)
-                                }
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    let title = format!("Import memberships from {}", path);
+                    let task = JobTask::MembershipImport {
+                        path: path.clone(),
+                        member_attr: member_attr.clone(),
+                    };
+                    self.jobs.spawn(conn_id, title, task, |_| async move {
+                        let parsed = match loom_core::import::membership::import(
+                            std::path::Path::new(&path),
+                        ) {
+                            Ok(parsed) => parsed,
+                            Err(e) => {
+                                return Action::ErrorMessage(format!(
+                                    "Membership import failed: {}",
+                                    e
+                                ));
                             }
-                            Err(e) => Err(e),
                         };
 
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::DnSearchResults {
-                                    generation,
-                                    entries,
-                                });
+                        let mut conn = connection.lock().await;
+                        match conn
+                            .apply_memberships(&parsed.rows, &member_attr, MEMBERSHIP_BATCH_SIZE)
+                            .await
+                        {
+                            Ok(result) => {
+                                let summary = format!(
+                                    "Memberships: {} added to {} groups, {} already present, {} duplicate rows, {} unresolved, {} failed",
+                                    result.added,
+                                    result.groups,
+                                    result.already_members,
+                                    parsed.duplicates,
+                                    result.unresolved.len(),
+                                    result.errors.len()
+                                );
+                                let problems = result
+                                    .unresolved
+                                    .iter()
+                                    .map(|(ident, reason)| {
+                                        format!("Unresolved '{}': {}", ident, reason)
+                                    })
+                                    .chain(result.errors.iter().map(|(dn, err)| {
+                                        format!("Membership update failed for {}: {}", dn, err)
+                                    }))
+                                    .collect();
+                                Action::MembershipImportComplete(summary, problems)
                             }
                             Err(e) => {
-                                // Silently log — no error popup spam during live search
-                                debug!("DN search failed: {}", e);
+                                Action::ErrorMessage(format!("Membership import failed: {}", e))
                             }
                         }
                     });
@@ -992,47 +2510,47 @@ impl App {
         }
     }
 
-    fn spawn_live_search(&self, conn_id: ConnectionId, generation: u64, filter: String) {
-        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+    fn spawn_create_entry(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        attributes: Vec<(String, Vec<String>)>,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            let base_dn = tab.directory_tree.root_dn.clone();
+            if tab.read_only && !tab.dry_run {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
             let tx = self.action_tx.clone();
 
             match &tab.backend {
-                TabBackend::Offline(dir) => {
-                    let mut entries = dir.search(&base_dn, &filter);
-                    entries.truncate(50);
-                    let _ = tx.send(Action::LiveSearchResults {
-                        generation,
-                        entries,
-                    });
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
                     tokio::spawn(async move {
                         let mut conn = connection.lock().await;
-                        let result = match conn.search_limited(&base_dn, &filter, &["*"], 50).await
-                        {
-                            Ok(entries) => Ok(entries),
-                            Err(e) if LdapConnection::is_connection_error(&e) => {
-                                if conn.reconnect().await.is_ok() {
-                                    conn.search_limited(&base_dn, &filter, &["*"], 50).await
-                                } else {
-                                    Err(e)
-                                }
-                            }
-                            Err(e) => Err(e),
-                        };
+                        // Convert Vec<String> -> HashSet<String> for ldap3
+                        let attrs: Vec<(String, std::collections::HashSet<String>)> = attributes
+                            .into_iter()
+                            .map(|(k, v)| (k, v.into_iter().collect()))
+                            .collect();
 
-                        match result {
-                            Ok(entries) => {
-                                let _ = tx.send(Action::LiveSearchResults {
-                                    generation,
-                                    entries,
-                                });
+                        match conn.add_entry(&dn, attrs).await {
+                            Ok(()) => {
+                                let _ = tx.send(Action::EntryCreated(dn));
                             }
                             Err(e) => {
-                                debug!("Live search failed: {}", e);
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to create entry: {}",
+                                    e
+                                )));
                             }
                         }
                     });
@@ -1041,16 +2559,10 @@ impl App {
         }
     }
 
-    fn spawn_add_multiple_values(
-        &self,
-        conn_id: ConnectionId,
-        dn: String,
-        attr: String,
-        values: Vec<String>,
-    ) {
+    fn spawn_delete_entry(&self, conn_id: ConnectionId, dn: String) {
         let tab = self.tabs.iter().find(|t| t.id == conn_id);
         if let Some(tab) = tab {
-            if tab.read_only {
+            if tab.read_only && !tab.dry_run {
                 let _ = self
                     .action_tx
                     .send(Action::ErrorMessage("Connection is read-only".to_string()));
@@ -1064,17 +2576,17 @@ impl App {
                         "Example directory is read-only".to_string(),
                     ));
                 }
-                TabBackend::Live(connection) => {
-                    let connection = connection.clone();
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
                     tokio::spawn(async move {
                         let mut conn = connection.lock().await;
-                        match conn.add_attribute_values(&dn, &attr, values).await {
+                        match conn.delete_entry(&dn).await {
                             Ok(()) => {
-                                let _ = tx.send(Action::AttributeSaved(dn));
+                                let _ = tx.send(Action::EntryDeleted(dn));
                             }
                             Err(e) => {
                                 let _ = tx.send(Action::ErrorMessage(format!(
-                                    "Failed to add values: {}",
+                                    "Failed to delete entry: {}",
                                     e
                                 )));
                             }
@@ -1085,507 +2597,2436 @@ impl App {
         }
     }
 
-    /// Look up whether an attribute has DN syntax and whether it's multi-valued,
-    /// using the active tab's schema cache.
-    fn lookup_attr_schema(&self, attr: &str) -> (bool, bool) {
-        if let Some(tab) = self.active_tab() {
-            if let Some(ref schema) = tab.schema {
-                let is_dn = matches!(schema.attribute_syntax(attr), AttributeSyntax::Dn);
-                let multi_valued = !schema.is_single_valued(attr);
-                return (is_dn, multi_valued);
+    fn spawn_move_entry(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        new_parent: String,
+        subtree: bool,
+        copy_fallback: bool,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            if tab.read_only && !tab.dry_run {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
             }
-        }
-        (false, true) // default: not DN, multi-valued
-    }
-
-    /// Check if any popup/dialog is currently visible.
-    fn popup_active(&self) -> bool {
-        self.context_menu.visible
-            || self.confirm_dialog.visible
-            || self.cert_trust_dialog.visible
-            || self.connect_dialog.visible
-            || self.new_connection_dialog.visible
-            || self.credential_prompt.visible
-            || self.search_dialog.visible
-            || self.attribute_editor.visible
-            || self.attribute_picker.visible
-            || self.export_dialog.visible
-            || self.bulk_update_dialog.visible
-            || self.create_entry_dialog.visible
-            || self.schema_viewer.visible
-            || self.help_popup.visible
-            || self.about_popup.visible
-            || self.log_panel.visible
-            || self.profile_export_dialog.visible
-            || self.profile_import_dialog.visible
-    }
-
-    /// Check if any popup, dialog, or text-input mode is active.
-    fn any_popup_or_input_active(&self) -> bool {
-        self.context_menu.visible
-            || self.attribute_editor.visible
-            || self.attribute_picker.visible
-            || self.confirm_dialog.visible
-            || self.cert_trust_dialog.visible
-            || self.connect_dialog.visible
-            || self.new_connection_dialog.visible
-            || self.credential_prompt.visible
-            || self.search_dialog.visible
-            || self.export_dialog.visible
-            || self.bulk_update_dialog.visible
-            || self.create_entry_dialog.visible
-            || self.schema_viewer.visible
-            || self.help_popup.visible
-            || self.about_popup.visible
-            || self.log_panel.visible
-            || self.profile_export_dialog.visible
-            || self.profile_import_dialog.visible
-            || self.command_panel.input_active
-            || (self.connection_form.is_editing()
-                && self.active_layout == ActiveLayout::Profiles
-                && self.focus.current() == FocusTarget::ConnectionForm)
-    }
-
-    /// Dismiss every open popup/dialog.
-    fn dismiss_all_popups(&mut self) {
-        self.context_menu.hide();
-        self.confirm_dialog.hide();
-        self.cert_trust_dialog.hide();
-        self.connect_dialog.hide();
-        self.new_connection_dialog.hide();
-        self.credential_prompt.hide();
-        self.search_dialog.hide();
-        self.command_panel.soft_deactivate();
-        self.attribute_editor.hide();
-        self.attribute_picker.hide();
-        self.export_dialog.hide();
-        self.bulk_update_dialog.hide();
-        self.create_entry_dialog.hide();
-        self.schema_viewer.hide();
-        self.help_popup.hide();
-        self.about_popup.hide();
-        self.log_panel.hide();
-        self.profile_export_dialog.hide();
-        self.profile_import_dialog.hide();
-    }
-
-    /// Main event loop.
-    pub async fn run(&mut self) -> anyhow::Result<()> {
-        tui::install_panic_hook();
-        let mut terminal = tui::init()?;
-
-        let tick_rate = Duration::from_millis(self.config.general.tick_rate_ms);
-
-        loop {
-            // Render
-            terminal.draw(|frame| self.render(frame))?;
+            let tx = self.action_tx.clone();
 
-            // Poll for events
-            if let Some(app_event) = event::poll_event(tick_rate) {
-                match app_event {
-                    AppEvent::Key(key) => {
-                        // Clear pending 'g' if a popup/input became active
-                        if self.pending_g && self.any_popup_or_input_active() {
-                            self.pending_g = false;
-                        }
-
-                        // Search binding activates search from any non-input context,
-                        // but yields to dialogs/popups/editors that capture keystrokes.
-                        let action = if self.pending_g {
-                            // Resolve vim-style gt/gT tab switching
-                            self.pending_g = false;
-                            match key.code {
-                                KeyCode::Char('t') => Action::NextTab,
-                                KeyCode::Char('T') => Action::PrevTab,
-                                _ => Action::None,
-                            }
-                        } else if !self.any_popup_or_input_active()
-                            && matches!(
-                                self.keymap.resolve_global_only(&key),
-                                Action::SearchFocusInput
-                            )
-                        {
-                            self.dismiss_all_popups();
-                            if self.active_layout != ActiveLayout::Browser {
-                                let _ = self
-                                    .action_tx
-                                    .send(Action::SwitchLayout(ActiveLayout::Browser));
-                            }
-                            Action::SearchFocusInput
-                        // Popups intercept keys first
-                        } else if self.context_menu.visible {
-                            self.context_menu.handle_key_event(key)
-                        } else if self.attribute_editor.visible {
-                            self.attribute_editor.handle_key_event(key)
-                        } else if self.attribute_picker.visible {
-                            self.attribute_picker.handle_key_event(key)
-                        } else if self.confirm_dialog.visible {
-                            self.confirm_dialog.handle_key_event(key)
-                        } else if self.cert_trust_dialog.visible {
-                            self.cert_trust_dialog.handle_key_event(key)
-                        } else if self.connect_dialog.visible {
-                            self.connect_dialog.handle_key_event(key)
-                        } else if self.new_connection_dialog.visible {
-                            self.new_connection_dialog.handle_key_event(key)
-                        } else if self.credential_prompt.visible {
-                            self.credential_prompt.handle_key_event(key)
-                        } else if self.vault_password_dialog.visible {
-                            self.vault_password_dialog.handle_key_event(key)
-                        } else if self.search_dialog.visible {
-                            // Search popup is open — route keys based on input state
-                            if matches!(
-                                self.keymap.resolve_global_only(&key),
-                                Action::SearchFocusInput
-                            ) {
-                                // F9 toggles popup closed
-                                self.search_dialog.hide();
-                                self.command_panel.soft_deactivate();
-                                Action::None
-                            } else if self.command_panel.input_active {
-                                // Input is active — route to command panel
-                                self.command_panel.handle_input_key(key)
-                            } else {
-                                // Input not active — navigate results or edit filter
-                                match key.code {
-                                    KeyCode::Char('/') => {
-                                        // Reactivate input editing
-                                        self.command_panel.resume_input();
-                                        Action::None
-                                    }
-                                    KeyCode::Up
-                                    | KeyCode::Down
-                                    | KeyCode::Enter
-                                    | KeyCode::Esc
-                                    | KeyCode::Char('j')
-                                    | KeyCode::Char('k')
-                                    | KeyCode::Char('q') => {
-                                        let a = self.search_dialog.handle_key_event(key);
-                                        if matches!(&a, Action::TreeSelect(_)) {
-                                            self.command_panel.soft_deactivate();
-                                            let _ = self
-                                                .action_tx
-                                                .send(Action::FocusPanel(FocusTarget::DetailPanel));
-                                        }
-                                        a
-                                    }
-                                    KeyCode::Char(c) if !c.is_control() => {
-                                        // Start editing with this character
-                                        self.command_panel.resume_input();
-                                        self.command_panel.handle_input_key(key)
-                                    }
-                                    _ => Action::None,
-                                }
-                            }
-                        } else if self.export_dialog.visible {
-                            self.export_dialog.handle_key_event(key)
-                        } else if self.bulk_update_dialog.visible {
-                            self.bulk_update_dialog.handle_key_event(key)
-                        } else if self.profile_export_dialog.visible {
-                            self.profile_export_dialog
-                                .handle_key_event(key, &self.config.connections)
-                        } else if self.profile_import_dialog.visible {
-                            self.profile_import_dialog.handle_key_event(key)
-                        } else if self.create_entry_dialog.visible {
-                            self.create_entry_dialog.handle_key_event(key)
-                        } else if self.schema_viewer.visible {
-                            self.schema_viewer.handle_key_event(key)
-                        } else if self.help_popup.visible {
-                            let a = self.help_popup.handle_key_event(key);
-                            if matches!(a, Action::ClosePopup) && self.show_connect_after_help {
-                                self.show_connect_after_help = false;
-                                // Ask about vault setup, then show connect dialog
-                                let _ = self.action_tx.send(Action::ShowConfirm(
-                                    "Use an encrypted vault for password storage?".to_string(),
-                                    Box::new(Action::VaultSetupPrompt),
-                                ));
-                                let _ = self.action_tx.send(Action::ShowConnectDialog);
-                            }
-                            a
-                        } else if self.about_popup.visible {
-                            self.about_popup.handle_key_event(key)
-                        } else if self.log_panel.visible {
-                            self.log_panel.handle_key_event(key)
-                        } else if self.command_panel.input_active
-                            && self.active_layout == ActiveLayout::Browser
-                        {
-                            self.command_panel.handle_input_key(key)
-                        } else if self.connection_form.is_editing()
-                            && self.active_layout == ActiveLayout::Profiles
-                            && self.focus.current() == FocusTarget::ConnectionForm
+            match &tab.backend {
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
+                }
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    tokio::spawn(async move {
+                        let mut conn = connection.lock().await;
+                        match conn
+                            .move_entry(&dn, &new_parent, subtree, copy_fallback)
+                            .await
                         {
-                            // Connection form in edit/create mode captures all keys
-                            self.connection_form.handle_key_event(key)
-                        } else if self.active_layout == ActiveLayout::Profiles {
-                            // Connections layout: route to connections panels first
-                            let panel_action = match self.focus.current() {
-                                FocusTarget::ConnectionsTree => {
-                                    self.connections_tree.handle_key_event(key)
-                                }
-                                FocusTarget::ConnectionForm => {
-                                    self.connection_form.handle_key_event(key)
-                                }
-                                _ => Action::None,
-                            };
-                            if matches!(panel_action, Action::None) {
-                                self.keymap.resolve(key, self.focus.current())
-                            } else {
-                                panel_action
+                            Ok((new_dn, method)) => {
+                                let copied = match method {
+                                    MoveMethod::Renamed => None,
+                                    MoveMethod::Copied(count) => Some(count),
+                                };
+                                let _ = tx.send(Action::EntryMoved {
+                                    old_dn: dn,
+                                    new_dn,
+                                    copied,
+                                });
                             }
-                        } else {
-                            // Browser layout: intercept '/' to open search popup
-                            if matches!(key.code, KeyCode::Char('/'))
-                                && !self.any_popup_or_input_active()
-                            {
-                                Action::SearchFocusInput
-                            } else {
-                                // Try panel-specific handler first, fall back to global keymap
-                                let panel_action = match self.focus.current() {
-                                    FocusTarget::TreePanel => self.tree_panel.handle_key_event(key),
-                                    FocusTarget::DetailPanel => {
-                                        self.detail_panel.handle_key_event(key)
-                                    }
-                                    _ => Action::None,
+                            Err(CoreError::SubtreeMoveUnsupported(reason)) => {
+                                let msg = format!(
+                                    "The server can't move this subtree in place:\n{}\n\n\
+                                     Copy it under the new parent and delete the originals?\n\
+                                     The copies get new identities (objectGUID, objectSid) \
+                                     and lose attributes the server doesn't return, such as \
+                                     password hashes.",
+                                    reason
+                                );
+                                let copy = Action::MoveEntry {
+                                    dn,
+                                    new_parent,
+                                    subtree,
+                                    copy_fallback: true,
                                 };
-                                if matches!(panel_action, Action::None) {
-                                    self.keymap.resolve(key, self.focus.current())
-                                } else {
-                                    panel_action
-                                }
+                                let _ = tx.send(Action::ShowConfirm(msg, Box::new(copy)));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to move entry: {}",
+                                    e
+                                )));
                             }
-                        };
-                        // Initiate vim 'g' prefix when g produces no action
-                        if matches!(action, Action::None)
-                            && matches!(key.code, KeyCode::Char('g'))
-                            && key.modifiers == KeyModifiers::NONE
-                            && !self.any_popup_or_input_active()
-                        {
-                            self.pending_g = true;
-                        } else {
-                            let _ = self.action_tx.send(action);
-                        }
-                    }
-                    AppEvent::Mouse(mouse) => {
-                        let action = self.handle_mouse(mouse);
-                        if !matches!(action, Action::None) {
-                            let _ = self.action_tx.send(action);
                         }
-                    }
-                    AppEvent::Resize(w, h) => {
-                        let _ = self.action_tx.send(Action::Resize(w, h));
-                    }
-                    AppEvent::Tick => {
-                        let _ = self.action_tx.send(Action::Tick);
-                    }
+                    });
                 }
             }
+        }
+    }
 
-            // Drain action queue
-            while let Ok(action) = self.action_rx.try_recv() {
-                self.process_action(action).await;
+    fn spawn_load_password_policy(&self, conn_id: ConnectionId, dn: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(_) => {
+                let _ = tx.send(Action::ErrorMessage(
+                    "Password policy needs a live connection".to_string(),
+                ));
             }
-
-            if self.should_quit {
-                break;
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn.password_policy(&dn).await {
+                        Ok(report) => {
+                            let _ = tx.send(Action::PasswordPolicyLoaded(Box::new(report)));
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Password policy of {} failed: {}",
+                                dn, e
+                            )));
+                        }
+                    }
+                });
             }
         }
-
-        tui::restore()?;
-        Ok(())
     }
 
-    fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> Action {
-        // Popups block mouse events; also clear any drag
-        if self.popup_active() {
-            self.drag_target = None;
-            return Action::None;
-        }
-
-        match mouse.kind {
-            MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
-                // Check if click is on a panel divider (start drag)
-                if let Some(target) = self.divider_hit(mouse.column, mouse.row) {
-                    self.drag_target = Some(target);
-                    return Action::None;
-                }
-
-                let pos = Rect::new(mouse.column, mouse.row, 1, 1);
-
-                // Check layout bar clicks (unified tab strip)
-                if let Some(bar) = self.layout_bar_area {
-                    if bar.intersects(pos) {
-                        for &(start, end, ref target) in &self.layout_bar.hit_regions {
-                            if mouse.column >= start && mouse.column < end {
-                                return match target {
-                                    None => Action::SwitchLayout(ActiveLayout::Profiles),
-                                    Some(id) => Action::SwitchTab(*id),
-                                };
-                            }
-                        }
-                    }
-                }
-
-                // Check connections layout panels
-                if self.active_layout == ActiveLayout::Profiles {
-                    if let Some(ct) = self.conn_tree_area {
-                        if ct.intersects(pos) {
-                            return Action::FocusPanel(FocusTarget::ConnectionsTree);
+    fn spawn_nested_membership(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        expansion: Option<Expansion>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let active_directory = tab.server_type.starts_with("Active Directory");
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let Some(entry) = dir.entry(&dn) else {
+                    let _ = tx.send(Action::ErrorMessage(format!("{} was not found", dn)));
+                    return;
+                };
+                let expansion = expansion.unwrap_or_else(|| groups::default_expansion(&entry));
+                let graph = groups::group_graph(&dir.search(&base_dn, "(objectClass=*)"));
+                let report = groups::expand(&graph, &dn, expansion);
+                let _ = tx.send(Action::NestedMembershipLoaded(Box::new(report)));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn
+                        .nested_membership(&dn, &base_dn, expansion, active_directory)
+                        .await
+                    {
+                        Ok(report) => {
+                            let _ = tx.send(Action::NestedMembershipLoaded(Box::new(report)));
                         }
-                    }
-                    if let Some(cf) = self.conn_form_area {
-                        if cf.intersects(pos) {
-                            return Action::FocusPanel(FocusTarget::ConnectionForm);
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Nested membership of {} failed: {}",
+                                dn, e
+                            )));
                         }
                     }
-                    return Action::None;
-                }
-
-                // Browser layout panels
-                if let Some(tree) = self.tree_area {
-                    if tree.intersects(pos) {
-                        return Action::FocusPanel(FocusTarget::TreePanel);
-                    }
-                }
-                if let Some(detail) = self.detail_area {
-                    if detail.intersects(pos) {
-                        return Action::FocusPanel(FocusTarget::DetailPanel);
-                    }
-                }
-                Action::None
+                });
             }
-            MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
-                if let Some(target) = self.drag_target {
-                    self.apply_drag(target, mouse.column, mouse.row);
-                }
-                Action::None
+        }
+    }
+
+    fn spawn_orphan_scan(&self, conn_id: ConnectionId, base_dn: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let entries = dir.search(&base_dn, "(objectClass=*)");
+                let report = orphans::scan(&base_dn, &entries, REFERENCE_ATTRIBUTES, |dn| {
+                    dir.entry(dn).is_some()
+                });
+                let _ = tx.send(Action::OrphanScanLoaded(Box::new(report)));
             }
-            MouseEventKind::Down(crossterm::event::MouseButton::Right) => {
-                if self.active_layout == ActiveLayout::Profiles {
-                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
-                    if let Some(ct) = self.conn_tree_area {
-                        if ct.intersects(pos) {
-                            self.context_menu
-                                .show_for_profiles(self.connection_form.profile_index);
-                            self.context_menu.set_anchor(mouse.column, mouse.row);
-                            return Action::Render;
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn
+                        .find_orphaned_references(&base_dn, REFERENCE_ATTRIBUTES)
+                        .await
+                    {
+                        Ok(report) => {
+                            let _ = tx.send(Action::OrphanScanLoaded(Box::new(report)));
                         }
-                    }
-                    if let Some(cf) = self.conn_form_area {
-                        if cf.intersects(pos) {
-                            self.context_menu
-                                .show_for_profiles(self.connection_form.profile_index);
-                            self.context_menu.set_anchor(mouse.column, mouse.row);
-                            return Action::Render;
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Orphaned reference scan of {} failed: {}",
+                                base_dn, e
+                            )));
                         }
                     }
-                }
-                if self.active_layout == ActiveLayout::Browser {
-                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
-                    if let Some(tree) = self.tree_area {
-                        if tree.intersects(pos) {
-                            if let Some(dn) = self.tree_panel.selected_dn().cloned() {
-                                self.context_menu.show_for_tree(&dn);
-                                self.context_menu.set_anchor(mouse.column, mouse.row);
-                                return Action::Render;
-                            }
+                });
+            }
+        }
+    }
+
+    fn spawn_find_duplicates(
+        &self,
+        conn_id: ConnectionId,
+        attribute: String,
+        base_dn: Option<String>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let base_dn = base_dn.unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let entries = dir.search(&base_dn, "(objectClass=*)");
+                let report = loom_core::duplicates::find_duplicates(&base_dn, &entries, &attribute);
+                let _ = tx.send(Action::DuplicatesLoaded(Box::new(report)));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn.find_duplicate_values(&base_dn, &attribute).await {
+                        Ok(report) => {
+                            let _ = tx.send(Action::DuplicatesLoaded(Box::new(report)));
                         }
-                    }
-                    if let Some(detail) = self.detail_area {
-                        if detail.intersects(pos) {
-                            if let (Some(entry), Some((attr, val))) = (
-                                &self.detail_panel.entry,
-                                self.detail_panel.selected_attr_value(),
-                            ) {
-                                let dn = entry.dn.clone();
-                                let attr = attr.to_string();
-                                let val = val.to_string();
-                                self.context_menu.show_for_detail(&dn, &attr, &val);
-                                self.context_menu.set_anchor(mouse.column, mouse.row);
-                                return Action::Render;
-                            }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Duplicate {} scan failed: {}",
+                                attribute, e
+                            )));
                         }
                     }
-                }
-                Action::None
-            }
-            MouseEventKind::Up(_) => {
-                self.drag_target = None;
-                Action::None
+                });
             }
-            _ => Action::None,
         }
     }
 
-    /// Check if a mouse position is on (or within 1 cell of) a panel divider.
-    fn divider_hit(&self, col: u16, row: u16) -> Option<DragTarget> {
-        match self.active_layout {
-            ActiveLayout::Browser => {
-                // Vertical divider: right edge of tree panel
-                if let Some(tree) = self.tree_area {
-                    let divider_col = tree.x + tree.width;
-                    if col.abs_diff(divider_col) <= 1 && row >= tree.y && row < tree.y + tree.height
-                    {
-                        return Some(DragTarget::Tree);
-                    }
+    /// Gather attribute statistics of a subtree as a job, which can be
+    /// cancelled from the jobs panel.
+    fn spawn_attribute_stats(
+        &mut self,
+        conn_id: ConnectionId,
+        base_dn: String,
+        sample: Option<usize>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let summary = |stats: &loom_core::attribute_stats::SubtreeStats| {
+            format!(
+                "Attribute statistics: {} attributes in {} entries under {}",
+                stats.attributes.len(),
+                stats.entries_scanned,
+                stats.base_dn
+            )
+        };
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let mut entries = dir.search(&base_dn, "(objectClass=*)");
+                if let Some(limit) = sample {
+                    entries.truncate(limit);
                 }
+                let mut collector = loom_core::attribute_stats::StatsCollector::default();
+                collector.add(&entries);
+                let stats = collector.finish(&base_dn, sample.is_some());
+                let _ = self.action_tx.send(Action::AttributeStatsComplete(
+                    summary(&stats),
+                    Box::new(stats),
+                ));
             }
-            ActiveLayout::Profiles => {
-                // Vertical divider: right edge of profiles tree
-                if let Some(ct) = self.conn_tree_area {
-                    let divider_col = ct.x + ct.width;
-                    if col.abs_diff(divider_col) <= 1 && row >= ct.y && row < ct.y + ct.height {
-                        return Some(DragTarget::Tree);
-                    }
-                }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let title = match sample {
+                    Some(n) => format!("Attribute statistics of {} ({} entries)", base_dn, n),
+                    None => format!("Attribute statistics of {}", base_dn),
+                };
+                let task = JobTask::AttributeStats {
+                    base_dn: base_dn.clone(),
+                    sample,
+                };
+                self.jobs
+                    .spawn(conn_id, title, task, move |job| async move {
+                        let mut conn = pool.long_reader().await;
+                        match conn
+                            .attribute_stats(&base_dn, sample, |done| job.progress(done, sample))
+                            .await
+                        {
+                            Ok(stats) => {
+                                Action::AttributeStatsComplete(summary(&stats), Box::new(stats))
+                            }
+                            Err(e) => Action::ErrorMessage(format!(
+                                "Attribute statistics of {} failed: {}",
+                                base_dn, e
+                            )),
+                        }
+                    });
             }
         }
-        None
     }
 
-    /// Update split percentages based on the current drag position.
-    fn apply_drag(&mut self, target: DragTarget, col: u16, _row: u16) {
-        // We need a reference area to compute the percentage from pixel position.
-        match target {
-            DragTarget::Tree => {
-                if let (Some(tree), Some(detail)) = (self.tree_area, self.detail_area) {
-                    let total_w = (tree.width + detail.width) as u32;
-                    if total_w == 0 {
-                        return;
-                    }
-                    let offset = col.saturating_sub(tree.x) as u32;
-                    let pct = ((offset * 100) / total_w) as u16;
-                    self.tree_split_pct = pct.clamp(10, 90);
-                }
+    fn spawn_orphan_cleanup(&mut self, conn_id: ConnectionId, refs: Vec<Reference>) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        if tab.read_only && !tab.dry_run {
+            let _ = self
+                .action_tx
+                .send(Action::ErrorMessage("Connection is read-only".to_string()));
+            return;
+        }
+        match &tab.backend {
+            TabBackend::Offline(_) => {
+                let _ = self.action_tx.send(Action::ErrorMessage(
+                    "Example directory is read-only".to_string(),
+                ));
+            }
+            TabBackend::Live(pool) => {
+                let connection = pool.main();
+                let title = format!("Remove {} orphaned references", refs.len());
+                let task = JobTask::OrphanCleanup(refs.clone());
+                self.jobs.spawn(conn_id, title, task, |_| async move {
+                    let mut conn = connection.lock().await;
+                    let result = conn.remove_orphaned_references(&refs).await;
+                    let summary = format!(
+                        "Orphaned references: {} removed, {} kept because the entry exists, {} entries failed",
+                        result.removed,
+                        result.skipped,
+                        result.errors.len()
+                    );
+                    let problems = result
+                        .errors
+                        .iter()
+                        .map(|(dn, err)| format!("Removing orphaned references from {} failed: {}", dn, err))
+                        .collect();
+                    Action::OrphanCleanupComplete(summary, problems)
+                });
             }
         }
     }
 
-    async fn process_action(&mut self, action: Action) {
-        match action {
-            Action::Quit => {
-                self.should_quit = true;
+    /// Read each entry's values of `attr` for the batch editor.
+    fn spawn_batch_edit_load(&self, conn_id: ConnectionId, dns: Vec<String>, attr: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let TabBackend::Live(pool) = &tab.backend else {
+            let _ = tx.send(Action::ErrorMessage(
+                "Example directory is read-only".to_string(),
+            ));
+            return;
+        };
+        let pool = pool.clone();
+        let activity = self.jobs.track(conn_id);
+        tokio::spawn(async move {
+            let _activity = activity;
+            let mut conn = pool.reader().await;
+            let (values, errors) = conn.read_values(&dns, &attr).await;
+            let _ = tx.send(Action::BatchEditLoaded {
+                attr,
+                values,
+                errors,
+            });
+        });
+    }
+
+    /// Write a batch edit's values as a background job.
+    fn spawn_batch_edit(&mut self, conn_id: ConnectionId, attr: String, edits: Vec<ValueEdit>) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        if tab.read_only && !tab.dry_run {
+            let _ = self
+                .action_tx
+                .send(Action::ErrorMessage("Connection is read-only".to_string()));
+            return;
+        }
+        if let Some(msg) = self.policy_error(conn_id, &attr) {
+            let _ = self.action_tx.send(Action::ErrorMessage(msg));
+            return;
+        }
+        let TabBackend::Live(pool) = &tab.backend else {
+            let _ = self.action_tx.send(Action::ErrorMessage(
+                "Example directory is read-only".to_string(),
+            ));
+            return;
+        };
+        let connection = pool.main();
+        let hook = self.hook_context(conn_id, HookEvent::PostBulkUpdate);
+        let hooks = self.hooks.clone();
+        let throttle = self.config.bulk.clone();
+        let title = format!("Batch edit of {} on {} entries", attr, edits.len());
+        let task = JobTask::BatchEdit {
+            attr: attr.clone(),
+            edits: edits.clone(),
+        };
+        self.jobs.spawn(conn_id, title, task, |job| async move {
+            let mut conn = connection.lock().await;
+            let progress = |done, total| job.progress(done, Some(total));
+            let outcomes = conn
+                .apply_value_edits(&attr, &edits, &throttle, progress)
+                .await;
+            let count =
+                |wanted: fn(&EditOutcome) -> bool| outcomes.iter().filter(|o| wanted(o)).count();
+            let summary = format!(
+                "Batch edit of {}: {} applied, {} unchanged, {} failed",
+                attr,
+                count(|o| *o == EditOutcome::Applied),
+                count(|o| *o == EditOutcome::Unchanged),
+                count(|o| matches!(o, EditOutcome::Failed(_)))
+            );
+            if let Some(context) = hook {
+                hooks.run(context.message(&summary));
             }
-            Action::FocusNext => {
-                self.focus.next();
+            let outcomes = edits.into_iter().map(|e| e.dn).zip(outcomes).collect();
+            Action::BatchEditComplete {
+                attr,
+                summary,
+                outcomes,
             }
-            Action::FocusPrev => {
-                self.focus.prev();
+        });
+    }
+
+    /// Apply the tab's staged changes in order as a background job.
+    fn spawn_apply_staged(&mut self, conn_id: ConnectionId, stop_on_error: bool) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let TabBackend::Live(pool) = &tab.backend else {
+            return;
+        };
+        let changes = pool.changes();
+        if changes.is_empty() {
+            let _ = self
+                .action_tx
+                .send(Action::StatusMessage("No changes staged".to_string()));
+            return;
+        }
+        if changes.is_applying() {
+            let _ = self.action_tx.send(Action::ErrorMessage(
+                "The staged changes are already being applied".to_string(),
+            ));
+            return;
+        }
+        let connection = pool.main();
+        let title = format!("Apply {} staged changes", changes.len());
+        let task = JobTask::ApplyStaged { stop_on_error };
+        self.jobs.spawn(conn_id, title, task, |job| async move {
+            let mut conn = connection.lock().await;
+            let progress = |done, total| job.progress(done, Some(total));
+            let result = conn.apply_staged(stop_on_error, progress).await;
+            let mut summary = format!(
+                "Staged changes: {} applied, {} failed",
+                result.applied,
+                result.errors.len()
+            );
+            if result.remaining > 0 {
+                summary.push_str(&format!(", {} still staged", result.remaining));
             }
-            Action::FocusPanel(target) => {
-                self.focus.set(target);
+            let problems = result
+                .errors
+                .iter()
+                .map(|(dn, err)| format!("Staged change to {} failed: {}", dn, err))
+                .collect();
+            Action::StagedChangesApplied(summary, problems)
+        });
+    }
+
+    fn spawn_account_report(&self, conn_id: ConnectionId, report: Report, days: u32) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let query = ReportQuery::new(
+            report,
+            days,
+            tab.server_type.starts_with("Active Directory"),
+        );
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let entries = query.apply(dir.search(&base_dn, "(objectClass=*)"));
+                let _ = tx.send(Action::SearchResults(conn_id, entries));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn.run_report(&base_dn, &query).await {
+                        Ok(entries) => {
+                            let _ = tx.send(Action::SearchResults(conn_id, entries));
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Report \"{}\" failed: {}",
+                                query.report.title(query.days),
+                                e
+                            )));
+                        }
+                    }
+                });
             }
+        }
+    }
 
-            // Tab management — cycle through Profiles → conn1 → conn2 → … → Profiles
-            Action::NextTab => {
-                let tab_count = self.tab_bar.tabs.len();
-                if tab_count > 0 {
-                    if self.active_layout == ActiveLayout::Profiles {
-                        let first_id = self.tab_bar.tabs[0].id;
-                        self.switch_to_tab(first_id);
-                        self.active_layout = ActiveLayout::Browser;
-                        self.layout_bar.active = ActiveLayout::Browser;
-                        self.focus.set_layout(ActiveLayout::Browser);
-                    } else {
-                        let current_idx = self
+    fn spawn_defined_report(
+        &self,
+        conn_id: ConnectionId,
+        report: ReportDefinition,
+        values: BTreeMap<String, String>,
+    ) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let tx = self.action_tx.clone();
+        match &tab.backend {
+            TabBackend::Offline(dir) => match report.render_filter(&values, chrono::Utc::now()) {
+                Ok(filter) => {
+                    let base_dn = report.base_dn.as_deref().unwrap_or(&base_dn);
+                    let entries = report.decode(dir.search(base_dn, &filter));
+                    let _ = tx.send(Action::SearchResults(conn_id, entries));
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Report {} failed: {}",
+                        report.name, e
+                    )));
+                }
+            },
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    match conn.run_report_definition(&base_dn, &report, &values).await {
+                        Ok(entries) => {
+                            let _ = tx.send(Action::SearchResults(conn_id, entries));
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Report {} failed: {}",
+                                report.name, e
+                            )));
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    fn spawn_safe_rename(&self, conn_id: ConnectionId, plan: RenamePlan) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            if tab.read_only && !tab.dry_run {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
+                }
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    tokio::spawn(async move {
+                        let mut conn = connection.lock().await;
+                        match conn.apply_rename_plan(&plan).await {
+                            Ok(new_dn) => {
+                                let _ = tx.send(Action::SafeRenameComplete {
+                                    old_dn: plan.dn,
+                                    new_dn,
+                                });
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Rename of {} failed: {}",
+                                    plan.dn, e
+                                )));
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Look up every favorite on the tab and report the ones that are gone.
+    fn spawn_check_favorites(&self, conn_id: ConnectionId) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        if tab.favorites.is_empty() {
+            return;
+        }
+        let favorites = tab.favorites.clone();
+        let tx = self.action_tx.clone();
+
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let stale = favorites
+                    .into_iter()
+                    .filter(|dn| dir.entry(dn).is_none())
+                    .collect();
+                let _ = tx.send(Action::FavoritesChecked(conn_id, stale));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let mut conn = pool.reader().await;
+                    let mut stale = Vec::new();
+                    for dn in favorites {
+                        // Lookup errors are not proof the entry is gone
+                        if let Ok(false) = conn.entry_exists(&dn).await {
+                            stale.push(dn);
+                        }
+                    }
+                    let _ = tx.send(Action::FavoritesChecked(conn_id, stale));
+                });
+            }
+        }
+    }
+
+    /// Run a saved search for its tree folder; only the DNs are fetched.
+    fn spawn_saved_search(&mut self, conn_id: ConnectionId, name: String) {
+        let Some(filter) = self
+            .tabs
+            .iter()
+            .find(|t| t.id == conn_id)
+            .and_then(|t| t.search_folders.iter().find(|f| f.name == name))
+            .map(|f| f.filter.clone())
+        else {
+            return;
+        };
+        // Expanded on every run, so date macros move with the clock
+        let filter = match self.expand_filter_macros(conn_id, &filter) {
+            Ok(filter) => filter,
+            Err(e) => {
+                self.push_error(format!("Saved search '{}': {}", name, e));
+                return;
+            }
+        };
+        let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) else {
+            return;
+        };
+        if let Some(folder) = tab.search_folders.iter_mut().find(|f| f.name == name) {
+            folder.loading = true;
+        }
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let tx = self.action_tx.clone();
+
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let dns = dir.search(&base_dn, &filter).into_iter().map(|e| e.dn);
+                let _ = tx.send(Action::SavedSearchResults(conn_id, name, Ok(dns.collect())));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let activity = self.jobs.track(conn_id);
+                tokio::spawn(async move {
+                    let _activity = activity;
+                    let mut conn = pool.reader().await;
+                    let result = conn
+                        .search_subtree(&base_dn, &filter, &["1.1"])
+                        .await
+                        .map(|entries| entries.into_iter().map(|e| e.dn).collect())
+                        .map_err(|e| e.to_string());
+                    let _ = tx.send(Action::SavedSearchResults(conn_id, name, result));
+                });
+            }
+        }
+    }
+
+    /// Write a tab's saved searches back to its profile.
+    fn save_search_folders(&mut self, conn_id: ConnectionId) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let searches = tab
+            .search_folders
+            .iter()
+            .map(|f| SavedSearch {
+                name: f.name.clone(),
+                filter: f.filter.clone(),
+            })
+            .collect();
+        let label = tab.label.clone();
+        if let Some(profile) = self.config.connections.iter_mut().find(|p| p.name == label) {
+            profile.saved_searches = searches;
+            if let Err(e) = self.config.save() {
+                self.push_error(format!("Failed to save config: {}", e));
+            }
+        }
+    }
+
+    fn spawn_dn_search(
+        &self,
+        conn_id: ConnectionId,
+        generation: u64,
+        query: String,
+        base_dn: String,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    let entries = dir.search_limited(&base_dn, &query, 50);
+                    let _ = tx.send(Action::DnSearchResults {
+                        generation,
+                        entries,
+                    });
+                }
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    tokio::spawn(async move {
+                        let mut conn = pool.reader().await;
+                        let result = match conn
+                            .search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
+                            .await
+                        {
+                            Ok(entries) => Ok(entries),
+                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                                if conn.reconnect().await.is_ok() {
+                                    report_server(&tx, conn_id, &conn);
+                                    conn.search_limited(&base_dn, &query, &["cn", "uid", "sn"], 50)
+                                        .await
+                                } else {
+                                    Err(e)
+                                }
+                            }
+                            Err(e) => Err(e),
+                        };
+
+                        match result {
+                            Ok(entries) => {
+                                let _ = tx.send(Action::DnSearchResults {
+                                    generation,
+                                    entries,
+                                });
+                            }
+                            Err(e) => {
+                                // Silently log — no error popup spam during live search
+                                debug!("DN search failed: {}", e);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    fn spawn_live_search(&self, conn_id: ConnectionId, generation: u64, filter: String) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            let base_dn = tab.directory_tree.root_dn.clone();
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    let mut entries = dir.search(&base_dn, &filter);
+                    entries.truncate(50);
+                    let _ = tx.send(Action::LiveSearchResults {
+                        generation,
+                        entries,
+                    });
+                }
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let activity = self.jobs.track(conn_id);
+                    tokio::spawn(async move {
+                        let _activity = activity;
+                        let mut conn = pool.reader().await;
+                        let result = match conn.search_limited(&base_dn, &filter, &["*"], 50).await
+                        {
+                            Ok(entries) => Ok(entries),
+                            Err(e) if LdapConnection::is_connection_error(&e) => {
+                                if conn.reconnect().await.is_ok() {
+                                    report_server(&tx, conn_id, &conn);
+                                    conn.search_limited(&base_dn, &filter, &["*"], 50).await
+                                } else {
+                                    Err(e)
+                                }
+                            }
+                            Err(e) => Err(e),
+                        };
+
+                        match result {
+                            Ok(entries) => {
+                                let _ = tx.send(Action::LiveSearchResults {
+                                    generation,
+                                    entries,
+                                });
+                            }
+                            Err(e) => {
+                                debug!("Live search failed: {}", e);
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Entries scanned when sampling an attribute's values.
+    const VALUE_SAMPLE_SIZE: usize = 1000;
+    /// Attributes with more distinct values than this get no suggestions.
+    const VALUE_SAMPLE_MAX_DISTINCT: usize = 200;
+
+    fn spawn_sample_values(&self, conn_id: ConnectionId, attr: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let tx = self.action_tx.clone();
+
+        match &tab.backend {
+            TabBackend::Offline(dir) => {
+                let filter = loom_core::filter::Filter::present(&attr).to_string();
+                let mut entries = dir.search(&base_dn, &filter);
+                entries.truncate(Self::VALUE_SAMPLE_SIZE);
+                let values = loom_core::search::distinct_attribute_values(
+                    &entries,
+                    &attr,
+                    Self::VALUE_SAMPLE_MAX_DISTINCT,
+                );
+                let _ = tx.send(Action::AttributeValuesSampled(conn_id, attr, values));
+            }
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                tokio::spawn(async move {
+                    let mut conn = pool.reader().await;
+                    let values = conn
+                        .sample_attribute_values(
+                            &base_dn,
+                            &attr,
+                            Self::VALUE_SAMPLE_SIZE,
+                            Self::VALUE_SAMPLE_MAX_DISTINCT,
+                        )
+                        .await
+                        .unwrap_or_else(|e| {
+                            debug!("Value sample for {} failed: {}", attr, e);
+                            Vec::new()
+                        });
+                    let _ = tx.send(Action::AttributeValuesSampled(conn_id, attr, values));
+                });
+            }
+        }
+    }
+
+    fn spawn_add_multiple_values(
+        &self,
+        conn_id: ConnectionId,
+        dn: String,
+        attr: String,
+        values: Vec<String>,
+    ) {
+        let tab = self.tabs.iter().find(|t| t.id == conn_id);
+        if let Some(tab) = tab {
+            if tab.read_only && !tab.dry_run {
+                let _ = self
+                    .action_tx
+                    .send(Action::ErrorMessage("Connection is read-only".to_string()));
+                return;
+            }
+            if let Some(msg) = self.policy_error(conn_id, &attr) {
+                let _ = self.action_tx.send(Action::ErrorMessage(msg));
+                return;
+            }
+            let tx = self.action_tx.clone();
+
+            match &tab.backend {
+                TabBackend::Offline(_) => {
+                    let _ = tx.send(Action::ErrorMessage(
+                        "Example directory is read-only".to_string(),
+                    ));
+                }
+                TabBackend::Live(pool) => {
+                    let connection = pool.main();
+                    tokio::spawn(async move {
+                        let mut conn = connection.lock().await;
+                        match conn.add_attribute_values(&dn, &attr, values).await {
+                            Ok(()) => {
+                                let _ = tx.send(Action::AttributeSaved(dn));
+                            }
+                            Err(e) => {
+                                let _ = tx.send(Action::ErrorMessage(format!(
+                                    "Failed to add values: {}",
+                                    e
+                                )));
+                            }
+                        }
+                    });
+                }
+            }
+        }
+    }
+
+    /// Look up whether an attribute has DN syntax and whether it's multi-valued,
+    /// using the active tab's schema cache.
+    fn lookup_attr_schema(&self, attr: &str) -> (bool, bool) {
+        if let Some(tab) = self.active_tab() {
+            if let Some(ref schema) = tab.schema {
+                let is_dn = matches!(schema.attribute_syntax(attr), AttributeSyntax::Dn);
+                let multi_valued = !schema.is_single_valued(attr);
+                return (is_dn, multi_valued);
+            }
+        }
+        (false, true) // default: not DN, multi-valued
+    }
+
+    /// Current values of `attr` on the entry shown in the detail panel.
+    fn detail_values(&self, dn: &str, attr: &str) -> Vec<String> {
+        self.detail_panel
+            .entry
+            .as_ref()
+            .filter(|e| e.dn.eq_ignore_ascii_case(dn))
+            .and_then(|e| find_values_ci(&e.attributes, attr).cloned())
+            .unwrap_or_default()
+    }
+
+    /// Have the open attribute editor check its value against the active
+    /// tab's schema on save. `existing` are the attribute's current values,
+    /// `replacing` the one being edited.
+    fn apply_value_rules(
+        &mut self,
+        attr: &str,
+        mut existing: Vec<String>,
+        replacing: Option<&str>,
+    ) {
+        let Some(rules) = self
+            .active_tab()
+            .and_then(|tab| tab.schema.as_ref())
+            .and_then(|schema| schema.value_rules(attr))
+        else {
+            return;
+        };
+        if let Some(pos) = replacing.and_then(|old| existing.iter().position(|v| v == old)) {
+            existing.remove(pos);
+        }
+        self.attribute_editor.set_value_rules(rules, existing);
+    }
+
+    /// True when the idle timeout has elapsed and there is an unlocked vault to lock.
+    fn idle_lock_due(&self) -> bool {
+        let minutes = self.config.general.idle_lock_minutes;
+        minutes > 0
+            && self.vault.is_some()
+            && !self.lock_screen.visible
+            && self.last_activity.elapsed() >= Duration::from_secs(minutes * 60)
+    }
+
+    /// Serve the configured (or socket-activated) remote control socket.
+    fn start_remote_control(&mut self) {
+        let path = self.config.general.ipc_socket.as_deref().map(expand_home);
+        let rpc = self.config.general.rpc_enabled;
+        match ipc::start(path.as_deref(), rpc, self.action_tx.clone()) {
+            Ok(Some(control)) => {
+                let msg = match control.token_path() {
+                    Some(token) => format!(
+                        "Listening for remote control commands and JSON-RPC (token in {})",
+                        token.display()
+                    ),
+                    None => "Listening for remote control commands".to_string(),
+                };
+                self.log_panel.push_info(msg);
+                self.remote_control = Some(control);
+            }
+            Ok(None) => {}
+            Err(e) => self.push_error(format!("Remote control disabled: {}", e)),
+        }
+    }
+
+    /// Carry out a command received on the remote control socket.
+    fn run_remote_command(&mut self, cmd: RemoteCommand) {
+        self.log_panel.push_info(format!("Remote: {}", cmd));
+        match cmd {
+            RemoteCommand::Goto(dn) => self.goto_dn_in_any_tab(dn),
+            RemoteCommand::Search(filter) => {
+                let _ = self.action_tx.send(Action::SearchExecute(filter));
+            }
+            RemoteCommand::Export { path, filter } => self.export_active_tab(path, filter),
+        }
+    }
+
+    /// Carry out a JSON-RPC request, answering through the call.
+    fn run_rpc_call(&mut self, call: RpcCall) {
+        debug!("JSON-RPC: {}", call.method);
+        match call.method.as_str() {
+            "connections" => {
+                let connections: Vec<serde_json::Value> = self
+                    .tabs
+                    .iter()
+                    .map(|tab| {
+                        serde_json::json!({
+                            "name": tab.label,
+                            "host": tab.host,
+                            "base_dn": tab.directory_tree.root_dn,
+                            "read_only": tab.read_only,
+                            "active": self.active_tab_id == Some(tab.id),
+                        })
+                    })
+                    .collect();
+                call.respond(Ok(connections.into()));
+            }
+            "goto" => {
+                let result = call.required("dn").and_then(|dn| {
+                    loom_core::dn::validate_dn(dn).map_err(RpcError::invalid_params)?;
+                    self.goto_dn_in_any_tab(dn.to_string());
+                    Ok(true.into())
+                });
+                call.respond(result);
+            }
+            "export" => {
+                let result = call.required("path").and_then(|path| {
+                    let filter = call.param("filter");
+                    if let Some(filter) = filter {
+                        loom_core::filter::validate_filter(filter)
+                            .map_err(RpcError::invalid_params)?;
+                    }
+                    if self.active_tab().is_none() {
+                        return Err(RpcError::failed("No active connection"));
+                    }
+                    self.export_active_tab(path.to_string(), filter.map(str::to_string));
+                    Ok(true.into())
+                });
+                call.respond(result);
+            }
+            "search" | "get_entry" => {
+                if let Err(e) = self.spawn_rpc_read(&call) {
+                    call.respond(Err(e));
+                }
+            }
+            other => call.respond(Err(RpcError::new(
+                ipc::METHOD_NOT_FOUND,
+                format!("unknown method '{}'", other),
+            ))),
+        }
+    }
+
+    /// Answer a `search` or `get_entry` request from the connection it names,
+    /// or the active one.
+    fn spawn_rpc_read(&self, call: &RpcCall) -> Result<(), RpcError> {
+        let tab = match call.param("connection") {
+            Some(name) => self
+                .tabs
+                .iter()
+                .find(|t| t.label.eq_ignore_ascii_case(name))
+                .ok_or_else(|| RpcError::invalid_params(format!("no connection '{}'", name)))?,
+            None => self
+                .active_tab()
+                .ok_or_else(|| RpcError::failed("No active connection"))?,
+        };
+        let base_dn = call
+            .param("base")
+            .unwrap_or(&tab.directory_tree.root_dn)
+            .to_string();
+        let attributes: Vec<String> = match call.params.get("attributes") {
+            Some(serde_json::Value::Array(attrs)) => attrs
+                .iter()
+                .map(|a| a.as_str().map(str::to_string))
+                .collect::<Option<_>>()
+                .ok_or_else(|| RpcError::invalid_params("attributes must be strings"))?,
+            Some(_) => return Err(RpcError::invalid_params("attributes must be a list")),
+            None => vec!["*".to_string()],
+        };
+        let limit = call
+            .params
+            .get("limit")
+            .and_then(serde_json::Value::as_u64)
+            .map(|n| n as usize);
+        let to_json = move |entries: Vec<LdapEntry>| {
+            let entries: Vec<LdapEntry> = entries
+                .into_iter()
+                .take(limit.unwrap_or(usize::MAX))
+                .collect();
+            serde_json::to_value(entries).map_err(|e| RpcError::failed(e.to_string()))
+        };
+
+        if call.method == "get_entry" {
+            let dn = call.required("dn")?.to_string();
+            match &tab.backend {
+                TabBackend::Offline(dir) => {
+                    call.respond(
+                        serde_json::to_value(dir.entry(&dn))
+                            .map_err(|e| RpcError::failed(e.to_string())),
+                    );
+                }
+                TabBackend::Live(pool) => {
+                    let pool = pool.clone();
+                    let call = call.clone();
+                    tokio::spawn(async move {
+                        let mut conn = pool.reader().await;
+                        let result = match conn.search_entry(&dn).await {
+                            Ok(entry) => serde_json::to_value(entry)
+                                .map_err(|e| RpcError::failed(e.to_string())),
+                            Err(e) => Err(RpcError::failed(e.to_string())),
+                        };
+                        call.respond(result);
+                    });
+                }
+            }
+            return Ok(());
+        }
+
+        let filter = call.required("filter")?.to_string();
+        loom_core::filter::validate_filter(&filter).map_err(RpcError::invalid_params)?;
+        match &tab.backend {
+            TabBackend::Offline(dir) => call.respond(to_json(dir.search(&base_dn, &filter))),
+            TabBackend::Live(pool) => {
+                let pool = pool.clone();
+                let call = call.clone();
+                tokio::spawn(async move {
+                    let mut conn = pool.reader().await;
+                    let attrs: Vec<&str> = attributes.iter().map(String::as_str).collect();
+                    let result = match conn.search_subtree(&base_dn, &filter, &attrs).await {
+                        Ok(entries) => to_json(entries),
+                        Err(e) => Err(RpcError::failed(e.to_string())),
+                    };
+                    call.respond(result);
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Run a command typed on the `:` command line.
+    fn run_ex_command(&mut self, command: ExCommand) {
+        let action = match command {
+            ExCommand::Connect(name) => {
+                let index = self
+                    .config
+                    .connections
+                    .iter()
+                    .position(|p| p.name.eq_ignore_ascii_case(&name))
+                    .or_else(|| {
+                        example_profile()
+                            .name
+                            .eq_ignore_ascii_case(&name)
+                            .then_some(self.config.connections.len())
+                    });
+                match index {
+                    Some(index) => Action::ConnectByIndex(index),
+                    None => {
+                        self.push_error(format!("No profile named '{}'", name));
+                        return;
+                    }
+                }
+            }
+            ExCommand::Workspace(name) => Action::OpenWorkspace(name),
+            ExCommand::Goto(dn) => {
+                self.goto_dn_in_any_tab(dn);
+                return;
+            }
+            ExCommand::Filter(filter) => Action::SearchExecute(filter),
+            ExCommand::SaveSearch { name, filter } => {
+                let filter = filter.unwrap_or_else(|| self.search_dialog.filter.clone());
+                if filter.is_empty() {
+                    self.push_error("No search to save -- run one or give a filter".to_string());
+                    return;
+                }
+                Action::SaveSearch { name, filter }
+            }
+            ExCommand::Report { report, days } => Action::RunAccountReport { report, days },
+            ExCommand::DefinedReport { name, values } => Action::RunDefinedReport { name, values },
+            ExCommand::Duplicates { attribute, base_dn } => {
+                Action::FindDuplicates { attribute, base_dn }
+            }
+            ExCommand::Export { path, filter } => {
+                let path = expand_home(&path).display().to_string();
+                self.export_active_tab(path, filter);
+                return;
+            }
+            ExCommand::TabNext => Action::NextTab,
+            ExCommand::TabPrev => Action::PrevTab,
+            ExCommand::TabClose => Action::CloseCurrentTab,
+            ExCommand::Split => Action::ToggleSplitView,
+            ExCommand::Schema => Action::ShowSchemaViewer,
+            ExCommand::ThemeReload => {
+                self.switch_theme(self.theme_name.clone(), self.theme_variant);
+                return;
+            }
+            ExCommand::Theme { name, variant } => {
+                self.switch_theme(name, variant.unwrap_or(self.theme_variant));
+                return;
+            }
+            ExCommand::Help => Action::ShowHelp,
+            ExCommand::Quit => Action::Quit,
+        };
+        let _ = self.action_tx.send(action);
+    }
+
+    /// Load a theme and restyle every component with it, keeping the
+    /// current one if the theme can't be loaded.
+    fn switch_theme(&mut self, name: String, variant: ThemeVariant) {
+        match Theme::load_variant(&name, variant) {
+            Ok(theme) => {
+                self.apply_theme(&theme);
+                self.push_message(format!("Theme: {}", name));
+                self.theme_name = name;
+                self.theme_variant = variant;
+            }
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    fn apply_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.layout_bar.set_theme(theme);
+        self.tab_bar.set_theme(theme);
+        self.tree_panel.set_theme(theme);
+        self.detail_panel.set_theme(theme);
+        self.command_panel.set_theme(theme);
+        self.status_bar.set_theme(theme);
+        self.connections_tree.set_theme(theme);
+        self.connection_form.set_theme(theme);
+        self.context_menu.set_theme(theme);
+        self.confirm_dialog.set_theme(theme);
+        self.cert_trust_dialog.set_theme(theme);
+        self.connect_dialog.set_theme(theme);
+        self.new_connection_dialog.set_theme(theme);
+        self.credential_prompt.set_theme(theme);
+        self.vault_password_dialog.set_theme(theme);
+        self.search_dialog.set_theme(theme);
+        self.attribute_editor.set_theme(theme);
+        self.attribute_picker.set_theme(theme);
+        self.export_dialog.set_theme(theme);
+        self.bulk_update_dialog.set_theme(theme);
+        self.membership_import_dialog.set_theme(theme);
+        self.import_dialog.set_theme(theme);
+        self.conflict_dialog.set_theme(theme);
+        self.move_profile_dialog.set_theme(theme);
+        self.recent_popup.set_theme(theme);
+        self.history_popup.set_theme(theme);
+        self.favorites_popup.set_theme(theme);
+        self.workspace_popup.set_theme(theme);
+        self.command_palette.set_theme(theme);
+        self.command_line.set_theme(theme);
+        self.goto_dn_dialog.set_theme(theme);
+        self.batch_edit_dialog.set_theme(theme);
+        self.quick_open.set_theme(theme);
+        self.rename_user_dialog.set_theme(theme);
+        self.report_dialog.set_theme(theme);
+        self.create_entry_dialog.set_theme(theme);
+        self.schema_viewer.set_theme(theme);
+        self.help_popup.set_theme(theme);
+        self.about_popup.set_theme(theme);
+        self.log_panel.set_theme(theme);
+        self.jobs_panel.set_theme(theme);
+        self.trace_panel.set_theme(theme);
+        self.changes_panel.set_theme(theme);
+        self.metrics_panel.set_theme(theme);
+        self.replication_panel.set_theme(theme);
+        self.password_policy_popup.set_theme(theme);
+        self.account_reports.set_theme(theme);
+        self.membership_popup.set_theme(theme);
+        self.orphans_panel.set_theme(theme);
+        self.duplicates_panel.set_theme(theme);
+        self.attribute_stats_panel.set_theme(theme);
+        self.profile_export_dialog.set_theme(theme);
+        self.profile_import_dialog.set_theme(theme);
+        self.lock_screen.set_theme(theme);
+        if let Some(ref mut pane) = self.split {
+            pane.tree_panel.set_theme(theme);
+            pane.detail_panel.set_theme(theme);
+        }
+    }
+
+    /// Show an entry, switching to the tab whose base DN contains it.
+    fn goto_dn_in_any_tab(&mut self, dn: String) {
+        // Prefer the active tab, then any tab whose tree holds the DN
+        let holds = |tab: &ConnectionTab| {
+            let base = &tab.directory_tree.root_dn;
+            dn.eq_ignore_ascii_case(base) || loom_core::dn::is_ancestor(&dn, base)
+        };
+        let tab_id = self
+            .active_tab()
+            .filter(|tab| holds(tab))
+            .or_else(|| self.tabs.iter().find(|tab| holds(tab)))
+            .map(|tab| tab.id);
+        match tab_id {
+            Some(id) => {
+                if self.active_tab_id != Some(id) || self.active_layout != ActiveLayout::Browser {
+                    let _ = self.action_tx.send(Action::SwitchTab(id));
+                }
+                let _ = self.action_tx.send(Action::RevealEntry(dn));
+            }
+            None => self.push_error(format!("No open connection contains {}", dn)),
+        }
+    }
+
+    /// Export the active tab's subtree, optionally filtered, to `path`.
+    fn export_active_tab(&mut self, path: String, filter: Option<String>) {
+        let Some(base_dn) = self.active_tab().map(|t| t.directory_tree.root_dn.clone()) else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let _ = self.action_tx.send(Action::ExportExecute {
+            base_dn,
+            path,
+            filter: filter.unwrap_or_else(|| "(objectClass=*)".to_string()),
+            attributes: vec!["*".to_string()],
+        });
+    }
+
+    /// Write the stats file when one is configured and the interval has passed.
+    fn write_stats_if_due(&mut self) {
+        let Some(path) = self.config.general.stats_file.clone() else {
+            return;
+        };
+        let interval = Duration::from_secs(self.config.general.stats_interval_secs.max(1));
+        if self
+            .stats_written_at
+            .is_some_and(|at| at.elapsed() < interval)
+        {
+            return;
+        }
+        self.stats_written_at = Some(Instant::now());
+        if let Err(e) = self.session_stats().write_to(Path::new(&path)) {
+            // Log file only: a monitoring aid shouldn't interrupt the session
+            warn!("Failed to write stats file {}: {}", path, e);
+        }
+    }
+
+    fn session_stats(&self) -> SessionStats {
+        SessionStats {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            pid: std::process::id(),
+            uptime_secs: self.started_at.elapsed().as_secs(),
+            idle_secs: self.last_activity.elapsed().as_secs(),
+            open_connections: self.tabs.len(),
+            queued_actions: self.action_rx.len(),
+            actions_processed: self.actions_processed,
+            errors: self.error_count,
+            recent_entries: self.recent_entries.values().map(Vec::len).sum(),
+            locked: self.lock_screen.visible,
+            connections: self
+                .tabs
+                .iter()
+                .map(|tab| ConnectionStats {
+                    label: tab.label.clone(),
+                    host: tab.host.clone(),
+                    server_type: tab.server_type.clone(),
+                    offline: matches!(tab.backend, TabBackend::Offline(_)),
+                    read_only: tab.read_only,
+                    dry_run: tab.dry_run,
+                    tree_nodes: tab.directory_tree.root.loaded_descendants(),
+                    schema_attribute_types: tab
+                        .schema
+                        .as_ref()
+                        .map_or(0, |s| s.attribute_types.len()),
+                })
+                .collect(),
+        }
+    }
+
+    /// Drop the unlocked vault, wipe each tab's bind password and cover
+    /// the UI with the lock screen; open popups stay behind it. With
+    /// `idle_lock_disconnect`, live connections are also unbound.
+    fn lock_session(&mut self) {
+        let Some(vault) = self.vault.take() else {
+            return;
+        };
+        // Dropping the vault zeroizes the master key and stored passwords
+        self.locked_vault_path = Some(vault.path().to_path_buf());
+        drop(vault);
+
+        self.vault_password_dialog.hide();
+        self.lock_screen.show();
+
+        let disconnect = self.config.general.idle_lock_disconnect;
+        for tab in &self.tabs {
+            if let TabBackend::Live(ref pool) = tab.backend {
+                let pool = pool.clone();
+                let label = tab.label.clone();
+                tokio::spawn(async move {
+                    // Readers hold the password too, so close them
+                    pool.suspend().await;
+                    let connection = pool.main();
+                    let mut conn = connection.lock().await;
+                    conn.clear_password();
+                    if disconnect {
+                        if let Err(e) = conn.disconnect().await {
+                            debug!("Unbind of '{}' on idle lock failed: {}", label, e);
+                        }
+                    }
+                });
+            }
+        }
+
+        info!("Session locked after inactivity");
+        self.log_panel
+            .push_info("Session locked after inactivity".to_string());
+    }
+
+    /// Re-open the vault with the master password entered on the lock screen.
+    fn unlock_session(&mut self, password: &str) {
+        let Some(path) = self.locked_vault_path.clone() else {
+            self.lock_screen.hide();
+            return;
+        };
+        match Vault::open(&path, password) {
+            Ok(vault) => {
+                self.vault = Some(vault);
+                self.locked_vault_path = None;
+                self.lock_screen.hide();
+                self.last_activity = Instant::now();
+                self.spawn_restore_credentials();
+                self.push_message("Session unlocked".to_string());
+                for cmd in std::mem::take(&mut self.locked_remote_commands) {
+                    self.run_remote_command(cmd);
+                }
+            }
+            Err(e) => {
+                self.log_panel.push_error(format!("Unlock failed: {}", e));
+                self.lock_screen.set_error(e.to_string());
+            }
+        }
+    }
+
+    /// Give every live connection its bind password back after an unlock,
+    /// from the profile's credential method or else a prompt, rebinding it
+    /// (reconnecting when the lock unbound it) and letting its pool open
+    /// extra readers again.
+    fn spawn_restore_credentials(&self) {
+        let disconnected = self.config.general.idle_lock_disconnect;
+        for tab in &self.tabs {
+            if let TabBackend::Live(ref pool) = tab.backend {
+                let password = self
+                    .config
+                    .connections
+                    .iter()
+                    .find(|p| p.name == tab.label)
+                    .and_then(|p| resolve_password(p, &self.vault).ok())
+                    .filter(|password| !password.is_empty());
+                let pool = pool.clone();
+                let conn_id = tab.id;
+                let label = tab.label.clone();
+                let tx = self.action_tx.clone();
+                tokio::spawn(async move {
+                    let connection = pool.main();
+                    let mut conn = connection.lock().await;
+                    let result = match (conn.cleared_bind_dn().map(str::to_string), password) {
+                        // Nothing was wiped: an anonymous or SASL bind
+                        (None, _) if disconnected => conn.reconnect().await,
+                        (None, _) => Ok(()),
+                        (Some(bind_dn), Some(password)) => {
+                            rebind(&mut conn, bind_dn, password, disconnected).await
+                        }
+                        (Some(bind_dn), None) => {
+                            let _ = tx.send(Action::RebindNeeded(conn_id, bind_dn));
+                            return;
+                        }
+                    };
+                    match result {
+                        Ok(()) => {
+                            pool.resume(&conn);
+                            report_server(&tx, conn_id, &conn);
+                        }
+                        Err(e) => {
+                            let _ = tx.send(Action::ErrorMessage(format!(
+                                "Reconnect to '{}' failed: {}",
+                                label, e
+                            )));
+                        }
+                    }
+                });
+            }
+        }
+    }
+
+    /// Rebind a tab with the password typed into the unlock prompt, asking
+    /// again when the server refuses it.
+    fn spawn_rebind(&self, conn_id: ConnectionId, bind_dn: String, password: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let TabBackend::Live(ref pool) = tab.backend else {
+            return;
+        };
+        let pool = pool.clone();
+        let label = tab.label.clone();
+        let disconnected = self.config.general.idle_lock_disconnect;
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            let connection = pool.main();
+            let mut conn = connection.lock().await;
+            match rebind(&mut conn, bind_dn.clone(), password, disconnected).await {
+                Ok(()) => {
+                    pool.resume(&conn);
+                    report_server(&tx, conn_id, &conn);
+                    let _ = tx.send(Action::StatusMessage(format!(
+                        "Rebound '{}' as {}",
+                        label, bind_dn
+                    )));
+                }
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!(
+                        "Rebind of '{}' failed: {}",
+                        label, e
+                    )));
+                    let _ = tx.send(Action::RebindNeeded(conn_id, bind_dn));
+                }
+            }
+        });
+    }
+
+    /// Queue an unlock password prompt for a tab, as a profile showing its
+    /// host and the bind DN whose password was wiped.
+    fn queue_rebind_prompt(&mut self, conn_id: ConnectionId, bind_dn: String) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let mut profile = self
+            .config
+            .connections
+            .iter()
+            .find(|p| p.name == tab.label)
+            .cloned()
+            .unwrap_or_else(|| ConnectionProfile {
+                name: tab.label.clone(),
+                host: tab.host.clone(),
+                ..ConnectionProfile::default()
+            });
+        profile.bind_dn = Some(bind_dn);
+        self.pending_rebinds.push_back((conn_id, profile));
+    }
+
+    /// Check if any popup/dialog is currently visible.
+    fn popup_active(&self) -> bool {
+        self.context_menu.visible
+            || self.confirm_dialog.visible
+            || self.cert_trust_dialog.visible
+            || self.connect_dialog.visible
+            || self.new_connection_dialog.visible
+            || self.credential_prompt.visible
+            || self.search_dialog.visible
+            || self.attribute_editor.visible
+            || self.attribute_picker.visible
+            || self.export_dialog.visible
+            || self.bulk_update_dialog.visible
+            || self.membership_import_dialog.visible
+            || self.import_dialog.visible
+            || self.conflict_dialog.visible
+            || self.move_profile_dialog.visible
+            || self.recent_popup.visible
+            || self.history_popup.visible
+            || self.favorites_popup.visible
+            || self.workspace_popup.visible
+            || self.command_palette.visible
+            || self.command_line.visible
+            || self.goto_dn_dialog.visible
+            || self.batch_edit_dialog.visible
+            || self.quick_open.visible
+            || self.rename_user_dialog.visible
+            || self.report_dialog.visible
+            || self.create_entry_dialog.visible
+            || self.schema_viewer.visible
+            || self.help_popup.visible
+            || self.about_popup.visible
+            || self.log_panel.visible
+            || self.jobs_panel.visible
+            || self.trace_panel.visible
+            || self.changes_panel.visible
+            || self.metrics_panel.visible
+            || self.replication_panel.visible
+            || self.password_policy_popup.visible
+            || self.account_reports.visible
+            || self.membership_popup.visible
+            || self.orphans_panel.visible
+            || self.duplicates_panel.visible
+            || self.attribute_stats_panel.visible
+            || self.profile_export_dialog.visible
+            || self.profile_import_dialog.visible
+            || self.lock_screen.visible
+    }
+
+    /// Check if any popup, dialog, or text-input mode is active.
+    fn any_popup_or_input_active(&self) -> bool {
+        self.context_menu.visible
+            || self.attribute_editor.visible
+            || self.attribute_picker.visible
+            || self.confirm_dialog.visible
+            || self.cert_trust_dialog.visible
+            || self.connect_dialog.visible
+            || self.new_connection_dialog.visible
+            || self.credential_prompt.visible
+            || self.search_dialog.visible
+            || self.export_dialog.visible
+            || self.bulk_update_dialog.visible
+            || self.membership_import_dialog.visible
+            || self.import_dialog.visible
+            || self.conflict_dialog.visible
+            || self.move_profile_dialog.visible
+            || self.recent_popup.visible
+            || self.history_popup.visible
+            || self.favorites_popup.visible
+            || self.workspace_popup.visible
+            || self.command_palette.visible
+            || self.command_line.visible
+            || self.goto_dn_dialog.visible
+            || self.batch_edit_dialog.visible
+            || self.quick_open.visible
+            || self.rename_user_dialog.visible
+            || self.report_dialog.visible
+            || self.create_entry_dialog.visible
+            || self.schema_viewer.visible
+            || self.help_popup.visible
+            || self.about_popup.visible
+            || self.log_panel.visible
+            || self.jobs_panel.visible
+            || self.trace_panel.visible
+            || self.changes_panel.visible
+            || self.metrics_panel.visible
+            || self.replication_panel.visible
+            || self.password_policy_popup.visible
+            || self.account_reports.visible
+            || self.membership_popup.visible
+            || self.orphans_panel.visible
+            || self.duplicates_panel.visible
+            || self.attribute_stats_panel.visible
+            || self.profile_export_dialog.visible
+            || self.profile_import_dialog.visible
+            || self.lock_screen.visible
+            || self.command_panel.input_active
+            || self.detail_panel.is_filtering()
+            || self.tree_panel.is_filtering()
+            || (self.connection_form.is_editing()
+                && self.active_layout == ActiveLayout::Profiles
+                && self.focus.current() == FocusTarget::ConnectionForm)
+    }
+
+    /// Dismiss every open popup/dialog.
+    fn dismiss_all_popups(&mut self) {
+        self.context_menu.hide();
+        self.confirm_dialog.hide();
+        self.cert_trust_dialog.hide();
+        self.connect_dialog.hide();
+        self.new_connection_dialog.hide();
+        self.credential_prompt.hide();
+        self.search_dialog.hide();
+        self.command_panel.soft_deactivate();
+        self.attribute_editor.hide();
+        self.attribute_picker.hide();
+        self.export_dialog.hide();
+        self.bulk_update_dialog.hide();
+        self.membership_import_dialog.hide();
+        self.import_dialog.hide();
+        self.conflict_dialog.hide();
+        self.move_profile_dialog.hide();
+        self.recent_popup.hide();
+        self.history_popup.hide();
+        self.favorites_popup.hide();
+        self.workspace_popup.hide();
+        self.command_palette.hide();
+        self.command_line.hide();
+        self.goto_dn_dialog.hide();
+        self.batch_edit_dialog.hide();
+        self.quick_open.hide();
+        self.rename_user_dialog.hide();
+        self.report_dialog.hide();
+        self.create_entry_dialog.hide();
+        self.schema_viewer.hide();
+        self.help_popup.hide();
+        self.about_popup.hide();
+        self.log_panel.hide();
+        self.jobs_panel.hide();
+        self.trace_panel.hide();
+        self.changes_panel.hide();
+        self.metrics_panel.hide();
+        self.replication_panel.hide();
+        self.password_policy_popup.hide();
+        self.account_reports.hide();
+        self.membership_popup.hide();
+        self.orphans_panel.hide();
+        self.duplicates_panel.hide();
+        self.attribute_stats_panel.hide();
+        self.profile_export_dialog.hide();
+        self.profile_import_dialog.hide();
+    }
+
+    /// Save the value returned from the external editor.
+    fn finish_external_edit(&mut self, edit: ExternalEdit, result: Result<Option<String>, String>) {
+        match result {
+            Ok(Some(new_value)) => {
+                // An invalid value goes back into the inline editor with the
+                // error shown, rather than to the server
+                let existing = self.detail_values(&edit.dn, &edit.attr);
+                self.attribute_editor.edit_value_from(
+                    edit.dn.clone(),
+                    edit.attr.clone(),
+                    edit.value.clone(),
+                    new_value.clone(),
+                );
+                self.apply_value_rules(&edit.attr, existing, Some(&edit.value));
+                if !self.attribute_editor.validate() {
+                    return;
+                }
+                self.attribute_editor.hide();
+                if let Some(id) = self.active_tab_id {
+                    let result = EditResult {
+                        dn: edit.dn,
+                        op: EditOp::Replace {
+                            attr: edit.attr,
+                            old_value: edit.value,
+                        },
+                        new_value,
+                    };
+                    self.spawn_save_attribute(id, result);
+                }
+            }
+            Ok(None) => {
+                self.status_bar
+                    .set_message(format!("{} unchanged", edit.attr));
+            }
+            Err(e) => self.push_error(e),
+        }
+    }
+
+    /// Main event loop.
+    pub async fn run(&mut self) -> anyhow::Result<()> {
+        tui::install_panic_hook();
+        let mut terminal = tui::init()?;
+        self.start_remote_control();
+        self.start_exporter();
+
+        let tick_rate = Duration::from_millis(self.config.general.tick_rate_ms);
+
+        loop {
+            // Render
+            terminal.draw(|frame| self.render(frame))?;
+
+            // Poll for events
+            if let Some(app_event) = event::poll_event(tick_rate) {
+                match app_event {
+                    AppEvent::Key(key) => self.handle_key(key),
+                    AppEvent::Paste(text) => {
+                        self.last_activity = Instant::now();
+                        self.handle_paste(text);
+                    }
+                    AppEvent::Mouse(mouse) => {
+                        self.last_activity = Instant::now();
+                        let action = self.handle_mouse(mouse);
+                        if !matches!(action, Action::None) {
+                            let _ = self.action_tx.send(action);
+                        }
+                    }
+                    AppEvent::Resize(w, h) => {
+                        let _ = self.action_tx.send(Action::Resize(w, h));
+                    }
+                    AppEvent::Tick => {
+                        let _ = self.action_tx.send(Action::Tick);
+                    }
+                }
+            }
+
+            // Drain action queue
+            while let Ok(action) = self.action_rx.try_recv() {
+                self.process_action(action).await;
+            }
+
+            if !self.credential_prompt.visible && !self.lock_screen.visible {
+                if let Some((conn_id, profile)) = self.pending_rebinds.pop_front() {
+                    self.credential_prompt.show_rebind(conn_id, profile);
+                }
+            }
+
+            if let Some(edit) = self.pending_external_edit.take() {
+                // Hand the terminal to the editor, then take it back
+                tui::restore()?;
+                let result = external_editor::edit_value(&edit.value);
+                terminal = tui::init()?;
+                terminal.clear()?;
+                self.finish_external_edit(edit, result);
+            }
+
+            if self.should_quit {
+                break;
+            }
+        }
+
+        tui::restore()?;
+        Ok(())
+    }
+
+    /// Route a key press to the topmost popup, input or panel.
+    fn handle_key(&mut self, key: KeyEvent) {
+        self.last_activity = Instant::now();
+
+        // Clear pending 'g' if a popup/input became active
+        if self.pending_g && self.any_popup_or_input_active() {
+            self.pending_g = false;
+        }
+
+        // `[keybindings.dialogs]` apply above whichever popup is open
+        let dialog_binding = if self.popup_active() && !self.search_dialog.visible {
+            self.keymap.resolve_context(KeyContext::Dialogs, &key, None)
+        } else {
+            None
+        };
+
+        // Search binding activates search from any non-input context,
+        // but yields to dialogs/popups/editors that capture keystrokes.
+        let action = if self.lock_screen.visible {
+            // Lock screen swallows every key until unlocked
+            self.lock_screen.handle_key_event(key)
+        } else if self.pending_g {
+            // Resolve vim-style gt/gT tab switching
+            self.pending_g = false;
+            match key.code {
+                KeyCode::Char('t') => Action::NextTab,
+                KeyCode::Char('T') => Action::PrevTab,
+                _ => Action::None,
+            }
+        } else if let Some(action) = dialog_binding {
+            action
+        } else if !self.any_popup_or_input_active()
+            && matches!(
+                self.keymap.resolve_global_only(&key),
+                Action::SearchFocusInput
+            )
+        {
+            self.dismiss_all_popups();
+            if self.active_layout != ActiveLayout::Browser {
+                let _ = self
+                    .action_tx
+                    .send(Action::SwitchLayout(ActiveLayout::Browser));
+            }
+            Action::SearchFocusInput
+        // Popups intercept keys first
+        } else if self.context_menu.visible {
+            self.context_menu.handle_key_event(key)
+        } else if self.attribute_editor.visible {
+            self.attribute_editor.handle_key_event(key)
+        } else if self.attribute_picker.visible {
+            self.attribute_picker.handle_key_event(key)
+        } else if self.report_dialog.visible {
+            // Above search results, which it can be opened from
+            self.report_dialog.handle_key_event(key)
+        } else if self.confirm_dialog.visible {
+            self.confirm_dialog.handle_key_event(key)
+        } else if self.cert_trust_dialog.visible {
+            self.cert_trust_dialog.handle_key_event(key)
+        } else if self.connect_dialog.visible {
+            self.connect_dialog.handle_key_event(key)
+        } else if self.new_connection_dialog.visible {
+            self.new_connection_dialog.handle_key_event(key)
+        } else if self.credential_prompt.visible {
+            self.credential_prompt.handle_key_event(key)
+        } else if self.vault_password_dialog.visible {
+            self.vault_password_dialog.handle_key_event(key)
+        } else if self.search_dialog.visible {
+            // Search popup is open — route keys based on input state
+            if matches!(
+                self.keymap.resolve_global_only(&key),
+                Action::SearchFocusInput
+            ) {
+                // F9 toggles popup closed
+                self.search_dialog.hide();
+                self.command_panel.soft_deactivate();
+                Action::None
+            } else if self.command_panel.input_active {
+                // Input is active — route to command panel
+                self.command_panel.handle_input_key(key)
+            } else if self.search_dialog.refining() {
+                self.search_dialog.handle_key_event(key)
+            } else if let Some(action) = self.keymap.resolve_context(
+                KeyContext::SearchResults,
+                &key,
+                self.search_dialog
+                    .selected_dns()
+                    .first()
+                    .map(String::as_str),
+            ) {
+                action
+            } else {
+                // Input not active — navigate results or edit filter
+                match key.code {
+                    KeyCode::Char('/') => {
+                        // Reactivate input editing
+                        self.command_panel.resume_input();
+                        Action::None
+                    }
+                    KeyCode::Up
+                    | KeyCode::Down
+                    | KeyCode::Enter
+                    | KeyCode::Esc
+                    | KeyCode::Char('j')
+                    | KeyCode::Char('k')
+                    | KeyCode::Char('q')
+                    | KeyCode::Char(' ')
+                    | KeyCode::Char('e')
+                    | KeyCode::Char('r')
+                    | KeyCode::Char('s')
+                    | KeyCode::Char('S')
+                    | KeyCode::Char('g')
+                    | KeyCode::Char('t')
+                    | KeyCode::Char('f')
+                    | KeyCode::Char('x')
+                    | KeyCode::Char('b')
+                    | KeyCode::Char('c')
+                    | KeyCode::Char('m') => {
+                        let a = self.search_dialog.handle_key_event(key);
+                        if matches!(&a, Action::TreeSelect(_)) {
+                            self.command_panel.soft_deactivate();
+                            let _ = self
+                                .action_tx
+                                .send(Action::FocusPanel(FocusTarget::DetailPanel));
+                        }
+                        a
+                    }
+                    KeyCode::Char(c) if !c.is_control() => {
+                        // Start editing with this character
+                        self.command_panel.resume_input();
+                        self.command_panel.handle_input_key(key)
+                    }
+                    _ => Action::None,
+                }
+            }
+        } else if self.export_dialog.visible {
+            self.export_dialog.handle_key_event(key)
+        } else if self.bulk_update_dialog.visible {
+            self.bulk_update_dialog.handle_key_event(key)
+        } else if self.membership_import_dialog.visible {
+            self.membership_import_dialog.handle_key_event(key)
+        } else if self.import_dialog.visible {
+            self.import_dialog.handle_key_event(key)
+        } else if self.conflict_dialog.visible {
+            self.conflict_dialog.handle_key_event(key)
+        } else if self.move_profile_dialog.visible {
+            self.move_profile_dialog.handle_key_event(key)
+        } else if self.recent_popup.visible {
+            self.recent_popup.handle_key_event(key)
+        } else if self.history_popup.visible {
+            self.history_popup.handle_key_event(key)
+        } else if self.favorites_popup.visible {
+            self.favorites_popup.handle_key_event(key)
+        } else if self.workspace_popup.visible {
+            self.workspace_popup.handle_key_event(key)
+        } else if self.command_palette.visible {
+            self.command_palette.handle_key_event(key)
+        } else if self.command_line.visible {
+            self.command_line.handle_key_event(key)
+        } else if self.goto_dn_dialog.visible {
+            self.goto_dn_dialog.handle_key_event(key)
+        } else if self.quick_open.visible {
+            self.quick_open.handle_key_event(key)
+        } else if self.batch_edit_dialog.visible {
+            self.batch_edit_dialog.handle_key_event(key)
+        } else if self.rename_user_dialog.visible {
+            self.rename_user_dialog.handle_key_event(key)
+        } else if self.profile_export_dialog.visible {
+            self.profile_export_dialog
+                .handle_key_event(key, &self.config.connections)
+        } else if self.profile_import_dialog.visible {
+            self.profile_import_dialog.handle_key_event(key)
+        } else if self.create_entry_dialog.visible {
+            self.create_entry_dialog.handle_key_event(key)
+        } else if self.schema_viewer.visible {
+            self.schema_viewer.handle_key_event(key)
+        } else if self.help_popup.visible {
+            let a = self.help_popup.handle_key_event(key);
+            if matches!(a, Action::ClosePopup) && self.show_connect_after_help {
+                self.show_connect_after_help = false;
+                // Ask about vault setup, then show connect dialog
+                let _ = self.action_tx.send(Action::ShowConfirm(
+                    "Use an encrypted vault for password storage?".to_string(),
+                    Box::new(Action::VaultSetupPrompt),
+                ));
+                let _ = self.action_tx.send(Action::ShowConnectDialog);
+            }
+            a
+        } else if self.about_popup.visible {
+            self.about_popup.handle_key_event(key)
+        } else if self.log_panel.visible {
+            self.log_panel.handle_key_event(key)
+        } else if self.jobs_panel.visible {
+            self.jobs_panel.handle_key_event(key, &self.jobs)
+        } else if self.trace_panel.visible {
+            let trace = self.active_trace();
+            self.trace_panel.handle_key_event(key, trace.as_ref())
+        } else if self.changes_panel.visible {
+            let changes = self.active_changes();
+            self.changes_panel.handle_key_event(key, changes.as_ref())
+        } else if self.metrics_panel.visible {
+            self.metrics_panel.handle_key_event(key)
+        } else if self.replication_panel.visible {
+            self.replication_panel.handle_key_event(key)
+        } else if self.password_policy_popup.visible {
+            self.password_policy_popup.handle_key_event(key)
+        } else if self.account_reports.visible {
+            self.account_reports.handle_key_event(key)
+        } else if self.membership_popup.visible {
+            self.membership_popup.handle_key_event(key)
+        } else if self.orphans_panel.visible {
+            self.orphans_panel.handle_key_event(key)
+        } else if self.duplicates_panel.visible {
+            self.duplicates_panel.handle_key_event(key)
+        } else if self.attribute_stats_panel.visible {
+            self.attribute_stats_panel.handle_key_event(key)
+        } else if self.command_panel.input_active && self.active_layout == ActiveLayout::Browser {
+            self.command_panel.handle_input_key(key)
+        } else if self.detail_panel.is_filtering()
+            && self.active_layout == ActiveLayout::Browser
+            && self.focus.current() == FocusTarget::DetailPanel
+        {
+            // Typing an attribute filter captures all keys
+            self.detail_panel.handle_key_event(key)
+        } else if self.tree_panel.is_filtering()
+            && self.active_layout == ActiveLayout::Browser
+            && self.focus.current() == FocusTarget::TreePanel
+        {
+            // So does typing a quick find in the tree
+            self.tree_panel.handle_key_event(key)
+        } else if self.connection_form.is_editing()
+            && self.active_layout == ActiveLayout::Profiles
+            && self.focus.current() == FocusTarget::ConnectionForm
+        {
+            // Connection form in edit/create mode captures all keys
+            self.connection_form.handle_key_event(key)
+        } else if self.active_layout == ActiveLayout::Profiles {
+            // Connections layout: route to connections panels first
+            let panel_action = match self.focus.current() {
+                FocusTarget::ConnectionsTree => self.connections_tree.handle_key_event(key),
+                FocusTarget::ConnectionForm => self.connection_form.handle_key_event(key),
+                _ => Action::None,
+            };
+            if matches!(panel_action, Action::None) {
+                self.keymap.resolve(key, self.focus.current())
+            } else {
+                panel_action
+            }
+        } else {
+            // Browser layout: intercept '/' to open search popup
+            if matches!(key.code, KeyCode::Char('/')) && !self.any_popup_or_input_active() {
+                Action::SearchFocusInput
+            } else if matches!(key.code, KeyCode::Char(':')) && !self.any_popup_or_input_active() {
+                Action::ShowCommandLine
+            } else if let Some(action) = self.context_binding(&key) {
+                action
+            } else {
+                // Try panel-specific handler first, fall back to global keymap
+                let panel_action = match self.focus.current() {
+                    FocusTarget::TreePanel => self.tree_panel.handle_key_event(key),
+                    FocusTarget::DetailPanel => self.detail_panel.handle_key_event(key),
+                    _ => Action::None,
+                };
+                if matches!(panel_action, Action::None) {
+                    self.keymap.resolve(key, self.focus.current())
+                } else {
+                    panel_action
+                }
+            }
+        };
+        // Initiate vim 'g' prefix when g produces no action
+        if matches!(action, Action::None)
+            && matches!(key.code, KeyCode::Char('g'))
+            && key.modifiers == KeyModifiers::NONE
+            && !self.any_popup_or_input_active()
+        {
+            self.pending_g = true;
+        } else {
+            let _ = self.action_tx.send(action);
+        }
+    }
+
+    /// The `[keybindings.tree]` or `[keybindings.detail]` action for a key
+    /// in the focused browser panel, applied to its entry.
+    fn context_binding(&self, key: &KeyEvent) -> Option<Action> {
+        match self.focus.current() {
+            FocusTarget::TreePanel => self.keymap.resolve_context(
+                KeyContext::Tree,
+                key,
+                self.tree_panel.selected_dn().map(String::as_str),
+            ),
+            FocusTarget::DetailPanel => self.keymap.resolve_context(
+                KeyContext::Detail,
+                key,
+                self.detail_panel.entry.as_ref().map(|e| e.dn.as_str()),
+            ),
+            _ => None,
+        }
+    }
+
+    /// Route pasted text: a filter replaces the search input, LDIF pre-fills the
+    /// create-entry dialog and a DN pasted on the tree jumps to it. Pastes
+    /// anywhere else are dropped rather than replayed as key presses, which
+    /// could trigger shortcuts such as a confirmation's `y`.
+    fn handle_paste(&mut self, text: String) {
+        if self.lock_screen.visible {
+            return;
+        }
+        if self.goto_dn_dialog.visible {
+            self.goto_dn_dialog.paste(&text);
+        } else if self.quick_open.visible {
+            self.quick_open.paste(&text);
+        } else if self.attribute_editor.visible {
+            self.attribute_editor.paste(&text);
+        } else if self.create_entry_dialog.visible {
+            let action = self.create_entry_dialog.paste(&text);
+            let _ = self.action_tx.send(action);
+        } else if self.command_panel.input_active
+            && self.active_layout == ActiveLayout::Browser
+            && (self.search_dialog.visible || !self.popup_active())
+        {
+            self.command_panel.paste_filter(&text);
+        } else if self.any_popup_or_input_active() {
+            debug!("Paste dropped: the open popup doesn't take pasted text");
+        } else if self.active_layout == ActiveLayout::Browser
+            && self.focus.current() == FocusTarget::TreePanel
+        {
+            let dn = text.trim();
+            let dn = match dn.get(..3) {
+                Some(prefix) if prefix.eq_ignore_ascii_case("dn:") => dn[3..].trim(),
+                _ => dn,
+            };
+            if dn.contains('=') && !dn.contains('\n') {
+                let _ = self.action_tx.send(Action::RevealEntry(dn.to_string()));
+            }
+        }
+    }
+
+    fn handle_mouse(&mut self, mouse: crossterm::event::MouseEvent) -> Action {
+        match mouse.kind {
+            MouseEventKind::ScrollUp => return self.handle_scroll(mouse, -1),
+            MouseEventKind::ScrollDown => return self.handle_scroll(mouse, 1),
+            _ => {}
+        }
+
+        // Popups block mouse events; also clear any drag
+        if self.popup_active() {
+            self.drag_target = None;
+            return Action::None;
+        }
+
+        match mouse.kind {
+            MouseEventKind::Down(crossterm::event::MouseButton::Left) => {
+                // Check if click is on a panel divider (start drag)
+                if let Some(target) = self.divider_hit(mouse.column, mouse.row) {
+                    self.drag_target = Some(target);
+                    return Action::None;
+                }
+
+                let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                let double = self.clicks.click(mouse.column, mouse.row);
+
+                // Check layout bar clicks (unified tab strip)
+                if let Some(bar) = self.layout_bar_area {
+                    if bar.intersects(pos) {
+                        if let Some(target) = self.layout_bar.hit(mouse.column) {
+                            return match target {
+                                LayoutBarHit::Profiles => {
+                                    Action::SwitchLayout(ActiveLayout::Profiles)
+                                }
+                                LayoutBarHit::Close(id) => Action::CloseTab(id),
+                                // A double-click also moves focus into the tab
+                                LayoutBarHit::Tab(id) if double => {
+                                    let _ = self.action_tx.send(Action::SwitchTab(id));
+                                    Action::FocusPanel(FocusTarget::TreePanel)
+                                }
+                                LayoutBarHit::Tab(id) => Action::SwitchTab(id),
+                            };
+                        }
+                    }
+                }
+
+                // Check connections layout panels
+                if self.active_layout == ActiveLayout::Profiles {
+                    if let Some(ct) = self.conn_tree_area {
+                        if ct.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::ConnectionsTree);
+                        }
+                    }
+                    if let Some(cf) = self.conn_form_area {
+                        if cf.intersects(pos) {
+                            return Action::FocusPanel(FocusTarget::ConnectionForm);
+                        }
+                    }
+                    return Action::None;
+                }
+
+                // Browser layout panels: a click selects the row under the
+                // pointer, a double-click opens it
+                if let Some(tree) = self.tree_area {
+                    if tree.intersects(pos) {
+                        let focus = Action::FocusPanel(FocusTarget::TreePanel);
+                        let Some(select) = self.tree_panel.click_row(mouse.row) else {
+                            return focus;
+                        };
+                        let _ = self.action_tx.send(focus);
+                        return if double {
+                            self.tree_panel.activate_selected()
+                        } else {
+                            select
+                        };
+                    }
+                }
+                if let Some(detail) = self.detail_area {
+                    if detail.intersects(pos) {
+                        let hit = self.detail_panel.click_row(detail, mouse.row);
+                        if hit && double {
+                            let _ = self
+                                .action_tx
+                                .send(Action::FocusPanel(FocusTarget::DetailPanel));
+                            return match self.detail_panel.activate_selected() {
+                                Action::None => Action::Render,
+                                action => action,
+                            };
+                        }
+                        return Action::FocusPanel(FocusTarget::DetailPanel);
+                    }
+                }
+                Action::None
+            }
+            // Middle-click closes a connection tab
+            MouseEventKind::Down(crossterm::event::MouseButton::Middle) => {
+                let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                if self.layout_bar_area.is_some_and(|bar| bar.intersects(pos)) {
+                    if let Some(LayoutBarHit::Tab(id) | LayoutBarHit::Close(id)) =
+                        self.layout_bar.hit(mouse.column)
+                    {
+                        return Action::CloseTab(id);
+                    }
+                }
+                Action::None
+            }
+            MouseEventKind::Drag(crossterm::event::MouseButton::Left) => {
+                if let Some(target) = self.drag_target {
+                    self.apply_drag(target, mouse.column, mouse.row);
+                }
+                Action::None
+            }
+            MouseEventKind::Down(crossterm::event::MouseButton::Right) => {
+                if self.active_layout == ActiveLayout::Profiles {
+                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                    if let Some(ct) = self.conn_tree_area {
+                        if ct.intersects(pos) {
+                            self.context_menu
+                                .show_for_profiles(self.connection_form.profile_index);
+                            self.context_menu.set_anchor(mouse.column, mouse.row);
+                            return Action::Render;
+                        }
+                    }
+                    if let Some(cf) = self.conn_form_area {
+                        if cf.intersects(pos) {
+                            self.context_menu
+                                .show_for_profiles(self.connection_form.profile_index);
+                            self.context_menu.set_anchor(mouse.column, mouse.row);
+                            return Action::Render;
+                        }
+                    }
+                }
+                if self.active_layout == ActiveLayout::Browser {
+                    let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+                    if let Some(tree) = self.tree_area {
+                        if tree.intersects(pos) {
+                            if let Some(dn) = self.tree_panel.selected_dn().cloned() {
+                                self.context_menu.show_for_tree(&dn);
+                                self.context_menu.set_anchor(mouse.column, mouse.row);
+                                return Action::Render;
+                            }
+                        }
+                    }
+                    if let Some(detail) = self.detail_area {
+                        if detail.intersects(pos) {
+                            if let (Some(entry), Some((attr, val))) = (
+                                &self.detail_panel.entry,
+                                self.detail_panel.selected_attr_value(),
+                            ) {
+                                let dn = entry.dn.clone();
+                                let attr = attr.to_string();
+                                let val = val.to_string();
+                                self.context_menu.show_for_detail(&dn, &attr, &val);
+                                self.context_menu.set_anchor(mouse.column, mouse.row);
+                                return Action::Render;
+                            }
+                        }
+                    }
+                }
+                Action::None
+            }
+            MouseEventKind::Up(_) => {
+                self.drag_target = None;
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Scroll the view of the panel under the mouse `direction` notches
+    /// (negative is up); its selection only moves if it would go off
+    /// screen. While a popup is open, only a popup that scrolls (schema
+    /// viewer, log, search results) takes the wheel.
+    fn handle_scroll(&mut self, mouse: crossterm::event::MouseEvent, direction: isize) -> Action {
+        #[derive(Clone, Copy)]
+        enum Target {
+            Schema,
+            Log,
+            Search,
+            Tree,
+            Detail,
+        }
+        let pos = Rect::new(mouse.column, mouse.row, 1, 1);
+        let under = |area: Option<Rect>| area.is_some_and(|a| a.intersects(pos));
+        let target = if self.lock_screen.visible {
+            None
+        } else if self.schema_viewer.visible {
+            Some(Target::Schema)
+        } else if self.log_panel.visible {
+            Some(Target::Log)
+        } else if self.search_dialog.visible {
+            Some(Target::Search)
+        } else if self.popup_active() || self.active_layout != ActiveLayout::Browser {
+            None
+        } else if under(self.tree_area) {
+            Some(Target::Tree)
+        } else if under(self.detail_area) {
+            Some(Target::Detail)
+        } else {
+            None
+        };
+        let Some(target) = target else {
+            return Action::None;
+        };
+
+        let delta = direction * self.config.general.scroll_lines.max(1) as isize;
+        match target {
+            Target::Schema => self.schema_viewer.scroll(delta),
+            Target::Log => self.log_panel.scroll(delta),
+            Target::Search => self.search_dialog.scroll(delta),
+            Target::Tree => return self.tree_panel.scroll(delta),
+            Target::Detail => {
+                if let Some(area) = self.detail_area {
+                    self.detail_panel.scroll(area, delta);
+                }
+            }
+        }
+        Action::Render
+    }
+
+    /// Check if a mouse position is on (or within 1 cell of) a panel divider.
+    fn divider_hit(&self, col: u16, row: u16) -> Option<DragTarget> {
+        match self.active_layout {
+            ActiveLayout::Browser => {
+                // Vertical divider: right edge of tree panel
+                if let Some(tree) = self.tree_area {
+                    let divider_col = tree.x + tree.width;
+                    if col.abs_diff(divider_col) <= 1 && row >= tree.y && row < tree.y + tree.height
+                    {
+                        return Some(DragTarget::Tree);
+                    }
+                }
+            }
+            ActiveLayout::Profiles => {
+                // Vertical divider: right edge of profiles tree
+                if let Some(ct) = self.conn_tree_area {
+                    let divider_col = ct.x + ct.width;
+                    if col.abs_diff(divider_col) <= 1 && row >= ct.y && row < ct.y + ct.height {
+                        return Some(DragTarget::Tree);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Update split percentages based on the current drag position.
+    fn apply_drag(&mut self, target: DragTarget, col: u16, _row: u16) {
+        // We need a reference area to compute the percentage from pixel position.
+        match target {
+            DragTarget::Tree => {
+                if let (Some(tree), Some(detail)) = (self.tree_area, self.detail_area) {
+                    let total_w = (tree.width + detail.width) as u32;
+                    if total_w == 0 {
+                        return;
+                    }
+                    let offset = col.saturating_sub(tree.x) as u32;
+                    let pct = ((offset * 100) / total_w) as u16;
+                    self.tree_split_pct = pct.clamp(10, 90);
+                }
+            }
+        }
+    }
+
+    async fn process_action(&mut self, action: Action) {
+        if !matches!(action, Action::Tick | Action::Render) {
+            self.actions_processed += 1;
+        }
+        let action = match action {
+            Action::PolicyCleared(action) => *action,
+            action => match self.enforce_safety(action) {
+                Some(action) => action,
+                None => return,
+            },
+        };
+        match action {
+            Action::Quit => {
+                self.save_session();
+                self.should_quit = true;
+            }
+            Action::RestoreSession => {
+                if let Some(session) = self.saved_session.take() {
+                    self.restore_session(session).await;
+                }
+            }
+            Action::FocusNext => {
+                self.focus.next();
+            }
+            Action::FocusPrev => {
+                self.focus.prev();
+            }
+            Action::FocusPanel(target) => {
+                self.focus.set(target);
+            }
+
+            // Tab management — cycle through Profiles → conn1 → conn2 → … → Profiles
+            Action::NextTab => {
+                let tab_count = self.tab_bar.tabs.len();
+                if tab_count > 0 {
+                    if self.active_layout == ActiveLayout::Profiles {
+                        let first_id = self.tab_bar.tabs[0].id;
+                        self.switch_to_tab(first_id);
+                        self.active_layout = ActiveLayout::Browser;
+                        self.layout_bar.active = ActiveLayout::Browser;
+                        self.focus.set_layout(ActiveLayout::Browser);
+                    } else {
+                        let current_idx = self
                             .tab_bar
                             .tabs
                             .iter()
@@ -1602,985 +5043,2754 @@ impl App {
                     }
                 }
             }
-            Action::PrevTab => {
-                let tab_count = self.tab_bar.tabs.len();
-                if tab_count > 0 {
-                    if self.active_layout == ActiveLayout::Profiles {
-                        let last_id = self.tab_bar.tabs[tab_count - 1].id;
-                        self.switch_to_tab(last_id);
-                        self.active_layout = ActiveLayout::Browser;
-                        self.layout_bar.active = ActiveLayout::Browser;
-                        self.focus.set_layout(ActiveLayout::Browser);
-                    } else {
-                        let current_idx = self
-                            .tab_bar
-                            .tabs
-                            .iter()
-                            .position(|t| Some(t.id) == self.active_tab_id)
-                            .unwrap_or(0);
-                        if current_idx > 0 {
-                            let prev_id = self.tab_bar.tabs[current_idx - 1].id;
-                            self.switch_to_tab(prev_id);
-                        } else {
-                            self.active_layout = ActiveLayout::Profiles;
-                            self.layout_bar.active = ActiveLayout::Profiles;
-                            self.focus.set_layout(ActiveLayout::Profiles);
+            Action::PrevTab => {
+                let tab_count = self.tab_bar.tabs.len();
+                if tab_count > 0 {
+                    if self.active_layout == ActiveLayout::Profiles {
+                        let last_id = self.tab_bar.tabs[tab_count - 1].id;
+                        self.switch_to_tab(last_id);
+                        self.active_layout = ActiveLayout::Browser;
+                        self.layout_bar.active = ActiveLayout::Browser;
+                        self.focus.set_layout(ActiveLayout::Browser);
+                    } else {
+                        let current_idx = self
+                            .tab_bar
+                            .tabs
+                            .iter()
+                            .position(|t| Some(t.id) == self.active_tab_id)
+                            .unwrap_or(0);
+                        if current_idx > 0 {
+                            let prev_id = self.tab_bar.tabs[current_idx - 1].id;
+                            self.switch_to_tab(prev_id);
+                        } else {
+                            self.active_layout = ActiveLayout::Profiles;
+                            self.layout_bar.active = ActiveLayout::Profiles;
+                            self.focus.set_layout(ActiveLayout::Profiles);
+                        }
+                    }
+                }
+            }
+            Action::ToggleSplitView => {
+                if self.split.take().is_some() {
+                    self.status_bar.set_message("Split view closed".to_string());
+                } else {
+                    self.open_split_view();
+                }
+            }
+            Action::SwapSplitFocus => {
+                if self.split.is_some() {
+                    self.swap_split_sides();
+                } else {
+                    self.status_bar.set_error(format!(
+                        "Split view is off (press {} to start)",
+                        self.keymap.hint("toggle_split")
+                    ));
+                }
+            }
+            Action::CopyToOtherSide => self.copy_to_other_side(),
+            Action::SwitchTab(id) => {
+                self.switch_to_tab(id);
+                // If switching from Connections layout, go to Browser
+                if self.active_layout == ActiveLayout::Profiles {
+                    self.active_layout = ActiveLayout::Browser;
+                    self.layout_bar.active = ActiveLayout::Browser;
+                    self.focus.set_layout(ActiveLayout::Browser);
+                }
+            }
+            Action::ShowConnectDialog => {
+                let mut profiles = self.config.connections.clone();
+                profiles.push(example_profile());
+                self.connect_dialog.show(profiles);
+            }
+            Action::ShowNewConnectionForm => {
+                self.connect_dialog.hide();
+                self.new_connection_dialog.show();
+            }
+            Action::ConnectByIndex(idx) => {
+                // The example profile is appended after saved profiles
+                let profile = if idx == self.config.connections.len() {
+                    Some(example_profile())
+                } else {
+                    self.config.connections.get(idx).cloned()
+                };
+                if let Some(profile) = profile {
+                    match self.connect_profile(&profile).await {
+                        Ok(()) => {}
+                        Err(e) if is_auth_error(&e) => {
+                            self.push_error(format!("Authentication failed: {}", e));
+                            self.credential_prompt.show(profile);
+                        }
+                        Err(e) => {
+                            self.push_error(format!("Connection failed: {}", e));
+                        }
+                    }
+                }
+            }
+            Action::ConnectAdHoc(profile, password) => {
+                let profile_clone = profile.clone();
+                match self.connect_with_password(&profile, &password).await {
+                    Ok(()) => {
+                        self.last_adhoc_profile = Some(profile_clone);
+                        let tip_msg = format!(
+                            "Tip: Press {} to save this connection to config",
+                            self.keymap.hint("save_connection"),
+                        );
+                        self.status_bar.set_message(tip_msg.clone());
+                        self.log_panel.push_info(tip_msg);
+                    }
+                    Err(e) if extract_cert_trust_error(&e).is_some() => {
+                        let info = extract_cert_trust_error(&e).unwrap();
+                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
+                            cert_info: Box::new(info),
+                            profile: Box::new(profile_clone),
+                            password,
+                        });
+                    }
+                    Err(e) if is_auth_error(&e) => {
+                        self.push_error(format!("Authentication failed: {}", e));
+                        self.credential_prompt.show(profile_clone);
+                    }
+                    Err(e) => {
+                        self.push_error(format!("Connection failed: {}", e));
+                    }
+                }
+            }
+            Action::PromptCredentials(profile) => {
+                self.credential_prompt.show(profile);
+            }
+            Action::ConnectWithCredentials(profile, password) => {
+                let profile_clone = profile.clone();
+                match self.connect_with_password(&profile, &password).await {
+                    Ok(()) => {
+                        let auth_msg = format!(
+                            "Authenticated as {}",
+                            profile.bind_dn.as_deref().unwrap_or("anonymous")
+                        );
+                        self.status_bar.set_message(auth_msg.clone());
+                        self.log_panel.push_info(auth_msg);
+                        // Auto-store in vault if using Vault method
+                        if matches!(profile.credential_method, CredentialMethod::Vault) {
+                            if let Some(ref mut vault) = self.vault {
+                                if !password.is_empty() {
+                                    let _ = vault.set_password(&profile.name, &password);
+                                }
+                            }
+                        }
+                    }
+                    Err(e) if extract_cert_trust_error(&e).is_some() => {
+                        let info = extract_cert_trust_error(&e).unwrap();
+                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
+                            cert_info: Box::new(info),
+                            profile: Box::new(profile_clone),
+                            password,
+                        });
+                    }
+                    Err(e) if is_auth_error(&e) => {
+                        self.push_error(format!("Authentication failed: {}", e));
+                        self.credential_prompt.show(profile_clone);
+                    }
+                    Err(e) => {
+                        self.push_error(format!("Connection failed: {}", e));
+                    }
+                }
+            }
+            Action::SaveCurrentConnection => {
+                if let Some(profile) = self.last_adhoc_profile.take() {
+                    match AppConfig::append_connection(&profile) {
+                        Ok(()) => {
+                            let save_msg = format!("Saved connection '{}' to config", profile.name);
+                            self.status_bar.set_message(save_msg.clone());
+                            self.log_panel.push_info(save_msg);
+                            self.config.connections.push(profile);
+                        }
+                        Err(e) => {
+                            self.push_error(format!("Failed to save connection: {}", e));
+                        }
+                    }
+                } else {
+                    self.push_message("No ad-hoc connection to save".to_string());
+                }
+            }
+            // Layout switching
+            Action::SwitchLayout(layout) => {
+                self.active_layout = layout;
+                self.layout_bar.active = layout;
+                self.focus.set_layout(layout);
+            }
+
+            // Connections Manager
+            Action::ConnMgrSelect(idx) => {
+                if idx == self.config.connections.len() {
+                    // Example profile — show it in view mode
+                    let profile = example_profile();
+                    self.connection_form.view_profile(idx, &profile);
+                } else if let Some(profile) = self.config.connections.get(idx) {
+                    self.connection_form.view_profile(idx, profile);
+                    let active = self
+                        .tabs
+                        .iter()
+                        .find(|t| t.label == profile.name)
+                        .and_then(|t| t.server_info.iter().find(|(k, _)| k == "server"))
+                        .and_then(|(_, values)| values.first().cloned());
+                    self.connection_form.set_active_host(active);
+                }
+            }
+            Action::ConnMgrNew => {
+                self.connection_form.new_profile();
+                self.focus.set(FocusTarget::ConnectionForm);
+            }
+            Action::ConnMgrSave(idx, profile) => {
+                if idx >= self.config.connections.len() {
+                    self.push_error("Cannot edit example profile".to_string());
+                } else {
+                    // Rename vault entry if profile name changed
+                    let old_name = self.config.connections[idx].name.clone();
+                    let new_name = profile.name.clone();
+                    let is_vault_method =
+                        matches!(profile.credential_method, CredentialMethod::Vault);
+
+                    self.config.update_connection(idx, *profile);
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.status_bar.set_message("Profile saved".to_string());
+                        self.log_panel.push_info("Profile saved".to_string());
+                    }
+
+                    // Handle vault rename
+                    if old_name != new_name {
+                        if let Some(ref mut vault) = self.vault {
+                            let _ = vault.rename_profile(&old_name, &new_name);
+                            let _ = vault.rename_profile(
+                                &ProxySettings::credential_key(&old_name),
+                                &ProxySettings::credential_key(&new_name),
+                            );
+                        }
+                    }
+
+                    // If using Vault method, prompt to store password
+                    if is_vault_method {
+                        if let Some(ref vault) = self.vault {
+                            if vault.get_password(&new_name).is_none() {
+                                self.vault_password_dialog.show_store_password(&new_name);
+                            }
+                        }
+                    }
+
+                    if let Some(updated) = self.config.connections.get(idx) {
+                        self.connection_form.view_profile(idx, updated);
+                    }
+                }
+            }
+            Action::ConnMgrCreate(profile) => {
+                let is_vault_method = matches!(profile.credential_method, CredentialMethod::Vault);
+                let profile_name = profile.name.clone();
+
+                self.config.connections.push(*profile);
+                let new_idx = self.config.connections.len() - 1;
+                if let Err(e) = self.config.save() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message("Profile created".to_string());
+                }
+                if let Some(created) = self.config.connections.get(new_idx) {
+                    self.connection_form.view_profile(new_idx, created);
+                }
+
+                // If using Vault method, prompt to store password
+                if is_vault_method && self.vault.is_some() {
+                    self.vault_password_dialog
+                        .show_store_password(&profile_name);
+                }
+            }
+            Action::ConnMgrDelete(idx) => {
+                if idx >= self.config.connections.len() {
+                    self.push_error("Cannot delete example profile".to_string());
+                } else {
+                    let profile_name = self.config.connections[idx].name.clone();
+                    self.config.delete_connection(idx);
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.push_message("Profile deleted".to_string());
+                    }
+                    // Remove from vault
+                    if let Some(ref mut vault) = self.vault {
+                        let _ = vault.remove_password(&profile_name);
+                        let _ =
+                            vault.remove_password(&ProxySettings::credential_key(&profile_name));
+                    }
+                    self.connection_form.clear();
+                }
+            }
+            Action::ConnMgrDuplicate(idx) => {
+                if let Some(profile) = self.config.connections.get(idx).cloned() {
+                    let mut dup = profile;
+                    dup.name = format!("{}-copy", dup.name);
+                    self.config.connections.push(dup);
+                    let new_idx = self.config.connections.len() - 1;
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.push_message("Profile duplicated".to_string());
+                    }
+                    if let Some(created) = self.config.connections.get(new_idx) {
+                        self.connection_form.view_profile(new_idx, created);
+                    }
+                }
+            }
+            Action::ConnMgrConnect(idx) => {
+                let profile = if idx == self.config.connections.len() {
+                    Some(example_profile())
+                } else {
+                    self.config.connections.get(idx).cloned()
+                };
+                if let Some(profile) = profile {
+                    match self.connect_profile(&profile).await {
+                        Ok(()) => {
+                            // Switch to Browser layout on successful connect
+                            self.active_layout = ActiveLayout::Browser;
+                            self.layout_bar.active = ActiveLayout::Browser;
+                            self.focus.set_layout(ActiveLayout::Browser);
+                        }
+                        Err(e) if is_auth_error(&e) => {
+                            self.push_error(format!("Authentication failed: {}", e));
+                            self.credential_prompt.show(profile);
+                        }
+                        Err(e) => {
+                            self.push_error(format!("Connection failed: {}", e));
+                        }
+                    }
+                }
+            }
+
+            Action::ConnMgrExport => {
+                if self.config.connections.is_empty() {
+                    self.push_error("No profiles to export".to_string());
+                } else {
+                    self.profile_export_dialog.show(&self.config.connections);
+                }
+            }
+            Action::ConnMgrImport => {
+                self.profile_import_dialog.show();
+            }
+            Action::ConnMgrImportExecute(profiles) => {
+                let count = profiles.len();
+                for p in profiles {
+                    self.config.connections.push(p);
+                }
+                if let Err(e) = self.config.save() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message(format!("Imported {} profile(s)", count));
+                }
+                // Refresh the form if a profile was being viewed
+                if let Some(idx) = self.config.connections.len().checked_sub(1) {
+                    self.connection_form
+                        .view_profile(idx, &self.config.connections[idx].clone());
+                }
+            }
+
+            Action::ConnMgrSelectFolder(path) => {
+                let desc = self
+                    .config
+                    .folder_description(&path)
+                    .unwrap_or_default()
+                    .to_string();
+                self.connection_form.view_folder(&path, &desc);
+            }
+            Action::ConnMgrSaveFolderDesc(path, description) => {
+                // Update or insert the folder config
+                if let Some(existing) = self.config.folders.iter_mut().find(|f| f.path == path) {
+                    existing.description = description.clone();
+                } else {
+                    self.config.folders.push(crate::config::FolderConfig {
+                        path: path.clone(),
+                        description: description.clone(),
+                    });
+                }
+                if let Err(e) = self.config.save() {
+                    self.push_error(format!("Failed to save config: {}", e));
+                } else {
+                    self.push_message("Folder description saved".to_string());
+                }
+                self.connection_form.view_folder(&path, &description);
+            }
+
+            Action::ConnMgrMoveProfile(idx, delta) => {
+                if idx >= self.config.connections.len() {
+                    self.push_error("Cannot move example profile".to_string());
+                } else if let Some(new_idx) = self.config.move_connection(idx, delta) {
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    }
+                    let profile = self.config.connections[new_idx].clone();
+                    self.connections_tree
+                        .select_profile(new_idx, profile.folder.as_deref());
+                    self.connection_form.view_profile(new_idx, &profile);
+                }
+            }
+            Action::ShowMoveProfileDialog(idx) => {
+                if let Some(profile) = self.config.connections.get(idx) {
+                    self.move_profile_dialog.show(
+                        idx,
+                        &profile.name,
+                        profile.folder.as_deref(),
+                        self.config.folder_paths(),
+                    );
+                } else {
+                    self.push_error("Cannot move example profile".to_string());
+                }
+            }
+            Action::ConnMgrMoveToFolder(idx, folder) => {
+                if let Some(profile) = self.config.connections.get_mut(idx) {
+                    profile.folder = folder.clone();
+                    let profile = profile.clone();
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    } else {
+                        self.push_message(format!(
+                            "Moved {} to {}",
+                            profile.name,
+                            folder.as_deref().unwrap_or("top level")
+                        ));
+                    }
+                    self.connections_tree.select_profile(idx, folder.as_deref());
+                    self.connection_form.view_profile(idx, &profile);
+                }
+            }
+
+            // Certificate Trust
+            Action::ShowCertTrustDialog {
+                cert_info,
+                profile,
+                password,
+            } => {
+                self.cert_trust_dialog.show(*cert_info, *profile, password);
+            }
+            Action::TrustCertAndConnect {
+                cert_info,
+                fingerprint,
+                always,
+                profile,
+                password,
+            } => {
+                if always {
+                    let entry = TrustedCertEntry {
+                        host: cert_info.host.clone(),
+                        port: cert_info.port,
+                        fingerprint_sha256: fingerprint.clone(),
+                        subject: cert_info.subject.clone(),
+                    };
+                    self.trust_store.trust_always(entry);
+                    // Persist to config
+                    self.config.trusted_certificates = self.trust_store.to_config_entries();
+                    if let Err(e) = self.config.save() {
+                        self.push_error(format!("Failed to save config: {}", e));
+                    }
+                    self.push_message("Certificate trusted permanently".to_string());
+                } else {
+                    self.trust_store.trust_session(fingerprint);
+                    self.push_message("Certificate trusted for this session".to_string());
+                }
+                // Retry the connection
+                match self.connect_with_password(&profile, &password).await {
+                    Ok(()) => {}
+                    Err(e) if is_auth_error(&e) => {
+                        self.push_error(format!("Authentication failed: {}", e));
+                        self.credential_prompt.show(*profile);
+                    }
+                    Err(e) => {
+                        self.push_error(format!("Connection failed: {}", e));
+                    }
+                }
+            }
+
+            Action::CloseCurrentTab => {
+                if let Some(id) = self.active_tab_id {
+                    self.close_split_with(id);
+                    self.tabs.retain(|t| t.id != id);
+                    self.tab_bar.remove_tab(id);
+                    self.active_tab_id = self.tab_bar.active_tab;
+                    self.detail_panel.clear();
+                    if self.active_tab_id.is_none() {
+                        self.status_bar.set_disconnected();
+                        self.active_layout = ActiveLayout::Profiles;
+                        self.layout_bar.active = ActiveLayout::Profiles;
+                        self.focus.set_layout(ActiveLayout::Profiles);
+                    }
+                }
+            }
+            Action::CloseTab(id) => {
+                self.close_split_with(id);
+                self.tabs.retain(|t| t.id != id);
+                self.tab_bar.remove_tab(id);
+                if self.active_tab_id == Some(id) {
+                    self.active_tab_id = self.tab_bar.active_tab;
+                    self.detail_panel.clear();
+                    if self.active_tab_id.is_none() {
+                        self.status_bar.set_disconnected();
+                        self.active_layout = ActiveLayout::Profiles;
+                        self.layout_bar.active = ActiveLayout::Profiles;
+                        self.focus.set_layout(ActiveLayout::Profiles);
+                    }
+                }
+            }
+
+            // Tree
+            Action::TreeExpand(dn) if !dn.is_empty() => {
+                if let Some(id) = self.active_tab_id {
+                    // Children already loaded stay until the subtree is refreshed
+                    let loaded = self.active_tab().is_some_and(|t| {
+                        t.directory_tree
+                            .find_node(&dn)
+                            .is_some_and(|n| n.children.is_some())
+                    });
+                    if !loaded {
+                        self.spawn_load_children(id, dn.clone());
+                    }
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+            Action::RefreshSubtree(dn) if !dn.is_empty() => {
+                if let Some(id) = self.active_tab_id {
+                    let loaded = self
+                        .active_tab()
+                        .map(|t| t.directory_tree.loaded_subtree(&dn))
+                        .unwrap_or_default();
+                    self.invalidate_cached(id, &dn);
+                    self.push_message(format!(
+                        "Refreshing {}...",
+                        loom_core::dn::rdn_display_name(&dn)
+                    ));
+                    for parent in loaded {
+                        self.spawn_load_children(id, parent);
+                    }
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+            Action::TreeCollapse(_dn) => {}
+            Action::TreeSelect(dn) if !dn.is_empty() => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+            Action::TreeChildrenLoaded(conn_id, parent_dn, nodes) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    let diff = tab.directory_tree.insert_children(&parent_dn, nodes);
+                    if matches!(self.pending_reveal, Some((id, _)) if id == conn_id) {
+                        self.advance_reveal();
+                    }
+                    if self.restoring.contains_key(&conn_id) {
+                        self.advance_restore(conn_id);
+                    }
+                    let name = loom_core::dn::rdn_display_name(&parent_dn);
+                    let loaded_msg = match diff {
+                        Some(diff) if diff.is_empty() => format!("{} is unchanged", name),
+                        Some(diff) => format!(
+                            "Refreshed {}: {} added, {} removed",
+                            name, diff.added, diff.removed
+                        ),
+                        None => format!("Loaded children of {}", name),
+                    };
+                    self.status_bar.set_message(loaded_msg.clone());
+                    self.log_panel.push_info(loaded_msg);
+                }
+            }
+            Action::ChildCountLoaded(conn_id, dn, count) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    tab.directory_tree.set_child_count(&dn, count);
+                }
+            }
+            Action::EntryLoaded(conn_id, entry) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    if matches!(tab.backend, TabBackend::Live(_)) {
+                        tab.entry_cache.insert(entry.clone());
+                    }
+                }
+                self.show_entry(conn_id, entry);
+            }
+            Action::EntryRefresh => {
+                if let (Some(id), Some(dn)) = (
+                    self.active_tab_id,
+                    self.detail_panel.entry.as_ref().map(|e| e.dn.clone()),
+                ) {
+                    self.invalidate_cached(id, &dn);
+                    self.push_message(format!(
+                        "Reloading {}...",
+                        loom_core::dn::rdn_display_name(&dn)
+                    ));
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+
+            // Search
+            Action::SearchExecute(filter) => {
+                if let Err(e) = loom_core::filter::validate_filter(&filter) {
+                    self.status_bar.set_error(format!("Invalid filter: {}", e));
+                    // Re-activate input so user can fix the filter
+                    self.command_panel.resume_input();
+                    self.command_panel.input_buffer = filter;
+                    self.command_panel.cursor_pos = self.command_panel.input_buffer.len();
+                } else if let Some(id) = self.active_tab_id {
+                    match self.expand_filter_macros(id, &filter) {
+                        Ok(expanded) => {
+                            self.status_bar
+                                .set_message(format!("Searching: {}...", expanded));
+                            // The unexpanded filter is kept, so a rerun moves with the clock
+                            self.search_dialog.filter = filter;
+                            self.search_dialog.columns.clear();
+                            self.search_started = Some(Instant::now());
+                            self.spawn_search(id, expanded, self.search_dialog.timeout());
+                        }
+                        Err(e) => {
+                            self.status_bar.set_error(format!("Invalid filter: {}", e));
+                            self.command_panel.resume_input();
+                        }
+                    }
+                } else {
+                    self.status_bar
+                        .set_error("No active connection".to_string());
+                }
+            }
+            Action::SearchResults(conn_id, entries) if self.active_tab_id == Some(conn_id) => {
+                let mut msg = format!("Found {} entries", humanize::count(entries.len()));
+                if let Some(started) = self.search_started.take() {
+                    msg.push_str(&format!(" in {}", humanize::duration(started.elapsed())));
+                }
+                self.status_bar.set_message(msg);
+                self.command_panel.observe_results(&entries);
+                // Store results in search dialog (keep popup visible)
+                let filter = self.search_dialog.filter.clone();
+                self.search_dialog.show_results(filter, entries);
+            }
+            Action::SearchFocusInput => {
+                self.dismiss_all_popups();
+                self.search_dialog.visible = true;
+                if self.search_dialog.has_results() {
+                    // Results exist — open in navigation mode (press / to edit filter)
+                    self.command_panel.soft_deactivate();
+                } else if self.command_panel.input_buffer.is_empty() {
+                    self.command_panel.activate_input();
+                    if let Some(filter) = self.active_tab().and_then(|t| t.default_filter.clone()) {
+                        self.command_panel.input_buffer = filter;
+                        self.command_panel.resume_input();
+                    }
+                } else {
+                    self.command_panel.resume_input();
+                }
+            }
+
+            // Live Search (debounced preview)
+            Action::LiveSearchRequest { generation, filter } => {
+                if let Some(id) = self.active_tab_id {
+                    match self.expand_filter_macros(id, &filter) {
+                        Ok(expanded) => self.spawn_live_search(id, generation, expanded),
+                        Err(e) => {
+                            debug!("Live search skipped: {}", e);
+                            self.command_panel.receive_live_results(generation);
                         }
                     }
                 }
             }
-            Action::SwitchTab(id) => {
-                self.switch_to_tab(id);
-                // If switching from Connections layout, go to Browser
-                if self.active_layout == ActiveLayout::Profiles {
-                    self.active_layout = ActiveLayout::Browser;
-                    self.layout_bar.active = ActiveLayout::Browser;
-                    self.focus.set_layout(ActiveLayout::Browser);
+            Action::LiveSearchResults {
+                generation,
+                entries,
+            } if self.command_panel.receive_live_results(generation) => {
+                self.command_panel.observe_results(&entries);
+                // Feed live results directly into the search dialog table
+                let filter = self.command_panel.input_buffer.clone();
+                self.search_dialog.filter = filter;
+                self.search_dialog.set_results(entries);
+            }
+
+            Action::SampleAttributeValues(attr) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_sample_values(id, attr);
+                }
+            }
+            Action::AttributeValuesSampled(conn_id, attr, values) => {
+                debug!("Sampled {} values of {}", values.len(), attr);
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    // Cached even when empty so the scan is not repeated
+                    tab.value_samples
+                        .insert(attr.to_lowercase(), values.clone());
+                }
+                if self.active_tab_id == Some(conn_id) {
+                    self.command_panel.add_value_samples(&attr, values);
+                }
+            }
+
+            // Attribute editing
+            Action::EditAttribute(dn, attr, value) => {
+                if self.edit_locked(&attr) {
+                    return;
+                }
+                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
+                let existing = self.detail_values(&dn, &attr);
+                self.attribute_editor.edit_value_with_options(
+                    dn,
+                    attr.clone(),
+                    value.clone(),
+                    is_dn,
+                    multi_valued,
+                );
+                self.apply_value_rules(&attr, existing, Some(&value));
+            }
+            Action::EditInExternalEditor(dn, attr, value) => {
+                if self.edit_locked(&attr) {
+                    return;
+                }
+                if self
+                    .active_tab()
+                    .is_some_and(|tab| tab.read_only && !tab.dry_run)
+                {
+                    self.push_error("Connection is read-only".to_string());
+                } else {
+                    self.pending_external_edit = Some(ExternalEdit { dn, attr, value });
+                }
+            }
+            Action::AddAttribute(dn, attr) => {
+                if self.edit_locked(&attr) {
+                    return;
+                }
+                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
+                let existing = self.detail_values(&dn, &attr);
+                self.attribute_editor
+                    .add_value_with_options(dn, attr.clone(), is_dn, multi_valued);
+                self.apply_value_rules(&attr, existing, None);
+            }
+            Action::ShowAddAttribute(dn) => {
+                // Build candidate list from schema
+                let candidates = if let Some(tab) = self.active_tab() {
+                    if let Some(ref schema) = tab.schema {
+                        let present: std::collections::HashSet<String> = self
+                            .detail_panel
+                            .entry
+                            .as_ref()
+                            .map(|e| e.attributes.keys().map(|k| k.to_lowercase()).collect())
+                            .unwrap_or_default();
+
+                        let ocs: Vec<&str> = self
+                            .detail_panel
+                            .entry
+                            .as_ref()
+                            .map(|e| e.object_classes())
+                            .unwrap_or_default();
+
+                        let attr_names = if !ocs.is_empty() {
+                            schema.allowed_attributes(&ocs)
+                        } else {
+                            schema.all_user_attributes()
+                        };
+
+                        attr_names
+                            .into_iter()
+                            .filter(|name| !present.contains(&name.to_lowercase()))
+                            .map(|name| {
+                                let syntax_label = schema
+                                    .get_attribute_type(&name)
+                                    .map(|at| format!("{:?}", at.syntax))
+                                    .unwrap_or_default();
+                                (name, syntax_label)
+                            })
+                            .collect()
+                    } else {
+                        Vec::new()
+                    }
+                } else {
+                    Vec::new()
+                };
+
+                self.attribute_picker.show(dn, candidates);
+            }
+            Action::DeleteAttributeValue(dn, attr, value) => {
+                if self.edit_locked(&attr) {
+                    return;
+                }
+                let result = EditResult {
+                    dn,
+                    op: EditOp::Delete { attr, value },
+                    new_value: String::new(),
+                };
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_save_attribute(id, result);
+                }
+            }
+            Action::SaveAttribute(result) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_save_attribute(id, result);
+                }
+            }
+            Action::ShowModifyConflict(conflict) => {
+                self.log_panel.push_info(format!(
+                    "Edit conflict on {} of {}",
+                    conflict.attr, conflict.dn
+                ));
+                self.conflict_dialog.show(*conflict);
+            }
+            Action::ConflictMerge {
+                dn,
+                attr,
+                base_value,
+                draft,
+            } => {
+                let existing = self.detail_values(&dn, &attr);
+                self.attribute_editor
+                    .edit_value_from(dn, attr.clone(), base_value.clone(), draft);
+                self.apply_value_rules(&attr, existing, Some(&base_value));
+            }
+            Action::ConflictAborted(dn) => {
+                self.status_bar
+                    .set_message("Edit discarded; reloaded server version".to_string());
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &dn);
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+            Action::AttributeSaved(dn) => {
+                self.run_entry_hook(HookEvent::EntryModified, &dn);
+                let saved_msg =
+                    format!("Saved changes to {}", loom_core::dn::rdn_display_name(&dn));
+                self.status_bar.set_message(saved_msg.clone());
+                self.log_panel.push_info(saved_msg);
+                // Refresh the entry
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &dn);
+                    self.spawn_load_entry(id, dn);
+                }
+            }
+
+            // Export
+            Action::ShowExportDialog => {
+                if let Some(tab) = self.active_tab() {
+                    let base_dn = self
+                        .tree_panel
+                        .selected_dn()
+                        .cloned()
+                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
+                    self.export_dialog.show(&base_dn);
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
+            }
+            Action::ExportExecute {
+                base_dn,
+                path,
+                filter,
+                attributes,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Exporting to {} (filter: {})...", path, filter));
+                    self.spawn_export(id, path, base_dn, filter, attributes);
+                }
+            }
+            Action::ShowExportEntriesDialog(dns) => {
+                if self.active_tab_id.is_some() {
+                    self.export_dialog.show_entries(dns);
+                } else {
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::ShowConnectDialog => {
-                let mut profiles = self.config.connections.clone();
-                profiles.push(example_profile());
-                self.connect_dialog.show(profiles);
-            }
-            Action::ShowNewConnectionForm => {
-                self.connect_dialog.hide();
-                self.new_connection_dialog.show();
+            Action::ExportEntries {
+                dns,
+                path,
+                attributes,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Exporting {} entries to {}...", dns.len(), path));
+                    self.spawn_export_entries(id, dns, path, attributes);
+                }
             }
-            Action::ConnectByIndex(idx) => {
-                // The example profile is appended after saved profiles
-                let profile = if idx == self.config.connections.len() {
-                    Some(example_profile())
+            Action::ShowReportDialog(dns) => {
+                if self.active_tab_id.is_some() {
+                    self.report_dialog.show(dns);
                 } else {
-                    self.config.connections.get(idx).cloned()
-                };
-                if let Some(profile) = profile {
-                    match self.connect_profile(&profile).await {
-                        Ok(()) => {}
-                        Err(e) if is_auth_error(&e) => {
-                            self.push_error(format!("Authentication failed: {}", e));
-                            self.credential_prompt.show(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Connection failed: {}", e));
-                        }
-                    }
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::ConnectAdHoc(profile, password) => {
-                let profile_clone = profile.clone();
-                match self.connect_with_password(&profile, &password).await {
-                    Ok(()) => {
-                        self.last_adhoc_profile = Some(profile_clone);
-                        let tip_msg = format!(
-                            "Tip: Press {} to save this connection to config",
-                            self.keymap.hint("save_connection"),
-                        );
-                        self.status_bar.set_message(tip_msg.clone());
-                        self.log_panel.push_info(tip_msg);
-                    }
-                    Err(e) if extract_cert_trust_error(&e).is_some() => {
-                        let info = extract_cert_trust_error(&e).unwrap();
-                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
-                            cert_info: Box::new(info),
-                            profile: Box::new(profile_clone),
-                            password,
-                        });
-                    }
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(profile_clone);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
-                    }
+            Action::ReportExecute {
+                dns,
+                path,
+                sections,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_report(id, dns, path, sections);
                 }
             }
-            Action::PromptCredentials(profile) => {
-                self.credential_prompt.show(profile);
+            Action::ExportComplete(msg) => {
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
             }
-            Action::ConnectWithCredentials(profile, password) => {
-                let profile_clone = profile.clone();
-                match self.connect_with_password(&profile, &password).await {
-                    Ok(()) => {
-                        let auth_msg = format!(
-                            "Authenticated as {}",
-                            profile.bind_dn.as_deref().unwrap_or("anonymous")
-                        );
-                        self.status_bar.set_message(auth_msg.clone());
-                        self.log_panel.push_info(auth_msg);
-                        // Auto-store in vault if using Vault method
-                        if matches!(profile.credential_method, CredentialMethod::Vault) {
-                            if let Some(ref mut vault) = self.vault {
-                                if !password.is_empty() {
-                                    let _ = vault.set_password(&profile.name, &password);
-                                }
+
+            // Bulk Update
+            Action::ShowBulkUpdateDialog => {
+                if self.active_tab_id.is_some() {
+                    self.bulk_update_dialog.show(&self.config.bulk);
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
+            }
+            Action::BulkUpdateExecute {
+                filter,
+                attribute,
+                value,
+                op,
+                throttle,
+            } => {
+                if let Some(id) = self.active_tab_id {
+                    use crate::components::bulk_update_dialog::BulkOp;
+                    let modifications = match op {
+                        BulkOp::Replace => Ok(vec![BulkMod::ReplaceAttribute {
+                            attr: attribute,
+                            value,
+                        }]),
+                        BulkOp::Add => Ok(vec![BulkMod::AddValue {
+                            attr: attribute,
+                            value,
+                        }]),
+                        BulkOp::Delete => {
+                            if value.is_empty() {
+                                Ok(vec![BulkMod::DeleteAttribute { attr: attribute }])
+                            } else {
+                                Ok(vec![BulkMod::DeleteValue {
+                                    attr: attribute,
+                                    value,
+                                }])
                             }
                         }
-                    }
-                    Err(e) if extract_cert_trust_error(&e).is_some() => {
-                        let info = extract_cert_trust_error(&e).unwrap();
-                        let _ = self.action_tx.send(Action::ShowCertTrustDialog {
-                            cert_info: Box::new(info),
-                            profile: Box::new(profile_clone),
-                            password,
-                        });
-                    }
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(profile_clone);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
+                        BulkOp::Script => script_mods(&value),
+                    };
+                    match modifications {
+                        Ok(modifications) => {
+                            self.push_message(format!("Executing bulk update: {}...", filter));
+                            self.spawn_bulk_update(id, filter, modifications, throttle);
+                        }
+                        Err(e) => self.push_error(e),
                     }
                 }
             }
-            Action::SaveCurrentConnection => {
-                if let Some(profile) = self.last_adhoc_profile.take() {
-                    match AppConfig::append_connection(&profile) {
-                        Ok(()) => {
-                            let save_msg = format!("Saved connection '{}' to config", profile.name);
-                            self.status_bar.set_message(save_msg.clone());
-                            self.log_panel.push_info(save_msg);
-                            self.config.connections.push(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Failed to save connection: {}", e));
-                        }
+            Action::BulkPreview { filter, script } => {
+                if let Some(id) = self.active_tab_id {
+                    match script_mods(&script) {
+                        Ok(modifications) => self.spawn_bulk_preview(id, filter, modifications),
+                        Err(e) => self.push_error(e),
                     }
-                } else {
-                    self.push_message("No ad-hoc connection to save".to_string());
                 }
             }
-            // Layout switching
-            Action::SwitchLayout(layout) => {
-                self.active_layout = layout;
-                self.layout_bar.active = layout;
-                self.focus.set_layout(layout);
+            Action::BulkPreviewReady(total, values) => {
+                self.bulk_update_dialog.set_preview(total, values);
+            }
+            Action::BulkUpdateComplete(msg) => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
             }
 
-            // Connections Manager
-            Action::ConnMgrSelect(idx) => {
-                if idx == self.config.connections.len() {
-                    // Example profile — show it in view mode
-                    let profile = example_profile();
-                    self.connection_form.view_profile(idx, &profile);
-                } else if let Some(profile) = self.config.connections.get(idx) {
-                    self.connection_form.view_profile(idx, profile);
+            // Dry run
+            Action::ToggleDryRun => self.spawn_toggle_dry_run(),
+            Action::DryRunToggled(conn_id, enabled, msg) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    tab.dry_run = enabled;
+                    self.tab_bar.set_label(conn_id, tab.display_label());
                 }
+                self.push_message(msg);
             }
-            Action::ConnMgrNew => {
-                self.connection_form.new_profile();
-                self.focus.set(FocusTarget::ConnectionForm);
+            Action::ExportDryRunScript => self.spawn_export_dry_run_script(),
+
+            // Staged changes
+            Action::ToggleStaging => self.toggle_staging(),
+            Action::ToggleChangesPanel => self.changes_panel.toggle(),
+            Action::ApplyStagedChanges { stop_on_error } => {
+                if let Some(conn_id) = self.active_tab_id {
+                    self.spawn_apply_staged(conn_id, stop_on_error);
+                }
             }
-            Action::ConnMgrSave(idx, profile) => {
-                if idx >= self.config.connections.len() {
-                    self.push_error("Cannot edit example profile".to_string());
-                } else {
-                    // Rename vault entry if profile name changed
-                    let old_name = self.config.connections[idx].name.clone();
-                    let new_name = profile.name.clone();
-                    let is_vault_method =
-                        matches!(profile.credential_method, CredentialMethod::Vault);
+            Action::StagedChangesApplied(summary, problems) => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(summary.clone());
+                self.log_panel.push_info(summary);
+                for problem in problems {
+                    self.log_panel.push_error(problem);
+                }
+                if let Some(root) = self.active_tab().map(|t| t.directory_tree.root_dn.clone()) {
+                    let _ = self.action_tx.send(Action::RefreshSubtree(root));
+                }
+            }
+            Action::ExportStagedChanges => self.export_staged_changes(),
+            Action::CopyLdapSearch => self.copy_ldapsearch(),
+            Action::CopyLdapModify(ldif) => match self.active_cli_settings() {
+                Ok(settings) => {
+                    let command = cli_command::ldapmodify(&settings, &ldif);
+                    let _ = self.action_tx.send(Action::CopyToClipboard(command));
+                }
+                Err(e) => self.push_error(e),
+            },
+            Action::DiscardStagedChanges => match self.active_changes() {
+                Some(changes) if changes.clear() => {
+                    self.push_message("Discarded the staged changes".to_string())
+                }
+                Some(_) => self.push_error("The staged changes are being applied".to_string()),
+                None => {}
+            },
 
-                    self.config.update_connection(idx, *profile);
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
+            // Recently viewed entries
+            Action::ShowRecentEntries => match self.active_tab() {
+                Some(tab) => {
+                    let label = tab.label.clone();
+                    let recent = self.recent_entries.get(&label).cloned().unwrap_or_default();
+                    if recent.is_empty() {
+                        self.status_bar
+                            .set_message("No recently viewed entries".to_string());
                     } else {
-                        self.status_bar.set_message("Profile saved".to_string());
-                        self.log_panel.push_info("Profile saved".to_string());
-                    }
-
-                    // Handle vault rename
-                    if old_name != new_name {
-                        if let Some(ref mut vault) = self.vault {
-                            let _ = vault.rename_profile(&old_name, &new_name);
-                        }
-                    }
-
-                    // If using Vault method, prompt to store password
-                    if is_vault_method {
-                        if let Some(ref vault) = self.vault {
-                            if vault.get_password(&new_name).is_none() {
-                                self.vault_password_dialog.show_store_password(&new_name);
-                            }
-                        }
+                        self.recent_popup.show(&label, recent);
                     }
-
-                    if let Some(updated) = self.config.connections.get(idx) {
-                        self.connection_form.view_profile(idx, updated);
+                }
+                None => self.push_error("No active connection".to_string()),
+            },
+            Action::ShowGoToDn => {
+                if self.active_tab_id.is_some() {
+                    self.goto_dn_dialog.show();
+                } else {
+                    self.push_error("No active connection".to_string());
+                }
+            }
+            Action::ShowQuickOpen => match self.active_tab() {
+                Some(tab) => {
+                    let dns = self.quick_open_candidates(tab);
+                    self.quick_open.show(dns);
+                }
+                None => self.push_error("No active connection".to_string()),
+            },
+            Action::RevealEntry(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.pending_reveal = Some((id, dn));
+                    self.advance_reveal();
+                    self.focus.set(FocusTarget::TreePanel);
+                }
+            }
+            Action::FollowReference(attr, dn) => {
+                let (is_dn, _) = self.lookup_attr_schema(&attr);
+                if !is_dn && !loom_core::dn::looks_like_dn(&dn) {
+                    self.status_bar
+                        .set_error(format!("{} does not hold a DN", attr));
+                    return;
+                }
+                self.navigate_to_entry(dn);
+            }
+            Action::NavigateBack | Action::NavigateForward => {
+                let back = matches!(action, Action::NavigateBack);
+                let target = self.active_tab_mut().and_then(|tab| {
+                    if back {
+                        tab.history.back()
+                    } else {
+                        tab.history.forward()
                     }
+                });
+                match target {
+                    Some(dn) => self.navigate_to_entry(dn),
+                    None => self.status_bar.set_message(if back {
+                        "No earlier entry in history".to_string()
+                    } else {
+                        "No later entry in history".to_string()
+                    }),
                 }
             }
-            Action::ConnMgrCreate(profile) => {
-                let is_vault_method = matches!(profile.credential_method, CredentialMethod::Vault);
-                let profile_name = profile.name.clone();
-
-                self.config.connections.push(*profile);
-                let new_idx = self.config.connections.len() - 1;
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
-                } else {
-                    self.push_message("Profile created".to_string());
+            Action::ShowHistory => match self.active_tab() {
+                Some(tab) => {
+                    let (dns, current) = tab.history.entries();
+                    if dns.is_empty() {
+                        self.status_bar
+                            .set_message("No entries viewed on this tab yet".to_string());
+                    } else {
+                        let label = tab.label.clone();
+                        self.history_popup.show(&label, dns.to_vec(), current);
+                    }
                 }
-                if let Some(created) = self.config.connections.get(new_idx) {
-                    self.connection_form.view_profile(new_idx, created);
+                None => self.push_error("No active connection".to_string()),
+            },
+            Action::HistoryJump(index) => {
+                if let Some(dn) = self
+                    .active_tab_mut()
+                    .and_then(|tab| tab.history.jump(index))
+                {
+                    self.navigate_to_entry(dn);
+                }
+            }
+            Action::ToggleReference(dn) => {
+                if self
+                    .detail_panel
+                    .reference()
+                    .is_some_and(|r| r.dn.eq_ignore_ascii_case(&dn))
+                {
+                    self.set_reference(None);
+                    self.status_bar
+                        .set_message("Stopped comparing with the reference".to_string());
+                    return;
                 }
-
-                // If using Vault method, prompt to store password
-                if is_vault_method && self.vault.is_some() {
-                    self.vault_password_dialog
-                        .show_store_password(&profile_name);
+                let shown = self
+                    .detail_panel
+                    .entry
+                    .as_ref()
+                    .filter(|e| e.dn.eq_ignore_ascii_case(&dn))
+                    .cloned();
+                match shown {
+                    Some(entry) => {
+                        self.set_reference(Some(entry));
+                        self.status_bar.set_message(format!(
+                            "Entries are compared with {} (M again to stop)",
+                            dn
+                        ));
+                    }
+                    None => self
+                        .status_bar
+                        .set_error("Open the entry to use it as the reference".to_string()),
                 }
             }
-            Action::ConnMgrDelete(idx) => {
-                if idx >= self.config.connections.len() {
-                    self.push_error("Cannot delete example profile".to_string());
+            Action::ToggleFavorite(dn) => {
+                let Some(tab) = self.active_tab_mut() else {
+                    return;
+                };
+                let pinned = match tab
+                    .favorites
+                    .iter()
+                    .position(|f| f.eq_ignore_ascii_case(&dn))
+                {
+                    Some(i) => {
+                        tab.favorites.remove(i);
+                        tab.stale_favorites.remove(&dn.to_lowercase());
+                        false
+                    }
+                    None => {
+                        tab.favorites.push(dn.clone());
+                        true
+                    }
+                };
+                let (label, favorites) = (tab.label.clone(), tab.favorites.clone());
+                let msg = if pinned {
+                    format!("Pinned {}", dn)
                 } else {
-                    let profile_name = self.config.connections[idx].name.clone();
-                    self.config.delete_connection(idx);
+                    format!("Unpinned {}", dn)
+                };
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+                if let Some(profile) = self.config.connections.iter_mut().find(|p| p.name == label)
+                {
+                    profile.favorites = favorites;
                     if let Err(e) = self.config.save() {
                         self.push_error(format!("Failed to save config: {}", e));
-                    } else {
-                        self.push_message("Profile deleted".to_string());
-                    }
-                    // Remove from vault
-                    if let Some(ref mut vault) = self.vault {
-                        let _ = vault.remove_password(&profile_name);
                     }
-                    self.connection_form.clear();
                 }
             }
-            Action::ConnMgrDuplicate(idx) => {
-                if let Some(profile) = self.config.connections.get(idx).cloned() {
-                    let mut dup = profile;
-                    dup.name = format!("{}-copy", dup.name);
-                    self.config.connections.push(dup);
-                    let new_idx = self.config.connections.len() - 1;
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
-                    } else {
-                        self.push_message("Profile duplicated".to_string());
+            Action::ShowFavorites => match self.active_tab() {
+                Some(tab) => {
+                    let (id, label) = (tab.id, tab.label.clone());
+                    let (favorites, stale) = (tab.favorites.clone(), tab.stale_favorites.clone());
+                    self.favorites_popup.show(&label, favorites, stale);
+                    self.spawn_check_favorites(id);
+                }
+                None => self.push_error("No active connection".to_string()),
+            },
+            Action::ShowWorkspaces => {
+                if self.config.workspaces.is_empty() {
+                    self.status_bar.set_message(
+                        "No workspaces configured -- add [[workspaces]] to config.toml".to_string(),
+                    );
+                } else {
+                    self.workspace_popup.show(self.config.workspaces.clone());
+                }
+            }
+            Action::OpenWorkspace(name) => self.open_workspace(&name).await,
+            Action::ShowCommandLine => {
+                let mut profiles: Vec<String> = self
+                    .config
+                    .connections
+                    .iter()
+                    .map(|p| p.name.clone())
+                    .collect();
+                profiles.push(example_profile().name);
+                let dns = self
+                    .active_tab()
+                    .map(|tab| loaded_dns(&tab.directory_tree.root))
+                    .unwrap_or_default();
+                self.command_line.show(CompletionSource {
+                    profiles,
+                    workspaces: self
+                        .config
+                        .workspaces
+                        .iter()
+                        .map(|w| w.name.clone())
+                        .collect(),
+                    dns,
+                    themes: theme::available_themes(),
+                    reports: self.config.reports.iter().map(|r| r.name.clone()).collect(),
+                });
+            }
+            Action::RunCommandLine(line) => match ExCommand::parse(&line) {
+                Ok(command) => self.run_ex_command(command),
+                Err(e) => self.push_error(e),
+            },
+            Action::ShowCommandPalette => {
+                let mut items = command_palette::global_commands(&self.keymap);
+                // What the context menu would offer for the focused selection
+                let (prefix, context) = if self.active_layout != ActiveLayout::Browser {
+                    ("", Vec::new())
+                } else if self.focus.is_focused(FocusTarget::TreePanel) {
+                    match self.tree_panel.selected_dn() {
+                        Some(dn) => ("Entry: ", ContextMenu::tree_items(dn)),
+                        None => ("", Vec::new()),
                     }
-                    if let Some(created) = self.config.connections.get(new_idx) {
-                        self.connection_form.view_profile(new_idx, created);
+                } else if self.focus.is_focused(FocusTarget::DetailPanel) {
+                    match (
+                        &self.detail_panel.entry,
+                        self.detail_panel.selected_attr_value(),
+                    ) {
+                        (Some(entry), Some((attr, value))) => (
+                            "Attribute: ",
+                            ContextMenu::detail_items(&entry.dn, attr, value),
+                        ),
+                        _ => ("", Vec::new()),
+                    }
+                } else {
+                    ("", Vec::new())
+                };
+                items.extend(context.into_iter().map(|item| MenuItem {
+                    label: format!("{}{}", prefix, item.label),
+                    ..item
+                }));
+                self.command_palette.show(items);
+            }
+            Action::FavoritesChecked(conn_id, stale) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    tab.stale_favorites = stale.iter().map(|dn| dn.to_lowercase()).collect();
+                    if self.active_tab_id == Some(conn_id) {
+                        self.favorites_popup.set_stale(tab.stale_favorites.clone());
                     }
                 }
+                for dn in stale {
+                    self.log_panel
+                        .push_error(format!("Favorite no longer exists: {}", dn));
+                }
             }
-            Action::ConnMgrConnect(idx) => {
-                let profile = if idx == self.config.connections.len() {
-                    Some(example_profile())
-                } else {
-                    self.config.connections.get(idx).cloned()
+            Action::SaveSearch { name, filter } => {
+                let Some(tab) = self.active_tab_mut() else {
+                    self.push_error("No active connection".to_string());
+                    return;
                 };
-                if let Some(profile) = profile {
-                    match self.connect_profile(&profile).await {
-                        Ok(()) => {
-                            // Switch to Browser layout on successful connect
-                            self.active_layout = ActiveLayout::Browser;
-                            self.layout_bar.active = ActiveLayout::Browser;
-                            self.focus.set_layout(ActiveLayout::Browser);
-                        }
-                        Err(e) if is_auth_error(&e) => {
-                            self.push_error(format!("Authentication failed: {}", e));
-                            self.credential_prompt.show(profile);
-                        }
-                        Err(e) => {
-                            self.push_error(format!("Connection failed: {}", e));
-                        }
+                let id = tab.id;
+                let folder = SearchFolder::new(name.clone(), filter);
+                // Saving under an existing name replaces that search
+                match tab.search_folders.iter_mut().find(|f| f.name == name) {
+                    Some(existing) => *existing = folder,
+                    None => tab.search_folders.push(folder),
+                }
+                let msg = format!("Saved search '{}' to the tree", name);
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+                self.save_search_folders(id);
+            }
+            Action::RunSavedSearch(name) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_saved_search(id, name);
+                }
+            }
+            Action::DeleteSavedSearch(name) => {
+                let Some(tab) = self.active_tab_mut() else {
+                    return;
+                };
+                let id = tab.id;
+                tab.search_folders.retain(|f| f.name != name);
+                self.status_bar
+                    .set_message(format!("Deleted saved search '{}'", name));
+                self.save_search_folders(id);
+            }
+            Action::SavedSearchResults(conn_id, name, result) => {
+                let Some(folder) = self
+                    .tabs
+                    .iter_mut()
+                    .find(|t| t.id == conn_id)
+                    .and_then(|t| t.search_folders.iter_mut().find(|f| f.name == name))
+                else {
+                    return;
+                };
+                folder.loading = false;
+                match result {
+                    Ok(dns) => {
+                        let msg = format!("Saved search '{}': {} entries", name, dns.len());
+                        folder.results = Some(dns);
+                        self.status_bar.set_message(msg);
                     }
+                    Err(e) => self.push_error(format!("Saved search '{}' failed: {}", name, e)),
+                }
+            }
+            Action::OpenRecentEntry(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_load_entry(id, dn);
+                    self.focus.set(FocusTarget::DetailPanel);
                 }
             }
 
-            Action::ConnMgrExport => {
-                if self.config.connections.is_empty() {
-                    self.push_error("No profiles to export".to_string());
+            // Entry import
+            Action::ShowImportDialog => {
+                if self.active_tab_id.is_some() {
+                    let schema = self.active_tab().and_then(|t| t.schema.clone());
+                    self.import_dialog.set_schema(schema);
+                    self.import_dialog.show();
                 } else {
-                    self.profile_export_dialog.show(&self.config.connections);
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::ConnMgrImport => {
-                self.profile_import_dialog.show();
+            Action::ImportPreview(entries) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_import_preview(id, entries);
+                }
             }
-            Action::ConnMgrImportExecute(profiles) => {
-                let count = profiles.len();
-                for p in profiles {
-                    self.config.connections.push(p);
+            Action::ImportPreviewReady(items) => {
+                self.import_dialog.show_preview(items);
+            }
+            Action::ImportExecute(items) => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Importing {} entries...", items.len()));
+                    self.spawn_import(id, items);
                 }
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
+            }
+            Action::ImportComplete(summary, problems) => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(summary.clone());
+                self.log_panel.push_info(summary.clone());
+                for problem in &problems {
+                    self.log_panel.push_error(problem.clone());
+                }
+                self.import_dialog.show_report(summary, problems);
+            }
+
+            // Group membership import
+            Action::ShowMembershipImportDialog => {
+                if self.active_tab_id.is_some() {
+                    self.membership_import_dialog.show();
                 } else {
-                    self.push_message(format!("Imported {} profile(s)", count));
+                    self.push_error("No active connection".to_string());
                 }
-                // Refresh the form if a profile was being viewed
-                if let Some(idx) = self.config.connections.len().checked_sub(1) {
-                    self.connection_form
-                        .view_profile(idx, &self.config.connections[idx].clone());
+            }
+            Action::MembershipImportExecute { path, member_attr } => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Importing group memberships from {}...", path));
+                    self.spawn_membership_import(id, path, member_attr);
                 }
             }
-
-            Action::ConnMgrSelectFolder(path) => {
-                let desc = self
-                    .config
-                    .folder_description(&path)
-                    .unwrap_or_default()
-                    .to_string();
-                self.connection_form.view_folder(&path, &desc);
+            Action::MembershipImportComplete(summary, problems) => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(summary.clone());
+                self.log_panel.push_info(summary);
+                for problem in problems {
+                    self.log_panel.push_error(problem);
+                }
             }
-            Action::ConnMgrSaveFolderDesc(path, description) => {
-                // Update or insert the folder config
-                if let Some(existing) = self.config.folders.iter_mut().find(|f| f.path == path) {
-                    existing.description = description.clone();
+
+            // Create / Delete Entry
+            Action::ShowCreateEntryDialog(parent_dn) => {
+                if self.active_tab_id.is_some() {
+                    let schema = self.active_tab().and_then(|t| t.schema.clone());
+                    self.create_entry_dialog.set_schema(schema);
+                    self.create_entry_dialog.show(parent_dn);
                 } else {
-                    self.config.folders.push(crate::config::FolderConfig {
-                        path: path.clone(),
-                        description: description.clone(),
-                    });
+                    self.push_error("No active connection".to_string());
                 }
-                if let Err(e) = self.config.save() {
-                    self.push_error(format!("Failed to save config: {}", e));
-                } else {
-                    self.push_message("Folder description saved".to_string());
+            }
+            Action::CreateEntry { dn, attributes } => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Creating entry: {}...", dn));
+                    self.spawn_create_entry(id, dn, attributes);
+                }
+            }
+            Action::EntryCreated(dn) => {
+                self.run_entry_hook(HookEvent::EntryAdded, &dn);
+                let created_msg =
+                    format!("Created entry: {}", loom_core::dn::rdn_display_name(&dn));
+                self.status_bar.set_message(created_msg.clone());
+                self.log_panel.push_info(created_msg);
+                // Refresh parent's children in the tree
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &dn);
+                    if let Some(parent) = loom_core::dn::parent_dn(&dn) {
+                        self.spawn_load_children(id, parent.to_string());
+                    }
+                }
+            }
+            Action::DeleteEntry(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Deleting entry: {}...", dn));
+                    self.spawn_delete_entry(id, dn);
+                }
+            }
+            Action::EntryDeleted(dn) => {
+                self.run_entry_hook(HookEvent::EntryDeleted, &dn);
+                let deleted_msg =
+                    format!("Deleted entry: {}", loom_core::dn::rdn_display_name(&dn));
+                self.status_bar.set_message(deleted_msg.clone());
+                self.log_panel.push_info(deleted_msg);
+                // Clear detail panel if showing the deleted entry
+                if let Some(ref entry) = self.detail_panel.entry {
+                    if entry.dn == dn {
+                        self.detail_panel.clear();
+                    }
+                }
+                // Refresh parent's children in the tree
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &dn);
+                    if let Some(parent) = loom_core::dn::parent_dn(&dn) {
+                        self.spawn_load_children(id, parent.to_string());
+                    }
                 }
-                self.connection_form.view_folder(&path, &description);
             }
 
-            // Certificate Trust
-            Action::ShowCertTrustDialog {
-                cert_info,
-                profile,
-                password,
+            // Move entry
+            Action::CutEntry { dn, subtree } => {
+                if let Some(conn_id) = self.active_tab_id {
+                    let what = if subtree { "subtree" } else { "entry" };
+                    self.status_bar.set_message(format!(
+                        "Cut {} {} -- select the new parent and press p",
+                        what,
+                        loom_core::dn::rdn_display_name(&dn)
+                    ));
+                    self.cut_mark = Some(CutMark {
+                        conn_id,
+                        dn,
+                        subtree,
+                    });
+                }
+            }
+            Action::PasteEntry(new_parent) => self.confirm_paste(new_parent),
+            Action::MoveEntry {
+                dn,
+                new_parent,
+                subtree,
+                copy_fallback,
             } => {
-                self.cert_trust_dialog.show(*cert_info, *profile, password);
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Moving entry: {}...", dn));
+                    self.spawn_move_entry(id, dn, new_parent, subtree, copy_fallback);
+                }
             }
-            Action::TrustCertAndConnect {
-                cert_info,
-                fingerprint,
-                always,
-                profile,
-                password,
+            Action::EntryMoved {
+                old_dn,
+                new_dn,
+                copied,
             } => {
-                if always {
-                    let entry = TrustedCertEntry {
-                        host: cert_info.host.clone(),
-                        port: cert_info.port,
-                        fingerprint_sha256: fingerprint.clone(),
-                        subject: cert_info.subject.clone(),
-                    };
-                    self.trust_store.trust_always(entry);
-                    // Persist to config
-                    self.config.trusted_certificates = self.trust_store.to_config_entries();
-                    if let Err(e) = self.config.save() {
-                        self.push_error(format!("Failed to save config: {}", e));
+                self.cut_mark = None;
+                let moved_msg = match copied {
+                    None => format!("Moved entry to {}", new_dn),
+                    Some(count) => format!(
+                        "Moved {} entries to {} (copied, then deleted the originals)",
+                        count, new_dn
+                    ),
+                };
+                self.status_bar.set_message(moved_msg.clone());
+                self.log_panel.push_info(moved_msg);
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &old_dn);
+                }
+                self.relocate_in_tree(&old_dn, new_dn);
+            }
+
+            // Safe rename of an AD user
+            Action::ShowRenameUser(dn) => match self.detail_panel.entry.clone() {
+                Some(entry) if entry.dn.eq_ignore_ascii_case(&dn) => {
+                    if loom_core::util::has_attr(&entry.attributes, "sAMAccountName") {
+                        self.rename_user_dialog.show(entry);
+                    } else {
+                        self.push_error(
+                            "Safe rename is for Active Directory users (no sAMAccountName)"
+                                .to_string(),
+                        );
+                    }
+                }
+                _ => self.push_error("Select the user to rename first".to_string()),
+            },
+            Action::ShowPasswordPolicy(dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_load_password_policy(id, dn);
+                }
+            }
+            Action::PasswordPolicyLoaded(report) => {
+                self.password_policy_popup.show(*report);
+            }
+            Action::ShowNestedMembership { dn, expansion } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_nested_membership(id, dn, expansion);
+                }
+            }
+            Action::NestedMembershipLoaded(report) => {
+                self.dismiss_all_popups();
+                self.membership_popup.show(*report);
+            }
+            Action::ShowOrphanScan(base_dn) => {
+                if let Some(id) = self.active_tab_id {
+                    self.push_message(format!("Scanning {} for orphaned references...", base_dn));
+                    self.spawn_orphan_scan(id, base_dn);
+                }
+            }
+            Action::OrphanScanLoaded(report) => {
+                self.dismiss_all_popups();
+                self.orphans_panel.show(*report);
+            }
+            Action::SaveOrphanReport => {
+                if let Some(report) = self.orphans_panel.report() {
+                    let path = report_file_path("orphans");
+                    let written = std::fs::File::create(&path)
+                        .map_err(|e| CoreError::ExportError(e.to_string()))
+                        .and_then(|file| report.write_csv(file));
+                    match written {
+                        Ok(count) => self.push_message(format!(
+                            "Saved {} orphaned references to {}",
+                            count,
+                            path.display()
+                        )),
+                        Err(e) => self.push_error(format!("Saving the report failed: {}", e)),
                     }
-                    self.push_message("Certificate trusted permanently".to_string());
+                }
+            }
+            Action::CleanupOrphans(refs) => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_orphan_cleanup(id, refs);
+                }
+            }
+            Action::ShowBatchEdit(dns) => {
+                if self.active_tab_id.is_some() {
+                    self.batch_edit_dialog.show(dns);
                 } else {
-                    self.trust_store.trust_session(fingerprint);
-                    self.push_message("Certificate trusted for this session".to_string());
+                    self.push_error("No active connection".to_string());
                 }
-                // Retry the connection
-                match self.connect_with_password(&profile, &password).await {
-                    Ok(()) => {}
-                    Err(e) if is_auth_error(&e) => {
-                        self.push_error(format!("Authentication failed: {}", e));
-                        self.credential_prompt.show(*profile);
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Connection failed: {}", e));
-                    }
+            }
+            Action::BatchEditLoad { dns, attr } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_batch_edit_load(id, dns, attr);
                 }
             }
-
-            Action::CloseCurrentTab => {
+            Action::BatchEditLoaded {
+                attr,
+                values,
+                errors,
+            } => {
+                for (dn, error) in &errors {
+                    self.log_panel
+                        .push_error(format!("Reading {} of {} failed: {}", attr, dn, error));
+                }
+                if !errors.is_empty() {
+                    self.status_bar.set_error(format!(
+                        "{} entries couldn't be read and were left out",
+                        errors.len()
+                    ));
+                }
+                self.batch_edit_dialog.receive_values(&attr, values);
+            }
+            Action::BatchEditExecute { attr, edits } => {
                 if let Some(id) = self.active_tab_id {
-                    self.tabs.retain(|t| t.id != id);
-                    self.tab_bar.remove_tab(id);
-                    self.active_tab_id = self.tab_bar.active_tab;
-                    self.detail_panel.clear();
-                    if self.active_tab_id.is_none() {
-                        self.status_bar.set_disconnected();
-                        self.active_layout = ActiveLayout::Profiles;
-                        self.layout_bar.active = ActiveLayout::Profiles;
-                        self.focus.set_layout(ActiveLayout::Profiles);
-                    }
+                    self.spawn_batch_edit(id, attr, edits);
                 }
             }
-            Action::CloseTab(id) => {
-                self.tabs.retain(|t| t.id != id);
-                self.tab_bar.remove_tab(id);
-                if self.active_tab_id == Some(id) {
-                    self.active_tab_id = self.tab_bar.active_tab;
-                    self.detail_panel.clear();
-                    if self.active_tab_id.is_none() {
-                        self.status_bar.set_disconnected();
-                        self.active_layout = ActiveLayout::Profiles;
-                        self.layout_bar.active = ActiveLayout::Profiles;
-                        self.focus.set_layout(ActiveLayout::Profiles);
+            Action::BatchEditComplete {
+                attr,
+                summary,
+                outcomes,
+            } => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(summary.clone());
+                self.log_panel.push_info(summary);
+                for (dn, outcome) in &outcomes {
+                    if let EditOutcome::Failed(e) = outcome {
+                        self.log_panel
+                            .push_error(format!("Setting {} on {} failed: {}", attr, dn, e));
                     }
                 }
+                self.batch_edit_dialog.receive_outcomes(&attr, outcomes);
             }
-
-            // Tree
-            Action::TreeExpand(dn) => {
-                if !dn.is_empty() {
-                    if let Some(id) = self.active_tab_id {
-                        self.spawn_load_children(id, dn.clone());
-                        self.spawn_load_entry(id, dn);
-                    }
+            Action::FindDuplicates { attribute, base_dn } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_find_duplicates(id, attribute, base_dn);
                 }
             }
-            Action::TreeCollapse(_dn) => {}
-            Action::TreeSelect(dn) => {
-                if !dn.is_empty() {
-                    if let Some(id) = self.active_tab_id {
-                        self.spawn_load_entry(id, dn);
+            Action::DuplicatesLoaded(report) => {
+                self.dismiss_all_popups();
+                self.duplicates_panel.show(*report);
+            }
+            Action::SaveDuplicateReport => {
+                if let Some(report) = self.duplicates_panel.report() {
+                    let path = report_file_path(&format!("duplicates-{}", report.attribute));
+                    let written = std::fs::File::create(&path)
+                        .map_err(|e| CoreError::ExportError(e.to_string()))
+                        .and_then(|file| report.write_csv(file));
+                    match written {
+                        Ok(count) => self.push_message(format!(
+                            "Saved {} conflicting entries to {}",
+                            count,
+                            path.display()
+                        )),
+                        Err(e) => self.push_error(format!("Saving the report failed: {}", e)),
                     }
                 }
             }
-            Action::TreeChildrenLoaded(conn_id, parent_dn, nodes) => {
-                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
-                    tab.directory_tree.insert_children(&parent_dn, nodes);
-                    let loaded_msg = format!(
-                        "Loaded children of {}",
-                        loom_core::dn::rdn_display_name(&parent_dn)
-                    );
-                    self.status_bar.set_message(loaded_msg.clone());
-                    self.log_panel.push_info(loaded_msg);
+            Action::ShowAttributeStats(base_dn) => {
+                self.dismiss_all_popups();
+                self.attribute_stats_panel.show_setup(base_dn);
+            }
+            Action::RunAttributeStats { base_dn, sample } => {
+                if let Some(id) = self.active_tab_id {
+                    self.spawn_attribute_stats(id, base_dn, sample);
                 }
             }
-            Action::EntryLoaded(_conn_id, entry) => {
-                let schema = self.active_tab().and_then(|t| t.schema.clone());
-                self.detail_panel.set_entry(entry, schema.as_ref());
+            Action::AttributeStatsComplete(summary, stats) => {
+                self.push_message(summary);
+                self.dismiss_all_popups();
+                self.attribute_stats_panel.show(*stats);
             }
-            Action::EntryRefresh => {
-                if let (Some(id), Some(ref entry)) = (self.active_tab_id, &self.detail_panel.entry)
-                {
-                    self.spawn_load_entry(id, entry.dn.clone());
+            Action::SaveAttributeStats => {
+                if let Some(stats) = self.attribute_stats_panel.stats() {
+                    let path = report_file_path("attribute-stats");
+                    let written = std::fs::File::create(&path)
+                        .map_err(|e| CoreError::ExportError(e.to_string()))
+                        .and_then(|file| stats.write_csv(file));
+                    match written {
+                        Ok(count) => self.push_message(format!(
+                            "Saved statistics of {} attributes to {}",
+                            count,
+                            path.display()
+                        )),
+                        Err(e) => self.push_error(format!("Saving the statistics failed: {}", e)),
+                    }
                 }
             }
-
-            // Search
-            Action::SearchExecute(filter) => {
-                if let Err(e) = loom_core::filter::validate_filter(&filter) {
-                    self.status_bar.set_error(format!("Invalid filter: {}", e));
-                    // Re-activate input so user can fix the filter
-                    self.command_panel.resume_input();
-                    self.command_panel.input_buffer = filter;
-                    self.command_panel.cursor_pos = self.command_panel.input_buffer.len();
-                } else if let Some(id) = self.active_tab_id {
+            Action::OrphanCleanupComplete(summary, problems) => {
+                self.clear_entry_cache();
+                self.status_bar.set_message(summary.clone());
+                self.log_panel.push_info(summary);
+                for problem in problems {
+                    self.log_panel.push_error(problem);
+                }
+                if let Some(report) = self.orphans_panel.report() {
+                    let _ = self
+                        .action_tx
+                        .send(Action::ShowOrphanScan(report.base_dn.clone()));
+                }
+            }
+            Action::ShowAccountReports => {
+                self.dismiss_all_popups();
+                self.account_reports.show();
+            }
+            Action::RunAccountReport { report, days } => {
+                if let Some(id) = self.active_tab_id {
+                    let title = report.title(days);
                     self.status_bar
-                        .set_message(format!("Searching: {}...", filter));
-                    self.search_dialog.filter = filter.clone();
-                    self.spawn_search(id, filter);
+                        .set_message(format!("Running report: {}...", title));
+                    self.search_dialog.filter = title;
+                    self.search_dialog.columns.clear();
+                    self.search_started = Some(Instant::now());
+                    self.spawn_account_report(id, report, days);
                 } else {
-                    self.status_bar
-                        .set_error("No active connection".to_string());
+                    self.push_error("No active connection".to_string());
                 }
             }
-            Action::SearchResults(conn_id, entries) => {
-                if self.active_tab_id == Some(conn_id) {
-                    let count = entries.len();
-                    self.status_bar
-                        .set_message(format!("Found {} entries", count));
-                    // Store results in search dialog (keep popup visible)
-                    let filter = self.search_dialog.filter.clone();
-                    self.search_dialog.show_results(filter, entries);
+            Action::RunDefinedReport { name, values } => {
+                let Some(report) = self.config.report(&name).cloned() else {
+                    self.push_error(format!("No report named {} in the config", name));
+                    return;
+                };
+                let Some(id) = self.active_tab_id else {
+                    self.push_error("No active connection".to_string());
+                    return;
+                };
+                match report.values(&values) {
+                    Ok(values) => {
+                        self.status_bar
+                            .set_message(format!("Running report: {}...", report.name));
+                        self.search_dialog.filter = report.name.clone();
+                        self.search_dialog.columns = report.columns.clone();
+                        self.search_started = Some(Instant::now());
+                        self.spawn_defined_report(id, report, values);
+                    }
+                    Err(e) => self.push_error(format!("Report {}: {}", report.name, e)),
                 }
             }
-            Action::SearchFocusInput => {
-                self.dismiss_all_popups();
-                self.search_dialog.visible = true;
-                if self.search_dialog.has_results() {
-                    // Results exist — open in navigation mode (press / to edit filter)
-                    self.command_panel.soft_deactivate();
-                } else if self.command_panel.input_buffer.is_empty() {
-                    self.command_panel.activate_input();
-                } else {
-                    self.command_panel.resume_input();
+            Action::ExportSearchResults => {
+                let results = &self.search_dialog.results;
+                if !results.is_empty() {
+                    let path = results_file_path(&self.search_dialog.filter);
+                    let attributes = if self.search_dialog.columns.is_empty() {
+                        vec!["*".to_string()]
+                    } else {
+                        self.search_dialog.columns.clone()
+                    };
+                    let metadata = ExportMetadata {
+                        server: self.active_tab().map(|t| t.host.clone()),
+                        ..Default::default()
+                    };
+                    let schema = self.active_tab().and_then(|t| t.schema.clone());
+                    let started = Instant::now();
+                    match Self::write_export(
+                        results,
+                        &path,
+                        &attributes,
+                        schema.as_ref(),
+                        &metadata,
+                    ) {
+                        Ok(count) => {
+                            let mut msg = Self::export_summary(count, &path);
+                            jobs::append_elapsed(&mut msg, started);
+                            self.push_message(msg);
+                        }
+                        Err(e) => self.push_error(format!("Export failed: {}", e)),
+                    }
                 }
             }
-
-            // Live Search (debounced preview)
-            Action::LiveSearchRequest { generation, filter } => {
+            Action::SafeRenameExecute(plan) => {
                 if let Some(id) = self.active_tab_id {
-                    self.spawn_live_search(id, generation, filter);
+                    self.push_message(format!(
+                        "Renaming {} ({} steps)...",
+                        plan.dn,
+                        plan.steps.len()
+                    ));
+                    for line in plan.preview_lines() {
+                        self.log_panel.push_info(line);
+                    }
+                    self.spawn_safe_rename(id, *plan);
                 }
             }
-            Action::LiveSearchResults {
-                generation,
-                entries,
-            } => {
-                if self.command_panel.receive_live_results(generation) {
-                    // Feed live results directly into the search dialog table
-                    let filter = self.command_panel.input_buffer.clone();
-                    self.search_dialog.filter = filter;
-                    self.search_dialog.results = entries;
-                    self.search_dialog.reset_selection();
+            Action::SafeRenameComplete { old_dn, new_dn } => {
+                let msg = format!("Renamed user to {}", new_dn);
+                self.status_bar.set_message(msg.clone());
+                self.log_panel.push_info(msg);
+                if let Some(id) = self.active_tab_id {
+                    self.invalidate_cached(id, &old_dn);
+                }
+                if old_dn == new_dn {
+                    if let Some(id) = self.active_tab_id {
+                        self.spawn_load_entry(id, new_dn);
+                    }
+                } else {
+                    self.relocate_in_tree(&old_dn, new_dn);
                 }
             }
 
-            // Attribute editing
-            Action::EditAttribute(dn, attr, value) => {
-                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
-                self.attribute_editor
-                    .edit_value_with_options(dn, attr, value, is_dn, multi_valued);
-            }
-            Action::AddAttribute(dn, attr) => {
-                let (is_dn, multi_valued) = self.lookup_attr_schema(&attr);
-                self.attribute_editor
-                    .add_value_with_options(dn, attr, is_dn, multi_valued);
-            }
-            Action::ShowAddAttribute(dn) => {
-                // Build candidate list from schema
-                let candidates = if let Some(tab) = self.active_tab() {
+            // Schema
+            Action::ShowSchemaViewer => {
+                let schema_and_id = self.active_tab().map(|tab| {
                     if let Some(ref schema) = tab.schema {
-                        let present: std::collections::HashSet<String> = self
-                            .detail_panel
-                            .entry
-                            .as_ref()
-                            .map(|e| e.attributes.keys().map(|k| k.to_lowercase()).collect())
-                            .unwrap_or_default();
-
-                        let ocs: Vec<&str> = self
-                            .detail_panel
-                            .entry
-                            .as_ref()
-                            .map(|e| e.object_classes())
-                            .unwrap_or_default();
-
-                        let attr_names = if !ocs.is_empty() {
-                            schema.allowed_attributes(&ocs)
-                        } else {
-                            schema.all_user_attributes()
-                        };
-
-                        attr_names
-                            .into_iter()
-                            .filter(|name| !present.contains(&name.to_lowercase()))
-                            .map(|name| {
-                                let syntax_label = schema
-                                    .get_attribute_type(&name)
-                                    .map(|at| format!("{:?}", at.syntax))
-                                    .unwrap_or_default();
-                                (name, syntax_label)
-                            })
-                            .collect()
+                        (schema.clone(), tab.id)
                     } else {
-                        Vec::new()
+                        (SchemaCache::new(), tab.id)
+                    }
+                });
+                match schema_and_id {
+                    Some((schema, _id)) if !schema.attribute_types.is_empty() => {
+                        self.schema_viewer.show(&schema);
+                    }
+                    Some((_, id)) => {
+                        self.push_message("Loading schema...".to_string());
+                        // Mark viewer as pending so SchemaLoaded knows to show it
+                        self.schema_viewer.visible = true;
+                        self.spawn_load_schema(id);
+                    }
+                    None => {
+                        self.push_error("No active connection".to_string());
+                    }
+                }
+            }
+            Action::SchemaExport(path) => self.export_schema(&path),
+            Action::SchemaLoaded(conn_id, schema) => {
+                debug!(
+                    "SchemaLoaded: conn_id={}, attr_types={}, obj_classes={}, active_tab={:?}",
+                    conn_id,
+                    schema.attribute_types.len(),
+                    schema.object_classes.len(),
+                    self.active_tab_id,
+                );
+                // Only show schema viewer if it was already visible (user triggered ShowSchemaViewer)
+                let viewer_was_open = self.schema_viewer.visible;
+                let schema_empty = schema.attribute_types.is_empty();
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    if !schema_empty {
+                        let schema_msg = format!(
+                            "Schema loaded: {} attribute types, {} object classes",
+                            schema.attribute_types.len(),
+                            schema.object_classes.len()
+                        );
+                        self.status_bar.set_message(schema_msg.clone());
+                        self.log_panel.push_info(schema_msg);
+                    } else {
+                        debug!("SchemaLoaded: schema is empty for conn_id={}, will use fallback attributes", conn_id);
+                    }
+                    tab.schema = Some(*schema.clone());
+                    if viewer_was_open && !schema_empty {
+                        self.schema_viewer.show(&schema);
                     }
                 } else {
-                    Vec::new()
-                };
+                    debug!("SchemaLoaded: no tab found for conn_id={}", conn_id);
+                }
+                // Update command panel autocomplete if this is the active tab
+                if self.active_tab_id == Some(conn_id) {
+                    if schema_empty {
+                        debug!(
+                            "SchemaLoaded: calling set_fallback_attributes for conn_id={}",
+                            conn_id
+                        );
+                        self.command_panel.set_fallback_attributes();
+                    } else {
+                        let names = schema.all_attribute_names();
+                        debug!(
+                            "SchemaLoaded: setting {} attribute names from schema for conn_id={}",
+                            names.len(),
+                            conn_id
+                        );
+                        self.command_panel.set_attribute_names(names);
+                    }
+                    self.command_panel.set_schema(Some(*schema.clone()));
+                } else {
+                    debug!(
+                        "SchemaLoaded: conn_id={} is not active tab ({:?}), skipping command panel update",
+                        conn_id, self.active_tab_id
+                    );
+                }
+            }
+
+            // Help / About
+            Action::ShowHelp => {
+                self.help_popup.show(&self.keymap);
+            }
+            Action::ShowAbout => {
+                self.about_popup.show();
+            }
 
-                self.attribute_picker.show(dn, candidates);
+            // Log Panel
+            Action::ToggleLogPanel => {
+                self.log_panel.toggle();
             }
-            Action::DeleteAttributeValue(dn, attr, value) => {
-                let result = EditResult {
-                    dn,
-                    op: EditOp::Delete { attr, value },
-                    new_value: String::new(),
-                };
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_save_attribute(id, result);
+            Action::SaveLog(text) => {
+                let path = log_file_path();
+                match std::fs::write(&path, text) {
+                    Ok(()) => self.push_message(format!("Saved log to {}", path.display())),
+                    Err(e) => self.push_error(format!("Failed to save log: {}", e)),
                 }
             }
-            Action::SaveAttribute(result) => {
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_save_attribute(id, result);
+
+            // Operation trace
+            Action::ToggleTracePanel => {
+                self.trace_panel.toggle();
+            }
+            Action::SaveTrace(text) => {
+                let label = self
+                    .active_tab()
+                    .map(|t| t.label.clone())
+                    .unwrap_or_default();
+                let path = trace_file_path(&label);
+                match std::fs::write(&path, text) {
+                    Ok(()) => self.push_message(format!("Saved trace to {}", path.display())),
+                    Err(e) => self.push_error(format!("Failed to save trace: {}", e)),
                 }
             }
-            Action::AttributeSaved(dn) => {
-                let saved_msg =
-                    format!("Saved changes to {}", loom_core::dn::rdn_display_name(&dn));
-                self.status_bar.set_message(saved_msg.clone());
-                self.log_panel.push_info(saved_msg);
-                // Refresh the entry
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_load_entry(id, dn);
+            Action::ClearTrace => {
+                if let Some(trace) = self.active_trace() {
+                    trace.clear();
                 }
             }
 
-            // Export
-            Action::ShowExportDialog => {
-                if let Some(tab) = self.active_tab() {
-                    let base_dn = self
-                        .tree_panel
-                        .selected_dn()
-                        .cloned()
-                        .unwrap_or_else(|| tab.directory_tree.root_dn.clone());
-                    self.export_dialog.show(&base_dn);
-                } else {
-                    self.push_error("No active connection".to_string());
+            // Server metrics
+            Action::ToggleMetricsPanel => {
+                self.metrics_panel.toggle();
+                if self.metrics_panel.visible {
+                    self.refresh_metrics();
                 }
             }
-            Action::ExportExecute {
-                base_dn,
-                path,
-                filter,
-                attributes,
-            } => {
-                if let Some(id) = self.active_tab_id {
-                    self.push_message(format!("Exporting to {} (filter: {})...", path, filter));
-                    self.spawn_export(id, path, base_dn, filter, attributes);
+            Action::RefreshMetrics => self.refresh_metrics(),
+            Action::MetricsLoaded(conn_id, metrics) => {
+                self.metrics_panel.set_metrics(conn_id, *metrics);
+            }
+            Action::MetricsFailed(conn_id, error) => {
+                self.metrics_panel.set_error(conn_id, error);
+            }
+
+            // Replication
+            Action::ToggleReplicationPanel => {
+                self.replication_panel.toggle();
+                if self.replication_panel.visible {
+                    self.check_replication();
                 }
             }
-            Action::ExportComplete(msg) => {
-                self.status_bar.set_message(msg.clone());
-                self.log_panel.push_info(msg);
+            Action::RefreshReplication => self.check_replication(),
+            Action::ReplicationLoaded(conn_id, report) => {
+                self.sample_replication(conn_id, &report);
+                self.replication_panel.set_report(conn_id, *report);
+            }
+            Action::ReplicationSampled(conn_id, report) => {
+                self.sample_replication(conn_id, &report);
+            }
+            Action::ReplicationFailed(conn_id, error) => {
+                self.replication_panel.set_error(conn_id, error);
             }
 
-            // Bulk Update
-            Action::ShowBulkUpdateDialog => {
-                if self.active_tab_id.is_some() {
-                    self.bulk_update_dialog.show();
-                } else {
-                    self.push_error("No active connection".to_string());
+            // Jobs
+            Action::ToggleJobsPanel => {
+                self.jobs_panel.toggle();
+            }
+            Action::JobProgress(id, done, total) => {
+                self.jobs.set_progress(id, done, total);
+            }
+            Action::JobFinished(id, state) => {
+                if state == JobState::Cancelled {
+                    if let Some(job) = self.jobs.get(id) {
+                        self.push_message(format!("Cancelled: {}", job.title));
+                    }
                 }
+                self.jobs.finish(id, state);
             }
-            Action::BulkUpdateExecute {
-                filter,
-                attribute,
-                value,
-                op,
-            } => {
-                if let Some(id) = self.active_tab_id {
-                    use crate::components::bulk_update_dialog::BulkOp;
-                    let modification = match op {
-                        BulkOp::Replace => BulkMod::ReplaceAttribute {
-                            attr: attribute,
-                            value,
-                        },
-                        BulkOp::Add => BulkMod::AddValue {
-                            attr: attribute,
-                            value,
-                        },
-                        BulkOp::Delete => {
-                            if value.is_empty() {
-                                BulkMod::DeleteAttribute { attr: attribute }
-                            } else {
-                                BulkMod::DeleteValue {
-                                    attr: attribute,
-                                    value,
-                                }
-                            }
-                        }
-                    };
-                    self.push_message(format!("Executing bulk update: {}...", filter));
-                    self.spawn_bulk_update(id, filter, vec![modification]);
+            Action::CancelJob(id) => {
+                self.jobs.cancel(id);
+            }
+            Action::RetryJob(id) => {
+                if let Some(job) = self.jobs.get(id).filter(|j| j.can_retry()) {
+                    let (conn_id, task) = (job.conn_id, job.task.clone());
+                    if self.tabs.iter().any(|t| t.id == conn_id) {
+                        self.run_job(conn_id, task);
+                    } else {
+                        self.push_error("The job's connection is closed".to_string());
+                    }
                 }
             }
-            Action::BulkUpdateComplete(msg) => {
-                self.status_bar.set_message(msg.clone());
-                self.log_panel.push_info(msg);
+            Action::ClearFinishedJobs => {
+                self.jobs.clear_finished();
             }
 
-            // Create / Delete Entry
-            Action::ShowCreateEntryDialog(parent_dn) => {
-                if self.active_tab_id.is_some() {
-                    self.create_entry_dialog.show(parent_dn);
-                } else {
-                    self.push_error("No active connection".to_string());
+            // Popups
+            Action::ShowConfirm(msg, on_confirm) => {
+                self.confirm_dialog.show(msg, *on_confirm);
+            }
+            Action::ClosePopup => {
+                self.confirm_dialog.hide();
+                self.connect_dialog.hide();
+                self.new_connection_dialog.hide();
+                self.credential_prompt.hide();
+                self.search_dialog.hide();
+                self.command_panel.soft_deactivate();
+                self.attribute_editor.hide();
+                self.attribute_picker.hide();
+                self.export_dialog.hide();
+                self.bulk_update_dialog.hide();
+                self.create_entry_dialog.hide();
+                self.schema_viewer.hide();
+                self.help_popup.hide();
+                self.log_panel.hide();
+                self.profile_export_dialog.hide();
+                self.profile_import_dialog.hide();
+            }
+
+            // Status
+            Action::StatusMessage(msg) => {
+                self.log_panel.push_info(msg.clone());
+                self.status_bar.set_message(msg);
+            }
+            Action::ActiveServer(conn_id, server) => {
+                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
+                    match tab.server_info.iter_mut().find(|(k, _)| k == "server") {
+                        Some((_, values)) if values.first() == Some(&server) => {}
+                        Some((_, values)) => {
+                            self.log_panel.push_info(format!(
+                                "'{}' failed over from {} to {}",
+                                tab.label,
+                                values.join(", "),
+                                server
+                            ));
+                            *values = vec![server];
+                        }
+                        None => tab
+                            .server_info
+                            .insert(0, ("server".to_string(), vec![server])),
+                    }
+                    if self.active_tab_id == Some(conn_id) {
+                        self.status_bar.set_server_info(&tab.server_info);
+                    }
                 }
             }
-            Action::CreateEntry { dn, attributes } => {
+            Action::ErrorMessage(msg) => {
+                self.error_count += 1;
+                error!("{}", msg);
+                self.log_panel.push_error(msg.clone());
+                self.status_bar.set_error(msg);
+            }
+
+            // DN search
+            Action::DnSearchRequest {
+                generation,
+                query,
+                base_dn,
+            } => {
                 if let Some(id) = self.active_tab_id {
-                    self.push_message(format!("Creating entry: {}...", dn));
-                    self.spawn_create_entry(id, dn, attributes);
+                    self.spawn_dn_search(id, generation, query, base_dn);
                 }
             }
-            Action::EntryCreated(dn) => {
-                let created_msg =
-                    format!("Created entry: {}", loom_core::dn::rdn_display_name(&dn));
-                self.status_bar.set_message(created_msg.clone());
-                self.log_panel.push_info(created_msg);
-                // Refresh parent's children in the tree
-                if let Some(id) = self.active_tab_id {
-                    if let Some(parent) = loom_core::dn::parent_dn(&dn) {
-                        self.spawn_load_children(id, parent.to_string());
-                    }
+            Action::DnSearchResults {
+                generation,
+                entries,
+            } => {
+                if self.goto_dn_dialog.visible {
+                    self.goto_dn_dialog.receive_results(generation, entries);
+                } else if self.quick_open.visible {
+                    self.quick_open.receive_results(generation, entries);
+                } else {
+                    self.attribute_editor.receive_results(generation, entries);
                 }
             }
-            Action::DeleteEntry(dn) => {
+            Action::AddMultipleValues { dn, attr, values } => {
                 if let Some(id) = self.active_tab_id {
-                    self.push_message(format!("Deleting entry: {}...", dn));
-                    self.spawn_delete_entry(id, dn);
+                    self.spawn_add_multiple_values(id, dn, attr, values);
                 }
             }
-            Action::EntryDeleted(dn) => {
-                let deleted_msg =
-                    format!("Deleted entry: {}", loom_core::dn::rdn_display_name(&dn));
-                self.status_bar.set_message(deleted_msg.clone());
-                self.log_panel.push_info(deleted_msg);
-                // Clear detail panel if showing the deleted entry
-                if let Some(ref entry) = self.detail_panel.entry {
-                    if entry.dn == dn {
-                        self.detail_panel.clear();
+
+            Action::Tick => {
+                if self.idle_lock_due() {
+                    let _ = self.action_tx.send(Action::LockSession);
+                }
+                self.write_stats_if_due();
+                self.update_exporter();
+                let in_flight = self.jobs.in_flight();
+                let activity = self
+                    .tabs
+                    .iter()
+                    .filter_map(|t| in_flight.get(&t.id).map(|&n| (t.label.clone(), n)))
+                    .collect();
+                self.status_bar.set_activity(activity);
+                for log in log_capture::drain() {
+                    self.log_panel.push_captured(log);
+                }
+                if self.metrics_panel.visible {
+                    let interval = self.metrics_interval();
+                    if self
+                        .active_tab_id
+                        .is_some_and(|id| self.metrics_panel.refresh_due(id, interval))
+                    {
+                        self.refresh_metrics();
                     }
                 }
-                // Refresh parent's children in the tree
-                if let Some(id) = self.active_tab_id {
-                    if let Some(parent) = loom_core::dn::parent_dn(&dn) {
-                        self.spawn_load_children(id, parent.to_string());
+                if self.replication_panel.visible
+                    && self
+                        .active_tab_id
+                        .is_some_and(|id| self.replication_panel.is_stale(id))
+                {
+                    self.check_replication();
+                }
+                // Dispatch tick to attribute editor for debounced DN search
+                if self.attribute_editor.visible {
+                    let base_dn = self
+                        .active_tab()
+                        .map(|t| t.directory_tree.root_dn.clone())
+                        .unwrap_or_default();
+                    let tick_action = self.attribute_editor.tick(&base_dn);
+                    if !matches!(tick_action, Action::None) {
+                        let _ = self.action_tx.send(tick_action);
                     }
                 }
-            }
-
-            // Schema
-            Action::ShowSchemaViewer => {
-                let schema_and_id = self.active_tab().map(|tab| {
-                    if let Some(ref schema) = tab.schema {
-                        (schema.clone(), tab.id)
+                if self.goto_dn_dialog.visible || self.quick_open.visible {
+                    let base_dn = self
+                        .active_tab()
+                        .map(|t| t.directory_tree.root_dn.clone())
+                        .unwrap_or_default();
+                    let tick_action = if self.goto_dn_dialog.visible {
+                        self.goto_dn_dialog.tick(&base_dn)
                     } else {
-                        (SchemaCache::new(), tab.id)
+                        self.quick_open.tick(&base_dn)
+                    };
+                    if !matches!(tick_action, Action::None) {
+                        let _ = self.action_tx.send(tick_action);
                     }
-                });
-                match schema_and_id {
-                    Some((schema, _id)) if !schema.attribute_types.is_empty() => {
-                        self.schema_viewer.show(&schema);
+                }
+                // Dispatch tick to command panel for debounced live search
+                if self.command_panel.input_active {
+                    let tick_action = self.command_panel.tick();
+                    if !matches!(tick_action, Action::None) {
+                        let _ = self.action_tx.send(tick_action);
                     }
-                    Some((_, id)) => {
-                        self.push_message("Loading schema...".to_string());
-                        // Mark viewer as pending so SchemaLoaded knows to show it
-                        self.schema_viewer.visible = true;
-                        self.spawn_load_schema(id);
+                }
+            }
+            // Context Menu
+            Action::ShowContextMenu(source) => match &source {
+                ContextMenuSource::Tree { dn } => {
+                    self.context_menu.show_for_tree(dn);
+                }
+                ContextMenuSource::Detail {
+                    dn,
+                    attr_name,
+                    attr_value,
+                } => {
+                    self.context_menu.show_for_detail(dn, attr_name, attr_value);
+                }
+                ContextMenuSource::SearchResult { dn, columns } => {
+                    self.context_menu.show_for_search_result(dn, columns);
+                }
+            },
+            Action::CopyEntryAs {
+                dn,
+                format,
+                attributes,
+            } => {
+                let Some(entry) = self.loaded_entry(&dn) else {
+                    self.push_error(format!("{} isn't loaded", dn));
+                    return;
+                };
+                match export::entries_to_string(&[entry], &attributes, format) {
+                    Ok(text) => {
+                        let _ = self.action_tx.send(Action::CopyToClipboard(text));
+                    }
+                    Err(e) => self.push_error(format!("Copy failed: {}", e)),
+                }
+            }
+            Action::CopyToClipboard(text) => match arboard::Clipboard::new() {
+                Ok(mut clipboard) => match clipboard.set_text(&text) {
+                    Ok(_) => {
+                        // First line only, so a copied LDIF entry fits the status bar
+                        let first = text.lines().next().unwrap_or("");
+                        let preview =
+                            if first.chars().count() > 40 || first.len() < text.trim_end().len() {
+                                format!("{}...", first.chars().take(40).collect::<String>())
+                            } else {
+                                text.clone()
+                            };
+                        let _ = self
+                            .action_tx
+                            .send(Action::StatusMessage(format!("Copied: {}", preview)));
                     }
-                    None => {
-                        self.push_error("No active connection".to_string());
+                    Err(e) => {
+                        let _ = self
+                            .action_tx
+                            .send(Action::ErrorMessage(format!("Clipboard error: {}", e)));
                     }
+                },
+                Err(e) => {
+                    let _ = self.action_tx.send(Action::ErrorMessage(format!(
+                        "Clipboard unavailable: {}",
+                        e
+                    )));
                 }
+            },
+
+            // Vault actions
+            Action::VaultSetupPrompt => {
+                self.vault_password_dialog.show_create();
             }
-            Action::SchemaLoaded(conn_id, schema) => {
-                debug!(
-                    "SchemaLoaded: conn_id={}, attr_types={}, obj_classes={}, active_tab={:?}",
-                    conn_id,
-                    schema.attribute_types.len(),
-                    schema.object_classes.len(),
-                    self.active_tab_id,
-                );
-                // Only show schema viewer if it was already visible (user triggered ShowSchemaViewer)
-                let viewer_was_open = self.schema_viewer.visible;
-                let schema_empty = schema.attribute_types.is_empty();
-                if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == conn_id) {
-                    if !schema_empty {
-                        let schema_msg = format!(
-                            "Schema loaded: {} attribute types, {} object classes",
-                            schema.attribute_types.len(),
-                            schema.object_classes.len()
-                        );
-                        self.status_bar.set_message(schema_msg.clone());
-                        self.log_panel.push_info(schema_msg);
-                    } else {
-                        debug!("SchemaLoaded: schema is empty for conn_id={}, will use fallback attributes", conn_id);
+            Action::VaultPasswordEntered(password) => {
+                // Create a new vault with the entered master password
+                let vault_path = Vault::default_path();
+                match Vault::create(&vault_path, &password) {
+                    Ok(vault) => {
+                        self.vault = Some(vault);
+                        self.config.general.vault_enabled = true;
+                        if let Err(e) = self.config.save() {
+                            self.push_error(format!("Failed to save config: {}", e));
+                        }
+                        self.push_message("Vault created successfully".to_string());
                     }
-                    tab.schema = Some(*schema.clone());
-                    if viewer_was_open && !schema_empty {
-                        self.schema_viewer.show(&schema);
+                    Err(e) => {
+                        self.push_error(format!("Failed to create vault: {}", e));
                     }
-                } else {
-                    debug!("SchemaLoaded: no tab found for conn_id={}", conn_id);
                 }
-                // Update command panel autocomplete if this is the active tab
-                if self.active_tab_id == Some(conn_id) {
-                    if schema_empty {
-                        debug!(
-                            "SchemaLoaded: calling set_fallback_attributes for conn_id={}",
-                            conn_id
-                        );
-                        self.command_panel.set_fallback_attributes();
+            }
+            Action::RemoteCommand(cmd) if self.lock_screen.visible => {
+                self.log_panel
+                    .push_info(format!("Remote: {} (waiting for unlock)", cmd));
+                self.locked_remote_commands.push(cmd);
+            }
+            Action::RemoteCommand(cmd) => self.run_remote_command(cmd),
+            Action::RpcCall(call) if self.lock_screen.visible => {
+                call.respond(Err(RpcError::failed("the session is locked")));
+            }
+            Action::RpcCall(call) => self.run_rpc_call(call),
+            Action::LockSession => self.lock_session(),
+            Action::UnlockSession(password) => self.unlock_session(&password),
+            Action::RebindNeeded(conn_id, bind_dn) => self.queue_rebind_prompt(conn_id, bind_dn),
+            Action::RebindWithCredentials(conn_id, bind_dn, password) => {
+                self.spawn_rebind(conn_id, bind_dn, password);
+            }
+            Action::SkipRebind(conn_id) => {
+                if let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) {
+                    let msg = format!(
+                        "'{}' has no password, so it can't reconnect until it's reopened",
+                        tab.label
+                    );
+                    self.push_message(msg);
+                }
+            }
+            Action::VaultStorePassword(profile_name, password) => {
+                if let Some(ref mut vault) = self.vault {
+                    if let Err(e) = vault.set_password(&profile_name, &password) {
+                        self.push_error(format!("Failed to store password in vault: {}", e));
                     } else {
-                        let names = schema.all_attribute_names();
-                        debug!(
-                            "SchemaLoaded: setting {} attribute names from schema for conn_id={}",
-                            names.len(),
-                            conn_id
-                        );
-                        self.command_panel.set_attribute_names(names);
+                        self.push_message(format!(
+                            "Password for '{}' stored in vault",
+                            profile_name
+                        ));
                     }
-                    self.command_panel.set_schema(Some(*schema.clone()));
                 } else {
-                    debug!(
-                        "SchemaLoaded: conn_id={} is not active tab ({:?}), skipping command panel update",
-                        conn_id, self.active_tab_id
-                    );
+                    self.push_error("No vault loaded".to_string());
                 }
             }
+            Action::Render | Action::Resize(_, _) | Action::None => {}
+            _ => {}
+        }
+    }
 
-            // Help / About
-            Action::ShowHelp => {
-                self.help_popup.show(&self.keymap);
-            }
-            Action::ShowAbout => {
-                self.about_popup.show();
+    /// Remember `dn` as the most recently viewed entry for a connection.
+    /// History is keyed by the tab label (the profile name), so it survives
+    /// closing and reopening the connection.
+    fn record_recent_entry(&mut self, conn_id: ConnectionId, dn: &str) {
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let recent = self.recent_entries.entry(tab.label.clone()).or_default();
+        recent.retain(|d| !d.eq_ignore_ascii_case(dn));
+        recent.insert(0, dn.to_string());
+        recent.truncate(RECENT_ENTRIES_LIMIT);
+    }
+
+    /// The DNs already loaded in `tab` for quick open, most relevant first:
+    /// its history and recent entries, then search results under its base,
+    /// then every node of its tree.
+    fn quick_open_candidates(&self, tab: &ConnectionTab) -> Vec<String> {
+        let root = tab.directory_tree.root_dn.to_lowercase();
+        let mut dns: Vec<String> = tab.history.entries().0.iter().rev().cloned().collect();
+        if let Some(recent) = self.recent_entries.get(&tab.label) {
+            dns.extend(recent.iter().cloned());
+        }
+        dns.extend(
+            self.search_dialog
+                .results
+                .iter()
+                .filter(|e| e.dn.to_lowercase().ends_with(&root))
+                .map(|e| e.dn.clone()),
+        );
+        dns.extend(tab.directory_tree.known_dns());
+        dns
+    }
+
+    /// Hold a write up against the active tab's safety policy: refuse it
+    /// outside the policy's subtree, and ask for the stronger confirmations
+    /// the policy wants. Returns the action to go on with, or None when it
+    /// was refused or waits on the user.
+    fn enforce_safety(&mut self, action: Action) -> Option<Action> {
+        let Some(tab) = self.active_tab() else {
+            return Some(action);
+        };
+        let Some(policy) = &tab.safety else {
+            return Some(action);
+        };
+        let refused = write_targets(&action, &tab.directory_tree.root_dn)
+            .iter()
+            .find_map(|dn| policy.check_scope(dn).err());
+        let (typed_delete, bulk_limit) = (policy.confirm_delete_rdn, policy.bulk_confirm_above);
+        if let Some(msg) = refused {
+            self.push_error(msg);
+            return None;
+        }
+
+        match action {
+            Action::ShowConfirm(msg, on_confirm) if typed_delete => match *on_confirm {
+                Action::DeleteEntry(dn) => {
+                    let rdn = loom_core::dn::rdn(&dn).to_string();
+                    self.confirm_dialog
+                        .show_typed(msg, rdn, Action::DeleteEntry(dn));
+                    None
+                }
+                on_confirm => Some(Action::ShowConfirm(msg, Box::new(on_confirm))),
+            },
+            Action::BulkUpdateExecute { .. } if bulk_limit.is_some() => {
+                self.spawn_bulk_match_count(action);
+                None
             }
+            action => Some(action),
+        }
+    }
 
-            // Log Panel
-            Action::ToggleLogPanel => {
-                self.log_panel.toggle();
+    /// Count the entries a bulk update matches, then ask twice before
+    /// carrying it out when there are more than the safety policy allows.
+    fn spawn_bulk_match_count(&self, action: Action) {
+        let Action::BulkUpdateExecute { filter, .. } = &action else {
+            return;
+        };
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        let TabBackend::Live(pool) = &tab.backend else {
+            let _ = tx.send(Action::PolicyCleared(Box::new(action)));
+            return;
+        };
+        let pool = pool.clone();
+        let policy = tab.safety.clone().unwrap_or_default();
+        let base_dn = tab.directory_tree.root_dn.clone();
+        let filter = filter.clone();
+        tokio::spawn(async move {
+            let mut conn = pool.reader().await;
+            let matched = match conn.search_subtree(&base_dn, &filter, &["1.1"]).await {
+                Ok(entries) => entries.len(),
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!("Bulk update failed: {}", e)));
+                    return;
+                }
+            };
+            let cleared = Action::PolicyCleared(Box::new(action));
+            let next = if policy.needs_second_confirm(matched) {
+                Action::ShowConfirm(
+                    format!("The bulk update matches {} entries.\nUpdate them?", matched),
+                    Box::new(Action::ShowConfirm(
+                        format!(
+                            "Really change all {} entries? This can't be undone.",
+                            matched
+                        ),
+                        Box::new(cleared),
+                    )),
+                )
+            } else {
+                cleared
+            };
+            let _ = tx.send(next);
+        });
+    }
+
+    /// Check a paste target and ask for confirmation before moving the cut
+    /// entry under it, with the number of entries a subtree move takes.
+    fn confirm_paste(&mut self, new_parent: String) {
+        let Some(mark) = self.cut_mark.as_ref() else {
+            self.status_bar
+                .set_error("Nothing to paste (press x or X on an entry first)".to_string());
+            return;
+        };
+        if self.active_tab_id != Some(mark.conn_id) {
+            self.status_bar
+                .set_error("The cut entry belongs to another connection".to_string());
+            return;
+        }
+        let current_parent = loom_core::dn::parent_dn(&mark.dn).unwrap_or_default();
+        if current_parent.eq_ignore_ascii_case(&new_parent) {
+            self.status_bar
+                .set_error("The entry is already under this parent".to_string());
+            return;
+        }
+        if new_parent.eq_ignore_ascii_case(&mark.dn)
+            || loom_core::dn::is_ancestor(&new_parent, &mark.dn)
+        {
+            self.status_bar
+                .set_error("Can't move an entry under itself".to_string());
+            return;
+        }
+
+        let new_dn = format!("{},{}", loom_core::dn::rdn(&mark.dn), new_parent);
+        let dn = mark.dn.clone();
+        let subtree = mark.subtree;
+        let action = Action::MoveEntry {
+            dn: dn.clone(),
+            new_parent,
+            subtree,
+            copy_fallback: false,
+        };
+        let tx = self.action_tx.clone();
+        let pool = match self.active_tab().map(|t| &t.backend) {
+            Some(TabBackend::Live(pool)) if subtree => pool.clone(),
+            _ => {
+                let msg = format!(
+                    "Move entry (entry only -- fails if it has children)?\n{}\n\u{2192} {}",
+                    dn, new_dn
+                );
+                let _ = tx.send(Action::ShowConfirm(msg, Box::new(action)));
+                return;
             }
+        };
+        tokio::spawn(async move {
+            let mut conn = pool.reader().await;
+            let count = match conn.search_subtree(&dn, "(objectClass=*)", &["1.1"]).await {
+                Ok(entries) => entries.len(),
+                Err(e) => {
+                    let _ = tx.send(Action::ErrorMessage(format!("Move failed: {}", e)));
+                    return;
+                }
+            };
+            let _ = tx.send(Action::ShowConfirm(
+                paste_summary(&dn, &new_dn, count),
+                Box::new(action),
+            ));
+        });
+    }
 
-            // Popups
-            Action::ShowConfirm(msg, on_confirm) => {
-                self.confirm_dialog.show(msg, *on_confirm);
+    /// Drop `old_dn` from the active tab's tree and reveal the entry at
+    /// `new_dn`, after it was moved or renamed.
+    fn relocate_in_tree(&mut self, old_dn: &str, new_dn: String) {
+        if let Some(tab) = self.active_tab_mut() {
+            let tree = &mut tab.directory_tree;
+            if let Some(old_parent) =
+                loom_core::dn::parent_dn(old_dn).and_then(|parent| tree.find_node_mut(parent))
+            {
+                if let Some(children) = old_parent.children.as_mut() {
+                    children.retain(|c| !c.dn.eq_ignore_ascii_case(old_dn));
+                }
             }
-            Action::ClosePopup => {
-                self.confirm_dialog.hide();
-                self.connect_dialog.hide();
-                self.new_connection_dialog.hide();
-                self.credential_prompt.hide();
-                self.search_dialog.hide();
-                self.command_panel.soft_deactivate();
-                self.attribute_editor.hide();
-                self.attribute_picker.hide();
-                self.export_dialog.hide();
-                self.bulk_update_dialog.hide();
-                self.create_entry_dialog.hide();
-                self.schema_viewer.hide();
-                self.help_popup.hide();
-                self.log_panel.hide();
-                self.profile_export_dialog.hide();
-                self.profile_import_dialog.hide();
+            // Reload the new parent so the reveal below finds the entry
+            if let Some(new_parent) =
+                loom_core::dn::parent_dn(&new_dn).and_then(|parent| tree.find_node_mut(parent))
+            {
+                new_parent.collapse();
             }
+            let id = tab.id;
+            self.pending_reveal = Some((id, new_dn));
+            self.advance_reveal();
+        }
+    }
 
-            // Status
-            Action::StatusMessage(msg) => {
-                self.log_panel.push_info(msg.clone());
-                self.status_bar.set_message(msg);
+    /// Show `dn` in the active tab: revealed in the tree when it lies under
+    /// the tab's base, otherwise just loaded into the detail panel.
+    fn navigate_to_entry(&mut self, dn: String) {
+        let Some(tab) = self.active_tab() else {
+            return;
+        };
+        let id = tab.id;
+        if matches!(tab.directory_tree.reveal_path(&dn), RevealStep::NotFound) {
+            self.status_bar
+                .set_message(format!("{} is outside the base DN", dn));
+            self.spawn_load_entry(id, dn);
+        } else {
+            self.pending_reveal = Some((id, dn));
+            self.advance_reveal();
+        }
+    }
+
+    /// Take the next step of a pending reveal: select the entry if its
+    /// ancestors are loaded, otherwise load the next ancestor's children.
+    fn advance_reveal(&mut self) {
+        let Some((conn_id, target)) = self.pending_reveal.clone() else {
+            return;
+        };
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            self.pending_reveal = None;
+            return;
+        };
+
+        match tab.directory_tree.reveal_path(&target) {
+            RevealStep::Found(path) => {
+                self.pending_reveal = None;
+                let tree_panel = if self.active_tab_id == Some(conn_id) {
+                    Some(&mut self.tree_panel)
+                } else {
+                    self.split
+                        .as_mut()
+                        .filter(|pane| pane.tab_id == conn_id)
+                        .map(|pane| &mut pane.tree_panel)
+                };
+                if let Some(tree_panel) = tree_panel {
+                    for depth in 1..path.len() {
+                        tree_panel.tree_state.open(path[..depth].to_vec());
+                    }
+                    tree_panel.tree_state.select(path.clone());
+                }
+                if let Some(dn) = path.last() {
+                    self.spawn_load_entry(conn_id, dn.clone());
+                }
             }
-            Action::ErrorMessage(msg) => {
-                error!("{}", msg);
-                self.log_panel.push_error(msg.clone());
-                self.status_bar.set_error(msg);
+            RevealStep::Load(parent) => self.spawn_load_children(conn_id, parent),
+            RevealStep::NotFound => {
+                // Not reachable through the tree; still show the entry itself
+                self.pending_reveal = None;
+                self.spawn_load_entry(conn_id, target);
             }
+        }
+    }
 
-            // DN search
-            Action::DnSearchRequest {
-                generation,
-                query,
-                base_dn,
-            } => {
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_dn_search(id, generation, query, base_dn);
+    /// Save the open tabs so the next launch can offer to reopen them.
+    /// Ad hoc connections have no profile to reopen from and are left out.
+    fn save_session(&self) {
+        if !self.config.general.restore_session {
+            return;
+        }
+        let mut session = Session {
+            browser: self.active_layout == ActiveLayout::Browser,
+            ..Session::default()
+        };
+        for entry in &self.tab_bar.tabs {
+            let Some(tab) = self.tabs.iter().find(|t| t.id == entry.id) else {
+                continue;
+            };
+            let profile = match tab.backend {
+                TabBackend::Offline(_) => example_profile().name,
+                TabBackend::Live(_) => tab.label.clone(),
+            };
+            if profile != example_profile().name
+                && !self.config.connections.iter().any(|p| p.name == profile)
+            {
+                continue;
+            }
+            let split = self.split.as_ref().filter(|pane| pane.tab_id == tab.id);
+            let tree_panel = if self.active_tab_id == Some(tab.id) {
+                Some(&self.tree_panel)
+            } else {
+                split.map(|pane| &pane.tree_panel)
+            };
+            let last_viewed = || {
+                self.recent_entries
+                    .get(&tab.label)
+                    .and_then(|recent| recent.first())
+                    .cloned()
+            };
+            // Only the tabs on screen have a tree state to save
+            let (expanded, selected) = match tree_panel {
+                Some(panel) => {
+                    let mut expanded: Vec<String> = panel
+                        .tree_state
+                        .opened()
+                        .iter()
+                        .filter(|dn| tab.directory_tree.find_node(dn).is_some())
+                        .cloned()
+                        .collect();
+                    expanded.sort();
+                    (expanded, panel.selected_dn().cloned().or_else(last_viewed))
                 }
+                None => (Vec::new(), last_viewed()),
+            };
+            if self.active_tab_id == Some(tab.id) {
+                session.active = Some(session.tabs.len());
+            }
+            if let Some(pane) = split {
+                session.split = Some(session.tabs.len());
+                session.split_on_left = pane.on_left;
+            }
+            session.tabs.push(SessionTab {
+                profile,
+                expanded,
+                selected,
+            });
+        }
+        if let Err(e) = session.save(&Session::default_path()) {
+            warn!("Could not save the session: {}", e);
+        }
+    }
+
+    /// Reconnect the tabs of a saved session. Profiles that would prompt
+    /// for a password are skipped rather than asking for each in turn.
+    async fn restore_session(&mut self, session: Session) {
+        let names: Vec<String> = session.tabs.iter().map(|t| t.profile.clone()).collect();
+        let opened = self.connect_profiles(&names).await;
+        // Each connect may have started towards a start DN; the saved
+        // selection takes over
+        self.pending_reveal = None;
+
+        for (saved, id) in session.tabs.iter().zip(&opened) {
+            if let (Some(id), Some(dn)) = (id, &saved.selected) {
+                self.record_recent_entry(*id, dn);
+            }
+        }
+        let tab_at = |index: Option<usize>| index.and_then(|i| opened.get(i).copied().flatten());
+        let active = tab_at(session.active).or(self.active_tab_id);
+        if let Some(active) = active {
+            self.switch_to_tab(active);
+            if let Some(other) = tab_at(session.split).filter(|&id| id != active) {
+                self.show_split(other, session.split_on_left);
+            }
+        }
+        for (saved, id) in session.tabs.into_iter().zip(opened) {
+            let Some(id) = id else {
+                continue;
+            };
+            if Some(id) == active || self.split.as_ref().is_some_and(|pane| pane.tab_id == id) {
+                self.restoring.insert(
+                    id,
+                    RestoringTab {
+                        expanded: saved.expanded,
+                        selected: saved.selected,
+                        requested: HashSet::new(),
+                    },
+                );
+                self.advance_restore(id);
+            }
+        }
+
+        if !session.browser {
+            self.active_layout = ActiveLayout::Profiles;
+            self.layout_bar.active = ActiveLayout::Profiles;
+            self.focus.set_layout(ActiveLayout::Profiles);
+        }
+    }
+
+    /// Open every profile of a workspace, then show the first of them.
+    pub async fn open_workspace(&mut self, name: &str) {
+        let Some(workspace) = self.config.workspace(name).cloned() else {
+            self.push_error(format!("No workspace named '{}'", name));
+            return;
+        };
+        let opened = self.connect_profiles(&workspace.profiles).await;
+        let count = opened.iter().flatten().count();
+        if let Some(&first) = opened.iter().flatten().next() {
+            self.switch_to_tab(first);
+            self.active_layout = ActiveLayout::Browser;
+            self.layout_bar.active = ActiveLayout::Browser;
+            self.focus.set_layout(ActiveLayout::Browser);
+        }
+        self.push_message(format!(
+            "Workspace {}: {} of {} connections open",
+            workspace.name,
+            count,
+            workspace.profiles.len()
+        ));
+    }
+
+    /// Connect the named profiles one after another, returning each one's
+    /// tab. A profile that is already open reuses its tab. Profiles that
+    /// would prompt for a password are skipped rather than asking for
+    /// each in turn.
+    async fn connect_profiles(&mut self, names: &[String]) -> Vec<Option<ConnectionId>> {
+        let mut opened = Vec::new();
+        let mut skipped = Vec::new();
+        for name in names {
+            let profile = if *name == example_profile().name {
+                Some(example_profile())
+            } else {
+                self.config
+                    .connections
+                    .iter()
+                    .find(|p| p.name == *name)
+                    .cloned()
+            };
+            let Some(profile) = profile else {
+                self.push_error(format!("No profile named '{}'", name));
+                opened.push(None);
+                continue;
+            };
+            if let Some(tab) = self
+                .tabs
+                .iter()
+                .find(|t| !profile.offline && t.label == *name)
+            {
+                opened.push(Some(tab.id));
+                continue;
             }
-            Action::DnSearchResults {
-                generation,
-                entries,
-            } => {
-                self.attribute_editor.receive_results(generation, entries);
+            if !profile.offline
+                && profile.bind_dn.is_some()
+                && matches!(profile.credential_method, CredentialMethod::Prompt)
+            {
+                skipped.push(profile.name);
+                opened.push(None);
+                continue;
             }
-            Action::AddMultipleValues { dn, attr, values } => {
-                if let Some(id) = self.active_tab_id {
-                    self.spawn_add_multiple_values(id, dn, attr, values);
-                }
+            let count = self.tabs.len();
+            if let Err(e) = self.connect_profile(&profile).await {
+                self.push_error(format!("Connection to {} failed: {}", profile.name, e));
             }
+            opened.push((self.tabs.len() > count).then(|| self.tabs[count].id));
+        }
+        if !skipped.is_empty() {
+            self.push_message(format!(
+                "Not opened, these need a password: {}",
+                skipped.join(", ")
+            ));
+        }
+        opened
+    }
 
-            Action::Tick => {
-                // Dispatch tick to attribute editor for debounced DN search
-                if self.attribute_editor.visible {
-                    let base_dn = self
-                        .active_tab()
-                        .map(|t| t.directory_tree.root_dn.clone())
-                        .unwrap_or_default();
-                    let tick_action = self.attribute_editor.tick(&base_dn);
-                    if !matches!(tick_action, Action::None) {
-                        let _ = self.action_tx.send(tick_action);
-                    }
-                }
-                // Dispatch tick to command panel for debounced live search
-                if self.command_panel.input_active {
-                    let tick_action = self.command_panel.tick();
-                    if !matches!(tick_action, Action::None) {
-                        let _ = self.action_tx.send(tick_action);
-                    }
-                }
+    /// Take the next step of restoring a tab's tree: open the saved nodes
+    /// whose children are loaded and load the rest, then select the saved
+    /// entry. Gives up once the tab is no longer on screen.
+    fn advance_restore(&mut self, conn_id: ConnectionId) {
+        let Some(mut restore) = self.restoring.remove(&conn_id) else {
+            return;
+        };
+        let Some(tab) = self.tabs.iter().find(|t| t.id == conn_id) else {
+            return;
+        };
+        let tree_panel = if self.active_tab_id == Some(conn_id) {
+            &mut self.tree_panel
+        } else {
+            match self.split.as_mut().filter(|pane| pane.tab_id == conn_id) {
+                Some(pane) => &mut pane.tree_panel,
+                None => return,
             }
-            // Context Menu
-            Action::ShowContextMenu(source) => match &source {
-                ContextMenuSource::Tree { dn } => {
-                    self.context_menu.show_for_tree(dn);
-                }
-                ContextMenuSource::Detail {
-                    dn,
-                    attr_name,
-                    attr_value,
-                } => {
-                    self.context_menu.show_for_detail(dn, attr_name, attr_value);
-                }
-            },
-            Action::CopyToClipboard(text) => match arboard::Clipboard::new() {
-                Ok(mut clipboard) => match clipboard.set_text(&text) {
-                    Ok(_) => {
-                        let preview = if text.len() > 40 {
-                            format!("{}...", &text[..40])
-                        } else {
-                            text.clone()
-                        };
-                        let _ = self
-                            .action_tx
-                            .send(Action::StatusMessage(format!("Copied: {}", preview)));
-                    }
-                    Err(e) => {
-                        let _ = self
-                            .action_tx
-                            .send(Action::ErrorMessage(format!("Clipboard error: {}", e)));
-                    }
-                },
-                Err(e) => {
-                    let _ = self.action_tx.send(Action::ErrorMessage(format!(
-                        "Clipboard unavailable: {}",
-                        e
-                    )));
-                }
-            },
+        };
+        let tree = &tab.directory_tree;
 
-            // Vault actions
-            Action::VaultSetupPrompt => {
-                self.vault_password_dialog.show_create();
+        let mut load = Vec::new();
+        let mut request = |dn: &str| {
+            if restore.requested.insert(dn.to_lowercase()) {
+                load.push(dn.to_string());
             }
-            Action::VaultPasswordEntered(password) => {
-                // Create a new vault with the entered master password
-                let vault_path = Vault::default_path();
-                match Vault::create(&vault_path, &password) {
-                    Ok(vault) => {
-                        self.vault = Some(vault);
-                        self.config.general.vault_enabled = true;
-                        if let Err(e) = self.config.save() {
-                            self.push_error(format!("Failed to save config: {}", e));
+        };
+        restore.expanded.retain(|dn| match tree.find_node(dn) {
+            Some(node) if node.children.is_some() => {
+                tree_panel.tree_state.open(vec![node.dn.clone()]);
+                false
+            }
+            Some(_) => {
+                request(dn);
+                true
+            }
+            None => match tree.reveal_path(dn) {
+                RevealStep::Load(parent) => {
+                    request(&parent);
+                    true
+                }
+                // Gone from the server since the session was saved
+                _ => false,
+            },
+        });
+        if restore.expanded.is_empty() {
+            if let Some(dn) = restore.selected.take() {
+                match tree.reveal_path(&dn) {
+                    RevealStep::Found(path) => {
+                        for depth in 1..path.len() {
+                            tree_panel.tree_state.open(path[..depth].to_vec());
                         }
-                        self.push_message("Vault created successfully".to_string());
-                    }
-                    Err(e) => {
-                        self.push_error(format!("Failed to create vault: {}", e));
+                        tree_panel.tree_state.select(path);
                     }
-                }
-            }
-            Action::VaultStorePassword(profile_name, password) => {
-                if let Some(ref mut vault) = self.vault {
-                    if let Err(e) = vault.set_password(&profile_name, &password) {
-                        self.push_error(format!("Failed to store password in vault: {}", e));
-                    } else {
-                        self.push_message(format!(
-                            "Password for '{}' stored in vault",
-                            profile_name
-                        ));
+                    RevealStep::Load(parent) => {
+                        request(&parent);
+                        restore.selected = Some(dn);
                     }
-                } else {
-                    self.push_error("No vault loaded".to_string());
+                    RevealStep::NotFound => {}
                 }
             }
-            Action::Render | Action::Resize(_, _) | Action::None => {}
-            _ => {}
+        }
+
+        let done = restore.expanded.is_empty() && restore.selected.is_none();
+        for dn in load {
+            self.spawn_load_children(conn_id, dn);
+        }
+        if !done {
+            self.restoring.insert(conn_id, restore);
+        }
+    }
+
+    /// Load the last entry viewed on this connection into the detail panel.
+    fn reopen_last_entry(&mut self, conn_id: ConnectionId) {
+        let last = self
+            .tabs
+            .iter()
+            .find(|t| t.id == conn_id)
+            .and_then(|t| self.recent_entries.get(&t.label))
+            .and_then(|recent| recent.first());
+        if let Some(dn) = last {
+            self.spawn_load_entry(conn_id, dn.clone());
         }
     }
 
     fn switch_to_tab(&mut self, id: ConnectionId) {
+        // The tab is already on screen in split view: just move focus to it
+        if self.split.as_ref().is_some_and(|pane| pane.tab_id == id) {
+            self.swap_split_sides();
+            return;
+        }
+
         self.active_tab_id = Some(id);
         self.tab_bar.set_active(id);
         self.detail_panel.clear();
-        self.tree_panel.tree_state = tui_tree_widget::TreeState::default();
+        self.tree_panel.tree_state = TreeViewState::default();
+        self.reopen_last_entry(id);
+        self.apply_tab_context(id);
+    }
 
+    /// Point the status bar and search completion at a tab's connection.
+    fn apply_tab_context(&mut self, id: ConnectionId) {
         if let Some(tab) = self.tabs.iter().find(|t| t.id == id) {
             self.status_bar.set_connected(&tab.host, &tab.server_type);
+            self.status_bar.set_server_info(&tab.server_info);
             if let Some(schema) = &tab.schema {
                 self.command_panel
                     .set_attribute_names(schema.all_attribute_names());
@@ -2589,9 +7799,196 @@ impl App {
                 self.command_panel.set_attribute_names(vec![]);
                 self.command_panel.set_schema(None);
             }
+            self.command_panel
+                .set_value_samples(tab.value_samples.clone());
+        }
+    }
+
+    /// Show the next connection tab beside the active one.
+    fn open_split_view(&mut self) {
+        let Some(active) = self.active_tab_id else {
+            self.push_error("No active connection".to_string());
+            return;
+        };
+        let ids: Vec<ConnectionId> = self.tab_bar.tabs.iter().map(|t| t.id).collect();
+        let Some(pos) = ids.iter().position(|&id| id == active) else {
+            return;
+        };
+        if ids.len() < 2 {
+            self.push_error("Split view needs two open connections".to_string());
+            return;
+        }
+        let other = ids[(pos + 1) % ids.len()];
+
+        self.show_split(other, false);
+        if self.active_layout == ActiveLayout::Profiles {
+            self.active_layout = ActiveLayout::Browser;
+            self.layout_bar.active = ActiveLayout::Browser;
+            self.focus.set_layout(ActiveLayout::Browser);
+        }
+        self.status_bar.set_message(format!(
+            "Split view: {} swaps sides, {} copies across",
+            self.keymap.hint("swap_split_focus"),
+            self.keymap.hint("copy_to_other_side")
+        ));
+    }
+
+    /// Compare the entries shown on both sides of the split view with
+    /// `reference`, or stop comparing.
+    fn set_reference(&mut self, reference: Option<LdapEntry>) {
+        if let Some(pane) = self.split.as_mut() {
+            pane.detail_panel.set_reference(reference.clone());
+        }
+        self.detail_panel.set_reference(reference);
+    }
+
+    /// Show tab `other` beside the active one.
+    fn show_split(&mut self, other: ConnectionId, on_left: bool) {
+        let mut detail_panel = DetailPanel::new(self.theme.clone())
+            .with_formats(self.display_formats.clone())
+            .with_groups(self.config.display.attribute_groups());
+        detail_panel.set_reference(self.detail_panel.reference().cloned());
+        self.split = Some(SplitPane {
+            tab_id: other,
+            tree_panel: TreePanel::new(self.theme.clone()),
+            detail_panel,
+            on_left,
+        });
+        self.reopen_last_entry(other);
+    }
+
+    /// Move focus to the other side of the split view.
+    fn swap_split_sides(&mut self) {
+        let (Some(pane), Some(active)) = (self.split.as_mut(), self.active_tab_id) else {
+            return;
+        };
+        std::mem::swap(&mut self.tree_panel, &mut pane.tree_panel);
+        std::mem::swap(&mut self.detail_panel, &mut pane.detail_panel);
+        let other = std::mem::replace(&mut pane.tab_id, active);
+        pane.on_left = !pane.on_left;
+
+        self.active_tab_id = Some(other);
+        self.tab_bar.set_active(other);
+        self.apply_tab_context(other);
+    }
+
+    /// Leave split view when one of its two tabs is closed.
+    fn close_split_with(&mut self, id: ConnectionId) {
+        if self.split.as_ref().is_some_and(|pane| pane.tab_id == id)
+            || (self.split.is_some() && self.active_tab_id == Some(id))
+        {
+            self.split = None;
+        }
+    }
+
+    /// Copy the selected DN (tree) or attribute value (detail) from the
+    /// focused side to the other one, then move focus there. A DN is mapped
+    /// onto the other connection's base DN and revealed in its tree; a value
+    /// opens the editor on the other side's entry, pre-filled.
+    fn copy_to_other_side(&mut self) {
+        let Some(pane) = self.split.as_ref() else {
+            self.status_bar.set_error(format!(
+                "Split view is off (press {} to start)",
+                self.keymap.hint("toggle_split")
+            ));
+            return;
+        };
+        let other = pane.tab_id;
+
+        if self.focus.is_focused(FocusTarget::DetailPanel) {
+            let Some((attr, value)) = self
+                .detail_panel
+                .selected_attr_value()
+                .map(|(a, v)| (a.to_string(), v.to_string()))
+            else {
+                return;
+            };
+            let Some(target) = pane.detail_panel.entry.as_ref() else {
+                self.push_error("No entry open on the other side".to_string());
+                return;
+            };
+            let target_dn = target.dn.clone();
+            let existing = find_values_ci(&target.attributes, &attr).cloned();
+            if existing.as_ref().is_some_and(|vals| vals.contains(&value)) {
+                self.status_bar
+                    .set_message(format!("{} already has this {} value", target_dn, attr));
+                return;
+            }
+
+            self.swap_split_sides();
+            if self.edit_locked(&attr) {
+                return;
+            }
+            let (_, multi_valued) = self.lookup_attr_schema(&attr);
+            let existing = existing.unwrap_or_default();
+            match existing.as_slice() {
+                // A single value that can't be joined by another: replace it
+                [only] if !multi_valued => {
+                    let only = only.clone();
+                    self.attribute_editor.edit_value_from(
+                        target_dn,
+                        attr.clone(),
+                        only.clone(),
+                        value,
+                    );
+                    self.apply_value_rules(&attr, existing, Some(&only));
+                }
+                _ => {
+                    self.attribute_editor
+                        .add_value_from(target_dn, attr.clone(), value);
+                    self.apply_value_rules(&attr, existing, None);
+                }
+            }
+            self.focus.set(FocusTarget::DetailPanel);
+        } else {
+            let Some(dn) = self.tree_panel.selected_dn().cloned() else {
+                return;
+            };
+            let from_base = self
+                .active_tab()
+                .map(|t| t.directory_tree.root_dn.clone())
+                .unwrap_or_default();
+            let to_base = self
+                .tabs
+                .iter()
+                .find(|t| t.id == other)
+                .map(|t| t.directory_tree.root_dn.clone())
+                .unwrap_or_default();
+            let target = loom_core::dn::rebase(&dn, &from_base, &to_base);
+
+            self.swap_split_sides();
+            self.pending_reveal = Some((other, target));
+            self.advance_reveal();
+            self.focus.set(FocusTarget::TreePanel);
         }
     }
 
+    /// Draw the unfocused side of the split view.
+    fn render_split_pane(&mut self, frame: &mut ratatui::Frame, area: Rect) {
+        let Some(pane) = self.split.as_mut() else {
+            return;
+        };
+        let Some(tab) = self.tabs.iter().find(|t| t.id == pane.tab_id) else {
+            return;
+        };
+        let tp = self.tree_split_pct;
+        let horizontal =
+            Layout::horizontal([Constraint::Percentage(tp), Constraint::Percentage(100 - tp)])
+                .split(area);
+
+        pane.tree_panel.render_tree(
+            frame,
+            horizontal[0],
+            false,
+            &tab.directory_tree,
+            &tab.favorites,
+            &tab.search_folders,
+            &tab.stale_favorites,
+            &tab.label,
+        );
+        pane.detail_panel.render(frame, horizontal[1], false);
+    }
+
     fn render(&mut self, frame: &mut ratatui::Frame) {
         let full = frame.area();
 
@@ -2621,13 +8018,35 @@ impl App {
             ActiveLayout::Browser => {
                 self.tab_area = Some(layout_bar_area);
 
+                // Split view: the focused connection on one half, the other on the rest
+                let split_on_left = self
+                    .split
+                    .as_ref()
+                    .filter(|pane| self.tabs.iter().any(|t| t.id == pane.tab_id))
+                    .map(|pane| pane.on_left);
+                let (main_area, split_area) = match split_on_left {
+                    Some(on_left) => {
+                        let halves = Layout::horizontal([
+                            Constraint::Percentage(50),
+                            Constraint::Percentage(50),
+                        ])
+                        .split(content_area);
+                        if on_left {
+                            (halves[1], Some(halves[0]))
+                        } else {
+                            (halves[0], Some(halves[1]))
+                        }
+                    }
+                    None => (content_area, None),
+                };
+
                 // Horizontal: tree | detail (full content area, no command panel)
                 let tp = self.tree_split_pct;
                 let horizontal = Layout::horizontal([
                     Constraint::Percentage(tp),
                     Constraint::Percentage(100 - tp),
                 ])
-                .split(content_area);
+                .split(main_area);
 
                 let tree_area = horizontal[0];
                 let detail_area = horizontal[1];
@@ -2638,14 +8057,21 @@ impl App {
 
                 // Render tree panel
                 let tree_focused = self.focus.is_focused(FocusTarget::TreePanel);
-                if let Some(tab) = self.active_tab() {
-                    let items = TreePanel::build_tree_items(&tab.directory_tree.root);
-                    self.tree_panel.render_with_items(
+                if let Some(tab) = self.tabs.iter().find(|t| Some(t.id) == self.active_tab_id) {
+                    let title = if split_area.is_some() {
+                        tab.label.clone()
+                    } else {
+                        "Tree".to_string()
+                    };
+                    self.tree_panel.render_tree(
                         frame,
                         tree_area,
                         tree_focused,
-                        &items,
-                        "Tree",
+                        &tab.directory_tree,
+                        &tab.favorites,
+                        &tab.search_folders,
+                        &tab.stale_favorites,
+                        &title,
                     );
                 } else {
                     self.tree_panel.render_empty(frame, tree_area, tree_focused);
@@ -2657,6 +8083,10 @@ impl App {
                     detail_area,
                     self.focus.is_focused(FocusTarget::DetailPanel),
                 );
+
+                if let Some(area) = split_area {
+                    self.render_split_pane(frame, area);
+                }
             }
             ActiveLayout::Profiles => {
                 // Horizontal: profiles tree | connection form (full content area)
@@ -2706,8 +8136,13 @@ impl App {
             }
         }
 
-        // Status bar
-        self.status_bar.render(frame, status_area, false);
+        // Status bar, or the command line being typed over it
+        if self.command_line.visible {
+            frame.render_widget(Clear, status_area);
+            self.command_line.render(frame, status_area);
+        } else {
+            self.status_bar.render(frame, status_area, false);
+        }
 
         // Render popups on top (order matters: last rendered is on top)
         if self.confirm_dialog.visible {
@@ -2738,13 +8173,8 @@ impl App {
 
             frame.render_widget(Clear, popup_area);
 
-            let title = format!(
-                " Search: {} ({} results) ",
-                self.search_dialog.filter,
-                self.search_dialog.results.len()
-            );
             let block = Block::default()
-                .title(title)
+                .title(self.search_dialog.title())
                 .borders(Borders::ALL)
                 .border_style(self.theme.popup_border)
                 .title_style(self.theme.popup_title);
@@ -2792,6 +8222,48 @@ impl App {
         if self.bulk_update_dialog.visible {
             self.bulk_update_dialog.render(frame, full);
         }
+        if self.membership_import_dialog.visible {
+            self.membership_import_dialog.render(frame, full);
+        }
+        if self.import_dialog.visible {
+            self.import_dialog.render(frame, full);
+        }
+        if self.conflict_dialog.visible {
+            self.conflict_dialog.render(frame, full);
+        }
+        if self.move_profile_dialog.visible {
+            self.move_profile_dialog.render(frame, full);
+        }
+        if self.recent_popup.visible {
+            self.recent_popup.render(frame, full);
+        }
+        if self.history_popup.visible {
+            self.history_popup.render(frame, full);
+        }
+        if self.favorites_popup.visible {
+            self.favorites_popup.render(frame, full);
+        }
+        if self.workspace_popup.visible {
+            self.workspace_popup.render(frame, full);
+        }
+        if self.command_palette.visible {
+            self.command_palette.render(frame, full);
+        }
+        if self.goto_dn_dialog.visible {
+            self.goto_dn_dialog.render(frame, full);
+        }
+        if self.quick_open.visible {
+            self.quick_open.render(frame, full);
+        }
+        if self.batch_edit_dialog.visible {
+            self.batch_edit_dialog.render(frame, full);
+        }
+        if self.rename_user_dialog.visible {
+            self.rename_user_dialog.render(frame, full);
+        }
+        if self.report_dialog.visible {
+            self.report_dialog.render(frame, full);
+        }
         if self.profile_export_dialog.visible {
             self.profile_export_dialog.render(frame, full);
         }
@@ -2813,16 +8285,118 @@ impl App {
         if self.log_panel.visible {
             self.log_panel.render(frame, full);
         }
+        if self.jobs_panel.visible {
+            self.jobs_panel.render(frame, full, &self.jobs);
+        }
+        if self.trace_panel.visible {
+            self.trace_panel
+                .render(frame, full, self.active_trace().as_ref());
+        }
+        if self.changes_panel.visible {
+            self.changes_panel
+                .render(frame, full, self.active_changes().as_ref());
+        }
+        if self.metrics_panel.visible {
+            self.metrics_panel
+                .render(frame, full, self.metrics_interval());
+        }
+        if self.replication_panel.visible {
+            self.replication_panel.render(frame, full);
+        }
+        if self.password_policy_popup.visible {
+            self.password_policy_popup.render(frame, full);
+        }
+        if self.account_reports.visible {
+            self.account_reports.render(frame, full);
+        }
+        if self.membership_popup.visible {
+            self.membership_popup.render(frame, full);
+        }
+        if self.orphans_panel.visible {
+            self.orphans_panel.render(frame, full);
+        }
+        if self.duplicates_panel.visible {
+            self.duplicates_panel.render(frame, full);
+        }
+        if self.attribute_stats_panel.visible {
+            self.attribute_stats_panel.render(frame, full);
+        }
         if self.context_menu.visible {
             self.context_menu.render(frame, full);
         }
+        // Lock screen covers everything, including other popups
+        if self.lock_screen.visible {
+            self.lock_screen.render(frame, full);
+        }
     }
 }
 
+/// File name for a saved dry-run script: `dry-run-<label>-<timestamp>.ldif`
+/// in the working directory.
+fn dry_run_script_path(label: &str) -> PathBuf {
+    let safe: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("dry-run-{}-{}.ldif", safe, stamp))
+}
+
+/// File name for saved staged changes:
+/// `staged-changes-<label>-<timestamp>.ldif` in the working directory.
+fn staged_changes_path(label: &str) -> PathBuf {
+    let safe: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("staged-changes-{}-{}.ldif", safe, stamp))
+}
+
+/// File name for a saved operation trace: `trace-<label>-<timestamp>.log`
+/// in the working directory.
+fn trace_file_path(label: &str) -> PathBuf {
+    let safe: String = label
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("trace-{}-{}.log", safe, stamp))
+}
+
+/// File name for exported search or report results:
+/// `results-<label>-<timestamp>.csv` in the working directory.
+fn results_file_path(label: &str) -> PathBuf {
+    let safe: String = label
+        .chars()
+        .take(40)
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("results-{}-{}.csv", safe, stamp))
+}
+
+/// File name for a saved report: `<name>-<timestamp>.csv` in the working
+/// directory.
+fn report_file_path(name: &str) -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("{}-{}.csv", name, stamp))
+}
+
+/// File name for a saved log: `loom-log-<timestamp>.log` in the working
+/// directory.
+fn log_file_path() -> PathBuf {
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    PathBuf::from(format!("loom-log-{}.log", stamp))
+}
+
 /// Resolve password from the connection profile's credential method.
 /// Returns empty string for Prompt method when LOOM_PASSWORD is not set,
 /// which signals the caller to show an interactive credential prompt.
-fn resolve_password(profile: &ConnectionProfile, vault: &Option<Vault>) -> anyhow::Result<String> {
+pub fn resolve_password(
+    profile: &ConnectionProfile,
+    vault: &Option<Vault>,
+) -> anyhow::Result<String> {
     match profile.credential_method {
         CredentialMethod::Prompt => Ok(std::env::var("LOOM_PASSWORD").unwrap_or_default()),
         CredentialMethod::Command => {
@@ -2844,7 +8418,162 @@ fn resolve_password(profile: &ConnectionProfile, vault: &Option<Vault>) -> anyho
     }
 }
 
+/// The profile's connection settings, with the proxy password filled in
+/// from the vault or else the OS keychain.
+pub fn connection_settings(
+    profile: &ConnectionProfile,
+    vault: &Option<Vault>,
+) -> ConnectionSettings {
+    let mut settings = profile.to_connection_settings();
+    if let Some(proxy) = settings.proxy.as_mut() {
+        if proxy.username.is_some() && proxy.password.is_none() {
+            let key = ProxySettings::credential_key(&profile.name);
+            proxy.password = vault
+                .as_ref()
+                .and_then(|v| v.get_password(&key))
+                .map(str::to_string)
+                .or_else(|| CredentialProvider::from_keychain(&key).ok());
+        }
+    }
+    settings
+}
+
+/// "; 2 entries couldn't be read: ...", naming the first few DNs, for an
+/// export that skipped them.
+fn unread_summary(unread: &[String]) -> String {
+    const SHOWN: usize = 3;
+    let mut msg = match unread.len() {
+        1 => "; 1 entry couldn't be read: ".to_string(),
+        n => format!("; {} entries couldn't be read: ", humanize::count(n)),
+    };
+    msg.push_str(&unread[..unread.len().min(SHOWN)].join("; "));
+    if unread.len() > SHOWN {
+        msg.push_str(&format!(
+            " and {} more",
+            humanize::count(unread.len() - SHOWN)
+        ));
+    }
+    msg
+}
+
+/// The confirmation for pasting a cut subtree of `count` entries.
+fn paste_summary(dn: &str, new_dn: &str, count: usize) -> String {
+    let entries = match count {
+        1 => "1 entry".to_string(),
+        n => format!("{} entries", humanize::count(n)),
+    };
+    format!(
+        "Move {} (the entry and everything below it)?\n{}\n\u{2192} {}\n\
+         If the server can't move the subtree in place, you'll be asked \
+         before it is copied and the originals deleted instead.",
+        entries, dn, new_dn
+    )
+}
+
 /// Check if an error is an LDAP authentication/bind failure (rc=49 etc.).
+/// The entries a write changes, for the safety policy's subtree check.
+/// Writes across the tab, like bulk updates, count as writes to `base_dn`.
+fn write_targets(action: &Action, base_dn: &str) -> Vec<String> {
+    match action {
+        Action::ShowConfirm(_, on_confirm) => write_targets(on_confirm, base_dn),
+        Action::SaveAttribute(result) => vec![result.dn.clone()],
+        Action::EditInExternalEditor(dn, ..)
+        | Action::AddAttribute(dn, _)
+        | Action::DeleteAttributeValue(dn, ..)
+        | Action::AddMultipleValues { dn, .. }
+        | Action::ConflictMerge { dn, .. }
+        | Action::CreateEntry { dn, .. }
+        | Action::DeleteEntry(dn) => vec![dn.clone()],
+        Action::MoveEntry { dn, new_parent, .. } => vec![dn.clone(), new_parent.clone()],
+        Action::SafeRenameExecute(plan) => vec![plan.dn.clone(), plan.new_dn.clone()],
+        Action::ImportExecute(items) => items.iter().map(|i| i.entry.dn.clone()).collect(),
+        Action::CleanupOrphans(refs) => refs.iter().map(|r| r.dn.clone()).collect(),
+        Action::BatchEditExecute { edits, .. } => edits.iter().map(|e| e.dn.clone()).collect(),
+        Action::BulkUpdateExecute { .. } => vec![base_dn.to_string()],
+        // Groups named by DN in the file, and those found under the base
+        Action::MembershipImportExecute { path, .. } => {
+            let groups = loom_core::import::membership::import(Path::new(path))
+                .map(|csv| csv.rows)
+                .unwrap_or_default();
+            std::iter::once(base_dn.to_string())
+                .chain(
+                    groups
+                        .into_iter()
+                        .map(|row| row.group)
+                        .filter(|group| loom_core::dn::looks_like_dn(group)),
+                )
+                .collect()
+        }
+        _ => Vec::new(),
+    }
+}
+
+/// A bulk update's modifications from a transform script, one per line.
+fn script_mods(source: &str) -> Result<Vec<BulkMod>, String> {
+    loom_core::transform::Script::parse(source)
+        .map(|script| {
+            script
+                .assignments
+                .into_iter()
+                .map(BulkMod::Compute)
+                .collect()
+        })
+        .map_err(|e| format!("Script error at {}", e))
+}
+
+/// A tab's replication status, with its `contextCSN`s compared against the
+/// `peers` when the server isn't Active Directory.
+async fn load_replication(
+    pool: &ConnectionPool,
+    peers: Vec<(String, Arc<ConnectionPool>)>,
+) -> Result<ReplicationReport, CoreError> {
+    let mut conn = pool.reader().await;
+    let mut report = conn.replication_status().await?;
+    drop(conn);
+    if report.highest_usn.is_none() {
+        let mut peer_csns = Vec::new();
+        for (label, pool) in peers {
+            let mut conn = pool.reader().await;
+            peer_csns.push((label, conn.context_csns(&[]).await));
+        }
+        report.compare_with(&peer_csns);
+    }
+    Ok(report)
+}
+
+/// Store a tab's bind password again after an unlock: rebind the open
+/// connection, or reconnect with it when the lock unbound it. The password
+/// stays wiped if the server refuses it.
+async fn rebind(
+    conn: &mut LdapConnection,
+    bind_dn: String,
+    password: String,
+    reconnect: bool,
+) -> Result<(), CoreError> {
+    let result = if reconnect {
+        conn.store_credentials(bind_dn, password);
+        conn.reconnect().await
+    } else {
+        conn.bind(Some(&bind_dn), &password).await
+    };
+    if result.is_err() {
+        conn.clear_password();
+    }
+    result
+}
+
+/// Tell the app which server a reconnect landed on, when the connection
+/// fails over between several.
+fn report_server(
+    tx: &tokio::sync::mpsc::UnboundedSender<Action>,
+    conn_id: ConnectionId,
+    conn: &LdapConnection,
+) {
+    if let Some(server) = &conn.server {
+        let _ = tx.send(Action::ActiveServer(conn_id, server.target.clone()));
+    }
+}
+
 fn is_auth_error(err: &anyhow::Error) -> bool {
     let msg = err.to_string().to_lowercase();
     msg.contains("bind failed")
@@ -2864,6 +8593,15 @@ fn extract_cert_trust_error(err: &anyhow::Error) -> Option<loom_core::tls::Certi
     })
 }
 
+/// DNs of every node loaded in a tree, for completing `:goto`.
+fn loaded_dns(node: &TreeNode) -> Vec<String> {
+    let mut out = vec![node.dn.clone()];
+    for child in node.children.iter().flatten() {
+        out.extend(loaded_dns(child));
+    }
+    out
+}
+
 /// Return the built-in example directory profile.
 fn example_profile() -> ConnectionProfile {
     ConnectionProfile {
@@ -2873,11 +8611,26 @@ fn example_profile() -> ConnectionProfile {
         tls_mode: loom_core::connection::TlsMode::None,
         bind_dn: None,
         base_dn: Some("dc=contoso,dc=com".to_string()),
+        start_dn: None,
+        default_filter: None,
+        root_dse_attributes: Vec::new(),
+        favorites: Vec::new(),
+        saved_searches: Vec::new(),
+        edit_policy: None,
         credential_method: CredentialMethod::Prompt,
         password_command: None,
         page_size: 500,
         timeout_secs: 30,
+        pool_size: 3,
         relax_rules: false,
+        sasl_mechanism: None,
+        authz_id: None,
+        proxy: None,
+        ssh_tunnel: None,
+        allow_commands: false,
+        dns_srv: false,
+        shuffle_hosts: false,
+        safety: None,
         folder: None,
         read_only: false,
         offline: true,
@@ -0,0 +1,487 @@
+//! The `:` command line: parsing ex-style commands and completing their
+//! names and arguments.
+//!
+//! ```text
+//! :connect Production
+//! :goto cn=jdoe,ou=People,dc=example,dc=com
+//! :filter (&(objectClass=user)(department=IT))
+//! :savesearch IT staff (&(objectClass=user)(department=IT))
+//! :export ~/people.csv (objectClass=person)
+//! :report inactive 90
+//! :tabclose
+//! ```
+
+use std::path::Path;
+
+use loom_core::duplicates::COMMON_ATTRIBUTES;
+use loom_core::reports::Report;
+
+use crate::config::expand_home;
+use crate::theme::ThemeVariant;
+
+/// Command names with their argument, for completion and `:help`.
+pub const COMMANDS: &[(&str, &str)] = &[
+    ("connect", "<profile>"),
+    ("workspace", "<name>"),
+    ("goto", "<dn>"),
+    ("filter", "<filter>"),
+    ("savesearch", "<name> [filter]"),
+    ("export", "<path> [filter]"),
+    ("report", "<name> [days | param=value ...]"),
+    ("duplicates", "<attribute> [base dn]"),
+    ("tabnext", ""),
+    ("tabprev", ""),
+    ("tabclose", ""),
+    ("split", ""),
+    ("schema", ""),
+    ("theme", "reload | <name> [dark|light]"),
+    ("help", ""),
+    ("quit", ""),
+];
+
+/// A parsed command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ExCommand {
+    /// Connect a saved profile.
+    Connect(String),
+    /// Open every profile of a workspace.
+    Workspace(String),
+    /// Reveal an entry in the tree.
+    Goto(String),
+    /// Run a search on the active tab.
+    Filter(String),
+    /// Save a filter, or the last search's, as a folder in the tree.
+    SaveSearch {
+        name: String,
+        filter: Option<String>,
+    },
+    /// Export the active tab's subtree, optionally filtered, to a file.
+    Export {
+        path: String,
+        filter: Option<String>,
+    },
+    /// Run a canned account report, with its number of days.
+    Report {
+        report: Report,
+        days: u32,
+    },
+    /// Run a report defined in the config, with parameter values.
+    DefinedReport {
+        name: String,
+        values: Vec<(String, String)>,
+    },
+    /// List values of an attribute held by several entries, under the
+    /// tab's base DN or another one.
+    Duplicates {
+        attribute: String,
+        base_dn: Option<String>,
+    },
+    TabNext,
+    TabPrev,
+    TabClose,
+    Split,
+    Schema,
+    /// Re-read the current theme, picking up edits to its file.
+    ThemeReload,
+    /// Switch to another theme, optionally changing the variant.
+    Theme {
+        name: String,
+        variant: Option<ThemeVariant>,
+    },
+    Help,
+    Quit,
+}
+
+impl ExCommand {
+    /// Parse a command line, with or without the leading `:`. Command
+    /// names may be shortened to any unambiguous prefix, e.g. `:ex`.
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim().trim_start_matches(':').trim_start();
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let name = resolve_name(word)?;
+
+        let arg = |what: &str| {
+            if rest.is_empty() {
+                Err(format!(":{} needs {}", name, what))
+            } else {
+                Ok(rest.to_string())
+            }
+        };
+        let command = match name {
+            "connect" => ExCommand::Connect(arg("a profile name")?),
+            "workspace" => ExCommand::Workspace(arg("a workspace name")?),
+            "goto" => {
+                let dn = arg("a DN")?;
+                loom_core::dn::validate_dn(&dn)?;
+                ExCommand::Goto(dn)
+            }
+            "filter" => {
+                let filter = arg("a search filter")?;
+                loom_core::filter::validate_filter(&filter)?;
+                ExCommand::Filter(filter)
+            }
+            "savesearch" => {
+                // As with :export, a filter starts at the first " ("
+                let arg = arg("a name")?;
+                let (name, filter) = match arg.find(" (") {
+                    Some(i) => (arg[..i].trim(), Some(arg[i..].trim())),
+                    None => (arg.as_str(), None),
+                };
+                if let Some(filter) = filter {
+                    loom_core::filter::validate_filter(filter)?;
+                }
+                ExCommand::SaveSearch {
+                    name: name.to_string(),
+                    filter: filter.map(str::to_string),
+                }
+            }
+            "export" => {
+                // The filter, if any, is everything from the first " ("
+                let (path, filter) = match rest.find(" (") {
+                    Some(i) => (rest[..i].trim(), Some(rest[i..].trim())),
+                    None => (rest, None),
+                };
+                if path.is_empty() {
+                    return Err(":export needs a file path".to_string());
+                }
+                if let Some(filter) = filter {
+                    loom_core::filter::validate_filter(filter)?;
+                }
+                ExCommand::Export {
+                    path: path.to_string(),
+                    filter: filter.map(str::to_string),
+                }
+            }
+            "report" => {
+                let arg = arg("a report name")?;
+                let (name, days) = arg.split_once(char::is_whitespace).unwrap_or((&arg, ""));
+                match Report::from_name(name) {
+                    Some(report) => {
+                        let days = match days.trim() {
+                            "" => report.default_days().unwrap_or(0),
+                            days => days
+                                .parse()
+                                .map_err(|_| format!("{} is not a number of days", days))?,
+                        };
+                        ExCommand::Report { report, days }
+                    }
+                    // A [[reports]] entry: its name, then name=value pairs
+                    None => {
+                        let mut words: Vec<&str> = arg.split_whitespace().collect();
+                        let mut values = Vec::new();
+                        while let Some((param, value)) =
+                            words.last().and_then(|w| w.split_once('='))
+                        {
+                            values.insert(0, (param.to_string(), value.to_string()));
+                            words.pop();
+                        }
+                        if words.is_empty() {
+                            return Err(":report needs a report name".to_string());
+                        }
+                        ExCommand::DefinedReport {
+                            name: words.join(" "),
+                            values,
+                        }
+                    }
+                }
+            }
+            "duplicates" => {
+                let arg = arg("an attribute")?;
+                let (attribute, base_dn) = match arg.split_once(char::is_whitespace) {
+                    Some((attribute, base)) => {
+                        let base = base.trim();
+                        loom_core::dn::validate_dn(base)?;
+                        (attribute, Some(base.to_string()))
+                    }
+                    None => (arg.as_str(), None),
+                };
+                ExCommand::Duplicates {
+                    attribute: attribute.to_string(),
+                    base_dn,
+                }
+            }
+            "tabnext" => ExCommand::TabNext,
+            "tabprev" => ExCommand::TabPrev,
+            "tabclose" => ExCommand::TabClose,
+            "split" => ExCommand::Split,
+            "schema" => ExCommand::Schema,
+            "theme" => {
+                let arg = arg("reload or a theme name")?;
+                if arg.eq_ignore_ascii_case("reload") {
+                    return Ok(ExCommand::ThemeReload);
+                }
+                match arg.rsplit_once(char::is_whitespace) {
+                    Some((name, variant)) if ThemeVariant::parse(variant).is_some() => {
+                        ExCommand::Theme {
+                            name: name.trim().to_string(),
+                            variant: ThemeVariant::parse(variant),
+                        }
+                    }
+                    _ => ExCommand::Theme {
+                        name: arg,
+                        variant: None,
+                    },
+                }
+            }
+            "help" => ExCommand::Help,
+            _ => ExCommand::Quit,
+        };
+        Ok(command)
+    }
+}
+
+/// The full command name for `word`: an exact name, `q`, or a prefix
+/// matching a single command.
+fn resolve_name(word: &str) -> Result<&'static str, String> {
+    let word = word.to_ascii_lowercase();
+    if word.is_empty() {
+        return Err("empty command".to_string());
+    }
+    if word == "q" {
+        return Ok("quit");
+    }
+    if let Some((name, _)) = COMMANDS.iter().find(|(name, _)| *name == word) {
+        return Ok(name);
+    }
+    let matches: Vec<&str> = COMMANDS
+        .iter()
+        .map(|(name, _)| *name)
+        .filter(|name| name.starts_with(&word))
+        .collect();
+    match matches.as_slice() {
+        [name] => Ok(name),
+        [] => Err(format!("unknown command :{}", word)),
+        _ => Err(format!(":{} is ambiguous ({})", word, matches.join(", "))),
+    }
+}
+
+/// Names completion draws on for command arguments.
+#[derive(Debug, Clone, Default)]
+pub struct CompletionSource {
+    pub profiles: Vec<String>,
+    pub workspaces: Vec<String>,
+    /// DNs loaded in the active tab's tree.
+    pub dns: Vec<String>,
+    /// Built-in themes and theme files.
+    pub themes: Vec<String>,
+    /// Names of the `[[reports]]` in the config.
+    pub reports: Vec<String>,
+}
+
+/// Completions for the word being typed at the end of `line`: command
+/// names for the first word, then profile, workspace, DN or file names
+/// depending on the command. Returns the offset the word starts at and
+/// the candidates, each a replacement for everything from there on.
+pub fn complete(line: &str, source: &CompletionSource) -> (usize, Vec<String>) {
+    let Some((word, rest)) = line.split_once(char::is_whitespace) else {
+        let word = line.to_ascii_lowercase();
+        let names = COMMANDS
+            .iter()
+            .filter(|(name, _)| name.starts_with(&word))
+            .map(|(name, _)| name.to_string())
+            .collect();
+        return (0, names);
+    };
+    let start = line.len() - rest.trim_start().len();
+    let arg = &line[start..];
+    let starts_with = |candidate: &String| {
+        candidate
+            .to_ascii_lowercase()
+            .starts_with(&arg.to_ascii_lowercase())
+    };
+    let candidates = match resolve_name(word) {
+        Ok("connect") => source
+            .profiles
+            .iter()
+            .filter(|p| starts_with(p))
+            .cloned()
+            .collect(),
+        Ok("workspace") => source
+            .workspaces
+            .iter()
+            .filter(|w| starts_with(w))
+            .cloned()
+            .collect(),
+        Ok("theme") => std::iter::once("reload".to_string())
+            .chain(source.themes.iter().cloned())
+            .filter(|t| starts_with(t))
+            .collect(),
+        Ok("goto") => source
+            .dns
+            .iter()
+            .filter(|dn| starts_with(dn))
+            .cloned()
+            .collect(),
+        Ok("report") => Report::ALL
+            .iter()
+            .map(|r| r.name().to_string())
+            .chain(source.reports.iter().cloned())
+            .filter(|name| starts_with(name))
+            .collect(),
+        Ok("duplicates") if !arg.contains(' ') => COMMON_ATTRIBUTES
+            .iter()
+            .map(|a| a.to_string())
+            .filter(|a| starts_with(a))
+            .collect(),
+        // Only the path is completed, not a filter after it
+        Ok("export") if !arg.contains(' ') => complete_path(arg),
+        _ => Vec::new(),
+    };
+    (start, candidates)
+}
+
+/// Files and directories starting with `partial`, directories with a
+/// trailing `/`. A leading `~/` is kept as typed.
+fn complete_path(partial: &str) -> Vec<String> {
+    let (dir, prefix) = match partial.rfind('/') {
+        Some(i) => (&partial[..=i], &partial[i + 1..]),
+        None => ("", partial),
+    };
+    let listed = if dir.is_empty() {
+        Path::new(".").to_path_buf()
+    } else {
+        expand_home(dir)
+    };
+    let Ok(entries) = std::fs::read_dir(&listed) else {
+        return Vec::new();
+    };
+    let mut names: Vec<String> = entries
+        .flatten()
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            if !name.starts_with(prefix) || (name.starts_with('.') && !prefix.starts_with('.')) {
+                return None;
+            }
+            let slash = if entry.path().is_dir() { "/" } else { "" };
+            Some(format!("{}{}{}", dir, name, slash))
+        })
+        .collect();
+    names.sort();
+    names
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(
+            ExCommand::parse(":connect Production"),
+            Ok(ExCommand::Connect("Production".to_string()))
+        );
+        assert_eq!(
+            ExCommand::parse("ex ~/out.csv (cn=x)"),
+            Ok(ExCommand::Export {
+                path: "~/out.csv".to_string(),
+                filter: Some("(cn=x)".to_string()),
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(":sa Locked accounts (lockoutTime>=1)"),
+            Ok(ExCommand::SaveSearch {
+                name: "Locked accounts".to_string(),
+                filter: Some("(lockoutTime>=1)".to_string()),
+            })
+        );
+        assert_eq!(ExCommand::parse(":q"), Ok(ExCommand::Quit));
+        assert_eq!(
+            ExCommand::parse(":theme reload"),
+            Ok(ExCommand::ThemeReload)
+        );
+        assert_eq!(
+            ExCommand::parse(":theme my ocean light"),
+            Ok(ExCommand::Theme {
+                name: "my ocean".to_string(),
+                variant: Some(ThemeVariant::Light),
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(":theme light"),
+            Ok(ExCommand::Theme {
+                name: "light".to_string(),
+                variant: None,
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(":report inactive 60"),
+            Ok(ExCommand::Report {
+                report: Report::InactiveAccounts,
+                days: 60,
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(":rep expiring"),
+            Ok(ExCommand::Report {
+                report: Report::ExpiringAccounts,
+                days: 30,
+            })
+        );
+        assert_eq!(
+            ExCommand::parse(":report Stale computers days=-90 ou=x"),
+            Ok(ExCommand::DefinedReport {
+                name: "Stale computers".to_string(),
+                values: vec![
+                    ("days".to_string(), "-90".to_string()),
+                    ("ou".to_string(), "x".to_string()),
+                ],
+            })
+        );
+        assert!(ExCommand::parse(":report inactive soon").is_err());
+        assert_eq!(
+            ExCommand::parse(":dup mail ou=People, dc=x"),
+            Ok(ExCommand::Duplicates {
+                attribute: "mail".to_string(),
+                base_dn: Some("ou=People, dc=x".to_string()),
+            })
+        );
+        assert_eq!(ExCommand::parse(":tabc"), Ok(ExCommand::TabClose));
+        assert!(ExCommand::parse(":tab").unwrap_err().contains("ambiguous"));
+        assert!(ExCommand::parse(":filter cn=x)").is_err());
+        assert!(ExCommand::parse(":goto")
+            .unwrap_err()
+            .contains("needs a DN"));
+        assert!(ExCommand::parse(":frobnicate").is_err());
+    }
+
+    #[test]
+    fn test_complete() {
+        let source = CompletionSource {
+            profiles: vec!["Production".to_string(), "Staging".to_string()],
+            dns: vec!["ou=People,dc=x".to_string()],
+            ..CompletionSource::default()
+        };
+        assert_eq!(
+            complete("tab", &source),
+            (
+                0,
+                vec!["tabnext".into(), "tabprev".into(), "tabclose".into()]
+            )
+        );
+        assert_eq!(
+            complete("connect pro", &source),
+            (8, vec!["Production".into()])
+        );
+        assert_eq!(
+            complete("goto OU=p", &source),
+            (5, vec!["ou=People,dc=x".into()])
+        );
+        assert_eq!(complete("tabclose x", &source), (9, Vec::<String>::new()));
+        assert_eq!(
+            complete("duplicates s", &source),
+            (11, vec!["sAMAccountName".into()])
+        );
+
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::create_dir(dir.path().join("exports")).unwrap();
+        std::fs::write(dir.path().join("example.csv"), "").unwrap();
+        let base = format!("{}/ex", dir.path().display());
+        let (start, paths) = complete(&format!("export {}", base), &source);
+        assert_eq!(start, 7);
+        assert_eq!(
+            paths,
+            [format!("{}ample.csv", base), format!("{}ports/", base)]
+        );
+    }
+}
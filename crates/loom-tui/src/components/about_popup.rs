@@ -24,6 +24,11 @@ impl AboutPopup {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self) {
         self.visible = true;
         self.popup.show();
@@ -0,0 +1,371 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::reports::{Report, ReportDefinition};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// The duplicate value report, listed after the canned account reports.
+const DUPLICATES: usize = Report::ALL.len();
+
+/// A popup to pick a report, the canned account reports and the duplicate
+/// value report followed by the `[[reports]]` from the config, fill in its
+/// parameters and run it.
+pub struct AccountReportsPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    definitions: Vec<ReportDefinition>,
+    selected: usize,
+    /// The selected report's parameter names and the values being typed.
+    fields: Vec<(String, String)>,
+    field: usize,
+}
+
+impl AccountReportsPopup {
+    pub fn new(theme: Theme) -> Self {
+        let mut popup = Self {
+            visible: false,
+            popup: Popup::new("Reports", theme.clone()).with_size(60, 50),
+            theme,
+            definitions: Vec::new(),
+            selected: 0,
+            fields: Vec::new(),
+            field: 0,
+        };
+        popup.reset_fields();
+        popup
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    /// List the reports defined in the config after the canned ones.
+    pub fn with_definitions(mut self, definitions: Vec<ReportDefinition>) -> Self {
+        self.definitions = definitions;
+        self
+    }
+
+    pub fn show(&mut self) {
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    fn len(&self) -> usize {
+        DUPLICATES + 1 + self.definitions.len()
+    }
+
+    /// The selected canned report, or `None` for another one.
+    fn canned(&self) -> Option<Report> {
+        Report::ALL.get(self.selected).copied()
+    }
+
+    fn definition(&self) -> Option<&ReportDefinition> {
+        self.definitions
+            .get(self.selected.checked_sub(DUPLICATES + 1)?)
+    }
+
+    fn reset_fields(&mut self) {
+        self.fields = match self.canned() {
+            Some(report) => report
+                .default_days()
+                .map(|days| vec![("days".to_string(), days.to_string())])
+                .unwrap_or_default(),
+            None if self.selected == DUPLICATES => vec![
+                ("attribute".to_string(), "mail".to_string()),
+                ("base DN".to_string(), String::new()),
+            ],
+            None => self
+                .definition()
+                .map(|d| {
+                    d.parameters
+                        .iter()
+                        .map(|p| (p.name.clone(), p.default.clone().unwrap_or_default()))
+                        .collect()
+                })
+                .unwrap_or_default(),
+        };
+        self.field = 0;
+    }
+
+    fn select(&mut self, index: usize) {
+        if index != self.selected {
+            self.selected = index;
+            self.reset_fields();
+        }
+    }
+
+    fn run(&mut self) -> Action {
+        let action = match self.canned() {
+            Some(report) => {
+                let days = match self.fields.first() {
+                    Some((_, value)) => match value.parse() {
+                        Ok(days) => days,
+                        Err(_) => {
+                            return Action::ErrorMessage("Enter a number of days".to_string())
+                        }
+                    },
+                    None => 0,
+                };
+                Action::RunAccountReport { report, days }
+            }
+            None if self.selected == DUPLICATES => {
+                let value = |i: usize| self.fields[i].1.trim().to_string();
+                if value(0).is_empty() {
+                    return Action::ErrorMessage("Enter the attribute to check".to_string());
+                }
+                let base_dn = Some(value(1)).filter(|dn| !dn.is_empty());
+                Action::FindDuplicates {
+                    attribute: value(0),
+                    base_dn,
+                }
+            }
+            None => match self.definition() {
+                Some(definition) => Action::RunDefinedReport {
+                    name: definition.name.clone(),
+                    values: self.fields.clone(),
+                },
+                None => return Action::None,
+            },
+        };
+        self.hide();
+        action
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let numeric = self.canned().is_some();
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up => {
+                self.select(self.selected.saturating_sub(1));
+                Action::None
+            }
+            KeyCode::Down => {
+                self.select((self.selected + 1).min(self.len() - 1));
+                Action::None
+            }
+            KeyCode::Tab if !self.fields.is_empty() => {
+                self.field = (self.field + 1) % self.fields.len();
+                Action::None
+            }
+            KeyCode::BackTab if !self.fields.is_empty() => {
+                self.field = (self.field + self.fields.len() - 1) % self.fields.len();
+                Action::None
+            }
+            KeyCode::Char(c) if !numeric || c.is_ascii_digit() => {
+                if let Some((_, value)) = self.fields.get_mut(self.field) {
+                    value.push(c);
+                }
+                Action::None
+            }
+            KeyCode::Backspace => {
+                if let Some((_, value)) = self.fields.get_mut(self.field) {
+                    value.pop();
+                }
+                Action::None
+            }
+            KeyCode::Enter => self.run(),
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Reports ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: reports | description (1) | parameters | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(self.fields.len() as u16),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let mut items: Vec<ListItem> = Report::ALL
+            .iter()
+            .map(|r| ListItem::new(r.label()).style(self.theme.normal))
+            .collect();
+        items.push(ListItem::new("Duplicate values").style(self.theme.normal));
+        items.extend(
+            self.definitions
+                .iter()
+                .map(|d| ListItem::new(d.name.clone()).style(self.theme.normal)),
+        );
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        let mut state = ListState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(list, layout[0], &mut state);
+
+        let description = match self.definition() {
+            Some(definition) => definition.description.as_str(),
+            None if self.selected == DUPLICATES => {
+                "Values shared by several entries, e.g. mail, uid, sAMAccountName, proxyAddresses"
+            }
+            None => "",
+        };
+        let description = Line::from(Span::styled(
+            format!("  {}", description),
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(description), layout[1]);
+
+        let fields: Vec<Line> = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, (name, value))| {
+                let style = if i == self.field {
+                    self.theme.header
+                } else {
+                    self.theme.normal
+                };
+                let cursor = if i == self.field { "_" } else { "" };
+                Line::from(vec![
+                    Span::styled(format!("  {}: ", name), self.theme.dimmed),
+                    Span::styled(format!("{}{}", value, cursor), style),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(fields), layout[2]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  Tab:next parameter  Enter:run  Esc:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::reports::ReportParameter;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_pick_report_and_days() {
+        let mut popup = AccountReportsPopup::new(Theme::default());
+        popup.show();
+        popup.handle_key_event(key(KeyCode::Down));
+        assert_eq!(popup.fields, [("days".to_string(), "90".to_string())]);
+        popup.handle_key_event(key(KeyCode::Backspace));
+        popup.handle_key_event(key(KeyCode::Backspace));
+        popup.handle_key_event(key(KeyCode::Char('6')));
+        popup.handle_key_event(key(KeyCode::Char('x')));
+        popup.handle_key_event(key(KeyCode::Char('0')));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::RunAccountReport {
+                report: Report::InactiveAccounts,
+                days: 60,
+            }
+        ));
+        assert!(!popup.visible);
+
+        // Reports without a number of days have no fields
+        popup.show();
+        popup.handle_key_event(key(KeyCode::Down));
+        assert!(popup.fields.is_empty());
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::RunAccountReport {
+                report: Report::PasswordNeverExpires,
+                days: 0,
+            }
+        ));
+    }
+
+    #[test]
+    fn test_defined_report_parameters() {
+        let mut popup =
+            AccountReportsPopup::new(Theme::default()).with_definitions(vec![ReportDefinition {
+                name: "By department".to_string(),
+                filter: "(department={dept})".to_string(),
+                parameters: vec![
+                    ReportParameter {
+                        name: "dept".to_string(),
+                        default: None,
+                    },
+                    ReportParameter {
+                        name: "days".to_string(),
+                        default: Some("30".to_string()),
+                    },
+                ],
+                ..Default::default()
+            }]);
+        popup.show();
+        for _ in 0..=DUPLICATES {
+            popup.handle_key_event(key(KeyCode::Down));
+        }
+        for c in "IT".chars() {
+            popup.handle_key_event(key(KeyCode::Char(c)));
+        }
+        popup.handle_key_event(key(KeyCode::Tab));
+        popup.handle_key_event(key(KeyCode::Char('0')));
+        let Action::RunDefinedReport { name, values } = popup.handle_key_event(key(KeyCode::Enter))
+        else {
+            panic!("expected a defined report run");
+        };
+        assert_eq!(name, "By department");
+        assert_eq!(
+            values,
+            [
+                ("dept".to_string(), "IT".to_string()),
+                ("days".to_string(), "300".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicates_report() {
+        let mut popup = AccountReportsPopup::new(Theme::default());
+        popup.show();
+        for _ in 0..DUPLICATES {
+            popup.handle_key_event(key(KeyCode::Down));
+        }
+        for _ in 0.."mail".len() {
+            popup.handle_key_event(key(KeyCode::Backspace));
+        }
+        for c in "uid".chars() {
+            popup.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::FindDuplicates { ref attribute, base_dn: None } if attribute == "uid"
+        ));
+    }
+}
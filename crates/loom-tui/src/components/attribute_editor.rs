@@ -7,6 +7,7 @@ use ratatui::Frame;
 use crate::action::Action;
 use crate::theme::Theme;
 use loom_core::entry::LdapEntry;
+use loom_core::schema::ValueRules;
 
 /// Edit mode for an attribute value.
 #[derive(Debug, Clone)]
@@ -16,6 +17,16 @@ pub enum EditOp {
     Delete { attr: String, value: String },
 }
 
+impl EditOp {
+    pub fn attr(&self) -> &str {
+        match self {
+            EditOp::Replace { attr, .. } | EditOp::Add { attr } | EditOp::Delete { attr, .. } => {
+                attr
+            }
+        }
+    }
+}
+
 /// Result of a completed edit operation.
 #[derive(Debug, Clone)]
 pub struct EditResult {
@@ -41,6 +52,16 @@ pub struct AttributeEditor {
     cursor_pos: usize,
     theme: Theme,
 
+    // Multi-line mode: Enter inserts a newline, Ctrl+Enter saves
+    multiline: bool,
+    scroll_row: usize,
+
+    // Schema checks run on save; a failure is shown in place of the hint
+    rules: Option<ValueRules>,
+    /// Values the attribute keeps besides the one being edited.
+    kept_values: Vec<String>,
+    error: Option<String>,
+
     // DN search mode
     is_dn_search: bool,
     multi_select: bool,
@@ -63,6 +84,11 @@ impl AttributeEditor {
             input_buffer: String::new(),
             cursor_pos: 0,
             theme,
+            multiline: false,
+            scroll_row: 0,
+            rules: None,
+            kept_values: Vec::new(),
+            error: None,
             is_dn_search: false,
             multi_select: false,
             focus: EditorFocus::Input,
@@ -76,6 +102,10 @@ impl AttributeEditor {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     fn reset_dn_search_state(&mut self) {
         self.is_dn_search = false;
         self.multi_select = false;
@@ -89,16 +119,27 @@ impl AttributeEditor {
         self.searching = false;
     }
 
+    fn reset_validation(&mut self) {
+        self.rules = None;
+        self.kept_values.clear();
+        self.error = None;
+    }
+
     /// Open editor to replace an existing attribute value.
     pub fn edit_value(&mut self, dn: String, attr: String, current_value: String) {
+        self.edit_value_from(dn, attr, current_value.clone(), current_value);
+    }
+
+    /// Open editor to replace `old_value`, starting from different text
+    /// (used to merge a conflicting edit by hand).
+    pub fn edit_value_from(&mut self, dn: String, attr: String, old_value: String, draft: String) {
         self.dn = dn;
-        self.input_buffer = current_value.clone();
+        self.input_buffer = draft;
         self.cursor_pos = self.input_buffer.len();
-        self.op = Some(EditOp::Replace {
-            attr,
-            old_value: current_value,
-        });
+        self.op = Some(EditOp::Replace { attr, old_value });
         self.reset_dn_search_state();
+        self.reset_validation();
+        self.set_multiline_for_buffer();
         self.visible = true;
     }
 
@@ -112,7 +153,7 @@ impl AttributeEditor {
         _multi_valued: bool,
     ) {
         self.edit_value(dn, attr, current_value);
-        if is_dn {
+        if is_dn && !self.multiline {
             self.is_dn_search = true;
             // Replace mode is always single-value
             self.multi_select = false;
@@ -126,9 +167,20 @@ impl AttributeEditor {
         self.cursor_pos = 0;
         self.op = Some(EditOp::Add { attr });
         self.reset_dn_search_state();
+        self.reset_validation();
+        self.multiline = false;
+        self.scroll_row = 0;
         self.visible = true;
     }
 
+    /// Open editor to add a new value, starting from `draft`.
+    pub fn add_value_from(&mut self, dn: String, attr: String, draft: String) {
+        self.add_value(dn, attr);
+        self.input_buffer = draft;
+        self.cursor_pos = self.input_buffer.len();
+        self.set_multiline_for_buffer();
+    }
+
     /// Open editor to add a new value, with DN search options.
     pub fn add_value_with_options(
         &mut self,
@@ -144,6 +196,49 @@ impl AttributeEditor {
         }
     }
 
+    /// Check the value against the attribute's schema before saving.
+    /// `kept_values` are the values the attribute keeps alongside it.
+    pub fn set_value_rules(&mut self, rules: ValueRules, kept_values: Vec<String>) {
+        self.rules = Some(rules);
+        self.kept_values = kept_values;
+    }
+
+    /// Check the current value against the schema rules, showing any
+    /// error inline. Returns true when the value may be saved.
+    pub fn validate(&mut self) -> bool {
+        let Some(rules) = &self.rules else {
+            return true;
+        };
+        let mut values: Vec<&str> = self.kept_values.iter().map(String::as_str).collect();
+        values.push(&self.input_buffer);
+        self.error = rules.check(&values).err();
+        self.error.is_none()
+    }
+
+    /// Values that already span lines open in multi-line mode.
+    fn set_multiline_for_buffer(&mut self) {
+        self.multiline = self.input_buffer.contains('\n');
+        self.scroll_row = 0;
+    }
+
+    /// Insert pasted text at the cursor. Line breaks are kept (switching to
+    /// multi-line mode) except in DN search, where they are dropped.
+    pub fn paste(&mut self, text: &str) {
+        let text = text.replace("\r\n", "\n").replace('\r', "\n");
+        let text = if self.is_dn_search {
+            text.replace('\n', "")
+        } else {
+            text
+        };
+        if text.contains('\n') {
+            self.multiline = true;
+        }
+        self.input_buffer.insert_str(self.cursor_pos, &text);
+        self.cursor_pos += text.len();
+        self.search_dirty = true;
+        self.error = None;
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.op = None;
@@ -246,16 +341,35 @@ impl AttributeEditor {
 
     /// Handle key events in plain (non-DN-search) mode.
     fn handle_input_key_plain(&mut self, key: KeyEvent) -> Action {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
         match key.code {
+            KeyCode::Enter if ctrl => self.commit_plain(),
+            KeyCode::Char('s') if ctrl => self.commit_plain(),
+            // Alt+Enter starts a second line; in multi-line mode Enter does
+            KeyCode::Enter if self.multiline || key.modifiers.contains(KeyModifiers::ALT) => {
+                self.multiline = true;
+                self.input_buffer.insert(self.cursor_pos, '\n');
+                self.cursor_pos += 1;
+                Action::None
+            }
             KeyCode::Enter => self.commit_plain(),
             KeyCode::Esc => {
                 self.hide();
                 Action::ClosePopup
             }
+            KeyCode::Up if self.multiline => {
+                self.move_cursor_line(false);
+                Action::None
+            }
+            KeyCode::Down if self.multiline => {
+                self.move_cursor_line(true);
+                Action::None
+            }
             _ => {
                 self.edit_text(key);
                 // Auto-detect DN pattern: input matches ^\w+=
-                if !self.is_dn_search && looks_like_dn_input(&self.input_buffer) {
+                if !self.is_dn_search && !self.multiline && looks_like_dn_input(&self.input_buffer)
+                {
                     self.is_dn_search = true;
                     self.multi_select = matches!(&self.op, Some(EditOp::Add { .. }));
                     self.search_dirty = true;
@@ -357,6 +471,9 @@ impl AttributeEditor {
 
     /// Commit the current input buffer as a plain edit result.
     fn commit_plain(&mut self) -> Action {
+        if !self.validate() {
+            return Action::None;
+        }
         if let Some(op) = self.op.take() {
             let result = EditResult {
                 dn: self.dn.clone(),
@@ -386,42 +503,88 @@ impl AttributeEditor {
 
     /// Apply text editing key to input buffer.
     fn edit_text(&mut self, key: KeyEvent) {
+        self.error = None;
         match key.code {
-            KeyCode::Backspace => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                    self.input_buffer.remove(self.cursor_pos);
-                }
+            KeyCode::Backspace if self.cursor_pos > 0 => {
+                self.cursor_pos = self.prev_boundary();
+                self.input_buffer.remove(self.cursor_pos);
             }
-            KeyCode::Delete => {
-                if self.cursor_pos < self.input_buffer.len() {
-                    self.input_buffer.remove(self.cursor_pos);
-                }
+            KeyCode::Delete if self.cursor_pos < self.input_buffer.len() => {
+                self.input_buffer.remove(self.cursor_pos);
             }
-            KeyCode::Left => {
-                if self.cursor_pos > 0 {
-                    self.cursor_pos -= 1;
-                }
+            KeyCode::Left if self.cursor_pos > 0 => {
+                self.cursor_pos = self.prev_boundary();
             }
-            KeyCode::Right => {
-                if self.cursor_pos < self.input_buffer.len() {
-                    self.cursor_pos += 1;
-                }
+            KeyCode::Right if self.cursor_pos < self.input_buffer.len() => {
+                self.cursor_pos += self.input_buffer[self.cursor_pos..]
+                    .chars()
+                    .next()
+                    .map_or(0, char::len_utf8);
             }
             KeyCode::Home => {
-                self.cursor_pos = 0;
+                self.cursor_pos = if self.multiline {
+                    self.line_start(self.cursor_pos)
+                } else {
+                    0
+                };
             }
             KeyCode::End => {
-                self.cursor_pos = self.input_buffer.len();
+                self.cursor_pos = if self.multiline {
+                    self.line_end(self.cursor_pos)
+                } else {
+                    self.input_buffer.len()
+                };
             }
             KeyCode::Char(c) => {
                 self.input_buffer.insert(self.cursor_pos, c);
-                self.cursor_pos += 1;
+                self.cursor_pos += c.len_utf8();
             }
             _ => {}
         }
     }
 
+    fn prev_boundary(&self) -> usize {
+        self.input_buffer[..self.cursor_pos]
+            .char_indices()
+            .next_back()
+            .map_or(0, |(i, _)| i)
+    }
+
+    /// Byte offset where the line holding `pos` starts.
+    fn line_start(&self, pos: usize) -> usize {
+        self.input_buffer[..pos].rfind('\n').map_or(0, |i| i + 1)
+    }
+
+    /// Byte offset of the newline ending the line holding `pos` (or the end).
+    fn line_end(&self, pos: usize) -> usize {
+        self.input_buffer[pos..]
+            .find('\n')
+            .map_or(self.input_buffer.len(), |i| pos + i)
+    }
+
+    /// Move the cursor to the same column of the previous or next line.
+    fn move_cursor_line(&mut self, down: bool) {
+        let start = self.line_start(self.cursor_pos);
+        let column = self.input_buffer[start..self.cursor_pos].chars().count();
+        let target_start = if down {
+            let end = self.line_end(self.cursor_pos);
+            if end == self.input_buffer.len() {
+                return;
+            }
+            end + 1
+        } else {
+            if start == 0 {
+                return;
+            }
+            self.line_start(start - 1)
+        };
+        let target_end = self.line_end(target_start);
+        self.cursor_pos = self.input_buffer[target_start..target_end]
+            .char_indices()
+            .nth(column)
+            .map_or(target_end, |(i, _)| target_start + i);
+    }
+
     pub fn render(&mut self, frame: &mut Frame, full: Rect) {
         if !self.visible {
             return;
@@ -429,6 +592,8 @@ impl AttributeEditor {
 
         if self.is_dn_search {
             self.render_dn_search(frame, full);
+        } else if self.multiline {
+            self.render_multiline(frame, full);
         } else {
             self.render_plain(frame, full);
         }
@@ -472,9 +637,87 @@ impl AttributeEditor {
         self.render_input_line(frame, layout[1]);
 
         // Hint
-        let hint = Line::from(Span::styled(
-            "Enter: save  Esc: cancel  Ctrl+Space: DN search",
-            self.theme.dimmed,
+        let hint = self.hint_line(
+            "Enter: save  Alt+Enter: new line  Esc: cancel  Ctrl+Space: DN search".to_string(),
+        );
+        frame.render_widget(Paragraph::new(hint), layout[2]);
+    }
+
+    /// Taller editor for values with line breaks; long lines soft-wrap.
+    fn render_multiline(&mut self, frame: &mut Frame, full: Rect) {
+        let popup_width = (full.width as u32 * 80 / 100).min(110) as u16;
+        // Wrap one column short so the cursor fits after a full row
+        let wrap_width = (popup_width as usize).saturating_sub(3).max(1);
+        let rows = wrap_rows(&self.input_buffer, wrap_width);
+        let text_height = (rows.len() as u16).clamp(3, full.height.saturating_sub(6).max(3));
+        let popup_height = (text_height + 4).min(full.height); // border + DN + hint
+
+        let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
+        let y = full.y + (full.height.saturating_sub(popup_height)) / 2;
+        let area = Rect::new(x, y, popup_width, popup_height);
+
+        frame.render_widget(Clear, area);
+
+        let title = format!(" {} ", self.op_label());
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // DN
+            Constraint::Min(1),    // Text
+            Constraint::Length(1), // Hint
+        ])
+        .split(inner);
+
+        let dn_line = Line::from(vec![
+            Span::styled("DN: ", self.theme.header),
+            Span::styled(&self.dn, self.theme.dimmed),
+        ]);
+        frame.render_widget(Paragraph::new(dn_line), layout[0]);
+
+        // Keep the cursor row in view
+        let cursor_row = rows
+            .iter()
+            .rposition(|(start, _)| *start <= self.cursor_pos)
+            .unwrap_or(0);
+        let visible = layout[1].height.max(1) as usize;
+        if cursor_row < self.scroll_row {
+            self.scroll_row = cursor_row;
+        } else if cursor_row >= self.scroll_row + visible {
+            self.scroll_row = cursor_row + 1 - visible;
+        }
+
+        let lines: Vec<Line> = rows
+            .iter()
+            .enumerate()
+            .skip(self.scroll_row)
+            .take(visible)
+            .map(|(i, &(start, end))| {
+                let row = &self.input_buffer[start..end];
+                if i != cursor_row {
+                    return Line::from(Span::styled(row, self.theme.normal));
+                }
+                let (before, after) = row.split_at(self.cursor_pos - start);
+                let mut chars = after.chars();
+                let under = chars.next().map_or("_".to_string(), |c| c.to_string());
+                Line::from(vec![
+                    Span::styled(before, self.theme.normal),
+                    Span::styled(under, self.theme.selected),
+                    Span::styled(chars.as_str(), self.theme.normal),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        let hint = self.hint_line(format!(
+            "Enter: new line  Ctrl+Enter/Ctrl+S: save  Esc: cancel  ({} lines)",
+            self.input_buffer.split('\n').count()
         ));
         frame.render_widget(Paragraph::new(hint), layout[2]);
     }
@@ -598,8 +841,15 @@ impl AttributeEditor {
         } else {
             "Enter: save  Tab: results  Esc: cancel".to_string()
         };
-        let hint = Line::from(Span::styled(hint_text, self.theme.dimmed));
-        frame.render_widget(Paragraph::new(hint), layout[3]);
+        frame.render_widget(Paragraph::new(self.hint_line(hint_text)), layout[3]);
+    }
+
+    /// The key hints, or the validation error from the last save attempt.
+    fn hint_line(&self, hint: String) -> Line<'_> {
+        match &self.error {
+            Some(e) => Line::from(Span::styled(e.as_str(), self.theme.error)),
+            None => Line::from(Span::styled(hint, self.theme.dimmed)),
+        }
     }
 
     fn render_input_line(&self, frame: &mut Frame, area: Rect) {
@@ -632,6 +882,25 @@ impl AttributeEditor {
     }
 }
 
+/// Split `text` into display rows of at most `width` characters, breaking
+/// at newlines. Returns each row's byte range, newlines excluded.
+fn wrap_rows(text: &str, width: usize) -> Vec<(usize, usize)> {
+    let mut rows = Vec::new();
+    let mut offset = 0;
+    for line in text.split('\n') {
+        let mut start = offset;
+        for (count, (i, _)) in line.char_indices().enumerate() {
+            if count > 0 && count % width == 0 {
+                rows.push((start, offset + i));
+                start = offset + i;
+            }
+        }
+        rows.push((start, offset + line.len()));
+        offset += line.len() + 1;
+    }
+    rows
+}
+
 /// Check if input looks like a DN fragment (matches `^\w+=`).
 fn looks_like_dn_input(input: &str) -> bool {
     if input.len() < 3 {
@@ -729,6 +998,85 @@ mod tests {
         assert_eq!(ldap_escape("a\\b"), "a\\5cb");
     }
 
+    fn key(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_schema_errors_block_save() {
+        use loom_core::schema::AttributeSyntax;
+
+        let mut editor = AttributeEditor::new(Theme::load("dark"));
+        editor.add_value("cn=a".to_string(), "uidNumber".to_string());
+        editor.set_value_rules(
+            ValueRules {
+                attr: "uidNumber".to_string(),
+                syntax: AttributeSyntax::Integer,
+                single_value: false,
+            },
+            Vec::new(),
+        );
+        editor.paste("10x");
+        assert!(matches!(
+            editor.handle_key_event(key(KeyCode::Enter, KeyModifiers::NONE)),
+            Action::None
+        ));
+        assert!(editor.visible);
+        assert!(editor.error.as_deref().unwrap().contains("not an integer"));
+
+        // Editing clears the error; a valid value saves
+        editor.handle_key_event(key(KeyCode::Backspace, KeyModifiers::NONE));
+        assert!(editor.error.is_none());
+        assert!(matches!(
+            editor.handle_key_event(key(KeyCode::Enter, KeyModifiers::NONE)),
+            Action::SaveAttribute(_)
+        ));
+    }
+
+    #[test]
+    fn test_multiline_editing_keeps_newlines() {
+        let mut editor = AttributeEditor::new(Theme::load("dark"));
+        editor.edit_value(
+            "cn=a".to_string(),
+            "description".to_string(),
+            "first".to_string(),
+        );
+        assert!(!editor.multiline);
+
+        // Alt+Enter switches to multi-line; after that Enter is a newline
+        editor.handle_key_event(key(KeyCode::Enter, KeyModifiers::ALT));
+        editor.handle_key_event(key(KeyCode::Char('x'), KeyModifiers::NONE));
+        editor.handle_key_event(key(KeyCode::Enter, KeyModifiers::NONE));
+        editor.paste("y\r\nz");
+        assert_eq!(editor.input_buffer, "first\nx\ny\nz");
+
+        // Up keeps the column where the line is long enough
+        editor.handle_key_event(key(KeyCode::Up, KeyModifiers::NONE));
+        editor.handle_key_event(key(KeyCode::Up, KeyModifiers::NONE));
+        editor.handle_key_event(key(KeyCode::Up, KeyModifiers::NONE));
+        assert_eq!(editor.cursor_pos, 1);
+
+        match editor.handle_key_event(key(KeyCode::Enter, KeyModifiers::CONTROL)) {
+            Action::SaveAttribute(result) => assert_eq!(result.new_value, "first\nx\ny\nz"),
+            other => panic!("expected SaveAttribute, got {:?}", other),
+        }
+
+        // Existing multi-line values open in multi-line mode
+        editor.edit_value("cn=a".to_string(), "info".to_string(), "a\nb".to_string());
+        assert!(editor.multiline);
+    }
+
+    #[test]
+    fn test_wrap_rows() {
+        assert_eq!(wrap_rows("", 4), [(0, 0)]);
+        assert_eq!(
+            wrap_rows("abcdefghij\n\nxy", 4),
+            [(0, 4), (4, 8), (8, 10), (11, 11), (12, 14)]
+        );
+        // Wrapping counts characters, not bytes
+        assert_eq!(wrap_rows("ééé", 2), [(0, 4), (4, 6)]);
+    }
+
     #[test]
     fn test_looks_like_dn_input() {
         assert!(looks_like_dn_input("cn=john"));
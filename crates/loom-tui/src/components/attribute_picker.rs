@@ -47,6 +47,10 @@ impl AttributePicker {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     /// Open the picker with the given DN and candidate attributes.
     pub fn show(&mut self, dn: String, candidates: Vec<(String, String)>) {
         self.dn = dn;
@@ -0,0 +1,278 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::attribute_stats::SubtreeStats;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Entries read by default; an empty sample size scans the whole subtree.
+const DEFAULT_SAMPLE: &str = "1000";
+
+/// A panel to start an attribute statistics scan of a subtree, and then to
+/// show its results.
+pub struct AttributeStatsPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    base_dn: String,
+    /// The sample size being typed, before a scan.
+    sample: String,
+    stats: Option<SubtreeStats>,
+    selected: usize,
+}
+
+impl AttributeStatsPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Attribute Statistics", theme.clone()).with_size(85, 70),
+            theme,
+            base_dn: String::new(),
+            sample: DEFAULT_SAMPLE.to_string(),
+            stats: None,
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    /// Ask how much of the subtree under `base_dn` to scan.
+    pub fn show_setup(&mut self, base_dn: String) {
+        self.base_dn = base_dn;
+        self.sample = DEFAULT_SAMPLE.to_string();
+        self.stats = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn show(&mut self, stats: SubtreeStats) {
+        self.base_dn = stats.base_dn.clone();
+        self.stats = Some(stats);
+        self.selected = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn stats(&self) -> Option<&SubtreeStats> {
+        self.stats.as_ref()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if matches!(key.code, KeyCode::Esc | KeyCode::Char('q')) {
+            self.hide();
+            return Action::ClosePopup;
+        }
+        let Some(stats) = &self.stats else {
+            return self.handle_setup_key(key);
+        };
+        let len = stats.attributes.len();
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('s') => Action::SaveAttributeStats,
+            KeyCode::Char('r') => {
+                self.show_setup(self.base_dn.clone());
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_setup_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char(c) if c.is_ascii_digit() => {
+                self.sample.push(c);
+                Action::None
+            }
+            KeyCode::Backspace => {
+                self.sample.pop();
+                Action::None
+            }
+            KeyCode::Enter => {
+                let sample = match self.sample.parse::<usize>() {
+                    Ok(0) => {
+                        return Action::ErrorMessage(
+                            "Sample at least one entry, or leave it empty".to_string(),
+                        )
+                    }
+                    Ok(n) => Some(n),
+                    Err(_) => None,
+                };
+                self.hide();
+                Action::RunAttributeStats {
+                    base_dn: self.base_dn.clone(),
+                    sample,
+                }
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Attribute Statistics of {} ", self.base_dn))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: body | summary (1) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let Some(stats) = &self.stats else {
+            let lines = vec![
+                Line::from(vec![
+                    Span::styled("  Entries to sample: ", self.theme.dimmed),
+                    Span::styled(format!("{}_", self.sample), self.theme.header),
+                ]),
+                Line::default(),
+                Line::from(Span::styled(
+                    "  Leave empty to scan the whole subtree. The scan runs as a background job.",
+                    self.theme.dimmed,
+                )),
+            ];
+            frame.render_widget(Paragraph::new(lines), layout[0]);
+            let hints = Line::from(Span::styled("Enter:scan  Esc:close", self.theme.dimmed));
+            frame.render_widget(Paragraph::new(hints), layout[2]);
+            return;
+        };
+
+        let header = Row::new([
+            "Attribute",
+            "Entries",
+            "Coverage",
+            "Values",
+            "Distinct",
+            "Min len",
+            "Max len",
+        ])
+        .style(self.theme.header);
+        let rows: Vec<Row> = stats
+            .attributes
+            .iter()
+            .map(|a| {
+                Row::new(vec![
+                    Cell::from(a.name.clone()),
+                    Cell::from(a.entries.to_string()),
+                    Cell::from(format!("{:.1}%", a.coverage(stats.entries_scanned))),
+                    Cell::from(a.values.to_string()),
+                    Cell::from(a.distinct.to_string()),
+                    Cell::from(a.min_len.to_string()),
+                    Cell::from(a.max_len.to_string()),
+                ])
+                .style(self.theme.normal)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Min(20),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(9),
+                Constraint::Length(8),
+                Constraint::Length(8),
+            ],
+        )
+        .header(header)
+        .highlight_style(self.theme.selected);
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, layout[0], &mut state);
+
+        let scope = if stats.sampled { "a sample of" } else { "all" };
+        let summary = format!(
+            "{} attributes in {} {} entries",
+            stats.attributes.len(),
+            scope,
+            stats.entries_scanned
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(summary, self.theme.dimmed))),
+            layout[1],
+        );
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  s:save CSV  r:scan again  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_setup_sample_size() {
+        let mut panel = AttributeStatsPanel::new(Theme::default());
+        panel.show_setup("ou=People,dc=x".to_string());
+        panel.handle_key_event(key(KeyCode::Backspace));
+        panel.handle_key_event(key(KeyCode::Char('x')));
+        panel.handle_key_event(key(KeyCode::Char('5')));
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Enter)),
+            Action::RunAttributeStats { ref base_dn, sample: Some(1005) }
+                if base_dn == "ou=People,dc=x"
+        ));
+        assert!(!panel.visible);
+
+        // Empty means the whole subtree
+        panel.show_setup("dc=x".to_string());
+        for _ in 0..DEFAULT_SAMPLE.len() {
+            panel.handle_key_event(key(KeyCode::Backspace));
+        }
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Enter)),
+            Action::RunAttributeStats { sample: None, .. }
+        ));
+
+        // Results go back to the setup to scan again
+        panel.show(SubtreeStats {
+            base_dn: "dc=x".to_string(),
+            ..Default::default()
+        });
+        panel.handle_key_event(key(KeyCode::Char('r')));
+        assert!(panel.stats().is_none());
+        assert!(panel.visible);
+    }
+}
@@ -0,0 +1,414 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use loom_core::bulk::{EditOutcome, ValueEdit};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// One entry of the batch: the values read, the value typed and, once
+/// applied, what became of it.
+struct EditRow {
+    dn: String,
+    old: Vec<String>,
+    new: String,
+    /// Typed into since the values were read or last applied; only these
+    /// rows are written.
+    edited: bool,
+    outcome: Option<EditOutcome>,
+}
+
+/// Batch editor: one attribute's values across the entries marked in the
+/// search results, with a new value typed per entry and written as a job.
+pub struct BatchEditDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dns: Vec<String>,
+    /// The attribute being edited, typed first.
+    attr: String,
+    /// Empty until the values have been read.
+    rows: Vec<EditRow>,
+    loading: bool,
+    table_state: TableState,
+}
+
+impl BatchEditDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Batch Edit", theme.clone()).with_size(85, 75),
+            theme,
+            dns: Vec::new(),
+            attr: String::new(),
+            rows: Vec::new(),
+            loading: false,
+            table_state: TableState::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    /// Open for `dns`, asking for the attribute first.
+    pub fn show(&mut self, dns: Vec<String>) {
+        self.dns = dns;
+        self.attr.clear();
+        self.rows.clear();
+        self.loading = false;
+        self.table_state.select(None);
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// The values read for `attr`; the new values start as the first one.
+    pub fn receive_values(&mut self, attr: &str, values: Vec<(String, Vec<String>)>) {
+        if !self.visible || !self.attr.eq_ignore_ascii_case(attr) {
+            return;
+        }
+        self.loading = false;
+        self.rows = values
+            .into_iter()
+            .map(|(dn, old)| EditRow {
+                dn,
+                new: old.first().cloned().unwrap_or_default(),
+                old,
+                edited: false,
+                outcome: None,
+            })
+            .collect();
+        self.table_state
+            .select((!self.rows.is_empty()).then_some(0));
+    }
+
+    /// What became of each entry; applied rows now hold their new value.
+    pub fn receive_outcomes(&mut self, attr: &str, outcomes: Vec<(String, EditOutcome)>) {
+        if !self.attr.eq_ignore_ascii_case(attr) {
+            return;
+        }
+        for (dn, outcome) in outcomes {
+            let Some(row) = self.rows.iter_mut().find(|r| r.dn == dn) else {
+                continue;
+            };
+            if outcome != EditOutcome::Applied && outcome != EditOutcome::Unchanged {
+                row.outcome = Some(outcome);
+                continue;
+            }
+            row.old = if row.new.is_empty() {
+                Vec::new()
+            } else {
+                vec![row.new.clone()]
+            };
+            row.edited = false;
+            row.outcome = Some(outcome);
+        }
+    }
+
+    /// The edited rows, to be written.
+    fn edits(&self) -> Vec<ValueEdit> {
+        self.rows
+            .iter()
+            .filter(|r| r.edited)
+            .map(|r| ValueEdit {
+                dn: r.dn.clone(),
+                old: r.old.clone(),
+                new: r.new.clone(),
+            })
+            .collect()
+    }
+
+    fn selected_mut(&mut self) -> Option<&mut EditRow> {
+        let i = self.table_state.selected()?;
+        self.rows.get_mut(i)
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if key.code == KeyCode::Esc {
+            self.hide();
+            return Action::ClosePopup;
+        }
+        if self.rows.is_empty() {
+            return self.handle_attr_key(key);
+        }
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+        match key.code {
+            KeyCode::Up => {
+                let i = self.table_state.selected().unwrap_or(0);
+                self.table_state.select(Some(i.saturating_sub(1)));
+            }
+            KeyCode::Down => {
+                let i = self.table_state.selected().unwrap_or(0);
+                self.table_state
+                    .select(Some((i + 1).min(self.rows.len() - 1)));
+            }
+            KeyCode::Char('d') if ctrl => {
+                // Fill down: the selected value into every row below
+                if let Some(i) = self.table_state.selected() {
+                    let value = self.rows[i].new.clone();
+                    for row in &mut self.rows[i + 1..] {
+                        row.new = value.clone();
+                        row.edited = true;
+                    }
+                }
+            }
+            KeyCode::Char('u') if ctrl => {
+                if let Some(row) = self.selected_mut() {
+                    row.new.clear();
+                    row.edited = true;
+                }
+            }
+            KeyCode::Char('r') if ctrl => {
+                // Revert the row to the value read
+                if let Some(row) = self.selected_mut() {
+                    row.new = row.old.first().cloned().unwrap_or_default();
+                    row.edited = false;
+                }
+            }
+            KeyCode::F(10) => {
+                let edits = self.edits();
+                if edits.is_empty() {
+                    return Action::StatusMessage("No values edited".to_string());
+                }
+                let msg = format!("Set {} on {} entries?", self.attr, edits.len());
+                return Action::ShowConfirm(
+                    msg,
+                    Box::new(Action::BatchEditExecute {
+                        attr: self.attr.clone(),
+                        edits,
+                    }),
+                );
+            }
+            KeyCode::Backspace => {
+                if let Some(row) = self.selected_mut() {
+                    row.new.pop();
+                    row.edited = true;
+                }
+            }
+            KeyCode::Char(c) if !ctrl => {
+                if let Some(row) = self.selected_mut() {
+                    row.new.push(c);
+                    row.edited = true;
+                }
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// Keys while the attribute is typed, before the values are read.
+    fn handle_attr_key(&mut self, key: KeyEvent) -> Action {
+        if self.loading {
+            return Action::None;
+        }
+        match key.code {
+            KeyCode::Enter if !self.attr.trim().is_empty() => {
+                self.attr = self.attr.trim().to_string();
+                self.loading = true;
+                Action::BatchEditLoad {
+                    dns: self.dns.clone(),
+                    attr: self.attr.clone(),
+                }
+            }
+            KeyCode::Backspace => {
+                self.attr.pop();
+                Action::None
+            }
+            KeyCode::Char(c) if !c.is_whitespace() => {
+                self.attr.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn status_cell(&self, row: &EditRow) -> Cell<'static> {
+        let (text, style) = match &row.outcome {
+            Some(EditOutcome::Failed(e)) => (format!("failed: {}", e), self.theme.error),
+            _ if row.edited => ("edited".to_string(), self.theme.warning),
+            None => (String::new(), self.theme.dimmed),
+            Some(EditOutcome::Applied) => ("applied".to_string(), self.theme.success),
+            Some(EditOutcome::Unchanged) => ("unchanged".to_string(), self.theme.dimmed),
+        };
+        Cell::from(Span::styled(text, style))
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let title = if self.rows.is_empty() {
+            format!(" Batch Edit: {} entries ", self.dns.len())
+        } else {
+            let edited = self.rows.iter().filter(|r| r.edited).count();
+            format!(
+                " Batch Edit: {} on {} entries ({} edited) ",
+                self.attr,
+                self.rows.len(),
+                edited
+            )
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        if self.rows.is_empty() {
+            let mut lines = vec![Line::from(vec![
+                Span::styled("Attribute: ", self.theme.header),
+                Span::styled(self.attr.as_str(), self.theme.normal),
+                Span::styled("_", self.theme.command_prompt),
+            ])];
+            if self.loading {
+                lines.push(Line::from(Span::styled(
+                    "Reading the current values...",
+                    self.theme.dimmed,
+                )));
+            }
+            frame.render_widget(Paragraph::new(lines), layout[0]);
+            let hints = Line::from(Span::styled(
+                "Enter: read the values  Esc: close",
+                self.theme.dimmed,
+            ));
+            frame.render_widget(Paragraph::new(hints), layout[1]);
+            return;
+        }
+
+        let selected = self.table_state.selected();
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                let new = if selected == Some(i) {
+                    format!("{}_", row.new)
+                } else {
+                    row.new.clone()
+                };
+                let new_style = if row.edited {
+                    self.theme.warning
+                } else {
+                    self.theme.normal
+                };
+                Row::new(vec![
+                    Cell::from(Line::from(self.theme.dn_spans(&row.dn))),
+                    Cell::from(Span::styled(row.old.join("; "), self.theme.dimmed)),
+                    Cell::from(Span::styled(new, new_style)),
+                    self.status_cell(row),
+                ])
+            })
+            .collect();
+        let header = Row::new(
+            ["DN", "Current", "New", "Status"]
+                .map(|title| Cell::from(Span::styled(title, self.theme.header))),
+        );
+        let widths = [35, 20, 25, 20].map(Constraint::Percentage);
+        let table = Table::new(rows, widths)
+            .header(header)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+        frame.render_stateful_widget(table, layout[0], &mut self.table_state);
+
+        let hints = Line::from(Span::styled(
+            "Type: new value  Ctrl+d: fill down  Ctrl+u: clear (removes)  Ctrl+r: revert  F10: apply  Esc: close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn ctrl(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL)
+    }
+
+    #[test]
+    fn test_edit_fill_down_and_apply() {
+        let mut dialog = BatchEditDialog::new(Theme::default());
+        let dns: Vec<String> = ["uid=a,dc=x", "uid=b,dc=x", "uid=c,dc=x"]
+            .map(str::to_string)
+            .to_vec();
+        dialog.show(dns.clone());
+        for c in "department".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::BatchEditLoad { attr, dns: d } if attr == "department" && d == dns
+        ));
+        dialog.receive_values(
+            "Department",
+            vec![
+                ("uid=a,dc=x".to_string(), vec!["Sales".to_string()]),
+                ("uid=b,dc=x".to_string(), vec![]),
+                ("uid=c,dc=x".to_string(), vec!["HR".to_string()]),
+            ],
+        );
+        assert_eq!(dialog.rows[0].new, "Sales");
+
+        // Edit the first row and fill it down
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        dialog.handle_key_event(key(KeyCode::Char('Z')));
+        dialog.handle_key_event(ctrl('d'));
+        // ...then take the last row back to what it held
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(ctrl('r'));
+        let Action::ShowConfirm(_, on_confirm) = dialog.handle_key_event(key(KeyCode::F(10)))
+        else {
+            panic!("expected a confirmation");
+        };
+        let Action::BatchEditExecute { attr, edits } = *on_confirm else {
+            panic!("expected the batch edit");
+        };
+        assert_eq!(attr, "department");
+        assert_eq!(
+            edits
+                .iter()
+                .map(|e| (e.dn.as_str(), e.new.as_str()))
+                .collect::<Vec<_>>(),
+            [("uid=a,dc=x", "SaleZ"), ("uid=b,dc=x", "SaleZ")]
+        );
+
+        dialog.receive_outcomes(
+            "department",
+            vec![
+                ("uid=a,dc=x".to_string(), EditOutcome::Applied),
+                (
+                    "uid=b,dc=x".to_string(),
+                    EditOutcome::Failed("rc=50".to_string()),
+                ),
+            ],
+        );
+        assert_eq!(dialog.rows[0].old, ["SaleZ"]);
+        assert!(!dialog.rows[0].edited);
+        // A failed row stays edited, to be applied again
+        assert!(dialog.rows[1].edited);
+        assert_eq!(dialog.edits().len(), 1);
+    }
+}
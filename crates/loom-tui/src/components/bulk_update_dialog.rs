@@ -1,4 +1,6 @@
 use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::bulk::{ComputedValue, Throttle};
+use loom_core::transform::Script;
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
@@ -14,6 +16,19 @@ enum BulkField {
     Filter,
     Attribute,
     Value,
+    Script,
+    Rate,
+    BatchSize,
+    BatchDelay,
+}
+
+impl BulkField {
+    fn is_numeric(self) -> bool {
+        matches!(
+            self,
+            BulkField::Rate | BulkField::BatchSize | BulkField::BatchDelay
+        )
+    }
 }
 
 /// Operation type for the bulk update.
@@ -22,6 +37,8 @@ pub enum BulkOp {
     Replace,
     Add,
     Delete,
+    /// Values computed per entry by a transform script.
+    Script,
 }
 
 impl BulkOp {
@@ -30,6 +47,7 @@ impl BulkOp {
             BulkOp::Replace => "Replace",
             BulkOp::Add => "Add",
             BulkOp::Delete => "Delete",
+            BulkOp::Script => "Script",
         }
     }
 
@@ -37,11 +55,15 @@ impl BulkOp {
         match self {
             BulkOp::Replace => BulkOp::Add,
             BulkOp::Add => BulkOp::Delete,
-            BulkOp::Delete => BulkOp::Replace,
+            BulkOp::Delete => BulkOp::Script,
+            BulkOp::Script => BulkOp::Replace,
         }
     }
 }
 
+/// Entries whose computed values are previewed.
+pub const PREVIEW_ENTRIES: usize = 20;
+
 /// Dialog for specifying bulk update parameters.
 pub struct BulkUpdateDialog {
     pub visible: bool,
@@ -52,27 +74,55 @@ pub struct BulkUpdateDialog {
     pub attribute: String,
     pub value: String,
     pub op: BulkOp,
+    /// Transform script, for the Script operation.
+    pub script: String,
+    /// Matching entries and the values computed for the first few.
+    preview: Option<(usize, Vec<ComputedValue>)>,
+    /// Throttle fields; empty means no limit.
+    rate: String,
+    batch_size: String,
+    batch_delay: String,
+    /// Configured throttle, whose retry settings are kept as-is.
+    throttle: Throttle,
 }
 
 impl BulkUpdateDialog {
     pub fn new(theme: Theme) -> Self {
         Self {
             visible: false,
-            popup: Popup::new("Bulk Update", theme.clone()).with_size(60, 45),
+            popup: Popup::new("Bulk Update", theme.clone()).with_size(70, 60),
             theme,
             active_field: BulkField::Filter,
             filter: String::new(),
             attribute: String::new(),
             value: String::new(),
             op: BulkOp::Replace,
+            script: String::new(),
+            preview: None,
+            rate: String::new(),
+            batch_size: String::new(),
+            batch_delay: String::new(),
+            throttle: Throttle::default(),
         }
     }
 
-    pub fn show(&mut self) {
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    /// Open the dialog with the throttle fields set from `throttle`.
+    pub fn show(&mut self, throttle: &Throttle) {
         self.filter.clear();
         self.attribute.clear();
         self.value.clear();
         self.op = BulkOp::Replace;
+        self.script.clear();
+        self.preview = None;
+        self.rate = nonzero(throttle.ops_per_second as u64);
+        self.batch_size = nonzero(throttle.batch_size as u64);
+        self.batch_delay = nonzero(throttle.batch_delay_ms);
+        self.throttle = throttle.clone();
         self.active_field = BulkField::Filter;
         self.visible = true;
         self.popup.show();
@@ -83,6 +133,53 @@ impl BulkUpdateDialog {
         self.popup.hide();
     }
 
+    pub fn set_preview(&mut self, total: usize, values: Vec<ComputedValue>) {
+        self.preview = Some((total, values));
+    }
+
+    /// The fields in Tab order; a script takes the attribute and value's place.
+    fn fields(&self) -> &'static [BulkField] {
+        if self.op == BulkOp::Script {
+            &[
+                BulkField::Filter,
+                BulkField::Script,
+                BulkField::Rate,
+                BulkField::BatchSize,
+                BulkField::BatchDelay,
+            ]
+        } else {
+            &[
+                BulkField::Filter,
+                BulkField::Attribute,
+                BulkField::Value,
+                BulkField::Rate,
+                BulkField::BatchSize,
+                BulkField::BatchDelay,
+            ]
+        }
+    }
+
+    fn move_field(&mut self, forward: bool) {
+        let fields = self.fields();
+        let i = fields
+            .iter()
+            .position(|f| *f == self.active_field)
+            .unwrap_or(0);
+        let next = if forward {
+            (i + 1) % fields.len()
+        } else {
+            (i + fields.len() - 1) % fields.len()
+        };
+        self.active_field = fields[next];
+    }
+
+    /// The error to show if the script doesn't parse.
+    fn script_error(&self) -> Option<Action> {
+        Script::parse(&self.script)
+            .err()
+            .map(|e| Action::ErrorMessage(format!("Script error at {}", e)))
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
         match key.code {
             KeyCode::Esc => {
@@ -90,29 +187,67 @@ impl BulkUpdateDialog {
                 Action::ClosePopup
             }
             KeyCode::Tab => {
-                self.active_field = match self.active_field {
-                    BulkField::Filter => BulkField::Attribute,
-                    BulkField::Attribute => BulkField::Value,
-                    BulkField::Value => BulkField::Filter,
-                };
+                self.move_field(true);
                 Action::None
             }
             KeyCode::BackTab => {
-                self.active_field = match self.active_field {
-                    BulkField::Filter => BulkField::Value,
-                    BulkField::Attribute => BulkField::Filter,
-                    BulkField::Value => BulkField::Attribute,
-                };
+                self.move_field(false);
                 Action::None
             }
             KeyCode::F(2) => {
                 self.op = self.op.next();
+                if !self.fields().contains(&self.active_field) {
+                    self.active_field = BulkField::Filter;
+                }
+                Action::None
+            }
+            KeyCode::F(5) if self.op == BulkOp::Script => {
+                if self.filter.is_empty() {
+                    return Action::ErrorMessage("A filter is required".to_string());
+                }
+                if let Some(action) = self.script_error() {
+                    return action;
+                }
+                Action::BulkPreview {
+                    filter: self.filter.clone(),
+                    script: self.script.clone(),
+                }
+            }
+            KeyCode::Enter if self.active_field == BulkField::Script => {
+                self.script.push('\n');
+                self.preview = None;
                 Action::None
             }
+            KeyCode::Enter if self.op == BulkOp::Script => {
+                if self.filter.is_empty() {
+                    return Action::ErrorMessage("A filter is required".to_string());
+                }
+                if let Some(action) = self.script_error() {
+                    return action;
+                }
+                let Some(throttle) = self.parsed_throttle() else {
+                    return Action::ErrorMessage(
+                        "Rate, batch size and delay must be whole numbers".to_string(),
+                    );
+                };
+                self.hide();
+                Action::BulkUpdateExecute {
+                    filter: self.filter.clone(),
+                    attribute: String::new(),
+                    value: self.script.clone(),
+                    op: BulkOp::Script,
+                    throttle,
+                }
+            }
             KeyCode::Enter => {
                 if self.filter.is_empty() || self.attribute.is_empty() {
                     return Action::ErrorMessage("Filter and attribute are required".to_string());
                 }
+                let Some(throttle) = self.parsed_throttle() else {
+                    return Action::ErrorMessage(
+                        "Rate, batch size and delay must be whole numbers".to_string(),
+                    );
+                };
                 let filter = self.filter.clone();
                 let attr = self.attribute.clone();
                 let value = self.value.clone();
@@ -123,14 +258,20 @@ impl BulkUpdateDialog {
                     attribute: attr,
                     value,
                     op,
+                    throttle,
                 }
             }
             KeyCode::Backspace => {
                 self.active_buffer_mut().pop();
+                self.preview = None;
+                Action::None
+            }
+            KeyCode::Char(c) if self.active_field.is_numeric() && !c.is_ascii_digit() => {
                 Action::None
             }
             KeyCode::Char(c) => {
                 self.active_buffer_mut().push(c);
+                self.preview = None;
                 Action::None
             }
             _ => Action::None,
@@ -142,7 +283,28 @@ impl BulkUpdateDialog {
             BulkField::Filter => &mut self.filter,
             BulkField::Attribute => &mut self.attribute,
             BulkField::Value => &mut self.value,
+            BulkField::Script => &mut self.script,
+            BulkField::Rate => &mut self.rate,
+            BulkField::BatchSize => &mut self.batch_size,
+            BulkField::BatchDelay => &mut self.batch_delay,
+        }
+    }
+
+    /// The throttle from the dialog's fields, or None if one isn't a number.
+    fn parsed_throttle(&self) -> Option<Throttle> {
+        fn parse<T: std::str::FromStr + Default>(s: &str) -> Option<T> {
+            if s.is_empty() {
+                Some(T::default())
+            } else {
+                s.parse().ok()
+            }
         }
+        Some(Throttle {
+            ops_per_second: parse(&self.rate)?,
+            batch_size: parse(&self.batch_size)?,
+            batch_delay_ms: parse(&self.batch_delay)?,
+            ..self.throttle.clone()
+        })
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -162,15 +324,36 @@ impl BulkUpdateDialog {
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        // Layout: operation (2) | filter (2) | attribute (2) | value (2) | hints (flex)
-        let layout = Layout::vertical([
-            Constraint::Length(2),
-            Constraint::Length(2),
-            Constraint::Length(2),
-            Constraint::Length(2),
-            Constraint::Min(1),
-        ])
-        .split(inner);
+        // Layout: operation (2) | filter (2) | attribute (2) | value (2) | throttle (2) | hints (flex),
+        // or with a script: operation | filter | script | throttle | preview (flex) | hints (2)
+        let scripted = self.op == BulkOp::Script;
+        let script_height = self.script.split('\n').count().clamp(1, 8) as u16 + 1;
+        let layout = if scripted {
+            Layout::vertical([
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Length(script_height + 1),
+                Constraint::Length(2),
+                Constraint::Min(1),
+                Constraint::Length(2),
+            ])
+            .split(inner)
+        } else {
+            Layout::vertical([
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Length(2),
+                Constraint::Min(1),
+            ])
+            .split(inner)
+        };
+        let (throttle_area, hints_area) = if scripted {
+            (layout[3], layout[5])
+        } else {
+            (layout[4], layout[5])
+        };
 
         // Operation
         let op_line = vec![
@@ -186,24 +369,127 @@ impl BulkUpdateDialog {
         // Filter field
         self.render_field(frame, layout[1], "Filter", &self.filter, BulkField::Filter);
 
-        // Attribute field
+        if scripted {
+            self.render_script(frame, layout[2], script_height as usize - 1);
+            self.render_preview(frame, layout[4]);
+        } else {
+            // Attribute field
+            self.render_field(
+                frame,
+                layout[2],
+                "Attribute",
+                &self.attribute,
+                BulkField::Attribute,
+            );
+
+            // Value field
+            self.render_field(frame, layout[3], "Value", &self.value, BulkField::Value);
+        }
+
+        // Throttle fields, side by side
+        let throttle = Layout::horizontal([
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(throttle_area);
+        self.render_field(frame, throttle[0], "Ops/sec", &self.rate, BulkField::Rate);
         self.render_field(
             frame,
-            layout[2],
-            "Attribute",
-            &self.attribute,
-            BulkField::Attribute,
+            throttle[1],
+            "Batch size",
+            &self.batch_size,
+            BulkField::BatchSize,
+        );
+        self.render_field(
+            frame,
+            throttle[2],
+            "Batch delay (ms)",
+            &self.batch_delay,
+            BulkField::BatchDelay,
         );
-
-        // Value field
-        self.render_field(frame, layout[3], "Value", &self.value, BulkField::Value);
 
         // Hints
-        let hints = Paragraph::new(Line::from(Span::styled(
-            "Tab:next field  F2:operation  Enter:execute  Esc:cancel",
-            self.theme.dimmed,
-        )));
-        frame.render_widget(hints, layout[4]);
+        let hints = Paragraph::new(vec![
+            Line::from(Span::styled(
+                format!(
+                    "Empty throttle fields mean no limit. Busy servers are retried up to {} times.",
+                    self.throttle.max_retries
+                ),
+                self.theme.dimmed,
+            )),
+            Line::from(Span::styled(
+                if scripted {
+                    "Tab:next field  F2:operation  F5:preview  Enter:new line / execute  Esc:cancel"
+                } else {
+                    "Tab:next field  F2:operation  Enter:execute  Esc:cancel"
+                },
+                self.theme.dimmed,
+            )),
+        ]);
+        frame.render_widget(hints, hints_area);
+    }
+
+    /// The script editor, showing its last `height` lines.
+    fn render_script(&self, frame: &mut Frame, area: Rect, height: usize) {
+        let is_active = self.active_field == BulkField::Script;
+        let (label_style, value_style) = if is_active {
+            (self.theme.header, self.theme.normal)
+        } else {
+            (self.theme.dimmed, self.theme.dimmed)
+        };
+        let lines: Vec<&str> = self.script.split('\n').collect();
+        let skip = lines.len().saturating_sub(height);
+        let mut text = vec![Line::from(Span::styled(
+            "Script (attr = expression, one per line):",
+            label_style,
+        ))];
+        for (i, line) in lines.iter().enumerate().skip(skip) {
+            let mut spans = vec![Span::styled(*line, value_style)];
+            if is_active && i + 1 == lines.len() {
+                spans.push(Span::styled("_", self.theme.command_prompt));
+            }
+            text.push(Line::from(spans));
+        }
+        frame.render_widget(Paragraph::new(text), area);
+    }
+
+    fn render_preview(&self, frame: &mut Frame, area: Rect) {
+        let Some((total, values)) = &self.preview else {
+            let hint = "Press F5 to preview the values computed for the first matching entries.";
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(hint, self.theme.dimmed))),
+                area,
+            );
+            return;
+        };
+        let shown = values
+            .iter()
+            .map(|v| v.dn.as_str())
+            .collect::<std::collections::HashSet<_>>()
+            .len();
+        let mut lines = vec![Line::from(Span::styled(
+            format!("Preview: {} matching entries, {} shown", total, shown),
+            self.theme.header,
+        ))];
+        for value in values {
+            let old = value.old.as_deref().unwrap_or("");
+            let new = if value.new.is_empty() || value.new == old {
+                Span::styled("(unchanged)", self.theme.dimmed)
+            } else {
+                Span::styled(value.new.clone(), self.theme.success)
+            };
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{}  ", loom_core::dn::rdn(&value.dn)),
+                    self.theme.normal,
+                ),
+                Span::styled(format!("{}: ", value.attr), self.theme.header),
+                Span::styled(format!("{} \u{2192} ", old), self.theme.dimmed),
+                new,
+            ]));
+        }
+        frame.render_widget(Paragraph::new(lines), area);
     }
 
     fn render_field(
@@ -240,3 +526,96 @@ impl BulkUpdateDialog {
         frame.render_widget(Paragraph::new(lines), area);
     }
 }
+
+/// A number for a throttle field, with 0 (no limit) shown as empty.
+fn nonzero(n: u64) -> String {
+    if n == 0 {
+        String::new()
+    } else {
+        n.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn press(dialog: &mut BulkUpdateDialog, code: KeyCode) -> Action {
+        dialog.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_str(dialog: &mut BulkUpdateDialog, s: &str) {
+        for c in s.chars() {
+            press(dialog, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_throttle_fields() {
+        let mut dialog = BulkUpdateDialog::new(Theme::default());
+        dialog.show(&Throttle {
+            batch_size: 100,
+            batch_delay_ms: 1000,
+            ..Default::default()
+        });
+        type_str(&mut dialog, "(objectClass=person)");
+        press(&mut dialog, KeyCode::Tab);
+        type_str(&mut dialog, "description");
+        press(&mut dialog, KeyCode::Tab);
+        press(&mut dialog, KeyCode::Tab);
+        // Only digits are accepted in the rate field
+        type_str(&mut dialog, "1x0");
+
+        match press(&mut dialog, KeyCode::Enter) {
+            Action::BulkUpdateExecute { throttle, .. } => {
+                assert_eq!(throttle.ops_per_second, 10);
+                assert_eq!(throttle.batch_size, 100);
+                assert_eq!(throttle.batch_delay_ms, 1000);
+                assert_eq!(throttle.max_retries, Throttle::default().max_retries);
+            }
+            other => panic!("expected execute, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_script_operation() {
+        let mut dialog = BulkUpdateDialog::new(Theme::default());
+        dialog.show(&Throttle::default());
+        type_str(&mut dialog, "(objectClass=person)");
+        press(&mut dialog, KeyCode::Tab);
+        type_str(&mut dialog, "description");
+        // The attribute field gives way to the script
+        for _ in 0..3 {
+            press(&mut dialog, KeyCode::F(2));
+        }
+        assert_eq!(dialog.op, BulkOp::Script);
+        assert_eq!(dialog.active_field, BulkField::Filter);
+        press(&mut dialog, KeyCode::Tab);
+        assert_eq!(dialog.active_field, BulkField::Script);
+
+        // Enter starts a new line in the script
+        type_str(&mut dialog, "mail = lower(uid)");
+        press(&mut dialog, KeyCode::Enter);
+        type_str(&mut dialog, "cn = upper(sn");
+        assert!(matches!(
+            press(&mut dialog, KeyCode::F(5)),
+            Action::ErrorMessage(msg) if msg == "Script error at line 2, column 14: Expected ')'"
+        ));
+        type_str(&mut dialog, ")");
+        assert!(matches!(
+            press(&mut dialog, KeyCode::F(5)),
+            Action::BulkPreview { ref script, .. } if script == "mail = lower(uid)\ncn = upper(sn)"
+        ));
+
+        press(&mut dialog, KeyCode::Tab);
+        match press(&mut dialog, KeyCode::Enter) {
+            Action::BulkUpdateExecute { op, value, .. } => {
+                assert_eq!(op, BulkOp::Script);
+                assert_eq!(value, "mail = lower(uid)\ncn = upper(sn)");
+            }
+            other => panic!("expected execute, got {:?}", other),
+        }
+    }
+}
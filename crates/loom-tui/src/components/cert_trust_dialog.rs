@@ -33,6 +33,10 @@ impl CertTrustDialog {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn show(
         &mut self,
         cert_info: CertificateInfo,
@@ -0,0 +1,279 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::staging::ChangeQueue;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// A toggleable view of the active tab's staged changes: the queue in the
+/// order it will be applied, with the selected change shown as LDIF.
+pub struct ChangesPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    selected: usize,
+}
+
+impl ChangesPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Staged Changes", theme.clone()).with_size(85, 75),
+            theme,
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.popup.show();
+            self.selected = 0;
+        } else {
+            self.popup.hide();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent, queue: Option<&ChangeQueue>) -> Action {
+        let len = queue.map(ChangeQueue::len).unwrap_or_default();
+        self.selected = self.selected.min(len.saturating_sub(1));
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.selected = len.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('K') | KeyCode::Char('J') => {
+                let delta = if key.code == KeyCode::Char('K') {
+                    -1
+                } else {
+                    1
+                };
+                if let Some(index) = queue.and_then(|q| q.shift(self.selected, delta)) {
+                    self.selected = index;
+                }
+                Action::None
+            }
+            KeyCode::Char('d') | KeyCode::Delete => match queue {
+                Some(q) if q.is_applying() => {
+                    Action::ErrorMessage("The staged changes are being applied".to_string())
+                }
+                Some(q) if q.remove(self.selected) => {
+                    self.selected = self.selected.min(len.saturating_sub(2));
+                    Action::StatusMessage("Removed the staged change".to_string())
+                }
+                _ => Action::None,
+            },
+            _ if len == 0 => Action::None,
+            KeyCode::Char('a') | KeyCode::Char('A') => {
+                let stop_on_error = key.code == KeyCode::Char('a');
+                let how = if stop_on_error {
+                    "stopping at the first error"
+                } else {
+                    "continuing past errors"
+                };
+                Action::ShowConfirm(
+                    format!("Apply {} staged changes, {}?", len, how),
+                    Box::new(Action::ApplyStagedChanges { stop_on_error }),
+                )
+            }
+            KeyCode::Char('s') => Action::ExportStagedChanges,
+            KeyCode::Char('c') => match queue.map(ChangeQueue::changes) {
+                Some(changes) => changes
+                    .get(self.selected)
+                    .map_or(Action::None, |c| Action::CopyLdapModify(c.op.to_ldif())),
+                None => Action::None,
+            },
+            KeyCode::Char('C') => {
+                queue.map_or(Action::None, |q| Action::CopyLdapModify(q.to_ldif()))
+            }
+            KeyCode::Char('D') => Action::ShowConfirm(
+                format!("Discard all {} staged changes?", len),
+                Box::new(Action::DiscardStagedChanges),
+            ),
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect, queue: Option<&ChangeQueue>) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let changes = queue.map(ChangeQueue::changes).unwrap_or_default();
+        let state = match queue {
+            Some(q) if q.is_applying() => " | applying",
+            Some(q) if q.is_staging() => " | staging on",
+            _ => " | staging off",
+        };
+        let block = Block::default()
+            .title(format!(" Staged Changes ({}{}) ", changes.len(), state))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: changes | selected change as LDIF | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Percentage(45),
+            Constraint::Min(3),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        if changes.is_empty() {
+            let text = if queue.is_none() {
+                "No LDAP connection on this tab."
+            } else {
+                "No changes staged."
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(text, self.theme.dimmed))),
+                layout[0],
+            );
+        }
+
+        let height = layout[0].height as usize;
+        let offset = (self.selected + 1).saturating_sub(height);
+        let lines: Vec<Line> = changes
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, change)| {
+                let dn_style = if i == self.selected {
+                    self.theme.selected.add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.normal
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:>3}. ", i + 1), self.theme.dimmed),
+                    Span::styled(format!("{:<7}", change.op.label()), self.theme.header),
+                    Span::styled(change.op.dn().to_string(), dn_style),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let ldif: Vec<Line> = changes
+            .get(self.selected)
+            .map(|change| {
+                change
+                    .op
+                    .to_ldif()
+                    .lines()
+                    .map(|l| Line::from(Span::styled(l.to_string(), self.theme.normal)))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let ldif_block = Block::default()
+            .borders(Borders::TOP)
+            .border_style(self.theme.dimmed);
+        frame.render_widget(Paragraph::new(ldif).block(ldif_block), layout[1]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  K/J:move up/down  d:remove  a:apply  A:apply past errors  s:export  c/C:copy as ldapmodify (all)  D:discard  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use loom_core::staging::ChangeOp;
+
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn dns(queue: &ChangeQueue) -> Vec<String> {
+        queue
+            .changes()
+            .iter()
+            .map(|c| c.op.dn().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_reorder_remove_and_apply() {
+        let queue = ChangeQueue::default();
+        for dn in ["cn=a,dc=x", "cn=b,dc=x", "cn=c,dc=x"] {
+            queue.push(ChangeOp::Delete { dn: dn.to_string() });
+        }
+
+        let mut panel = ChangesPanel::new(Theme::default());
+        panel.toggle();
+        panel.handle_key_event(key('G'), Some(&queue));
+        panel.handle_key_event(key('K'), Some(&queue));
+        panel.handle_key_event(key('K'), Some(&queue));
+        assert_eq!(dns(&queue), ["cn=c,dc=x", "cn=a,dc=x", "cn=b,dc=x"]);
+        assert_eq!(panel.selected, 0);
+
+        panel.handle_key_event(key('j'), Some(&queue));
+        panel.handle_key_event(key('d'), Some(&queue));
+        assert_eq!(dns(&queue), ["cn=c,dc=x", "cn=b,dc=x"]);
+
+        assert!(matches!(
+            panel.handle_key_event(key('A'), Some(&queue)),
+            Action::ShowConfirm(ref msg, ref action)
+                if msg == "Apply 2 staged changes, continuing past errors?"
+                    && matches!(**action, Action::ApplyStagedChanges { stop_on_error: false })
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key('c'), Some(&queue)),
+            Action::CopyLdapModify(ldif) if ldif == "dn: cn=b,dc=x\nchangetype: delete\n"
+        ));
+
+        // Nothing to apply or export from an empty queue
+        queue.clear();
+        assert!(matches!(
+            panel.handle_key_event(key('a'), Some(&queue)),
+            Action::None
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key('s'), None),
+            Action::None
+        ));
+    }
+}
@@ -0,0 +1,223 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::Rect;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::Paragraph;
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::commands::{self, CompletionSource};
+use crate::theme::Theme;
+
+/// Maximum number of command lines remembered for Up/Down recall.
+const MAX_HISTORY: usize = 50;
+
+/// The `:` command line, drawn over the status bar while typing. Tab
+/// completes the word under the cursor and cycles through the
+/// alternatives; Up/Down recall earlier commands.
+pub struct CommandLine {
+    pub visible: bool,
+    theme: Theme,
+    input: String,
+    source: CompletionSource,
+    /// Offset of the word being completed and its candidates, while Tab
+    /// is cycling through them.
+    completions: Option<(usize, Vec<String>)>,
+    completion_index: usize,
+    history: Vec<String>,
+    history_index: Option<usize>,
+}
+
+impl CommandLine {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            theme,
+            input: String::new(),
+            source: CompletionSource::default(),
+            completions: None,
+            completion_index: 0,
+            history: Vec::new(),
+            history_index: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
+    pub fn show(&mut self, source: CompletionSource) {
+        self.source = source;
+        self.input.clear();
+        self.completions = None;
+        self.history_index = None;
+        self.visible = true;
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.completions = None;
+        self.source = CompletionSource::default();
+    }
+
+    fn complete(&mut self) {
+        if let Some((start, ref candidates)) = self.completions {
+            // Already cycling: move to the next alternative
+            self.completion_index = (self.completion_index + 1) % candidates.len();
+            self.input.truncate(start);
+            self.input.push_str(&candidates[self.completion_index]);
+            return;
+        }
+        let (start, candidates) = commands::complete(&self.input, &self.source);
+        match candidates.len() {
+            0 => {}
+            1 => {
+                self.input.truncate(start);
+                self.input.push_str(&candidates[0]);
+                // A completed command name is followed by its argument
+                if start == 0 {
+                    self.input.push(' ');
+                }
+            }
+            _ => {
+                self.input.truncate(start);
+                self.input.push_str(&candidates[0]);
+                self.completion_index = 0;
+                self.completions = Some((start, candidates));
+            }
+        }
+    }
+
+    fn recall(&mut self, older: bool) {
+        if self.history.is_empty() {
+            return;
+        }
+        let index = match (self.history_index, older) {
+            (None, true) => self.history.len() - 1,
+            (None, false) => return,
+            (Some(i), true) => i.saturating_sub(1),
+            (Some(i), false) if i + 1 < self.history.len() => i + 1,
+            (Some(_), false) => {
+                self.history_index = None;
+                self.input.clear();
+                return;
+            }
+        };
+        self.history_index = Some(index);
+        self.input = self.history[index].clone();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if key.code != KeyCode::Tab {
+            self.completions = None;
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::None
+            }
+            KeyCode::Enter => {
+                let line = self.input.trim().to_string();
+                self.hide();
+                if line.is_empty() {
+                    return Action::None;
+                }
+                if self.history.last() != Some(&line) {
+                    self.history.push(line.clone());
+                    if self.history.len() > MAX_HISTORY {
+                        self.history.remove(0);
+                    }
+                }
+                Action::RunCommandLine(line)
+            }
+            KeyCode::Tab => {
+                self.complete();
+                Action::None
+            }
+            KeyCode::Up => {
+                self.recall(true);
+                Action::None
+            }
+            KeyCode::Down => {
+                self.recall(false);
+                Action::None
+            }
+            KeyCode::Backspace => {
+                // Backspace on an empty line leaves command mode, as in vim
+                if self.input.pop().is_none() {
+                    self.hide();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, area: Rect) {
+        if !self.visible {
+            return;
+        }
+        let mut spans = vec![
+            Span::styled(":", self.theme.header),
+            Span::styled(self.input.as_str(), self.theme.normal),
+            Span::styled("_", self.theme.selected),
+        ];
+        if let Some((_, ref candidates)) = self.completions {
+            spans.push(Span::styled(
+                format!("   {}", candidates.join("  ")),
+                self.theme.dimmed,
+            ));
+        }
+        frame.render_widget(Paragraph::new(Line::from(spans)), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_str(line: &mut CommandLine, text: &str) {
+        for c in text.chars() {
+            line.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_complete_and_run() {
+        let mut line = CommandLine::new(Theme::load("dark"));
+        line.show(CompletionSource {
+            profiles: vec!["Production".to_string()],
+            ..CompletionSource::default()
+        });
+        type_str(&mut line, "con");
+        line.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(line.input, "connect ");
+        type_str(&mut line, "p");
+        line.handle_key_event(key(KeyCode::Tab));
+        let action = line.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::RunCommandLine(l) if l == "connect Production"));
+        assert!(!line.visible);
+
+        // Tab cycles through several candidates
+        line.show(CompletionSource::default());
+        type_str(&mut line, "tab");
+        line.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(line.input, "tabnext");
+        line.handle_key_event(key(KeyCode::Tab));
+        assert_eq!(line.input, "tabprev");
+
+        // Up recalls the last command
+        line.handle_key_event(key(KeyCode::Esc));
+        line.show(CompletionSource::default());
+        line.handle_key_event(key(KeyCode::Up));
+        assert_eq!(line.input, "connect Production");
+    }
+}
@@ -0,0 +1,265 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::context_menu::MenuItem;
+use crate::components::popup::Popup;
+use crate::keymap::Keymap;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::{FuzzyFilter, FuzzyMatch};
+
+/// Global commands offered by the palette: keymap binding name and label.
+const GLOBAL_COMMANDS: &[(&str, &str)] = &[
+    ("show_connect_dialog", "Connect to a Profile"),
+    ("show_workspaces", "Open Workspace"),
+    ("switch_to_profiles", "Profiles Layout"),
+    ("save_connection", "Save Connection as Profile"),
+    ("search", "Search"),
+    ("goto_dn", "Go to DN"),
+    ("quick_open", "Quick Open Entry"),
+    ("show_favorites", "Favorites"),
+    ("show_recent", "Recent Entries"),
+    ("show_history", "Entry History"),
+    ("nav_back", "Back in Entry History"),
+    ("nav_forward", "Forward in Entry History"),
+    ("force_refresh", "Reload Entry from Server"),
+    ("show_export_dialog", "Export"),
+    ("show_bulk_update", "Bulk Update"),
+    ("show_schema_viewer", "Schema Viewer"),
+    ("toggle_dry_run", "Toggle Dry Run"),
+    ("toggle_staging", "Toggle Staging"),
+    ("toggle_changes_panel", "Staged Changes"),
+    ("toggle_split", "Toggle Split View"),
+    ("swap_split_focus", "Swap Split Side"),
+    ("copy_to_other_side", "Copy DN/Value to Other Side"),
+    ("next_tab", "Next Tab"),
+    ("prev_tab", "Previous Tab"),
+    ("close_tab", "Close Tab"),
+    ("toggle_log_panel", "Log Panel"),
+    ("toggle_jobs_panel", "Jobs Panel"),
+    ("toggle_trace_panel", "Operation Trace"),
+    ("toggle_metrics_panel", "Server Metrics"),
+    ("show_replication", "Replication Status"),
+    ("show_account_reports", "Reports"),
+    ("show_help", "Keyboard Shortcuts"),
+    ("quit", "Quit"),
+];
+
+/// The palette's global commands with their current key bindings.
+pub fn global_commands(keymap: &Keymap) -> Vec<MenuItem> {
+    let mut items: Vec<MenuItem> = GLOBAL_COMMANDS
+        .iter()
+        .filter_map(|(name, label)| {
+            Some(MenuItem {
+                label: label.to_string(),
+                hint: keymap.hint(name).to_string(),
+                action: keymap.action(name)?.clone(),
+            })
+        })
+        .collect();
+    items.push(MenuItem {
+        label: "About".into(),
+        hint: "F3".into(),
+        action: Action::ShowAbout,
+    });
+    items
+}
+
+/// A fuzzy-searchable list of commands with their key bindings, so
+/// features can be found and run without knowing the key.
+pub struct CommandPalette {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    input: String,
+    items: Vec<MenuItem>,
+    labels: Vec<String>,
+    filtered: Vec<FuzzyMatch>,
+    selected: usize,
+    fuzzy: FuzzyFilter,
+}
+
+impl CommandPalette {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Commands", theme.clone()).with_size(60, 60),
+            theme,
+            input: String::new(),
+            items: Vec::new(),
+            labels: Vec::new(),
+            filtered: Vec::new(),
+            selected: 0,
+            fuzzy: FuzzyFilter::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, items: Vec<MenuItem>) {
+        self.labels = items.iter().map(|item| item.label.clone()).collect();
+        self.items = items;
+        self.input.clear();
+        self.refilter();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.items.clear();
+        self.labels.clear();
+        self.filtered.clear();
+    }
+
+    fn refilter(&mut self) {
+        self.filtered = self.fuzzy.filter(&self.input, &self.labels);
+        self.selected = 0;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down => {
+                if self.selected + 1 < self.filtered.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Enter => {
+                let Some(m) = self.filtered.get(self.selected) else {
+                    return Action::None;
+                };
+                let action = self.items[m.index].action.clone();
+                self.hide();
+                action
+            }
+            KeyCode::Backspace => {
+                if self.input.pop().is_some() {
+                    self.refilter();
+                }
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.refilter();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Commands ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        if inner.height < 3 {
+            return;
+        }
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Input
+            Constraint::Min(1),    // List
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let input_line = Line::from(vec![
+            Span::styled("> ", self.theme.header),
+            Span::styled(self.input.as_str(), self.theme.normal),
+            Span::styled("_", self.theme.selected),
+        ]);
+        frame.render_widget(Paragraph::new(input_line), layout[0]);
+
+        // Keep the selected command in view
+        let height = layout[1].height as usize;
+        let offset = (self.selected + 1).saturating_sub(height);
+        let width = layout[1].width as usize;
+        let lines: Vec<Line> = self
+            .filtered
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, m)| {
+                let item = &self.items[m.index];
+                let (style, hint_style) = if i == self.selected {
+                    (self.theme.selected, self.theme.selected)
+                } else {
+                    (self.theme.normal, self.theme.dimmed)
+                };
+                let gap = width
+                    .saturating_sub(item.label.chars().count() + item.hint.chars().count() + 2);
+                Line::from(vec![
+                    Span::styled(format!(" {}{}", item.label, " ".repeat(gap)), style),
+                    Span::styled(format!("{} ", item.hint), hint_style),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "type to filter  \u{2191}/\u{2193}:select  Enter:run  Esc:close",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_global_commands_have_bindings() {
+        let items = global_commands(&Keymap::default());
+        assert_eq!(items.len(), GLOBAL_COMMANDS.len() + 1);
+        let schema = items.iter().find(|i| i.label == "Schema Viewer").unwrap();
+        assert_eq!(schema.hint, "F6");
+        assert!(matches!(schema.action, Action::ShowSchemaViewer));
+    }
+
+    #[test]
+    fn test_filter_and_run() {
+        let mut palette = CommandPalette::new(Theme::load("dark"));
+        palette.show(global_commands(&Keymap::default()));
+        for c in "schema".chars() {
+            palette.handle_key_event(key(KeyCode::Char(c)));
+        }
+        let action = palette.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::ShowSchemaViewer));
+        assert!(!palette.visible);
+    }
+}
@@ -1,5 +1,9 @@
-use crossterm::event::{KeyCode, KeyEvent};
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Duration, Utc};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{
     Block, BorderType, Borders, Cell, Clear, List, ListItem, Paragraph, Row, Table,
@@ -8,8 +12,10 @@ use ratatui::Frame;
 use tracing::debug;
 
 use loom_core::entry::LdapEntry;
-use loom_core::filter::{detect_filter_context, validate_filter, FilterContext};
-use loom_core::schema::SchemaCache;
+use loom_core::filter::{
+    detect_filter_context, lint_filter, validate_filter, FilterContext, FilterDiagnostic, Severity,
+};
+use loom_core::schema::{AttributeSyntax, SchemaCache};
 
 use crate::action::Action;
 use crate::component::Component;
@@ -65,6 +71,30 @@ const FILTER_TEMPLATES: &[&str] = &[
     "(|(cn=*)(sn=*))",
 ];
 
+/// Low-cardinality attributes whose values are sampled from the directory
+/// and offered as completions, like object class names.
+const SAMPLED_VALUE_ATTRIBUTES: &[&str] = &[
+    "c",
+    "co",
+    "l",
+    "st",
+    "title",
+    "department",
+    "company",
+    "physicalDeliveryOfficeName",
+    "employeeType",
+];
+
+/// Relative times offered for GeneralizedTime attributes, expanded into
+/// timestamps by [`expand_time_snippet`] when accepted.
+const TIME_SNIPPETS: &[&str] = &["now", "-1h", "-1d", "-7d", "-30d", "-90d", "-365d"];
+
+/// Values remembered per attribute from recent search results.
+const OBSERVED_VALUES: usize = 20;
+
+/// Longer values aren't worth offering as completions.
+const MAX_OBSERVED_LEN: usize = 64;
+
 /// Which kind of completions are currently displayed.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum CompletionKind {
@@ -89,9 +119,12 @@ pub struct CommandPanel {
     // Cursor position within input_buffer
     pub cursor_pos: usize,
 
-    // Schema for value suggestions
+    // Schema for value suggestions and filter linting
     schema: Option<SchemaCache>,
 
+    // Problems found in input_buffer, most severe first
+    diagnostics: Vec<FilterDiagnostic>,
+
     // Autocomplete state
     attribute_names: Vec<String>,
     fuzzy: FuzzyFilter,
@@ -101,6 +134,16 @@ pub struct CommandPanel {
     completion_kind: CompletionKind,
     value_items: Vec<String>,
 
+    // Whether the value being completed is a GeneralizedTime
+    completing_time: bool,
+
+    // Directory-sampled values, keyed by lowercased attribute name
+    value_samples: HashMap<String, Vec<String>>,
+    // Values seen in recent search results, newest first, keyed the same way
+    observed_values: HashMap<String, Vec<String>>,
+    samples_requested: HashSet<String>,
+    pending_sample: Option<String>,
+
     // Live search debounce state
     search_generation: u64,
     search_dirty: bool,
@@ -129,6 +172,7 @@ impl CommandPanel {
             live_search_enabled,
             cursor_pos: 0,
             schema: None,
+            diagnostics: Vec::new(),
             attribute_names: Vec::new(),
             fuzzy: FuzzyFilter::new(),
             completions: Vec::new(),
@@ -136,6 +180,11 @@ impl CommandPanel {
             completion_selected: 0,
             completion_kind: CompletionKind::Attributes,
             value_items: Vec::new(),
+            completing_time: false,
+            value_samples: HashMap::new(),
+            observed_values: HashMap::new(),
+            samples_requested: HashSet::new(),
+            pending_sample: None,
             search_generation: 0,
             search_dirty: false,
             last_search_text: String::new(),
@@ -145,6 +194,10 @@ impl CommandPanel {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn push_message(&mut self, text: String) {
         self.messages.push(StatusMessage {
             text,
@@ -169,6 +222,7 @@ impl CommandPanel {
     pub fn activate_input(&mut self) {
         self.input_active = true;
         self.input_buffer.clear();
+        self.diagnostics.clear();
         self.cursor_pos = 0;
         self.search_dirty = false;
         self.last_search_text.clear();
@@ -179,6 +233,7 @@ impl CommandPanel {
     pub fn deactivate_input(&mut self) {
         self.input_active = false;
         self.input_buffer.clear();
+        self.diagnostics.clear();
         self.cursor_pos = 0;
         self.search_dirty = false;
         self.last_search_text.clear();
@@ -202,6 +257,18 @@ impl CommandPanel {
     pub fn resume_input(&mut self) {
         self.input_active = true;
         self.cursor_pos = self.input_buffer.len();
+        self.lint();
+        self.update_completions();
+    }
+
+    /// Replace the input with a pasted filter. Line breaks and the indentation
+    /// of a pretty-printed filter are removed.
+    pub fn paste_filter(&mut self, text: &str) {
+        self.input_buffer = text.lines().map(str::trim).collect();
+        self.cursor_pos = self.input_buffer.len();
+        self.search_dirty = true;
+        self.clear_preview();
+        self.lint();
         self.update_completions();
     }
 
@@ -224,6 +291,52 @@ impl CommandPanel {
             schema.as_ref().map_or(0, |s| s.object_classes.len()),
         );
         self.schema = schema;
+        self.lint();
+    }
+
+    /// Replace the sampled values with a connection's cache (on tab switch).
+    /// Values observed in the previous tab's results are forgotten.
+    pub fn set_value_samples(&mut self, samples: HashMap<String, Vec<String>>) {
+        self.samples_requested = samples.keys().cloned().collect();
+        self.value_samples = samples;
+        self.observed_values.clear();
+        self.pending_sample = None;
+    }
+
+    /// Remember short values from search results, to offer as completions
+    /// for their attributes. Password attributes are skipped.
+    pub fn observe_results(&mut self, entries: &[LdapEntry]) {
+        for entry in entries {
+            for (attr, values) in &entry.attributes {
+                let key = attr.to_lowercase();
+                if key == "objectclass" || key.contains("password") || key.contains("pwd") {
+                    continue;
+                }
+                let observed = self.observed_values.entry(key).or_default();
+                for value in values {
+                    if value.is_empty()
+                        || value.len() > MAX_OBSERVED_LEN
+                        || value.chars().any(char::is_control)
+                    {
+                        continue;
+                    }
+                    observed.retain(|v| v != value);
+                    observed.insert(0, value.clone());
+                }
+                observed.truncate(OBSERVED_VALUES);
+            }
+        }
+    }
+
+    /// Store the sampled values for one attribute and refresh the completions
+    /// in case the cursor is still on that attribute's value.
+    pub fn add_value_samples(&mut self, attr: &str, values: Vec<String>) {
+        let key = attr.to_lowercase();
+        self.samples_requested.insert(key.clone());
+        self.value_samples.insert(key, values);
+        if self.input_active {
+            self.update_completions();
+        }
     }
 
     /// Populate attribute_names with well-known LDAP attributes as a fallback
@@ -243,6 +356,29 @@ impl CommandPanel {
         }
     }
 
+    /// Re-check the input against the filter grammar and the schema.
+    fn lint(&mut self) {
+        self.diagnostics = lint_filter(&self.input_buffer, self.schema.as_ref());
+    }
+
+    /// Apply the first available quick fix, keeping the cursor on the
+    /// same text.
+    fn apply_fix(&mut self) {
+        let Some(fix) = self.diagnostics.iter().find_map(|d| d.fix.clone()) else {
+            return;
+        };
+        self.input_buffer = fix.apply(&self.input_buffer);
+        if self.cursor_pos >= fix.span.end {
+            self.cursor_pos = self.cursor_pos + fix.replacement.len() - fix.span.len();
+        } else if self.cursor_pos > fix.span.start {
+            self.cursor_pos = fix.span.start + fix.replacement.len();
+        }
+        self.search_dirty = true;
+        self.clear_preview();
+        self.lint();
+        self.update_completions();
+    }
+
     fn hide_completions(&mut self) {
         self.completion_visible = false;
         self.completions.clear();
@@ -330,6 +466,8 @@ impl CommandPanel {
                 self.value_items.clear();
 
                 let attr_lower = attr.to_lowercase();
+                let syntax = self.schema.as_ref().map(|s| s.attribute_syntax(&attr));
+                self.completing_time = syntax == Some(AttributeSyntax::GeneralizedTime);
 
                 if attr_lower == "objectclass" {
                     // Suggest object class names from schema
@@ -342,11 +480,44 @@ impl CommandPanel {
                     } else {
                         debug!("update_completions: Value context for objectClass but no schema available");
                     }
+                } else {
+                    match syntax {
+                        Some(AttributeSyntax::Boolean) => {
+                            self.value_items = vec!["TRUE".to_string(), "FALSE".to_string()];
+                        }
+                        Some(AttributeSyntax::GeneralizedTime) => {
+                            self.value_items =
+                                TIME_SNIPPETS.iter().map(|s| s.to_string()).collect();
+                        }
+                        _ => {}
+                    }
+                    if SAMPLED_VALUE_ATTRIBUTES
+                        .iter()
+                        .any(|a| a.eq_ignore_ascii_case(&attr))
+                    {
+                        // Suggest values seen in the directory, sampling them once
+                        if let Some(values) = self.value_samples.get(&attr_lower) {
+                            self.value_items.extend(values.iter().cloned());
+                        } else if self.samples_requested.insert(attr_lower.clone()) {
+                            debug!("update_completions: requesting value sample for {}", attr);
+                            self.pending_sample = Some(attr.clone());
+                        }
+                    }
+                    // Then values seen in recent search results
+                    for value in self.observed_values.get(&attr_lower).into_iter().flatten() {
+                        if !self
+                            .value_items
+                            .iter()
+                            .any(|v| v.eq_ignore_ascii_case(value))
+                        {
+                            self.value_items.push(value.clone());
+                        }
+                    }
                 }
 
                 // Only show value completions if we have meaningful suggestions
-                // (e.g. objectClass names from schema). Don't show generic
-                // placeholders like *, TRUE, FALSE — they're not helpful.
+                // (e.g. objectClass names from schema, sampled or observed values,
+                // booleans). Don't show generic placeholders like * — they're not helpful.
                 if self.value_items.is_empty() {
                     debug!(
                         "update_completions: Value attr={:?}, no value suggestions available, hiding",
@@ -427,7 +598,10 @@ impl CommandPanel {
                 }
             }
             CompletionKind::Values => {
-                let value = self.value_items[selected.index].clone();
+                let mut value = self.value_items[selected.index].clone();
+                if self.completing_time {
+                    value = expand_time_snippet(&value, Utc::now()).unwrap_or(value);
+                }
                 debug!("accept_completion: accepting value {:?}", value);
                 if let Some(FilterContext::Value { partial, .. }) =
                     detect_filter_context(text_to_cursor)
@@ -463,6 +637,7 @@ impl CommandPanel {
 
         self.hide_completions();
         // Trigger completions update for the new buffer state
+        self.lint();
         self.update_completions();
     }
 
@@ -470,6 +645,10 @@ impl CommandPanel {
     /// Called by App on Action::Tick when input is active.
     /// Returns a LiveSearchRequest if the filter changed and is valid.
     pub fn tick(&mut self) -> Action {
+        if let Some(attr) = self.pending_sample.take() {
+            return Action::SampleAttributeValues(attr);
+        }
+
         if !self.live_search_enabled || !self.search_dirty {
             return Action::None;
         }
@@ -582,6 +761,10 @@ impl CommandPanel {
 
         // Input mode: capture text
         match key.code {
+            KeyCode::Enter if key.modifiers.contains(KeyModifiers::ALT) => {
+                self.apply_fix();
+                Action::None
+            }
             KeyCode::Enter => {
                 let query = normalize_filter(&self.input_buffer);
                 self.soft_deactivate();
@@ -618,6 +801,7 @@ impl CommandPanel {
                     }
                     self.search_dirty = true;
                     self.clear_preview();
+                    self.lint();
                     self.update_completions();
                 }
                 Action::None
@@ -644,6 +828,7 @@ impl CommandPanel {
                     }
                     self.search_dirty = true;
                     self.clear_preview();
+                    self.lint();
                     self.update_completions();
                 }
                 Action::None
@@ -696,6 +881,7 @@ impl CommandPanel {
                 }
                 self.search_dirty = true;
                 self.clear_preview();
+                self.lint();
                 self.update_completions();
                 Action::None
             }
@@ -704,6 +890,25 @@ impl CommandPanel {
     }
 }
 
+/// Expand a [`TIME_SNIPPETS`]-style relative time -- `now`, or `-<n>` with
+/// an `h` or `d` unit -- into a GeneralizedTime timestamp relative to `now`.
+fn expand_time_snippet(snippet: &str, now: DateTime<Utc>) -> Option<String> {
+    let at = if snippet == "now" {
+        now
+    } else {
+        let ago = snippet.strip_prefix('-')?;
+        let unit = ago.chars().last()?;
+        let count: i64 = ago[..ago.len() - unit.len_utf8()].parse().ok()?;
+        let span = match unit {
+            'h' => Duration::try_hours(count)?,
+            'd' => Duration::try_days(count)?,
+            _ => return None,
+        };
+        now.checked_sub_signed(span)?
+    };
+    Some(at.format("%Y%m%d%H%M%SZ").to_string())
+}
+
 /// Normalize a filter string before submission:
 /// - Bare filter (no parens): wrap in `()` — e.g. `cn=test` → `(cn=test)`
 /// - Multiple top-level `(...)` groups: wrap in `(&...)` — e.g. `(cn=r)(mail=r)` → `(&(cn=r)(mail=r))`
@@ -998,7 +1203,13 @@ impl CommandPanel {
                 } else {
                     self.theme.normal
                 };
-                ListItem::new(Span::styled(name.as_str(), style))
+                let mut spans = vec![Span::styled(name.as_str(), style)];
+                if self.completion_kind == CompletionKind::Values && self.completing_time {
+                    if let Some(time) = expand_time_snippet(name, Utc::now()) {
+                        spans.push(Span::styled(format!("  {}", time), self.theme.dimmed));
+                    }
+                }
+                ListItem::new(Line::from(spans))
             })
             .collect();
 
@@ -1083,89 +1294,145 @@ impl CommandPanel {
 }
 
 impl CommandPanel {
-    /// Render just the input field and completions popup (no messages, no border).
-    /// Used inside the search popup.
-    pub fn render_input_only(&self, frame: &mut Frame, area: Rect) {
-        // Calculate input height: multi-line for compound filters, 1 otherwise
-        let (formatted_lines, cursor_row, cursor_col, _) = if self.input_active {
-            self.format_input_for_display()
-        } else {
-            (
-                vec![self.input_buffer.clone()],
-                0,
-                self.input_buffer.len(),
-                Vec::new(),
-            )
+    /// Filter input text with its syntax colored, the characters in
+    /// `marks` underlined, and the cursor on the character at byte
+    /// `cursor` (or after the text) when given.
+    fn input_spans(
+        &self,
+        text: &str,
+        cursor: Option<usize>,
+        marks: &HashMap<usize, Severity>,
+    ) -> Vec<Span<'static>> {
+        let styles = self.theme.filter_styles(text);
+        let mut spans: Vec<Span<'static>> = Vec::new();
+        for ((i, c), style) in text.char_indices().zip(styles) {
+            let style = if cursor == Some(i) {
+                self.theme.command_prompt
+            } else if let Some(&severity) = marks.get(&i) {
+                self.squiggle(style, severity)
+            } else {
+                style
+            };
+            match spans.last_mut() {
+                Some(last) if last.style == style && cursor != Some(i) => {
+                    last.content.to_mut().push(c);
+                }
+                _ => spans.push(Span::styled(c.to_string(), style)),
+            }
+        }
+        if cursor.is_some_and(|pos| pos >= text.len()) {
+            spans.push(Span::styled("_", self.theme.command_prompt));
+        }
+        spans
+    }
+
+    /// `style` underlined in the color of `severity`.
+    fn squiggle(&self, style: Style, severity: Severity) -> Style {
+        let color = match severity {
+            Severity::Error => self.theme.error.fg,
+            Severity::Warning => self.theme.warning.fg,
         };
+        let style = style.add_modifier(Modifier::UNDERLINED);
+        match color {
+            Some(color) => style.underline_color(color),
+            None => style,
+        }
+    }
 
-        if self.input_active {
-            if formatted_lines.len() <= 1 {
-                // Single-line rendering
-                let before_cursor = &self.input_buffer[..self.cursor_pos];
-                let at_cursor = if self.cursor_pos < self.input_buffer.len() {
-                    &self.input_buffer[self.cursor_pos..self.cursor_pos + 1]
+    /// For each display row, the columns inside a diagnostic's span and
+    /// the most severe diagnostic covering each. `cursor_map` is the one
+    /// from [`Self::format_input_for_display`], empty for a single line.
+    fn diagnostic_marks(
+        &self,
+        rows: usize,
+        cursor_map: &[(usize, usize)],
+    ) -> Vec<HashMap<usize, Severity>> {
+        let mut marks = vec![HashMap::new(); rows.max(1)];
+        for diagnostic in &self.diagnostics {
+            for i in diagnostic.span.clone() {
+                let (row, col) = if cursor_map.is_empty() {
+                    (0, i)
                 } else {
-                    "_"
-                };
-                let after_cursor = if self.cursor_pos < self.input_buffer.len() {
-                    &self.input_buffer[self.cursor_pos + 1..]
-                } else {
-                    ""
+                    match cursor_map.get(i) {
+                        Some(&position) => position,
+                        None => continue,
+                    }
                 };
-
-                let mut spans = vec![
-                    Span::styled("/ ", self.theme.command_prompt),
-                    Span::styled(before_cursor.to_string(), self.theme.normal),
-                    Span::styled(at_cursor.to_string(), self.theme.command_prompt),
-                ];
-                if !after_cursor.is_empty() {
-                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal));
+                if let Some(row) = marks.get_mut(row) {
+                    let severity = row.entry(col).or_insert(diagnostic.severity);
+                    *severity = (*severity).min(diagnostic.severity);
                 }
-                if self.live_searching {
-                    spans.push(Span::styled(" ...", self.theme.dimmed));
-                }
-                let input_line = Line::from(spans);
-                frame.render_widget(Paragraph::new(input_line), area);
-            } else {
-                // Multi-line rendering for compound filters
-                let display_lines: Vec<Line> = formatted_lines
-                    .iter()
-                    .enumerate()
-                    .map(|(row_idx, line_text)| {
-                        let prefix = if row_idx == 0 { "/ " } else { "  " };
-
-                        if row_idx == cursor_row {
-                            let before = &line_text[..cursor_col.min(line_text.len())];
-                            let at = if cursor_col < line_text.len() {
-                                &line_text[cursor_col..cursor_col + 1]
-                            } else {
-                                "_"
-                            };
-                            let after = if cursor_col < line_text.len() {
-                                &line_text[cursor_col + 1..]
-                            } else {
-                                ""
-                            };
-
-                            let mut spans = vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(before.to_string(), self.theme.normal),
-                                Span::styled(at.to_string(), self.theme.command_prompt),
-                            ];
-                            if !after.is_empty() {
-                                spans.push(Span::styled(after.to_string(), self.theme.normal));
-                            }
-                            Line::from(spans)
-                        } else {
-                            Line::from(vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(line_text.clone(), self.theme.normal),
-                            ])
-                        }
-                    })
-                    .collect();
-                frame.render_widget(Paragraph::new(display_lines), area);
             }
+        }
+        marks
+    }
+
+    /// The first diagnostic's message, and the key for the quick fix.
+    fn diagnostic_spans(&self) -> Vec<Span<'static>> {
+        let Some(first) = self.diagnostics.first() else {
+            return Vec::new();
+        };
+        let style = match first.severity {
+            Severity::Error => self.theme.error,
+            Severity::Warning => self.theme.warning,
+        };
+        let mut spans = vec![Span::styled(format!("  {}", first.message), style)];
+        if self.diagnostics.len() > 1 {
+            spans.push(Span::styled(
+                format!(" (+{} more)", self.diagnostics.len() - 1),
+                self.theme.dimmed,
+            ));
+        }
+        if let Some(fix) = self.diagnostics.iter().find_map(|d| d.fix.as_ref()) {
+            spans.push(Span::styled(
+                format!("  Alt+Enter: {}", fix.label),
+                self.theme.dimmed,
+            ));
+        }
+        spans
+    }
+
+    /// The active input as lines: the filter, or the pretty-printed
+    /// compound filter, with diagnostics underlined and the first one
+    /// described after it.
+    fn input_lines(&self) -> Vec<Line<'static>> {
+        let (formatted_lines, cursor_row, cursor_col, cursor_map) = self.format_input_for_display();
+        let marks = self.diagnostic_marks(formatted_lines.len(), &cursor_map);
+        let mut lines: Vec<Line<'static>> = if formatted_lines.len() <= 1 {
+            let mut spans = vec![Span::styled("/ ", self.theme.command_prompt)];
+            spans.extend(self.input_spans(&self.input_buffer, Some(self.cursor_pos), &marks[0]));
+            if self.live_searching {
+                spans.push(Span::styled(" ...", self.theme.dimmed));
+            }
+            vec![Line::from(spans)]
+        } else {
+            // Multi-line rendering for compound filters
+            formatted_lines
+                .iter()
+                .zip(&marks)
+                .enumerate()
+                .map(|(row_idx, (line_text, marks))| {
+                    // First line gets "/ " prompt; others get "  " for alignment
+                    let prefix = if row_idx == 0 { "/ " } else { "  " };
+
+                    let cursor = (row_idx == cursor_row).then_some(cursor_col);
+                    let mut spans = vec![Span::styled(prefix, self.theme.command_prompt)];
+                    spans.extend(self.input_spans(line_text, cursor, marks));
+                    Line::from(spans)
+                })
+                .collect()
+        };
+        if let Some(last) = lines.last_mut() {
+            last.spans.extend(self.diagnostic_spans());
+        }
+        lines
+    }
+
+    /// Render just the input field and completions popup (no messages, no border).
+    /// Used inside the search popup.
+    pub fn render_input_only(&self, frame: &mut Frame, area: Rect) {
+        if self.input_active {
+            frame.render_widget(Paragraph::new(self.input_lines()), area);
         } else {
             // Not active — show filter text as dimmed, or hint
             if self.input_buffer.is_empty() {
@@ -1207,12 +1474,12 @@ impl Component for CommandPanel {
         frame.render_widget(block, area);
 
         // Calculate input height: multi-line for compound filters, 1 otherwise
-        let (formatted_lines, cursor_row, cursor_col, _) = if self.input_active {
-            self.format_input_for_display()
+        let input_lines = if self.input_active {
+            self.input_lines()
         } else {
-            (vec![String::new()], 0, 0, Vec::new())
+            Vec::new()
         };
-        let input_height = (formatted_lines.len() as u16).clamp(1, 8);
+        let input_height = (input_lines.len() as u16).clamp(1, 8);
 
         // Layout: messages (flex) | input area (dynamic height)
         let layout =
@@ -1240,75 +1507,7 @@ impl Component for CommandPanel {
 
         // Input area with cursor position support
         if self.input_active {
-            if formatted_lines.len() <= 1 {
-                // Single-line rendering (original behavior)
-                let before_cursor = &self.input_buffer[..self.cursor_pos];
-                let at_cursor = if self.cursor_pos < self.input_buffer.len() {
-                    &self.input_buffer[self.cursor_pos..self.cursor_pos + 1]
-                } else {
-                    "_"
-                };
-                let after_cursor = if self.cursor_pos < self.input_buffer.len() {
-                    &self.input_buffer[self.cursor_pos + 1..]
-                } else {
-                    ""
-                };
-
-                let mut spans = vec![
-                    Span::styled("/ ", self.theme.command_prompt),
-                    Span::styled(before_cursor.to_string(), self.theme.normal),
-                    Span::styled(at_cursor.to_string(), self.theme.command_prompt),
-                ];
-                if !after_cursor.is_empty() {
-                    spans.push(Span::styled(after_cursor.to_string(), self.theme.normal));
-                }
-                if self.live_searching {
-                    spans.push(Span::styled(" ...", self.theme.dimmed));
-                }
-                let input_line = Line::from(spans);
-                frame.render_widget(Paragraph::new(input_line), layout[1]);
-            } else {
-                // Multi-line rendering for compound filters
-                let display_lines: Vec<Line> = formatted_lines
-                    .iter()
-                    .enumerate()
-                    .map(|(row_idx, line_text)| {
-                        // First line gets "/ " prompt; others get "  " for alignment
-                        let prefix = if row_idx == 0 { "/ " } else { "  " };
-
-                        if row_idx == cursor_row {
-                            // This line contains the cursor — split at cursor position
-                            let before = &line_text[..cursor_col.min(line_text.len())];
-                            let at = if cursor_col < line_text.len() {
-                                &line_text[cursor_col..cursor_col + 1]
-                            } else {
-                                "_"
-                            };
-                            let after = if cursor_col < line_text.len() {
-                                &line_text[cursor_col + 1..]
-                            } else {
-                                ""
-                            };
-
-                            let mut spans = vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(before.to_string(), self.theme.normal),
-                                Span::styled(at.to_string(), self.theme.command_prompt),
-                            ];
-                            if !after.is_empty() {
-                                spans.push(Span::styled(after.to_string(), self.theme.normal));
-                            }
-                            Line::from(spans)
-                        } else {
-                            Line::from(vec![
-                                Span::styled(prefix, self.theme.command_prompt),
-                                Span::styled(line_text.clone(), self.theme.normal),
-                            ])
-                        }
-                    })
-                    .collect();
-                frame.render_widget(Paragraph::new(display_lines), layout[1]);
-            }
+            frame.render_widget(Paragraph::new(input_lines), layout[1]);
         } else if focused {
             let input_line = Line::from(Span::styled("Press / to search", self.theme.dimmed));
             frame.render_widget(Paragraph::new(input_line), layout[1]);
@@ -1329,3 +1528,95 @@ impl Component for CommandPanel {
         self.area
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_quick_fix() {
+        let mut panel = CommandPanel::new(Theme::default(), false, false);
+        panel.activate_input();
+        panel.paste_filter("(&(cn=a)(sn=b*)");
+        assert_eq!(panel.diagnostics[0].severity, Severity::Error);
+        let marks = panel.diagnostic_marks(1, &[]);
+        assert_eq!(marks[0].get(&0), Some(&Severity::Error));
+        assert_eq!(marks[0].get(&1), None);
+
+        panel.cursor_pos = 3;
+        panel.handle_input_key(KeyEvent::new(KeyCode::Enter, KeyModifiers::ALT));
+        assert_eq!(panel.input_buffer, "(&(cn=a)(sn=b*))");
+        assert_eq!(panel.cursor_pos, 3);
+        assert!(panel.diagnostics.is_empty());
+
+        // Plain Enter still searches
+        assert!(matches!(
+            panel.handle_input_key(key(KeyCode::Enter)),
+            Action::SearchExecute(ref f) if f == "(&(cn=a)(sn=b*))"
+        ));
+    }
+
+    #[test]
+    fn test_value_completions() {
+        let mut schema = loom_core::offline::OfflineDirectory::load_embedded()
+            .schema()
+            .clone();
+        let mut flag = schema.get_attribute_type("cn").unwrap().clone();
+        flag.names = vec!["isLocked".to_string()];
+        flag.syntax = AttributeSyntax::Boolean;
+        schema.attribute_types.insert("islocked".to_string(), flag);
+
+        let mut panel = CommandPanel::new(Theme::default(), true, false);
+        panel.set_schema(Some(schema));
+        panel.activate_input();
+        panel.observe_results(&[LdapEntry::new(
+            "uid=a,dc=x".to_string(),
+            [
+                ("mail".to_string(), vec!["a@x.com".to_string()]),
+                ("userPassword".to_string(), vec!["secret".to_string()]),
+                ("isLocked".to_string(), vec!["true".to_string()]),
+            ]
+            .into(),
+        )]);
+
+        panel.paste_filter("(mail=");
+        assert_eq!(panel.value_items, ["a@x.com"]);
+        panel.paste_filter("(userPassword=");
+        assert!(!panel.completion_visible);
+        // Observed values don't repeat a boolean in another case
+        panel.paste_filter("(isLocked=");
+        assert_eq!(panel.value_items, ["TRUE", "FALSE"]);
+
+        panel.paste_filter("(modifyTimestamp>=");
+        assert!(panel.completing_time);
+        assert_eq!(panel.value_items[0], "now");
+        let now = DateTime::parse_from_rfc3339("2026-03-31T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            expand_time_snippet("-30d", now).as_deref(),
+            Some("20260301120000Z")
+        );
+        assert_eq!(
+            expand_time_snippet("now", now).as_deref(),
+            Some("20260331120000Z")
+        );
+        assert_eq!(expand_time_snippet("-3w", now), None);
+        assert_eq!(expand_time_snippet("-é", now), None);
+
+        panel.completion_selected = 4; // -30d
+        panel.handle_input_key(key(KeyCode::Tab));
+        let today = Utc::now() - Duration::days(30);
+        assert!(
+            panel
+                .input_buffer
+                .starts_with(&format!("(modifyTimestamp>={}", today.format("%Y%m%d"))),
+            "{}",
+            panel.input_buffer
+        );
+    }
+}
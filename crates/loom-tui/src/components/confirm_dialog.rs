@@ -7,12 +7,15 @@ use ratatui::Frame;
 use crate::action::Action;
 use crate::theme::Theme;
 
-/// A confirmation dialog: "Are you sure?" with Yes/No buttons.
+/// A confirmation dialog: "Are you sure?" with Yes/No buttons, or a text
+/// the user must type to confirm.
 pub struct ConfirmDialog {
     pub visible: bool,
     pub message: String,
     pub on_confirm: Option<Box<Action>>,
     selected: usize, // 0 = Yes, 1 = No
+    /// The text to type, and what has been typed so far.
+    typed: Option<(String, String)>,
     theme: Theme,
 }
 
@@ -23,17 +26,29 @@ impl ConfirmDialog {
             message: String::new(),
             on_confirm: None,
             selected: 1, // Default to No for safety
+            typed: None,
             theme,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn show(&mut self, message: String, on_confirm: Action) {
         self.message = message;
         self.on_confirm = Some(Box::new(on_confirm));
         self.selected = 1;
+        self.typed = None;
         self.visible = true;
     }
 
+    /// Ask for `expected` to be typed before carrying out `on_confirm`.
+    pub fn show_typed(&mut self, message: String, expected: String, on_confirm: Action) {
+        self.show(message, on_confirm);
+        self.typed = Some((expected, String::new()));
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.on_confirm = None;
@@ -43,6 +58,28 @@ impl ConfirmDialog {
         if !self.visible {
             return Action::None;
         }
+        if let Some((expected, input)) = &mut self.typed {
+            return match key.code {
+                KeyCode::Char(c) => {
+                    input.push(c);
+                    Action::None
+                }
+                KeyCode::Backspace => {
+                    input.pop();
+                    Action::None
+                }
+                KeyCode::Enter if input.trim().eq_ignore_ascii_case(expected) => {
+                    self.visible = false;
+                    self.on_confirm.take().map(|a| *a).unwrap_or(Action::None)
+                }
+                KeyCode::Enter => Action::ErrorMessage(format!("Type {} to confirm", expected)),
+                KeyCode::Esc => {
+                    self.hide();
+                    Action::ClosePopup
+                }
+                _ => Action::None,
+            };
+        }
 
         match key.code {
             KeyCode::Left | KeyCode::Char('h') => {
@@ -81,7 +118,7 @@ impl ConfirmDialog {
 
         // Center a 50x10 popup
         let popup_width = (full.width as u32 * 50 / 100).min(60) as u16;
-        let popup_height = 8u16.min(full.height);
+        let popup_height = if self.typed.is_some() { 10u16 } else { 8 }.min(full.height);
 
         let x = full.x + (full.width.saturating_sub(popup_width)) / 2;
         let y = full.y + (full.height.saturating_sub(popup_height)) / 2;
@@ -107,6 +144,15 @@ impl ConfirmDialog {
             .wrap(Wrap { trim: true });
         frame.render_widget(msg, layout[0]);
 
+        if let Some((expected, input)) = &self.typed {
+            let prompt = Line::from(vec![
+                Span::styled(format!("Type {}: ", expected), self.theme.dimmed),
+                Span::styled(format!("{}_", input), self.theme.header),
+            ]);
+            frame.render_widget(Paragraph::new(prompt), layout[1]);
+            return;
+        }
+
         // Buttons
         let yes_style = if self.selected == 0 {
             self.theme.selected
@@ -129,3 +175,47 @@ impl ConfirmDialog {
         frame.render_widget(Paragraph::new(buttons), layout[1]);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_typed_confirmation() {
+        let mut dialog = ConfirmDialog::new(Theme::default());
+        dialog.show_typed(
+            "Delete?".to_string(),
+            "uid=alice".to_string(),
+            Action::DeleteEntry("uid=alice,dc=x".to_string()),
+        );
+        // y is just a character here, and a wrong text doesn't confirm
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::None
+        ));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ErrorMessage(_)
+        ));
+        dialog.handle_key_event(key(KeyCode::Backspace));
+        for c in "UID=alice".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::DeleteEntry(ref dn) if dn == "uid=alice,dc=x"
+        ));
+        assert!(!dialog.visible);
+
+        // A plain confirmation afterwards takes y again
+        dialog.show("Sure?".to_string(), Action::ClosePopup);
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('y'))),
+            Action::ClosePopup
+        ));
+    }
+}
@@ -0,0 +1,267 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::modify::ModifyConflict;
+
+use crate::action::Action;
+use crate::components::attribute_editor::{EditOp, EditResult};
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Three-way view shown when a value edit is rejected because the entry
+/// changed on the server: the value the edit started from, the user's new
+/// value, and the attribute's current server values.
+pub struct ConflictDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    conflict: Option<ModifyConflict>,
+    /// Which current server value overwrite/merge applies to.
+    cursor: usize,
+}
+
+impl ConflictDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Edit Conflict", theme.clone()).with_size(70, 60),
+            theme,
+            conflict: None,
+            cursor: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, conflict: ModifyConflict) {
+        // Start on the server value most like the one being replaced
+        self.cursor = conflict
+            .current_values
+            .iter()
+            .position(|v| v.eq_ignore_ascii_case(&conflict.base_value))
+            .unwrap_or(0);
+        self.conflict = Some(conflict);
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.conflict = None;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let Some(conflict) = self.conflict.as_ref() else {
+            return Action::None;
+        };
+        let count = conflict.current_values.len();
+
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.cursor = self.cursor.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.cursor + 1 < count {
+                    self.cursor += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('o') => {
+                let result = EditResult {
+                    dn: conflict.dn.clone(),
+                    op: self.overwrite_op(conflict),
+                    new_value: conflict.new_value.clone(),
+                };
+                self.hide();
+                Action::SaveAttribute(result)
+            }
+            KeyCode::Char('m') => {
+                let action = Action::ConflictMerge {
+                    dn: conflict.dn.clone(),
+                    attr: conflict.attr.clone(),
+                    base_value: self.selected_value(conflict).unwrap_or_default(),
+                    draft: conflict.new_value.clone(),
+                };
+                self.hide();
+                action
+            }
+            KeyCode::Char('a') | KeyCode::Esc => {
+                let dn = conflict.dn.clone();
+                self.hide();
+                Action::ConflictAborted(dn)
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn selected_value(&self, conflict: &ModifyConflict) -> Option<String> {
+        conflict.current_values.get(self.cursor).cloned()
+    }
+
+    /// Overwrite replaces the selected server value, or adds the new value
+    /// when the attribute no longer has any.
+    fn overwrite_op(&self, conflict: &ModifyConflict) -> EditOp {
+        match self.selected_value(conflict) {
+            Some(old_value) => EditOp::Replace {
+                attr: conflict.attr.clone(),
+                old_value,
+            },
+            None => EditOp::Add {
+                attr: conflict.attr.clone(),
+            },
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(conflict) = self.conflict.as_ref() else {
+            return;
+        };
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Edit Conflict: {} ", conflict.attr))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let server_height = conflict.current_values.len().max(1) as u16 + 1;
+        let layout = Layout::vertical([
+            Constraint::Length(3),             // Explanation
+            Constraint::Length(2),             // Base value
+            Constraint::Length(2),             // My value
+            Constraint::Length(server_height), // Server values
+            Constraint::Min(1),                // Hints
+        ])
+        .split(inner);
+
+        let info = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "This entry was changed on the server since you opened it:",
+                self.theme.warning,
+            )),
+            Line::from(Span::styled(conflict.dn.as_str(), self.theme.dimmed)),
+        ])
+        .wrap(Wrap { trim: false });
+        frame.render_widget(info, layout[0]);
+
+        let value_block = |label: &'static str, value: &str| {
+            Paragraph::new(vec![
+                Line::from(Span::styled(label, self.theme.header)),
+                Line::from(Span::styled(format!("  {}", value), self.theme.normal)),
+            ])
+        };
+        frame.render_widget(
+            value_block("Base (when you started editing):", &conflict.base_value),
+            layout[1],
+        );
+        frame.render_widget(
+            value_block("Mine (your new value):", &conflict.new_value),
+            layout[2],
+        );
+
+        let mut lines = vec![Line::from(Span::styled(
+            "Server (current):",
+            self.theme.header,
+        ))];
+        if conflict.current_values.is_empty() {
+            lines.push(Line::from(Span::styled("  (no values)", self.theme.dimmed)));
+        }
+        for (i, value) in conflict.current_values.iter().enumerate() {
+            let (prefix, style) = if i == self.cursor {
+                ("> ", self.theme.selected.add_modifier(Modifier::BOLD))
+            } else {
+                ("  ", self.theme.normal)
+            };
+            lines.push(Line::from(Span::styled(
+                format!("{}{}", prefix, value),
+                style,
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), layout[3]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "o:overwrite  m:merge manually  a/Esc:abort  j/k:select server value",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[4]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn conflict(current_values: &[&str]) -> ModifyConflict {
+        ModifyConflict {
+            dn: "cn=a,dc=x".to_string(),
+            attr: "mail".to_string(),
+            base_value: "a@x.org".to_string(),
+            new_value: "a@example.org".to_string(),
+            current_values: current_values.iter().map(|v| v.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_overwrite_merge_and_abort() {
+        let mut dialog = ConflictDialog::new(Theme::default());
+
+        // Starts on the server value like the edited one; overwrite replaces it
+        dialog.show(conflict(&["b@x.org", "A@X.org"]));
+        assert!(matches!(
+            dialog.handle_key_event(key('o')),
+            Action::SaveAttribute(EditResult {
+                op: EditOp::Replace { ref old_value, .. },
+                ref new_value,
+                ..
+            }) if old_value == "A@X.org" && new_value == "a@example.org"
+        ));
+        assert!(!dialog.visible);
+
+        // With no server values left, overwrite adds the value back
+        dialog.show(conflict(&[]));
+        assert!(matches!(
+            dialog.handle_key_event(key('o')),
+            Action::SaveAttribute(EditResult {
+                op: EditOp::Add { .. },
+                ..
+            })
+        ));
+
+        dialog.show(conflict(&["b@x.org", "c@x.org"]));
+        dialog.handle_key_event(key('j'));
+        assert!(matches!(
+            dialog.handle_key_event(key('m')),
+            Action::ConflictMerge { ref base_value, ref draft, .. }
+                if base_value == "c@x.org" && draft == "a@example.org"
+        ));
+
+        dialog.show(conflict(&["b@x.org"]));
+        assert!(matches!(
+            dialog.handle_key_event(key('a')),
+            Action::ConflictAborted(ref dn) if dn == "cn=a,dc=x"
+        ));
+        assert!(matches!(dialog.handle_key_event(key('o')), Action::None));
+    }
+}
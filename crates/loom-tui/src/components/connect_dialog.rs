@@ -27,6 +27,10 @@ impl ConnectDialog {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn show(&mut self, profiles: Vec<ConnectionProfile>) {
         self.profiles = profiles;
         self.list_state.select(Some(0));
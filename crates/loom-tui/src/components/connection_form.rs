@@ -4,11 +4,12 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
 
+use loom_core::auth::SaslMechanism;
 use loom_core::connection::TlsMode;
 use loom_core::credentials::CredentialMethod;
 
 use crate::action::Action;
-use crate::config::ConnectionProfile;
+use crate::config::{normalize_folder_path, ConnectionProfile};
 use crate::theme::Theme;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -27,8 +28,12 @@ enum Field {
     Port,
     BindDn,
     BaseDn,
+    StartDn,
+    DefaultFilter,
+    RootDseAttrs,
     Folder,
     TlsMode,
+    BindMethod,
     CredentialMethod,
     PasswordCommand,
     PageSize,
@@ -44,9 +49,13 @@ impl Field {
             Field::Host => Field::Port,
             Field::Port => Field::BindDn,
             Field::BindDn => Field::BaseDn,
-            Field::BaseDn => Field::Folder,
+            Field::BaseDn => Field::StartDn,
+            Field::StartDn => Field::DefaultFilter,
+            Field::DefaultFilter => Field::RootDseAttrs,
+            Field::RootDseAttrs => Field::Folder,
             Field::Folder => Field::TlsMode,
-            Field::TlsMode => Field::CredentialMethod,
+            Field::TlsMode => Field::BindMethod,
+            Field::BindMethod => Field::CredentialMethod,
             Field::CredentialMethod => Field::PasswordCommand,
             Field::PasswordCommand => Field::PageSize,
             Field::PageSize => Field::Timeout,
@@ -63,9 +72,13 @@ impl Field {
             Field::Port => Field::Host,
             Field::BindDn => Field::Port,
             Field::BaseDn => Field::BindDn,
-            Field::Folder => Field::BaseDn,
+            Field::StartDn => Field::BaseDn,
+            Field::DefaultFilter => Field::StartDn,
+            Field::RootDseAttrs => Field::DefaultFilter,
+            Field::Folder => Field::RootDseAttrs,
             Field::TlsMode => Field::Folder,
-            Field::CredentialMethod => Field::TlsMode,
+            Field::BindMethod => Field::TlsMode,
+            Field::CredentialMethod => Field::BindMethod,
             Field::PasswordCommand => Field::CredentialMethod,
             Field::PageSize => Field::PasswordCommand,
             Field::Timeout => Field::PageSize,
@@ -89,8 +102,15 @@ pub struct ConnectionForm {
     port: String,
     bind_dn: String,
     base_dn: String,
+    start_dn: String,
+    default_filter: String,
+    root_dse_attrs: String,
+    /// The host the profile's open connection is using, shown beside a
+    /// list of failover hosts.
+    active_host: Option<String>,
     folder: String,
     tls_mode: TlsMode,
+    sasl_mechanism: Option<SaslMechanism>,
     credential_method: CredentialMethod,
     password_command: String,
     page_size: String,
@@ -98,6 +118,9 @@ pub struct ConnectionForm {
     relax_rules: bool,
     read_only: bool,
 
+    /// The profile as loaded; saving keeps whatever the form doesn't edit.
+    original: ConnectionProfile,
+
     // Folder view/edit fields
     folder_path: String,
     folder_description: String,
@@ -147,19 +170,29 @@ impl ConnectionForm {
             port: "389".to_string(),
             bind_dn: String::new(),
             base_dn: String::new(),
+            start_dn: String::new(),
+            default_filter: String::new(),
+            root_dse_attrs: String::new(),
+            active_host: None,
             folder: String::new(),
             tls_mode: TlsMode::Auto,
+            sasl_mechanism: None,
             credential_method: CredentialMethod::Prompt,
             password_command: String::new(),
             page_size: "500".to_string(),
             timeout: "30".to_string(),
             relax_rules: false,
             read_only: false,
+            original: ConnectionProfile::default(),
             folder_path: String::new(),
             folder_description: String::new(),
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     /// Show folder details in the right panel.
     pub fn view_folder(&mut self, path: &str, description: &str) {
         self.mode = FormMode::FolderView;
@@ -175,6 +208,11 @@ impl ConnectionForm {
         self.load_from_profile(profile);
     }
 
+    /// Show which of the profile's hosts its open connection is using.
+    pub fn set_active_host(&mut self, host: Option<String>) {
+        self.active_host = host;
+    }
+
     /// Switch to edit mode (must be viewing a profile).
     pub fn edit_profile(&mut self) {
         if self.profile_index.is_some() {
@@ -193,14 +231,20 @@ impl ConnectionForm {
         self.port = "389".to_string();
         self.bind_dn.clear();
         self.base_dn.clear();
+        self.start_dn.clear();
+        self.default_filter.clear();
+        self.root_dse_attrs.clear();
+        self.active_host = None;
         self.folder.clear();
         self.tls_mode = TlsMode::Auto;
+        self.sasl_mechanism = None;
         self.credential_method = CredentialMethod::Prompt;
         self.password_command.clear();
         self.page_size = "500".to_string();
         self.timeout = "30".to_string();
         self.relax_rules = false;
         self.read_only = false;
+        self.original = ConnectionProfile::default();
     }
 
     /// Clear the form (no profile selected).
@@ -212,12 +256,18 @@ impl ConnectionForm {
         self.port.clear();
         self.bind_dn.clear();
         self.base_dn.clear();
+        self.start_dn.clear();
+        self.default_filter.clear();
+        self.root_dse_attrs.clear();
+        self.active_host = None;
         self.folder.clear();
+        self.sasl_mechanism = None;
         self.password_command.clear();
         self.page_size.clear();
         self.timeout.clear();
         self.relax_rules = false;
         self.read_only = false;
+        self.original = ConnectionProfile::default();
     }
 
     fn load_from_profile(&mut self, profile: &ConnectionProfile) {
@@ -226,14 +276,20 @@ impl ConnectionForm {
         self.port = profile.port.to_string();
         self.bind_dn = profile.bind_dn.clone().unwrap_or_default();
         self.base_dn = profile.base_dn.clone().unwrap_or_default();
+        self.start_dn = profile.start_dn.clone().unwrap_or_default();
+        self.default_filter = profile.default_filter.clone().unwrap_or_default();
+        self.root_dse_attrs = profile.root_dse_attributes.join(", ");
+        self.active_host = None;
         self.folder = profile.folder.clone().unwrap_or_default();
         self.tls_mode = profile.tls_mode.clone();
+        self.sasl_mechanism = profile.sasl_mechanism;
         self.credential_method = profile.credential_method.clone();
         self.password_command = profile.password_command.clone().unwrap_or_default();
         self.page_size = profile.page_size.to_string();
         self.timeout = profile.timeout_secs.to_string();
         self.relax_rules = profile.relax_rules;
         self.read_only = profile.read_only;
+        self.original = profile.clone();
     }
 
     fn to_profile(&self) -> Result<ConnectionProfile, String> {
@@ -274,11 +330,23 @@ impl ConnectionForm {
             } else {
                 Some(self.base_dn.trim().to_string())
             },
-            folder: if self.folder.trim().is_empty() {
+            start_dn: if self.start_dn.trim().is_empty() {
                 None
             } else {
-                Some(self.folder.trim().to_string())
+                Some(self.start_dn.trim().to_string())
             },
+            default_filter: if self.default_filter.trim().is_empty() {
+                None
+            } else {
+                Some(self.default_filter.trim().to_string())
+            },
+            root_dse_attributes: self
+                .root_dse_attrs
+                .split(',')
+                .map(|a| a.trim().to_string())
+                .filter(|a| !a.is_empty())
+                .collect(),
+            folder: normalize_folder_path(&self.folder),
             credential_method: self.credential_method.clone(),
             password_command: if self.password_command.trim().is_empty() {
                 None
@@ -288,8 +356,10 @@ impl ConnectionForm {
             page_size,
             timeout_secs: timeout,
             relax_rules: self.relax_rules,
+            sasl_mechanism: self.sasl_mechanism,
             read_only: self.read_only,
             offline: false,
+            ..self.original.clone()
         })
     }
 
@@ -318,12 +388,19 @@ impl ConnectionForm {
             Field::Port => Some(&mut self.port),
             Field::BindDn => Some(&mut self.bind_dn),
             Field::BaseDn => Some(&mut self.base_dn),
+            Field::StartDn => Some(&mut self.start_dn),
+            Field::DefaultFilter => Some(&mut self.default_filter),
+            Field::RootDseAttrs => Some(&mut self.root_dse_attrs),
             Field::Folder => Some(&mut self.folder),
             Field::PasswordCommand => Some(&mut self.password_command),
             Field::PageSize => Some(&mut self.page_size),
             Field::Timeout => Some(&mut self.timeout),
             // These are cycled with special keys, not typed
-            Field::TlsMode | Field::CredentialMethod | Field::RelaxRules | Field::ReadOnly => None,
+            Field::TlsMode
+            | Field::BindMethod
+            | Field::CredentialMethod
+            | Field::RelaxRules
+            | Field::ReadOnly => None,
         }
     }
 
@@ -467,6 +544,10 @@ impl ConnectionForm {
                         self.tls_mode = self.tls_mode.next();
                         Action::None
                     }
+                    Field::BindMethod => {
+                        self.sasl_mechanism = SaslMechanism::cycle(self.sasl_mechanism);
+                        Action::None
+                    }
                     Field::CredentialMethod => {
                         self.credential_method = match self.credential_method {
                             CredentialMethod::Prompt => CredentialMethod::Command,
@@ -560,15 +641,19 @@ impl ConnectionForm {
 
         let editable = self.mode != FormMode::View;
 
-        // Layout: 13 fields at 2 lines each + hints
+        // Layout: 17 fields at 2 lines each + hints
         let layout = Layout::vertical([
             Constraint::Length(2), // Name
             Constraint::Length(2), // Host
             Constraint::Length(2), // Port
             Constraint::Length(2), // Bind DN
             Constraint::Length(2), // Base DN
+            Constraint::Length(2), // Start DN
+            Constraint::Length(2), // Default Filter
+            Constraint::Length(2), // RootDSE Attributes
             Constraint::Length(2), // Folder
             Constraint::Length(2), // TLS Mode
+            Constraint::Length(2), // Bind Method
             Constraint::Length(2), // Credential Method
             Constraint::Length(2), // Password Command
             Constraint::Length(2), // Page Size
@@ -580,7 +665,13 @@ impl ConnectionForm {
         .split(inner);
 
         self.render_field(frame, layout[0], "Name", &self.name, Field::Name, editable);
-        self.render_field(frame, layout[1], "Host", &self.host, Field::Host, editable);
+        let host = match &self.active_host {
+            Some(active) if !editable && self.host.contains(',') => {
+                format!("{}  (using {})", self.host, active)
+            }
+            _ => self.host.clone(),
+        };
+        self.render_field(frame, layout[1], "Host", &host, Field::Host, editable);
         self.render_field(frame, layout[2], "Port", &self.port, Field::Port, editable);
         self.render_field(
             frame,
@@ -601,6 +692,30 @@ impl ConnectionForm {
         self.render_field(
             frame,
             layout[5],
+            "Start DN",
+            &self.start_dn,
+            Field::StartDn,
+            editable,
+        );
+        self.render_field(
+            frame,
+            layout[6],
+            "Default Filter",
+            &self.default_filter,
+            Field::DefaultFilter,
+            editable,
+        );
+        self.render_field(
+            frame,
+            layout[7],
+            "RootDSE Attrs",
+            &self.root_dse_attrs,
+            Field::RootDseAttrs,
+            editable,
+        );
+        self.render_field(
+            frame,
+            layout[8],
             "Folder",
             &self.folder,
             Field::Folder,
@@ -610,13 +725,27 @@ impl ConnectionForm {
         // TLS Mode (special: shows label, not a text buffer)
         self.render_field(
             frame,
-            layout[6],
+            layout[9],
             "TLS Mode",
             self.tls_mode.label(),
             Field::TlsMode,
             editable,
         );
 
+        // Bind Method
+        let bind_label = match self.sasl_mechanism {
+            Some(mechanism) => format!("SASL {}", mechanism.name()),
+            None => "Simple".to_string(),
+        };
+        self.render_field(
+            frame,
+            layout[10],
+            "Bind Method",
+            &bind_label,
+            Field::BindMethod,
+            editable,
+        );
+
         // Credential Method
         let cred_label = match self.credential_method {
             CredentialMethod::Prompt => "Prompt",
@@ -626,7 +755,7 @@ impl ConnectionForm {
         };
         self.render_field(
             frame,
-            layout[7],
+            layout[11],
             "Credential",
             cred_label,
             Field::CredentialMethod,
@@ -635,7 +764,7 @@ impl ConnectionForm {
 
         self.render_field(
             frame,
-            layout[8],
+            layout[12],
             "Password Cmd",
             &self.password_command,
             Field::PasswordCommand,
@@ -643,7 +772,7 @@ impl ConnectionForm {
         );
         self.render_field(
             frame,
-            layout[9],
+            layout[13],
             "Page Size",
             &self.page_size,
             Field::PageSize,
@@ -651,7 +780,7 @@ impl ConnectionForm {
         );
         self.render_field(
             frame,
-            layout[10],
+            layout[14],
             "Timeout (s)",
             &self.timeout,
             Field::Timeout,
@@ -662,7 +791,7 @@ impl ConnectionForm {
         let relax_str = if self.relax_rules { "Yes" } else { "No" };
         self.render_field(
             frame,
-            layout[11],
+            layout[15],
             "Relax Rules",
             relax_str,
             Field::RelaxRules,
@@ -673,7 +802,7 @@ impl ConnectionForm {
         let read_only_str = if self.read_only { "Yes" } else { "No" };
         self.render_field(
             frame,
-            layout[12],
+            layout[16],
             "Read Only",
             read_only_str,
             Field::ReadOnly,
@@ -691,7 +820,7 @@ impl ConnectionForm {
             FormMode::FolderView | FormMode::FolderEdit => unreachable!(),
         };
         let hints = Paragraph::new(Line::from(Span::styled(hints_text, self.theme.dimmed)));
-        frame.render_widget(hints, layout[13]);
+        frame.render_widget(hints, layout[17]);
     }
 
     fn render_folder(&self, frame: &mut Frame, area: Rect, focused: bool) {
@@ -6,11 +6,19 @@ use ratatui::Frame;
 use tui_tree_widget::{Tree, TreeItem, TreeState};
 
 use crate::action::{Action, ConnectionId};
-use crate::config::ConnectionProfile;
+use crate::config::{normalize_folder_path, ConnectionProfile};
 use crate::theme::Theme;
 
 use std::collections::BTreeMap;
 
+/// One level of the profile folder hierarchy.
+#[derive(Default)]
+struct FolderNode {
+    folders: BTreeMap<String, FolderNode>,
+    /// (profile index, profile name) in config order.
+    profiles: Vec<(usize, String)>,
+}
+
 /// Info about an active connection for display in the tree.
 #[derive(Debug, Clone)]
 pub struct ActiveConnInfo {
@@ -41,6 +49,10 @@ impl ConnectionsTree {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     /// Get the currently selected key from the tree state.
     fn selected_key(&self) -> Option<&String> {
         self.tree_state.selected().last()
@@ -133,6 +145,18 @@ impl ConnectionsTree {
                     Action::None
                 }
             }
+            KeyCode::Char('K') => match self.selected_profile_index() {
+                Some(idx) => Action::ConnMgrMoveProfile(idx, -1),
+                None => Action::None,
+            },
+            KeyCode::Char('J') => match self.selected_profile_index() {
+                Some(idx) => Action::ConnMgrMoveProfile(idx, 1),
+                None => Action::None,
+            },
+            KeyCode::Char('m') => match self.selected_profile_index() {
+                Some(idx) => Action::ShowMoveProfileDialog(idx),
+                None => Action::None,
+            },
             KeyCode::Char('x') => Action::ConnMgrExport,
             KeyCode::Char('i') => Action::ConnMgrImport,
             _ => Action::None,
@@ -179,83 +203,73 @@ impl ConnectionsTree {
             top_items.push(active_section);
         }
 
-        // Group profiles by folder
-        let mut folders: BTreeMap<String, Vec<(usize, &ConnectionProfile)>> = BTreeMap::new();
-        let mut ungrouped: Vec<(usize, &ConnectionProfile)> = Vec::new();
-
+        // Group profiles into a folder hierarchy ("prod/emea/dc1")
+        let mut root = FolderNode::default();
         for (idx, profile) in profiles.iter().enumerate() {
-            if let Some(ref folder) = profile.folder {
-                folders
-                    .entry(folder.clone())
-                    .or_default()
-                    .push((idx, profile));
-            } else {
-                ungrouped.push((idx, profile));
+            let mut node = &mut root;
+            if let Some(path) = profile.folder.as_deref().and_then(normalize_folder_path) {
+                for part in path.split('/') {
+                    node = node.folders.entry(part.to_string()).or_default();
+                }
             }
+            node.profiles.push((idx, profile.name.clone()));
         }
 
-        // Build folder tree nodes
-        // We need to handle nested folders like "System/Production"
-        let mut folder_tree: BTreeMap<String, Vec<TreeItem<'static, String>>> = BTreeMap::new();
+        top_items.extend(self.folder_children(&root, ""));
+        // "+ New..." at the bottom
+        top_items.push(TreeItem::new_leaf(
+            "action:new".to_string(),
+            "+ New...".to_string(),
+        ));
 
-        for (folder_path, profiles_in_folder) in &folders {
-            let parts: Vec<&str> = folder_path.split('/').collect();
-            let folder_str = folder_path.as_str();
-            let leaf_folder = parts.last().unwrap_or(&folder_str);
+        top_items
+    }
 
-            let mut children = Vec::new();
-            for (idx, profile) in profiles_in_folder {
-                let key = format!("profile:{}", idx);
-                self.profile_keys.push((key.clone(), *idx));
-                let item = TreeItem::new_leaf(key, profile.name.clone());
-                children.push(item);
-            }
+    /// Tree items for a folder's contents: subfolders first (sorted by name),
+    /// then profiles in config order.
+    fn folder_children(&mut self, node: &FolderNode, path: &str) -> Vec<TreeItem<'static, String>> {
+        let mut items = Vec::new();
 
-            if parts.len() == 1 {
-                // Simple folder
-                let folder_key = format!("folder:{}", folder_path);
-                self.folder_keys
-                    .push((folder_key.clone(), folder_path.clone()));
-                let folder_item = TreeItem::new(folder_key, leaf_folder.to_string(), children)
-                    .expect("tree item");
-                top_items.push(folder_item);
+        for (name, child) in &node.folders {
+            let child_path = if path.is_empty() {
+                name.clone()
             } else {
-                // Nested: group under top-level
-                let top_key = parts[0].to_string();
-                let sub_key = parts[1..].join("/");
-                let sub_folder_key = format!("folder:{}", folder_path);
-                self.folder_keys
-                    .push((sub_folder_key.clone(), folder_path.clone()));
-                let sub_item =
-                    TreeItem::new(sub_folder_key, sub_key.clone(), children).expect("tree item");
-                folder_tree.entry(top_key).or_default().push(sub_item);
-            }
-        }
-
-        // Merge nested folder groups
-        for (top_name, sub_items) in folder_tree {
-            let folder_key = format!("folder:{}", top_name);
+                format!("{}/{}", path, name)
+            };
+            let folder_key = format!("folder:{}", child_path);
             self.folder_keys
-                .push((folder_key.clone(), top_name.clone()));
-            let folder_item = TreeItem::new(folder_key, top_name, sub_items).expect("tree item");
-            top_items.push(folder_item);
+                .push((folder_key.clone(), child_path.clone()));
+            let children = self.folder_children(child, &child_path);
+            let item = TreeItem::new(folder_key, name.clone(), children).expect("tree item");
+            items.push(item);
         }
 
-        // Ungrouped profiles at root level
-        for (idx, profile) in &ungrouped {
+        for (idx, name) in &node.profiles {
             let key = format!("profile:{}", idx);
             self.profile_keys.push((key.clone(), *idx));
-            let item = TreeItem::new_leaf(key, profile.name.clone());
-            top_items.push(item);
+            items.push(TreeItem::new_leaf(key, name.clone()));
         }
 
-        // "+ New..." at the bottom
-        top_items.push(TreeItem::new_leaf(
-            "action:new".to_string(),
-            "+ New...".to_string(),
-        ));
+        items
+    }
 
-        top_items
+    /// Select a profile in the tree, opening the folders that contain it.
+    pub fn select_profile(&mut self, idx: usize, folder: Option<&str>) {
+        let mut path = Vec::new();
+        if let Some(folder) = folder.and_then(normalize_folder_path) {
+            let mut prefix = String::new();
+            for part in folder.split('/') {
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(part);
+                let key = format!("folder:{}", prefix);
+                path.push(key.clone());
+                self.tree_state.open(path.clone());
+            }
+        }
+        path.push(format!("profile:{}", idx));
+        self.tree_state.select(path);
     }
 
     pub fn render_with_items(
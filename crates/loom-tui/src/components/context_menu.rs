@@ -5,6 +5,8 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
+use loom_core::export::ExportFormat;
+
 use crate::action::Action;
 use crate::theme::Theme;
 
@@ -36,9 +38,21 @@ impl ContextMenu {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     /// Show the menu for a tree node.
     pub fn show_for_tree(&mut self, dn: &str) {
-        self.items = vec![
+        self.items = Self::tree_items(dn);
+        self.selected = 0;
+        self.anchor = None;
+        self.visible = true;
+    }
+
+    /// Items for a tree node, also offered by the command palette.
+    pub fn tree_items(dn: &str) -> Vec<MenuItem> {
+        vec![
             MenuItem {
                 label: "Copy DN".into(),
                 hint: String::new(),
@@ -55,9 +69,9 @@ impl ContextMenu {
                 action: Action::ShowExportDialog,
             },
             MenuItem {
-                label: "Refresh".into(),
+                label: "Refresh Subtree".into(),
                 hint: "r".into(),
-                action: Action::EntryRefresh,
+                action: Action::RefreshSubtree(dn.to_string()),
             },
             MenuItem {
                 label: "Delete Entry".into(),
@@ -67,15 +81,99 @@ impl ContextMenu {
                     Box::new(Action::DeleteEntry(dn.to_string())),
                 ),
             },
-        ];
+            MenuItem {
+                label: "Import Entries".into(),
+                hint: String::new(),
+                action: Action::ShowImportDialog,
+            },
+            MenuItem {
+                label: "Import Group Memberships".into(),
+                hint: String::new(),
+                action: Action::ShowMembershipImportDialog,
+            },
+            MenuItem {
+                label: "Save Dry-Run Script".into(),
+                hint: String::new(),
+                action: Action::ExportDryRunScript,
+            },
+            MenuItem {
+                label: "Cut Entry".into(),
+                hint: "x".into(),
+                action: Action::CutEntry {
+                    dn: dn.to_string(),
+                    subtree: false,
+                },
+            },
+            MenuItem {
+                label: "Cut Subtree".into(),
+                hint: "X".into(),
+                action: Action::CutEntry {
+                    dn: dn.to_string(),
+                    subtree: true,
+                },
+            },
+            MenuItem {
+                label: "Paste Under".into(),
+                hint: "p".into(),
+                action: Action::PasteEntry(dn.to_string()),
+            },
+            MenuItem {
+                label: "Toggle Favorite".into(),
+                hint: "*".into(),
+                action: Action::ToggleFavorite(dn.to_string()),
+            },
+            MenuItem {
+                label: "Toggle Reference Entry".into(),
+                hint: "M".into(),
+                action: Action::ToggleReference(dn.to_string()),
+            },
+            MenuItem {
+                label: "Safe Rename User (AD)".into(),
+                hint: String::new(),
+                action: Action::ShowRenameUser(dn.to_string()),
+            },
+            MenuItem {
+                label: "Password Policy".into(),
+                hint: String::new(),
+                action: Action::ShowPasswordPolicy(dn.to_string()),
+            },
+            MenuItem {
+                label: "Resolve Nested Membership".into(),
+                hint: String::new(),
+                action: Action::ShowNestedMembership {
+                    dn: dn.to_string(),
+                    expansion: None,
+                },
+            },
+            MenuItem {
+                label: "Find Orphaned References".into(),
+                hint: String::new(),
+                action: Action::ShowOrphanScan(dn.to_string()),
+            },
+            MenuItem {
+                label: "Attribute Statistics".into(),
+                hint: String::new(),
+                action: Action::ShowAttributeStats(dn.to_string()),
+            },
+            MenuItem {
+                label: "Entry Report".into(),
+                hint: String::new(),
+                action: Action::ShowReportDialog(vec![dn.to_string()]),
+            },
+        ]
+    }
+
+    /// Show the menu for a detail panel attribute.
+    pub fn show_for_detail(&mut self, dn: &str, attr_name: &str, attr_value: &str) {
+        self.items = Self::detail_items(dn, attr_name, attr_value);
         self.selected = 0;
         self.anchor = None;
         self.visible = true;
     }
 
-    /// Show the menu for a detail panel attribute.
-    pub fn show_for_detail(&mut self, dn: &str, attr_name: &str, attr_value: &str) {
-        self.items = vec![
+    /// Items for a detail panel attribute, also offered by the command palette.
+    pub fn detail_items(dn: &str, attr_name: &str, attr_value: &str) -> Vec<MenuItem> {
+        let mut items = vec![
             MenuItem {
                 label: "Copy Attribute Name".into(),
                 hint: String::new(),
@@ -100,6 +198,15 @@ impl ContextMenu {
                     attr_value.to_string(),
                 ),
             },
+            MenuItem {
+                label: "Edit in External Editor".into(),
+                hint: "E".into(),
+                action: Action::EditInExternalEditor(
+                    dn.to_string(),
+                    attr_name.to_string(),
+                    attr_value.to_string(),
+                ),
+            },
             MenuItem {
                 label: "Add Value".into(),
                 hint: "+".into(),
@@ -118,6 +225,82 @@ impl ContextMenu {
                 ),
             },
         ];
+        items.splice(3..3, Self::copy_entry_items(dn, &["*".to_string()]));
+        items.push(MenuItem {
+            label: "Export This Entry".into(),
+            hint: "w".into(),
+            action: Action::ShowExportEntriesDialog(vec![dn.to_string()]),
+        });
+        if loom_core::dn::looks_like_dn(attr_value) {
+            items.push(MenuItem {
+                label: "Open Referenced Entry".into(),
+                hint: "o".into(),
+                action: Action::FollowReference(attr_name.to_string(), attr_value.to_string()),
+            });
+        }
+        items
+    }
+
+    /// Copy the whole entry as LDIF or JSON, or `csv_attributes` of it as
+    /// a CSV row under its header.
+    fn copy_entry_items(dn: &str, csv_attributes: &[String]) -> Vec<MenuItem> {
+        let copy = |format, attributes: &[String]| Action::CopyEntryAs {
+            dn: dn.to_string(),
+            format,
+            attributes: attributes.to_vec(),
+        };
+        let all = ["*".to_string()];
+        vec![
+            MenuItem {
+                label: "Copy as LDIF".into(),
+                hint: String::new(),
+                action: copy(ExportFormat::Ldif, &all),
+            },
+            MenuItem {
+                label: "Copy as JSON".into(),
+                hint: String::new(),
+                action: copy(ExportFormat::Json, &all),
+            },
+            MenuItem {
+                label: "Copy Row as CSV".into(),
+                hint: String::new(),
+                action: copy(ExportFormat::Csv, csv_attributes),
+            },
+        ]
+    }
+
+    /// Show the menu for a search result. The CSV row has the columns the
+    /// results table shows, or every attribute when it shows none.
+    pub fn show_for_search_result(&mut self, dn: &str, columns: &[String]) {
+        let csv_attributes = if columns.is_empty() {
+            vec!["*".to_string()]
+        } else {
+            columns.to_vec()
+        };
+        let mut items = vec![MenuItem {
+            label: "Copy DN".into(),
+            hint: String::new(),
+            action: Action::CopyToClipboard(dn.to_string()),
+        }];
+        items.extend(Self::copy_entry_items(dn, &csv_attributes));
+        items.extend([
+            MenuItem {
+                label: "Go to Entry".into(),
+                hint: "Enter".into(),
+                action: Action::RevealEntry(dn.to_string()),
+            },
+            MenuItem {
+                label: "Export This Entry".into(),
+                hint: String::new(),
+                action: Action::ShowExportEntriesDialog(vec![dn.to_string()]),
+            },
+            MenuItem {
+                label: "Entry Report".into(),
+                hint: String::new(),
+                action: Action::ShowReportDialog(vec![dn.to_string()]),
+            },
+        ]);
+        self.items = items;
         self.selected = 0;
         self.anchor = None;
         self.visible = true;
@@ -318,10 +501,13 @@ mod tests {
         menu.show_for_tree("dc=example,dc=com");
         assert!(menu.visible);
         assert_eq!(menu.selected, 0);
-        assert_eq!(menu.item_count(), 5);
+        assert_eq!(menu.item_count(), 19);
         assert_eq!(menu.items[0].label, "Copy DN");
         assert_eq!(menu.items[1].label, "Create Child Entry");
         assert_eq!(menu.items[4].label, "Delete Entry");
+        assert_eq!(menu.items[5].label, "Import Entries");
+        assert_eq!(menu.items[6].label, "Import Group Memberships");
+        assert_eq!(menu.items[10].label, "Paste Under");
     }
 
     #[test]
@@ -330,11 +516,33 @@ mod tests {
         menu.show_for_detail("dc=example,dc=com", "cn", "Test User");
         assert!(menu.visible);
         assert_eq!(menu.selected, 0);
-        assert_eq!(menu.item_count(), 6);
+        assert_eq!(menu.item_count(), 11);
         assert_eq!(menu.items[0].label, "Copy Attribute Name");
         assert_eq!(menu.items[1].label, "Copy Attribute Value");
         assert_eq!(menu.items[2].label, "Copy DN");
-        assert_eq!(menu.items[3].label, "Edit Value");
+        assert_eq!(menu.items[3].label, "Copy as LDIF");
+        assert_eq!(menu.items[6].label, "Edit Value");
+        assert_eq!(menu.items[10].label, "Export This Entry");
+
+        // DN values can be followed
+        menu.show_for_detail("dc=example,dc=com", "manager", "cn=Boss,dc=example,dc=com");
+        assert_eq!(menu.item_count(), 12);
+        assert_eq!(menu.items[11].label, "Open Referenced Entry");
+    }
+
+    #[test]
+    fn test_show_for_search_result_copies_shown_columns() {
+        let mut menu = make_menu();
+        menu.show_for_search_result("cn=a,dc=x", &["mail".to_string()]);
+        assert_eq!(menu.items[3].label, "Copy Row as CSV");
+        assert!(matches!(
+            &menu.items[3].action,
+            Action::CopyEntryAs { format: ExportFormat::Csv, attributes, .. } if attributes == &["mail"]
+        ));
+        assert!(matches!(
+            &menu.items[1].action,
+            Action::CopyEntryAs { format: ExportFormat::Ldif, attributes, .. } if attributes == &["*"]
+        ));
     }
 
     #[test]
@@ -439,7 +647,7 @@ mod tests {
         // 'e' should jump to "Export Subtree" (index 2)
         menu.handle_key_event(key(KeyCode::Char('e')));
         assert_eq!(menu.selected, 2);
-        // 'r' should jump to "Refresh" (index 3)
+        // 'r' should jump to "Refresh Subtree" (index 3)
         menu.handle_key_event(key(KeyCode::Char('r')));
         assert_eq!(menu.selected, 3);
         // 'd' should jump to "Delete Entry" (index 4)
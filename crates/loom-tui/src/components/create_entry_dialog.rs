@@ -4,6 +4,9 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
+use loom_core::dn;
+use loom_core::schema::SchemaCache;
+
 use crate::action::Action;
 use crate::components::popup::Popup;
 use crate::theme::Theme;
@@ -26,6 +29,8 @@ pub struct CreateEntryDialog {
     rdn: String,
     object_classes: String,
     extra_attributes: String,
+    /// The connection's schema, for listing required attributes.
+    schema: Option<SchemaCache>,
 }
 
 impl CreateEntryDialog {
@@ -39,9 +44,19 @@ impl CreateEntryDialog {
             rdn: String::new(),
             object_classes: String::new(),
             extra_attributes: String::new(),
+            schema: None,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn set_schema(&mut self, schema: Option<SchemaCache>) {
+        self.schema = schema;
+    }
+
     pub fn show(&mut self, parent_dn: String) {
         self.parent_dn = parent_dn;
         self.rdn.clear();
@@ -92,6 +107,60 @@ impl CreateEntryDialog {
         }
     }
 
+    /// Handle pasted text: an LDIF entry pre-fills every field, anything else
+    /// is inserted into the active field with line breaks removed.
+    pub fn paste(&mut self, text: &str) -> Action {
+        let is_ldif = text
+            .lines()
+            .map(str::trim)
+            .find(|l| !l.is_empty() && !l.starts_with('#') && !l.starts_with("version:"))
+            .is_some_and(|l| l.len() > 3 && l[..3].eq_ignore_ascii_case("dn:"));
+        if !is_ldif {
+            let line: String = text.chars().filter(|c| !matches!(c, '\r' | '\n')).collect();
+            self.active_text_buffer_mut().push_str(&line);
+            return Action::None;
+        }
+
+        let entry = match loom_core::import::ldif::parse_ldif(text) {
+            Ok(entries) if !entries.is_empty() => entries.into_iter().next().unwrap(),
+            _ => return Action::ErrorMessage("Pasted LDIF has no entry".to_string()),
+        };
+
+        let rdn = dn::rdn(&entry.dn).trim().to_string();
+        let (rdn_attr, rdn_val) = rdn.split_once('=').unwrap_or((rdn.as_str(), ""));
+        let mut object_classes = Vec::new();
+        let mut extras = Vec::new();
+        let mut skipped = 0;
+        for (attr, vals) in &entry.attributes {
+            for val in vals {
+                if attr.eq_ignore_ascii_case("objectClass") {
+                    object_classes.push(val.clone());
+                } else if attr.eq_ignore_ascii_case(rdn_attr.trim()) && val == rdn_val.trim() {
+                    // Added from the RDN on submit
+                } else if val.contains(',') {
+                    // The extra attributes field is comma-separated
+                    skipped += 1;
+                } else {
+                    extras.push(format!("{}={}", attr, val));
+                }
+            }
+        }
+
+        self.rdn = rdn;
+        self.object_classes = object_classes.join(", ");
+        self.extra_attributes = extras.join(", ");
+        self.active_field = CreateField::Rdn;
+
+        if skipped > 0 {
+            Action::StatusMessage(format!(
+                "Pasted LDIF entry; {} value(s) containing commas were skipped",
+                skipped
+            ))
+        } else {
+            Action::StatusMessage("Pasted LDIF entry".to_string())
+        }
+    }
+
     fn active_text_buffer_mut(&mut self) -> &mut String {
         match self.active_field {
             CreateField::Rdn => &mut self.rdn,
@@ -115,11 +184,32 @@ impl CreateEntryDialog {
         }
 
         let full_dn = format!("{},{}", rdn, self.parent_dn);
+        let attributes = self.entered_attributes();
 
+        let missing = self.missing_attributes(&attributes);
+        if !missing.is_empty() {
+            return Action::ErrorMessage(format!(
+                "Missing required attributes: {} (add them as extra attributes)",
+                missing.join(", ")
+            ));
+        }
+
+        self.hide();
+        Action::CreateEntry {
+            dn: full_dn,
+            attributes,
+        }
+    }
+
+    /// The attributes the entry would be created with: objectClass, the
+    /// RDN attribute and the extra `attr=value` pairs.
+    fn entered_attributes(&self) -> Vec<(String, Vec<String>)> {
+        let rdn = self.rdn.trim();
         let mut attributes: Vec<(String, Vec<String>)> = Vec::new();
 
         // Add objectClass values
-        let oc_values: Vec<String> = oc_input
+        let oc_values: Vec<String> = self
+            .object_classes
             .split(',')
             .map(|s| s.trim().to_string())
             .filter(|s| !s.is_empty())
@@ -154,12 +244,22 @@ impl CreateEntryDialog {
                 }
             }
         }
+        attributes
+    }
 
-        self.hide();
-        Action::CreateEntry {
-            dn: full_dn,
-            attributes,
-        }
+    /// MUST attributes of the entered object classes not yet given a value.
+    /// Empty when the schema isn't loaded.
+    fn missing_attributes(&self, attributes: &[(String, Vec<String>)]) -> Vec<String> {
+        let Some(schema) = &self.schema else {
+            return Vec::new();
+        };
+        let object_classes: Vec<&str> = attributes
+            .iter()
+            .find(|(a, _)| a == "objectClass")
+            .map(|(_, ocs)| ocs.iter().map(String::as_str).collect())
+            .unwrap_or_default();
+        let present: Vec<&str> = attributes.iter().map(|(a, _)| a.as_str()).collect();
+        schema.missing_must_attributes(&object_classes, &present)
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -186,6 +286,7 @@ impl CreateEntryDialog {
             Constraint::Length(2), // Object Classes
             Constraint::Length(2), // Additional Attributes
             Constraint::Length(2), // Preview full DN
+            Constraint::Length(2), // Missing MUST attributes
             Constraint::Min(1),    // Hints
         ])
         .split(inner);
@@ -237,12 +338,25 @@ impl CreateEntryDialog {
         ];
         frame.render_widget(Paragraph::new(preview_lines), layout[4]);
 
+        // Required attributes still to fill in
+        let missing = self.missing_attributes(&self.entered_attributes());
+        if !missing.is_empty() {
+            let missing_lines = vec![
+                Line::from(Span::styled(
+                    "Missing required attributes:",
+                    self.theme.dimmed,
+                )),
+                Line::from(Span::styled(missing.join(", "), self.theme.error)),
+            ];
+            frame.render_widget(Paragraph::new(missing_lines), layout[5]);
+        }
+
         // Hints
         let hints = Paragraph::new(Line::from(Span::styled(
             "Tab:next field  Enter:create  Esc:cancel",
             self.theme.dimmed,
         )));
-        frame.render_widget(hints, layout[5]);
+        frame.render_widget(hints, layout[6]);
     }
 
     fn render_text_field(
@@ -279,3 +393,67 @@ impl CreateEntryDialog {
         frame.render_widget(Paragraph::new(lines), area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_paste_ldif_prefills_fields() {
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        dialog.show("ou=People,dc=example,dc=com".to_string());
+        let action = dialog.paste(
+            "dn: cn=Alice,ou=Old,dc=example,dc=com\n\
+             objectClass: top\n\
+             objectClass: person\n\
+             cn: Alice\n\
+             sn: Smith\n\
+             description: Smith, Alice\n",
+        );
+
+        assert_eq!(dialog.rdn, "cn=Alice");
+        assert_eq!(dialog.object_classes, "top, person");
+        assert_eq!(dialog.extra_attributes, "sn=Smith");
+        assert!(matches!(action, Action::StatusMessage(msg) if msg.contains("1 value")));
+    }
+
+    #[test]
+    fn test_submit_blocked_until_must_attributes_given() {
+        let mut schema = SchemaCache::new();
+        schema.object_classes.insert(
+            "person".to_string(),
+            loom_core::schema::ObjectClassInfo {
+                oid: "2.5.6.6".to_string(),
+                names: vec!["person".to_string()],
+                description: None,
+                superior: None,
+                kind: loom_core::schema::ObjectClassKind::Structural,
+                must: vec!["cn".to_string(), "sn".to_string()],
+                may: Vec::new(),
+            },
+        );
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        dialog.set_schema(Some(schema));
+        dialog.show("ou=People,dc=example,dc=com".to_string());
+        dialog.rdn = "cn=Alice".to_string();
+        dialog.object_classes = "person".to_string();
+
+        match dialog.submit() {
+            Action::ErrorMessage(msg) => assert!(msg.contains(": sn ")),
+            other => panic!("expected ErrorMessage, got {:?}", other),
+        }
+        assert!(dialog.visible);
+
+        dialog.extra_attributes = "sn=Smith".to_string();
+        assert!(matches!(dialog.submit(), Action::CreateEntry { .. }));
+    }
+
+    #[test]
+    fn test_paste_plain_text_into_active_field() {
+        let mut dialog = CreateEntryDialog::new(Theme::load("dark"));
+        dialog.show("dc=example,dc=com".to_string());
+        dialog.paste("cn=Bob\n");
+        assert_eq!(dialog.rdn, "cn=Bob");
+        assert!(dialog.object_classes.is_empty());
+    }
+}
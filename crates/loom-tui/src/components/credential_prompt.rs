@@ -4,7 +4,7 @@ use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Clear, Paragraph};
 use ratatui::Frame;
 
-use crate::action::Action;
+use crate::action::{Action, ConnectionId};
 use crate::components::popup::Popup;
 use crate::config::ConnectionProfile;
 use crate::theme::Theme;
@@ -24,6 +24,8 @@ pub struct CredentialPromptDialog {
     bind_dn: String,
     password: String,
     profile: Option<ConnectionProfile>,
+    /// The tab to rebind instead of opening a new one, after an unlock.
+    rebind: Option<ConnectionId>,
 }
 
 impl CredentialPromptDialog {
@@ -36,9 +38,15 @@ impl CredentialPromptDialog {
             bind_dn: String::new(),
             password: String::new(),
             profile: None,
+            rebind: None,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self, profile: ConnectionProfile) {
         self.bind_dn = profile.bind_dn.clone().unwrap_or_default();
         self.password.clear();
@@ -49,10 +57,17 @@ impl CredentialPromptDialog {
             Field::Password
         };
         self.profile = Some(profile);
+        self.rebind = None;
         self.visible = true;
         self.popup.show();
     }
 
+    /// Ask for the password an idle lock wiped from an open tab.
+    pub fn show_rebind(&mut self, conn_id: ConnectionId, profile: ConnectionProfile) {
+        self.show(profile);
+        self.rebind = Some(conn_id);
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.popup.hide();
@@ -63,7 +78,10 @@ impl CredentialPromptDialog {
         match key.code {
             KeyCode::Esc => {
                 self.hide();
-                Action::ClosePopup
+                match self.rebind.take() {
+                    Some(conn_id) => Action::SkipRebind(conn_id),
+                    None => Action::ClosePopup,
+                }
             }
             KeyCode::Tab | KeyCode::BackTab => {
                 self.active_field = match self.active_field {
@@ -101,7 +119,14 @@ impl CredentialPromptDialog {
 
         let password = self.password.clone();
         self.hide();
-        Action::ConnectWithCredentials(profile, password)
+        match self.rebind.take() {
+            Some(conn_id) => Action::RebindWithCredentials(
+                conn_id,
+                profile.bind_dn.unwrap_or_default(),
+                password,
+            ),
+            None => Action::ConnectWithCredentials(profile, password),
+        }
     }
 
     fn active_buffer_mut(&mut self) -> &mut String {
@@ -175,7 +200,11 @@ impl CredentialPromptDialog {
 
         // Hints
         let hints = Paragraph::new(Line::from(Span::styled(
-            "Tab:switch field  Enter:connect  Esc:cancel",
+            if self.rebind.is_some() {
+                "Session unlocked, password needed  Enter:rebind  Esc:skip"
+            } else {
+                "Tab:switch field  Enter:connect  Esc:cancel"
+            },
             self.theme.dimmed,
         )));
         frame.render_widget(hints, layout[3]);
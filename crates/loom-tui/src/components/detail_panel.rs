@@ -1,14 +1,23 @@
+use std::collections::{HashMap, HashSet};
+
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span, Text};
-use ratatui::widgets::{Block, BorderType, Borders, Cell, Row, Table, TableState};
+use ratatui::widgets::{
+    Block, BorderType, Borders, Cell, List, ListItem, ListState, Row, Table, TableState,
+};
 use ratatui::Frame;
 
 use crate::action::{Action, ContextMenuSource};
 use crate::component::Component;
+use crate::config::AttributeGroup;
 use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+use crate::widgets::scroll::scroll_view;
 use loom_core::entry::LdapEntry;
+use loom_core::format::DisplayFormats;
+use loom_core::policy::EditPolicy;
 use loom_core::schema::SchemaCache;
 
 /// Whether an attribute is user-editable or operational/system.
@@ -28,15 +37,68 @@ struct AttrRow {
     /// True for first value of an attribute (displays the attribute name).
     is_first: bool,
     kind: AttrKind,
+    /// Set when the edit policy reserves the attribute for another system.
+    locked: bool,
+    /// Index into the panel's group names.
+    group: usize,
+    /// How the value compares with the reference entry, when one is set.
+    diff: Option<ValueDiff>,
+}
+
+/// A value compared with the reference entry's.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ValueDiff {
+    Same,
+    /// The reference has the attribute, but not this value.
+    Different,
+    /// The reference doesn't have the attribute.
+    Extra,
+    /// Only the reference has this value; shown but not editable.
+    Missing,
 }
 
+/// A line of the table: a group heading or one attribute value.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum ViewRow {
+    Header(usize),
+    Value(usize),
+}
+
+/// Name of the group holding attributes no configured group lists.
+const OTHER_GROUP: &str = "other";
+/// Unlisted operational attributes join this group when it is configured.
+const OPERATIONAL_GROUP: &str = "operational";
+
 /// The top-right panel: entry detail viewer.
 pub struct DetailPanel {
     pub entry: Option<LdapEntry>,
     pub table_state: TableState,
     rows: Vec<AttrRow>,
+    /// What the table shows, after grouping, collapsing and filtering.
+    view: Vec<ViewRow>,
+    /// Group names in display order; empty when grouping is off.
+    groups: Vec<String>,
+    /// Lowercased attribute name to its group.
+    group_of: HashMap<String, usize>,
+    /// Collapsed groups by name, kept across entries.
+    collapsed: HashSet<String>,
+    /// Fuzzy filter on attribute names.
+    filter: String,
+    filter_input: bool,
+    matcher: FuzzyFilter,
+    /// Showing the entry as LDIF instead of the attribute table.
+    ldif_view: bool,
+    ldif_lines: Vec<String>,
+    ldif_state: ListState,
     theme: Theme,
+    formats: DisplayFormats,
     area: Option<Rect>,
+    /// First table row as last drawn, for mouse clicks and scrolling.
+    table_offset: std::cell::Cell<usize>,
+    /// First LDIF line as last drawn.
+    ldif_offset: std::cell::Cell<usize>,
+    /// Entry the shown ones are compared with, kept across entries.
+    reference: Option<LdapEntry>,
 }
 
 impl DetailPanel {
@@ -45,33 +107,440 @@ impl DetailPanel {
             entry: None,
             table_state: TableState::default(),
             rows: Vec::new(),
+            view: Vec::new(),
+            groups: Vec::new(),
+            group_of: HashMap::new(),
+            collapsed: HashSet::new(),
+            filter: String::new(),
+            filter_input: false,
+            matcher: FuzzyFilter::new(),
+            ldif_view: false,
+            ldif_lines: Vec::new(),
+            ldif_state: ListState::default(),
             theme,
+            formats: DisplayFormats::default(),
             area: None,
+            table_offset: std::cell::Cell::new(0),
+            ldif_offset: std::cell::Cell::new(0),
+            reference: None,
         }
     }
 
-    pub fn set_entry(&mut self, entry: LdapEntry, schema: Option<&SchemaCache>) {
-        self.rows = build_rows(&entry, schema);
-        self.table_state
-            .select(if self.rows.is_empty() { None } else { Some(0) });
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
+    /// Render values with the configured `[display.attributes]` rules.
+    pub fn with_formats(mut self, formats: DisplayFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
+    /// Show attributes in collapsible `groups`; none shows a flat list.
+    pub fn with_groups(mut self, groups: Vec<AttributeGroup>) -> Self {
+        self.group_of.clear();
+        self.groups.clear();
+        if groups.is_empty() {
+            return self;
+        }
+        for (i, group) in groups.iter().enumerate() {
+            for attr in &group.attributes {
+                self.group_of.entry(attr.to_lowercase()).or_insert(i);
+            }
+        }
+        self.groups = groups.into_iter().map(|g| g.name).collect();
+        if !self.groups.iter().any(|g| g == OTHER_GROUP) {
+            self.groups.push(OTHER_GROUP.to_string());
+        }
+        self
+    }
+
+    pub fn set_entry(
+        &mut self,
+        entry: LdapEntry,
+        schema: Option<&SchemaCache>,
+        policy: Option<&EditPolicy>,
+    ) {
+        self.rows = build_rows(&entry, schema, policy, &self.formats);
+        let groups: Vec<usize> = self
+            .rows
+            .iter()
+            .map(|r| self.group_for(&r.attr_name, r.kind))
+            .collect();
+        for (row, group) in self.rows.iter_mut().zip(groups) {
+            row.group = group;
+        }
+        // Stable, so attributes keep their order within a group
+        self.rows.sort_by_key(|r| r.group);
+        self.ldif_lines = entry_ldif(&entry);
+        self.ldif_state.select(Some(0));
         self.entry = Some(entry);
+        self.table_state.select(None);
+        self.compare_with_reference();
+        self.rebuild_view();
+    }
+
+    /// Compare the entries shown from now on with `reference`, or stop.
+    pub fn set_reference(&mut self, reference: Option<LdapEntry>) {
+        self.reference = reference;
+        self.compare_with_reference();
+        self.rebuild_view();
+    }
+
+    pub fn reference(&self) -> Option<&LdapEntry> {
+        self.reference.as_ref()
+    }
+
+    /// Mark each value against the reference entry, and add the reference's
+    /// values the entry lacks.
+    fn compare_with_reference(&mut self) {
+        self.rows.retain(|r| r.diff != Some(ValueDiff::Missing));
+        let Some(reference) = &self.reference else {
+            for row in &mut self.rows {
+                row.diff = None;
+            }
+            return;
+        };
+        for row in &mut self.rows {
+            row.diff = Some(match find_attr(reference, &row.attr_name) {
+                None => ValueDiff::Extra,
+                Some((_, values)) if values.contains(&row.raw_value) => ValueDiff::Same,
+                Some(_) => ValueDiff::Different,
+            });
+        }
+
+        let mut missing = Vec::new();
+        for (name, values) in &reference.attributes {
+            // Listed under the entry's spelling of the name, if it has one
+            let have = self.entry.as_ref().and_then(|e| find_attr(e, name));
+            let attr_name = have.map_or(name, |(attr, _)| attr);
+            let mut is_first = have.is_none();
+            for value in values {
+                if have.is_some_and(|(_, h)| h.contains(value)) {
+                    continue;
+                }
+                missing.push(AttrRow {
+                    attr_name: attr_name.clone(),
+                    raw_value: value.clone(),
+                    display_value: sanitize_for_display(&self.formats.display(name, value)),
+                    is_first,
+                    kind: AttrKind::Normal,
+                    locked: false,
+                    group: 0,
+                    diff: Some(ValueDiff::Missing),
+                });
+                is_first = false;
+            }
+        }
+        for row in &mut missing {
+            row.group = self.group_for(&row.attr_name, row.kind);
+        }
+        self.rows.extend(missing);
+        // Stable: each attribute's own values stay ahead of the missing ones
+        self.rows
+            .sort_by_cached_key(|r| (r.group, r.attr_name.to_lowercase()));
     }
 
     pub fn clear(&mut self) {
         self.entry = None;
         self.rows.clear();
+        self.view.clear();
         self.table_state.select(None);
+        self.ldif_lines.clear();
+        self.ldif_state.select(None);
+    }
+
+    /// True while the attribute filter is being typed, when every key
+    /// belongs to the panel.
+    pub fn is_filtering(&self) -> bool {
+        self.filter_input
     }
 
     /// Get the attribute name and raw value at the currently selected row.
     pub fn selected_attr_value(&self) -> Option<(&str, &str)> {
-        let idx = self.table_state.selected()?;
-        let row = self.rows.get(idx)?;
+        if self.ldif_view {
+            return None;
+        }
+        let row = self.selected_row()?;
+        if row.diff == Some(ValueDiff::Missing) {
+            return None;
+        }
         Some((&row.attr_name, &row.raw_value))
     }
 
+    fn selected_row(&self) -> Option<&AttrRow> {
+        match self.view.get(self.table_state.selected()?)? {
+            ViewRow::Value(i) => self.rows.get(*i),
+            ViewRow::Header(_) => None,
+        }
+    }
+
+    fn group_for(&self, attr: &str, kind: AttrKind) -> usize {
+        if self.groups.is_empty() {
+            return 0;
+        }
+        if let Some(&i) = self.group_of.get(&attr.to_lowercase()) {
+            return i;
+        }
+        let fallback = match kind {
+            AttrKind::Operational if self.groups.iter().any(|g| g == OPERATIONAL_GROUP) => {
+                OPERATIONAL_GROUP
+            }
+            _ => OTHER_GROUP,
+        };
+        self.groups
+            .iter()
+            .position(|g| g == fallback)
+            .unwrap_or(self.groups.len() - 1)
+    }
+
+    /// Recompute the visible lines, keeping the selection on the same value
+    /// when it is still shown, else moving it to the first value.
+    fn rebuild_view(&mut self) {
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|i| self.view.get(i).copied())
+            .filter(|v| matches!(v, ViewRow::Value(_)));
+
+        let query = self.filter.trim().to_string();
+        let mut matches: HashMap<&str, bool> = HashMap::new();
+        let mut view = Vec::new();
+        let mut current_group = None;
+        for (i, row) in self.rows.iter().enumerate() {
+            let matched = query.is_empty()
+                || *matches
+                    .entry(row.attr_name.as_str())
+                    .or_insert_with(|| self.matcher.score(&query, &row.attr_name).is_some());
+            if !matched {
+                continue;
+            }
+            if self.groups.is_empty() {
+                view.push(ViewRow::Value(i));
+                continue;
+            }
+            if current_group != Some(row.group) {
+                current_group = Some(row.group);
+                view.push(ViewRow::Header(row.group));
+            }
+            // Filtering looks inside collapsed groups too
+            if !query.is_empty() || !self.collapsed.contains(&self.groups[row.group]) {
+                view.push(ViewRow::Value(i));
+            }
+        }
+        self.view = view;
+
+        let index = selected
+            .and_then(|s| self.view.iter().position(|v| *v == s))
+            .or_else(|| {
+                self.view
+                    .iter()
+                    .position(|v| matches!(v, ViewRow::Value(_)))
+            })
+            .or(if self.view.is_empty() { None } else { Some(0) });
+        self.table_state.select(index);
+    }
+
+    fn toggle_group(&mut self, group: usize) {
+        let name = &self.groups[group];
+        if !self.collapsed.remove(name) {
+            self.collapsed.insert(name.clone());
+        }
+        self.rebuild_view();
+        // Stay on the heading rather than jumping into another group
+        let index = self.view.iter().position(|v| *v == ViewRow::Header(group));
+        self.table_state.select(index);
+    }
+
+    /// Lines the table row at `index` of the view takes up.
+    fn row_height(&self, index: usize) -> u16 {
+        match self.view.get(index) {
+            Some(ViewRow::Value(i)) => self.rows[*i].display_value.split('\n').count() as u16,
+            _ => 1,
+        }
+    }
+
+    /// Scroll the attribute table, or the LDIF lines, of the panel drawn in
+    /// `area` by `delta` rows. The selection stays put unless it would go
+    /// off screen.
+    pub fn scroll(&mut self, area: Rect, delta: isize) {
+        if self.ldif_view {
+            let height = area.height.saturating_sub(2) as usize;
+            let (offset, selected) = scroll_view(
+                self.ldif_offset.get(),
+                self.ldif_state.selected(),
+                self.ldif_lines.len(),
+                delta,
+                |_| height,
+            );
+            self.ldif_state.select(selected);
+            *self.ldif_state.offset_mut() = offset;
+            self.ldif_offset.set(offset);
+            return;
+        }
+        // Rows fit below the border and the DN line
+        let lines = area.height.saturating_sub(3);
+        let shown = |offset: usize| {
+            let mut used = 0;
+            (offset..self.view.len())
+                .take_while(|&i| {
+                    used += self.row_height(i);
+                    used <= lines
+                })
+                .count()
+        };
+        let (offset, selected) = scroll_view(
+            self.table_offset.get(),
+            self.table_state.selected(),
+            self.view.len(),
+            delta,
+            shown,
+        );
+        self.table_state.select(selected);
+        *self.table_state.offset_mut() = offset;
+        self.table_offset.set(offset);
+    }
+
+    /// Select the attribute row drawn at screen line `y`, with the panel
+    /// drawn in `area`. Returns false when no row is drawn there.
+    pub fn click_row(&mut self, area: Rect, y: u16) -> bool {
+        if self.ldif_view || self.entry.is_none() {
+            return false;
+        }
+        // Rows start below the border and the DN line
+        let mut top = area.y + 2;
+        let bottom = (area.y + area.height).saturating_sub(1);
+        for i in self.table_offset.get()..self.view.len() {
+            if top >= bottom {
+                break;
+            }
+            let height = self.row_height(i);
+            if y >= top && y < top + height {
+                self.table_state.select(Some(i));
+                return true;
+            }
+            top += height;
+        }
+        false
+    }
+
+    /// Open the selected row: fold a group heading, or edit a value.
+    pub fn activate_selected(&mut self) -> Action {
+        match self.selected_header() {
+            Some(group) => {
+                self.toggle_group(group);
+                Action::None
+            }
+            None => self.edit_selected(),
+        }
+    }
+
+    fn edit_selected(&self) -> Action {
+        match (&self.entry, self.selected_attr_value()) {
+            (Some(entry), Some((attr, val))) => {
+                Action::EditAttribute(entry.dn.clone(), attr.to_string(), val.to_string())
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn selected_header(&self) -> Option<usize> {
+        match self.view.get(self.table_state.selected()?)? {
+            ViewRow::Header(group) => Some(*group),
+            ViewRow::Value(_) => None,
+        }
+    }
+
+    /// Number of values in `group`, for its heading.
+    fn group_size(&self, group: usize) -> usize {
+        self.rows.iter().filter(|r| r.group == group).count()
+    }
+
+    fn handle_filter_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter_input = false;
+                self.filter.clear();
+                self.rebuild_view();
+            }
+            KeyCode::Enter => self.filter_input = false,
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.rebuild_view();
+            }
+            KeyCode::Up | KeyCode::Down => {
+                self.filter_input = false;
+                let action = self.handle_key_event(key);
+                self.filter_input = true;
+                return action;
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.rebuild_view();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    fn handle_ldif_key(&mut self, key: KeyEvent) -> Action {
+        let i = self.ldif_state.selected().unwrap_or(0);
+        let max = self.ldif_lines.len().saturating_sub(1);
+        match key.code {
+            KeyCode::Up | KeyCode::Char('k') => self.ldif_state.select(Some(i.saturating_sub(1))),
+            KeyCode::Down | KeyCode::Char('j') => self.ldif_state.select(Some((i + 1).min(max))),
+            KeyCode::PageUp => self.ldif_state.select(Some(i.saturating_sub(10))),
+            KeyCode::PageDown => self.ldif_state.select(Some((i + 10).min(max))),
+            KeyCode::Char('y') => {
+                if let Some(line) = self.ldif_lines.get(i) {
+                    return Action::CopyToClipboard(line.clone());
+                }
+            }
+            KeyCode::Char('Y') if !self.ldif_lines.is_empty() => {
+                return Action::CopyToClipboard(self.ldif_lines.join("\n") + "\n");
+            }
+            KeyCode::Char('v') => self.ldif_view = false,
+            KeyCode::Char('r') => return Action::EntryRefresh,
+            _ => {}
+        }
+        Action::None
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.filter_input {
+            return self.handle_filter_key(key);
+        }
+        if self.ldif_view {
+            return self.handle_ldif_key(key);
+        }
+        if matches!(key.code, KeyCode::Enter | KeyCode::Char(' ')) {
+            if let Some(group) = self.selected_header() {
+                self.toggle_group(group);
+                return Action::None;
+            }
+        }
         match key.code {
+            KeyCode::Char('f') if self.entry.is_some() => {
+                self.filter_input = true;
+                Action::None
+            }
+            KeyCode::Char('v') if self.entry.is_some() => {
+                self.ldif_view = true;
+                Action::None
+            }
+            KeyCode::Char('w') => match &self.entry {
+                Some(entry) => Action::ShowExportEntriesDialog(vec![entry.dn.clone()]),
+                None => Action::None,
+            },
+            KeyCode::Char('M') => match &self.entry {
+                Some(entry) => Action::ToggleReference(entry.dn.clone()),
+                None => Action::None,
+            },
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.rebuild_view();
+                Action::None
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 let i = self.table_state.selected().unwrap_or(0);
                 if i > 0 {
@@ -81,7 +550,7 @@ impl DetailPanel {
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 let i = self.table_state.selected().unwrap_or(0);
-                if i + 1 < self.rows.len() {
+                if i + 1 < self.view.len() {
                     self.table_state.select(Some(i + 1));
                 }
                 Action::None
@@ -93,15 +562,16 @@ impl DetailPanel {
             }
             KeyCode::PageDown => {
                 let i = self.table_state.selected().unwrap_or(0);
-                let max = self.rows.len().saturating_sub(1);
+                let max = self.view.len().saturating_sub(1);
                 self.table_state.select(Some((i + 10).min(max)));
                 Action::None
             }
-            KeyCode::Char('e') => {
-                // Edit the selected attribute value
+            KeyCode::Char('e') => self.edit_selected(),
+            KeyCode::Char('E') => {
+                // Edit the selected value in $EDITOR
                 if let (Some(entry), Some((attr, val))) = (&self.entry, self.selected_attr_value())
                 {
-                    return Action::EditAttribute(
+                    return Action::EditInExternalEditor(
                         entry.dn.clone(),
                         attr.to_string(),
                         val.to_string(),
@@ -109,10 +579,10 @@ impl DetailPanel {
                 }
                 Action::None
             }
-            KeyCode::Enter => {
-                // Jump to the selected value as a DN
-                if let Some((_attr, val)) = self.selected_attr_value() {
-                    return Action::TreeSelect(val.to_string());
+            KeyCode::Enter | KeyCode::Char('o') => {
+                // Open the entry the selected value refers to
+                if let Some((attr, val)) = self.selected_attr_value() {
+                    return Action::FollowReference(attr.to_string(), val.to_string());
                 }
                 Action::None
             }
@@ -127,8 +597,7 @@ impl DetailPanel {
                 // Add value to selected attribute (reuses existing attribute editor)
                 if let (Some(entry), Some((attr, _val))) = (&self.entry, self.selected_attr_value())
                 {
-                    let row = self.rows.get(self.table_state.selected().unwrap_or(0));
-                    if row.map(|r| r.kind) != Some(AttrKind::Operational) {
+                    if self.selected_row().map(|r| r.kind) != Some(AttrKind::Operational) {
                         return Action::AddAttribute(entry.dn.clone(), attr.to_string());
                     }
                 }
@@ -138,8 +607,7 @@ impl DetailPanel {
                 // Delete selected attribute value (with confirmation)
                 if let (Some(entry), Some((attr, val))) = (&self.entry, self.selected_attr_value())
                 {
-                    let row = self.rows.get(self.table_state.selected().unwrap_or(0));
-                    if row.map(|r| r.kind) != Some(AttrKind::Operational) {
+                    if self.selected_row().map(|r| r.kind) != Some(AttrKind::Operational) {
                         let msg = format!(
                             "Delete value '{}' from '{}'?",
                             flatten_for_message(val),
@@ -183,45 +651,125 @@ impl Component for DetailPanel {
             self.theme.border
         };
 
+        let title = if self.ldif_view {
+            " Details (LDIF) ".to_string()
+        } else if self.filter_input || !self.filter.is_empty() {
+            format!(
+                " Details  filter: {}{} ",
+                self.filter,
+                if self.filter_input { "_" } else { "" }
+            )
+        } else {
+            " Details ".to_string()
+        };
+        let title = match &self.reference {
+            Some(reference) => format!("{} vs {} ", title, reference.rdn()),
+            None => title,
+        };
         let mut block = Block::default()
-            .title(" Details ")
+            .title(title)
             .borders(Borders::ALL)
             .border_style(border_style);
         if focused {
             block = block.border_type(BorderType::Double);
         }
 
-        if let Some(ref entry) = self.entry {
+        if self.ldif_view && self.entry.is_some() {
+            let items: Vec<ListItem> = self
+                .ldif_lines
+                .iter()
+                .map(|line| ListItem::new(Span::styled(line.as_str(), self.theme.normal)))
+                .collect();
+            let list = List::new(items)
+                .block(block)
+                .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+            let mut state = self.ldif_state.clone();
+            frame.render_stateful_widget(list, area, &mut state);
+            self.ldif_offset.set(state.offset());
+        } else if let Some(ref entry) = self.entry {
             // Build header with DN
-            let dn_line = Line::from(vec![
-                Span::styled("DN: ", self.theme.header),
-                Span::styled(&entry.dn, self.theme.normal),
-            ]);
+            let mut dn_spans = vec![Span::styled("DN: ", self.theme.header)];
+            dn_spans.extend(self.theme.dn_spans(&entry.dn));
+            let dn_line = Line::from(dn_spans);
 
             // Build attribute rows
             let rows: Vec<Row> = self
-                .rows
+                .view
                 .iter()
-                .map(|r| {
+                .map(|line| {
+                    let r = match *line {
+                        ViewRow::Value(i) => &self.rows[i],
+                        ViewRow::Header(group) => {
+                            let marker = if self.collapsed.contains(&self.groups[group]) {
+                                "▸"
+                            } else {
+                                "▾"
+                            };
+                            let heading = format!(
+                                "{} {} ({})",
+                                marker,
+                                self.groups[group],
+                                self.group_size(group)
+                            );
+                            return Row::new(vec![
+                                Cell::from(Span::styled(
+                                    heading,
+                                    self.theme.header.add_modifier(Modifier::BOLD),
+                                )),
+                                Cell::from(""),
+                            ]);
+                        }
+                    };
                     let attr_style = match r.kind {
                         AttrKind::Operational => self.theme.attr_operational,
                         AttrKind::Normal => self.theme.header,
                     };
-                    let value_style = match r.kind {
-                        AttrKind::Operational => self.theme.attr_operational,
-                        AttrKind::Normal => self.theme.normal,
+                    let value_style = match (r.diff, r.kind) {
+                        (Some(ValueDiff::Different), _) => self.theme.warning,
+                        (Some(ValueDiff::Extra), _) => self.theme.success,
+                        (Some(ValueDiff::Missing), _) => {
+                            self.theme.error.add_modifier(Modifier::CROSSED_OUT)
+                        }
+                        (_, AttrKind::Operational) => self.theme.attr_operational,
+                        (_, AttrKind::Normal) => self.theme.normal,
+                    };
+                    // Markers too, for themes where the colors are close
+                    let marker = match r.diff {
+                        None => None,
+                        Some(ValueDiff::Same) => Some("  "),
+                        Some(ValueDiff::Different) => Some("~ "),
+                        Some(ValueDiff::Extra) => Some("+ "),
+                        Some(ValueDiff::Missing) => Some("- "),
+                    };
+                    let attr_cell = match (r.is_first, r.locked) {
+                        (false, _) => Line::from(""),
+                        (true, false) => Line::from(Span::styled(r.attr_name.as_str(), attr_style)),
+                        (true, true) => Line::from(vec![
+                            Span::styled(r.attr_name.as_str(), attr_style),
+                            Span::styled(" [locked]", self.theme.dimmed),
+                        ]),
                     };
-                    let attr_display = if r.is_first { r.attr_name.as_str() } else { "" };
 
-                    let value_lines: Vec<Line> = r
-                        .display_value
-                        .split('\n')
-                        .map(|line| Line::from(Span::styled(line.to_string(), value_style)))
-                        .collect();
+                    let mut value_lines: Vec<Line> = if r.display_value.contains('\n')
+                        || r.kind == AttrKind::Operational
+                        || r.diff.is_some_and(|d| d != ValueDiff::Same)
+                    {
+                        r.display_value
+                            .split('\n')
+                            .map(|line| Line::from(Span::styled(line, value_style)))
+                            .collect()
+                    } else {
+                        vec![Line::from(
+                            self.theme.value_spans(&r.display_value, value_style),
+                        )]
+                    };
+                    if let (Some(marker), Some(first)) = (marker, value_lines.first_mut()) {
+                        first.spans.insert(0, Span::styled(marker, value_style));
+                    }
                     let height = value_lines.len() as u16;
 
                     Row::new(vec![
-                        Cell::from(Span::styled(attr_display, attr_style)),
+                        Cell::from(attr_cell),
                         Cell::from(Text::from(value_lines)),
                     ])
                     .height(height)
@@ -241,7 +789,9 @@ impl Component for DetailPanel {
                 .block(block)
                 .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
 
-            frame.render_stateful_widget(table, area, &mut self.table_state.clone());
+            let mut state = self.table_state.clone();
+            frame.render_stateful_widget(table, area, &mut state);
+            self.table_offset.set(state.offset());
 
             // Render DN above the table (inside the block)
             let inner = area.inner(ratatui::layout::Margin {
@@ -272,6 +822,14 @@ impl Component for DetailPanel {
     }
 }
 
+/// An attribute of `entry` and its values, matching the name in any case.
+fn find_attr<'a>(entry: &'a LdapEntry, name: &str) -> Option<(&'a String, &'a Vec<String>)> {
+    entry
+        .attributes
+        .iter()
+        .find(|(attr, _)| attr.eq_ignore_ascii_case(name))
+}
+
 /// Sanitize a value for multi-line display: normalize line endings and
 /// replace non-printable control characters, but preserve newlines.
 fn sanitize_for_display(value: &str) -> String {
@@ -302,9 +860,28 @@ fn flatten_for_message(value: &str) -> String {
     out
 }
 
-fn build_rows(entry: &LdapEntry, schema: Option<&SchemaCache>) -> Vec<AttrRow> {
+/// The entry as LDIF lines, with base64 for values that need it.
+fn entry_ldif(entry: &LdapEntry) -> Vec<String> {
+    let mut buf = Vec::new();
+    let all = ["*".to_string()];
+    if loom_core::export::ldif::write_ldif(&mut buf, std::slice::from_ref(entry), &all).is_err() {
+        return Vec::new();
+    }
+    String::from_utf8_lossy(&buf)
+        .lines()
+        .map(str::to_string)
+        .collect()
+}
+
+fn build_rows(
+    entry: &LdapEntry,
+    schema: Option<&SchemaCache>,
+    policy: Option<&EditPolicy>,
+    formats: &DisplayFormats,
+) -> Vec<AttrRow> {
     let mut rows = Vec::new();
     for (name, values) in &entry.attributes {
+        let locked = policy.is_some_and(|p| p.owner(name).is_some());
         let kind = schema
             .and_then(|s| s.get_attribute_type(name))
             .map(|at| {
@@ -319,11 +896,255 @@ fn build_rows(entry: &LdapEntry, schema: Option<&SchemaCache>) -> Vec<AttrRow> {
             rows.push(AttrRow {
                 attr_name: name.clone(),
                 raw_value: val.clone(),
-                display_value: sanitize_for_display(val),
+                display_value: sanitize_for_display(&formats.display(name, val)),
                 is_first: i == 0,
                 kind,
+                locked,
+                group: 0,
+                diff: None,
             });
         }
     }
     rows
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn panel() -> DetailPanel {
+        let mut panel = DetailPanel::new(Theme::default()).with_groups(vec![
+            AttributeGroup {
+                name: "identity".to_string(),
+                attributes: vec!["cn".to_string(), "sn".to_string()],
+            },
+            AttributeGroup {
+                name: "contact".to_string(),
+                attributes: vec!["mail".to_string(), "proxyAddresses".to_string()],
+            },
+        ]);
+        let mut attributes = std::collections::BTreeMap::new();
+        for (attr, values) in [
+            ("cn", vec!["jdoe"]),
+            ("mail", vec!["jdoe@example.com"]),
+            (
+                "proxyAddresses",
+                vec!["SMTP:jdoe@example.com", "smtp:jd@example.com"],
+            ),
+            ("sn", vec!["Doe"]),
+            ("uSNChanged", vec!["4711"]),
+        ] {
+            attributes.insert(
+                attr.to_string(),
+                values.into_iter().map(str::to_string).collect(),
+            );
+        }
+        let entry = LdapEntry::new("cn=jdoe,dc=example,dc=com".to_string(), attributes);
+        panel.set_entry(entry, None, None);
+        panel
+    }
+
+    #[test]
+    fn test_grouped_rows() {
+        let mut panel = panel();
+        assert_eq!(
+            panel.view,
+            vec![
+                ViewRow::Header(0),
+                ViewRow::Value(0),
+                ViewRow::Value(1),
+                ViewRow::Header(1),
+                ViewRow::Value(2),
+                ViewRow::Value(3),
+                ViewRow::Value(4),
+                ViewRow::Header(2),
+                ViewRow::Value(5),
+            ]
+        );
+        assert_eq!(panel.groups[2], OTHER_GROUP);
+        // The first value is selected, not the heading
+        assert_eq!(panel.selected_attr_value(), Some(("cn", "jdoe")));
+
+        // Enter on a heading collapses its group
+        panel.table_state.select(Some(3));
+        assert!(panel.selected_attr_value().is_none());
+        panel.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(panel.view.len(), 6);
+        assert_eq!(panel.table_state.selected(), Some(3));
+        panel.handle_key_event(key(KeyCode::Char(' ')));
+        assert_eq!(panel.view.len(), 9);
+    }
+
+    #[test]
+    fn test_reference_diff() {
+        let mut panel = panel();
+        let mut attributes = std::collections::BTreeMap::new();
+        for (attr, values) in [
+            ("CN", vec!["jdoe"]),
+            ("mail", vec!["jane@example.com"]),
+            ("title", vec!["Engineer"]),
+            ("sn", vec!["Doe"]),
+        ] {
+            attributes.insert(
+                attr.to_string(),
+                values.into_iter().map(str::to_string).collect(),
+            );
+        }
+        let reference = LdapEntry::new("cn=jane,dc=example,dc=com".to_string(), attributes);
+        panel.set_reference(Some(reference));
+
+        let diffs: Vec<(&str, &str, Option<ValueDiff>)> = panel
+            .rows
+            .iter()
+            .map(|r| (r.attr_name.as_str(), r.raw_value.as_str(), r.diff))
+            .collect();
+        assert_eq!(
+            diffs,
+            vec![
+                ("cn", "jdoe", Some(ValueDiff::Same)),
+                ("sn", "Doe", Some(ValueDiff::Same)),
+                ("mail", "jdoe@example.com", Some(ValueDiff::Different)),
+                ("mail", "jane@example.com", Some(ValueDiff::Missing)),
+                (
+                    "proxyAddresses",
+                    "SMTP:jdoe@example.com",
+                    Some(ValueDiff::Extra)
+                ),
+                (
+                    "proxyAddresses",
+                    "smtp:jd@example.com",
+                    Some(ValueDiff::Extra)
+                ),
+                ("title", "Engineer", Some(ValueDiff::Missing)),
+                ("uSNChanged", "4711", Some(ValueDiff::Extra)),
+            ]
+        );
+        // Only the reference's values can't be edited
+        panel.table_state.select(Some(4));
+        assert!(panel.selected_attr_value().is_some());
+        panel.table_state.select(Some(5));
+        assert!(panel.selected_attr_value().is_none());
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('M'))),
+            Action::ToggleReference(dn) if dn == "cn=jdoe,dc=example,dc=com"
+        ));
+
+        panel.set_reference(None);
+        assert_eq!(panel.rows.len(), 6);
+        assert!(panel.rows.iter().all(|r| r.diff.is_none()));
+    }
+
+    #[test]
+    fn test_filter_attributes() {
+        let mut panel = panel();
+        panel.table_state.select(Some(3));
+        panel.handle_key_event(key(KeyCode::Enter));
+
+        panel.handle_key_event(key(KeyCode::Char('f')));
+        assert!(panel.is_filtering());
+        for c in "prox".chars() {
+            panel.handle_key_event(key(KeyCode::Char(c)));
+        }
+        // Matches inside the collapsed group are shown
+        assert_eq!(
+            panel.view,
+            vec![ViewRow::Header(1), ViewRow::Value(3), ViewRow::Value(4)]
+        );
+        assert_eq!(
+            panel.selected_attr_value(),
+            Some(("proxyAddresses", "SMTP:jdoe@example.com"))
+        );
+
+        panel.handle_key_event(key(KeyCode::Enter));
+        assert!(!panel.is_filtering());
+        assert_eq!(panel.view.len(), 3);
+        panel.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(panel.view.len(), 6);
+    }
+
+    #[test]
+    fn test_ldif_view() {
+        let mut panel = panel();
+        panel.handle_key_event(key(KeyCode::Char('v')));
+        assert!(panel.selected_attr_value().is_none());
+        assert_eq!(panel.ldif_lines[0], "dn: cn=jdoe,dc=example,dc=com");
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('y'))),
+            Action::CopyToClipboard(line) if line == "dn: cn=jdoe,dc=example,dc=com"
+        ));
+        panel.handle_key_event(key(KeyCode::Char('j')));
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('y'))),
+            Action::CopyToClipboard(line) if line == "cn: jdoe"
+        ));
+        // Editing keys do nothing here
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('d'))),
+            Action::None
+        ));
+        match panel.handle_key_event(key(KeyCode::Char('Y'))) {
+            Action::CopyToClipboard(text) => {
+                assert!(text.starts_with("dn: cn=jdoe"));
+                assert!(text.ends_with("uSNChanged: 4711\n"));
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        panel.handle_key_event(key(KeyCode::Char('v')));
+        assert_eq!(panel.selected_attr_value(), Some(("cn", "jdoe")));
+    }
+
+    #[test]
+    fn test_wheel_scrolls_the_view() {
+        let mut panel = panel();
+        // Three rows fit between the border, the DN line and the border
+        let area = Rect::new(0, 0, 60, 6);
+        panel.table_state.select(Some(1));
+        panel.scroll(area, 1);
+        assert_eq!(panel.table_state.offset(), 1);
+        assert_eq!(panel.table_state.selected(), Some(1));
+        panel.scroll(area, 1);
+        assert_eq!(panel.table_state.offset(), 2);
+        assert_eq!(panel.table_state.selected(), Some(2));
+        panel.scroll(area, -2);
+        assert_eq!(panel.table_state.offset(), 0);
+        assert_eq!(panel.table_state.selected(), Some(2));
+        // Clicks land on the rows now on screen
+        panel.scroll(area, 3);
+        assert!(panel.click_row(area, 2));
+        assert_eq!(panel.table_state.selected(), Some(3));
+    }
+
+    #[test]
+    fn test_click_rows() {
+        let mut panel = panel();
+        let area = Rect::new(0, 0, 60, 20);
+        // Border and DN line come first
+        assert!(!panel.click_row(area, 1));
+        assert!(panel.click_row(area, 3));
+        assert!(matches!(
+            panel.activate_selected(),
+            Action::EditAttribute(_, attr, value) if attr == "cn" && value == "jdoe"
+        ));
+
+        let rows = panel.view.len();
+        assert!(panel.click_row(area, 2));
+        assert!(matches!(panel.activate_selected(), Action::None));
+        assert!(panel.view.len() < rows);
+        assert!(!panel.click_row(area, 19));
+    }
+
+    #[test]
+    fn test_ungrouped_rows() {
+        let mut panel = DetailPanel::new(Theme::default());
+        let attributes = [("cn".to_string(), vec!["a".to_string()])].into();
+        let entry = LdapEntry::new("cn=a,dc=example".to_string(), attributes);
+        panel.set_entry(entry, None, None);
+        assert_eq!(panel.view, vec![ViewRow::Value(0)]);
+    }
+}
@@ -0,0 +1,230 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::duplicates::DuplicateReport;
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// A panel listing the values of an attribute held by several entries,
+/// with the conflicting DNs grouped under each value.
+pub struct DuplicatesPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: Option<DuplicateReport>,
+    /// One row per conflicting DN: its group and the DN.
+    rows: Vec<(usize, String)>,
+    selected: usize,
+}
+
+impl DuplicatesPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Duplicate Values", theme.clone()).with_size(85, 70),
+            theme,
+            report: None,
+            rows: Vec::new(),
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, report: DuplicateReport) {
+        self.rows = report
+            .groups
+            .iter()
+            .enumerate()
+            .flat_map(|(i, g)| g.dns.iter().map(move |dn| (i, dn.clone())))
+            .collect();
+        self.report = Some(report);
+        self.selected = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn report(&self) -> Option<&DuplicateReport> {
+        self.report.as_ref()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < self.rows.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Enter => match self.rows.get(self.selected) {
+                Some((_, dn)) => {
+                    let dn = dn.clone();
+                    self.hide();
+                    Action::TreeSelect(dn)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('s') if !self.rows.is_empty() => Action::SaveDuplicateReport,
+            KeyCode::Char('r') => match &self.report {
+                Some(report) => Action::FindDuplicates {
+                    attribute: report.attribute.clone(),
+                    base_dn: Some(report.base_dn.clone()),
+                },
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(
+                " Duplicate {} under {} ",
+                report.attribute, report.base_dn
+            ))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: table | summary (1) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let header = Row::new(["Value", "Entries", "DN"]).style(self.theme.header);
+        let rows: Vec<Row> = self
+            .rows
+            .iter()
+            .enumerate()
+            .map(|(i, (group, dn))| {
+                // The value and count only on the first row of each group
+                let first = i == 0 || self.rows[i - 1].0 != *group;
+                let (value, count) = match report.groups.get(*group) {
+                    Some(g) if first => (g.value.clone(), g.dns.len().to_string()),
+                    _ => (String::new(), String::new()),
+                };
+                Row::new(vec![
+                    Cell::from(value).style(self.theme.warning),
+                    Cell::from(count),
+                    Cell::from(dn.clone()),
+                ])
+                .style(self.theme.normal)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Percentage(35),
+                Constraint::Length(7),
+                Constraint::Percentage(65),
+            ],
+        )
+        .header(header)
+        .highlight_style(self.theme.selected);
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, layout[0], &mut state);
+
+        let summary = format!(
+            "{} duplicated value(s) across {} entries; {} entries with {} scanned",
+            report.groups.len(),
+            report.conflicting_entries(),
+            report.entries_scanned,
+            report.attribute
+        );
+        let style = if report.groups.is_empty() {
+            self.theme.success
+        } else {
+            self.theme.warning
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(summary, style))),
+            layout[1],
+        );
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  Enter:go to entry  s:save report  r:rescan  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::duplicates::DuplicateGroup;
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_rows_follow_groups() {
+        let mut panel = DuplicatesPanel::new(Theme::default());
+        panel.show(DuplicateReport {
+            base_dn: "dc=x".to_string(),
+            attribute: "mail".to_string(),
+            entries_scanned: 10,
+            groups: vec![
+                DuplicateGroup {
+                    value: "a@x".to_string(),
+                    dns: vec!["cn=1".into(), "cn=2".into()],
+                },
+                DuplicateGroup {
+                    value: "b@x".to_string(),
+                    dns: vec!["cn=3".into(), "cn=4".into()],
+                },
+            ],
+        });
+        assert_eq!(panel.rows.len(), 4);
+
+        panel.handle_key_event(key(KeyCode::Down));
+        panel.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('r'))),
+            Action::FindDuplicates { ref attribute, base_dn: Some(ref base) }
+                if attribute == "mail" && base == "dc=x"
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Enter)),
+            Action::TreeSelect(ref dn) if dn == "cn=3"
+        ));
+    }
+}
@@ -15,6 +15,7 @@ const FORMATS: &[(&str, &str)] = &[
     ("JSON", ".json"),
     ("CSV", ".csv"),
     ("Excel", ".xlsx"),
+    ("Markdown", ".md"),
 ];
 
 /// Which field is currently active.
@@ -27,7 +28,8 @@ enum ExportField {
     Filename,
 }
 
-/// Dialog for exporting entries to a file.
+/// Dialog for exporting entries to a file, either those matching a filter
+/// or a chosen set of entries.
 pub struct ExportDialog {
     pub visible: bool,
     popup: Popup,
@@ -38,6 +40,9 @@ pub struct ExportDialog {
     filter: String,
     attributes: String,
     filename: String,
+    /// Entries picked in the detail panel or search results; when set, the
+    /// base DN and filter fields are not used.
+    dns: Vec<String>,
 }
 
 impl ExportDialog {
@@ -52,10 +57,17 @@ impl ExportDialog {
             filter: String::new(),
             attributes: String::new(),
             filename: String::new(),
+            dns: Vec::new(),
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self, base_dn: &str) {
+        self.dns.clear();
         self.base_dn = base_dn.to_string();
         self.filter = "(objectClass=*)".to_string();
         self.attributes = "*".to_string();
@@ -66,6 +78,13 @@ impl ExportDialog {
         self.popup.show();
     }
 
+    /// Show the dialog for exporting exactly `dns`.
+    pub fn show_entries(&mut self, dns: Vec<String>) {
+        self.show("");
+        self.dns = dns;
+        self.active_field = ExportField::Attributes;
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
         self.popup.hide();
@@ -83,6 +102,7 @@ impl ExportDialog {
                     ExportField::Filter => ExportField::Attributes,
                     ExportField::Attributes => ExportField::Format,
                     ExportField::Format => ExportField::Filename,
+                    ExportField::Filename if !self.dns.is_empty() => ExportField::Attributes,
                     ExportField::Filename => ExportField::BaseDn,
                 };
                 Action::None
@@ -91,6 +111,7 @@ impl ExportDialog {
                 self.active_field = match self.active_field {
                     ExportField::BaseDn => ExportField::Filename,
                     ExportField::Filter => ExportField::BaseDn,
+                    ExportField::Attributes if !self.dns.is_empty() => ExportField::Filename,
                     ExportField::Attributes => ExportField::Filter,
                     ExportField::Format => ExportField::Attributes,
                     ExportField::Filename => ExportField::Format,
@@ -134,11 +155,13 @@ impl ExportDialog {
     }
 
     fn submit(&mut self) -> Action {
-        if self.base_dn.trim().is_empty() {
-            return Action::ErrorMessage("Base DN is required".to_string());
-        }
-        if self.filter.trim().is_empty() {
-            return Action::ErrorMessage("Search filter is required".to_string());
+        if self.dns.is_empty() {
+            if self.base_dn.trim().is_empty() {
+                return Action::ErrorMessage("Base DN is required".to_string());
+            }
+            if self.filter.trim().is_empty() {
+                return Action::ErrorMessage("Search filter is required".to_string());
+            }
         }
         if self.filename.trim().is_empty() {
             return Action::ErrorMessage("Filename is required".to_string());
@@ -148,6 +171,10 @@ impl ExportDialog {
         let mut path = self.filename.trim().to_string();
         let filter = self.filter.trim().to_string();
 
+        // A directory means one file per entry, named by RDN
+        if path.ends_with('/') || path.ends_with('\\') {
+            path.push_str("{rdn}");
+        }
         // Append the format extension if the filename has none
         if std::path::Path::new(&path).extension().is_none() {
             path.push_str(FORMATS[self.format_idx].1);
@@ -166,6 +193,13 @@ impl ExportDialog {
         };
 
         self.hide();
+        if !self.dns.is_empty() {
+            return Action::ExportEntries {
+                dns: std::mem::take(&mut self.dns),
+                path,
+                attributes: attrs,
+            };
+        }
         Action::ExportExecute {
             base_dn,
             path,
@@ -221,23 +255,35 @@ impl ExportDialog {
         ])
         .split(inner);
 
-        // Base DN field
-        self.render_text_field(
-            frame,
-            layout[0],
-            "Base DN",
-            &self.base_dn,
-            ExportField::BaseDn,
-        );
+        if self.dns.is_empty() {
+            // Base DN field
+            self.render_text_field(
+                frame,
+                layout[0],
+                "Base DN",
+                &self.base_dn,
+                ExportField::BaseDn,
+            );
 
-        // Filter field
-        self.render_text_field(
-            frame,
-            layout[1],
-            "Search Filter",
-            &self.filter,
-            ExportField::Filter,
-        );
+            // Filter field
+            self.render_text_field(
+                frame,
+                layout[1],
+                "Search Filter",
+                &self.filter,
+                ExportField::Filter,
+            );
+        } else {
+            let summary = match self.dns.as_slice() {
+                [dn] => dn.clone(),
+                dns => format!("{} selected entries", dns.len()),
+            };
+            let lines = vec![
+                Line::from(Span::styled("Entries:", self.theme.dimmed)),
+                Line::from(Span::styled(summary, self.theme.normal)),
+            ];
+            frame.render_widget(Paragraph::new(lines), layout[0]);
+        }
 
         // Attributes field
         self.render_text_field(
@@ -278,7 +324,7 @@ impl ExportDialog {
         self.render_text_field(
             frame,
             layout[4],
-            "Filename",
+            "Filename (dir/ or dir/{uid} for a file per entry)",
             &self.filename,
             ExportField::Filename,
         );
@@ -327,3 +373,48 @@ impl ExportDialog {
         frame.render_widget(Paragraph::new(lines), area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_export_selected_entries() {
+        let mut dialog = ExportDialog::new(Theme::default());
+        dialog.show_entries(vec!["cn=a,dc=x".to_string(), "cn=b,dc=x".to_string()]);
+        assert_eq!(dialog.active_field, ExportField::Attributes);
+
+        // Tab skips the base DN and filter, which don't apply
+        for _ in 0..3 {
+            dialog.handle_key_event(key(KeyCode::Tab));
+        }
+        assert_eq!(dialog.active_field, ExportField::Attributes);
+
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Down));
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::ExportEntries {
+                dns,
+                path,
+                attributes,
+            } => {
+                assert_eq!(dns, vec!["cn=a,dc=x", "cn=b,dc=x"]);
+                assert_eq!(path, "export.json");
+                assert_eq!(attributes, vec!["*"]);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+
+        // A filter export afterwards is unaffected
+        dialog.show("dc=x");
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::ExportExecute { .. }
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Popup listing the DNs pinned on the active connection's profile.
+/// Enter jumps to the entry in the tree; `d` unpins it.
+pub struct FavoritesPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    connection: String,
+    dns: Vec<String>,
+    /// Lowercased DNs found to no longer exist.
+    stale: HashSet<String>,
+    list_state: ListState,
+}
+
+impl FavoritesPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Favorites", theme.clone()).with_size(70, 60),
+            theme,
+            connection: String::new(),
+            dns: Vec::new(),
+            stale: HashSet::new(),
+            list_state: ListState::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, connection: &str, dns: Vec<String>, stale: HashSet<String>) {
+        self.connection = connection.to_string();
+        self.dns = dns;
+        self.stale = stale;
+        self.list_state.select(Some(0));
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Update the stale marks while the popup is open.
+    pub fn set_stale(&mut self, stale: HashSet<String>) {
+        self.stale = stale;
+    }
+
+    fn selected(&self) -> Option<String> {
+        self.list_state
+            .selected()
+            .and_then(|i| self.dns.get(i))
+            .cloned()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                if i + 1 < self.dns.len() {
+                    self.list_state.select(Some(i + 1));
+                }
+                Action::None
+            }
+            KeyCode::Enter => match self.selected() {
+                Some(dn) => {
+                    self.hide();
+                    Action::RevealEntry(dn)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('d') | KeyCode::Delete => {
+                let Some(dn) = self.selected() else {
+                    return Action::None;
+                };
+                self.dns.retain(|d| d != &dn);
+                let last = self.dns.len().saturating_sub(1);
+                if self.list_state.selected().is_some_and(|i| i > last) {
+                    self.list_state.select(Some(last));
+                }
+                Action::ToggleFavorite(dn)
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Favorites: {} ", self.connection))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // List
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        if self.dns.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No favorites yet -- press * on a tree entry to pin it",
+                    self.theme.dimmed,
+                ))),
+                layout[0],
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .dns
+                .iter()
+                .map(|dn| {
+                    let stale = self.stale.contains(&dn.to_lowercase());
+                    let name_style = if stale {
+                        self.theme.dimmed
+                    } else {
+                        self.theme.normal
+                    };
+                    let mut spans = vec![
+                        Span::styled(
+                            format!("{:<24} ", loom_core::dn::rdn_display_name(dn)),
+                            name_style,
+                        ),
+                        Span::styled(dn.as_str(), self.theme.dimmed),
+                    ];
+                    if stale {
+                        spans.push(Span::styled("  (missing)", self.theme.error));
+                    }
+                    ListItem::new(Line::from(spans))
+                })
+                .collect();
+
+            let list = List::new(items)
+                .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+                .highlight_symbol("> ");
+            frame.render_stateful_widget(list, layout[0], &mut self.list_state);
+        }
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:navigate  Enter:go  d:unpin  Esc:close",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_unpin_and_go() {
+        let mut popup = FavoritesPopup::new(Theme::load("dark"));
+        let dns = vec![
+            "cn=svc,dc=example".to_string(),
+            "cn=admins,dc=example".to_string(),
+        ];
+        popup.show("Test", dns, HashSet::new());
+
+        popup.handle_key_event(key(KeyCode::Down));
+        let action = popup.handle_key_event(key(KeyCode::Char('d')));
+        assert!(matches!(action, Action::ToggleFavorite(dn) if dn == "cn=admins,dc=example"));
+        assert_eq!(popup.list_state.selected(), Some(0));
+
+        let action = popup.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::RevealEntry(dn) if dn == "cn=svc,dc=example"));
+        assert!(!popup.visible);
+    }
+}
@@ -0,0 +1,297 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::entry::LdapEntry;
+
+use crate::action::Action;
+use crate::components::attribute_editor::build_dn_search_filter;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+
+/// "Go to DN" popup: type or paste a DN, pick from live search suggestions,
+/// and the tree expands down to the entry.
+pub struct GoToDnDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    input: String,
+    fuzzy: FuzzyFilter,
+    suggestions: Vec<String>,
+    /// Highlighted suggestion; `None` while the input line is focused.
+    list_state: ListState,
+
+    // Live search debounce state (same scheme as the attribute editor)
+    search_generation: u64,
+    search_dirty: bool,
+    last_search_text: String,
+    searching: bool,
+}
+
+impl GoToDnDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Go to DN", theme.clone()).with_size(70, 50),
+            theme,
+            input: String::new(),
+            fuzzy: FuzzyFilter::new(),
+            suggestions: Vec::new(),
+            list_state: ListState::default(),
+            search_generation: 0,
+            search_dirty: false,
+            last_search_text: String::new(),
+            searching: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self) {
+        self.input.clear();
+        self.suggestions.clear();
+        self.list_state.select(None);
+        self.search_dirty = false;
+        self.last_search_text.clear();
+        self.searching = false;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Replace the input with pasted text (first line only).
+    pub fn paste(&mut self, text: &str) {
+        self.input = text.lines().next().unwrap_or_default().trim().to_string();
+        self.input_changed();
+    }
+
+    fn input_changed(&mut self) {
+        self.search_dirty = true;
+        self.list_state.select(None);
+    }
+
+    /// Debounced live search, called on every tick while visible. Searches
+    /// on the first component typed so far, e.g. `cn=ali` of `cn=ali,ou=...`.
+    pub fn tick(&mut self, base_dn: &str) -> Action {
+        if !self.visible || !self.search_dirty {
+            return Action::None;
+        }
+        let first = self.input.split(',').next().unwrap_or_default().trim();
+        if first.len() < 2 || self.input == self.last_search_text {
+            return Action::None;
+        }
+        self.search_dirty = false;
+        self.last_search_text = self.input.clone();
+        self.search_generation += 1;
+        self.searching = true;
+        Action::DnSearchRequest {
+            generation: self.search_generation,
+            query: build_dn_search_filter(first),
+            base_dn: base_dn.to_string(),
+        }
+    }
+
+    /// Receive search results, ranked against the full input. Stale
+    /// generations are ignored.
+    pub fn receive_results(&mut self, generation: u64, entries: Vec<LdapEntry>) {
+        if generation != self.search_generation {
+            return;
+        }
+        self.searching = false;
+        let dns: Vec<String> = entries.into_iter().map(|e| e.dn).collect();
+        self.suggestions = self
+            .fuzzy
+            .filter(&self.input, &dns)
+            .into_iter()
+            .map(|m| dns[m.index].clone())
+            .collect();
+        self.list_state.select(None);
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => {
+                let dn = match self.list_state.selected() {
+                    Some(i) => self.suggestions.get(i).cloned().unwrap_or_default(),
+                    None => self.input.trim().to_string(),
+                };
+                if dn.is_empty() {
+                    return Action::None;
+                }
+                self.hide();
+                Action::RevealEntry(dn)
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                if !self.suggestions.is_empty() {
+                    let next = self
+                        .list_state
+                        .selected()
+                        .map_or(0, |i| (i + 1).min(self.suggestions.len() - 1));
+                    self.list_state.select(Some(next));
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                // Moving up from the first suggestion returns to the input
+                let prev = self.list_state.selected().and_then(|i| i.checked_sub(1));
+                self.list_state.select(prev);
+                Action::None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.input_changed();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.input_changed();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Go to DN ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Input
+            Constraint::Length(1), // Spacer
+            Constraint::Min(1),    // Suggestions
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let input_style = if self.list_state.selected().is_none() {
+            self.theme.normal
+        } else {
+            self.theme.dimmed
+        };
+        let input_line = Line::from(vec![
+            Span::styled("DN: ", self.theme.header),
+            Span::styled(self.input.as_str(), input_style),
+            if self.list_state.selected().is_none() {
+                Span::styled("_", self.theme.command_prompt)
+            } else {
+                Span::raw("")
+            },
+        ]);
+        frame.render_widget(Paragraph::new(input_line), layout[0]);
+
+        if self.suggestions.is_empty() {
+            let msg = if self.searching {
+                "Searching..."
+            } else if self.input.trim().is_empty() {
+                "Type or paste a DN, or part of a name to search"
+            } else {
+                "Enter goes to the DN as typed"
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(msg, self.theme.dimmed))),
+                layout[2],
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .suggestions
+                .iter()
+                .map(|dn| ListItem::new(Line::from(Span::styled(dn.as_str(), self.theme.normal))))
+                .collect();
+            let list = List::new(items)
+                .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+                .highlight_symbol("> ");
+            frame.render_stateful_widget(list, layout[2], &mut self.list_state);
+        }
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Tab/Down:suggestions  Enter:go  Esc:close",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn typed(text: &str) -> GoToDnDialog {
+        let mut dialog = GoToDnDialog::new(Theme::load("dark"));
+        dialog.show();
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        dialog
+    }
+
+    #[test]
+    fn test_tick_searches_first_component() {
+        let mut dialog = typed("cn=ali,ou=people");
+        match dialog.tick("dc=example,dc=com") {
+            Action::DnSearchRequest { query, base_dn, .. } => {
+                assert_eq!(query, "(cn=ali*)");
+                assert_eq!(base_dn, "dc=example,dc=com");
+            }
+            other => panic!("expected DnSearchRequest, got {:?}", other),
+        }
+        // Nothing new typed: no repeat search
+        assert!(matches!(dialog.tick("dc=example,dc=com"), Action::None));
+    }
+
+    #[test]
+    fn test_enter_picks_suggestion_or_input() {
+        let mut dialog = typed("alice");
+        let Action::DnSearchRequest { generation, .. } = dialog.tick("dc=example,dc=com") else {
+            panic!("expected a search");
+        };
+        dialog.receive_results(
+            generation,
+            vec![
+                LdapEntry::new("cn=Bob,dc=example,dc=com".into(), Default::default()),
+                LdapEntry::new("cn=Alice,dc=example,dc=com".into(), Default::default()),
+            ],
+        );
+        assert_eq!(dialog.suggestions, vec!["cn=Alice,dc=example,dc=com"]);
+
+        dialog.handle_key_event(key(KeyCode::Down));
+        let action = dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::RevealEntry(dn) if dn == "cn=Alice,dc=example,dc=com"));
+
+        let mut dialog = typed("ou=People,dc=example,dc=com");
+        let action = dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::RevealEntry(dn) if dn == "ou=People,dc=example,dc=com"));
+    }
+}
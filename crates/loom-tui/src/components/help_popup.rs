@@ -36,6 +36,11 @@ impl HelpPopup {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self, keymap: &Keymap) {
         self.sections = build_sections(keymap);
         self.scroll_offset = 0;
@@ -185,6 +190,30 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                     keymap.hint("toggle_log_panel").to_string(),
                     "Log panel".to_string(),
                 ),
+                (
+                    keymap.hint("toggle_jobs_panel").to_string(),
+                    "Jobs panel".to_string(),
+                ),
+                (
+                    keymap.hint("toggle_trace_panel").to_string(),
+                    "LDAP operation trace".to_string(),
+                ),
+                (
+                    keymap.hint("toggle_metrics_panel").to_string(),
+                    "Server metrics".to_string(),
+                ),
+                (
+                    keymap.hint("show_replication").to_string(),
+                    "Replication status".to_string(),
+                ),
+                (
+                    keymap.hint("show_account_reports").to_string(),
+                    "Reports".to_string(),
+                ),
+                (
+                    keymap.hint("force_refresh").to_string(),
+                    "Reload entry from server".to_string(),
+                ),
                 (
                     keymap.hint("show_bulk_update").to_string(),
                     "Bulk update".to_string(),
@@ -217,6 +246,64 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                     keymap.hint("close_tab").to_string(),
                     "Close tab".to_string(),
                 ),
+                (
+                    keymap.hint("toggle_dry_run").to_string(),
+                    "Toggle dry run".to_string(),
+                ),
+                (
+                    keymap.hint("toggle_staging").to_string(),
+                    "Toggle staging".to_string(),
+                ),
+                (
+                    keymap.hint("toggle_changes_panel").to_string(),
+                    "Staged changes".to_string(),
+                ),
+                (
+                    keymap.hint("show_recent").to_string(),
+                    "Recent entries".to_string(),
+                ),
+                (keymap.hint("goto_dn").to_string(), "Go to DN".to_string()),
+                (
+                    keymap.hint("quick_open").to_string(),
+                    "Quick open a loaded entry".to_string(),
+                ),
+                (
+                    keymap.hint("show_favorites").to_string(),
+                    "Favorites".to_string(),
+                ),
+                (
+                    keymap.hint("show_workspaces").to_string(),
+                    "Workspaces".to_string(),
+                ),
+                (
+                    keymap.hint("command_palette").to_string(),
+                    "Command palette".to_string(),
+                ),
+                (":".to_string(), "Command line".to_string()),
+                (
+                    keymap.hint("nav_back").to_string(),
+                    "Back in entry history".to_string(),
+                ),
+                (
+                    keymap.hint("nav_forward").to_string(),
+                    "Forward in entry history".to_string(),
+                ),
+                (
+                    keymap.hint("show_history").to_string(),
+                    "Entry history".to_string(),
+                ),
+                (
+                    keymap.hint("toggle_split").to_string(),
+                    "Split view".to_string(),
+                ),
+                (
+                    keymap.hint("swap_split_focus").to_string(),
+                    "Swap split side".to_string(),
+                ),
+                (
+                    keymap.hint("copy_to_other_side").to_string(),
+                    "Copy DN/value to other side".to_string(),
+                ),
                 (keymap.hint("quit").to_string(), "Quit".to_string()),
                 (
                     keymap.hint("force_quit").to_string(),
@@ -238,6 +325,15 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                 ("h/\u{2190}".to_string(), "Collapse node".to_string()),
                 ("a".to_string(), "Create child entry".to_string()),
                 ("d/Delete".to_string(), "Delete entry".to_string()),
+                ("x/X".to_string(), "Cut entry / subtree".to_string()),
+                ("p".to_string(), "Paste cut entry under node".to_string()),
+                ("f".to_string(), "Quick find in loaded nodes".to_string()),
+                ("n/N".to_string(), "Next / previous match".to_string()),
+                ("*".to_string(), "Pin/unpin favorite".to_string()),
+                (
+                    "r".to_string(),
+                    "Refresh subtree or saved search".to_string(),
+                ),
                 ("Space".to_string(), "Context menu".to_string()),
             ],
         },
@@ -248,7 +344,9 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                     "j/k \u{2191}/\u{2193}".to_string(),
                     "Navigate attributes".to_string(),
                 ),
-                ("e/Enter".to_string(), "Edit attribute value".to_string()),
+                ("e".to_string(), "Edit attribute value".to_string()),
+                ("E".to_string(), "Edit value in $EDITOR".to_string()),
+                ("o/Enter".to_string(), "Open referenced entry".to_string()),
                 ("a".to_string(), "Add new attribute".to_string()),
                 ("+".to_string(), "Add value to attribute".to_string()),
                 ("d/Delete".to_string(), "Delete attribute value".to_string()),
@@ -256,6 +354,18 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                 ("x".to_string(), "Delete entry".to_string()),
                 ("r".to_string(), "Refresh entry".to_string()),
                 ("Space".to_string(), "Context menu".to_string()),
+                (
+                    "Enter/Space".to_string(),
+                    "Collapse/expand group heading".to_string(),
+                ),
+                ("f".to_string(), "Filter attributes by name".to_string()),
+                ("v".to_string(), "Toggle raw LDIF view".to_string()),
+                ("w".to_string(), "Export this entry".to_string()),
+                ("M".to_string(), "Compare entries with this one".to_string()),
+                (
+                    "y/Y".to_string(),
+                    "Copy LDIF line/entry (LDIF view)".to_string(),
+                ),
             ],
         },
         HelpSection {
@@ -278,6 +388,10 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
             entries: vec![
                 ("/ or :".to_string(), "Activate search input".to_string()),
                 ("Enter".to_string(), "Execute search filter".to_string()),
+                (
+                    "Alt+Enter".to_string(),
+                    "Apply the filter quick fix".to_string(),
+                ),
                 ("Esc".to_string(), "Cancel / deactivate input".to_string()),
             ],
         },
@@ -294,6 +408,8 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                 ("c".to_string(), "Connect to profile".to_string()),
                 ("n".to_string(), "New profile".to_string()),
                 ("d/Delete".to_string(), "Delete profile".to_string()),
+                ("J/K".to_string(), "Move profile down/up".to_string()),
+                ("m".to_string(), "Move profile to folder".to_string()),
             ],
         },
         HelpSection {
@@ -316,6 +432,14 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                     "Navigate results".to_string(),
                 ),
                 ("Enter".to_string(), "Go to selected entry".to_string()),
+                ("Space".to_string(), "Mark for export".to_string()),
+                ("e".to_string(), "Export marked results".to_string()),
+                ("r".to_string(), "Report on all results".to_string()),
+                ("c".to_string(), "Copy as an ldapsearch command".to_string()),
+                (
+                    "m".to_string(),
+                    "Menu: copy as LDIF, JSON or CSV".to_string(),
+                ),
                 ("Esc/q".to_string(), "Close".to_string()),
             ],
         },
@@ -355,6 +479,10 @@ fn build_sections(keymap: &Keymap) -> Vec<HelpSection> {
                 ("j/k \u{2191}/\u{2193}".to_string(), "Scroll".to_string()),
                 ("Tab".to_string(), "Switch Attributes/Classes".to_string()),
                 ("/".to_string(), "Filter".to_string()),
+                ("Enter".to_string(), "Details / follow link".to_string()),
+                ("Backspace".to_string(), "Back".to_string()),
+                ("s".to_string(), "Find entries using it".to_string()),
+                ("e".to_string(), "Export schema to file".to_string()),
                 ("Esc/q".to_string(), "Close".to_string()),
             ],
         },
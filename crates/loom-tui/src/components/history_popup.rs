@@ -0,0 +1,169 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Popup listing the active tab's navigation history, newest first, with
+/// the entry on screen marked. Enter jumps to the highlighted entry.
+pub struct HistoryPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    connection: String,
+    /// Visited DNs, oldest first (as stored in the history).
+    dns: Vec<String>,
+    current: usize,
+    list_state: ListState,
+}
+
+impl HistoryPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("History", theme.clone()).with_size(70, 60),
+            theme,
+            connection: String::new(),
+            dns: Vec::new(),
+            current: 0,
+            list_state: ListState::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, connection: &str, dns: Vec<String>, current: usize) {
+        self.connection = connection.to_string();
+        self.current = current;
+        self.list_state
+            .select(Some(dns.len().saturating_sub(1) - current));
+        self.dns = dns;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// History index of the highlighted row (rows are newest first).
+    fn selected_index(&self) -> Option<usize> {
+        let row = self.list_state.selected()?;
+        self.dns.len().checked_sub(row + 1)
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                if i + 1 < self.dns.len() {
+                    self.list_state.select(Some(i + 1));
+                }
+                Action::None
+            }
+            KeyCode::Enter => match self.selected_index() {
+                Some(index) => {
+                    self.hide();
+                    Action::HistoryJump(index)
+                }
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" History: {} ", self.connection))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // List
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let items: Vec<ListItem> = self
+            .dns
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(i, dn)| {
+                let marker = if i == self.current { "* " } else { "  " };
+                ListItem::new(Line::from(vec![
+                    Span::styled(marker, self.theme.header),
+                    Span::styled(
+                        format!("{:<24} ", loom_core::dn::rdn_display_name(dn)),
+                        self.theme.normal,
+                    ),
+                    Span::styled(dn.as_str(), self.theme.dimmed),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, layout[0], &mut self.list_state);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:navigate  Enter:go  Esc:close  (* current)",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_rows_are_newest_first() {
+        let mut popup = HistoryPopup::new(Theme::load("dark"));
+        let dns = vec!["cn=a".to_string(), "cn=b".to_string(), "cn=c".to_string()];
+        // Stepped back to cn=b: it is highlighted (row 1)
+        popup.show("Test", dns, 1);
+        assert_eq!(popup.list_state.selected(), Some(1));
+
+        popup.handle_key_event(key(KeyCode::Down));
+        let action = popup.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::HistoryJump(0)));
+        assert!(!popup.visible);
+    }
+}
@@ -0,0 +1,862 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::entry::LdapEntry;
+use loom_core::import::csv::CsvTable;
+use loom_core::import::mapping::{ColumnMapping, MappingResult};
+use loom_core::import::plan::{ConflictResolution, ImportItem};
+use loom_core::schema::SchemaCache;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::components::profile_export_dialog::expand_tilde;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+
+/// Attribute suggestions shown while mapping a column.
+const MAX_SUGGESTIONS: usize = 6;
+
+/// Which phase the import dialog is in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Phase {
+    /// User enters a file path.
+    FilePath,
+    /// User maps a CSV file's columns to attributes and a DN template.
+    Mapping,
+    /// The file is being parsed and its DNs looked up on the server.
+    Checking,
+    /// User reviews the entries and resolves conflicts.
+    Preview,
+    /// The import is being applied.
+    Applying,
+    /// The outcome of the import.
+    Report,
+}
+
+/// Dialog for importing entries from LDIF, JSON, CSV or XLSX: previews the
+/// entries as a tree, flags DNs that already exist and lets each conflict be
+/// skipped, overwritten or merged before anything is written. CSV files go
+/// through a column-mapping step first.
+pub struct ImportDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    phase: Phase,
+    file_path: String,
+    /// The connection's schema, for attribute suggestions and validation.
+    schema: Option<SchemaCache>,
+    /// The CSV being mapped, if the file is a CSV.
+    csv: Option<CsvTable>,
+    mapping: ColumnMapping,
+    /// Mapping row being edited: 0 is the DN template, then one per column.
+    map_cursor: usize,
+    attr_names: Vec<String>,
+    fuzzy: FuzzyFilter,
+    suggestions: Vec<String>,
+    /// The last dry run of the mapping.
+    validation: Option<MappingResult>,
+    items: Vec<ImportItem>,
+    cursor: usize,
+    /// Depth of the shallowest entry, so the preview tree starts at column 0.
+    min_depth: usize,
+    summary: String,
+    problems: Vec<String>,
+}
+
+impl ImportDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Import Entries", theme.clone()).with_size(70, 70),
+            theme,
+            phase: Phase::FilePath,
+            file_path: String::new(),
+            schema: None,
+            csv: None,
+            mapping: ColumnMapping::default(),
+            map_cursor: 0,
+            attr_names: Vec::new(),
+            fuzzy: FuzzyFilter::new(),
+            suggestions: Vec::new(),
+            validation: None,
+            items: Vec::new(),
+            cursor: 0,
+            min_depth: 0,
+            summary: String::new(),
+            problems: Vec::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn set_schema(&mut self, schema: Option<SchemaCache>) {
+        self.attr_names = schema
+            .as_ref()
+            .map(|s| {
+                s.unique_attribute_types()
+                    .iter()
+                    .filter_map(|at| at.names.first().cloned())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.schema = schema;
+    }
+
+    pub fn show(&mut self) {
+        self.phase = Phase::FilePath;
+        if self.file_path.is_empty() {
+            self.file_path = "import.ldif".to_string();
+        }
+        self.csv = None;
+        self.validation = None;
+        self.items.clear();
+        self.cursor = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Show the parsed entries (in tree order) for review.
+    pub fn show_preview(&mut self, items: Vec<ImportItem>) {
+        self.min_depth = items
+            .iter()
+            .map(|i| loom_core::dn::depth(&i.entry.dn))
+            .min()
+            .unwrap_or(0);
+        self.items = items;
+        self.cursor = 0;
+        self.phase = Phase::Preview;
+    }
+
+    /// Show the outcome of the import.
+    pub fn show_report(&mut self, summary: String, problems: Vec<String>) {
+        self.summary = summary;
+        self.problems = problems;
+        self.phase = Phase::Report;
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match self.phase {
+            Phase::FilePath | Phase::Checking => self.handle_filepath_key(key),
+            Phase::Mapping => self.handle_mapping_key(key),
+            Phase::Preview => self.handle_preview_key(key),
+            Phase::Applying | Phase::Report => match key.code {
+                KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q') => {
+                    self.hide();
+                    Action::ClosePopup
+                }
+                _ => Action::None,
+            },
+        }
+    }
+
+    fn handle_filepath_key(&mut self, key: KeyEvent) -> Action {
+        self.phase = Phase::FilePath;
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => self.open_file(),
+            KeyCode::Backspace => {
+                self.file_path.pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.file_path.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Read the file: CSV goes to the mapping step, other formats straight
+    /// to the server check.
+    fn open_file(&mut self) -> Action {
+        if self.file_path.trim().is_empty() {
+            return Action::ErrorMessage("File path is required".to_string());
+        }
+        let path = expand_tilde(self.file_path.trim());
+        let path = std::path::Path::new(&path);
+        let is_csv = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .is_some_and(|e| e.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            let table = match std::fs::read_to_string(path) {
+                Ok(content) => loom_core::import::csv::read_table(&content),
+                Err(e) => {
+                    return Action::ErrorMessage(format!(
+                        "Failed to read {}: {}",
+                        path.display(),
+                        e
+                    ))
+                }
+            };
+            return match table {
+                Ok(table) if table.rows.is_empty() => {
+                    Action::ErrorMessage(format!("No rows found in {}", path.display()))
+                }
+                Ok(table) => {
+                    self.mapping = ColumnMapping::guess(&table.headers, self.schema.as_ref());
+                    self.csv = Some(table);
+                    self.map_cursor = 0;
+                    self.validation = None;
+                    self.suggestions.clear();
+                    self.phase = Phase::Mapping;
+                    Action::None
+                }
+                Err(e) => Action::ErrorMessage(format!("Import failed: {}", e)),
+            };
+        }
+
+        self.csv = None;
+        match loom_core::import::import_entries(path) {
+            Ok(entries) if entries.is_empty() => {
+                Action::ErrorMessage(format!("No entries found in {}", path.display()))
+            }
+            Ok(entries) => self.check(entries),
+            Err(e) => Action::ErrorMessage(format!("Import failed: {}", e)),
+        }
+    }
+
+    fn check(&mut self, entries: Vec<LdapEntry>) -> Action {
+        self.phase = Phase::Checking;
+        Action::ImportPreview(entries)
+    }
+
+    fn handle_mapping_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.phase = Phase::FilePath;
+                self.csv = None;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let Some(result) = self.validate() else {
+                    return Action::None;
+                };
+                if !result.problems.is_empty() {
+                    let count = result.problems.len();
+                    self.validation = Some(result);
+                    return Action::ErrorMessage(format!(
+                        "{} rows have problems; fix the mapping to continue",
+                        count
+                    ));
+                }
+                self.validation = None;
+                self.check(result.entries)
+            }
+            KeyCode::F(2) => {
+                self.validation = self.validate();
+                Action::None
+            }
+            KeyCode::Up => {
+                self.map_cursor = self.map_cursor.saturating_sub(1);
+                self.update_suggestions();
+                Action::None
+            }
+            KeyCode::Down => {
+                if self.map_cursor < self.mapping.attributes.len() {
+                    self.map_cursor += 1;
+                }
+                self.update_suggestions();
+                Action::None
+            }
+            KeyCode::Tab => {
+                if let Some(first) = self.suggestions.first().cloned() {
+                    self.set_mapping_text(first);
+                }
+                Action::None
+            }
+            KeyCode::Backspace => {
+                let mut text = self.mapping_text();
+                text.pop();
+                self.set_mapping_text(text);
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                let mut text = self.mapping_text();
+                text.push(c);
+                self.set_mapping_text(text);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// Dry-run the mapping; a mapping error is shown as the only problem.
+    fn validate(&mut self) -> Option<MappingResult> {
+        let table = self.csv.as_ref()?;
+        Some(
+            self.mapping
+                .apply(table, self.schema.as_ref())
+                .unwrap_or_else(|e| MappingResult {
+                    entries: Vec::new(),
+                    problems: vec![e],
+                }),
+        )
+    }
+
+    /// The selected mapping row's text: the DN template, or a column's
+    /// attribute (empty when the column is left out).
+    fn mapping_text(&self) -> String {
+        match self.map_cursor {
+            0 => self.mapping.dn_template.clone(),
+            n => self.mapping.attributes[n - 1].clone().unwrap_or_default(),
+        }
+    }
+
+    fn set_mapping_text(&mut self, text: String) {
+        match self.map_cursor {
+            0 => self.mapping.dn_template = text,
+            n => {
+                let attr = text.trim();
+                self.mapping.attributes[n - 1] = (!attr.is_empty()).then(|| attr.to_string());
+            }
+        }
+        self.validation = None;
+        self.update_suggestions();
+    }
+
+    /// Schema attributes matching the selected column's text.
+    fn update_suggestions(&mut self) {
+        self.suggestions.clear();
+        if self.map_cursor == 0 {
+            return;
+        }
+        let query = self.mapping_text();
+        if query.is_empty() {
+            return;
+        }
+        self.suggestions = self
+            .fuzzy
+            .filter(&query, &self.attr_names)
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|m| self.attr_names[m.index].clone())
+            .filter(|name| name != &query)
+            .collect();
+    }
+
+    fn handle_preview_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.phase = if self.csv.is_some() {
+                    Phase::Mapping
+                } else {
+                    Phase::FilePath
+                };
+                self.items.clear();
+                Action::None
+            }
+            KeyCode::Enter => {
+                self.phase = Phase::Applying;
+                Action::ImportExecute(self.items.clone())
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.cursor = self.cursor.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.cursor + 1 < self.items.len() {
+                    self.cursor += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('n') => {
+                // Jump to the next conflict, wrapping around
+                let len = self.items.len();
+                if let Some(next) = (1..=len)
+                    .map(|step| (self.cursor + step) % len)
+                    .find(|&i| self.items[i].is_conflict())
+                {
+                    self.cursor = next;
+                }
+                Action::None
+            }
+            KeyCode::Char(' ') => {
+                if let Some(item) = self.items.get_mut(self.cursor) {
+                    if item.is_conflict() {
+                        item.resolution = item.resolution.next();
+                    }
+                }
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                let Some(resolution) = resolution_key(c.to_ascii_lowercase()) else {
+                    return Action::None;
+                };
+                if c.is_ascii_uppercase() {
+                    for item in self.items.iter_mut().filter(|i| i.is_conflict()) {
+                        item.resolution = resolution;
+                    }
+                } else if let Some(item) = self.items.get_mut(self.cursor) {
+                    if item.is_conflict() {
+                        item.resolution = resolution;
+                    }
+                }
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Import Entries ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        match self.phase {
+            Phase::FilePath | Phase::Checking => self.render_filepath(frame, inner),
+            Phase::Mapping => self.render_mapping(frame, inner),
+            Phase::Preview => self.render_preview(frame, inner),
+            Phase::Applying | Phase::Report => self.render_report(frame, inner),
+        }
+    }
+
+    fn render_filepath(&self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Length(2), // File path
+            Constraint::Length(2), // Formats or progress
+            Constraint::Min(1),    // Hints
+        ])
+        .split(area);
+
+        let lines = vec![
+            Line::from(Span::styled("File path:", self.theme.header)),
+            Line::from(vec![
+                Span::styled(&self.file_path, self.theme.normal),
+                Span::styled("_", self.theme.command_prompt),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let note = if self.phase == Phase::Checking {
+            "Reading the file and checking which entries exist..."
+        } else {
+            "Accepts LDIF, JSON, CSV and XLSX; CSV columns are mapped next"
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(note, self.theme.dimmed))),
+            layout[1],
+        );
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Enter:preview  Esc:cancel",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[2]);
+    }
+
+    fn render_mapping(&self, frame: &mut Frame, area: Rect) {
+        let Some(table) = &self.csv else {
+            return;
+        };
+        let problem_lines = self
+            .validation
+            .as_ref()
+            .map_or(0, |v| v.problems.len().min(5) + 1) as u16;
+        let layout = Layout::vertical([
+            Constraint::Length(2),             // DN template
+            Constraint::Length(1),             // Columns label
+            Constraint::Min(3),                // Columns
+            Constraint::Length(1),             // Suggestions
+            Constraint::Length(problem_lines), // Validation
+            Constraint::Length(2),             // Hints
+        ])
+        .split(area);
+
+        let template_active = self.map_cursor == 0;
+        let template = vec![
+            Line::from(Span::styled(
+                "DN template (e.g. uid={username},ou=people,dc=example,dc=com):",
+                if template_active {
+                    self.theme.header
+                } else {
+                    self.theme.dimmed
+                },
+            )),
+            Line::from(vec![
+                Span::styled(self.mapping.dn_template.clone(), self.theme.normal),
+                if template_active {
+                    Span::styled("_", self.theme.command_prompt)
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(template), layout[0]);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(
+                format!(
+                    "{} columns, {} rows. Map each column to an attribute:",
+                    table.headers.len(),
+                    table.rows.len()
+                ),
+                self.theme.header,
+            ))),
+            layout[1],
+        );
+
+        let width = table
+            .headers
+            .iter()
+            .map(|h| h.chars().count())
+            .max()
+            .unwrap_or(0);
+        let height = layout[2].height as usize;
+        let offset = self.map_cursor.saturating_sub(height);
+        let lines: Vec<Line> = table
+            .headers
+            .iter()
+            .zip(&self.mapping.attributes)
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, (header, attr))| {
+                let is_cursor = self.map_cursor == i + 1;
+                let sample = table
+                    .rows
+                    .first()
+                    .and_then(|row| row.get(i))
+                    .map(|v| v.as_str())
+                    .unwrap_or("");
+                let target = match attr {
+                    Some(attr) => Span::styled(attr.clone(), self.theme.normal),
+                    None => Span::styled("(not imported)", self.theme.dimmed),
+                };
+                Line::from(vec![
+                    Span::raw(if is_cursor { "> " } else { "  " }),
+                    Span::styled(
+                        format!("{:width$} -> ", header, width = width),
+                        if is_cursor {
+                            self.theme.selected.add_modifier(Modifier::BOLD)
+                        } else {
+                            self.theme.normal
+                        },
+                    ),
+                    target,
+                    if is_cursor {
+                        Span::styled("_", self.theme.command_prompt)
+                    } else {
+                        Span::raw("")
+                    },
+                    Span::styled(format!("  e.g. {}", sample), self.theme.dimmed),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[2]);
+
+        if !self.suggestions.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    format!("Tab: {}", self.suggestions.join("  ")),
+                    self.theme.dimmed,
+                ))),
+                layout[3],
+            );
+        }
+
+        if let Some(validation) = &self.validation {
+            let (summary, style) = if validation.problems.is_empty() {
+                (
+                    format!(
+                        "Dry run: all {} entries are valid",
+                        validation.entries.len()
+                    ),
+                    self.theme.success,
+                )
+            } else {
+                (
+                    format!(
+                        "Dry run: {} valid entries, {} problems",
+                        validation.entries.len(),
+                        validation.problems.len()
+                    ),
+                    self.theme.warning,
+                )
+            };
+            let mut lines = vec![Line::from(Span::styled(summary, style))];
+            lines.extend(
+                validation
+                    .problems
+                    .iter()
+                    .take(5)
+                    .map(|p| Line::from(Span::styled(p.clone(), self.theme.error))),
+            );
+            frame.render_widget(Paragraph::new(lines), layout[4]);
+        }
+
+        let hints = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "Up/Down:select  type:edit  Tab:complete  F2:dry run",
+                self.theme.dimmed,
+            )),
+            Line::from(Span::styled("Enter:preview  Esc:back", self.theme.dimmed)),
+        ]);
+        frame.render_widget(hints, layout[5]);
+    }
+
+    fn render_preview(&self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Counts
+            Constraint::Min(3),    // Tree
+            Constraint::Length(2), // Selected entry
+            Constraint::Length(2), // Hints
+        ])
+        .split(area);
+
+        let conflicts = self.items.iter().filter(|i| i.is_conflict()).count();
+        let counts = format!(
+            "{} entries: {} new, {} already exist",
+            self.items.len(),
+            self.items.len() - conflicts,
+            conflicts
+        );
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(counts, self.theme.header))),
+            layout[0],
+        );
+
+        let height = layout[1].height as usize;
+        let offset = (self.cursor + 1).saturating_sub(height);
+        let lines: Vec<Line> = self
+            .items
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, item)| {
+                let depth = loom_core::dn::depth(&item.entry.dn).saturating_sub(self.min_depth);
+                let (tag, tag_style) = match &item.existing {
+                    None => ("[new]".to_string(), self.theme.success),
+                    Some(_) => (
+                        format!("[exists: {}]", item.resolution.label()),
+                        self.theme.warning,
+                    ),
+                };
+                let is_cursor = i == self.cursor;
+                let name_style = if is_cursor {
+                    self.theme.selected.add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.normal
+                };
+                Line::from(vec![
+                    Span::raw(if is_cursor { "> " } else { "  " }),
+                    Span::raw("  ".repeat(depth)),
+                    Span::styled(loom_core::dn::rdn(&item.entry.dn).to_string(), name_style),
+                    Span::raw(" "),
+                    Span::styled(tag, tag_style),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        if let Some(item) = self.items.get(self.cursor) {
+            let detail = match item.resolution {
+                _ if !item.is_conflict() => {
+                    format!("{} attributes will be created", item.entry.attributes.len())
+                }
+                ConflictResolution::Skip => "The existing entry is left unchanged".to_string(),
+                _ => format!("{} attribute changes", item.mods().len()),
+            };
+            let lines = vec![
+                Line::from(Span::styled(item.entry.dn.clone(), self.theme.normal)),
+                Line::from(Span::styled(detail, self.theme.dimmed)),
+            ];
+            frame.render_widget(Paragraph::new(lines), layout[2]);
+        }
+
+        let hints = Paragraph::new(vec![
+            Line::from(Span::styled(
+                "Space:cycle  s/o/m:skip/overwrite/merge  S/O/M:all conflicts  n:next conflict",
+                self.theme.dimmed,
+            )),
+            Line::from(Span::styled("Enter:import  Esc:back", self.theme.dimmed)),
+        ]);
+        frame.render_widget(hints, layout[3]);
+    }
+
+    fn render_report(&self, frame: &mut Frame, area: Rect) {
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // Report
+            Constraint::Length(1), // Hints
+        ])
+        .split(area);
+
+        let mut lines = Vec::new();
+        if self.phase == Phase::Applying {
+            lines.push(Line::from(Span::styled(
+                format!("Importing {} entries...", self.items.len()),
+                self.theme.header,
+            )));
+        } else {
+            let summary_style = if self.problems.is_empty() {
+                self.theme.success
+            } else {
+                self.theme.warning
+            };
+            lines.push(Line::from(Span::styled(
+                self.summary.clone(),
+                summary_style,
+            )));
+            if !self.problems.is_empty() {
+                lines.push(Line::from(""));
+                lines.push(Line::from(Span::styled("Failures:", self.theme.header)));
+                for problem in &self.problems {
+                    lines.push(Line::from(Span::styled(problem.clone(), self.theme.error)));
+                }
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled("Esc:close", self.theme.dimmed))),
+            layout[1],
+        );
+    }
+}
+
+fn resolution_key(c: char) -> Option<ConflictResolution> {
+    match c {
+        's' => Some(ConflictResolution::Skip),
+        'o' => Some(ConflictResolution::Overwrite),
+        'm' => Some(ConflictResolution::Merge),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn item(dn: &str, exists: bool) -> ImportItem {
+        let entry = LdapEntry::new(
+            dn.to_string(),
+            BTreeMap::from([("cn".to_string(), vec!["x".to_string()])]),
+        );
+        let existing = exists.then(|| LdapEntry::new(dn.to_string(), BTreeMap::new()));
+        ImportItem::new(entry, existing)
+    }
+
+    #[test]
+    fn test_resolve_conflicts() {
+        let mut dialog = ImportDialog::new(Theme::default());
+        dialog.show();
+        dialog.show_preview(vec![
+            item("ou=people,dc=example,dc=com", true),
+            item("cn=a,ou=people,dc=example,dc=com", false),
+            item("cn=b,ou=people,dc=example,dc=com", true),
+        ]);
+
+        // New entries have nothing to resolve
+        dialog.handle_key_event(key('j'));
+        dialog.handle_key_event(key('o'));
+        assert_eq!(dialog.items[1].resolution, ConflictResolution::Skip);
+
+        dialog.handle_key_event(key('n'));
+        assert_eq!(dialog.cursor, 2);
+        dialog.handle_key_event(key(' '));
+        assert_eq!(dialog.items[2].resolution, ConflictResolution::Overwrite);
+
+        dialog.handle_key_event(key('M'));
+        assert!(dialog
+            .items
+            .iter()
+            .filter(|i| i.is_conflict())
+            .all(|i| i.resolution == ConflictResolution::Merge));
+
+        let action = dialog.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        match action {
+            Action::ImportExecute(items) => {
+                assert_eq!(items.len(), 3);
+                assert_eq!(items[0].resolution, ConflictResolution::Merge);
+            }
+            other => panic!("expected ImportExecute, got {:?}", other),
+        }
+    }
+
+    fn code(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_csv_mapping() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("people.csv");
+        std::fs::write(&path, "username,full name,notes\njdoe,John Doe,x\n").unwrap();
+
+        let mut dialog = ImportDialog::new(Theme::default());
+        dialog.show();
+        dialog.file_path = path.display().to_string();
+        assert!(matches!(
+            dialog.handle_key_event(code(KeyCode::Enter)),
+            Action::None
+        ));
+        assert_eq!(dialog.phase, Phase::Mapping);
+
+        // Without a DN template the dry run reports it
+        dialog.handle_key_event(code(KeyCode::F(2)));
+        assert!(!dialog.validation.as_ref().unwrap().problems.is_empty());
+
+        for c in "uid={username},dc=example,dc=com".chars() {
+            dialog.handle_key_event(key(c));
+        }
+        // Map "full name" to cn and leave "notes" out
+        dialog.handle_key_event(code(KeyCode::Down));
+        dialog.handle_key_event(code(KeyCode::Down));
+        for _ in 0.."full name".len() {
+            dialog.handle_key_event(code(KeyCode::Backspace));
+        }
+        dialog.handle_key_event(key('c'));
+        dialog.handle_key_event(key('n'));
+        dialog.handle_key_event(code(KeyCode::Down));
+        for _ in 0.."notes".len() {
+            dialog.handle_key_event(code(KeyCode::Backspace));
+        }
+
+        match dialog.handle_key_event(code(KeyCode::Enter)) {
+            Action::ImportPreview(entries) => {
+                assert_eq!(entries.len(), 1);
+                assert_eq!(entries[0].dn, "uid=jdoe,dc=example,dc=com");
+                assert_eq!(entries[0].first_value("cn"), Some("John Doe"));
+                assert!(entries[0].first_value("notes").is_none());
+            }
+            other => panic!("expected ImportPreview, got {:?}", other),
+        }
+        assert_eq!(dialog.phase, Phase::Checking);
+    }
+}
@@ -0,0 +1,234 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::jobs::{Job, JobRegistry, JobState};
+use crate::theme::Theme;
+
+/// A toggleable list of background jobs, running and finished, with
+/// cancel and retry.
+pub struct JobsPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    selected: usize,
+}
+
+impl JobsPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Jobs", theme.clone()).with_size(80, 50),
+            theme,
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.popup.show();
+            self.selected = 0;
+        } else {
+            self.popup.hide();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Jobs newest first, as listed.
+    fn listed(jobs: &JobRegistry) -> impl Iterator<Item = &Job> {
+        jobs.jobs().iter().rev()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent, jobs: &JobRegistry) -> Action {
+        let count = jobs.jobs().len();
+        self.selected = self.selected.min(count.saturating_sub(1));
+        let selected = Self::listed(jobs).nth(self.selected);
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < count {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('c') => match selected {
+                Some(job) if job.is_running() => Action::CancelJob(job.id),
+                _ => Action::None,
+            },
+            KeyCode::Char('r') => match selected {
+                Some(job) if job.can_retry() => {
+                    self.selected = 0;
+                    Action::RetryJob(job.id)
+                }
+                _ => Action::None,
+            },
+            KeyCode::Char('C') => {
+                self.selected = 0;
+                Action::ClearFinishedJobs
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect, jobs: &JobRegistry) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(
+                " Jobs ({} running, {} total) ",
+                jobs.running(),
+                jobs.jobs().len()
+            ))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: jobs | selected job's result (2) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        if jobs.jobs().is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No background jobs. Exports, bulk updates and imports are listed here.",
+                    self.theme.dimmed,
+                ))),
+                layout[0],
+            );
+        }
+
+        let height = layout[0].height as usize;
+        let offset = (self.selected + 1).saturating_sub(height);
+        let lines: Vec<Line> = Self::listed(jobs)
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, job)| {
+                let state_style = match job.state {
+                    JobState::Running => self.theme.warning,
+                    JobState::Done(_) => self.theme.success,
+                    JobState::Failed(_) => self.theme.error,
+                    JobState::Cancelled => self.theme.dimmed,
+                };
+                let title_style = if i == self.selected {
+                    self.theme.selected.add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.normal
+                };
+                let progress = match job.progress {
+                    Some((done, Some(total))) => format!("  {}/{}", done, total),
+                    Some((done, None)) => format!("  {} so far", done),
+                    None => String::new(),
+                };
+                Line::from(vec![
+                    Span::styled(format!("{:<10}", job.state.label()), state_style),
+                    Span::styled(job.title.clone(), title_style),
+                    Span::styled(progress, self.theme.normal),
+                    Span::styled(
+                        format!("  {}", loom_core::humanize::duration(job.elapsed())),
+                        self.theme.dimmed,
+                    ),
+                ])
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let detail = match Self::listed(jobs).nth(self.selected).map(|j| &j.state) {
+            Some(JobState::Done(msg)) => Span::styled(msg.clone(), self.theme.normal),
+            Some(JobState::Failed(msg)) => Span::styled(msg.clone(), self.theme.error),
+            _ => Span::raw(""),
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(detail)).wrap(ratatui::widgets::Wrap { trim: true }),
+            layout[1],
+        );
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  c:cancel  r:retry  C:clear finished  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+
+    use super::*;
+    use crate::jobs::JobTask;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_cancel_and_retry_keys() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        let task = JobTask::BulkUpdate {
+            filter: "(objectClass=person)".to_string(),
+            modifications: Vec::new(),
+            throttle: Default::default(),
+        };
+        let failed = jobs.register(0, "Bulk update".to_string(), task.clone());
+        jobs.finish(failed, JobState::Failed("Bulk update failed".to_string()));
+        let running = jobs.register(0, "Bulk update".to_string(), task);
+
+        let mut panel = JobsPanel::new(Theme::default());
+        panel.toggle();
+        // Newest first: the running job is selected
+        assert!(matches!(
+            panel.handle_key_event(key('r'), &jobs),
+            Action::None
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key('c'), &jobs),
+            Action::CancelJob(id) if id == running
+        ));
+
+        panel.handle_key_event(key('j'), &jobs);
+        assert!(matches!(
+            panel.handle_key_event(key('c'), &jobs),
+            Action::None
+        ));
+        assert!(matches!(
+            panel.handle_key_event(key('r'), &jobs),
+            Action::RetryJob(id) if id == failed
+        ));
+    }
+}
@@ -7,15 +7,26 @@ use crate::action::{ActiveLayout, ConnectionId};
 use crate::components::tab_bar::TabEntry;
 use crate::theme::Theme;
 
-/// Unified tab bar: `[Profiles] | [conn1] conn2`
+/// Close button drawn after each connection tab's label.
+const CLOSE_GLYPH: &str = "\u{2715}";
+
+/// What a click on the layout bar landed on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutBarHit {
+    Profiles,
+    Tab(ConnectionId),
+    Close(ConnectionId),
+}
+
+/// Unified tab bar: `[Profiles] | [conn1 ✕] conn2 ✕`
 ///
 /// Profiles is always the first tab. Connection tabs follow after a separator.
 pub struct LayoutBar {
     pub active: ActiveLayout,
     theme: Theme,
     /// Hit regions populated during render: (x_start, x_end_exclusive, target).
-    /// `None` = Profiles tab, `Some(id)` = connection tab.
-    pub hit_regions: Vec<(u16, u16, Option<ConnectionId>)>,
+    /// Close buttons come before the tab that contains them.
+    pub hit_regions: Vec<(u16, u16, LayoutBarHit)>,
 }
 
 impl LayoutBar {
@@ -27,6 +38,18 @@ impl LayoutBar {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
+    /// The tab or close button at column `x`, as of the last render.
+    pub fn hit(&self, x: u16) -> Option<LayoutBarHit> {
+        self.hit_regions
+            .iter()
+            .find(|(start, end, _)| x >= *start && x < *end)
+            .map(|(_, _, target)| *target)
+    }
+
     pub fn render(
         &mut self,
         frame: &mut Frame,
@@ -54,7 +77,8 @@ impl LayoutBar {
         };
         spans.push(Span::styled(profiles_label, profiles_style));
         let profiles_end = x + profiles_label.len() as u16;
-        self.hit_regions.push((x, profiles_end, None));
+        self.hit_regions
+            .push((x, profiles_end, LayoutBarHit::Profiles));
         x = profiles_end;
 
         // Connection tabs after separator
@@ -71,17 +95,26 @@ impl LayoutBar {
                 };
 
                 let tab_start = x;
+                let label = Span::styled(&tab.label, style);
+                let close_start = tab_start + 1 + label.width() as u16 + 1;
                 if is_active {
                     spans.push(Span::styled("[", style));
-                    spans.push(Span::styled(&tab.label, style));
+                    spans.push(label);
+                    spans.push(Span::styled(" ", style));
+                    spans.push(Span::styled(CLOSE_GLYPH, style));
                     spans.push(Span::styled("]", style));
                 } else {
                     spans.push(Span::styled(" ", self.theme.status_bar));
-                    spans.push(Span::styled(&tab.label, style));
+                    spans.push(label);
+                    spans.push(Span::styled(" ", self.theme.status_bar));
+                    spans.push(Span::styled(CLOSE_GLYPH, self.theme.dimmed));
                     spans.push(Span::styled(" ", self.theme.status_bar));
                 }
-                x += tab.label.len() as u16 + 2;
-                self.hit_regions.push((tab_start, x, Some(tab.id)));
+                x = close_start + 2;
+                self.hit_regions
+                    .push((close_start, close_start + 1, LayoutBarHit::Close(tab.id)));
+                self.hit_regions
+                    .push((tab_start, x, LayoutBarHit::Tab(tab.id)));
 
                 spans.push(Span::styled(" ", self.theme.status_bar));
                 x += 1;
@@ -89,7 +122,7 @@ impl LayoutBar {
         }
 
         // Pad remaining width
-        let content_len: usize = spans.iter().map(|s| s.content.len()).sum();
+        let content_len: usize = spans.iter().map(|s| s.width()).sum();
         let padding = " ".repeat(area.width as usize - content_len.min(area.width as usize));
         spans.push(Span::styled(padding, self.theme.status_bar));
 
@@ -98,3 +131,49 @@ impl LayoutBar {
         frame.render_widget(bar, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use ratatui::backend::TestBackend;
+    use ratatui::Terminal;
+
+    use super::*;
+
+    #[test]
+    fn test_hit_regions() {
+        let mut bar = LayoutBar::new(Theme::default());
+        bar.active = ActiveLayout::Browser;
+        let tabs = vec![
+            TabEntry {
+                id: 1,
+                label: "prod".to_string(),
+            },
+            TabEntry {
+                id: 2,
+                label: "dev".to_string(),
+            },
+        ];
+        let mut terminal = Terminal::new(TestBackend::new(60, 1)).unwrap();
+        terminal
+            .draw(|frame| bar.render(frame, frame.area(), &tabs, Some(1)))
+            .unwrap();
+
+        // "  Profiles  │ [prod ✕]  dev ✕  "
+        let line: String = terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol())
+            .collect();
+        assert!(line.starts_with("  Profiles  \u{2502} [prod \u{2715}]  dev \u{2715} "));
+        assert_eq!(bar.hit(0), None);
+        assert_eq!(bar.hit(1), Some(LayoutBarHit::Profiles));
+        assert_eq!(bar.hit(14), Some(LayoutBarHit::Tab(1)));
+        assert_eq!(bar.hit(20), Some(LayoutBarHit::Close(1)));
+        assert_eq!(bar.hit(21), Some(LayoutBarHit::Tab(1)));
+        assert_eq!(bar.hit(23), Some(LayoutBarHit::Tab(2)));
+        assert_eq!(bar.hit(28), Some(LayoutBarHit::Close(2)));
+        assert_eq!(bar.hit(30), None);
+    }
+}
@@ -0,0 +1,139 @@
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Full-screen overlay shown after the idle timeout locks the session.
+/// Only the vault master password (or quitting) dismisses it.
+pub struct LockScreen {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    password: String,
+    error: Option<String>,
+}
+
+impl LockScreen {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Session Locked", theme.clone()).with_size(50, 25),
+            theme,
+            password: String::new(),
+            error: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self) {
+        self.password.clear();
+        self.error = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+        self.password.clear();
+        self.error = None;
+    }
+
+    /// Show an unlock failure and clear the typed password.
+    pub fn set_error(&mut self, msg: String) {
+        self.password.clear();
+        self.error = Some(msg);
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if key.modifiers.contains(KeyModifiers::CONTROL)
+            && matches!(key.code, KeyCode::Char('c') | KeyCode::Char('q'))
+        {
+            return Action::Quit;
+        }
+
+        match key.code {
+            KeyCode::Enter => {
+                if self.password.is_empty() {
+                    return Action::None;
+                }
+                Action::UnlockSession(std::mem::take(&mut self.password))
+            }
+            KeyCode::Backspace => {
+                self.password.pop();
+                Action::None
+            }
+            KeyCode::Esc => {
+                self.password.clear();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.password.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        // Hide everything underneath, not just the dialog area
+        frame.render_widget(Clear, full);
+
+        let area = self.popup.centered_area(full);
+        let block = Block::default()
+            .title(" Session Locked ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Info
+            Constraint::Length(2), // Password
+            Constraint::Length(1), // Error
+            Constraint::Min(1),    // Hints
+        ])
+        .split(inner);
+
+        let info = Paragraph::new(Line::from(Span::styled(
+            "Locked after inactivity. Enter the vault master password:",
+            self.theme.normal,
+        )));
+        frame.render_widget(info, layout[0]);
+
+        let lines = vec![
+            Line::from(Span::styled("Password:", self.theme.header)),
+            Line::from(vec![
+                Span::styled("*".repeat(self.password.chars().count()), self.theme.normal),
+                Span::styled("_", self.theme.command_prompt),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        if let Some(ref err) = self.error {
+            let error = Paragraph::new(Line::from(Span::styled(err.as_str(), self.theme.error)));
+            frame.render_widget(error, layout[2]);
+        }
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Enter:unlock  Esc:clear  Ctrl+q:quit",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[3]);
+    }
+}
@@ -1,3 +1,4 @@
+use chrono::{DateTime, Local};
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::text::{Line, Span};
@@ -6,28 +7,94 @@ use ratatui::Frame;
 
 use crate::action::Action;
 use crate::components::popup::Popup;
+use crate::log_capture::CapturedLog;
 use crate::theme::Theme;
 
+/// Messages kept; the oldest are dropped first.
+const MAX_ENTRIES: usize = 2000;
+
 /// A toggleable in-TUI log viewer.
-/// Collects log messages and displays them in a scrollable popup.
+/// Collects log messages and displays them in a scrollable popup, filtered
+/// by severity and an optional search text.
 pub struct LogPanel {
     pub visible: bool,
     popup: Popup,
     theme: Theme,
     messages: Vec<LogEntry>,
+    /// Index, among the shown messages, of the bottom line on screen.
     scroll_offset: usize,
+    /// Keep the newest message in view as messages arrive.
+    follow: bool,
+    min_level: LogLevel,
+    search: String,
+    /// The search box has the keyboard.
+    searching: bool,
 }
 
 struct LogEntry {
+    time: DateTime<Local>,
     level: LogLevel,
+    /// Module a captured tracing event came from.
+    target: Option<String>,
     message: String,
 }
 
-#[derive(Clone, Copy)]
+impl LogEntry {
+    /// The entry as a line of a saved log file.
+    fn to_line(&self) -> String {
+        let target = self
+            .target
+            .as_deref()
+            .map(|t| format!("{}: ", t))
+            .unwrap_or_default();
+        format!(
+            "{} {:<5} {}{}",
+            self.time.format("%Y-%m-%d %H:%M:%S"),
+            self.level.label(),
+            target,
+            self.message
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
 enum LogLevel {
+    Debug,
     Info,
+    Warn,
     Error,
-    Debug,
+}
+
+impl LogLevel {
+    fn label(self) -> &'static str {
+        match self {
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Info => "INFO",
+            LogLevel::Warn => "WARN",
+            LogLevel::Error => "ERROR",
+        }
+    }
+
+    /// The next minimum level for the `l` key, wrapping around.
+    fn next(self) -> Self {
+        match self {
+            LogLevel::Debug => LogLevel::Info,
+            LogLevel::Info => LogLevel::Warn,
+            LogLevel::Warn => LogLevel::Error,
+            LogLevel::Error => LogLevel::Debug,
+        }
+    }
+}
+
+impl From<tracing::Level> for LogLevel {
+    fn from(level: tracing::Level) -> Self {
+        match level {
+            tracing::Level::ERROR => LogLevel::Error,
+            tracing::Level::WARN => LogLevel::Warn,
+            tracing::Level::INFO => LogLevel::Info,
+            _ => LogLevel::Debug,
+        }
+    }
 }
 
 impl LogPanel {
@@ -38,15 +105,23 @@ impl LogPanel {
             theme,
             messages: Vec::new(),
             scroll_offset: 0,
+            follow: true,
+            min_level: LogLevel::Info,
+            search: String::new(),
+            searching: false,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn toggle(&mut self) {
         self.visible = !self.visible;
         if self.visible {
             self.popup.show();
-            // Scroll to bottom
-            self.scroll_offset = self.messages.len().saturating_sub(1);
+            self.follow = true;
         } else {
             self.popup.hide();
         }
@@ -54,63 +129,154 @@ impl LogPanel {
 
     pub fn hide(&mut self) {
         self.visible = false;
+        self.searching = false;
         self.popup.hide();
     }
 
     pub fn push_info(&mut self, msg: String) {
-        self.messages.push(LogEntry {
-            level: LogLevel::Info,
-            message: msg,
-        });
-        self.trim();
+        self.push(LogLevel::Info, msg);
     }
 
     pub fn push_error(&mut self, msg: String) {
+        self.push(LogLevel::Error, msg);
+    }
+
+    pub fn push_debug(&mut self, msg: String) {
+        self.push(LogLevel::Debug, msg);
+    }
+
+    /// Add an event recorded by the tracing capture layer.
+    pub fn push_captured(&mut self, log: CapturedLog) {
         self.messages.push(LogEntry {
-            level: LogLevel::Error,
-            message: msg,
+            time: log.time,
+            level: log.level.into(),
+            target: Some(log.target),
+            message: log.message,
         });
         self.trim();
     }
 
-    pub fn push_debug(&mut self, msg: String) {
+    fn push(&mut self, level: LogLevel, message: String) {
         self.messages.push(LogEntry {
-            level: LogLevel::Debug,
-            message: msg,
+            time: Local::now(),
+            level,
+            target: None,
+            message,
         });
         self.trim();
     }
 
     fn trim(&mut self) {
-        if self.messages.len() > 500 {
-            self.messages.drain(..self.messages.len() - 500);
+        if self.messages.len() > MAX_ENTRIES {
+            self.messages.drain(..self.messages.len() - MAX_ENTRIES);
         }
     }
 
+    /// Messages passing the level filter and matching the search text.
+    fn shown(&self) -> Vec<&LogEntry> {
+        let search = self.search.to_lowercase();
+        self.messages
+            .iter()
+            .filter(|entry| entry.level >= self.min_level)
+            .filter(|entry| {
+                search.is_empty()
+                    || entry.message.to_lowercase().contains(&search)
+                    || entry
+                        .target
+                        .as_deref()
+                        .is_some_and(|t| t.to_lowercase().contains(&search))
+            })
+            .collect()
+    }
+
+    /// The shown messages as the text of a log file.
+    fn saved_text(&self) -> String {
+        self.shown()
+            .iter()
+            .map(|entry| entry.to_line() + "\n")
+            .collect()
+    }
+
+    fn handle_search_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.search.clear();
+                self.searching = false;
+            }
+            KeyCode::Enter => self.searching = false,
+            KeyCode::Backspace => {
+                self.search.pop();
+            }
+            KeyCode::Char(c) => self.search.push(c),
+            _ => {}
+        }
+        self.follow = true;
+        Action::None
+    }
+
+    /// Scroll the log `delta` lines with the mouse wheel. Scrolling up
+    /// stops following new messages.
+    pub fn scroll(&mut self, delta: isize) {
+        let count = self.shown().len();
+        if self.follow {
+            self.scroll_offset = count.saturating_sub(1);
+        }
+        if delta < 0 {
+            self.follow = false;
+        }
+        self.scroll_offset = self
+            .scroll_offset
+            .saturating_add_signed(delta)
+            .min(count.saturating_sub(1));
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.searching {
+            return self.handle_search_key(key);
+        }
+        let count = self.shown().len();
+        if self.follow {
+            self.scroll_offset = count.saturating_sub(1);
+        }
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.hide();
                 Action::ClosePopup
             }
             KeyCode::Up | KeyCode::Char('k') => {
+                self.follow = false;
                 self.scroll_offset = self.scroll_offset.saturating_sub(1);
                 Action::None
             }
             KeyCode::Down | KeyCode::Char('j') => {
-                if self.scroll_offset + 1 < self.messages.len() {
+                if self.scroll_offset + 1 < count {
                     self.scroll_offset += 1;
                 }
                 Action::None
             }
             KeyCode::Home | KeyCode::Char('g') => {
+                self.follow = false;
                 self.scroll_offset = 0;
                 Action::None
             }
             KeyCode::End | KeyCode::Char('G') => {
-                self.scroll_offset = self.messages.len().saturating_sub(1);
+                self.follow = true;
                 Action::None
             }
+            KeyCode::Char('f') => {
+                self.follow = !self.follow;
+                Action::None
+            }
+            KeyCode::Char('l') => {
+                self.min_level = self.min_level.next();
+                self.follow = true;
+                Action::None
+            }
+            KeyCode::Char('/') => {
+                self.searching = true;
+                Action::None
+            }
+            KeyCode::Char('s') => Action::SaveLog(self.saved_text()),
             _ => Action::None,
         }
     }
@@ -123,8 +289,16 @@ impl LogPanel {
         let area = self.popup.centered_area(full);
         frame.render_widget(Clear, area);
 
+        let shown = self.shown();
+        let follow = if self.follow { " | follow" } else { "" };
         let block = Block::default()
-            .title(format!(" Logs ({}) ", self.messages.len()))
+            .title(format!(
+                " Logs ({}/{}) | {}+{} ",
+                shown.len(),
+                self.messages.len(),
+                self.min_level.label().to_lowercase(),
+                follow
+            ))
             .borders(Borders::ALL)
             .border_style(self.theme.popup_border)
             .title_style(self.theme.popup_title);
@@ -132,38 +306,125 @@ impl LogPanel {
         let inner = block.inner(area);
         frame.render_widget(block, area);
 
-        // Layout: messages | hints (1)
-        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+        // Layout: messages | search (1) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
 
         // Messages
         let visible_height = layout[0].height as usize;
-        let start = self
-            .scroll_offset
-            .saturating_sub(visible_height.saturating_sub(1));
-        let end = (start + visible_height).min(self.messages.len());
+        let bottom = if self.follow {
+            shown.len().saturating_sub(1)
+        } else {
+            self.scroll_offset.min(shown.len().saturating_sub(1))
+        };
+        let start = bottom.saturating_sub(visible_height.saturating_sub(1));
+        let end = (start + visible_height).min(shown.len());
 
-        let lines: Vec<Line> = self.messages[start..end]
+        let lines: Vec<Line> = shown[start..end]
             .iter()
             .map(|entry| {
                 let (prefix, style) = match entry.level {
+                    LogLevel::Debug => ("[DBG]  ", self.theme.dimmed),
                     LogLevel::Info => ("[INFO] ", self.theme.normal),
+                    LogLevel::Warn => ("[WARN] ", self.theme.warning),
                     LogLevel::Error => ("[ERR]  ", self.theme.error),
-                    LogLevel::Debug => ("[DBG]  ", self.theme.dimmed),
                 };
-                Line::from(vec![
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{} ", entry.time.format("%H:%M:%S")),
+                        self.theme.dimmed,
+                    ),
                     Span::styled(prefix, style),
-                    Span::styled(&entry.message, style),
-                ])
+                ];
+                if let Some(target) = &entry.target {
+                    spans.push(Span::styled(format!("{}: ", target), self.theme.dimmed));
+                }
+                spans.push(Span::styled(entry.message.as_str(), style));
+                Line::from(spans)
             })
             .collect();
 
         frame.render_widget(Paragraph::new(lines), layout[0]);
 
+        // Search box
+        if self.searching || !self.search.is_empty() {
+            let cursor = if self.searching { "_" } else { "" };
+            let search = Line::from(vec![
+                Span::styled("/", self.theme.command_prompt),
+                Span::styled(format!("{}{}", self.search, cursor), self.theme.normal),
+            ]);
+            frame.render_widget(Paragraph::new(search), layout[1]);
+        }
+
         // Hints
         let hints = Line::from(Span::styled(
-            "\u{2191}/\u{2193}:scroll  g/G:top/bottom  q:close",
+            "\u{2191}/\u{2193}:scroll  g/G:top/bottom  l:level  /:search  f:follow  s:save  q:close",
             self.theme.dimmed,
         ));
-        frame.render_widget(Paragraph::new(hints), layout[1]);
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_level_filter_and_search() {
+        let mut panel = LogPanel::new(Theme::load("dark"));
+        panel.push_debug("pool opened".to_string());
+        panel.push_info("Connected to ldap.example.com".to_string());
+        panel.push_captured(CapturedLog {
+            time: Local::now(),
+            level: tracing::Level::WARN,
+            target: "loom_core::connection".to_string(),
+            message: "paged search fell back".to_string(),
+        });
+        panel.push_error("Search failed".to_string());
+        // Debug lines are hidden until the level is lowered
+        assert_eq!(panel.shown().len(), 3);
+        panel.handle_key_event(key(KeyCode::Char('l')));
+        assert_eq!(panel.shown().len(), 2);
+        for _ in 0..2 {
+            panel.handle_key_event(key(KeyCode::Char('l')));
+        }
+        assert_eq!(panel.min_level, LogLevel::Debug);
+        assert_eq!(panel.shown().len(), 4);
+
+        // Search matches messages and the source module
+        panel.handle_key_event(key(KeyCode::Char('/')));
+        for c in "CONNECT".chars() {
+            panel.handle_key_event(key(KeyCode::Char(c)));
+        }
+        panel.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(panel.shown().len(), 2);
+
+        let Action::SaveLog(text) = panel.handle_key_event(key(KeyCode::Char('s'))) else {
+            panic!("expected a save action");
+        };
+        assert_eq!(text.lines().count(), 2);
+        assert!(text.contains("WARN  loom_core::connection: paged search fell back"));
+    }
+
+    #[test]
+    fn test_scrolling_up_stops_following() {
+        let mut panel = LogPanel::new(Theme::load("dark"));
+        for i in 0..10 {
+            panel.push_info(format!("line {}", i));
+        }
+        panel.handle_key_event(key(KeyCode::Up));
+        assert!(!panel.follow);
+        assert_eq!(panel.scroll_offset, 8);
+        panel.handle_key_event(key(KeyCode::Char('G')));
+        assert!(panel.follow);
     }
 }
@@ -0,0 +1,184 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::components::profile_export_dialog::expand_tilde;
+use crate::theme::Theme;
+
+/// Which field is currently active.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    FilePath,
+    MemberAttr,
+}
+
+/// Dialog for importing group memberships from a two-column CSV (group, member).
+pub struct MembershipImportDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    active_field: Field,
+    file_path: String,
+    member_attr: String,
+}
+
+impl MembershipImportDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Import Group Memberships", theme.clone()).with_size(60, 35),
+            theme,
+            active_field: Field::FilePath,
+            file_path: String::new(),
+            member_attr: String::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self) {
+        self.file_path = "memberships.csv".to_string();
+        self.member_attr = "member".to_string();
+        self.active_field = Field::FilePath;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::BackTab => {
+                self.active_field = match self.active_field {
+                    Field::FilePath => Field::MemberAttr,
+                    Field::MemberAttr => Field::FilePath,
+                };
+                Action::None
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::Backspace => {
+                self.active_buffer_mut().pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.active_buffer_mut().push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn submit(&mut self) -> Action {
+        if self.file_path.trim().is_empty() {
+            return Action::ErrorMessage("File path is required".to_string());
+        }
+        if self.member_attr.trim().is_empty() {
+            return Action::ErrorMessage("Member attribute is required".to_string());
+        }
+        let path = expand_tilde(self.file_path.trim());
+        let member_attr = self.member_attr.trim().to_string();
+        self.hide();
+        Action::MembershipImportExecute { path, member_attr }
+    }
+
+    fn active_buffer_mut(&mut self) -> &mut String {
+        match self.active_field {
+            Field::FilePath => &mut self.file_path,
+            Field::MemberAttr => &mut self.member_attr,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Import Group Memberships ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Info
+            Constraint::Length(2), // File path
+            Constraint::Length(2), // Member attribute
+            Constraint::Min(1),    // Hints
+        ])
+        .split(inner);
+
+        let info = Paragraph::new(Line::from(Span::styled(
+            "CSV columns: group, member (DN or name)",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(info, layout[0]);
+
+        self.render_field(
+            frame,
+            layout[1],
+            "CSV file",
+            &self.file_path,
+            Field::FilePath,
+        );
+        self.render_field(
+            frame,
+            layout[2],
+            "Member attribute",
+            &self.member_attr,
+            Field::MemberAttr,
+        );
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "Tab:switch field  Enter:import  Esc:cancel",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[3]);
+    }
+
+    fn render_field(&self, frame: &mut Frame, area: Rect, label: &str, value: &str, field: Field) {
+        let is_active = self.active_field == field;
+        let label_style = if is_active {
+            self.theme.header
+        } else {
+            self.theme.dimmed
+        };
+        let value_style = if is_active {
+            self.theme.normal
+        } else {
+            self.theme.dimmed
+        };
+
+        let lines = vec![
+            Line::from(Span::styled(format!("{}:", label), label_style)),
+            Line::from(vec![
+                Span::styled(value, value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt)
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
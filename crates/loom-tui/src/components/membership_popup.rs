@@ -0,0 +1,229 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::groups::{MemberRow, MembershipReport};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Cycles listed under the table before the rest are only counted.
+const MAX_CYCLES_SHOWN: usize = 3;
+
+/// A popup with the flattened nested membership of an entry: every group
+/// it is in (or every member of a group) with how it was reached, and any
+/// membership cycles found on the way.
+pub struct MembershipPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: Option<MembershipReport>,
+    selected: usize,
+}
+
+impl MembershipPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Nested Membership", theme.clone()).with_size(85, 70),
+            theme,
+            report: None,
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, report: MembershipReport) {
+        self.report = Some(report);
+        self.selected = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    fn rows(&self) -> &[MemberRow] {
+        self.report
+            .as_ref()
+            .map(|r| r.rows.as_slice())
+            .unwrap_or(&[])
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let len = self.rows().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Enter => match self.rows().get(self.selected) {
+                Some(row) => {
+                    let dn = row.dn.clone();
+                    self.hide();
+                    Action::TreeSelect(dn)
+                }
+                None => Action::None,
+            },
+            KeyCode::Tab => match &self.report {
+                Some(report) => Action::ShowNestedMembership {
+                    dn: report.dn.clone(),
+                    expansion: Some(report.expansion.other()),
+                },
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" {} {} ", report.expansion.label(), report.dn))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: table | summary and cycles | hints (1)
+        let shown = report.cycles.len().min(MAX_CYCLES_SHOWN);
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1 + shown as u16),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let header = Row::new(["Depth", "Type", "DN", "Via"]).style(self.theme.header);
+        let rows: Vec<Row> = report
+            .rows
+            .iter()
+            .map(|r| {
+                let depth = r
+                    .depth
+                    .map(|d| d.to_string())
+                    .unwrap_or_else(|| "nested".to_string());
+                let kind = if r.is_group { "group" } else { "entry" };
+                let style = if r.in_cycle {
+                    self.theme.warning
+                } else {
+                    self.theme.normal
+                };
+                Row::new(vec![
+                    Cell::from(depth),
+                    Cell::from(kind),
+                    Cell::from(r.dn.clone()),
+                    Cell::from(r.via.clone().unwrap_or_default()),
+                ])
+                .style(style)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(6),
+                Constraint::Length(6),
+                Constraint::Percentage(55),
+                Constraint::Percentage(45),
+            ],
+        )
+        .header(header)
+        .highlight_style(self.theme.selected);
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, layout[0], &mut state);
+
+        let method = if report.server_resolved {
+            "resolved by the server"
+        } else {
+            "expanded from the groups under the base DN"
+        };
+        let mut lines = vec![Line::from(Span::styled(
+            format!(
+                "{} entries, {} direct; {} cycle(s); {}",
+                report.rows.len(),
+                report.direct_count(),
+                report.cycles.len(),
+                method
+            ),
+            self.theme.dimmed,
+        ))];
+        for cycle in report.cycles.iter().take(MAX_CYCLES_SHOWN) {
+            lines.push(Line::from(Span::styled(
+                format!("Cycle: {}", cycle.join(" \u{2192} ")),
+                self.theme.warning,
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), layout[1]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  Enter:go to entry  Tab:groups/members  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::groups::{expand, Expansion};
+
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    #[test]
+    fn test_select_and_switch_expansion() {
+        let groups = vec![
+            ("cn=a".to_string(), vec!["cn=u".to_string()]),
+            ("cn=b".to_string(), vec!["cn=a".to_string()]),
+        ];
+        let mut popup = MembershipPopup::new(Theme::default());
+        popup.show(expand(&groups, "cn=u", Expansion::Groups));
+
+        popup.handle_key_event(key(KeyCode::Down));
+        popup.handle_key_event(key(KeyCode::Down));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Tab)),
+            Action::ShowNestedMembership {
+                ref dn,
+                expansion: Some(Expansion::Members),
+            } if dn == "cn=u"
+        ));
+        assert!(matches!(
+            popup.handle_key_event(key(KeyCode::Enter)),
+            Action::TreeSelect(ref dn) if dn == "cn=b"
+        ));
+        assert!(!popup.visible);
+    }
+}
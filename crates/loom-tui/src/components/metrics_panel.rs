@@ -0,0 +1,307 @@
+use std::time::{Duration, Instant};
+
+use chrono::{DateTime, Local};
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::monitor::{rate, ServerMetrics};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::{Action, ConnectionId};
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// A toggleable view of the active tab's server activity: connections,
+/// operation rates and replication state, refreshed on an interval.
+pub struct MetricsPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    /// The tab the samples below belong to.
+    conn_id: Option<ConnectionId>,
+    /// The latest sample and the one before it, for rates.
+    current: Option<Sample>,
+    previous: Option<Sample>,
+    error: Option<String>,
+    requested_at: Option<Instant>,
+    loading: bool,
+}
+
+struct Sample {
+    at: Instant,
+    time: DateTime<Local>,
+    metrics: ServerMetrics,
+}
+
+impl MetricsPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Server Metrics", theme.clone()).with_size(70, 70),
+            theme,
+            conn_id: None,
+            current: None,
+            previous: None,
+            error: None,
+            requested_at: None,
+            loading: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.popup.show();
+        } else {
+            self.popup.hide();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Whether `conn_id` should be read now: it is a different tab from
+    /// the one shown, or `interval` has passed since the last request.
+    pub fn refresh_due(&self, conn_id: ConnectionId, interval: Duration) -> bool {
+        if self.conn_id != Some(conn_id) {
+            return true;
+        }
+        !self.loading && !self.requested_at.is_some_and(|at| at.elapsed() < interval)
+    }
+
+    /// Note a request for `conn_id`'s metrics, dropping the samples of any
+    /// other tab.
+    pub fn start_refresh(&mut self, conn_id: ConnectionId) {
+        if self.conn_id != Some(conn_id) {
+            self.conn_id = Some(conn_id);
+            self.current = None;
+            self.previous = None;
+            self.error = None;
+        }
+        self.requested_at = Some(Instant::now());
+        self.loading = true;
+    }
+
+    pub fn set_metrics(&mut self, conn_id: ConnectionId, metrics: ServerMetrics) {
+        if self.conn_id != Some(conn_id) {
+            return;
+        }
+        self.previous = self.current.take();
+        self.current = Some(Sample {
+            at: Instant::now(),
+            time: Local::now(),
+            metrics,
+        });
+        self.error = None;
+        self.loading = false;
+    }
+
+    pub fn set_error(&mut self, conn_id: ConnectionId, error: String) {
+        if self.conn_id != Some(conn_id) {
+            return;
+        }
+        self.error = Some(error);
+        self.loading = false;
+    }
+
+    /// Per-second change of a counter between the last two samples.
+    fn rate_of(&self, counter: impl Fn(&ServerMetrics) -> Option<u64>) -> Option<f64> {
+        let (previous, current) = (self.previous.as_ref()?, self.current.as_ref()?);
+        rate(
+            counter(&previous.metrics),
+            counter(&current.metrics),
+            current.at.duration_since(previous.at),
+        )
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('r') => Action::RefreshMetrics,
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect, interval: Duration) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let title = match &self.current {
+            Some(sample) => format!(
+                " Server Metrics ({}, {}) ",
+                sample.metrics.source,
+                sample.time.format("%H:%M:%S")
+            ),
+            None => " Server Metrics ".to_string(),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: figures | hints (1)
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let mut lines = Vec::new();
+        if let Some(error) = &self.error {
+            lines.push(Line::from(Span::styled(error.clone(), self.theme.error)));
+        }
+        match &self.current {
+            Some(sample) => lines.extend(self.metric_lines(&sample.metrics)),
+            None if self.error.is_none() => {
+                lines.push(Line::from(Span::styled(
+                    "Reading server metrics...",
+                    self.theme.dimmed,
+                )));
+            }
+            None => {}
+        }
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let hints = Line::from(Span::styled(
+            format!("r:refresh (every {}s)  q:close", interval.as_secs()),
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+
+    fn metric_lines(&self, metrics: &ServerMetrics) -> Vec<Line<'static>> {
+        let mut lines = Vec::new();
+
+        let mut connections = Vec::new();
+        if let Some(current) = metrics.current_connections {
+            connections.push(self.row("Open", current.to_string(), None));
+        }
+        if let Some(total) = metrics.total_connections {
+            let opened = self.rate_of(|m| m.total_connections);
+            connections.push(self.row("Total", total.to_string(), opened));
+        }
+        self.section(&mut lines, "Connections", connections);
+
+        let mut operations = Vec::new();
+        if let Some(completed) = metrics.operations_completed {
+            let ops = self.rate_of(|m| m.operations_completed);
+            operations.push(self.row("Completed", completed.to_string(), ops));
+        }
+        for (name, count) in &metrics.operations {
+            let ops = self.rate_of(|m| {
+                m.operations
+                    .iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, count)| *count)
+            });
+            operations.push(self.row(name, count.to_string(), ops));
+        }
+        if let Some(changes) = metrics.changes {
+            let commits = self.rate_of(|m| m.changes);
+            operations.push(self.row("Changes", changes.to_string(), commits));
+        }
+        self.section(&mut lines, "Operations", operations);
+
+        let replication = metrics
+            .replication
+            .iter()
+            .map(|(name, value)| self.row(name, value.clone(), None))
+            .collect();
+        self.section(&mut lines, "Replication", replication);
+
+        let info = metrics
+            .info
+            .iter()
+            .map(|(name, value)| self.row(name, value.clone(), None))
+            .collect();
+        self.section(&mut lines, "Server", info);
+        lines
+    }
+
+    fn section(&self, lines: &mut Vec<Line<'static>>, title: &str, rows: Vec<Line<'static>>) {
+        if rows.is_empty() {
+            return;
+        }
+        if !lines.is_empty() {
+            lines.push(Line::default());
+        }
+        lines.push(Line::from(Span::styled(
+            title.to_string(),
+            self.theme.header,
+        )));
+        lines.extend(rows);
+    }
+
+    fn row(&self, name: &str, value: String, per_sec: Option<f64>) -> Line<'static> {
+        let mut spans = vec![
+            Span::styled(format!("  {:<22}", name), self.theme.dimmed),
+            Span::styled(format!("{:>14}", value), self.theme.normal),
+        ];
+        if let Some(per_sec) = per_sec {
+            spans.push(Span::styled(
+                format!("  {:>9.1}/s", per_sec),
+                self.theme.success,
+            ));
+        }
+        Line::from(spans)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metrics(completed: u64, searches: u64) -> ServerMetrics {
+        ServerMetrics {
+            source: "cn=Monitor".to_string(),
+            operations_completed: Some(completed),
+            operations: vec![("Search".to_string(), searches)],
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_samples_and_rates() {
+        let mut panel = MetricsPanel::new(Theme::default());
+        let interval = Duration::from_secs(5);
+        assert!(panel.refresh_due(1, interval));
+        panel.start_refresh(1);
+        assert!(!panel.refresh_due(1, interval));
+        panel.set_metrics(1, metrics(100, 40));
+        assert!(panel.rate_of(|m| m.operations_completed).is_none());
+
+        panel.start_refresh(1);
+        panel.set_metrics(1, metrics(150, 60));
+        // Place the first sample two seconds before the second
+        let current = panel.current.as_ref().unwrap().at;
+        panel.previous.as_mut().unwrap().at = current - Duration::from_secs(2);
+        assert_eq!(panel.rate_of(|m| m.operations_completed), Some(25.0));
+        assert_eq!(
+            panel.rate_of(|m| m.operations.first().map(|(_, c)| *c)),
+            Some(10.0)
+        );
+
+        // Results for a tab no longer shown are ignored, and switching tabs
+        // starts over
+        panel.set_error(2, "unavailable".to_string());
+        assert!(panel.error.is_none());
+        assert!(panel.refresh_due(2, interval));
+        panel.start_refresh(2);
+        assert!(panel.current.is_none() && panel.previous.is_none());
+    }
+}
@@ -1,10 +1,17 @@
 pub mod about_popup;
+pub mod account_reports;
 pub mod attribute_editor;
 pub mod attribute_picker;
+pub mod attribute_stats_panel;
+pub mod batch_edit_dialog;
 pub mod bulk_update_dialog;
 pub mod cert_trust_dialog;
+pub mod changes_panel;
+pub mod command_line;
+pub mod command_palette;
 pub mod command_panel;
 pub mod confirm_dialog;
+pub mod conflict_dialog;
 pub mod connect_dialog;
 pub mod connection_form;
 pub mod connections_tree;
@@ -12,17 +19,38 @@ pub mod context_menu;
 pub mod create_entry_dialog;
 pub mod credential_prompt;
 pub mod detail_panel;
+pub mod duplicates_panel;
 pub mod export_dialog;
+pub mod favorites_popup;
+pub mod goto_dn_dialog;
 pub mod help_popup;
+pub mod history_popup;
+pub mod import_dialog;
+pub mod jobs_panel;
 pub mod layout_bar;
+pub mod lock_screen;
 pub mod log_panel;
+pub mod membership_import_dialog;
+pub mod membership_popup;
+pub mod metrics_panel;
+pub mod move_profile_dialog;
 pub mod new_connection_dialog;
+pub mod orphans_panel;
+pub mod password_policy_popup;
 pub mod popup;
 pub mod profile_export_dialog;
 pub mod profile_import_dialog;
+pub mod quick_open;
+pub mod recent_popup;
+pub mod rename_user_dialog;
+pub mod replication_panel;
+pub mod report_dialog;
 pub mod schema_viewer;
 pub mod search_dialog;
 pub mod status_bar;
 pub mod tab_bar;
+pub mod trace_panel;
 pub mod tree_panel;
+pub mod tree_view;
 pub mod vault_password_dialog;
+pub mod workspace_popup;
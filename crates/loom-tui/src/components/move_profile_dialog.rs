@@ -0,0 +1,202 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::config::normalize_folder_path;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+
+/// Dialog for moving a profile to another folder. Type a path (new
+/// folders are created implicitly) or pick an existing folder from the list.
+pub struct MoveProfileDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    profile_idx: usize,
+    profile_name: String,
+    /// Known folder paths, sorted.
+    folders: Vec<String>,
+    input: String,
+    /// Highlighted entry in the filtered folder list.
+    cursor: Option<usize>,
+    fuzzy: FuzzyFilter,
+    /// Indexes into `folders` matching the typed text, best first.
+    matches: Vec<usize>,
+}
+
+impl MoveProfileDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Move Profile", theme.clone()).with_size(50, 50),
+            theme,
+            profile_idx: 0,
+            profile_name: String::new(),
+            folders: Vec::new(),
+            input: String::new(),
+            cursor: None,
+            fuzzy: FuzzyFilter::new(),
+            matches: Vec::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(
+        &mut self,
+        profile_idx: usize,
+        profile_name: &str,
+        current_folder: Option<&str>,
+        folders: Vec<String>,
+    ) {
+        self.profile_idx = profile_idx;
+        self.profile_name = profile_name.to_string();
+        self.folders = folders;
+        self.input = current_folder.unwrap_or_default().to_string();
+        self.cursor = None;
+        self.refilter();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Re-rank the folder list against the typed text.
+    fn refilter(&mut self) {
+        self.matches = self
+            .fuzzy
+            .filter(&self.input, &self.folders)
+            .into_iter()
+            .map(|m| m.index)
+            .collect();
+    }
+
+    /// Folders matching the typed text (kept as-is while picking from the list).
+    fn filtered(&self) -> Vec<&str> {
+        self.matches
+            .iter()
+            .filter_map(|&i| self.folders.get(i))
+            .map(|f| f.as_str())
+            .collect()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => {
+                let folder = normalize_folder_path(&self.input);
+                self.hide();
+                Action::ConnMgrMoveToFolder(self.profile_idx, folder)
+            }
+            KeyCode::Down => {
+                let filtered = self.filtered();
+                let next = match self.cursor {
+                    None => 0,
+                    Some(i) => (i + 1).min(filtered.len().saturating_sub(1)),
+                };
+                if let Some(path) = filtered.get(next) {
+                    self.input = path.to_string();
+                    self.cursor = Some(next);
+                }
+                Action::None
+            }
+            KeyCode::Up => {
+                if let Some(i) = self.cursor {
+                    let prev = i.saturating_sub(1);
+                    if let Some(path) = self.filtered().get(prev) {
+                        self.input = path.to_string();
+                        self.cursor = Some(prev);
+                    }
+                }
+                Action::None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.cursor = None;
+                self.refilter();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.cursor = None;
+                self.refilter();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Move: {} ", self.profile_name))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(2), // Input
+            Constraint::Min(1),    // Folder list
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let lines = vec![
+            Line::from(Span::styled(
+                "Folder path (empty = top level):",
+                self.theme.header,
+            )),
+            Line::from(vec![
+                Span::styled(&self.input, self.theme.normal),
+                Span::styled("_", self.theme.command_prompt),
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let list: Vec<Line> = self
+            .filtered()
+            .into_iter()
+            .enumerate()
+            .map(|(i, path)| {
+                if Some(i) == self.cursor {
+                    Line::from(Span::styled(
+                        format!("> {}", path),
+                        self.theme.selected.add_modifier(Modifier::BOLD),
+                    ))
+                } else {
+                    Line::from(Span::styled(format!("  {}", path), self.theme.normal))
+                }
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(list), layout[1]);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "\u{2191}/\u{2193}:pick folder  Enter:move  Esc:cancel",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[2]);
+    }
+}
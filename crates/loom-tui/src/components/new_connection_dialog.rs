@@ -109,6 +109,11 @@ impl NewConnectionDialog {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self) {
         self.name.clear();
         self.host.clear();
@@ -205,11 +210,26 @@ impl NewConnectionDialog {
             } else {
                 Some(self.base_dn.trim().to_string())
             },
+            start_dn: None,
+            default_filter: None,
+            root_dse_attributes: Vec::new(),
+            favorites: Vec::new(),
+            saved_searches: Vec::new(),
+            edit_policy: None,
             credential_method: CredentialMethod::Prompt,
             password_command: None,
             page_size: 500,
             timeout_secs: 30,
+            pool_size: 3,
             relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            allow_commands: false,
+            dns_srv: false,
+            shuffle_hosts: false,
+            safety: None,
             folder: None,
             read_only: false,
             offline: false,
@@ -0,0 +1,283 @@
+use std::collections::BTreeSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::orphans::{OrphanReport, Reference};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// A panel listing DN values under a subtree that point at missing
+/// entries, where values can be marked and removed.
+pub struct OrphansPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: Option<OrphanReport>,
+    selected: usize,
+    marked: BTreeSet<usize>,
+}
+
+impl OrphansPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Orphaned References", theme.clone()).with_size(85, 70),
+            theme,
+            report: None,
+            selected: 0,
+            marked: BTreeSet::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, report: OrphanReport) {
+        self.report = Some(report);
+        self.selected = 0;
+        self.marked.clear();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn report(&self) -> Option<&OrphanReport> {
+        self.report.as_ref()
+    }
+
+    fn orphans(&self) -> &[Reference] {
+        self.report
+            .as_ref()
+            .map(|r| r.orphans.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Ask to remove the marked values. The panel closes so the
+    /// confirmation shows; a rescan after the cleanup brings it back.
+    fn cleanup(&mut self) -> Action {
+        let refs: Vec<Reference> = self
+            .marked
+            .iter()
+            .filter_map(|&i| self.orphans().get(i).cloned())
+            .collect();
+        if refs.is_empty() {
+            return Action::ErrorMessage("Mark the values to remove with Space".to_string());
+        }
+        let entries: BTreeSet<&str> = refs.iter().map(|r| r.dn.as_str()).collect();
+        let msg = format!(
+            "Remove {} dangling value(s) from {} entries?",
+            refs.len(),
+            entries.len()
+        );
+        self.hide();
+        Action::ShowConfirm(msg, Box::new(Action::CleanupOrphans(refs)))
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let len = self.orphans().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char(' ') if len > 0 => {
+                if !self.marked.remove(&self.selected) {
+                    self.marked.insert(self.selected);
+                }
+                if self.selected + 1 < len {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('a') => {
+                if self.marked.len() == len {
+                    self.marked.clear();
+                } else {
+                    self.marked = (0..len).collect();
+                }
+                Action::None
+            }
+            KeyCode::Enter => match self.orphans().get(self.selected) {
+                Some(orphan) => {
+                    let dn = orphan.dn.clone();
+                    self.hide();
+                    Action::TreeSelect(dn)
+                }
+                None => Action::None,
+            },
+            KeyCode::Char('d') => self.cleanup(),
+            KeyCode::Char('s') if len > 0 => Action::SaveOrphanReport,
+            KeyCode::Char('r') => match &self.report {
+                Some(report) => Action::ShowOrphanScan(report.base_dn.clone()),
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Orphaned References under {} ", report.base_dn))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: table | summary (1) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        let header = Row::new(["", "Entry", "Attribute", "Missing DN"]).style(self.theme.header);
+        let rows: Vec<Row> = report
+            .orphans
+            .iter()
+            .enumerate()
+            .map(|(i, r)| {
+                let mark = if self.marked.contains(&i) {
+                    "[x]"
+                } else {
+                    "[ ]"
+                };
+                Row::new(vec![
+                    Cell::from(mark),
+                    Cell::from(r.dn.clone()),
+                    Cell::from(r.attribute.clone()),
+                    Cell::from(r.target().to_string()),
+                ])
+                .style(self.theme.normal)
+            })
+            .collect();
+        let table = Table::new(
+            rows,
+            [
+                Constraint::Length(3),
+                Constraint::Percentage(45),
+                Constraint::Length(14),
+                Constraint::Percentage(45),
+            ],
+        )
+        .header(header)
+        .highlight_style(self.theme.selected);
+        let mut state = TableState::default().with_selected(Some(self.selected));
+        frame.render_stateful_widget(table, layout[0], &mut state);
+
+        let summary = format!(
+            "{} dangling value(s) in {} entries scanned, {} referenced DNs checked; {} marked",
+            report.orphans.len(),
+            report.entries_scanned,
+            report.targets_checked,
+            self.marked.len()
+        );
+        let style = if report.orphans.is_empty() {
+            self.theme.success
+        } else {
+            self.theme.warning
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(summary, style))),
+            layout[1],
+        );
+
+        let hints = Line::from(Span::styled(
+            "Space:mark  a:mark all  d:remove marked  s:save report  Enter:go to entry  r:rescan  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::from(code)
+    }
+
+    fn orphan(dn: &str, value: &str) -> Reference {
+        Reference {
+            dn: dn.to_string(),
+            attribute: "member".to_string(),
+            value: value.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_mark_and_clean_up() {
+        let mut panel = OrphansPanel::new(Theme::default());
+        panel.show(OrphanReport {
+            base_dn: "dc=x".to_string(),
+            entries_scanned: 2,
+            targets_checked: 2,
+            orphans: vec![
+                orphan("cn=a,dc=x", "uid=gone,dc=x"),
+                orphan("cn=a,dc=x", "uid=left,dc=x"),
+                orphan("cn=b,dc=x", "uid=gone,dc=x"),
+            ],
+        });
+
+        assert!(matches!(
+            panel.handle_key_event(key(KeyCode::Char('d'))),
+            Action::ErrorMessage(_)
+        ));
+
+        // `a` marks everything, then nothing
+        panel.handle_key_event(key(KeyCode::Char('a')));
+        assert_eq!(panel.marked.len(), 3);
+        panel.handle_key_event(key(KeyCode::Char('a')));
+        assert!(panel.marked.is_empty());
+
+        // Space marks and moves down
+        panel.handle_key_event(key(KeyCode::Char(' ')));
+        panel.handle_key_event(key(KeyCode::Down));
+        panel.handle_key_event(key(KeyCode::Char(' ')));
+        let Action::ShowConfirm(msg, action) = panel.handle_key_event(key(KeyCode::Char('d')))
+        else {
+            panic!("expected a confirmation");
+        };
+        assert_eq!(msg, "Remove 2 dangling value(s) from 2 entries?");
+        let Action::CleanupOrphans(refs) = *action else {
+            panic!("expected a cleanup");
+        };
+        assert_eq!(refs[0].value, "uid=gone,dc=x");
+        assert_eq!(refs[1].dn, "cn=b,dc=x");
+        assert!(!panel.visible);
+    }
+}
@@ -0,0 +1,232 @@
+use chrono::{DateTime, Local, Utc};
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::password_policy::{Expiry, Lockout, PasswordPolicyReport};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Style;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Passwords expiring within this many days are shown as a warning.
+const EXPIRY_WARNING_DAYS: i64 = 14;
+
+/// How serious a line of the report is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Severity {
+    Ok,
+    Warning,
+    Problem,
+}
+
+/// A popup showing the password policy in effect for a user, when their
+/// password expires and whether they are locked out.
+pub struct PasswordPolicyPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    report: Option<PasswordPolicyReport>,
+}
+
+impl PasswordPolicyPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Password Policy", theme.clone()).with_size(70, 60),
+            theme,
+            report: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, report: PasswordPolicyReport) {
+        self.report = Some(report);
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Char('r') => match &self.report {
+                Some(report) => Action::ShowPasswordPolicy(report.dn.clone()),
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn style(&self, severity: Severity) -> Style {
+        match severity {
+            Severity::Ok => self.theme.success,
+            Severity::Warning => self.theme.warning,
+            Severity::Problem => self.theme.error,
+        }
+    }
+
+    fn row(&self, label: &str, value: String, style: Style) -> Line<'static> {
+        Line::from(vec![
+            Span::styled(format!("  {:<26}", label), self.theme.dimmed),
+            Span::styled(value, style),
+        ])
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+        let Some(report) = &self.report else {
+            return;
+        };
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Password Policy ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: report | hints (1)
+        let layout = Layout::vertical([Constraint::Min(1), Constraint::Length(1)]).split(inner);
+
+        let now = Utc::now();
+        let mut lines = vec![
+            Line::from(Span::styled(report.dn.clone(), self.theme.header)),
+            Line::default(),
+        ];
+        let policy = match &report.policy_dn {
+            Some(dn) => format!("{}: {}", report.source, dn),
+            None => report.source.clone(),
+        };
+        lines.push(self.row("Policy", policy, self.theme.normal));
+        lines.push(
+            self.row(
+                "Password last set",
+                report
+                    .password_changed
+                    .map(local_time)
+                    .unwrap_or_else(|| "unknown".to_string()),
+                self.theme.normal,
+            ),
+        );
+        let (expiry, severity) = expiry_text(&report.expiry, now);
+        lines.push(self.row("Password expires", expiry, self.style(severity)));
+        let (lockout, severity) = lockout_text(&report.lockout);
+        lines.push(self.row("Lockout", lockout, self.style(severity)));
+        if let Some(failed) = report.failed_logins {
+            lines.push(self.row("Failed logins", failed.to_string(), self.theme.normal));
+        }
+        if let Some(history) = report.history_length {
+            lines.push(self.row(
+                "Password history",
+                format!("{} remembered", history),
+                self.theme.normal,
+            ));
+        }
+
+        if !report.settings.is_empty() {
+            lines.push(Line::default());
+            lines.push(Line::from(Span::styled("Settings", self.theme.header)));
+            for (label, value) in &report.settings {
+                lines.push(self.row(label, value.clone(), self.theme.normal));
+            }
+        }
+        frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[0]);
+
+        let hints = Line::from(Span::styled("r:check again  q:close", self.theme.dimmed));
+        frame.render_widget(Paragraph::new(hints), layout[1]);
+    }
+}
+
+fn local_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M:%S")
+        .to_string()
+}
+
+fn expiry_text(expiry: &Expiry, now: DateTime<Utc>) -> (String, Severity) {
+    match expiry {
+        Expiry::Never => ("never".to_string(), Severity::Ok),
+        Expiry::Unknown => ("unknown".to_string(), Severity::Warning),
+        Expiry::MustChange => (
+            "must be changed at next logon".to_string(),
+            Severity::Warning,
+        ),
+        Expiry::At(at) if *at <= now => (
+            format!(
+                "{} (expired {} days ago)",
+                local_time(*at),
+                (now - *at).num_days()
+            ),
+            Severity::Problem,
+        ),
+        Expiry::At(at) => {
+            let days = (*at - now).num_days();
+            let severity = if days < EXPIRY_WARNING_DAYS {
+                Severity::Warning
+            } else {
+                Severity::Ok
+            };
+            (format!("{} (in {} days)", local_time(*at), days), severity)
+        }
+    }
+}
+
+fn lockout_text(lockout: &Lockout) -> (String, Severity) {
+    match lockout {
+        Lockout::Unlocked => ("not locked".to_string(), Severity::Ok),
+        Lockout::Until(at) => (
+            format!("locked until {}", local_time(*at)),
+            Severity::Problem,
+        ),
+        Lockout::UntilReset => (
+            "locked until an administrator unlocks it".to_string(),
+            Severity::Problem,
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_and_lockout_text() {
+        let now = DateTime::parse_from_rfc3339("2024-03-15T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let days = |n: i64| now + chrono::Duration::days(n);
+
+        let (text, severity) = expiry_text(&Expiry::At(days(30)), now);
+        assert!(text.ends_with("(in 30 days)"));
+        assert_eq!(severity, Severity::Ok);
+        assert_eq!(expiry_text(&Expiry::At(days(3)), now).1, Severity::Warning);
+        let (text, severity) = expiry_text(&Expiry::At(days(-2)), now);
+        assert!(text.ends_with("(expired 2 days ago)"));
+        assert_eq!(severity, Severity::Problem);
+        assert_eq!(expiry_text(&Expiry::Never, now).1, Severity::Ok);
+
+        assert_eq!(lockout_text(&Lockout::Unlocked).1, Severity::Ok);
+        assert_eq!(lockout_text(&Lockout::UntilReset).1, Severity::Problem);
+    }
+}
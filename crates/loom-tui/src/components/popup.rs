@@ -24,6 +24,10 @@ impl Popup {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn with_size(mut self, width_percent: u16, height_percent: u16) -> Self {
         self.width_percent = width_percent;
         self.height_percent = height_percent;
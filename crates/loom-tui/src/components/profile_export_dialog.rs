@@ -44,6 +44,11 @@ impl ProfileExportDialog {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     /// Show the dialog populated with the given profiles.
     pub fn show(&mut self, profiles: &[ConnectionProfile]) {
         self.profiles = profiles.iter().map(|p| (p.name.clone(), true)).collect();
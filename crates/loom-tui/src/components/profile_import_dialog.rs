@@ -8,7 +8,8 @@ use ratatui::Frame;
 use crate::action::Action;
 use crate::components::popup::Popup;
 use crate::components::profile_export_dialog::expand_tilde;
-use crate::config::{AppConfig, ConnectionProfile};
+use crate::config::ConnectionProfile;
+use crate::profile_import::{self, ProfileSource};
 use crate::theme::Theme;
 
 /// Which phase the import dialog is in.
@@ -20,7 +21,8 @@ enum Phase {
     SelectProfiles,
 }
 
-/// Dialog for importing connection profiles from a TOML file.
+/// Dialog for importing connection profiles from a TOML file, an Apache
+/// Directory Studio `connections.xml` export, or an `ldap.conf`/`.ldaprc`.
 pub struct ProfileImportDialog {
     pub visible: bool,
     popup: Popup,
@@ -32,6 +34,8 @@ pub struct ProfileImportDialog {
     parsed_profiles: Vec<(ConnectionProfile, bool)>,
     /// Cursor position in profile list.
     cursor: usize,
+    /// Format the parsed profiles were read from.
+    source: ProfileSource,
 }
 
 impl ProfileImportDialog {
@@ -44,9 +48,15 @@ impl ProfileImportDialog {
             file_path: String::new(),
             parsed_profiles: Vec::new(),
             cursor: 0,
+            source: ProfileSource::Toml,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self) {
         self.phase = Phase::FilePath;
         self.file_path = "profiles.toml".to_string();
@@ -142,8 +152,9 @@ impl ProfileImportDialog {
             }
         };
 
-        match AppConfig::import_profiles(&content) {
-            Ok(profiles) => {
+        match profile_import::parse_profiles(&path, &content) {
+            Ok((source, profiles)) => {
+                self.source = source;
                 self.parsed_profiles = profiles.into_iter().map(|p| (p, true)).collect();
                 self.cursor = 0;
                 self.phase = Phase::SelectProfiles;
@@ -195,6 +206,7 @@ impl ProfileImportDialog {
     fn render_filepath(&self, frame: &mut Frame, area: Rect) {
         let layout = Layout::vertical([
             Constraint::Length(2), // File path
+            Constraint::Length(2), // Formats
             Constraint::Min(1),    // Hints
         ])
         .split(area);
@@ -208,11 +220,17 @@ impl ProfileImportDialog {
         ];
         frame.render_widget(Paragraph::new(lines), layout[0]);
 
+        let formats = Paragraph::new(Line::from(Span::styled(
+            "Accepts TOML, Directory Studio connections.xml, ldap.conf",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(formats, layout[1]);
+
         let hints = Paragraph::new(Line::from(Span::styled(
             "Enter:open file  Esc:cancel",
             self.theme.dimmed,
         )));
-        frame.render_widget(hints, layout[1]);
+        frame.render_widget(hints, layout[2]);
     }
 
     fn render_select(&self, frame: &mut Frame, area: Rect) {
@@ -224,7 +242,11 @@ impl ProfileImportDialog {
         .split(area);
 
         let mut lines = vec![Line::from(Span::styled(
-            format!("Found {} profile(s):", self.parsed_profiles.len()),
+            format!(
+                "Found {} profile(s) ({}):",
+                self.parsed_profiles.len(),
+                self.source.label()
+            ),
             self.theme.header,
         ))];
 
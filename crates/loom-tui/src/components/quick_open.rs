@@ -0,0 +1,336 @@
+use std::collections::HashSet;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use loom_core::entry::LdapEntry;
+
+use crate::action::Action;
+use crate::components::attribute_editor::build_dn_search_filter;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+use crate::widgets::fuzzy_input::FuzzyFilter;
+
+/// Matches listed at most, of the loaded entries.
+const MAX_MATCHES: usize = 100;
+
+/// Quick open: fuzzy-find an entry among those already loaded in the tab
+/// -- history, search results and tree nodes -- without asking the server.
+/// When nothing loaded matches, a limited server search is run instead.
+pub struct QuickOpenDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    input: String,
+    fuzzy: FuzzyFilter,
+    /// Loaded DNs, most relevant first.
+    candidates: Vec<String>,
+    matches: Vec<String>,
+    /// Whether `matches` came from the server search.
+    from_server: bool,
+    list_state: ListState,
+
+    // Live search debounce state (same scheme as Go to DN)
+    search_generation: u64,
+    search_dirty: bool,
+    searching: bool,
+}
+
+impl QuickOpenDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Quick Open", theme.clone()).with_size(75, 60),
+            theme,
+            input: String::new(),
+            fuzzy: FuzzyFilter::new(),
+            candidates: Vec::new(),
+            matches: Vec::new(),
+            from_server: false,
+            list_state: ListState::default(),
+            search_generation: 0,
+            search_dirty: false,
+            searching: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    /// Open with the tab's loaded DNs, most relevant first. Later
+    /// duplicates (ignoring case) are dropped.
+    pub fn show(&mut self, candidates: Vec<String>) {
+        let mut seen = HashSet::new();
+        self.candidates = candidates
+            .into_iter()
+            .filter(|dn| seen.insert(dn.to_lowercase()))
+            .collect();
+        self.input.clear();
+        self.searching = false;
+        self.input_changed();
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Replace the input with pasted text (first line only).
+    pub fn paste(&mut self, text: &str) {
+        self.input = text.lines().next().unwrap_or_default().trim().to_string();
+        self.input_changed();
+    }
+
+    /// Rank the loaded entries against the input; with no match, ask for a
+    /// server search on the next tick.
+    fn input_changed(&mut self) {
+        self.matches = self
+            .fuzzy
+            .filter(&self.input, &self.candidates)
+            .into_iter()
+            .take(MAX_MATCHES)
+            .map(|m| self.candidates[m.index].clone())
+            .collect();
+        self.from_server = false;
+        self.search_dirty = self.matches.is_empty() && self.input.trim().len() >= 2;
+        if !self.search_dirty {
+            // A search still running is for older input
+            self.searching = false;
+        }
+        self.select_first();
+    }
+
+    fn select_first(&mut self) {
+        let first = (!self.matches.is_empty()).then_some(0);
+        self.list_state.select(first);
+    }
+
+    /// Debounced fallback search, called on every tick while visible.
+    pub fn tick(&mut self, base_dn: &str) -> Action {
+        if !self.visible || !self.search_dirty {
+            return Action::None;
+        }
+        self.search_dirty = false;
+        self.search_generation += 1;
+        self.searching = true;
+        Action::DnSearchRequest {
+            generation: self.search_generation,
+            query: build_dn_search_filter(&self.input),
+            base_dn: base_dn.to_string(),
+        }
+    }
+
+    /// Receive the fallback search's results, those matching the input
+    /// best first. Stale generations are ignored.
+    pub fn receive_results(&mut self, generation: u64, entries: Vec<LdapEntry>) {
+        if generation != self.search_generation || !self.searching {
+            return;
+        }
+        self.searching = false;
+        let dns: Vec<String> = entries.into_iter().map(|e| e.dn).collect();
+        let mut order: Vec<usize> = self
+            .fuzzy
+            .filter(&self.input, &dns)
+            .into_iter()
+            .map(|m| m.index)
+            .collect();
+        // The server matched on attribute values too; keep those after
+        let ranked: HashSet<usize> = order.iter().copied().collect();
+        order.extend((0..dns.len()).filter(|i| !ranked.contains(i)));
+        self.matches = order.into_iter().map(|i| dns[i].clone()).collect();
+        self.from_server = true;
+        self.select_first();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Enter => {
+                let Some(dn) = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.matches.get(i))
+                    .cloned()
+                else {
+                    return Action::None;
+                };
+                self.hide();
+                Action::RevealEntry(dn)
+            }
+            KeyCode::Down | KeyCode::Tab => {
+                if let Some(i) = self.list_state.selected() {
+                    self.list_state
+                        .select(Some((i + 1).min(self.matches.len() - 1)));
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::BackTab => {
+                if let Some(i) = self.list_state.selected() {
+                    self.list_state.select(Some(i.saturating_sub(1)));
+                }
+                Action::None
+            }
+            KeyCode::Backspace => {
+                self.input.pop();
+                self.input_changed();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.input.push(c);
+                self.input_changed();
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Quick Open ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(1), // Input
+            Constraint::Length(1), // Spacer
+            Constraint::Min(1),    // Matches
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let input_line = Line::from(vec![
+            Span::styled("> ", self.theme.command_prompt),
+            Span::styled(self.input.as_str(), self.theme.normal),
+            Span::styled("_", self.theme.command_prompt),
+        ]);
+        frame.render_widget(Paragraph::new(input_line), layout[0]);
+
+        if self.matches.is_empty() {
+            let msg = if self.searching {
+                "Nothing loaded matches; searching the server..."
+            } else if self.input.trim().len() >= 2 {
+                "No entries found"
+            } else if self.candidates.is_empty() {
+                "No entries loaded yet; type a name to search the server"
+            } else {
+                "Type to filter"
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(msg, self.theme.dimmed))),
+                layout[2],
+            );
+        } else {
+            let items: Vec<ListItem> = self
+                .matches
+                .iter()
+                .map(|dn| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            format!("{:<24} ", loom_core::dn::rdn_display_name(dn)),
+                            self.theme.normal,
+                        ),
+                        Span::styled(dn.as_str(), self.theme.dimmed),
+                    ]))
+                })
+                .collect();
+            let list = List::new(items)
+                .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+                .highlight_symbol("> ");
+            frame.render_stateful_widget(list, layout[2], &mut self.list_state);
+        }
+
+        let source = if self.from_server {
+            format!("{} from the server", self.matches.len())
+        } else {
+            format!("{} of {} loaded", self.matches.len(), self.candidates.len())
+        };
+        let hints = Paragraph::new(Line::from(vec![
+            Span::styled(format!("{}  ", source), self.theme.header),
+            Span::styled(
+                "\u{2191}/\u{2193}:select  Enter:open  Esc:close",
+                self.theme.dimmed,
+            ),
+        ]));
+        frame.render_widget(hints, layout[3]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut QuickOpenDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_local_matches_then_server_fallback() {
+        let mut dialog = QuickOpenDialog::new(Theme::load("dark"));
+        dialog.show(vec![
+            "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            "ou=People,dc=example,dc=com".to_string(),
+            "CN=alice,ou=People,dc=example,dc=com".to_string(),
+        ]);
+        assert_eq!(dialog.candidates.len(), 2);
+
+        type_text(&mut dialog, "alice");
+        assert_eq!(dialog.matches, ["cn=Alice,ou=People,dc=example,dc=com"]);
+        // Found locally: the server isn't asked
+        assert!(matches!(dialog.tick("dc=example,dc=com"), Action::None));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::RevealEntry(dn) if dn == "cn=Alice,ou=People,dc=example,dc=com"
+        ));
+
+        dialog.show(Vec::new());
+        type_text(&mut dialog, "bob");
+        let Action::DnSearchRequest {
+            generation, query, ..
+        } = dialog.tick("dc=example,dc=com")
+        else {
+            panic!("expected a search");
+        };
+        assert!(query.contains("bob"), "{}", query);
+        dialog.receive_results(
+            generation,
+            vec![
+                LdapEntry::new("uid=rsmith,dc=example,dc=com".into(), Default::default()),
+                LdapEntry::new("cn=Bob,dc=example,dc=com".into(), Default::default()),
+            ],
+        );
+        assert!(dialog.from_server);
+        assert_eq!(
+            dialog.matches,
+            ["cn=Bob,dc=example,dc=com", "uid=rsmith,dc=example,dc=com"]
+        );
+    }
+}
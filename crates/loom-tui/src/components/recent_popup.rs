@@ -0,0 +1,139 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Popup listing the entries most recently viewed on the active connection,
+/// newest first. Enter reopens the highlighted entry.
+pub struct RecentPopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    connection: String,
+    dns: Vec<String>,
+    list_state: ListState,
+}
+
+impl RecentPopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Recent Entries", theme.clone()).with_size(70, 60),
+            theme,
+            connection: String::new(),
+            dns: Vec::new(),
+            list_state: ListState::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, connection: &str, dns: Vec<String>) {
+        self.connection = connection.to_string();
+        self.dns = dns;
+        // The newest entry is usually the one on screen; start on the one before it
+        let start = if self.dns.len() > 1 { 1 } else { 0 };
+        self.list_state.select(Some(start));
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                if i + 1 < self.dns.len() {
+                    self.list_state.select(Some(i + 1));
+                }
+                Action::None
+            }
+            KeyCode::Enter => {
+                let dn = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.dns.get(i))
+                    .cloned();
+                match dn {
+                    Some(dn) => {
+                        self.hide();
+                        Action::OpenRecentEntry(dn)
+                    }
+                    None => Action::None,
+                }
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(format!(" Recent: {} ", self.connection))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // List
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let items: Vec<ListItem> = self
+            .dns
+            .iter()
+            .map(|dn| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!("{:<24} ", loom_core::dn::rdn_display_name(dn)),
+                        self.theme.normal,
+                    ),
+                    Span::styled(dn.as_str(), self.theme.dimmed),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, layout[0], &mut self.list_state);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:navigate  Enter:open  Esc:close",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+}
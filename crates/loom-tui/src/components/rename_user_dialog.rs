@@ -0,0 +1,337 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use loom_core::entry::LdapEntry;
+use loom_core::rename::{retarget_upn, RenamePlan, RenameTarget};
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Which field is currently active.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Field {
+    Cn,
+    SamAccountName,
+    UserPrincipalName,
+    Mail,
+}
+
+const FIELDS: [Field; 4] = [
+    Field::Cn,
+    Field::SamAccountName,
+    Field::UserPrincipalName,
+    Field::Mail,
+];
+
+/// Guided rename of an Active Directory user: edit the names, review the
+/// staged changes, then apply them in order.
+pub struct RenameUserDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    entry: Option<LdapEntry>,
+    target: RenameTarget,
+    active_field: Field,
+    /// The UPN name part tracks sAMAccountName until the UPN is edited.
+    upn_follows_sam: bool,
+    /// Set once the changes have been staged for review.
+    plan: Option<RenamePlan>,
+}
+
+impl RenameUserDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Rename User", theme.clone()).with_size(70, 60),
+            theme,
+            entry: None,
+            target: RenameTarget::default(),
+            active_field: Field::Cn,
+            upn_follows_sam: false,
+            plan: None,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, entry: LdapEntry) {
+        self.target = RenameTarget::from_entry(&entry);
+        self.upn_follows_sam = self
+            .target
+            .user_principal_name
+            .split('@')
+            .next()
+            .is_some_and(|name| name.eq_ignore_ascii_case(&self.target.sam_account_name));
+        self.entry = Some(entry);
+        self.active_field = Field::Cn;
+        self.plan = None;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.entry = None;
+        self.plan = None;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.plan.is_some() {
+            return self.handle_preview_key(key);
+        }
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab | KeyCode::Down => {
+                self.cycle_field(1);
+                Action::None
+            }
+            KeyCode::BackTab | KeyCode::Up => {
+                self.cycle_field(FIELDS.len() - 1);
+                Action::None
+            }
+            KeyCode::Enter => self.stage(),
+            KeyCode::Backspace => {
+                self.edit(|value| {
+                    value.pop();
+                });
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                self.edit(|value| value.push(c));
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn handle_preview_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            // Back to editing
+            KeyCode::Esc | KeyCode::Backspace => {
+                self.plan = None;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let Some(plan) = self.plan.take() else {
+                    return Action::None;
+                };
+                self.hide();
+                Action::SafeRenameExecute(Box::new(plan))
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn cycle_field(&mut self, step: usize) {
+        let i = FIELDS
+            .iter()
+            .position(|f| *f == self.active_field)
+            .unwrap_or(0);
+        self.active_field = FIELDS[(i + step) % FIELDS.len()];
+    }
+
+    fn edit(&mut self, f: impl FnOnce(&mut String)) {
+        match self.active_field {
+            Field::Cn => f(&mut self.target.cn),
+            Field::SamAccountName => {
+                f(&mut self.target.sam_account_name);
+                if self.upn_follows_sam {
+                    self.target.user_principal_name = retarget_upn(
+                        &self.target.user_principal_name,
+                        &self.target.sam_account_name,
+                    );
+                }
+            }
+            Field::UserPrincipalName => {
+                f(&mut self.target.user_principal_name);
+                self.upn_follows_sam = false;
+            }
+            Field::Mail => f(&mut self.target.mail),
+        }
+    }
+
+    /// Build the plan from the edited names and switch to the preview.
+    fn stage(&mut self) -> Action {
+        let Some(entry) = &self.entry else {
+            return Action::None;
+        };
+        match RenamePlan::build(entry, &self.target) {
+            Ok(plan) if plan.steps.is_empty() => {
+                Action::ErrorMessage("Nothing to rename: all names are unchanged".to_string())
+            }
+            Ok(plan) => {
+                self.plan = Some(plan);
+                Action::None
+            }
+            Err(e) => Action::ErrorMessage(e),
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let title = if self.plan.is_some() {
+            " Rename User: Review "
+        } else {
+            " Rename User "
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(2), // DN
+            Constraint::Min(1),    // Fields or preview
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let dn = self.entry.as_ref().map_or("", |e| e.dn.as_str());
+        frame.render_widget(
+            Paragraph::new(Line::from(vec![
+                Span::styled("DN: ", self.theme.header),
+                Span::styled(dn, self.theme.normal),
+            ])),
+            layout[0],
+        );
+
+        let hints = if let Some(ref plan) = self.plan {
+            let lines: Vec<Line> = plan
+                .preview_lines()
+                .into_iter()
+                .map(|l| Line::from(Span::styled(l, self.theme.normal)))
+                .collect();
+            frame.render_widget(Paragraph::new(lines).wrap(Wrap { trim: false }), layout[1]);
+            "Enter:apply in this order  Esc:back to editing"
+        } else {
+            let rows = Layout::vertical([Constraint::Length(2); 4]).split(layout[1]);
+            self.render_field(frame, rows[0], "New cn", &self.target.cn, Field::Cn);
+            self.render_field(
+                frame,
+                rows[1],
+                "sAMAccountName",
+                &self.target.sam_account_name,
+                Field::SamAccountName,
+            );
+            self.render_field(
+                frame,
+                rows[2],
+                "userPrincipalName",
+                &self.target.user_principal_name,
+                Field::UserPrincipalName,
+            );
+            self.render_field(frame, rows[3], "mail", &self.target.mail, Field::Mail);
+            "Tab:switch field  Enter:review changes  Esc:cancel"
+        };
+
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hints, self.theme.dimmed))),
+            layout[2],
+        );
+    }
+
+    fn render_field(&self, frame: &mut Frame, area: Rect, label: &str, value: &str, field: Field) {
+        let is_active = self.active_field == field;
+        let label_style = if is_active {
+            self.theme.header
+        } else {
+            self.theme.dimmed
+        };
+        let value_style = if is_active {
+            self.theme.normal
+        } else {
+            self.theme.dimmed
+        };
+
+        let lines = vec![
+            Line::from(Span::styled(format!("{}:", label), label_style)),
+            Line::from(vec![
+                Span::styled(value, value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt)
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use std::collections::BTreeMap;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn type_text(dialog: &mut RenameUserDialog, text: &str) {
+        for c in text.chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+    }
+
+    #[test]
+    fn test_upn_follows_sam_then_stages_and_applies() {
+        let entry = LdapEntry::new(
+            "CN=John Doe,OU=Users,DC=corp,DC=com".to_string(),
+            BTreeMap::from([
+                ("cn".to_string(), vec!["John Doe".to_string()]),
+                ("sAMAccountName".to_string(), vec!["jdoe".to_string()]),
+                (
+                    "userPrincipalName".to_string(),
+                    vec!["jdoe@corp.com".to_string()],
+                ),
+            ]),
+        );
+        let mut dialog = RenameUserDialog::new(Theme::load("dark"));
+        dialog.show(entry);
+
+        // Move to sAMAccountName and retype it
+        dialog.handle_key_event(key(KeyCode::Tab));
+        for _ in 0..4 {
+            dialog.handle_key_event(key(KeyCode::Backspace));
+        }
+        type_text(&mut dialog, "jsmith");
+        assert_eq!(dialog.target.user_principal_name, "jsmith@corp.com");
+
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Enter)),
+            Action::None
+        ));
+        assert!(dialog.plan.is_some());
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::SafeRenameExecute(plan) => {
+                assert_eq!(plan.steps.len(), 1);
+                assert_eq!(plan.new_dn, "CN=John Doe,OU=Users,DC=corp,DC=com");
+            }
+            other => panic!("expected SafeRenameExecute, got {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+}
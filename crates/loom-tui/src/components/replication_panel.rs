@@ -0,0 +1,348 @@
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::replication::{Health, PartnerStatus, ReplicationReport};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Row, Table, TableState};
+use ratatui::Frame;
+
+use crate::action::{Action, ConnectionId};
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// A popup listing the active tab's replication partners per partition
+/// with their lag, coloured by health.
+pub struct ReplicationPanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    /// The tab the report belongs to.
+    conn_id: Option<ConnectionId>,
+    report: Option<ReplicationReport>,
+    loaded_at: Option<DateTime<Local>>,
+    error: Option<String>,
+    loading: bool,
+    selected: usize,
+}
+
+impl ReplicationPanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Replication", theme.clone()).with_size(90, 70),
+            theme,
+            conn_id: None,
+            report: None,
+            loaded_at: None,
+            error: None,
+            loading: false,
+            selected: 0,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.popup.show();
+        } else {
+            self.popup.hide();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Whether the report shown is for a different tab than `conn_id`.
+    pub fn is_stale(&self, conn_id: ConnectionId) -> bool {
+        self.conn_id != Some(conn_id)
+    }
+
+    /// Note a check of `conn_id`, dropping any other tab's report.
+    pub fn start_loading(&mut self, conn_id: ConnectionId) {
+        if self.conn_id != Some(conn_id) {
+            self.conn_id = Some(conn_id);
+            self.report = None;
+            self.selected = 0;
+        }
+        self.error = None;
+        self.loading = true;
+    }
+
+    pub fn set_report(&mut self, conn_id: ConnectionId, report: ReplicationReport) {
+        if self.conn_id != Some(conn_id) {
+            return;
+        }
+        self.selected = self.selected.min(report.partners.len().saturating_sub(1));
+        self.report = Some(report);
+        self.loaded_at = Some(Local::now());
+        self.loading = false;
+    }
+
+    pub fn set_error(&mut self, conn_id: ConnectionId, error: String) {
+        if self.conn_id != Some(conn_id) {
+            return;
+        }
+        self.error = Some(error);
+        self.loading = false;
+    }
+
+    fn partners(&self) -> &[PartnerStatus] {
+        self.report
+            .as_ref()
+            .map(|r| r.partners.as_slice())
+            .unwrap_or_default()
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        let count = self.partners().len();
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < count {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Char('r') if !self.loading => Action::RefreshReplication,
+            _ => Action::None,
+        }
+    }
+
+    fn health_style(&self, health: Health) -> Style {
+        match health {
+            Health::Healthy => self.theme.success,
+            Health::Lagging => self.theme.warning,
+            Health::Failing => self.theme.error,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let partners = self.partners();
+        let mut title = format!(" Replication ({} partners", partners.len());
+        if let Some(at) = self.loaded_at.filter(|_| self.report.is_some()) {
+            title.push_str(&format!(", checked {}", at.format("%H:%M:%S")));
+        }
+        if self.loading {
+            title.push_str(", checking...");
+        }
+        title.push_str(") ");
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: partners | selected partner's note and server summary (2) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(2),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        if partners.is_empty() {
+            let (text, style) = match &self.error {
+                Some(error) => (error.clone(), self.theme.error),
+                None if self.loading => ("Checking replication...".to_string(), self.theme.dimmed),
+                None => (
+                    "No replication partners found. For OpenLDAP, open a tab to each \
+                     server of the suffix to compare them."
+                        .to_string(),
+                    self.theme.dimmed,
+                ),
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(text, style))),
+                layout[0],
+            );
+        } else {
+            let header = Row::new([
+                "Status",
+                "Partition",
+                "Partner",
+                "Dir",
+                "Last update",
+                "Lag",
+                "Fails",
+                "USN",
+            ])
+            .style(self.theme.header);
+            let rows = partners.iter().map(|p| {
+                Row::new([
+                    p.health.label().to_string(),
+                    p.partition.clone(),
+                    p.partner.clone(),
+                    p.direction.label().to_string(),
+                    p.last_success
+                        .map(|t| {
+                            t.with_timezone(&Local)
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|| "never".to_string()),
+                    p.lag.map(format_lag).unwrap_or_default(),
+                    p.failures.to_string(),
+                    p.usn.map(|u| u.to_string()).unwrap_or_default(),
+                ])
+                .style(self.health_style(p.health))
+            });
+            let table = Table::new(
+                rows,
+                [
+                    Constraint::Length(8),
+                    Constraint::Min(20),
+                    Constraint::Min(12),
+                    Constraint::Length(4),
+                    Constraint::Length(19),
+                    Constraint::Length(8),
+                    Constraint::Length(5),
+                    Constraint::Length(10),
+                ],
+            )
+            .header(header)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+            let mut state = TableState::default().with_selected(Some(self.selected));
+            frame.render_stateful_widget(table, layout[0], &mut state);
+        }
+
+        let mut summary = Vec::new();
+        if let Some(partner) = partners.get(self.selected) {
+            if !partner.note.is_empty() {
+                summary.push(Line::from(Span::styled(
+                    partner.note.clone(),
+                    self.health_style(partner.health),
+                )));
+            }
+        }
+        if let Some(report) = &self.report {
+            let text = match report.highest_usn {
+                Some(usn) => format!("highestCommittedUSN {}", usn),
+                None if !report.providers.is_empty() => {
+                    format!("Consumes from {}", report.providers.join(", "))
+                }
+                None => String::new(),
+            };
+            summary.push(Line::from(Span::styled(text, self.theme.dimmed)));
+        }
+        frame.render_widget(Paragraph::new(summary), layout[1]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  r:check again  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+}
+
+/// "45s", "12m 30s", "3h 05m" or "2d 04h".
+fn format_lag(lag: Duration) -> String {
+    let secs = lag.as_secs();
+    let (days, hours, minutes, seconds) = (
+        secs / 86400,
+        secs % 86400 / 3600,
+        secs % 3600 / 60,
+        secs % 60,
+    );
+    if days > 0 {
+        format!("{}d {:02}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {:02}m", hours, minutes)
+    } else if minutes > 0 {
+        format!("{}m {:02}s", minutes, seconds)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::replication::Direction;
+
+    use super::*;
+
+    fn partner(name: &str, health: Health) -> PartnerStatus {
+        PartnerStatus {
+            partition: "dc=example,dc=com".to_string(),
+            partner: name.to_string(),
+            direction: Direction::Inbound,
+            last_success: None,
+            failures: 0,
+            last_result: 0,
+            usn: None,
+            lag: None,
+            health,
+            note: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_report_for_the_current_tab_only() {
+        let mut panel = ReplicationPanel::new(Theme::default());
+        panel.toggle();
+        panel.start_loading(1);
+        let report = ReplicationReport {
+            partners: vec![
+                partner("DC02", Health::Healthy),
+                partner("DC03", Health::Failing),
+            ],
+            ..Default::default()
+        };
+        panel.set_report(2, report.clone());
+        assert!(panel.partners().is_empty());
+        panel.set_report(1, report);
+        assert_eq!(panel.partners().len(), 2);
+        assert!(!panel.is_stale(1));
+        assert!(panel.is_stale(2));
+
+        let down = KeyEvent::from(KeyCode::Down);
+        panel.handle_key_event(down);
+        panel.handle_key_event(down);
+        assert_eq!(panel.selected, 1);
+        assert!(matches!(
+            panel.handle_key_event(KeyEvent::from(KeyCode::Char('r'))),
+            Action::RefreshReplication
+        ));
+
+        panel.start_loading(2);
+        assert!(panel.partners().is_empty());
+        assert_eq!(panel.selected, 0);
+    }
+
+    #[test]
+    fn test_format_lag() {
+        assert_eq!(format_lag(Duration::from_secs(45)), "45s");
+        assert_eq!(format_lag(Duration::from_secs(750)), "12m 30s");
+        assert_eq!(format_lag(Duration::from_secs(3 * 3600 + 300)), "3h 05m");
+        assert_eq!(
+            format_lag(Duration::from_secs(2 * 86400 + 4 * 3600)),
+            "2d 04h"
+        );
+    }
+}
@@ -0,0 +1,355 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph};
+use ratatui::Frame;
+
+use loom_core::export::report::ReportSection;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Report format options.
+const FORMATS: &[(&str, &str)] = &[
+    ("Markdown", ".md"),
+    ("Plain text", ".txt"),
+    ("Typst (for PDF)", ".typ"),
+];
+
+/// Which field is currently active.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ReportField {
+    Sections,
+    Format,
+    Filename,
+}
+
+/// Dialog for writing a printable report of one or more entries.
+pub struct ReportDialog {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    dns: Vec<String>,
+    active_field: ReportField,
+    /// Whether each of `ReportSection::ALL` is included.
+    included: [bool; 4],
+    section_idx: usize,
+    format_idx: usize,
+    filename: String,
+}
+
+impl ReportDialog {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Entry Report", theme.clone()).with_size(60, 55),
+            theme,
+            dns: Vec::new(),
+            active_field: ReportField::Sections,
+            included: [true; 4],
+            section_idx: 0,
+            format_idx: 0,
+            filename: String::new(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, dns: Vec<String>) {
+        let stem = match dns.as_slice() {
+            [dn] => format!(
+                "report-{}",
+                loom_core::dn::rdn_display_name(dn).replace([' ', '/', '\\'], "_")
+            ),
+            _ => "report".to_string(),
+        };
+        self.dns = dns;
+        self.filename = format!("{}{}", stem, FORMATS[self.format_idx].1);
+        self.active_field = ReportField::Sections;
+        self.section_idx = 0;
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Tab => {
+                self.active_field = match self.active_field {
+                    ReportField::Sections => ReportField::Format,
+                    ReportField::Format => ReportField::Filename,
+                    ReportField::Filename => ReportField::Sections,
+                };
+                Action::None
+            }
+            KeyCode::BackTab => {
+                self.active_field = match self.active_field {
+                    ReportField::Sections => ReportField::Filename,
+                    ReportField::Format => ReportField::Sections,
+                    ReportField::Filename => ReportField::Format,
+                };
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') if self.active_field != ReportField::Filename => {
+                self.step(false);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') if self.active_field != ReportField::Filename => {
+                self.step(true);
+                Action::None
+            }
+            KeyCode::Char(' ') if self.active_field == ReportField::Sections => {
+                self.included[self.section_idx] = !self.included[self.section_idx];
+                Action::None
+            }
+            KeyCode::Enter => self.submit(),
+            KeyCode::Backspace if self.active_field == ReportField::Filename => {
+                self.filename.pop();
+                Action::None
+            }
+            KeyCode::Char(c) if self.active_field == ReportField::Filename => {
+                self.filename.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    fn step(&mut self, down: bool) {
+        match self.active_field {
+            ReportField::Sections => {
+                let max = ReportSection::ALL.len() - 1;
+                self.section_idx = if down {
+                    (self.section_idx + 1).min(max)
+                } else {
+                    self.section_idx.saturating_sub(1)
+                };
+            }
+            ReportField::Format => {
+                let idx = if down {
+                    (self.format_idx + 1).min(FORMATS.len() - 1)
+                } else {
+                    self.format_idx.saturating_sub(1)
+                };
+                if idx != self.format_idx {
+                    self.format_idx = idx;
+                    if let Some(dot_pos) = self.filename.rfind('.') {
+                        self.filename.truncate(dot_pos);
+                    }
+                    self.filename.push_str(FORMATS[idx].1);
+                }
+            }
+            ReportField::Filename => {}
+        }
+    }
+
+    fn submit(&mut self) -> Action {
+        let sections: Vec<ReportSection> = ReportSection::ALL
+            .iter()
+            .zip(self.included)
+            .filter(|(_, on)| *on)
+            .map(|(s, _)| *s)
+            .collect();
+        if sections.is_empty() {
+            return Action::ErrorMessage("Choose at least one section".to_string());
+        }
+        let mut path = self.filename.trim().to_string();
+        if path.is_empty() {
+            return Action::ErrorMessage("Filename is required".to_string());
+        }
+        if std::path::Path::new(&path).extension().is_none() {
+            path.push_str(FORMATS[self.format_idx].1);
+        }
+        self.hide();
+        Action::ReportExecute {
+            dns: std::mem::take(&mut self.dns),
+            path,
+            sections,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let title = match self.dns.len() {
+            1 => " Entry Report ".to_string(),
+            n => format!(" Entry Report ({} entries) ", n),
+        };
+        let block = Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Length(ReportSection::ALL.len() as u16 + 2), // Sections
+            Constraint::Length(FORMATS.len() as u16 + 2),            // Format
+            Constraint::Length(2),                                   // Filename
+            Constraint::Min(1),                                      // Hints
+        ])
+        .split(inner);
+
+        let sections: Vec<(String, bool)> = ReportSection::ALL
+            .iter()
+            .zip(self.included)
+            .map(|(s, on)| {
+                let check = if on { "[x]" } else { "[ ]" };
+                (format!("{} {}", check, s.label()), false)
+            })
+            .collect();
+        self.render_choices(
+            frame,
+            layout[0],
+            "Sections",
+            &sections,
+            self.section_idx,
+            ReportField::Sections,
+        );
+
+        let formats: Vec<(String, bool)> = FORMATS
+            .iter()
+            .enumerate()
+            .map(|(i, (name, ext))| (format!("{} ({})", name, ext), i == self.format_idx))
+            .collect();
+        self.render_choices(
+            frame,
+            layout[1],
+            "Format",
+            &formats,
+            self.format_idx,
+            ReportField::Format,
+        );
+
+        let is_active = self.active_field == ReportField::Filename;
+        let (label_style, value_style) = if is_active {
+            (self.theme.header, self.theme.normal)
+        } else {
+            (self.theme.dimmed, self.theme.dimmed)
+        };
+        let filename_lines = vec![
+            Line::from(Span::styled("Filename:", label_style)),
+            Line::from(vec![
+                Span::styled(self.filename.as_str(), value_style),
+                if is_active {
+                    Span::styled("_", self.theme.command_prompt)
+                } else {
+                    Span::raw("")
+                },
+            ]),
+        ];
+        frame.render_widget(Paragraph::new(filename_lines), layout[2]);
+
+        let hint_text = match self.active_field {
+            ReportField::Sections => "Tab:next  j/k:move  Space:toggle  Enter:write  Esc:cancel",
+            ReportField::Format => "Tab:next  j/k:select  Enter:write  Esc:cancel",
+            ReportField::Filename => "Tab:next  Enter:write  Esc:cancel",
+        };
+        frame.render_widget(
+            Paragraph::new(Line::from(Span::styled(hint_text, self.theme.dimmed))),
+            layout[3],
+        );
+    }
+
+    /// A labelled list with a cursor; `bold` marks the chosen option.
+    fn render_choices(
+        &self,
+        frame: &mut Frame,
+        area: Rect,
+        label: &str,
+        items: &[(String, bool)],
+        cursor: usize,
+        field: ReportField,
+    ) {
+        let is_active = self.active_field == field;
+        let label_style = if is_active {
+            self.theme.header
+        } else {
+            self.theme.dimmed
+        };
+        let mut lines = vec![Line::from(Span::styled(format!("{}:", label), label_style))];
+        for (i, (text, bold)) in items.iter().enumerate() {
+            let marker = if i == cursor && is_active { "> " } else { "  " };
+            let mut style = if i == cursor && is_active {
+                self.theme.selected
+            } else if is_active {
+                self.theme.normal
+            } else {
+                self.theme.dimmed
+            };
+            if *bold {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            lines.push(Line::from(Span::styled(
+                format!("{}{}", marker, text),
+                style,
+            )));
+        }
+        frame.render_widget(Paragraph::new(lines), area);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_toggle_sections_and_format() {
+        let mut dialog = ReportDialog::new(Theme::load("dark"));
+        dialog.show(vec!["CN=John Doe,OU=Users,DC=corp,DC=com".to_string()]);
+        assert_eq!(dialog.filename, "report-John_Doe.md");
+
+        // Drop Contact, switch to plain text
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Tab));
+        dialog.handle_key_event(key(KeyCode::Down));
+        assert_eq!(dialog.filename, "report-John_Doe.txt");
+
+        match dialog.handle_key_event(key(KeyCode::Enter)) {
+            Action::ReportExecute {
+                dns,
+                path,
+                sections,
+            } => {
+                assert_eq!(dns.len(), 1);
+                assert_eq!(path, "report-John_Doe.txt");
+                assert_eq!(
+                    sections,
+                    [
+                        ReportSection::Identity,
+                        ReportSection::Groups,
+                        ReportSection::Audit
+                    ]
+                );
+            }
+            other => panic!("expected ReportExecute, got {:?}", other),
+        }
+        assert!(!dialog.visible);
+    }
+}
@@ -2,13 +2,16 @@ use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, Borders, Cell, Clear, Row, Table, TableState, Tabs};
+use ratatui::widgets::{
+    Block, Borders, Cell, Clear, List, ListItem, ListState, Paragraph, Row, Table, TableState, Tabs,
+};
 use ratatui::Frame;
 
 use crate::action::Action;
 use crate::components::popup::Popup;
 use crate::theme::Theme;
-use loom_core::schema::{AttributeTypeInfo, ObjectClassInfo, ObjectClassKind, SchemaCache};
+use crate::widgets::scroll::scroll_view;
+use loom_core::schema::{AttributeTypeInfo, ObjectClassInfo, SchemaCache};
 
 /// Which tab is active in the schema viewer.
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,7 +20,21 @@ enum SchemaTab {
     AttributeTypes,
 }
 
-/// A full-screen schema browser overlay.
+/// A schema element opened in the detail view.
+#[derive(Debug, Clone, PartialEq)]
+enum SchemaRef {
+    ObjectClass(String),
+    Attribute(String),
+}
+
+/// A cross-reference from the element on screen to another one.
+struct SchemaLink {
+    label: &'static str,
+    target: SchemaRef,
+}
+
+/// A full-screen schema browser overlay. Enter opens an element's details,
+/// from where its cross-references can be followed; Backspace goes back.
 pub struct SchemaViewer {
     pub visible: bool,
     popup: Popup,
@@ -32,6 +49,15 @@ pub struct SchemaViewer {
     // Filter
     filter: String,
     filter_active: bool,
+    /// The export file name being typed, while the export prompt is open.
+    export_path: Option<String>,
+    // Detail view: the elements opened, most recent last
+    schema: SchemaCache,
+    stack: Vec<SchemaRef>,
+    links: Vec<SchemaLink>,
+    link_state: ListState,
+    /// Rows the table or link list had room for when last drawn.
+    view_height: usize,
 }
 
 impl SchemaViewer {
@@ -47,38 +73,31 @@ impl SchemaViewer {
             at_state: TableState::default(),
             filter: String::new(),
             filter_active: false,
+            export_path: None,
+            schema: SchemaCache::new(),
+            stack: Vec::new(),
+            links: Vec::new(),
+            link_state: ListState::default(),
+            view_height: 0,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     pub fn show(&mut self, schema: &SchemaCache) {
-        // Deduplicate by OID (schema entries are stored per-name)
-        let mut seen_oc = std::collections::HashSet::new();
         self.oc_items = schema
-            .object_classes
-            .values()
-            .filter(|oc| seen_oc.insert(oc.oid.clone()))
+            .unique_object_classes()
+            .into_iter()
             .cloned()
             .collect();
-        self.oc_items.sort_by(|a, b| {
-            a.names
-                .first()
-                .unwrap_or(&a.oid)
-                .cmp(b.names.first().unwrap_or(&b.oid))
-        });
-
-        let mut seen_at = std::collections::HashSet::new();
         self.at_items = schema
-            .attribute_types
-            .values()
-            .filter(|at| seen_at.insert(at.oid.clone()))
+            .unique_attribute_types()
+            .into_iter()
             .cloned()
             .collect();
-        self.at_items.sort_by(|a, b| {
-            a.names
-                .first()
-                .unwrap_or(&a.oid)
-                .cmp(b.names.first().unwrap_or(&b.oid))
-        });
 
         self.oc_state.select(if self.oc_items.is_empty() {
             None
@@ -93,6 +112,10 @@ impl SchemaViewer {
 
         self.filter.clear();
         self.filter_active = false;
+        self.export_path = None;
+        self.schema = schema.clone();
+        self.stack.clear();
+        self.links.clear();
         self.visible = true;
         self.popup.show();
     }
@@ -106,6 +129,16 @@ impl SchemaViewer {
         if self.filter_active {
             return self.handle_filter_key(key);
         }
+        if self.export_path.is_some() {
+            return self.handle_export_key(key);
+        }
+        if key.code == KeyCode::Char('e') {
+            self.export_path = Some("schema.ldif".to_string());
+            return Action::None;
+        }
+        if !self.stack.is_empty() {
+            return self.handle_detail_key(key);
+        }
 
         match key.code {
             KeyCode::Esc | KeyCode::Char('q') => {
@@ -132,10 +165,170 @@ impl SchemaViewer {
                 self.filter.clear();
                 Action::None
             }
+            KeyCode::Enter => {
+                if let Some(target) = self.selected_row() {
+                    self.open(target);
+                }
+                Action::None
+            }
+            KeyCode::Char('s') => match self.selected_row() {
+                Some(target) => self.usage_search(&target),
+                None => Action::None,
+            },
+            _ => Action::None,
+        }
+    }
+
+    fn handle_detail_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Left | KeyCode::Char('h') => {
+                self.stack.pop();
+                if let Some(top) = self.stack.last().cloned() {
+                    self.load_links(&top);
+                }
+                Action::None
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.link_state.selected().unwrap_or(0);
+                self.link_state.select(Some(i.saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.link_state.selected().unwrap_or(0);
+                if i + 1 < self.links.len() {
+                    self.link_state.select(Some(i + 1));
+                }
+                Action::None
+            }
+            KeyCode::Enter | KeyCode::Right | KeyCode::Char('l') => {
+                if let Some(target) = self.selected_link() {
+                    self.open(target);
+                }
+                Action::None
+            }
+            // Search for the element on screen, not the highlighted link
+            KeyCode::Char('s') => match self.stack.last().cloned() {
+                Some(target) => self.usage_search(&target),
+                None => Action::None,
+            },
             _ => Action::None,
         }
     }
 
+    fn handle_export_key(&mut self, key: KeyEvent) -> Action {
+        let Some(path) = self.export_path.as_mut() else {
+            return Action::None;
+        };
+        match key.code {
+            KeyCode::Esc => {
+                self.export_path = None;
+                Action::None
+            }
+            KeyCode::Enter => {
+                let path = path.trim().to_string();
+                if path.is_empty() {
+                    return Action::ErrorMessage("Filename is required".to_string());
+                }
+                self.export_path = None;
+                Action::SchemaExport(path)
+            }
+            KeyCode::Backspace => {
+                path.pop();
+                Action::None
+            }
+            KeyCode::Char(c) => {
+                path.push(c);
+                Action::None
+            }
+            _ => Action::None,
+        }
+    }
+
+    /// The element under the cursor in the active table.
+    fn selected_row(&self) -> Option<SchemaRef> {
+        match self.tab {
+            SchemaTab::ObjectClasses => {
+                let oc = *self.filtered_oc().get(self.oc_state.selected()?)?;
+                Some(SchemaRef::ObjectClass(display_name(&oc.names, &oc.oid)))
+            }
+            SchemaTab::AttributeTypes => {
+                let at = *self.filtered_at().get(self.at_state.selected()?)?;
+                Some(SchemaRef::Attribute(display_name(&at.names, &at.oid)))
+            }
+        }
+    }
+
+    fn selected_link(&self) -> Option<SchemaRef> {
+        self.link_state
+            .selected()
+            .and_then(|i| self.links.get(i))
+            .map(|link| link.target.clone())
+    }
+
+    /// Show the details of `target` on top of the current view.
+    fn open(&mut self, target: SchemaRef) {
+        self.load_links(&target);
+        self.stack.push(target);
+    }
+
+    fn load_links(&mut self, target: &SchemaRef) {
+        let link = |label, target| SchemaLink { label, target };
+        self.links = match target {
+            SchemaRef::ObjectClass(name) => {
+                let (must, may) = self.schema.must_may_attributes(&[name.as_str()]);
+                let superiors = self.schema.superior_chain(name);
+                superiors
+                    .into_iter()
+                    .map(|s| link("Superior", SchemaRef::ObjectClass(s)))
+                    .chain(
+                        must.into_iter()
+                            .map(|a| link("MUST", SchemaRef::Attribute(a))),
+                    )
+                    .chain(
+                        may.into_iter()
+                            .map(|a| link("MAY", SchemaRef::Attribute(a))),
+                    )
+                    .collect()
+            }
+            SchemaRef::Attribute(name) => {
+                let (must, may) = self.schema.classes_using_attribute(name);
+                let superior = self
+                    .schema
+                    .get_attribute_type(name)
+                    .and_then(|at| at.superior.clone());
+                superior
+                    .into_iter()
+                    .map(|s| link("Superior", SchemaRef::Attribute(s)))
+                    .chain(
+                        must.into_iter()
+                            .map(|c| link("MUST in", SchemaRef::ObjectClass(c))),
+                    )
+                    .chain(
+                        may.into_iter()
+                            .map(|c| link("MAY in", SchemaRef::ObjectClass(c))),
+                    )
+                    .collect()
+            }
+        };
+        self.link_state
+            .select(if self.links.is_empty() { None } else { Some(0) });
+    }
+
+    /// Close the viewer and search the active connection for entries that
+    /// carry the attribute, or belong to the object class.
+    fn usage_search(&mut self, target: &SchemaRef) -> Action {
+        let filter = match target {
+            SchemaRef::ObjectClass(name) => format!("(objectClass={})", name),
+            SchemaRef::Attribute(name) => format!("({}=*)", name),
+        };
+        self.hide();
+        Action::SearchExecute(filter)
+    }
+
     fn handle_filter_key(&mut self, key: KeyEvent) -> Action {
         match key.code {
             KeyCode::Esc => {
@@ -159,10 +352,38 @@ impl SchemaViewer {
         }
     }
 
+    /// Scroll the table, or the open element's links, `delta` rows with
+    /// the mouse wheel. The selection stays put unless it would go off
+    /// screen.
+    pub fn scroll(&mut self, delta: isize) {
+        let height = self.view_height;
+        if !self.stack.is_empty() {
+            let state = &mut self.link_state;
+            let (offset, selected) = scroll_view(
+                state.offset(),
+                state.selected(),
+                self.links.len(),
+                delta,
+                |_| height,
+            );
+            state.select(selected);
+            *state.offset_mut() = offset;
+            return;
+        }
+        let (len, state) = match self.tab {
+            SchemaTab::ObjectClasses => (self.filtered_oc().len(), &mut self.oc_state),
+            SchemaTab::AttributeTypes => (self.filtered_at().len(), &mut self.at_state),
+        };
+        let (offset, selected) =
+            scroll_view(state.offset(), state.selected(), len, delta, |_| height);
+        state.select(selected);
+        *state.offset_mut() = offset;
+    }
+
     fn move_selection(&mut self, delta: i32) {
         let len = match self.tab {
-            SchemaTab::ObjectClasses => self.filtered_oc_count(),
-            SchemaTab::AttributeTypes => self.filtered_at_count(),
+            SchemaTab::ObjectClasses => self.filtered_oc().len(),
+            SchemaTab::AttributeTypes => self.filtered_at().len(),
         };
 
         if len == 0 {
@@ -179,44 +400,20 @@ impl SchemaViewer {
         state.select(Some(next));
     }
 
-    fn filtered_oc_count(&self) -> usize {
-        if self.filter.is_empty() {
-            self.oc_items.len()
-        } else {
-            let f = self.filter.to_lowercase();
-            self.oc_items
-                .iter()
-                .filter(|oc| {
-                    oc.names.iter().any(|n| n.to_lowercase().contains(&f))
-                        || oc
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&f)
-                })
-                .count()
-        }
+    fn filtered_oc(&self) -> Vec<&ObjectClassInfo> {
+        let f = self.filter.to_lowercase();
+        self.oc_items
+            .iter()
+            .filter(|oc| matches_filter(&oc.names, oc.description.as_deref(), &f))
+            .collect()
     }
 
-    fn filtered_at_count(&self) -> usize {
-        if self.filter.is_empty() {
-            self.at_items.len()
-        } else {
-            let f = self.filter.to_lowercase();
-            self.at_items
-                .iter()
-                .filter(|at| {
-                    at.names.iter().any(|n| n.to_lowercase().contains(&f))
-                        || at
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&f)
-                })
-                .count()
-        }
+    fn filtered_at(&self) -> Vec<&AttributeTypeInfo> {
+        let f = self.filter.to_lowercase();
+        self.at_items
+            .iter()
+            .filter(|at| matches_filter(&at.names, at.description.as_deref(), &f))
+            .collect()
     }
 
     pub fn render(&mut self, frame: &mut Frame, full: Rect) {
@@ -259,14 +456,25 @@ impl SchemaViewer {
             .highlight_style(self.theme.tab_active);
         frame.render_widget(tabs, layout[0]);
 
-        // Table
-        match self.tab {
-            SchemaTab::ObjectClasses => self.render_oc_table(frame, layout[1]),
-            SchemaTab::AttributeTypes => self.render_at_table(frame, layout[1]),
+        // Table, or the details of the element opened from it
+        match (self.stack.last().cloned(), self.tab) {
+            (Some(target), _) => self.render_detail(frame, layout[1], &target),
+            (None, SchemaTab::ObjectClasses) => self.render_oc_table(frame, layout[1]),
+            (None, SchemaTab::AttributeTypes) => self.render_at_table(frame, layout[1]),
         }
 
         // Filter line
-        let filter_line = if self.filter_active {
+        let filter_line = if let Some(path) = &self.export_path {
+            Line::from(vec![
+                Span::styled("Export to: ", self.theme.command_prompt),
+                Span::styled(path.as_str(), self.theme.normal),
+                Span::styled("_", self.theme.command_prompt),
+                Span::styled(
+                    "  (.ldif, .json or .md; Enter:write  Esc:cancel)",
+                    self.theme.dimmed,
+                ),
+            ])
+        } else if self.filter_active {
             Line::from(vec![
                 Span::styled("/ ", self.theme.command_prompt),
                 Span::styled(&self.filter, self.theme.normal),
@@ -278,45 +486,128 @@ impl SchemaViewer {
                 Span::styled(&self.filter, self.theme.normal),
                 Span::styled(" (/ to edit, Esc to clear)", self.theme.dimmed),
             ])
+        } else if !self.stack.is_empty() {
+            Line::from(Span::styled(
+                "\u{2191}/\u{2193}:navigate  Enter:follow  Backspace:back  s:find entries  e:export  q:close",
+                self.theme.dimmed,
+            ))
         } else {
             Line::from(Span::styled(
-                "Tab:switch  \u{2191}/\u{2193}:navigate  /:filter  q:close",
+                "Tab:switch  \u{2191}/\u{2193}:navigate  Enter:details  /:filter  s:find entries  e:export  q:close",
                 self.theme.dimmed,
             ))
         };
-        frame.render_widget(ratatui::widgets::Paragraph::new(filter_line), layout[2]);
+        frame.render_widget(Paragraph::new(filter_line), layout[2]);
     }
 
-    fn render_oc_table(&mut self, frame: &mut Frame, area: Rect) {
-        let filter_lower = self.filter.to_lowercase();
-        let filtered: Vec<&ObjectClassInfo> = if self.filter.is_empty() {
-            self.oc_items.iter().collect()
-        } else {
-            self.oc_items
-                .iter()
-                .filter(|oc| {
-                    oc.names
-                        .iter()
-                        .any(|n| n.to_lowercase().contains(&filter_lower))
-                        || oc
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                })
-                .collect()
+    fn render_detail(&mut self, frame: &mut Frame, area: Rect, target: &SchemaRef) {
+        let field = |label: &str, value: String| {
+            Line::from(vec![
+                Span::styled(format!("{:<12}", label), self.theme.dimmed),
+                Span::styled(value, self.theme.normal),
+            ])
         };
+        let or_dash = |v: Option<&str>| v.unwrap_or("-").to_string();
+
+        let mut lines = Vec::new();
+        match target {
+            SchemaRef::ObjectClass(name) => match self.schema.get_object_class(name) {
+                Some(oc) => {
+                    let kind = oc.kind.keyword();
+                    lines.push(field("Class", oc.names.join(", ")));
+                    lines.push(field("OID", oc.oid.clone()));
+                    lines.push(field("Kind", kind.to_string()));
+                    lines.push(field("Description", or_dash(oc.description.as_deref())));
+                }
+                None => lines.push(field("Class", format!("{} (not in schema)", name))),
+            },
+            SchemaRef::Attribute(name) => match self.schema.get_attribute_type(name) {
+                Some(at) => {
+                    let mut flags = Vec::new();
+                    if at.single_value {
+                        flags.push("SINGLE-VALUE");
+                    }
+                    if at.no_user_modification {
+                        flags.push("NO-USER-MODIFICATION");
+                    }
+                    let syntax = match &at.syntax_oid {
+                        Some(oid) => format!("{:?} ({})", at.syntax, oid),
+                        None => format!("{:?}", at.syntax),
+                    };
+                    lines.push(field("Attribute", at.names.join(", ")));
+                    lines.push(field("OID", at.oid.clone()));
+                    lines.push(field("Syntax", syntax));
+                    lines.push(field("Equality", or_dash(at.equality.as_deref())));
+                    lines.push(field("Ordering", or_dash(at.ordering.as_deref())));
+                    lines.push(field("Substring", or_dash(at.substr.as_deref())));
+                    lines.push(field(
+                        "Flags",
+                        if flags.is_empty() {
+                            "-".to_string()
+                        } else {
+                            flags.join(" ")
+                        },
+                    ));
+                    lines.push(field("Description", or_dash(at.description.as_deref())));
+                }
+                None => lines.push(field("Attribute", format!("{} (not in schema)", name))),
+            },
+        }
+
+        let layout = Layout::vertical([
+            Constraint::Length(lines.len() as u16 + 1),
+            Constraint::Min(1),
+        ])
+        .split(area);
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        if self.links.is_empty() {
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(
+                    "No cross-references",
+                    self.theme.dimmed,
+                ))),
+                layout[1],
+            );
+            return;
+        }
+        let items: Vec<ListItem> = self
+            .links
+            .iter()
+            .map(|link| {
+                let (name, style) = match &link.target {
+                    SchemaRef::ObjectClass(n) => (n, self.theme.normal),
+                    SchemaRef::Attribute(n) if link.label == "MUST" => (n, self.theme.warning),
+                    SchemaRef::Attribute(n) => (n, self.theme.normal),
+                };
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<10}", link.label), self.theme.dimmed),
+                    Span::styled(name.as_str(), style),
+                ]))
+            })
+            .collect();
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        self.view_height = layout[1].height as usize;
+        frame.render_stateful_widget(list, layout[1], &mut self.link_state);
+    }
+
+    fn render_oc_table(&mut self, frame: &mut Frame, area: Rect) {
+        // Filtered from the field directly: the rows borrow from it while
+        // the table state is borrowed mutably
+        let f = self.filter.to_lowercase();
+        let filtered: Vec<&ObjectClassInfo> = self
+            .oc_items
+            .iter()
+            .filter(|oc| matches_filter(&oc.names, oc.description.as_deref(), &f))
+            .collect();
 
         let rows: Vec<Row> = filtered
             .iter()
             .map(|oc| {
                 let name = oc.names.first().map(|s| s.as_str()).unwrap_or(&oc.oid);
-                let kind = match oc.kind {
-                    ObjectClassKind::Abstract => "ABSTRACT",
-                    ObjectClassKind::Structural => "STRUCTURAL",
-                    ObjectClassKind::Auxiliary => "AUXILIARY",
-                };
+                let kind = oc.kind.keyword();
                 let sup = oc.superior.as_deref().unwrap_or("-");
                 let must = oc.must.join(", ");
                 let may_str = if oc.may.len() > 3 {
@@ -356,29 +647,17 @@ impl SchemaViewer {
             )
             .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
 
+        self.view_height = area.height.saturating_sub(1) as usize;
         frame.render_stateful_widget(table, area, &mut self.oc_state);
     }
 
     fn render_at_table(&mut self, frame: &mut Frame, area: Rect) {
-        let filter_lower = self.filter.to_lowercase();
-        let filtered: Vec<&AttributeTypeInfo> = if self.filter.is_empty() {
-            self.at_items.iter().collect()
-        } else {
-            self.at_items
-                .iter()
-                .filter(|at| {
-                    at.names
-                        .iter()
-                        .any(|n| n.to_lowercase().contains(&filter_lower))
-                        || at
-                            .description
-                            .as_deref()
-                            .unwrap_or("")
-                            .to_lowercase()
-                            .contains(&filter_lower)
-                })
-                .collect()
-        };
+        let f = self.filter.to_lowercase();
+        let filtered: Vec<&AttributeTypeInfo> = self
+            .at_items
+            .iter()
+            .filter(|at| matches_filter(&at.names, at.description.as_deref(), &f))
+            .collect();
 
         let rows: Vec<Row> = filtered
             .iter()
@@ -416,6 +695,146 @@ impl SchemaViewer {
             )
             .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
 
+        self.view_height = area.height.saturating_sub(1) as usize;
         frame.render_stateful_widget(table, area, &mut self.at_state);
     }
 }
+
+/// The first name of a schema element, or its OID when it has none.
+fn display_name(names: &[String], oid: &str) -> String {
+    names.first().cloned().unwrap_or_else(|| oid.to_string())
+}
+
+/// Case-insensitive match of a lowercased filter against names and description.
+fn matches_filter(names: &[String], description: Option<&str>, filter_lower: &str) -> bool {
+    filter_lower.is_empty()
+        || names
+            .iter()
+            .any(|n| n.to_lowercase().contains(filter_lower))
+        || description
+            .unwrap_or("")
+            .to_lowercase()
+            .contains(filter_lower)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+    use loom_core::schema::{AttributeSyntax, ObjectClassKind};
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn schema() -> SchemaCache {
+        let mut schema = SchemaCache::new();
+        for (name, sup, must, may) in [
+            ("top", None, vec!["objectClass"], vec![]),
+            ("person", Some("top"), vec!["sn", "cn"], vec!["description"]),
+        ] {
+            schema.object_classes.insert(
+                name.to_string(),
+                ObjectClassInfo {
+                    oid: format!("oid.{}", name),
+                    names: vec![name.to_string()],
+                    description: None,
+                    superior: sup.map(String::from),
+                    kind: ObjectClassKind::Structural,
+                    must: must.into_iter().map(String::from).collect(),
+                    may: may.into_iter().map(String::from).collect(),
+                },
+            );
+        }
+        for name in ["cn", "description", "objectClass", "sn"] {
+            schema.attribute_types.insert(
+                name.to_lowercase(),
+                AttributeTypeInfo {
+                    oid: format!("oid.{}", name),
+                    names: vec![name.to_string()],
+                    description: None,
+                    syntax: AttributeSyntax::DirectoryString,
+                    syntax_oid: None,
+                    syntax_length: None,
+                    superior: None,
+                    equality: Some("caseIgnoreMatch".to_string()),
+                    ordering: None,
+                    substr: None,
+                    single_value: false,
+                    no_user_modification: false,
+                },
+            );
+        }
+        schema
+    }
+
+    #[test]
+    fn test_follow_cross_references() {
+        let mut viewer = SchemaViewer::new(Theme::load("dark"));
+        viewer.show(&schema());
+
+        // person -> its superior, then MUST attributes
+        viewer.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(
+            viewer.stack,
+            vec![SchemaRef::ObjectClass("person".to_string())]
+        );
+        let labels: Vec<_> = viewer.links.iter().map(|l| l.label).collect();
+        assert_eq!(labels, ["Superior", "MUST", "MUST", "MUST", "MAY"]);
+
+        // Follow MUST cn, then the class that requires it
+        viewer.handle_key_event(key(KeyCode::Down));
+        viewer.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(
+            viewer.stack.last(),
+            Some(&SchemaRef::Attribute("cn".to_string()))
+        );
+        assert_eq!(
+            viewer.links[0].target,
+            SchemaRef::ObjectClass("person".to_string())
+        );
+
+        // Back to person, with its links restored
+        viewer.handle_key_event(key(KeyCode::Backspace));
+        assert_eq!(viewer.links.len(), 5);
+        viewer.handle_key_event(key(KeyCode::Backspace));
+        assert!(viewer.stack.is_empty());
+        assert!(viewer.visible);
+    }
+
+    #[test]
+    fn test_usage_search() {
+        let mut viewer = SchemaViewer::new(Theme::load("dark"));
+        viewer.show(&schema());
+        viewer.handle_key_event(key(KeyCode::Tab));
+        let action = viewer.handle_key_event(key(KeyCode::Char('s')));
+        assert!(matches!(action, Action::SearchExecute(f) if f == "(cn=*)"));
+        assert!(!viewer.visible);
+
+        // From a class's details, s searches for the class
+        viewer.show(&schema());
+        viewer.handle_key_event(key(KeyCode::Tab));
+        viewer.handle_key_event(key(KeyCode::Enter));
+        let action = viewer.handle_key_event(key(KeyCode::Char('s')));
+        assert!(matches!(action, Action::SearchExecute(f) if f == "(objectClass=person)"));
+    }
+
+    #[test]
+    fn test_export_prompt() {
+        let mut viewer = SchemaViewer::new(Theme::load("dark"));
+        viewer.show(&schema());
+        viewer.handle_key_event(key(KeyCode::Char('e')));
+        for _ in 0.."ldif".len() {
+            viewer.handle_key_event(key(KeyCode::Backspace));
+        }
+        for c in "md".chars() {
+            viewer.handle_key_event(key(KeyCode::Char(c)));
+        }
+        let action = viewer.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::SchemaExport(p) if p == "schema.md"));
+        assert!(viewer.visible);
+        // Keys go back to the browser
+        viewer.handle_key_event(key(KeyCode::Enter));
+        assert_eq!(viewer.stack.len(), 1);
+    }
+}
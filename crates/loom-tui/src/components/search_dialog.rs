@@ -1,21 +1,77 @@
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
+
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::{Constraint, Layout, Rect};
 use ratatui::style::Modifier;
 use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState};
 use ratatui::Frame;
+use regex_automata::meta::Regex;
+use regex_automata::util::syntax;
 
-use crate::action::Action;
+use crate::action::{Action, ContextMenuSource};
 use crate::theme::Theme;
+use crate::widgets::scroll::scroll_view;
 use loom_core::entry::LdapEntry;
+use loom_core::format::DisplayFormats;
+
+/// Columns shown after the DN by default: attribute and title.
+const DEFAULT_COLUMNS: &[(&str, &str)] = &[
+    ("sAMAccountName", "sAMAccountName"),
+    ("displayName", "Display Name"),
+    ("mail", "Mail"),
+];
+
+/// Search timeouts `t` cycles through, in seconds: the profile's first,
+/// then longer ones; `0` waits as long as the server takes.
+const TIMEOUT_CHOICES: &[Option<u64>] = &[None, Some(120), Some(600), Some(0)];
+
+/// What the results are ordered by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortKey {
+    /// As the server returned them.
+    Server,
+    Dn,
+    /// The column at this index after the DN.
+    Column(usize),
+}
+
+type EntryPredicate<'a> = Box<dyn Fn(&LdapEntry) -> bool + 'a>;
 
 /// The search results panel, shown as an overlay when a search has results.
 pub struct SearchDialog {
     pub visible: bool,
     pub filter: String,
     pub results: Vec<LdapEntry>,
+    /// Attributes shown after the DN, set by defined reports; empty shows
+    /// the default columns.
+    pub columns: Vec<String>,
+    /// Indices of results marked for export.
+    marked: BTreeSet<usize>,
+    /// Indices into `results` in display order; the selection is a
+    /// position in it.
+    order: Vec<usize>,
+    sort: SortKey,
+    descending: bool,
+    /// Show the results under headings for their parent DNs.
+    grouped: bool,
+    /// Results dropped for repeating an earlier DN.
+    duplicates: usize,
+    /// Client-side refinement of the shown results: a substring of the DN
+    /// or any column, or `~` and a regex matched against the DN.
+    refine: String,
+    refine_input: bool,
+    /// Why the refinement's regex doesn't compile; nothing is hidden then.
+    refine_error: Option<String>,
+    /// Timeout for searches run from here in place of the profile's.
+    timeout: Option<u64>,
     table_state: TableState,
+    /// First table row, and how many rows fit, as last drawn.
+    table_offset: std::cell::Cell<usize>,
+    view_height: std::cell::Cell<usize>,
     theme: Theme,
+    formats: DisplayFormats,
 }
 
 impl SearchDialog {
@@ -24,43 +80,242 @@ impl SearchDialog {
             visible: false,
             filter: String::new(),
             results: Vec::new(),
+            columns: Vec::new(),
+            marked: BTreeSet::new(),
+            order: Vec::new(),
+            sort: SortKey::Server,
+            descending: false,
+            grouped: false,
+            duplicates: 0,
+            refine: String::new(),
+            refine_input: false,
+            refine_error: None,
+            timeout: None,
             table_state: TableState::default(),
+            table_offset: std::cell::Cell::new(0),
+            view_height: std::cell::Cell::new(0),
             theme,
+            formats: DisplayFormats::default(),
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
+    /// Render column values with the configured `[display.attributes]` rules.
+    pub fn with_formats(mut self, formats: DisplayFormats) -> Self {
+        self.formats = formats;
+        self
+    }
+
     pub fn show_results(&mut self, filter: String, results: Vec<LdapEntry>) {
         self.filter = filter;
-        self.results = results;
-        self.table_state.select(if self.results.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
+        self.set_results(results);
         self.visible = true;
     }
 
+    /// Replace the results, dropping repeated DNs, in the current order
+    /// with the first row selected.
+    pub fn set_results(&mut self, results: Vec<LdapEntry>) {
+        let count = results.len();
+        let mut seen = HashSet::new();
+        self.results = results
+            .into_iter()
+            .filter(|e| seen.insert(e.dn.to_lowercase()))
+            .collect();
+        self.duplicates = count - self.results.len();
+        self.marked.clear();
+        self.refine.clear();
+        self.refine_input = false;
+        self.sort_results();
+        self.reset_selection();
+    }
+
     pub fn hide(&mut self) {
         self.visible = false;
     }
 
     /// Reset the table selection to the first row (or none if empty).
     pub fn reset_selection(&mut self) {
-        self.table_state.select(if self.results.is_empty() {
-            None
-        } else {
-            Some(0)
-        });
+        self.table_state
+            .select(if self.order.is_empty() { None } else { Some(0) });
     }
 
     pub fn has_results(&self) -> bool {
         !self.results.is_empty()
     }
 
+    /// The popup title: the filter and how many results it found.
+    pub fn title(&self) -> String {
+        let mut counts = if self.order.len() < self.results.len() {
+            format!(
+                "showing {} of {} results",
+                self.order.len(),
+                self.results.len()
+            )
+        } else {
+            format!("{} results", self.results.len())
+        };
+        if self.duplicates > 0 {
+            counts.push_str(&format!(", {} duplicates dropped", self.duplicates));
+        }
+        if !self.marked.is_empty() {
+            counts.push_str(&format!(", {} marked", self.marked.len()));
+        }
+        match self.timeout {
+            Some(0) => counts.push_str(", no timeout"),
+            Some(secs) => counts.push_str(&format!(", timeout {}s", secs)),
+            None => {}
+        }
+        format!(" Search: {} ({}) ", self.filter, counts)
+    }
+
+    /// The timeout chosen for the next search, in seconds (`0` for none),
+    /// or `None` for the profile's.
+    pub fn timeout(&self) -> Option<u64> {
+        self.timeout
+    }
+
+    fn cycle_timeout(&mut self) {
+        let i = TIMEOUT_CHOICES
+            .iter()
+            .position(|&t| t == self.timeout)
+            .unwrap_or(0);
+        self.timeout = TIMEOUT_CHOICES[(i + 1) % TIMEOUT_CHOICES.len()];
+    }
+
+    /// Whether the refinement is being typed, taking every key.
+    pub fn refining(&self) -> bool {
+        self.visible && self.refine_input
+    }
+
+    /// Keys while typing the refinement: `Enter` keeps it, `Esc` drops it.
+    fn handle_refine_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Enter => self.refine_input = false,
+            KeyCode::Esc => {
+                self.refine_input = false;
+                self.refine.clear();
+                self.sort_results();
+            }
+            KeyCode::Backspace => {
+                self.refine.pop();
+                self.sort_results();
+            }
+            KeyCode::Char(c) => {
+                self.refine.push(c);
+                self.sort_results();
+            }
+            _ => {}
+        }
+        Action::None
+    }
+
+    /// A predicate for the results the refinement keeps, or why its regex
+    /// doesn't compile.
+    fn refine_matcher(&self) -> Result<EntryPredicate<'_>, String> {
+        if let Some(pattern) = self.refine.strip_prefix('~') {
+            let regex = Regex::builder()
+                .syntax(syntax::Config::new().case_insensitive(true))
+                .build(pattern)
+                .map_err(|e| e.to_string())?;
+            return Ok(Box::new(move |e: &LdapEntry| regex.is_match(&e.dn)));
+        }
+        let needle = self.refine.to_lowercase();
+        if needle.is_empty() {
+            return Ok(Box::new(|_: &LdapEntry| true));
+        }
+        let columns = self.column_attrs();
+        let formats = &self.formats;
+        Ok(Box::new(move |e: &LdapEntry| {
+            e.dn.to_lowercase().contains(&needle)
+                || columns.iter().any(|attr| {
+                    e.first_value(attr).is_some_and(|value| {
+                        formats
+                            .display(attr, value)
+                            .to_lowercase()
+                            .contains(&needle)
+                    })
+                })
+        }))
+    }
+
+    /// The result at a position of the display order.
+    fn result_at(&self, pos: usize) -> Option<&LdapEntry> {
+        self.order.get(pos).and_then(|&i| self.results.get(i))
+    }
+
+    /// Rebuild the display order for the refinement, sort and grouping,
+    /// keeping the selected result selected if it's still shown.
+    fn sort_results(&mut self) {
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|pos| self.order.get(pos).copied());
+        let (mut order, error) = match self.refine_matcher() {
+            Ok(keep) => {
+                let order = (0..self.results.len())
+                    .filter(|&i| keep(&self.results[i]))
+                    .collect();
+                (order, None)
+            }
+            Err(e) => ((0..self.results.len()).collect::<Vec<usize>>(), Some(e)),
+        };
+        self.refine_error = error;
+        order.sort_by(|&a, &b| self.compare(&self.results[a], &self.results[b]));
+        if self.grouped {
+            // Stable, so each group keeps the order chosen above
+            order.sort_by_cached_key(|&i| parent_key(&self.results[i].dn));
+        }
+        self.order = order;
+        let pos = selected.and_then(|i| self.order.iter().position(|&o| o == i));
+        self.table_state
+            .select(pos.or((!self.order.is_empty()).then_some(0)));
+    }
+
+    fn compare(&self, a: &LdapEntry, b: &LdapEntry) -> Ordering {
+        let (a, b) = match self.sort {
+            SortKey::Server => return Ordering::Equal,
+            SortKey::Dn => (Some(a.dn.as_str()), Some(b.dn.as_str())),
+            SortKey::Column(i) => {
+                let attr = self.column_attrs()[i];
+                (a.first_value(attr), b.first_value(attr))
+            }
+        };
+        match (a, b) {
+            (Some(a), Some(b)) => {
+                let ordering = compare_values(a, b);
+                if self.descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            }
+            // Entries without the value come last either way
+            (a, b) => a.is_none().cmp(&b.is_none()),
+        }
+    }
+
+    /// Sort by the next column: server order, the DN, then each column.
+    fn cycle_sort(&mut self) {
+        let columns = self.column_attrs().len();
+        self.sort = match self.sort {
+            SortKey::Server => SortKey::Dn,
+            SortKey::Dn if columns > 0 => SortKey::Column(0),
+            SortKey::Column(i) if i + 1 < columns => SortKey::Column(i + 1),
+            _ => SortKey::Server,
+        };
+        self.sort_results();
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
         if !self.visible {
             return Action::None;
         }
+        if self.refine_input {
+            return self.handle_refine_key(key);
+        }
 
         match key.code {
             KeyCode::Up | KeyCode::Char('k') => {
@@ -72,7 +327,7 @@ impl SearchDialog {
             }
             KeyCode::Down | KeyCode::Char('j') => {
                 let i = self.table_state.selected().unwrap_or(0);
-                if i + 1 < self.results.len() {
+                if i + 1 < self.order.len() {
                     self.table_state.select(Some(i + 1));
                 }
                 Action::None
@@ -84,26 +339,26 @@ impl SearchDialog {
             }
             KeyCode::PageDown => {
                 let i = self.table_state.selected().unwrap_or(0);
-                let max = self.results.len().saturating_sub(1);
+                let max = self.order.len().saturating_sub(1);
                 self.table_state.select(Some((i + 10).min(max)));
                 Action::None
             }
             KeyCode::Home => {
-                if !self.results.is_empty() {
+                if !self.order.is_empty() {
                     self.table_state.select(Some(0));
                 }
                 Action::None
             }
             KeyCode::End => {
-                if !self.results.is_empty() {
+                if !self.order.is_empty() {
                     self.table_state
-                        .select(Some(self.results.len().saturating_sub(1)));
+                        .select(Some(self.order.len().saturating_sub(1)));
                 }
                 Action::None
             }
             KeyCode::Enter => {
-                if let Some(idx) = self.table_state.selected() {
-                    if let Some(entry) = self.results.get(idx) {
+                if let Some(pos) = self.table_state.selected() {
+                    if let Some(entry) = self.result_at(pos) {
                         let dn = entry.dn.clone();
                         self.visible = false;
                         return Action::TreeSelect(dn);
@@ -111,6 +366,76 @@ impl SearchDialog {
                 }
                 Action::None
             }
+            KeyCode::Char('r') if !self.results.is_empty() => {
+                Action::ShowReportDialog(self.results.iter().map(|e| e.dn.clone()).collect())
+            }
+            KeyCode::Char(' ') => {
+                // Mark or unmark, then move on so runs can be marked quickly
+                if let Some(pos) = self.table_state.selected() {
+                    if let Some(&i) = self.order.get(pos) {
+                        if !self.marked.remove(&i) {
+                            self.marked.insert(i);
+                        }
+                    }
+                    if pos + 1 < self.order.len() {
+                        self.table_state.select(Some(pos + 1));
+                    }
+                }
+                Action::None
+            }
+            KeyCode::Char('s') => {
+                self.cycle_sort();
+                Action::None
+            }
+            KeyCode::Char('S') => {
+                self.descending = !self.descending;
+                self.sort_results();
+                Action::None
+            }
+            KeyCode::Char('t') => {
+                self.cycle_timeout();
+                Action::None
+            }
+            KeyCode::Char('g') => {
+                self.grouped = !self.grouped;
+                self.sort_results();
+                Action::None
+            }
+            KeyCode::Char('f') if !self.results.is_empty() => {
+                self.refine_input = true;
+                Action::None
+            }
+            KeyCode::Char('x') if !self.refine.is_empty() => {
+                self.refine.clear();
+                self.sort_results();
+                Action::None
+            }
+            KeyCode::Char('e') => {
+                let dns = self.selected_dns();
+                if dns.is_empty() {
+                    Action::None
+                } else {
+                    Action::ShowExportEntriesDialog(dns)
+                }
+            }
+            KeyCode::Char('E') if !self.results.is_empty() => Action::ExportSearchResults,
+            KeyCode::Char('c') if self.has_results() => Action::CopyLdapSearch,
+            KeyCode::Char('m') => match self.table_state.selected().and_then(|p| self.result_at(p))
+            {
+                Some(entry) => Action::ShowContextMenu(ContextMenuSource::SearchResult {
+                    dn: entry.dn.clone(),
+                    columns: self.columns.clone(),
+                }),
+                None => Action::None,
+            },
+            KeyCode::Char('b') => {
+                let dns = self.selected_dns();
+                if dns.is_empty() {
+                    Action::None
+                } else {
+                    Action::ShowBatchEdit(dns)
+                }
+            }
             KeyCode::Esc | KeyCode::Char('q') => {
                 self.hide();
                 Action::ClosePopup
@@ -119,70 +444,273 @@ impl SearchDialog {
         }
     }
 
+    /// DNs of the marked results, or of the highlighted one if none are marked.
+    pub fn selected_dns(&self) -> Vec<String> {
+        if self.marked.is_empty() {
+            return self
+                .table_state
+                .selected()
+                .and_then(|pos| self.result_at(pos))
+                .map(|e| vec![e.dn.clone()])
+                .unwrap_or_default();
+        }
+        self.marked
+            .iter()
+            .filter_map(|&i| self.results.get(i))
+            .map(|e| e.dn.clone())
+            .collect()
+    }
+
+    /// What each table row shows: a result's position in `order`, or
+    /// `None` for a group heading.
+    fn row_positions(&self) -> Vec<Option<usize>> {
+        let mut rows = Vec::with_capacity(self.order.len());
+        let mut group: Option<String> = None;
+        for (pos, &i) in self.order.iter().enumerate() {
+            if self.grouped {
+                let parent = loom_core::dn::parent_dn(&self.results[i].dn)
+                    .unwrap_or_default()
+                    .to_lowercase();
+                if group.as_deref() != Some(&parent) {
+                    rows.push(None);
+                    group = Some(parent);
+                }
+            }
+            rows.push(Some(pos));
+        }
+        rows
+    }
+
+    /// Scroll the results `delta` rows with the mouse wheel. The selection
+    /// stays put unless it would go off screen; it then moves to the
+    /// nearest result on screen, never a group heading.
+    pub fn scroll(&mut self, delta: isize) {
+        let rows = self.row_positions();
+        let selected = self
+            .table_state
+            .selected()
+            .and_then(|pos| rows.iter().position(|r| *r == Some(pos)));
+        let height = self.view_height.get();
+        let (offset, row) =
+            scroll_view(self.table_offset.get(), selected, rows.len(), delta, |_| {
+                height
+            });
+        self.table_offset.set(offset);
+        let Some(row) = row.filter(|r| Some(*r) != selected) else {
+            return;
+        };
+        let at = |r: usize| rows.get(r).copied().flatten();
+        let pos = at(row).or_else(|| {
+            if Some(row) > selected {
+                at(row + 1)
+            } else {
+                row.checked_sub(1).and_then(at)
+            }
+        });
+        if pos.is_some() {
+            self.table_state.select(pos);
+        }
+    }
+
+    /// The table rows in display order, with a heading before each group
+    /// when grouped, and the row of the selected result.
+    fn result_rows(&self) -> (Vec<Row<'_>>, Option<usize>) {
+        let selected = self.table_state.selected();
+        let mut rows = Vec::with_capacity(self.order.len());
+        let mut selected_row = None;
+        let mut group: Option<String> = None;
+        for (pos, &i) in self.order.iter().enumerate() {
+            let entry = &self.results[i];
+            let parent = loom_core::dn::parent_dn(&entry.dn).unwrap_or_default();
+            if self.grouped && group.as_deref() != Some(&parent.to_lowercase()) {
+                let size = self.order[pos..]
+                    .iter()
+                    .take_while(|&&j| {
+                        loom_core::dn::parent_dn(&self.results[j].dn)
+                            .unwrap_or_default()
+                            .eq_ignore_ascii_case(parent)
+                    })
+                    .count();
+                let mut heading = vec![Span::styled("\u{25be} ", self.theme.header)];
+                heading.extend(self.theme.dn_spans(parent));
+                heading.push(Span::styled(format!(" ({})", size), self.theme.dimmed));
+                rows.push(Row::new(vec![Cell::from(Line::from(heading))]));
+                group = Some(parent.to_lowercase());
+            }
+            if selected == Some(pos) {
+                selected_row = Some(rows.len());
+            }
+
+            let column = |attr: &str| {
+                let value = entry.first_value(attr).unwrap_or("");
+                Cell::from(Span::styled(
+                    self.formats.display(attr, value),
+                    self.theme.normal,
+                ))
+            };
+            let marker = if self.marked.contains(&i) { "* " } else { "  " };
+            let mut dn_spans = vec![Span::styled(marker, self.theme.header)];
+            if self.grouped {
+                // The heading already shows the rest of the DN
+                dn_spans.push(Span::raw("  "));
+                dn_spans.extend(self.theme.dn_spans(loom_core::dn::rdn(&entry.dn)));
+            } else {
+                dn_spans.extend(self.theme.dn_spans(&entry.dn));
+            }
+            let mut cells = vec![Cell::from(Line::from(dn_spans))];
+            cells.extend(self.column_attrs().into_iter().map(column));
+            rows.push(Row::new(cells));
+        }
+        (rows, selected_row)
+    }
+
+    /// The arrow marking a column as the sort key, if it is one.
+    fn sort_arrow(&self, key: SortKey) -> &'static str {
+        match (self.sort == key, self.descending) {
+            (false, _) => "",
+            (true, false) => " \u{25b2}",
+            (true, true) => " \u{25bc}",
+        }
+    }
+
+    /// The attributes of the columns after the DN.
+    fn column_attrs(&self) -> Vec<&str> {
+        if self.columns.is_empty() {
+            DEFAULT_COLUMNS.iter().map(|(attr, _)| *attr).collect()
+        } else {
+            self.columns.iter().map(String::as_str).collect()
+        }
+    }
+
+    /// The results table, and the table state selecting the selected
+    /// result's row.
+    fn results_table(&self) -> (Table<'_>, TableState) {
+        let title = |i: usize, title: &str| {
+            let title = format!("{}{}", title, self.sort_arrow(SortKey::Column(i)));
+            Cell::from(Span::styled(title, self.theme.header))
+        };
+        let dn_title = format!("DN{}", self.sort_arrow(SortKey::Dn));
+        let mut header = vec![Cell::from(Span::styled(dn_title, self.theme.header))];
+        let widths: Vec<Constraint> = if self.columns.is_empty() {
+            header.extend(
+                DEFAULT_COLUMNS
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (_, t))| title(i, t)),
+            );
+            [40, 15, 20, 25].map(Constraint::Percentage).to_vec()
+        } else {
+            header.extend(
+                self.columns
+                    .iter()
+                    .enumerate()
+                    .map(|(i, attr)| title(i, attr)),
+            );
+            let share = 60 / self.columns.len() as u16;
+            std::iter::once(Constraint::Percentage(40))
+                .chain(self.columns.iter().map(|_| Constraint::Percentage(share)))
+                .collect()
+        };
+
+        let (rows, selected) = self.result_rows();
+        let table = Table::new(rows, widths)
+            .header(Row::new(header).style(self.theme.header))
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+        let state = TableState::default()
+            .with_offset(self.table_offset.get())
+            .with_selected(selected);
+        (table, state)
+    }
+
+    fn refine_height(&self) -> u16 {
+        u16::from(self.refine_input || !self.refine.is_empty())
+    }
+
+    /// The refinement being typed or applied, with how many results it shows.
+    fn refine_line(&self) -> Line<'_> {
+        let mut spans = vec![
+            Span::styled("  Refine: ", self.theme.header),
+            Span::styled(self.refine.as_str(), self.theme.normal),
+        ];
+        if self.refine_input {
+            spans.push(Span::styled("_", self.theme.command_prompt));
+        }
+        match &self.refine_error {
+            Some(error) => spans.push(Span::styled(
+                format!("  invalid regex: {}", error),
+                self.theme.error,
+            )),
+            None => spans.push(Span::styled(
+                format!("  showing {} of {}", self.order.len(), self.results.len()),
+                self.theme.dimmed,
+            )),
+        }
+        let keys = if self.refine_input {
+            "  Enter: keep  Esc: clear"
+        } else {
+            "  f: edit  x: reset"
+        };
+        spans.push(Span::styled(keys, self.theme.dimmed));
+        Line::from(spans)
+    }
+
     /// Render just the results table into a given area (no popup chrome, no Clear).
     pub fn render_results(&mut self, frame: &mut Frame, area: Rect) {
         if self.results.is_empty() {
-            let msg = Paragraph::new("No results found.").style(self.theme.dimmed);
+            let msg = Paragraph::new("No results found.  t: change the search timeout")
+                .style(self.theme.dimmed);
             frame.render_widget(msg, area);
             return;
         }
 
-        // Layout: hint (1 line) | results table
-        let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(area);
+        // Layout: hint (1 line) | refinement (while set) | results table
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(self.refine_height()),
+            Constraint::Min(1),
+        ])
+        .split(area);
 
         let hint = Line::from(vec![
             Span::styled("  \u{2191}/\u{2193}", self.theme.header),
             Span::styled(": navigate  ", self.theme.dimmed),
             Span::styled("Enter", self.theme.header),
             Span::styled(": select  ", self.theme.dimmed),
+            Span::styled("Space", self.theme.header),
+            Span::styled(": mark  ", self.theme.dimmed),
+            Span::styled("e", self.theme.header),
+            Span::styled(": export  ", self.theme.dimmed),
+            Span::styled("E", self.theme.header),
+            Span::styled(": export all  ", self.theme.dimmed),
+            Span::styled("r", self.theme.header),
+            Span::styled(": report  ", self.theme.dimmed),
+            Span::styled("b", self.theme.header),
+            Span::styled(": batch edit  ", self.theme.dimmed),
+            Span::styled("c", self.theme.header),
+            Span::styled(": copy as ldapsearch  ", self.theme.dimmed),
+            Span::styled("m", self.theme.header),
+            Span::styled(": menu  ", self.theme.dimmed),
+            Span::styled("s/S", self.theme.header),
+            Span::styled(": sort/reverse  ", self.theme.dimmed),
+            Span::styled("g", self.theme.header),
+            Span::styled(": group  ", self.theme.dimmed),
+            Span::styled("f", self.theme.header),
+            Span::styled(": refine  ", self.theme.dimmed),
+            Span::styled("t", self.theme.header),
+            Span::styled(": timeout  ", self.theme.dimmed),
             Span::styled("Esc", self.theme.header),
             Span::styled(": close  ", self.theme.dimmed),
             Span::styled("/", self.theme.header),
             Span::styled(": edit filter", self.theme.dimmed),
         ]);
         frame.render_widget(Paragraph::new(hint), layout[0]);
+        frame.render_widget(Paragraph::new(self.refine_line()), layout[1]);
 
-        let header = Row::new(vec![
-            Cell::from(Span::styled("DN", self.theme.header)),
-            Cell::from(Span::styled("sAMAccountName", self.theme.header)),
-            Cell::from(Span::styled("Display Name", self.theme.header)),
-            Cell::from(Span::styled("Mail", self.theme.header)),
-        ]);
-
-        let rows: Vec<Row> = self
-            .results
-            .iter()
-            .map(|entry| {
-                Row::new(vec![
-                    Cell::from(Span::styled(&entry.dn, self.theme.normal)),
-                    Cell::from(Span::styled(
-                        entry.first_value("sAMAccountName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("displayName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("mail").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                ])
-            })
-            .collect();
-
-        let widths = [
-            Constraint::Percentage(40),
-            Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(25),
-        ];
-
-        let table = Table::new(rows, widths)
-            .header(header.style(self.theme.header))
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
-
-        frame.render_stateful_widget(table, layout[1], &mut self.table_state.clone());
+        let (table, mut state) = self.results_table();
+        frame.render_stateful_widget(table, layout[2], &mut state);
+        self.table_offset.set(state.offset());
+        self.view_height
+            .set(layout[2].height.saturating_sub(1) as usize);
     }
 
     pub fn render(&self, frame: &mut Frame, full: Rect) {
@@ -199,9 +727,8 @@ impl SearchDialog {
 
         frame.render_widget(Clear, area);
 
-        let title = format!(" Search: {} ({} results) ", self.filter, self.results.len());
         let block = Block::default()
-            .title(title)
+            .title(self.title())
             .borders(Borders::ALL)
             .border_style(self.theme.popup_border)
             .title_style(self.theme.popup_title);
@@ -215,59 +742,254 @@ impl SearchDialog {
             return;
         }
 
-        // Layout: hint (1 line) | results table
-        let layout = Layout::vertical([Constraint::Length(1), Constraint::Min(1)]).split(inner);
+        // Layout: hint (1 line) | refinement (while set) | results table
+        let layout = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Length(self.refine_height()),
+            Constraint::Min(1),
+        ])
+        .split(inner);
 
         let hint = Line::from(vec![
             Span::styled("  \u{2191}/\u{2193}", self.theme.header),
             Span::styled(": navigate  ", self.theme.dimmed),
             Span::styled("Enter", self.theme.header),
             Span::styled(": select  ", self.theme.dimmed),
+            Span::styled("Space", self.theme.header),
+            Span::styled(": mark  ", self.theme.dimmed),
+            Span::styled("e", self.theme.header),
+            Span::styled(": export  ", self.theme.dimmed),
+            Span::styled("E", self.theme.header),
+            Span::styled(": export all  ", self.theme.dimmed),
+            Span::styled("r", self.theme.header),
+            Span::styled(": report  ", self.theme.dimmed),
+            Span::styled("b", self.theme.header),
+            Span::styled(": batch edit  ", self.theme.dimmed),
+            Span::styled("c", self.theme.header),
+            Span::styled(": copy as ldapsearch  ", self.theme.dimmed),
+            Span::styled("m", self.theme.header),
+            Span::styled(": menu  ", self.theme.dimmed),
+            Span::styled("s/S", self.theme.header),
+            Span::styled(": sort/reverse  ", self.theme.dimmed),
+            Span::styled("g", self.theme.header),
+            Span::styled(": group  ", self.theme.dimmed),
+            Span::styled("f", self.theme.header),
+            Span::styled(": refine  ", self.theme.dimmed),
             Span::styled("Esc", self.theme.header),
             Span::styled(": close", self.theme.dimmed),
         ]);
         frame.render_widget(Paragraph::new(hint), layout[0]);
+        frame.render_widget(Paragraph::new(self.refine_line()), layout[1]);
 
-        let header = Row::new(vec![
-            Cell::from(Span::styled("DN", self.theme.header)),
-            Cell::from(Span::styled("sAMAccountName", self.theme.header)),
-            Cell::from(Span::styled("Display Name", self.theme.header)),
-            Cell::from(Span::styled("Mail", self.theme.header)),
-        ]);
+        let (table, mut state) = self.results_table();
+        frame.render_stateful_widget(table, layout[2], &mut state);
+        self.table_offset.set(state.offset());
+        self.view_height
+            .set(layout[2].height.saturating_sub(1) as usize);
+    }
+}
+
+/// Orders values as numbers when both are, otherwise ignoring case.
+fn compare_values(a: &str, b: &str) -> Ordering {
+    match (a.parse::<i64>(), b.parse::<i64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        _ => a.to_lowercase().cmp(&b.to_lowercase()),
+    }
+}
+
+/// The components of a DN's parent from the top down, so sorted groups
+/// follow the tree's hierarchy.
+fn parent_key(dn: &str) -> Vec<String> {
+    let parent = loom_core::dn::parent_dn(dn).unwrap_or_default();
+    parent
+        .to_lowercase()
+        .rsplit(',')
+        .map(str::to_string)
+        .collect()
+}
 
-        let rows: Vec<Row> = self
-            .results
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_mark_and_export() {
+        let mut dialog = SearchDialog::new(Theme::default());
+        let results = ["cn=a,dc=x", "cn=b,dc=x", "cn=c,dc=x"]
             .iter()
-            .map(|entry| {
-                Row::new(vec![
-                    Cell::from(Span::styled(&entry.dn, self.theme.normal)),
-                    Cell::from(Span::styled(
-                        entry.first_value("sAMAccountName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("displayName").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                    Cell::from(Span::styled(
-                        entry.first_value("mail").unwrap_or(""),
-                        self.theme.normal,
-                    )),
-                ])
-            })
+            .map(|dn| LdapEntry::new(dn.to_string(), Default::default()))
             .collect();
+        dialog.show_results("(cn=*)".to_string(), results);
 
-        let widths = [
-            Constraint::Percentage(40),
-            Constraint::Percentage(15),
-            Constraint::Percentage(20),
-            Constraint::Percentage(25),
-        ];
+        // Nothing marked: the highlighted entry
+        assert_eq!(dialog.selected_dns(), vec!["cn=a,dc=x"]);
 
-        let table = Table::new(rows, widths)
-            .header(header.style(self.theme.header))
-            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        dialog.handle_key_event(key(KeyCode::Down));
+        dialog.handle_key_event(key(KeyCode::Char(' ')));
+        assert!(matches!(
+            dialog.handle_key_event(key(KeyCode::Char('e'))),
+            Action::ShowExportEntriesDialog(dns) if dns == vec!["cn=a,dc=x", "cn=c,dc=x"]
+        ));
+
+        // New results start unmarked
+        dialog.show_results("(cn=a)".to_string(), Vec::new());
+        assert!(dialog.selected_dns().is_empty());
+    }
+
+    #[test]
+    fn test_sort_and_group() {
+        let mut dialog = SearchDialog::new(Theme::default());
+        dialog.columns = vec!["uidNumber".to_string()];
+        let results = [
+            ("uid=b,ou=Staff,dc=x", "100"),
+            ("uid=a,ou=People,dc=x", "20"),
+            ("UID=B,ou=Staff,dc=x", "5"),
+            ("uid=c,ou=People,dc=x", "3"),
+        ]
+        .iter()
+        .map(|(dn, uid)| {
+            let attrs = [("uidNumber".to_string(), vec![uid.to_string()])].into();
+            LdapEntry::new(dn.to_string(), attrs)
+        })
+        .collect();
+        dialog.show_results("(uid=*)".to_string(), results);
+        assert_eq!(dialog.results.len(), 3);
+        assert!(dialog.title().contains("1 duplicates dropped"));
+        let shown = |d: &SearchDialog| -> Vec<String> {
+            (0..d.order.len())
+                .map(|pos| d.result_at(pos).unwrap().dn.clone())
+                .collect()
+        };
+
+        // The selection follows its entry through each sort
+        dialog.handle_key_event(key(KeyCode::Char('s')));
+        assert_eq!(
+            shown(&dialog),
+            [
+                "uid=a,ou=People,dc=x",
+                "uid=b,ou=Staff,dc=x",
+                "uid=c,ou=People,dc=x"
+            ]
+        );
+        assert_eq!(dialog.selected_dns(), ["uid=b,ou=Staff,dc=x"]);
+        // Numerically by the column, descending
+        dialog.handle_key_event(key(KeyCode::Char('s')));
+        dialog.handle_key_event(key(KeyCode::Char('S')));
+        assert_eq!(
+            shown(&dialog),
+            [
+                "uid=b,ou=Staff,dc=x",
+                "uid=a,ou=People,dc=x",
+                "uid=c,ou=People,dc=x"
+            ]
+        );
+
+        // Grouped under the parents, each group still sorted
+        dialog.handle_key_event(key(KeyCode::Char('g')));
+        assert_eq!(
+            shown(&dialog),
+            [
+                "uid=a,ou=People,dc=x",
+                "uid=c,ou=People,dc=x",
+                "uid=b,ou=Staff,dc=x"
+            ]
+        );
+        let (rows, selected) = dialog.result_rows();
+        assert_eq!(rows.len(), 5);
+        assert_eq!(selected, Some(4));
+
+        // With two rows on screen, scrolling pushes the selection from a to
+        // c, then past the Staff heading onto b, and back up onto c
+        dialog.view_height.set(2);
+        dialog.table_state.select(Some(0));
+        dialog.scroll(2);
+        assert_eq!(dialog.table_offset.get(), 2);
+        assert_eq!(dialog.selected_dns(), ["uid=c,ou=People,dc=x"]);
+        dialog.scroll(2);
+        assert_eq!(dialog.table_offset.get(), 3);
+        assert_eq!(dialog.selected_dns(), ["uid=b,ou=Staff,dc=x"]);
+        dialog.scroll(-1);
+        assert_eq!(dialog.table_offset.get(), 2);
+        assert_eq!(dialog.selected_dns(), ["uid=c,ou=People,dc=x"]);
+    }
+
+    #[test]
+    fn test_timeout_override() {
+        let mut dialog = SearchDialog::new(Theme::default());
+        dialog.show_results("(cn=*)".to_string(), Vec::new());
+        assert_eq!(dialog.timeout(), None);
+
+        dialog.handle_key_event(key(KeyCode::Char('t')));
+        assert_eq!(dialog.timeout(), Some(120));
+        assert!(
+            dialog.title().contains("timeout 120s"),
+            "{}",
+            dialog.title()
+        );
+        dialog.handle_key_event(key(KeyCode::Char('t')));
+        dialog.handle_key_event(key(KeyCode::Char('t')));
+        assert_eq!(dialog.timeout(), Some(0));
+        assert!(dialog.title().contains("no timeout"));
+        // Back to the profile's
+        dialog.handle_key_event(key(KeyCode::Char('t')));
+        assert_eq!(dialog.timeout(), None);
+    }
+
+    #[test]
+    fn test_refine() {
+        let mut dialog = SearchDialog::new(Theme::default());
+        dialog.columns = vec!["uidNumber".to_string()];
+        let results = [
+            ("uid=a,ou=People,dc=x", "20"),
+            ("uid=b,ou=Staff,dc=x", "100"),
+            ("uid=c,ou=People,dc=x", "3"),
+        ]
+        .iter()
+        .map(|(dn, uid)| {
+            let attrs = [("uidNumber".to_string(), vec![uid.to_string()])].into();
+            LdapEntry::new(dn.to_string(), attrs)
+        })
+        .collect();
+        dialog.show_results("(uid=*)".to_string(), results);
+        let shown = |d: &SearchDialog| -> Vec<String> {
+            (0..d.order.len())
+                .map(|pos| d.result_at(pos).unwrap().dn.clone())
+                .collect()
+        };
+
+        // A substring of a column, then a regex on the DN
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        assert!(dialog.refining());
+        for c in "10".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        dialog.handle_key_event(key(KeyCode::Enter));
+        assert!(!dialog.refining());
+        assert_eq!(shown(&dialog), ["uid=b,ou=Staff,dc=x"]);
+        assert!(dialog.title().contains("showing 1 of 3 results"));
+        dialog.handle_key_event(key(KeyCode::Char('x')));
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        for c in "~^uid=[ac],OU=people".chars() {
+            dialog.handle_key_event(key(KeyCode::Char(c)));
+        }
+        assert_eq!(
+            shown(&dialog),
+            ["uid=a,ou=People,dc=x", "uid=c,ou=People,dc=x"]
+        );
+        dialog.handle_key_event(key(KeyCode::Esc));
+        assert_eq!(shown(&dialog).len(), 3);
 
-        frame.render_stateful_widget(table, layout[1], &mut self.table_state.clone());
+        // An invalid regex hides nothing
+        dialog.handle_key_event(key(KeyCode::Char('f')));
+        dialog.handle_key_event(key(KeyCode::Char('~')));
+        dialog.handle_key_event(key(KeyCode::Char('(')));
+        assert!(dialog.refine_error.is_some());
+        assert_eq!(shown(&dialog).len(), 3);
     }
 }
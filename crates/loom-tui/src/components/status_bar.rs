@@ -7,12 +7,20 @@ use crate::component::Component;
 use crate::keymap::Keymap;
 use crate::theme::Theme;
 
+/// Frames of the activity spinner, advanced once per tick.
+const SPINNER: &[char] = &['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
 /// Bottom status bar showing connection info (left) and keybinding hints (right).
 pub struct StatusBar {
     pub connection_info: String,
     pub entry_count: Option<usize>,
+    /// Extra `attr: value` segments shown after the connection info.
+    pub segments: Vec<String>,
     pub message: Option<String>,
     pub message_is_error: bool,
+    /// Tabs with operations in flight, and how many.
+    activity: Vec<(String, usize)>,
+    spinner_frame: usize,
     theme: Theme,
     hints: String,
 }
@@ -28,20 +36,39 @@ impl StatusBar {
         Self {
             connection_info: String::new(),
             entry_count: None,
+            segments: Vec::new(),
             message: None,
             message_is_error: false,
+            activity: Vec::new(),
+            spinner_frame: 0,
             theme,
             hints,
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn set_connected(&mut self, host: &str, server_type: &str) {
         self.connection_info = format!("{} ({})", host, server_type);
+        self.segments.clear();
+    }
+
+    /// Show RootDSE values next to the connection info, skipping attributes
+    /// the server didn't return.
+    pub fn set_server_info(&mut self, info: &[(String, Vec<String>)]) {
+        self.segments = info
+            .iter()
+            .filter(|(_, vals)| !vals.is_empty())
+            .map(|(attr, vals)| format!("{}: {}", attr, vals.join(", ")))
+            .collect();
     }
 
     pub fn set_disconnected(&mut self) {
         self.connection_info = String::new();
         self.entry_count = None;
+        self.segments.clear();
     }
 
     pub fn set_message(&mut self, text: String) {
@@ -53,6 +80,34 @@ impl StatusBar {
         self.message = Some(text);
         self.message_is_error = true;
     }
+
+    /// Update the in-flight operation counts, called once per tick. The
+    /// spinner turns while anything is running.
+    pub fn set_activity(&mut self, activity: Vec<(String, usize)>) {
+        if activity.is_empty() {
+            self.spinner_frame = 0;
+        } else {
+            self.spinner_frame = (self.spinner_frame + 1) % SPINNER.len();
+        }
+        self.activity = activity;
+    }
+
+    /// The spinner followed by each busy tab's count, e.g. `⠹ prod 2 · dev 1`.
+    fn activity_text(&self) -> String {
+        if self.activity.is_empty() {
+            return String::new();
+        }
+        let counts: Vec<String> = self
+            .activity
+            .iter()
+            .map(|(label, count)| format!("{} {}", label, count))
+            .collect();
+        format!(
+            " {} {} ",
+            SPINNER[self.spinner_frame],
+            counts.join(" \u{b7} ")
+        )
+    }
 }
 
 impl Component for StatusBar {
@@ -63,9 +118,12 @@ impl Component for StatusBar {
         let left = if self.connection_info.is_empty() {
             String::new()
         } else {
+            use std::fmt::Write;
             let mut s = format!(" {}", self.connection_info);
+            for segment in &self.segments {
+                let _ = write!(s, " | {}", segment);
+            }
             if let Some(count) = self.entry_count {
-                use std::fmt::Write;
                 let _ = write!(s, " | {} entries", count);
             }
             s
@@ -83,12 +141,13 @@ impl Component for StatusBar {
             self.theme.status_bar
         };
 
-        // Right side: keybinding hints (with trailing space)
+        // Right side: activity spinner, then keybinding hints (with trailing space)
+        let activity = self.activity_text();
         let right = format!("{} ", self.hints);
 
         let left_len = left.len();
         let mid_len = mid.len();
-        let right_len = right.len();
+        let right_len = activity.chars().count() + right.len();
         let gap = width.saturating_sub(left_len + mid_len + right_len);
         let padding = " ".repeat(gap);
 
@@ -96,9 +155,28 @@ impl Component for StatusBar {
             Span::styled(left, self.theme.status_bar),
             Span::styled(mid, mid_style),
             Span::styled(padding, self.theme.status_bar),
+            Span::styled(activity, self.theme.warning),
             Span::styled(right, self.theme.status_bar),
         ]);
         let bar = Paragraph::new(line);
         frame.render_widget(bar, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_text() {
+        let mut bar = StatusBar::new(Theme::load("dark"), &Keymap::default());
+        bar.set_activity(Vec::new());
+        assert_eq!(bar.activity_text(), "");
+
+        bar.set_activity(vec![("prod".to_string(), 2), ("dev".to_string(), 1)]);
+        assert_eq!(bar.activity_text(), " \u{2819} prod 2 \u{b7} dev 1 ");
+        // The spinner moves on each tick
+        bar.set_activity(vec![("prod".to_string(), 1)]);
+        assert_eq!(bar.activity_text(), " \u{2839} prod 1 ");
+    }
+}
@@ -29,6 +29,10 @@ impl TabBar {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+    }
+
     pub fn add_tab(&mut self, id: ConnectionId, label: String) {
         self.tabs.push(TabEntry { id, label });
         self.active_tab = Some(id);
@@ -41,6 +45,12 @@ impl TabBar {
         }
     }
 
+    pub fn set_label(&mut self, id: ConnectionId, label: String) {
+        if let Some(tab) = self.tabs.iter_mut().find(|t| t.id == id) {
+            tab.label = label;
+        }
+    }
+
     pub fn set_active(&mut self, id: ConnectionId) {
         if self.tabs.iter().any(|t| t.id == id) {
             self.active_tab = Some(id);
@@ -0,0 +1,302 @@
+use std::time::Duration;
+
+use crossterm::event::{KeyCode, KeyEvent};
+use loom_core::trace::{OperationTrace, TraceRecord};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, Paragraph, Wrap};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::theme::Theme;
+
+/// Requests taking at least this long are highlighted as slow.
+const SLOW: Duration = Duration::from_secs(1);
+
+/// A toggleable view of the active tab's LDAP operation trace: every
+/// request with its result code, duration and entry count.
+pub struct TracePanel {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    selected: usize,
+    /// Only list requests that failed.
+    failures_only: bool,
+}
+
+impl TracePanel {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Operation Trace", theme.clone()).with_size(90, 70),
+            theme,
+            selected: 0,
+            failures_only: false,
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        if self.visible {
+            self.popup.show();
+            self.selected = 0;
+        } else {
+            self.popup.hide();
+        }
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    /// Records newest first, as listed.
+    fn listed(&self, trace: Option<&OperationTrace>) -> Vec<TraceRecord> {
+        let mut records = trace.map(OperationTrace::records).unwrap_or_default();
+        if self.failures_only {
+            records.retain(TraceRecord::failed);
+        }
+        records.reverse();
+        records
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent, trace: Option<&OperationTrace>) -> Action {
+        let records = self.listed(trace);
+        self.selected = self.selected.min(records.len().saturating_sub(1));
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.selected = self.selected.saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                if self.selected + 1 < records.len() {
+                    self.selected += 1;
+                }
+                Action::None
+            }
+            KeyCode::Home | KeyCode::Char('g') => {
+                self.selected = 0;
+                Action::None
+            }
+            KeyCode::End | KeyCode::Char('G') => {
+                self.selected = records.len().saturating_sub(1);
+                Action::None
+            }
+            KeyCode::Char('f') => {
+                self.failures_only = !self.failures_only;
+                self.selected = 0;
+                Action::None
+            }
+            // Saved oldest first, like a log
+            KeyCode::Char('s') if !records.is_empty() => {
+                Action::SaveTrace(records.iter().rev().map(|r| r.to_line() + "\n").collect())
+            }
+            KeyCode::Char('C') => Action::ClearTrace,
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&self, frame: &mut Frame, full: Rect, trace: Option<&OperationTrace>) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let records = self.listed(trace);
+        let failed = records.iter().filter(|r| r.failed()).count();
+        let shown = if self.failures_only {
+            " | failures only"
+        } else {
+            ""
+        };
+        let block = Block::default()
+            .title(format!(
+                " Operation Trace ({} requests, {} failed{}) ",
+                records.len(),
+                failed,
+                shown
+            ))
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        // Layout: requests | selected request's details (4) | hints (1)
+        let layout = Layout::vertical([
+            Constraint::Min(1),
+            Constraint::Length(4),
+            Constraint::Length(1),
+        ])
+        .split(inner);
+
+        if records.is_empty() {
+            let text = if trace.is_none() {
+                "No LDAP connection on this tab."
+            } else {
+                "No requests recorded yet."
+            };
+            frame.render_widget(
+                Paragraph::new(Line::from(Span::styled(text, self.theme.dimmed))),
+                layout[0],
+            );
+        }
+
+        let height = layout[0].height as usize;
+        let offset = (self.selected + 1).saturating_sub(height);
+        let lines: Vec<Line> = records
+            .iter()
+            .enumerate()
+            .skip(offset)
+            .take(height)
+            .map(|(i, record)| {
+                let result = match record.result_code {
+                    Some(rc) => format!("rc={:<3}", rc),
+                    None => "error ".to_string(),
+                };
+                let result_style = if record.failed() {
+                    self.theme.error
+                } else {
+                    self.theme.success
+                };
+                let duration_style = if record.duration >= SLOW {
+                    self.theme.warning
+                } else {
+                    self.theme.dimmed
+                };
+                let dn_style = if i == self.selected {
+                    self.theme.selected.add_modifier(Modifier::BOLD)
+                } else {
+                    self.theme.normal
+                };
+                let entries = record
+                    .entries
+                    .map(|n| format!("{:>6}", n))
+                    .unwrap_or_else(|| " ".repeat(6));
+                let mut spans = vec![
+                    Span::styled(
+                        format!("{} ", record.time.format("%H:%M:%S%.3f")),
+                        self.theme.dimmed,
+                    ),
+                    Span::styled(format!("{:<7}", record.op.label()), self.theme.header),
+                    Span::styled(result, result_style),
+                    Span::styled(
+                        format!("{:>7}ms", record.duration.as_millis()),
+                        duration_style,
+                    ),
+                    Span::styled(entries, self.theme.normal),
+                    Span::styled("  ", self.theme.normal),
+                    Span::styled(display_dn(&record.dn), dn_style),
+                ];
+                if let Some(filter) = &record.filter {
+                    spans.push(Span::styled(format!("  {}", filter), self.theme.dimmed));
+                }
+                Line::from(spans)
+            })
+            .collect();
+        frame.render_widget(Paragraph::new(lines), layout[0]);
+
+        let detail = records
+            .get(self.selected)
+            .map(|record| self.detail_lines(record))
+            .unwrap_or_default();
+        frame.render_widget(Paragraph::new(detail).wrap(Wrap { trim: true }), layout[1]);
+
+        let hints = Line::from(Span::styled(
+            "\u{2191}/\u{2193}:select  f:failures only  s:save  C:clear  q:close",
+            self.theme.dimmed,
+        ));
+        frame.render_widget(Paragraph::new(hints), layout[2]);
+    }
+
+    fn detail_lines(&self, record: &TraceRecord) -> Vec<Line<'static>> {
+        let mut request = format!("{} {}", record.op.label(), display_dn(&record.dn));
+        if let Some(scope) = record.scope {
+            request.push_str(&format!("  scope={}", scope));
+        }
+        if !record.attributes.is_empty() {
+            request.push_str(&format!("  attrs={}", record.attributes.join(",")));
+        }
+        if !record.controls.is_empty() {
+            request.push_str(&format!("  controls={}", record.controls.join(",")));
+        }
+        let mut lines = vec![Line::from(Span::styled(request, self.theme.normal))];
+        if !record.message.is_empty() {
+            let style = if record.failed() {
+                self.theme.error
+            } else {
+                self.theme.dimmed
+            };
+            lines.push(Line::from(Span::styled(record.message.clone(), style)));
+        }
+        lines
+    }
+}
+
+/// The DN, or `(root)` for the RootDSE and anonymous binds.
+fn display_dn(dn: &str) -> String {
+    if dn.is_empty() {
+        "(root)".to_string()
+    } else {
+        dn.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crossterm::event::KeyModifiers;
+    use loom_core::trace::TraceOp;
+
+    use super::*;
+
+    fn key(c: char) -> KeyEvent {
+        KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)
+    }
+
+    fn record(trace: &OperationTrace, dn: &str, rc: u32) {
+        let mut record = TraceRecord::new(TraceOp::Delete, dn);
+        record.result_code = Some(rc);
+        trace.push(record);
+    }
+
+    #[test]
+    fn test_failures_only_and_save() {
+        let trace = OperationTrace::default();
+        record(&trace, "cn=a,dc=x", 0);
+        record(&trace, "cn=b,dc=x", 50);
+        record(&trace, "cn=c,dc=x", 0);
+
+        let mut panel = TracePanel::new(Theme::default());
+        panel.toggle();
+        assert_eq!(panel.listed(Some(&trace))[0].dn, "cn=c,dc=x");
+        panel.handle_key_event(key('f'), Some(&trace));
+        let listed = panel.listed(Some(&trace));
+        assert_eq!(listed.len(), 1);
+        assert_eq!(listed[0].dn, "cn=b,dc=x");
+
+        panel.handle_key_event(key('f'), Some(&trace));
+        let Action::SaveTrace(text) = panel.handle_key_event(key('s'), Some(&trace)) else {
+            panic!("expected a save action");
+        };
+        let dns: Vec<&str> = text.lines().map(|l| l.split('"').nth(1).unwrap()).collect();
+        assert_eq!(dns, ["cn=a,dc=x", "cn=b,dc=x", "cn=c,dc=x"]);
+        assert!(matches!(
+            panel.handle_key_event(key('s'), None),
+            Action::None
+        ));
+    }
+}
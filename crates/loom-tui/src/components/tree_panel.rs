@@ -1,54 +1,274 @@
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
+use std::hash::{Hash, Hasher};
+
 use crossterm::event::{KeyCode, KeyEvent};
 use ratatui::layout::Rect;
 use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
 use ratatui::widgets::{Block, BorderType, Borders, Paragraph};
 use ratatui::Frame;
-use tui_tree_widget::{Tree, TreeItem, TreeState};
 
 use crate::action::{Action, ContextMenuSource};
+use crate::components::tree_view::{SearchFolder, TreeViewState, ViewRow};
 use crate::theme::Theme;
-use loom_core::tree::TreeNode;
+use loom_core::tree::{ChildCount, DirectoryTree, TreeNode};
+
+/// Identifier of the Favorites section node; cannot clash with a DN.
+pub const FAVORITES_ID: &str = "\u{1}favorites";
+/// Identifier of the Searches section node; saved search folders are
+/// identified under it.
+pub const SEARCHES_ID: &str = "\u{1}searches";
+
+const NODE_OPEN: &str = "\u{25bc} ";
+const NODE_CLOSED: &str = "\u{25b6} ";
+const NODE_LEAF: &str = "  ";
 
 /// The left panel: directory tree browser.
 pub struct TreePanel {
-    pub tree_state: TreeState<String>,
+    pub tree_state: TreeViewState,
     pub theme: Theme,
     area: Option<Rect>,
+    /// Quick find on the names of loaded nodes.
+    filter: String,
+    filter_input: bool,
+    /// `filter` trimmed and lowercased, as matched against node names.
+    query: String,
+    /// Open the ancestors of matches on the next layout.
+    reveal_matches: bool,
+    /// The rows last drawn, reused while the layout and scroll position
+    /// stay the same.
+    lines: Vec<Line<'static>>,
+    lines_key: Option<LinesKey>,
+}
+
+/// What the drawn rows depend on besides the tree's own text.
+#[derive(Debug, PartialEq)]
+struct LinesKey {
+    stamp: u64,
+    offset: usize,
+    height: usize,
+    /// [`set_hash`] of the stale favorites.
+    stale: u64,
+}
+
+/// A hash of a set's members that doesn't depend on iteration order, so
+/// swapping one member for another changes it.
+fn set_hash(set: &HashSet<String>) -> u64 {
+    set.iter()
+        .map(|member| {
+            let mut hasher = DefaultHasher::new();
+            member.hash(&mut hasher);
+            hasher.finish()
+        })
+        .fold(0, u64::wrapping_add)
 }
 
 impl TreePanel {
     pub fn new(theme: Theme) -> Self {
         Self {
-            tree_state: TreeState::default(),
+            tree_state: TreeViewState::default(),
             theme,
             area: None,
+            filter: String::new(),
+            filter_input: false,
+            query: String::new(),
+            reveal_matches: false,
+            lines: Vec::new(),
+            lines_key: None,
         }
     }
 
-    /// Build tree items from the directory tree for rendering.
-    pub fn build_tree_items(node: &TreeNode) -> Vec<TreeItem<'static, String>> {
-        let mut items = Vec::new();
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        // The cached rows carry the old theme's colors
+        self.lines_key = None;
+    }
+
+    /// True while the quick find is being typed, when every key belongs
+    /// to the panel.
+    pub fn is_filtering(&self) -> bool {
+        self.filter_input
+    }
+
+    fn filter_changed(&mut self) {
+        self.query = self.filter.trim().to_lowercase();
+        self.reveal_matches = true;
+    }
+
+    /// The text of one directory node: its name, highlighted where it
+    /// matches the quick find, and its child count when known.
+    fn node_line(&self, node: &TreeNode, query: &str) -> Vec<Span<'static>> {
+        let mut spans = if !query.is_empty() && node.display_name.to_lowercase().contains(query) {
+            self.highlight(&node.display_name, query)
+        } else {
+            vec![Span::raw(node.display_name.clone())]
+        };
+        if let Some(count) = node.child_count.filter(|c| *c != ChildCount::Exact(0)) {
+            spans.push(Span::styled(format!(" {}", count), self.theme.dimmed));
+        }
+        spans
+    }
 
-        if let Some(ref children) = node.children {
-            for child in children {
-                let child_items = Self::build_tree_items(child);
-                let item = TreeItem::new(child.dn.clone(), child.display_name.clone(), child_items)
-                    .expect("tree item creation");
-                items.push(item);
+    /// Split a matching name so the matched part stands out.
+    fn highlight(&self, name: &str, query: &str) -> Vec<Span<'static>> {
+        let style = self.theme.warning.add_modifier(Modifier::BOLD);
+        let lower = name.to_lowercase();
+        match lower.find(query) {
+            // Lowercasing can change byte lengths outside ASCII
+            Some(start) if lower.len() == name.len() => {
+                let end = start + query.len();
+                vec![
+                    Span::raw(name[..start].to_string()),
+                    Span::styled(name[start..end].to_string(), style),
+                    Span::raw(name[end..].to_string()),
+                ]
             }
+            _ => vec![Span::styled(name.to_string(), style)],
         }
+    }
+
+    /// Select the next (or previous) match after the current selection.
+    fn jump_to_match(&mut self, forward: bool) -> Action {
+        let matches = self.tree_state.matches();
+        if matches.is_empty() {
+            return Action::None;
+        }
+        let current = self.tree_state.selected();
+        let pos = matches.iter().position(|m| m.as_slice() == current);
+        let next = match (pos, forward) {
+            (Some(i), true) => (i + 1) % matches.len(),
+            (Some(i), false) => (i + matches.len() - 1) % matches.len(),
+            (None, true) => 0,
+            (None, false) => matches.len() - 1,
+        };
+        let path = matches[next].clone();
+        let dn = path.last().cloned().unwrap_or_default();
+        self.tree_state.select(path);
+        Action::TreeSelect(dn)
+    }
 
-        items
+    fn handle_filter_key(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc => {
+                self.filter_input = false;
+                self.filter.clear();
+                self.filter_changed();
+            }
+            KeyCode::Enter => {
+                self.filter_input = false;
+                return self.jump_to_match(true);
+            }
+            KeyCode::Backspace => {
+                self.filter.pop();
+                self.filter_changed();
+            }
+            KeyCode::Up | KeyCode::Down => {
+                self.filter_input = false;
+                let action = self.handle_key_event(key);
+                self.filter_input = true;
+                return action;
+            }
+            KeyCode::Char(c) => {
+                self.filter.push(c);
+                self.filter_changed();
+            }
+            _ => {}
+        }
+        Action::None
     }
 
-    /// Get the currently selected DN.
+    /// Get the currently selected DN (none on section headings and saved
+    /// search folders).
     pub fn selected_dn(&self) -> Option<&String> {
-        self.tree_state.selected().last()
+        self.tree_state
+            .selected()
+            .last()
+            .filter(|id| !id.starts_with('\u{1}'))
+    }
+
+    /// True when the selection is in the Favorites or Searches section.
+    fn in_section(&self) -> bool {
+        self.tree_state
+            .selected()
+            .first()
+            .is_some_and(|id| id.starts_with('\u{1}'))
+    }
+
+    /// Expand or collapse the selected node and load its entry. A pinned
+    /// entry or search result jumps to its place in the directory tree
+    /// instead, and a saved search runs when first opened.
+    pub fn activate_selected(&mut self) -> Action {
+        match self.selected_dn().cloned() {
+            Some(dn) if self.in_section() => Action::RevealEntry(dn),
+            Some(dn) => {
+                self.tree_state.toggle_selected();
+                Action::TreeExpand(dn)
+            }
+            None => {
+                self.tree_state.toggle_selected();
+                let opened = (self.tree_state.selected().last())
+                    .is_some_and(|id| self.tree_state.is_open(id));
+                match self.tree_state.selected_search() {
+                    Some(search) if opened && search.results.is_none() && !search.loading => {
+                        Action::RunSavedSearch(search.name.clone())
+                    }
+                    _ => Action::None,
+                }
+            }
+        }
+    }
+
+    /// Select the row drawn at screen line `y`. Returns the action for the
+    /// new selection, or `None` when no row is drawn there.
+    pub fn click_row(&mut self, y: u16) -> Option<Action> {
+        let area = self.area?;
+        let top = area.y + 1;
+        if y < top || y + 1 >= area.y + area.height {
+            return None;
+        }
+        let i = self.tree_state.offset() + (y - top) as usize;
+        if i >= self.tree_state.len() {
+            return None;
+        }
+        self.tree_state.select_relative(|_| i);
+        Some(match self.selected_dn() {
+            Some(dn) => Action::TreeSelect(dn.clone()),
+            None => Action::Render,
+        })
     }
 
     /// Handle key events, mutating tree state.
+    /// Scroll the tree `delta` lines with the mouse wheel. The selection,
+    /// and so the entry shown, only changes when it would leave the screen.
+    pub fn scroll(&mut self, delta: isize) -> Action {
+        let height = self.area.map_or(0, |a| a.height.saturating_sub(2)) as usize;
+        if !self.tree_state.scroll(delta, height) {
+            return Action::Render;
+        }
+        match self.selected_dn().cloned() {
+            Some(dn) => Action::TreeSelect(dn),
+            None => Action::Render,
+        }
+    }
+
     pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        if self.filter_input {
+            return self.handle_filter_key(key);
+        }
+        let filtered = !self.query.is_empty();
         match key.code {
+            KeyCode::Char('f') => {
+                self.filter_input = true;
+                Action::None
+            }
+            KeyCode::Char('n') if filtered => self.jump_to_match(true),
+            KeyCode::Char('N') if filtered => self.jump_to_match(false),
+            KeyCode::Esc if !self.filter.is_empty() => {
+                self.filter.clear();
+                self.filter_changed();
+                Action::None
+            }
             KeyCode::Up | KeyCode::Char('k') => {
                 self.tree_state.key_up();
                 if let Some(dn) = self.selected_dn().cloned() {
@@ -65,21 +285,11 @@ impl TreePanel {
                     Action::None
                 }
             }
-            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => {
-                if let Some(dn) = self.selected_dn().cloned() {
-                    self.tree_state.toggle_selected();
-                    Action::TreeExpand(dn)
-                } else {
-                    Action::None
-                }
-            }
+            KeyCode::Right | KeyCode::Char('l') | KeyCode::Enter => self.activate_selected(),
             KeyCode::Left | KeyCode::Char('h') => {
-                if let Some(dn) = self.selected_dn().cloned() {
-                    self.tree_state.key_left();
-                    Action::TreeCollapse(dn)
-                } else {
-                    Action::None
-                }
+                let dn = self.selected_dn().filter(|_| !self.in_section()).cloned();
+                self.tree_state.key_left();
+                dn.map_or(Action::None, Action::TreeCollapse)
             }
             KeyCode::PageUp => {
                 self.tree_state
@@ -99,6 +309,23 @@ impl TreePanel {
                     Action::None
                 }
             }
+            KeyCode::Char('r') if self.tree_state.selected_search().is_some() => {
+                self.tree_state.open(self.tree_state.selected().to_vec());
+                let name = self.tree_state.selected_search().unwrap().name.clone();
+                Action::RunSavedSearch(name)
+            }
+            KeyCode::Char('d') | KeyCode::Delete if self.tree_state.selected_search().is_some() => {
+                let name = self.tree_state.selected_search().unwrap().name.clone();
+                let msg = format!("Delete saved search?\n{}", name);
+                Action::ShowConfirm(msg, Box::new(Action::DeleteSavedSearch(name)))
+            }
+            KeyCode::Char('r') => {
+                if let Some(dn) = self.selected_dn().cloned() {
+                    Action::RefreshSubtree(dn)
+                } else {
+                    Action::None
+                }
+            }
             KeyCode::Char('a') => {
                 if let Some(dn) = self.selected_dn().cloned() {
                     Action::ShowCreateEntryDialog(dn)
@@ -114,6 +341,35 @@ impl TreePanel {
                     Action::None
                 }
             }
+            KeyCode::Char('x') | KeyCode::Char('X') => {
+                if let Some(dn) = self.selected_dn().cloned() {
+                    let subtree = key.code == KeyCode::Char('X');
+                    Action::CutEntry { dn, subtree }
+                } else {
+                    Action::None
+                }
+            }
+            KeyCode::Char('*') => {
+                if let Some(dn) = self.selected_dn().cloned() {
+                    Action::ToggleFavorite(dn)
+                } else {
+                    Action::None
+                }
+            }
+            KeyCode::Char('M') => {
+                if let Some(dn) = self.selected_dn().cloned() {
+                    Action::ToggleReference(dn)
+                } else {
+                    Action::None
+                }
+            }
+            KeyCode::Char('p') => {
+                if let Some(dn) = self.selected_dn().cloned() {
+                    Action::PasteEntry(dn)
+                } else {
+                    Action::None
+                }
+            }
             KeyCode::Char(' ') => {
                 if let Some(dn) = self.selected_dn().cloned() {
                     Action::ShowContextMenu(ContextMenuSource::Tree { dn })
@@ -125,15 +381,35 @@ impl TreePanel {
         }
     }
 
-    /// Render the tree panel with items and mutable state access.
-    pub fn render_with_items(
+    fn layout(&mut self, tree: &DirectoryTree, favorites: &[String], searches: &[SearchFolder]) {
+        let reveal = std::mem::take(&mut self.reveal_matches);
+        self.tree_state
+            .layout(tree, favorites, searches, &self.query, reveal);
+    }
+
+    /// Render the directory tree, with the Favorites and Searches sections
+    /// first. Only the rows that fit in `area` are turned into text.
+    /// `stale` holds the lowercased favorite DNs found to no longer exist.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_tree(
         &mut self,
         frame: &mut Frame,
         area: Rect,
         focused: bool,
-        items: &[TreeItem<'_, String>],
+        tree: &DirectoryTree,
+        favorites: &[String],
+        searches: &[SearchFolder],
+        stale: &HashSet<String>,
         title: &str,
     ) {
+        // Favorites and searches aren't part of a quick find
+        let (favorites, searches) = if self.query.is_empty() {
+            (favorites, searches)
+        } else {
+            (&[][..], &[][..])
+        };
+        self.layout(tree, favorites, searches);
+
         let border_style = if focused {
             self.theme.border_focused
         } else {
@@ -141,22 +417,149 @@ impl TreePanel {
         };
 
         let mut block = Block::default()
-            .title(format!(" {} ", title))
+            .title(self.title_with_filter(title))
             .borders(Borders::ALL)
             .border_style(border_style);
         if focused {
             block = block.border_type(BorderType::Double);
         }
 
-        let tree_widget = Tree::new(items)
-            .expect("tree widget")
-            .block(block)
-            .highlight_style(self.theme.tree_node_selected.add_modifier(Modifier::BOLD));
+        let inner = block.inner(area);
+        let height = inner.height as usize;
+        self.tree_state.scroll_into_view(height);
+        let key = LinesKey {
+            stamp: self.tree_state.stamp(),
+            offset: self.tree_state.offset(),
+            height,
+            stale: set_hash(stale),
+        };
+        if self.lines_key.as_ref() != Some(&key) {
+            self.lines = self.build_lines(tree, favorites, searches, stale, key.offset, height);
+            self.lines_key = Some(key);
+        }
 
-        frame.render_stateful_widget(tree_widget, area, &mut self.tree_state);
+        frame.render_widget(block, area);
+        let offset = self.tree_state.offset();
+        let selected = self.tree_state.selected_index();
+        let selected_style = self.theme.tree_node_selected.add_modifier(Modifier::BOLD);
+        let buf = frame.buffer_mut();
+        for (i, line) in self.lines.iter().enumerate() {
+            let row = Rect::new(inner.x, inner.y + i as u16, inner.width, 1);
+            buf.set_line(row.x, row.y, line, row.width);
+            if selected == Some(offset + i) {
+                buf.set_style(row, selected_style);
+            }
+        }
         self.area = Some(area);
     }
 
+    /// Turn the rows from `offset` that fit in `height` lines into text.
+    fn build_lines(
+        &self,
+        tree: &DirectoryTree,
+        favorites: &[String],
+        searches: &[SearchFolder],
+        stale: &HashSet<String>,
+        offset: usize,
+        height: usize,
+    ) -> Vec<Line<'static>> {
+        (offset..self.tree_state.len().min(offset + height))
+            .filter_map(|i| {
+                let spans = match self.tree_state.row(i)? {
+                    ViewRow::FavoritesHeading => {
+                        let symbol = if self.tree_state.is_open(FAVORITES_ID) {
+                            NODE_OPEN
+                        } else {
+                            NODE_CLOSED
+                        };
+                        vec![Span::raw(format!(
+                            "{}\u{2605} Favorites ({})",
+                            symbol,
+                            favorites.len()
+                        ))]
+                    }
+                    ViewRow::Favorite(dn) => {
+                        let name = loom_core::dn::rdn_display_name(dn);
+                        let text = if stale.contains(&dn.to_lowercase()) {
+                            Span::styled(format!("{} (missing)", name), self.theme.dimmed)
+                        } else {
+                            Span::raw(name.to_string())
+                        };
+                        vec![Span::raw(format!("  {}", NODE_LEAF)), text]
+                    }
+                    ViewRow::SearchesHeading => {
+                        let symbol = if self.tree_state.is_open(SEARCHES_ID) {
+                            NODE_OPEN
+                        } else {
+                            NODE_CLOSED
+                        };
+                        vec![Span::raw(format!(
+                            "{}\u{2315} Searches ({})",
+                            symbol,
+                            searches.len()
+                        ))]
+                    }
+                    ViewRow::SearchFolder(folder) => {
+                        let symbol = match &folder.results {
+                            Some(r) if !r.is_empty() && self.tree_state.is_open(&folder.id()) => {
+                                NODE_OPEN
+                            }
+                            Some(r) if r.is_empty() => NODE_LEAF,
+                            _ => NODE_CLOSED,
+                        };
+                        let count = if folder.loading {
+                            " ...".to_string()
+                        } else {
+                            folder
+                                .results
+                                .as_ref()
+                                .map(|r| format!(" {}", r.len()))
+                                .unwrap_or_default()
+                        };
+                        vec![
+                            Span::raw(format!("  {}{}", symbol, folder.name)),
+                            Span::styled(count, self.theme.dimmed),
+                        ]
+                    }
+                    ViewRow::SearchResult(dn) => vec![Span::raw(format!(
+                        "    {}{}",
+                        NODE_LEAF,
+                        loom_core::dn::rdn_display_name(dn)
+                    ))],
+                    ViewRow::Node(row) => {
+                        let node = self.tree_state.node(tree, row)?;
+                        let symbol = match &node.children {
+                            Some(c) if !c.is_empty() && self.tree_state.is_open(&node.dn) => {
+                                NODE_OPEN
+                            }
+                            Some(c) if !c.is_empty() => NODE_CLOSED,
+                            None if node.has_children_hint => NODE_CLOSED,
+                            _ => NODE_LEAF,
+                        };
+                        let mut spans =
+                            vec![Span::raw(format!("{}{}", "  ".repeat(row.depth), symbol))];
+                        spans.extend(self.node_line(node, &self.query));
+                        spans
+                    }
+                };
+                Some(Line::from(spans))
+            })
+            .collect()
+    }
+
+    fn title_with_filter(&self, title: &str) -> String {
+        if !self.filter_input && self.filter.is_empty() {
+            return format!(" {} ", title);
+        }
+        format!(
+            " {}  find: {}{} ({}) ",
+            title,
+            self.filter,
+            if self.filter_input { "_" } else { "" },
+            self.tree_state.matches().len()
+        )
+    }
+
     /// Render an empty placeholder (no connection).
     pub fn render_empty(&self, frame: &mut Frame, area: Rect, focused: bool) {
         let border_style = if focused {
@@ -179,3 +582,77 @@ impl TreePanel {
         frame.render_widget(empty, area);
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    fn tree() -> DirectoryTree {
+        let mut tree = DirectoryTree::new("dc=x".to_string());
+        tree.insert_children(
+            "dc=x",
+            vec![
+                TreeNode::new("ou=People,dc=x".to_string()),
+                TreeNode::new("ou=Groups,dc=x".to_string()),
+            ],
+        );
+        tree.insert_children(
+            "ou=People,dc=x",
+            vec![
+                TreeNode::new("cn=Alice,ou=People,dc=x".to_string()),
+                TreeNode::new("cn=Bob,ou=People,dc=x".to_string()),
+            ],
+        );
+        tree.insert_children(
+            "ou=Groups,dc=x",
+            vec![TreeNode::new("cn=Alice Fans,ou=Groups,dc=x".to_string())],
+        );
+        tree
+    }
+
+    #[test]
+    fn test_set_hash() {
+        let set = |dns: &[&str]| dns.iter().map(|d| d.to_string()).collect::<HashSet<_>>();
+        assert_eq!(set_hash(&set(&["a", "b"])), set_hash(&set(&["b", "a"])));
+        // Same size, different members
+        assert_ne!(set_hash(&set(&["a", "b"])), set_hash(&set(&["a", "c"])));
+        assert_ne!(set_hash(&set(&[])), set_hash(&set(&["a"])));
+    }
+
+    #[test]
+    fn test_quick_find() {
+        let tree = tree();
+        let mut panel = TreePanel::new(Theme::dark());
+        panel.handle_key_event(key(KeyCode::Char('f')));
+        assert!(panel.is_filtering());
+        for c in "ALI".chars() {
+            panel.handle_key_event(key(KeyCode::Char(c)));
+        }
+
+        // Bob is hidden; both parents open as the way to their matches
+        panel.layout(&tree, &[], &[]);
+        assert_eq!(panel.tree_state.len(), 4);
+        assert_eq!(panel.tree_state.matches().len(), 2);
+        assert_eq!(panel.tree_state.opened().len(), 2);
+
+        let action = panel.handle_key_event(key(KeyCode::Enter));
+        assert!(!panel.is_filtering());
+        assert!(matches!(action, Action::TreeSelect(dn) if dn == "cn=Alice,ou=People,dc=x"));
+        let action = panel.handle_key_event(key(KeyCode::Char('n')));
+        assert!(matches!(action, Action::TreeSelect(dn) if dn == "cn=Alice Fans,ou=Groups,dc=x"));
+        let action = panel.handle_key_event(key(KeyCode::Char('n')));
+        assert!(matches!(action, Action::TreeSelect(dn) if dn == "cn=Alice,ou=People,dc=x"));
+        let action = panel.handle_key_event(key(KeyCode::Char('N')));
+        assert!(matches!(action, Action::TreeSelect(dn) if dn == "cn=Alice Fans,ou=Groups,dc=x"));
+
+        panel.handle_key_event(key(KeyCode::Esc));
+        panel.layout(&tree, &[], &[]);
+        assert_eq!(panel.tree_state.len(), 5);
+        assert!(panel.tree_state.matches().is_empty());
+    }
+}
@@ -0,0 +1,671 @@
+use std::collections::HashSet;
+
+use loom_core::tree::{DirectoryTree, TreeNode};
+
+use crate::components::tree_panel::{FAVORITES_ID, SEARCHES_ID};
+use crate::widgets::scroll::scroll_view;
+
+/// One directory node laid out as a row of the tree.
+#[derive(Debug)]
+pub struct Row {
+    pub depth: usize,
+    /// Row of the parent node; `None` on the first level.
+    parent: Option<usize>,
+    /// Position among the parent's children.
+    index: usize,
+    pub dn: String,
+}
+
+/// A saved search shown as a folder of the Searches section, listing the
+/// entries it matched.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SearchFolder {
+    pub name: String,
+    pub filter: String,
+    /// Matching DNs; `None` until the search has run.
+    pub results: Option<Vec<String>>,
+    pub loading: bool,
+}
+
+impl SearchFolder {
+    pub fn new(name: String, filter: String) -> Self {
+        Self {
+            name,
+            filter,
+            results: None,
+            loading: false,
+        }
+    }
+
+    /// Identifier of the folder's row; cannot clash with a DN.
+    pub fn id(&self) -> String {
+        format!("{}/{}", SEARCHES_ID, self.name)
+    }
+}
+
+/// A line of the Searches section.
+#[derive(Debug, Clone, Copy)]
+enum SearchLine {
+    Heading,
+    Folder(usize),
+    /// A matching entry: the folder, its line and the entry's position.
+    Result {
+        folder: usize,
+        line: usize,
+        index: usize,
+    },
+}
+
+/// What a line of the tree shows: the Favorites and Searches sections come
+/// first, then the directory.
+pub enum ViewRow<'a> {
+    FavoritesHeading,
+    Favorite(&'a str),
+    SearchesHeading,
+    SearchFolder(&'a SearchFolder),
+    SearchResult(&'a str),
+    Node(&'a Row),
+}
+
+/// Selection, open nodes and scroll position of the directory tree.
+///
+/// The rows are laid out straight from the `DirectoryTree` and kept until
+/// the tree, the open nodes or the quick find change, so a frame only turns
+/// the rows on screen into text however many children are loaded.
+#[derive(Debug, Default)]
+pub struct TreeViewState {
+    /// Identifiers (DNs, or the Favorites heading) of open nodes.
+    opened: HashSet<String>,
+    /// Identifier path of the selected row, from the first level down.
+    selected: Vec<String>,
+    selected_index: Option<usize>,
+    offset: usize,
+    /// Bumped whenever a node opens or closes.
+    version: u64,
+    rows: Vec<Row>,
+    /// Tree generation and open version the rows were laid out for, with
+    /// `layout_query`. Kept apart so checking them doesn't allocate.
+    layout_key: Option<(u64, u64)>,
+    layout_query: String,
+    /// Bumped on every new layout, so rendered rows can be reused until then.
+    stamp: u64,
+    favorites: Vec<String>,
+    searches: Vec<SearchFolder>,
+    /// Lines of the Searches section, laid out with the rows.
+    search_lines: Vec<SearchLine>,
+    /// Tree paths of nodes matching the quick find, in display order.
+    matches: Vec<Vec<String>>,
+    /// Matching nodes and their ancestors; the only rows shown while a
+    /// quick find is active.
+    keep: HashSet<String>,
+    /// Tree generation the matches were found in, with `matches_query`.
+    matches_generation: Option<u64>,
+    matches_query: String,
+}
+
+impl TreeViewState {
+    pub fn selected(&self) -> &[String] {
+        &self.selected
+    }
+
+    pub fn opened(&self) -> &HashSet<String> {
+        &self.opened
+    }
+
+    pub fn matches(&self) -> &[Vec<String>] {
+        &self.matches
+    }
+
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    pub fn is_open(&self, id: &str) -> bool {
+        self.opened.contains(id)
+    }
+
+    /// Select a row by identifier path. It takes effect on screen once the
+    /// row is laid out.
+    pub fn select(&mut self, path: Vec<String>) -> bool {
+        let changed = self.selected != path;
+        self.selected = path;
+        self.selected_index = self.find_selected();
+        changed
+    }
+
+    /// Open the node at the end of an identifier path.
+    pub fn open(&mut self, path: Vec<String>) -> bool {
+        match path.last() {
+            Some(id) if self.opened.insert(id.clone()) => {
+                self.version += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn close(&mut self, path: &[String]) -> bool {
+        match path.last() {
+            Some(id) if self.opened.remove(id) => {
+                self.version += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn toggle_selected(&mut self) -> bool {
+        let path = self.selected.clone();
+        match path.last() {
+            Some(id) if self.opened.contains(id) => self.close(&path),
+            Some(_) => self.open(path),
+            None => false,
+        }
+    }
+
+    /// Number of lines in the tree, Favorites and Searches included.
+    pub fn len(&self) -> usize {
+        self.favorites_len() + self.search_lines.len() + self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn favorites_len(&self) -> usize {
+        match self.favorites.len() {
+            0 => 0,
+            n if self.is_open(FAVORITES_ID) => n + 1,
+            _ => 1,
+        }
+    }
+
+    /// Lines before the directory rows.
+    fn sections_len(&self) -> usize {
+        self.favorites_len() + self.search_lines.len()
+    }
+
+    fn search_line(&self, i: usize) -> Option<SearchLine> {
+        let i = i.checked_sub(self.favorites_len())?;
+        self.search_lines.get(i).copied()
+    }
+
+    pub fn row(&self, i: usize) -> Option<ViewRow<'_>> {
+        let favorites = self.favorites_len();
+        let sections = self.sections_len();
+        if i >= sections {
+            return self.rows.get(i - sections).map(ViewRow::Node);
+        }
+        if i >= favorites {
+            return match self.search_line(i)? {
+                SearchLine::Heading => Some(ViewRow::SearchesHeading),
+                SearchLine::Folder(f) => Some(ViewRow::SearchFolder(&self.searches[f])),
+                SearchLine::Result { folder, index, .. } => {
+                    let results = self.searches[folder].results.as_ref()?;
+                    Some(ViewRow::SearchResult(&results[index]))
+                }
+            };
+        }
+        match i {
+            0 => Some(ViewRow::FavoritesHeading),
+            i => self.favorites.get(i - 1).map(|dn| ViewRow::Favorite(dn)),
+        }
+    }
+
+    /// The saved search whose folder is selected.
+    pub fn selected_search(&self) -> Option<&SearchFolder> {
+        match self.selected.as_slice() {
+            [section, id] if section == SEARCHES_ID => self.searches.iter().find(|f| f.id() == *id),
+            _ => None,
+        }
+    }
+
+    /// Identifier path of a line.
+    fn path_of(&self, i: usize) -> Vec<String> {
+        match self.row(i) {
+            Some(ViewRow::FavoritesHeading) => vec![FAVORITES_ID.to_string()],
+            Some(ViewRow::Favorite(dn)) => vec![FAVORITES_ID.to_string(), dn.to_string()],
+            Some(ViewRow::SearchesHeading) => vec![SEARCHES_ID.to_string()],
+            Some(ViewRow::SearchFolder(folder)) => vec![SEARCHES_ID.to_string(), folder.id()],
+            Some(ViewRow::SearchResult(dn)) => {
+                let Some(SearchLine::Result { folder, .. }) = self.search_line(i) else {
+                    return Vec::new();
+                };
+                vec![
+                    SEARCHES_ID.to_string(),
+                    self.searches[folder].id(),
+                    dn.to_string(),
+                ]
+            }
+            Some(ViewRow::Node(row)) => {
+                let mut path = vec![row.dn.clone()];
+                let mut parent = row.parent;
+                while let Some(p) = parent {
+                    path.push(self.rows[p].dn.clone());
+                    parent = self.rows[p].parent;
+                }
+                path.reverse();
+                path
+            }
+            None => Vec::new(),
+        }
+    }
+
+    fn find_selected(&self) -> Option<usize> {
+        let last = self.selected.last()?;
+        (0..self.len()).find(|&i| {
+            let matches = match self.row(i) {
+                Some(ViewRow::FavoritesHeading) => last == FAVORITES_ID,
+                Some(ViewRow::Favorite(dn)) | Some(ViewRow::SearchResult(dn)) => last == dn,
+                Some(ViewRow::SearchesHeading) => last == SEARCHES_ID,
+                Some(ViewRow::SearchFolder(folder)) => *last == folder.id(),
+                Some(ViewRow::Node(row)) => *last == row.dn,
+                None => false,
+            };
+            matches && self.path_of(i) == self.selected
+        })
+    }
+
+    fn select_index(&mut self, i: usize) -> bool {
+        if self.is_empty() {
+            return false;
+        }
+        let i = i.min(self.len() - 1);
+        let changed = self.selected_index != Some(i);
+        self.selected = self.path_of(i);
+        self.selected_index = Some(i);
+        changed
+    }
+
+    /// Move the selection to the line `change` picks from the current one.
+    pub fn select_relative(&mut self, change: impl FnOnce(Option<usize>) -> usize) -> bool {
+        let i = change(self.selected_index);
+        self.select_index(i)
+    }
+
+    pub fn key_up(&mut self) -> bool {
+        self.select_relative(|current| current.map_or(usize::MAX, |c| c.saturating_sub(1)))
+    }
+
+    pub fn key_down(&mut self) -> bool {
+        self.select_relative(|current| current.map_or(0, |c| c.saturating_add(1)))
+    }
+
+    /// Close the selected node, or if it is closed, move to its parent.
+    pub fn key_left(&mut self) -> bool {
+        let path = self.selected.clone();
+        if self.close(&path) {
+            return true;
+        }
+        let index = self.selected_index;
+        let parent = match index.and_then(|i| self.row(i)) {
+            Some(ViewRow::Favorite(_)) => Some(0),
+            Some(ViewRow::SearchFolder(_)) => Some(self.favorites_len()),
+            Some(ViewRow::SearchResult(_)) => match index.and_then(|i| self.search_line(i)) {
+                Some(SearchLine::Result { line, .. }) => Some(line + self.favorites_len()),
+                _ => None,
+            },
+            Some(ViewRow::Node(row)) => row.parent.map(|p| p + self.sections_len()),
+            _ => None,
+        };
+        match parent {
+            Some(p) => self.select_index(p),
+            None => false,
+        }
+    }
+
+    /// Scroll a view `height` lines tall by `delta` lines, leaving the
+    /// selection where it is unless it would go off screen. Returns whether
+    /// the selection moved.
+    pub fn scroll(&mut self, delta: isize, height: usize) -> bool {
+        let (offset, selected) =
+            scroll_view(self.offset, self.selected_index, self.len(), delta, |_| {
+                height
+            });
+        self.offset = offset;
+        match selected {
+            Some(i) if selected != self.selected_index => self.select_index(i),
+            _ => false,
+        }
+    }
+
+    /// Scroll so the selected line is within a view `height` lines tall.
+    pub fn scroll_into_view(&mut self, height: usize) {
+        let height = height.max(1);
+        if let Some(i) = self.selected_index {
+            if i < self.offset {
+                self.offset = i;
+            } else if i >= self.offset + height {
+                self.offset = i + 1 - height;
+            }
+        }
+        self.offset = self.offset.min(self.len().saturating_sub(height));
+    }
+
+    /// Lay out the rows for `tree` unless nothing they depend on changed.
+    /// With `reveal`, the ancestors of quick find matches are opened first.
+    pub fn layout(
+        &mut self,
+        tree: &DirectoryTree,
+        favorites: &[String],
+        searches: &[SearchFolder],
+        query: &str,
+        reveal: bool,
+    ) {
+        let mut stale = false;
+        if self.favorites != favorites {
+            self.favorites = favorites.to_vec();
+            stale = true;
+        }
+        if self.searches != searches {
+            self.searches = searches.to_vec();
+            stale = true;
+        }
+
+        if self.matches_generation != Some(tree.generation()) || self.matches_query != query {
+            self.find_matches(&tree.root, query);
+            self.matches_generation = Some(tree.generation());
+            self.matches_query = query.to_string();
+        }
+        if reveal {
+            let ancestors: Vec<String> = self
+                .matches
+                .iter()
+                .flat_map(|path| path[..path.len() - 1].iter().cloned())
+                .collect();
+            for id in ancestors {
+                self.open(vec![id]);
+            }
+        }
+
+        let key = (tree.generation(), self.version);
+        if !stale && self.layout_key == Some(key) && self.layout_query == query {
+            return;
+        }
+        let mut rows = std::mem::take(&mut self.rows);
+        rows.clear();
+        let keep = (!query.is_empty()).then_some(&self.keep);
+        push_rows(&mut rows, &tree.root, 0, None, &self.opened, keep);
+        self.rows = rows;
+        self.search_lines = self.lay_out_searches();
+        self.layout_key = Some(key);
+        self.layout_query = query.to_string();
+        self.stamp += 1;
+        self.selected_index = self.find_selected();
+    }
+
+    /// The Searches section: a heading, then each folder with its results
+    /// when open.
+    fn lay_out_searches(&self) -> Vec<SearchLine> {
+        if self.searches.is_empty() {
+            return Vec::new();
+        }
+        let mut lines = vec![SearchLine::Heading];
+        if !self.is_open(SEARCHES_ID) {
+            return lines;
+        }
+        for (folder, search) in self.searches.iter().enumerate() {
+            let line = lines.len();
+            lines.push(SearchLine::Folder(folder));
+            if let Some(results) = search
+                .results
+                .as_ref()
+                .filter(|_| self.is_open(&search.id()))
+            {
+                lines.extend((0..results.len()).map(|index| SearchLine::Result {
+                    folder,
+                    line,
+                    index,
+                }));
+            }
+        }
+        lines
+    }
+
+    /// Changes with every new layout.
+    pub fn stamp(&self) -> u64 {
+        self.stamp
+    }
+
+    fn find_matches(&mut self, root: &TreeNode, query: &str) {
+        fn walk(
+            node: &TreeNode,
+            query: &str,
+            path: &mut Vec<String>,
+            matches: &mut Vec<Vec<String>>,
+            keep: &mut HashSet<String>,
+        ) {
+            for child in node.children.iter().flatten() {
+                path.push(child.dn.clone());
+                if child.display_name.to_lowercase().contains(query) {
+                    keep.extend(path.iter().cloned());
+                    matches.push(path.clone());
+                }
+                walk(child, query, path, matches, keep);
+                path.pop();
+            }
+        }
+        self.matches.clear();
+        self.keep.clear();
+        if !query.is_empty() {
+            walk(
+                root,
+                query,
+                &mut Vec::new(),
+                &mut self.matches,
+                &mut self.keep,
+            );
+        }
+    }
+
+    /// The tree node a directory row shows.
+    pub fn node<'a>(&self, tree: &'a DirectoryTree, row: &Row) -> Option<&'a TreeNode> {
+        let mut indices = vec![row.index];
+        let mut parent = row.parent;
+        while let Some(p) = parent {
+            indices.push(self.rows[p].index);
+            parent = self.rows[p].parent;
+        }
+        let mut node = &tree.root;
+        for &i in indices.iter().rev() {
+            node = node.children.as_ref()?.get(i)?;
+        }
+        Some(node)
+    }
+
+    pub fn selected_index(&self) -> Option<usize> {
+        self.selected_index
+    }
+}
+
+fn push_rows(
+    rows: &mut Vec<Row>,
+    node: &TreeNode,
+    depth: usize,
+    parent: Option<usize>,
+    opened: &HashSet<String>,
+    keep: Option<&HashSet<String>>,
+) {
+    for (index, child) in node.children.iter().flatten().enumerate() {
+        if keep.is_some_and(|k| !k.contains(&child.dn)) {
+            continue;
+        }
+        rows.push(Row {
+            depth,
+            parent,
+            index,
+            dn: child.dn.clone(),
+        });
+        if opened.contains(&child.dn) {
+            let me = rows.len() - 1;
+            push_rows(rows, child, depth + 1, Some(me), opened, keep);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tree(children: usize) -> DirectoryTree {
+        let mut tree = DirectoryTree::new("dc=x".to_string());
+        tree.insert_children(
+            "dc=x",
+            vec![
+                TreeNode::new("ou=Big,dc=x".to_string()),
+                TreeNode::new("ou=Small,dc=x".to_string()),
+            ],
+        );
+        tree.insert_children(
+            "ou=Big,dc=x",
+            (0..children)
+                .map(|i| TreeNode::new(format!("cn=u{},ou=Big,dc=x", i)))
+                .collect(),
+        );
+        tree
+    }
+
+    #[test]
+    fn test_layout_follows_open_nodes() {
+        let tree = tree(3);
+        let mut state = TreeViewState::default();
+        state.layout(&tree, &[], &[], "", false);
+        assert_eq!(state.len(), 2);
+
+        state.key_down();
+        assert_eq!(state.selected(), ["ou=Big,dc=x".to_string()]);
+        state.toggle_selected();
+        state.layout(&tree, &[], &[], "", false);
+        assert_eq!(state.len(), 5);
+
+        state.key_down();
+        assert_eq!(
+            state.selected(),
+            ["ou=Big,dc=x".to_string(), "cn=u0,ou=Big,dc=x".to_string()]
+        );
+        let Some(ViewRow::Node(row)) = state.row(1) else {
+            panic!("expected a node row");
+        };
+        assert_eq!(row.depth, 1);
+        assert_eq!(state.node(&tree, row).unwrap().display_name, "u0");
+
+        // Left moves to the parent, then closes it
+        state.key_left();
+        assert_eq!(state.selected_index(), Some(0));
+        state.key_left();
+        state.layout(&tree, &[], &[], "", false);
+        assert_eq!(state.len(), 2);
+    }
+
+    #[test]
+    fn test_layout_is_reused_until_something_changes() {
+        let mut tree = tree(3);
+        let mut state = TreeViewState::default();
+        state.layout(&tree, &[], &[], "", false);
+        let stamp = state.stamp();
+        state.layout(&tree, &[], &[], "", false);
+        state.key_down();
+        state.layout(&tree, &[], &[], "", false);
+        assert_eq!(state.stamp(), stamp);
+
+        tree.insert_children("ou=Small,dc=x", Vec::new());
+        state.layout(&tree, &[], &[], "", false);
+        assert_ne!(state.stamp(), stamp);
+        let stamp = state.stamp();
+        state.toggle_selected();
+        state.layout(&tree, &[], &[], "", false);
+        assert_ne!(state.stamp(), stamp);
+    }
+
+    #[test]
+    fn test_favorites_come_first() {
+        let tree = tree(3);
+        let mut state = TreeViewState::default();
+        let favorites = vec!["cn=u1,ou=Big,dc=x".to_string()];
+        state.layout(&tree, &favorites, &[], "", false);
+        assert_eq!(state.len(), 3);
+        state.open(vec![FAVORITES_ID.to_string()]);
+        state.layout(&tree, &favorites, &[], "", false);
+        assert_eq!(state.len(), 4);
+        state.select_relative(|_| 1);
+        assert_eq!(
+            state.selected(),
+            [FAVORITES_ID.to_string(), "cn=u1,ou=Big,dc=x".to_string()]
+        );
+        assert!(matches!(state.row(2), Some(ViewRow::Node(_))));
+    }
+
+    #[test]
+    fn test_search_folders() {
+        let tree = tree(3);
+        let mut state = TreeViewState::default();
+        let mut folder = SearchFolder::new("Locked".to_string(), "(locked=TRUE)".to_string());
+        let mut searches = vec![folder.clone()];
+        state.layout(&tree, &[], &searches, "", false);
+        assert_eq!(state.len(), 3);
+        state.open(vec![SEARCHES_ID.to_string()]);
+        state.layout(&tree, &[], &searches, "", false);
+        assert_eq!(state.len(), 4);
+        state.select_relative(|_| 1);
+        assert_eq!(state.selected_search().unwrap().name, "Locked");
+
+        // Opened before the search ran: the results appear once it has
+        state.toggle_selected();
+        folder.results = Some(vec![
+            "cn=u0,ou=Big,dc=x".to_string(),
+            "cn=u2,ou=Big,dc=x".to_string(),
+        ]);
+        searches = vec![folder.clone()];
+        state.layout(&tree, &[], &searches, "", false);
+        assert_eq!(state.len(), 6);
+        state.select_relative(|_| 3);
+        assert_eq!(
+            state.selected(),
+            [
+                SEARCHES_ID.to_string(),
+                folder.id(),
+                "cn=u2,ou=Big,dc=x".to_string()
+            ]
+        );
+        assert!(matches!(state.row(4), Some(ViewRow::Node(_))));
+
+        // Left moves up to the folder, which closes next
+        state.key_left();
+        assert_eq!(state.selected_index(), Some(1));
+        state.key_left();
+        state.layout(&tree, &[], &searches, "", false);
+        assert_eq!(state.len(), 4);
+    }
+
+    #[test]
+    fn test_scroll_keeps_selection_visible() {
+        let tree = tree(50_000);
+        let mut state = TreeViewState::default();
+        state.open(vec!["ou=Big,dc=x".to_string()]);
+        state.layout(&tree, &[], &[], "", false);
+        assert_eq!(state.len(), 50_002);
+
+        state.select(vec![
+            "ou=Big,dc=x".to_string(),
+            "cn=u40000,ou=Big,dc=x".to_string(),
+        ]);
+        assert_eq!(state.selected_index(), Some(40_001));
+        state.scroll_into_view(20);
+        assert_eq!(state.offset(), 39_982);
+        state.select_relative(|_| 0);
+        state.scroll_into_view(20);
+        assert_eq!(state.offset(), 0);
+
+        // The wheel moves the view; the selection stays until it would
+        // leave the screen, then sticks to the top line
+        state.select_relative(|_| 5);
+        assert!(!state.scroll(3, 20));
+        assert_eq!((state.offset(), state.selected_index()), (3, Some(5)));
+        assert!(state.scroll(3, 20));
+        assert_eq!((state.offset(), state.selected_index()), (6, Some(6)));
+        state.scroll_into_view(20);
+        assert_eq!(state.offset(), 6);
+        assert!(!state.scroll(-100, 20));
+        assert_eq!((state.offset(), state.selected_index()), (0, Some(6)));
+    }
+}
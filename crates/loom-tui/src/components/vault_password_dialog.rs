@@ -49,6 +49,11 @@ impl VaultPasswordDialog {
         }
     }
 
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
     /// Show dialog for creating a new vault.
     pub fn show_create(&mut self) {
         self.mode = VaultDialogMode::CreateVault;
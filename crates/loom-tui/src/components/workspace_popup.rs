@@ -0,0 +1,158 @@
+use crossterm::event::{KeyCode, KeyEvent};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::Modifier;
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph};
+use ratatui::Frame;
+
+use crate::action::Action;
+use crate::components::popup::Popup;
+use crate::config::WorkspaceConfig;
+use crate::theme::Theme;
+
+/// Popup listing the configured workspaces. Enter opens every profile of
+/// the highlighted one.
+pub struct WorkspacePopup {
+    pub visible: bool,
+    popup: Popup,
+    theme: Theme,
+    workspaces: Vec<WorkspaceConfig>,
+    list_state: ListState,
+}
+
+impl WorkspacePopup {
+    pub fn new(theme: Theme) -> Self {
+        Self {
+            visible: false,
+            popup: Popup::new("Workspaces", theme.clone()).with_size(70, 60),
+            theme,
+            workspaces: Vec::new(),
+            list_state: ListState::default(),
+        }
+    }
+
+    pub fn set_theme(&mut self, theme: &Theme) {
+        self.theme = theme.clone();
+        self.popup.set_theme(theme);
+    }
+
+    pub fn show(&mut self, workspaces: Vec<WorkspaceConfig>) {
+        self.workspaces = workspaces;
+        self.list_state.select(Some(0));
+        self.visible = true;
+        self.popup.show();
+    }
+
+    pub fn hide(&mut self) {
+        self.visible = false;
+        self.popup.hide();
+    }
+
+    pub fn handle_key_event(&mut self, key: KeyEvent) -> Action {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.hide();
+                Action::ClosePopup
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                self.list_state.select(Some(i.saturating_sub(1)));
+                Action::None
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                let i = self.list_state.selected().unwrap_or(0);
+                if i + 1 < self.workspaces.len() {
+                    self.list_state.select(Some(i + 1));
+                }
+                Action::None
+            }
+            KeyCode::Enter => {
+                let name = self
+                    .list_state
+                    .selected()
+                    .and_then(|i| self.workspaces.get(i))
+                    .map(|w| w.name.clone());
+                match name {
+                    Some(name) => {
+                        self.hide();
+                        Action::OpenWorkspace(name)
+                    }
+                    None => Action::None,
+                }
+            }
+            _ => Action::None,
+        }
+    }
+
+    pub fn render(&mut self, frame: &mut Frame, full: Rect) {
+        if !self.visible {
+            return;
+        }
+
+        let area = self.popup.centered_area(full);
+        frame.render_widget(Clear, area);
+
+        let block = Block::default()
+            .title(" Workspaces ")
+            .borders(Borders::ALL)
+            .border_style(self.theme.popup_border)
+            .title_style(self.theme.popup_title);
+
+        let inner = block.inner(area);
+        frame.render_widget(block, area);
+
+        let layout = Layout::vertical([
+            Constraint::Min(1),    // List
+            Constraint::Length(1), // Hints
+        ])
+        .split(inner);
+
+        let items: Vec<ListItem> = self
+            .workspaces
+            .iter()
+            .map(|w| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(format!("{:<16} ", w.name), self.theme.normal),
+                    Span::styled(w.profiles.join(", "), self.theme.dimmed),
+                ]))
+            })
+            .collect();
+
+        let list = List::new(items)
+            .highlight_style(self.theme.selected.add_modifier(Modifier::BOLD))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(list, layout[0], &mut self.list_state);
+
+        let hints = Paragraph::new(Line::from(Span::styled(
+            "j/k:navigate  Enter:open all  Esc:close",
+            self.theme.dimmed,
+        )));
+        frame.render_widget(hints, layout[1]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyModifiers;
+
+    fn key(code: KeyCode) -> KeyEvent {
+        KeyEvent::new(code, KeyModifiers::NONE)
+    }
+
+    #[test]
+    fn test_open_workspace() {
+        let mut popup = WorkspacePopup::new(Theme::load("dark"));
+        let workspace = |name: &str| WorkspaceConfig {
+            name: name.to_string(),
+            profiles: vec!["dc1".to_string()],
+        };
+        popup.show(vec![workspace("prod"), workspace("lab")]);
+
+        popup.handle_key_event(key(KeyCode::Down));
+        popup.handle_key_event(key(KeyCode::Down));
+        let action = popup.handle_key_event(key(KeyCode::Enter));
+        assert!(matches!(action, Action::OpenWorkspace(name) if name == "lab"));
+        assert!(!popup.visible);
+    }
+}
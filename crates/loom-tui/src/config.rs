@@ -1,13 +1,34 @@
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
 use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
 
+use loom_core::audit::AuditSettings;
+use loom_core::auth::SaslMechanism;
+use loom_core::bulk::Throttle;
 use loom_core::connection::{ConnectionSettings, TlsMode};
-use loom_core::credentials::CredentialMethod;
+use loom_core::credentials::{CredentialMethod, CredentialProvider};
+use loom_core::format::FormatRule;
+use loom_core::policy::SafetyPolicy;
+use loom_core::reports::ReportDefinition;
 use loom_core::tls::TrustedCertEntry;
+use loom_core::tunnel::{ProxySettings, SshTunnelSettings};
+
+use crate::exporter::PrometheusConfig;
+use crate::hooks::HooksConfig;
+use crate::theme::ThemeVariant;
 
 /// A saved connection profile.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConnectionProfile {
     pub name: String,
+    /// One host, or several separated by commas to fail over between;
+    /// written in the config file as a string or a list.
+    #[serde(
+        deserialize_with = "host_list::deserialize",
+        serialize_with = "host_list::serialize"
+    )]
     pub host: String,
     #[serde(default = "default_port")]
     pub port: u16,
@@ -17,6 +38,25 @@ pub struct ConnectionProfile {
     pub bind_dn: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub base_dn: Option<String>,
+    /// Entry the tree opens and selects on connect.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_dn: Option<String>,
+    /// Filter pre-filled in the search input.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub default_filter: Option<String>,
+    /// Extra RootDSE attributes to request, shown in the status bar.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub root_dse_attributes: Vec<String>,
+    /// Pinned DNs, listed in the tree's Favorites section.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub favorites: Vec<String>,
+    /// Filters listed as folders in the tree's Searches section.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub saved_searches: Vec<SavedSearch>,
+    /// TOML file naming attributes owned by other systems, which loom
+    /// refuses to edit.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub edit_policy: Option<String>,
     #[serde(default)]
     pub credential_method: CredentialMethod,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -25,8 +65,42 @@ pub struct ConnectionProfile {
     pub page_size: u32,
     #[serde(default = "default_timeout")]
     pub timeout_secs: u64,
+    /// Connections opened for this profile's tab, including the main one.
+    /// Extra ones are opened on demand for reads; 1 disables pooling.
+    #[serde(
+        default = "default_pool_size",
+        skip_serializing_if = "is_default_pool_size"
+    )]
+    pub pool_size: usize,
     #[serde(default)]
     pub relax_rules: bool,
+    /// Bind with this SASL mechanism, checked against the server's
+    /// `supportedSASLMechanisms`; a simple bind when unset.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sasl_mechanism: Option<SaslMechanism>,
+    /// Identity to act as through the Proxied Authorization control.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authz_id: Option<String>,
+    /// SOCKS5 or HTTP CONNECT proxy the server is reached through.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub proxy: Option<ProxySettings>,
+    /// Jump host the server is reached through with `ssh`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ssh_tunnel: Option<SshTunnelSettings>,
+    /// Run `$(command)` substitutions in host, bind_dn and base_dn. Only
+    /// for profiles written by hand; importers never set it.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub allow_commands: bool,
+    /// `host` is a domain whose servers are found from DNS SRV records.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub dns_srv: bool,
+    /// Try the hosts in random order instead of as listed.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub shuffle_hosts: bool,
+    /// Typed delete confirmations, double-confirmed bulk updates and a
+    /// subtree writes are confined to.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub safety: Option<SafetyPolicy>,
     #[serde(default, skip_serializing_if = "Option::is_none")]
     pub folder: Option<String>,
     #[serde(default, skip_serializing_if = "is_false")]
@@ -35,10 +109,213 @@ pub struct ConnectionProfile {
     pub offline: bool,
 }
 
+impl Default for ConnectionProfile {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            host: String::new(),
+            port: default_port(),
+            tls_mode: TlsMode::default(),
+            bind_dn: None,
+            base_dn: None,
+            start_dn: None,
+            default_filter: None,
+            root_dse_attributes: Vec::new(),
+            favorites: Vec::new(),
+            saved_searches: Vec::new(),
+            edit_policy: None,
+            credential_method: CredentialMethod::default(),
+            password_command: None,
+            page_size: default_page_size(),
+            timeout_secs: default_timeout(),
+            pool_size: default_pool_size(),
+            relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            allow_commands: false,
+            dns_srv: false,
+            shuffle_hosts: false,
+            safety: None,
+            folder: None,
+            read_only: false,
+            offline: false,
+        }
+    }
+}
+
+/// A named filter saved on a profile, run when its tree folder opens.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SavedSearch {
+    pub name: String,
+    pub filter: String,
+}
+
+/// Expand a leading `~/` in a configured path to the home directory.
+pub fn expand_home(path: &str) -> PathBuf {
+    match path.strip_prefix("~/").zip(dirs::home_dir()) {
+        Some((rest, home)) => home.join(rest),
+        None => PathBuf::from(path),
+    }
+}
+
+/// Normalize a folder path like " prod//emea/ " to "prod/emea".
+/// Returns None when no non-empty segments remain (i.e. the root).
+pub fn normalize_folder_path(path: &str) -> Option<String> {
+    let parts: Vec<&str> = path
+        .split('/')
+        .map(str::trim)
+        .filter(|p| !p.is_empty())
+        .collect();
+    if parts.is_empty() {
+        None
+    } else {
+        Some(parts.join("/"))
+    }
+}
+
+/// Expand `${VAR}` from the environment and `$(command)` from the
+/// command's stdout (run via `sh -c`, trailing newlines trimmed).
+/// `$$` produces a literal `$`; any other `$` is kept as-is. A `$(` is an
+/// error unless `allow_commands` is set.
+pub fn interpolate(input: &str, allow_commands: bool) -> Result<String, String> {
+    let mut out = String::with_capacity(input.len());
+    let mut rest = input;
+
+    while let Some(pos) = rest.find('$') {
+        out.push_str(&rest[..pos]);
+        let tail = &rest[pos + 1..];
+
+        if let Some(after) = tail.strip_prefix('$') {
+            out.push('$');
+            rest = after;
+        } else if let Some(after) = tail.strip_prefix('{') {
+            let end = after
+                .find('}')
+                .ok_or_else(|| format!("Unterminated ${{ in '{}'", input))?;
+            let var = &after[..end];
+            let value = std::env::var(var)
+                .map_err(|_| format!("Environment variable '{}' is not set", var))?;
+            out.push_str(&value);
+            rest = &after[end + 1..];
+        } else if let Some(after) = tail.strip_prefix('(') {
+            if !allow_commands {
+                return Err(format!(
+                    "Command substitution in '{}' needs allow_commands = true",
+                    input
+                ));
+            }
+            let end = find_closing_paren(after)
+                .ok_or_else(|| format!("Unterminated $( in '{}'", input))?;
+            out.push_str(&run_substitution(&after[..end])?);
+            rest = &after[end + 1..];
+        } else {
+            out.push('$');
+            rest = tail;
+        }
+    }
+
+    out.push_str(rest);
+    Ok(out)
+}
+
+/// Byte offset of the ')' closing a `$(`, allowing nested parentheses.
+fn find_closing_paren(s: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    for (i, c) in s.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(i),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Escape every `$` in an imported value so loading it never expands
+/// anything.
+fn escape_interpolation(value: &str) -> String {
+    value.replace('$', "$$")
+}
+
+fn run_substitution(command: &str) -> Result<String, String> {
+    let output = std::process::Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .output()
+        .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+    if !output.status.success() {
+        return Err(format!("Command '{}' failed ({})", command, output.status));
+    }
+
+    String::from_utf8(output.stdout)
+        .map(|s| s.trim_end_matches(['\n', '\r']).to_string())
+        .map_err(|e| format!("Invalid UTF-8 from '{}': {}", command, e))
+}
+
+/// A profile's `host` as either a string or a list of hosts, held as one
+/// comma-separated string.
+mod host_list {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Hosts {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+        Ok(match Hosts::deserialize(deserializer)? {
+            Hosts::One(host) => host,
+            Hosts::Many(hosts) => hosts.join(", "),
+        })
+    }
+
+    pub fn serialize<S: Serializer>(host: &str, serializer: S) -> Result<S::Ok, S::Error> {
+        if host.contains(',') {
+            super::split_hosts(host).serialize(serializer)
+        } else {
+            host.serialize(serializer)
+        }
+    }
+}
+
+/// The hosts in a comma-separated `host`, in order.
+pub fn split_hosts(host: &str) -> Vec<&str> {
+    host.split(',')
+        .map(str::trim)
+        .filter(|h| !h.is_empty())
+        .collect()
+}
+
 fn is_false(v: &bool) -> bool {
     !v
 }
 
+fn is_zero(v: &u64) -> bool {
+    *v == 0
+}
+
+fn is_default_throttle(v: &Throttle) -> bool {
+    *v == Throttle::default()
+}
+
+fn is_default_audit(v: &AuditSettings) -> bool {
+    *v == AuditSettings::default()
+}
+
+fn is_default_prometheus(v: &PrometheusConfig) -> bool {
+    *v == PrometheusConfig::default()
+}
+
+fn is_default_variant(v: &ThemeVariant) -> bool {
+    *v == ThemeVariant::default()
+}
+
 fn is_true(v: &bool) -> bool {
     *v
 }
@@ -56,12 +333,19 @@ fn default_page_size() -> u32 {
 fn default_timeout() -> u64 {
     30
 }
+fn default_pool_size() -> usize {
+    3
+}
+fn is_default_pool_size(v: &usize) -> bool {
+    *v == default_pool_size()
+}
 
 impl ConnectionProfile {
     /// Convert to ConnectionSettings for connecting.
     pub fn to_connection_settings(&self) -> ConnectionSettings {
+        let hosts = split_hosts(&self.host);
         ConnectionSettings {
-            host: self.host.clone(),
+            host: hosts.first().copied().unwrap_or_default().to_string(),
             port: self.port,
             tls_mode: self.tls_mode.clone(),
             bind_dn: self.bind_dn.clone(),
@@ -69,6 +353,24 @@ impl ConnectionProfile {
             page_size: self.page_size,
             timeout_secs: self.timeout_secs,
             relax_rules: self.relax_rules,
+            sasl_mechanism: self.sasl_mechanism,
+            authz_id: self.authz_id.clone(),
+            proxy: self.proxy.clone(),
+            ssh_tunnel: self.ssh_tunnel.clone(),
+            dns_srv: self.dns_srv,
+            failover_hosts: hosts.iter().skip(1).map(|h| h.to_string()).collect(),
+            shuffle_hosts: self.shuffle_hosts,
+        }
+    }
+
+    /// Make a profile read from someone else's file safe to save: turn off
+    /// command substitution and escape `$` in the interpolated fields so
+    /// they load as written.
+    pub fn neutralize_imported(&mut self) {
+        self.allow_commands = false;
+        self.host = escape_interpolation(&self.host);
+        for value in [&mut self.bind_dn, &mut self.base_dn].into_iter().flatten() {
+            *value = escape_interpolation(value);
         }
     }
 }
@@ -89,11 +391,47 @@ pub struct KeybindingConfig {
     pub show_schema_viewer: String,
     pub show_help: String,
     pub toggle_log_panel: String,
+    pub toggle_jobs_panel: String,
+    pub toggle_trace_panel: String,
+    pub toggle_metrics_panel: String,
+    pub show_replication: String,
+    pub show_account_reports: String,
+    pub force_refresh: String,
     pub save_connection: String,
     pub switch_to_profiles: String,
     pub next_tab: String,
     pub prev_tab: String,
     pub close_tab: String,
+    pub toggle_dry_run: String,
+    pub toggle_staging: String,
+    pub toggle_changes_panel: String,
+    pub show_recent: String,
+    pub toggle_split: String,
+    pub swap_split_focus: String,
+    pub copy_to_other_side: String,
+    pub goto_dn: String,
+    pub quick_open: String,
+    pub nav_back: String,
+    pub nav_forward: String,
+    pub show_history: String,
+    pub show_favorites: String,
+    pub show_workspaces: String,
+    pub command_palette: String,
+    /// `[keybindings.global]`: extra `key = "action"` bindings active everywhere.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub global: BTreeMap<String, String>,
+    /// `[keybindings.tree]`: bindings for the tree panel, checked before its own keys.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tree: BTreeMap<String, String>,
+    /// `[keybindings.detail]`: bindings for the detail panel.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub detail: BTreeMap<String, String>,
+    /// `[keybindings.search_results]`: bindings for the search results list.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub search_results: BTreeMap<String, String>,
+    /// `[keybindings.dialogs]`: bindings for any open popup or dialog.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub dialogs: BTreeMap<String, String>,
 }
 
 impl Default for KeybindingConfig {
@@ -110,11 +448,37 @@ impl Default for KeybindingConfig {
             show_schema_viewer: "F6".to_string(),
             show_help: "F5".to_string(),
             toggle_log_panel: "F7".to_string(),
+            toggle_jobs_panel: "Alt+j".to_string(),
+            toggle_trace_panel: "Alt+o".to_string(),
+            toggle_metrics_panel: "Alt+m".to_string(),
+            show_replication: "Alt+r".to_string(),
+            show_account_reports: "Alt+u".to_string(),
+            force_refresh: "Ctrl+r".to_string(),
             save_connection: "F10".to_string(),
             switch_to_profiles: "F1".to_string(),
             next_tab: "Ctrl+Right".to_string(),
             prev_tab: "Ctrl+Left".to_string(),
             close_tab: "Ctrl+w".to_string(),
+            toggle_dry_run: "F12".to_string(),
+            toggle_staging: "Alt+s".to_string(),
+            toggle_changes_panel: "Alt+c".to_string(),
+            show_recent: "Ctrl+o".to_string(),
+            toggle_split: "Ctrl+s".to_string(),
+            swap_split_focus: "Ctrl+t".to_string(),
+            copy_to_other_side: "Ctrl+y".to_string(),
+            goto_dn: "Ctrl+g".to_string(),
+            quick_open: "Ctrl+e".to_string(),
+            nav_back: "Alt+Left".to_string(),
+            nav_forward: "Alt+Right".to_string(),
+            show_history: "Alt+h".to_string(),
+            show_favorites: "Ctrl+f".to_string(),
+            show_workspaces: "Alt+w".to_string(),
+            command_palette: "Ctrl+p".to_string(),
+            global: BTreeMap::new(),
+            tree: BTreeMap::new(),
+            detail: BTreeMap::new(),
+            search_results: BTreeMap::new(),
+            dialogs: BTreeMap::new(),
         }
     }
 }
@@ -127,6 +491,16 @@ pub struct FolderConfig {
     pub description: String,
 }
 
+/// `[[workspaces]]`: profiles opened together, e.g. every domain
+/// controller of one forest.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkspaceConfig {
+    pub name: String,
+    /// Profile names, opened in this order.
+    #[serde(default)]
+    pub profiles: Vec<String>,
+}
+
 /// Top-level application configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -134,21 +508,188 @@ pub struct AppConfig {
     pub general: GeneralConfig,
     #[serde(default)]
     pub keybindings: KeybindingConfig,
+    #[serde(default, skip_serializing_if = "DisplayConfig::is_empty")]
+    pub display: DisplayConfig,
+    /// `[bulk]`: default pacing for bulk updates.
+    #[serde(default, skip_serializing_if = "is_default_throttle")]
+    pub bulk: Throttle,
+    /// `[audit]`: the file writes are logged to, and its rotation.
+    #[serde(default, skip_serializing_if = "is_default_audit")]
+    pub audit: AuditSettings,
+    /// `[hooks]`: commands run on events such as exports and edits.
+    #[serde(default, skip_serializing_if = "HooksConfig::is_empty")]
+    pub hooks: HooksConfig,
+    /// `[prometheus]`: the metrics endpoint.
+    #[serde(default, skip_serializing_if = "is_default_prometheus")]
+    pub prometheus: PrometheusConfig,
     #[serde(default)]
     pub connections: Vec<ConnectionProfile>,
     #[serde(default)]
     pub folders: Vec<FolderConfig>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub workspaces: Vec<WorkspaceConfig>,
+    /// `[[reports]]`: user-defined reports, run from the reports popup,
+    /// `:report` and `--run-report`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reports: Vec<ReportDefinition>,
     #[serde(default)]
     pub trusted_certificates: Vec<TrustedCertEntry>,
     /// True when no config file was found on disk (first launch).
     #[serde(skip)]
     pub first_launch: bool,
+    /// Original `${VAR}` / `$(cmd)` templates for interpolated profile fields,
+    /// restored by `save()` so expanded values are never written back.
+    #[serde(skip)]
+    templates: Vec<FieldTemplate>,
+}
+
+/// `[display]`: how attribute values are shown.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DisplayConfig {
+    /// `[display.attributes]`: attribute name to renderer, applied in the
+    /// detail panel and search result columns.
+    #[serde(default)]
+    pub attributes: BTreeMap<String, FormatRule>,
+    /// `[[display.groups]]`: sections of the detail panel, in order. Unset
+    /// means the built-in groups; an empty list turns grouping off.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub groups: Option<Vec<AttributeGroup>>,
+}
+
+impl DisplayConfig {
+    fn is_empty(&self) -> bool {
+        self.attributes.is_empty() && self.groups.is_none()
+    }
+
+    /// The configured groups, or the built-in ones.
+    pub fn attribute_groups(&self) -> Vec<AttributeGroup> {
+        self.groups.clone().unwrap_or_else(AttributeGroup::defaults)
+    }
+}
+
+/// A collapsible section of the detail panel. Attributes not listed in any
+/// group are shown under "other".
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AttributeGroup {
+    pub name: String,
+    pub attributes: Vec<String>,
+}
+
+impl AttributeGroup {
+    fn new(name: &str, attributes: &[&str]) -> Self {
+        Self {
+            name: name.to_string(),
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+        }
+    }
+
+    /// Identity, contact, security and operational attributes common to
+    /// Active Directory and OpenLDAP.
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new(
+                "identity",
+                &[
+                    "objectClass",
+                    "cn",
+                    "displayName",
+                    "givenName",
+                    "sn",
+                    "uid",
+                    "sAMAccountName",
+                    "userPrincipalName",
+                    "employeeID",
+                    "employeeNumber",
+                    "employeeType",
+                    "title",
+                    "department",
+                    "company",
+                    "manager",
+                    "description",
+                ],
+            ),
+            Self::new(
+                "contact",
+                &[
+                    "mail",
+                    "proxyAddresses",
+                    "telephoneNumber",
+                    "mobile",
+                    "facsimileTelephoneNumber",
+                    "physicalDeliveryOfficeName",
+                    "streetAddress",
+                    "postalAddress",
+                    "l",
+                    "st",
+                    "postalCode",
+                    "c",
+                    "co",
+                ],
+            ),
+            Self::new(
+                "security",
+                &[
+                    "memberOf",
+                    "member",
+                    "userAccountControl",
+                    "pwdLastSet",
+                    "accountExpires",
+                    "lockoutTime",
+                    "badPwdCount",
+                    "badPasswordTime",
+                    "pwdChangedTime",
+                    "pwdAccountLockedTime",
+                    "pwdPolicySubentry",
+                    "objectSid",
+                    "primaryGroupID",
+                    "adminCount",
+                    "nsAccountLock",
+                ],
+            ),
+            Self::new(
+                "operational",
+                &[
+                    "whenCreated",
+                    "whenChanged",
+                    "createTimestamp",
+                    "modifyTimestamp",
+                    "creatorsName",
+                    "modifiersName",
+                    "lastLogon",
+                    "lastLogonTimestamp",
+                    "logonCount",
+                    "uSNCreated",
+                    "uSNChanged",
+                    "objectGUID",
+                    "entryUUID",
+                    "entryCSN",
+                    "entryDN",
+                    "distinguishedName",
+                    "instanceType",
+                    "objectCategory",
+                    "dSCorePropagationData",
+                ],
+            ),
+        ]
+    }
+}
+
+/// A profile field whose value was produced by interpolation.
+#[derive(Debug, Clone)]
+struct FieldTemplate {
+    profile: String,
+    field: &'static str,
+    template: String,
+    value: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GeneralConfig {
     #[serde(default = "default_theme")]
     pub theme: String,
+    /// Which of a theme file's `[dark]` and `[light]` tables to use.
+    #[serde(default, skip_serializing_if = "is_default_variant")]
+    pub theme_variant: ThemeVariant,
     #[serde(default = "default_tick_rate")]
     pub tick_rate_ms: u64,
     #[serde(default = "default_log_level")]
@@ -159,6 +700,60 @@ pub struct GeneralConfig {
     pub live_search: bool,
     #[serde(default, skip_serializing_if = "is_false")]
     pub vault_enabled: bool,
+    /// Minutes of inactivity before the vault is locked (0 disables auto-lock).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub idle_lock_minutes: u64,
+    /// Also unbind live connections when the session auto-locks.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub idle_lock_disconnect: bool,
+    /// Periodically write session metrics as JSON to this file.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stats_file: Option<String>,
+    /// Seconds between stats file writes.
+    #[serde(
+        default = "default_stats_interval",
+        skip_serializing_if = "is_default_stats_interval"
+    )]
+    pub stats_interval_secs: u64,
+    /// Seconds between refreshes of the server metrics view.
+    #[serde(
+        default = "default_metrics_interval",
+        skip_serializing_if = "is_default_metrics_interval"
+    )]
+    pub metrics_interval_secs: u64,
+    /// Listen for remote control commands on this Unix socket.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ipc_socket: Option<String>,
+    /// Also accept JSON-RPC requests on the remote control socket, from
+    /// clients holding the session token.
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub rpc_enabled: bool,
+    /// Seconds a loaded entry is reused before it is read again (0 disables
+    /// the entry cache).
+    #[serde(
+        default = "default_entry_cache_ttl",
+        skip_serializing_if = "is_default_entry_cache_ttl"
+    )]
+    pub entry_cache_ttl_secs: u64,
+    /// Entries cached per tab.
+    #[serde(
+        default = "default_entry_cache_size",
+        skip_serializing_if = "is_default_entry_cache_size"
+    )]
+    pub entry_cache_size: usize,
+    /// Count children with a sized search when the server doesn't report
+    /// a subordinate count.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub child_count_search: bool,
+    /// Rows moved per mouse wheel notch.
+    #[serde(
+        default = "default_scroll_lines",
+        skip_serializing_if = "is_default_scroll_lines"
+    )]
+    pub scroll_lines: usize,
+    /// Save the open tabs on quit and offer to reopen them at launch.
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub restore_session: bool,
 }
 
 fn default_theme() -> String {
@@ -170,16 +765,59 @@ fn default_tick_rate() -> u64 {
 fn default_log_level() -> String {
     "info".to_string()
 }
+fn default_stats_interval() -> u64 {
+    30
+}
+fn is_default_stats_interval(v: &u64) -> bool {
+    *v == default_stats_interval()
+}
+fn default_metrics_interval() -> u64 {
+    5
+}
+fn is_default_metrics_interval(v: &u64) -> bool {
+    *v == default_metrics_interval()
+}
+fn default_entry_cache_ttl() -> u64 {
+    60
+}
+fn is_default_entry_cache_ttl(v: &u64) -> bool {
+    *v == default_entry_cache_ttl()
+}
+fn default_entry_cache_size() -> usize {
+    500
+}
+fn is_default_entry_cache_size(v: &usize) -> bool {
+    *v == default_entry_cache_size()
+}
+fn default_scroll_lines() -> usize {
+    3
+}
+fn is_default_scroll_lines(v: &usize) -> bool {
+    *v == default_scroll_lines()
+}
 
 impl Default for GeneralConfig {
     fn default() -> Self {
         Self {
             theme: default_theme(),
+            theme_variant: ThemeVariant::default(),
             tick_rate_ms: default_tick_rate(),
             log_level: default_log_level(),
             autocomplete: true,
             live_search: true,
             vault_enabled: false,
+            idle_lock_minutes: 0,
+            idle_lock_disconnect: false,
+            stats_file: None,
+            stats_interval_secs: default_stats_interval(),
+            metrics_interval_secs: default_metrics_interval(),
+            ipc_socket: None,
+            rpc_enabled: false,
+            entry_cache_ttl_secs: default_entry_cache_ttl(),
+            entry_cache_size: default_entry_cache_size(),
+            child_count_search: true,
+            scroll_lines: default_scroll_lines(),
+            restore_session: true,
         }
     }
 }
@@ -193,8 +831,17 @@ impl AppConfig {
 
         if config_path.exists() {
             if let Ok(content) = std::fs::read_to_string(&config_path) {
-                if let Ok(config) = toml::from_str::<AppConfig>(&content) {
-                    return config;
+                match toml::from_str::<AppConfig>(&content) {
+                    Ok(mut config) => {
+                        config.interpolate_profiles();
+                        config.move_proxy_passwords();
+                        return config;
+                    }
+                    Err(e) => warn!(
+                        "Ignoring {}, which couldn't be read: {}",
+                        config_path.display(),
+                        e
+                    ),
                 }
             }
         }
@@ -205,6 +852,20 @@ impl AppConfig {
         }
     }
 
+    /// The workspace called `name`, ignoring case.
+    pub fn workspace(&self, name: &str) -> Option<&WorkspaceConfig> {
+        self.workspaces
+            .iter()
+            .find(|w| w.name.eq_ignore_ascii_case(name))
+    }
+
+    /// The `[[reports]]` entry named `name`, ignoring case.
+    pub fn report(&self, name: &str) -> Option<&ReportDefinition> {
+        self.reports
+            .iter()
+            .find(|r| r.name.eq_ignore_ascii_case(name))
+    }
+
     /// Parse config from a TOML string.
     pub fn from_toml(content: &str) -> Result<Self, toml::de::Error> {
         toml::from_str(content)
@@ -219,7 +880,7 @@ impl AppConfig {
         std::fs::create_dir_all(&config_dir)
             .map_err(|e| format!("Failed to create config dir: {}", e))?;
 
-        let content = toml::to_string_pretty(self)
+        let content = toml::to_string_pretty(&self.with_templates())
             .map_err(|e| format!("Failed to serialize config: {}", e))?;
 
         std::fs::write(config_dir.join("config.toml"), content)
@@ -228,6 +889,83 @@ impl AppConfig {
         Ok(())
     }
 
+    /// Expand `${VAR}` in each profile's host, bind_dn and base_dn, and
+    /// `$(command)` in profiles with `allow_commands`. A field that fails
+    /// to expand is left as written and a warning is logged.
+    pub fn interpolate_profiles(&mut self) {
+        for profile in &mut self.connections {
+            let name = profile.name.clone();
+            let allow_commands = profile.allow_commands;
+            let fields: [(&'static str, &mut String); 3] = [
+                ("host", &mut profile.host),
+                ("bind_dn", profile.bind_dn.get_or_insert_with(String::new)),
+                ("base_dn", profile.base_dn.get_or_insert_with(String::new)),
+            ];
+            for (field, value) in fields {
+                if !value.contains('$') {
+                    continue;
+                }
+                match interpolate(value, allow_commands) {
+                    Ok(expanded) => {
+                        self.templates.push(FieldTemplate {
+                            profile: name.clone(),
+                            field,
+                            template: std::mem::replace(value, expanded.clone()),
+                            value: expanded,
+                        });
+                    }
+                    Err(e) => warn!("Profile '{}' {}: {}", name, field, e),
+                }
+            }
+            // get_or_insert_with above must not turn None into Some("")
+            if profile.bind_dn.as_deref() == Some("") {
+                profile.bind_dn = None;
+            }
+            if profile.base_dn.as_deref() == Some("") {
+                profile.base_dn = None;
+            }
+        }
+    }
+
+    /// Move proxy passwords still written in the config file to the OS
+    /// keychain. They stay in memory for this session but are never saved
+    /// back to the file.
+    fn move_proxy_passwords(&self) {
+        for profile in &self.connections {
+            let Some(password) = profile.proxy.as_ref().and_then(|p| p.password.as_deref()) else {
+                continue;
+            };
+            let key = ProxySettings::credential_key(&profile.name);
+            match CredentialProvider::store_in_keychain(&key, password) {
+                Ok(()) => info!("Profile '{}': moved the proxy password to the keychain", profile.name),
+                Err(e) => warn!(
+                    "Profile '{}': couldn't move the proxy password to the keychain ({}); it is dropped from config.toml on the next save",
+                    profile.name, e
+                ),
+            }
+        }
+    }
+
+    /// Copy of the config with interpolated fields put back to their
+    /// templates, as long as the profile still holds the expanded value.
+    fn with_templates(&self) -> AppConfig {
+        let mut config = self.clone();
+        for t in &self.templates {
+            let Some(profile) = config.connections.iter_mut().find(|p| p.name == t.profile) else {
+                continue;
+            };
+            let slot = match t.field {
+                "host" => Some(&mut profile.host),
+                "bind_dn" => profile.bind_dn.as_mut(),
+                _ => profile.base_dn.as_mut(),
+            };
+            if let Some(slot) = slot.filter(|s| **s == t.value) {
+                *slot = t.template.clone();
+            }
+        }
+        config
+    }
+
     /// Look up a folder description by path. Returns None if not found or empty.
     pub fn folder_description(&self, path: &str) -> Option<&str> {
         self.folders
@@ -251,11 +989,57 @@ impl AppConfig {
         }
     }
 
+    /// Move a profile one place up (`delta < 0`) or down among the profiles
+    /// in the same folder. Returns the profile's new index, or None if it is
+    /// already first/last in its folder.
+    pub fn move_connection(&mut self, index: usize, delta: isize) -> Option<usize> {
+        let folder = self
+            .connections
+            .get(index)?
+            .folder
+            .as_deref()
+            .and_then(normalize_folder_path);
+        let same_folder =
+            |p: &ConnectionProfile| p.folder.as_deref().and_then(normalize_folder_path) == folder;
+
+        let target = if delta < 0 {
+            self.connections[..index].iter().rposition(same_folder)?
+        } else {
+            index + 1 + self.connections[index + 1..].iter().position(same_folder)?
+        };
+        self.connections.swap(index, target);
+        Some(target)
+    }
+
+    /// Every folder path in use, including parents of nested folders and
+    /// folders that only have a `[[folders]]` description, sorted.
+    pub fn folder_paths(&self) -> Vec<String> {
+        let mut paths = std::collections::BTreeSet::new();
+        let declared = self.folders.iter().map(|f| f.path.as_str());
+        let used = self.connections.iter().filter_map(|p| p.folder.as_deref());
+        for path in declared.chain(used).filter_map(normalize_folder_path) {
+            let mut prefix = String::new();
+            for part in path.split('/') {
+                if !prefix.is_empty() {
+                    prefix.push('/');
+                }
+                prefix.push_str(part);
+                paths.insert(prefix.clone());
+            }
+        }
+        paths.into_iter().collect()
+    }
+
     /// Serialize selected profiles to a TOML string with [[connections]] blocks.
+    /// No secrets are exported.
     pub fn export_profiles(profiles: &[ConnectionProfile]) -> Result<String, String> {
         let mut output = String::from("# loom-ldapbrowser — Exported Profiles\n");
         for profile in profiles {
-            let block = toml::to_string(profile)
+            let mut profile = profile.clone();
+            if let Some(proxy) = profile.proxy.as_mut() {
+                proxy.password = None;
+            }
+            let block = toml::to_string(&profile)
                 .map_err(|e| format!("Failed to serialize profile '{}': {}", profile.name, e))?;
             output.push_str("\n[[connections]]\n");
             output.push_str(&block);
@@ -264,6 +1048,7 @@ impl AppConfig {
     }
 
     /// Parse profiles from a TOML string (expects [[connections]] blocks).
+    /// The profiles are neutralized, so a shared file can't run commands.
     pub fn import_profiles(content: &str) -> Result<Vec<ConnectionProfile>, String> {
         // Wrap in a minimal AppConfig-like structure for parsing
         #[derive(Deserialize)]
@@ -271,11 +1056,14 @@ impl AppConfig {
             #[serde(default)]
             connections: Vec<ConnectionProfile>,
         }
-        let parsed: ProfilesFile =
+        let mut parsed: ProfilesFile =
             toml::from_str(content).map_err(|e| format!("Failed to parse TOML: {}", e))?;
         if parsed.connections.is_empty() {
             return Err("No [[connections]] profiles found in file".to_string());
         }
+        for profile in &mut parsed.connections {
+            profile.neutralize_imported();
+        }
         Ok(parsed.connections)
     }
 
@@ -381,11 +1169,26 @@ timeout_secs = 60
             tls_mode: TlsMode::None,
             bind_dn: Some("cn=admin".to_string()),
             base_dn: Some("dc=test".to_string()),
+            start_dn: None,
+            default_filter: None,
+            root_dse_attributes: Vec::new(),
+            favorites: Vec::new(),
+            saved_searches: Vec::new(),
+            edit_policy: None,
             credential_method: CredentialMethod::Prompt,
             password_command: None,
             page_size: 500,
             timeout_secs: 30,
+            pool_size: 3,
             relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            allow_commands: false,
+            dns_srv: false,
+            shuffle_hosts: false,
+            safety: None,
             folder: None,
             read_only: false,
             offline: false,
@@ -438,6 +1241,76 @@ host = "localhost"
         assert_eq!(conn.port, 389); // default
         assert_eq!(conn.page_size, 500); // default
         assert_eq!(conn.timeout_secs, 30); // default
+        assert_eq!(conn.pool_size, 3); // default
+    }
+
+    #[test]
+    fn test_parse_unsupported_sasl_mechanism() {
+        let toml = r#"
+[[connections]]
+name = "Digest"
+host = "localhost"
+sasl_mechanism = "DIGEST-MD5"
+"#;
+        let err = AppConfig::from_toml(toml).unwrap_err().to_string();
+        assert!(err.contains("SASL mechanism \"DIGEST-MD5\" isn't supported"));
+        assert!(err.contains("line 5"));
+    }
+
+    #[test]
+    fn test_parse_authz_id() {
+        let toml = r#"
+[[connections]]
+name = "Helpdesk"
+host = "localhost"
+bind_dn = "cn=svc-helpdesk,dc=test"
+authz_id = "dn:uid=alice,ou=People,dc=test"
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        let settings = config.connections[0].to_connection_settings();
+        assert_eq!(
+            settings.authz_id.as_deref(),
+            Some("dn:uid=alice,ou=People,dc=test")
+        );
+        assert_eq!(settings.sasl_mechanism, None);
+    }
+
+    #[test]
+    fn test_parse_host_list() {
+        let toml = r#"
+[[connections]]
+name = "Cluster"
+host = ["ldap1.example.com", "ldap2.example.com", "ldap3.example.com"]
+shuffle_hosts = true
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        let profile = &config.connections[0];
+        assert_eq!(
+            profile.host,
+            "ldap1.example.com, ldap2.example.com, ldap3.example.com"
+        );
+        let settings = profile.to_connection_settings();
+        assert_eq!(settings.host, "ldap1.example.com");
+        assert_eq!(
+            settings.failover_hosts,
+            ["ldap2.example.com", "ldap3.example.com"]
+        );
+        assert!(settings.shuffle_hosts);
+
+        // Written back as a list; a single host stays a string
+        let written = toml::to_string(profile).unwrap();
+        assert!(written
+            .contains(r#"host = ["ldap1.example.com", "ldap2.example.com", "ldap3.example.com"]"#));
+        let single =
+            AppConfig::from_toml("[[connections]]\nname = \"a\"\nhost = \"a.example.com\"\n")
+                .unwrap();
+        assert!(single.connections[0]
+            .to_connection_settings()
+            .failover_hosts
+            .is_empty());
+        assert!(toml::to_string(&single.connections[0])
+            .unwrap()
+            .contains("host = \"a.example.com\""));
     }
 
     #[test]
@@ -450,11 +1323,26 @@ host = "localhost"
                 tls_mode: TlsMode::Ldaps,
                 bind_dn: Some("cn=admin,dc=example,dc=com".to_string()),
                 base_dn: Some("dc=example,dc=com".to_string()),
+                start_dn: None,
+                default_filter: None,
+                root_dse_attributes: Vec::new(),
+                favorites: Vec::new(),
+                saved_searches: Vec::new(),
+                edit_policy: None,
                 credential_method: CredentialMethod::Prompt,
                 password_command: None,
                 page_size: 1000,
                 timeout_secs: 60,
+                pool_size: 3,
                 relax_rules: false,
+                sasl_mechanism: None,
+                authz_id: None,
+                proxy: None,
+                ssh_tunnel: None,
+                allow_commands: false,
+                dns_srv: false,
+                shuffle_hosts: false,
+                safety: None,
                 folder: None,
                 read_only: false,
                 offline: false,
@@ -466,11 +1354,26 @@ host = "localhost"
                 tls_mode: TlsMode::None,
                 bind_dn: None,
                 base_dn: None,
+                start_dn: None,
+                default_filter: None,
+                root_dse_attributes: Vec::new(),
+                favorites: Vec::new(),
+                saved_searches: Vec::new(),
+                edit_policy: None,
                 credential_method: CredentialMethod::Prompt,
                 password_command: None,
                 page_size: 500,
                 timeout_secs: 30,
+                pool_size: 3,
                 relax_rules: false,
+                sasl_mechanism: None,
+                authz_id: None,
+                proxy: None,
+                ssh_tunnel: None,
+                allow_commands: false,
+                dns_srv: false,
+                shuffle_hosts: false,
+                safety: None,
                 folder: None,
                 read_only: false,
                 offline: false,
@@ -544,6 +1447,84 @@ live_search = false
         assert!(!serialized.contains("live_search"));
     }
 
+    #[test]
+    fn test_idle_lock_defaults_disabled() {
+        let config = AppConfig::default();
+        assert_eq!(config.general.idle_lock_minutes, 0);
+        assert!(!config.general.idle_lock_disconnect);
+        let serialized = toml::to_string_pretty(&config).unwrap();
+        assert!(!serialized.contains("idle_lock"));
+    }
+
+    #[test]
+    fn test_idle_lock_parsing() {
+        let toml = r#"
+[general]
+idle_lock_minutes = 15
+idle_lock_disconnect = true
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        assert_eq!(config.general.idle_lock_minutes, 15);
+        assert!(config.general.idle_lock_disconnect);
+    }
+
+    #[test]
+    fn test_display_attributes_parsing() {
+        use loom_core::format::ValueFormat;
+
+        let toml = r#"
+[display.attributes]
+pwdLastSet = "filetime"
+objectSid = "sid"
+employeeID = { regex = '^(\d{3})(\d+)$', template = "$1-$2" }
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        let attrs = &config.display.attributes;
+        assert_eq!(
+            attrs["pwdLastSet"],
+            FormatRule::Builtin(ValueFormat::Filetime)
+        );
+        assert!(
+            matches!(&attrs["employeeID"], FormatRule::Template { template, .. } if template == "$1-$2")
+        );
+        // Round-trips, and an empty section isn't written
+        let again = AppConfig::from_toml(&toml::to_string_pretty(&config).unwrap()).unwrap();
+        assert_eq!(again.display.attributes, config.display.attributes);
+        assert!(!toml::to_string_pretty(&AppConfig::default())
+            .unwrap()
+            .contains("[display"));
+        assert!(AppConfig::from_toml(
+            "[display.attributes]
+cn = \"bogus\""
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_display_groups_parsing() {
+        let config = AppConfig::from_toml("").unwrap();
+        assert_eq!(
+            config.display.attribute_groups(),
+            AttributeGroup::defaults()
+        );
+
+        let toml = r#"
+[[display.groups]]
+name = "mail"
+attributes = ["mail", "proxyAddresses"]
+"#;
+        let config = AppConfig::from_toml(toml).unwrap();
+        let groups = config.display.attribute_groups();
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].attributes, vec!["mail", "proxyAddresses"]);
+
+        let config = AppConfig::from_toml("[display]\ngroups = []").unwrap();
+        assert!(config.display.attribute_groups().is_empty());
+        assert!(toml::to_string_pretty(&config)
+            .unwrap()
+            .contains("groups = []"));
+    }
+
     #[test]
     fn test_folder_config_parsing() {
         let toml = r#"
@@ -567,6 +1548,51 @@ description = ""
         assert_eq!(config.folders[1].description, "");
     }
 
+    #[test]
+    fn test_workspaces() {
+        let config = AppConfig::from_toml(
+            r#"
+[[workspaces]]
+name = "prod"
+profiles = ["dc1", "dc2"]
+
+[[workspaces]]
+name = "lab"
+"#,
+        )
+        .unwrap();
+        assert_eq!(config.workspace("PROD").unwrap().profiles, ["dc1", "dc2"]);
+        assert!(config.workspace("lab").unwrap().profiles.is_empty());
+        assert!(config.workspace("test").is_none());
+    }
+
+    #[test]
+    fn test_reports() {
+        let config = AppConfig::from_toml(
+            r#"
+[[reports]]
+name = "Stale computers"
+filter = "(&(objectClass=computer)(lastLogonTimestamp<={days:filetime}))"
+base_dn = "ou=Workstations,dc=example,dc=com"
+columns = ["cn", "lastLogonTimestamp"]
+parameters = [{ name = "days", default = "-180" }]
+
+[reports.decode]
+lastLogonTimestamp = "filetime"
+"#,
+        )
+        .unwrap();
+        let report = config.report("stale computers").unwrap();
+        assert_eq!(report.parameters[0].default.as_deref(), Some("-180"));
+        assert_eq!(
+            report.decode.get("lastLogonTimestamp"),
+            Some(&FormatRule::Builtin(
+                loom_core::format::ValueFormat::Filetime
+            ))
+        );
+        assert!(config.report("other").is_none());
+    }
+
     #[test]
     fn test_folder_description_lookup() {
         let config = AppConfig {
@@ -600,11 +1626,26 @@ description = ""
             tls_mode: TlsMode::None,
             bind_dn: Some("cn=admin".to_string()),
             base_dn: None,
+            start_dn: None,
+            default_filter: None,
+            root_dse_attributes: Vec::new(),
+            favorites: Vec::new(),
+            saved_searches: Vec::new(),
+            edit_policy: None,
             credential_method: CredentialMethod::Command,
             password_command: Some("pass show ldap".to_string()),
             page_size: 500,
             timeout_secs: 30,
+            pool_size: 3,
             relax_rules: false,
+            sasl_mechanism: None,
+            authz_id: None,
+            proxy: None,
+            ssh_tunnel: None,
+            allow_commands: false,
+            dns_srv: false,
+            shuffle_hosts: false,
+            safety: None,
             folder: None,
             read_only: false,
             offline: false,
@@ -616,4 +1657,174 @@ description = ""
         // There is no password field in ConnectionProfile, so no secrets leak
         assert!(!exported.contains("password ="));
     }
+
+    #[test]
+    fn test_export_profiles_drops_proxy_password() {
+        let toml_str = r#"
+[[connections]]
+name = "Datacenter"
+host = "ldap01.dc.internal"
+
+[connections.proxy]
+address = "proxy.example.com:1080"
+username = "alice"
+password = "secret"
+"#;
+        let config = AppConfig::from_toml(toml_str).unwrap();
+        let proxy = config.connections[0].proxy.as_ref().unwrap();
+        assert_eq!(proxy.password.as_deref(), Some("secret"));
+
+        let exported = AppConfig::export_profiles(&config.connections).unwrap();
+        assert!(exported.contains("username = \"alice\""));
+        assert!(!exported.contains("secret"));
+        // Nor is it saved back to the config file
+        let saved = toml::to_string(&config.with_templates()).unwrap();
+        assert!(!saved.contains("secret"));
+    }
+
+    #[test]
+    fn test_interpolate_env_and_command() {
+        std::env::set_var("LOOM_TEST_INTERP_USER", "alice");
+        assert_eq!(
+            interpolate("uid=${LOOM_TEST_INTERP_USER},ou=People", false).unwrap(),
+            "uid=alice,ou=People"
+        );
+        assert_eq!(
+            interpolate("$(echo ldap1).example.com", true).unwrap(),
+            "ldap1.example.com"
+        );
+        assert_eq!(
+            interpolate("cost $$5 and $x", false).unwrap(),
+            "cost $5 and $x"
+        );
+        assert_eq!(
+            interpolate("$(echo $(echo nested))", true).unwrap(),
+            "nested"
+        );
+    }
+
+    #[test]
+    fn test_interpolate_errors() {
+        assert!(interpolate("${LOOM_TEST_INTERP_UNSET_VAR}", false).is_err());
+        assert!(interpolate("${UNTERMINATED", false).is_err());
+        assert!(interpolate("$(false)", true).is_err());
+        let err = interpolate("$(echo ldap1)", false).unwrap_err();
+        assert!(err.contains("allow_commands"));
+    }
+
+    #[test]
+    fn test_imported_profiles_never_run_commands() {
+        let content = r#"
+[[connections]]
+name = "Shared"
+host = "$(touch /tmp/loom-test-pwned)"
+bind_dn = "${HOME}"
+allow_commands = true
+"#;
+        let mut imported = AppConfig::import_profiles(content).unwrap();
+        assert!(!imported[0].allow_commands);
+        assert_eq!(imported[0].host, "$$(touch /tmp/loom-test-pwned)");
+
+        // Saved and loaded again, the values stay literal
+        let mut config = AppConfig::default();
+        config.connections.append(&mut imported);
+        config.interpolate_profiles();
+        assert_eq!(config.connections[0].host, "$(touch /tmp/loom-test-pwned)");
+        assert_eq!(config.connections[0].bind_dn.as_deref(), Some("${HOME}"));
+        assert!(config.templates.iter().all(|t| t.template.contains("$$")));
+    }
+
+    #[test]
+    fn test_interpolated_profiles_save_templates() {
+        std::env::set_var("LOOM_TEST_INTERP_BIND", "cn=bob,dc=example,dc=com");
+        let toml_str = r#"
+[[connections]]
+name = "Shared"
+host = "ldap.example.com"
+bind_dn = "${LOOM_TEST_INTERP_BIND}"
+base_dn = "${LOOM_TEST_INTERP_UNSET_VAR}"
+"#;
+        let mut config = AppConfig::from_toml(toml_str).unwrap();
+        config.interpolate_profiles();
+
+        let profile = &config.connections[0];
+        assert_eq!(profile.bind_dn.as_deref(), Some("cn=bob,dc=example,dc=com"));
+        // Failed expansion leaves the field as written
+        assert_eq!(
+            profile.base_dn.as_deref(),
+            Some("${LOOM_TEST_INTERP_UNSET_VAR}")
+        );
+
+        let saved = toml::to_string(&config.with_templates()).unwrap();
+        assert!(saved.contains("${LOOM_TEST_INTERP_BIND}"));
+        assert!(!saved.contains("cn=bob"));
+
+        // An edited field is saved as the new value
+        config.connections[0].bind_dn = Some("cn=carol".to_string());
+        let saved = toml::to_string(&config.with_templates()).unwrap();
+        assert!(saved.contains("cn=carol"));
+    }
+
+    fn folder_profile(name: &str, folder: Option<&str>) -> ConnectionProfile {
+        let mut config = AppConfig::from_toml(&format!(
+            "[[connections]]\nname = \"{}\"\nhost = \"h\"\n",
+            name
+        ))
+        .unwrap();
+        let mut profile = config.connections.remove(0);
+        profile.folder = folder.map(str::to_string);
+        profile
+    }
+
+    #[test]
+    fn test_normalize_folder_path() {
+        assert_eq!(
+            normalize_folder_path(" prod//emea/ dc1 /"),
+            Some("prod/emea/dc1".to_string())
+        );
+        assert_eq!(normalize_folder_path("/"), None);
+        assert_eq!(normalize_folder_path(""), None);
+    }
+
+    #[test]
+    fn test_move_connection_within_folder() {
+        let mut config = AppConfig {
+            connections: vec![
+                folder_profile("a", Some("prod")),
+                folder_profile("b", None),
+                folder_profile("c", Some("prod/")),
+                folder_profile("d", Some("prod")),
+            ],
+            ..Default::default()
+        };
+
+        // "c" moves up past the unrelated root profile "b"
+        assert_eq!(config.move_connection(2, -1), Some(0));
+        let names: Vec<&str> = config.connections.iter().map(|p| p.name.as_str()).collect();
+        assert_eq!(names, vec!["c", "b", "a", "d"]);
+
+        assert_eq!(config.move_connection(0, -1), None);
+        assert_eq!(config.move_connection(3, 1), None);
+        assert_eq!(config.move_connection(1, 1), None);
+        assert_eq!(config.move_connection(2, 1), Some(3));
+    }
+
+    #[test]
+    fn test_folder_paths_include_parents() {
+        let config = AppConfig {
+            connections: vec![
+                folder_profile("a", Some("prod/emea/dc1")),
+                folder_profile("b", None),
+            ],
+            folders: vec![FolderConfig {
+                path: "staging".to_string(),
+                description: String::new(),
+            }],
+            ..Default::default()
+        };
+        assert_eq!(
+            config.folder_paths(),
+            vec!["prod", "prod/emea", "prod/emea/dc1", "staging"]
+        );
+    }
 }
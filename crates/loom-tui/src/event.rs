@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossterm::event::{self, Event as CrosstermEvent, KeyEvent, KeyEventKind, MouseEvent};
 
@@ -8,9 +8,40 @@ pub enum AppEvent {
     Key(KeyEvent),
     Mouse(MouseEvent),
     Resize(u16, u16),
+    /// Text pasted into the terminal (bracketed paste), delivered in one piece.
+    Paste(String),
     Tick,
 }
 
+/// Longest gap between two clicks on the same cell that makes a double-click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Tells double-clicks from single clicks by remembering the last one.
+#[derive(Debug, Default)]
+pub struct ClickTracker {
+    last: Option<(Instant, u16, u16)>,
+}
+
+impl ClickTracker {
+    /// Record a left click; true when it completes a double-click.
+    pub fn click(&mut self, column: u16, row: u16) -> bool {
+        self.click_at(Instant::now(), column, row)
+    }
+
+    fn click_at(&mut self, now: Instant, column: u16, row: u16) -> bool {
+        let double = self.last.is_some_and(|(at, c, r)| {
+            c == column && r == row && now.duration_since(at) <= DOUBLE_CLICK_INTERVAL
+        });
+        // A third click starts over rather than making another double
+        self.last = if double {
+            None
+        } else {
+            Some((now, column, row))
+        };
+        double
+    }
+}
+
 /// Poll for the next event with a timeout.
 pub fn poll_event(tick_rate: Duration) -> Option<AppEvent> {
     if event::poll(tick_rate).ok()? {
@@ -18,9 +49,28 @@ pub fn poll_event(tick_rate: Duration) -> Option<AppEvent> {
             CrosstermEvent::Key(key) if key.kind == KeyEventKind::Press => Some(AppEvent::Key(key)),
             CrosstermEvent::Mouse(mouse) => Some(AppEvent::Mouse(mouse)),
             CrosstermEvent::Resize(w, h) => Some(AppEvent::Resize(w, h)),
+            CrosstermEvent::Paste(text) => Some(AppEvent::Paste(text)),
             _ => None,
         }
     } else {
         Some(AppEvent::Tick)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_click() {
+        let mut clicks = ClickTracker::default();
+        let start = Instant::now();
+        assert!(!clicks.click_at(start, 3, 4));
+        assert!(clicks.click_at(start + Duration::from_millis(200), 3, 4));
+        // The next click begins a new pair
+        assert!(!clicks.click_at(start + Duration::from_millis(300), 3, 4));
+        // Too slow, or somewhere else
+        assert!(!clicks.click_at(start + Duration::from_millis(900), 3, 4));
+        assert!(!clicks.click_at(start + Duration::from_millis(1000), 5, 4));
+    }
+}
@@ -0,0 +1,409 @@
+//! A Prometheus endpoint for sessions left running to watch servers: LDAP
+//! operations, failures and latency per connection, reconnects, session
+//! errors and replication lag, served on a localhost port.
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use loom_core::replication::ReplicationReport;
+use loom_core::trace::OperationTrace;
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::task::JoinHandle;
+
+/// A scrape whose request hasn't arrived by then is dropped.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// `[prometheus]`: the metrics endpoint.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PrometheusConfig {
+    /// Serve metrics on 127.0.0.1 at this port; unset turns the endpoint off.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub port: Option<u16>,
+    /// Seconds between replication checks of every live connection, for
+    /// the lag gauges (0 only keeps the replication view's own checks).
+    pub replication_interval_secs: u64,
+}
+
+impl Default for PrometheusConfig {
+    fn default() -> Self {
+        Self {
+            port: None,
+            replication_interval_secs: 300,
+        }
+    }
+}
+
+/// A connection as seen at scrape time.
+#[derive(Debug, Clone)]
+pub struct ConnectionSample {
+    pub label: String,
+    pub host: String,
+    /// The connection's trace, whose totals are read at each scrape;
+    /// offline tabs have none.
+    pub trace: Option<OperationTrace>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct PartnerSample {
+    partition: String,
+    partner: String,
+    lag_secs: Option<f64>,
+    failures: u32,
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    uptime_secs: u64,
+    errors: u64,
+    connections: Vec<ConnectionSample>,
+    /// Replication partners by connection label, from the latest check.
+    replication: BTreeMap<String, Vec<PartnerSample>>,
+}
+
+/// The running endpoint. The app keeps its figures current; the server
+/// stops when this is dropped.
+pub struct MetricsExporter {
+    snapshot: Arc<Mutex<Snapshot>>,
+    port: u16,
+    task: JoinHandle<()>,
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+impl MetricsExporter {
+    /// Listen on `127.0.0.1:<port>`, answering `GET /metrics`.
+    pub fn start(port: u16) -> io::Result<Self> {
+        let listener = std::net::TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        let port = listener.local_addr()?.port();
+        let listener = tokio::net::TcpListener::from_std(listener)?;
+        let snapshot = Arc::new(Mutex::new(Snapshot::default()));
+        let shared = snapshot.clone();
+        let task = tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_scrape(stream, shared.clone()));
+                    }
+                    Err(e) => {
+                        tracing::warn!("Metrics endpoint stopped accepting: {}", e);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Self {
+            snapshot,
+            port,
+            task,
+        })
+    }
+
+    /// The port served, which the system picks when configured as 0.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Snapshot> {
+        self.snapshot.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Update the session figures and the open connections.
+    pub fn set_session(&self, uptime_secs: u64, errors: u64, connections: Vec<ConnectionSample>) {
+        let mut snapshot = self.lock();
+        snapshot.uptime_secs = uptime_secs;
+        snapshot.errors = errors;
+        // Closed connections' replication gauges go with them
+        snapshot
+            .replication
+            .retain(|label, _| connections.iter().any(|c| c.label == *label));
+        snapshot.connections = connections;
+    }
+
+    /// Record the latest replication check of a connection.
+    pub fn set_replication(&self, label: &str, report: &ReplicationReport) {
+        let partners = report
+            .partners
+            .iter()
+            .map(|p| PartnerSample {
+                partition: p.partition.clone(),
+                partner: p.partner.clone(),
+                lag_secs: p.lag.map(|lag| lag.as_secs_f64()),
+                failures: p.failures,
+            })
+            .collect();
+        self.lock().replication.insert(label.to_string(), partners);
+    }
+}
+
+/// Answer one HTTP request with the metrics, then close the connection.
+async fn serve_scrape(mut stream: tokio::net::TcpStream, snapshot: Arc<Mutex<Snapshot>>) {
+    let mut request = Vec::new();
+    let mut buf = [0u8; 1024];
+    let read_head = async {
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") && request.len() < 8192 {
+            match stream.read(&mut buf).await {
+                Ok(0) | Err(_) => break,
+                Ok(n) => request.extend_from_slice(&buf[..n]),
+            }
+        }
+    };
+    if tokio::time::timeout(REQUEST_TIMEOUT, read_head)
+        .await
+        .is_err()
+    {
+        return;
+    }
+    let head = String::from_utf8_lossy(&request);
+    let mut request_line = head.lines().next().unwrap_or("").split_whitespace();
+    let response = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics" | "/")) => {
+            let body = render(&snapshot.lock().unwrap_or_else(|e| e.into_inner()));
+            format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            )
+        }
+        _ => "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+    };
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Escape a label value for the text format.
+fn label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// The snapshot in the Prometheus text exposition format.
+fn render(snapshot: &Snapshot) -> String {
+    let mut out = String::new();
+    header(
+        &mut out,
+        "loom_uptime_seconds",
+        "gauge",
+        "Seconds since the session started.",
+    );
+    let _ = writeln!(out, "loom_uptime_seconds {}", snapshot.uptime_secs);
+    header(
+        &mut out,
+        "loom_errors_total",
+        "counter",
+        "Errors reported in the session.",
+    );
+    let _ = writeln!(out, "loom_errors_total {}", snapshot.errors);
+    header(
+        &mut out,
+        "loom_connections",
+        "gauge",
+        "Open connection tabs.",
+    );
+    let _ = writeln!(out, "loom_connections {}", snapshot.connections.len());
+
+    let traced: Vec<(String, &OperationTrace)> = snapshot
+        .connections
+        .iter()
+        .filter_map(|c| {
+            let labels = format!(
+                "connection=\"{}\",host=\"{}\"",
+                label(&c.label),
+                label(&c.host)
+            );
+            c.trace.as_ref().map(|trace| (labels, trace))
+        })
+        .collect();
+    let totals: Vec<_> = traced
+        .iter()
+        .map(|(labels, trace)| (labels, trace.totals()))
+        .collect();
+
+    header(
+        &mut out,
+        "loom_ldap_operations_total",
+        "counter",
+        "LDAP requests sent, by operation.",
+    );
+    for (labels, ops) in &totals {
+        for (op, t) in ops {
+            let op = op.label().to_ascii_lowercase();
+            let _ = writeln!(
+                out,
+                "loom_ldap_operations_total{{{},op=\"{}\"}} {}",
+                labels, op, t.count
+            );
+        }
+    }
+    header(
+        &mut out,
+        "loom_ldap_operation_failures_total",
+        "counter",
+        "LDAP requests that failed or got no result, by operation.",
+    );
+    for (labels, ops) in &totals {
+        for (op, t) in ops {
+            let op = op.label().to_ascii_lowercase();
+            let _ = writeln!(
+                out,
+                "loom_ldap_operation_failures_total{{{},op=\"{}\"}} {}",
+                labels, op, t.failures
+            );
+        }
+    }
+    header(
+        &mut out,
+        "loom_ldap_operation_duration_seconds",
+        "summary",
+        "Time spent waiting for LDAP results, by operation; op=\"bind\" is the bind latency.",
+    );
+    for (labels, ops) in &totals {
+        for (op, t) in ops {
+            let op = op.label().to_ascii_lowercase();
+            let _ = writeln!(
+                out,
+                "loom_ldap_operation_duration_seconds_sum{{{},op=\"{}\"}} {:.6}",
+                labels,
+                op,
+                t.duration.as_secs_f64()
+            );
+            let _ = writeln!(
+                out,
+                "loom_ldap_operation_duration_seconds_count{{{},op=\"{}\"}} {}",
+                labels, op, t.count
+            );
+        }
+    }
+    header(
+        &mut out,
+        "loom_ldap_reconnects_total",
+        "counter",
+        "Reconnects after a connection dropped.",
+    );
+    for (labels, trace) in &traced {
+        let _ = writeln!(
+            out,
+            "loom_ldap_reconnects_total{{{}}} {}",
+            labels,
+            trace.reconnects()
+        );
+    }
+
+    header(
+        &mut out,
+        "loom_replication_lag_seconds",
+        "gauge",
+        "How far a replication partner is behind, from the latest check.",
+    );
+    for (connection, partners) in &snapshot.replication {
+        for p in partners {
+            if let Some(lag) = p.lag_secs {
+                let _ = writeln!(
+                    out,
+                    "loom_replication_lag_seconds{{connection=\"{}\",partition=\"{}\",partner=\"{}\"}} {}",
+                    label(connection),
+                    label(&p.partition),
+                    label(&p.partner),
+                    lag
+                );
+            }
+        }
+    }
+    header(
+        &mut out,
+        "loom_replication_failures",
+        "gauge",
+        "Failed replication syncs in a row, from the latest check.",
+    );
+    for (connection, partners) in &snapshot.replication {
+        for p in partners {
+            let _ = writeln!(
+                out,
+                "loom_replication_failures{{connection=\"{}\",partition=\"{}\",partner=\"{}\"}} {}",
+                label(connection),
+                label(&p.partition),
+                label(&p.partner),
+                p.failures
+            );
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use loom_core::trace::{TraceOp, TraceRecord};
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_metrics_endpoint() {
+        let trace = OperationTrace::default();
+        for (op, rc) in [
+            (TraceOp::Bind, Some(0)),
+            (TraceOp::Search, Some(0)),
+            (TraceOp::Search, None),
+        ] {
+            let mut record = TraceRecord::new(op, "");
+            record.result_code = rc;
+            record.duration = Duration::from_millis(250);
+            trace.push(record);
+        }
+        trace.count_reconnect();
+
+        let exporter = MetricsExporter::start(0).unwrap();
+        exporter.set_session(
+            60,
+            2,
+            vec![ConnectionSample {
+                label: "Prod \"EU\"".to_string(),
+                host: "ldap.x".to_string(),
+                trace: Some(trace),
+            }],
+        );
+
+        let mut stream = tokio::net::TcpStream::connect(("127.0.0.1", exporter.port()))
+            .await
+            .unwrap();
+        stream
+            .write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        let labels = r#"connection="Prod \"EU\"",host="ldap.x""#;
+        for line in [
+            "loom_errors_total 2".to_string(),
+            format!("loom_ldap_operations_total{{{},op=\"search\"}} 2", labels),
+            format!(
+                "loom_ldap_operation_failures_total{{{},op=\"search\"}} 1",
+                labels
+            ),
+            format!(
+                "loom_ldap_operation_duration_seconds_sum{{{},op=\"bind\"}} 0.250000",
+                labels
+            ),
+            format!("loom_ldap_reconnects_total{{{}}} 1", labels),
+        ] {
+            assert!(response.contains(&format!("\n{}\n", line)), "{}", line);
+        }
+    }
+}
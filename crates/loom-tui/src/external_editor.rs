@@ -0,0 +1,96 @@
+use std::io::Write;
+use std::process::Command;
+
+/// An attribute value waiting to be opened in the external editor. The run
+/// loop picks it up, since the terminal has to be released first.
+#[derive(Debug, Clone)]
+pub struct ExternalEdit {
+    pub dn: String,
+    pub attr: String,
+    pub value: String,
+}
+
+/// The editor to launch: `$VISUAL`, then `$EDITOR`, then `vi`.
+pub fn editor_command() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|cmd| !cmd.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string())
+}
+
+/// Open `value` in the external editor and wait for it to exit.
+///
+/// The value is written byte for byte to a private temp file (removed
+/// afterwards). Returns `Ok(None)` when the value was left unchanged.
+pub fn edit_value(value: &str) -> Result<Option<String>, String> {
+    let mut file = tempfile::Builder::new()
+        .prefix("loom-value-")
+        .suffix(".txt")
+        .tempfile()
+        .map_err(|e| format!("Failed to create temp file: {}", e))?;
+    file.write_all(value.as_bytes())
+        .and_then(|_| file.flush())
+        .map_err(|e| format!("Failed to write temp file: {}", e))?;
+
+    // $EDITOR may carry arguments, e.g. "code --wait"
+    let command = editor_command();
+    let mut parts = command.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(file.path())
+        .status()
+        .map_err(|e| format!("Failed to run editor '{}': {}", command, e))?;
+    if !status.success() {
+        return Err(format!(
+            "Editor '{}' exited with {}; value not saved",
+            command, status
+        ));
+    }
+
+    let bytes =
+        std::fs::read(file.path()).map_err(|e| format!("Failed to read temp file: {}", e))?;
+    decode_edited(value, bytes)
+}
+
+/// Turn the saved file back into a value. Bytes that are not UTF-8 are
+/// refused rather than mangled, and the newline most editors append at
+/// end of file is dropped unless the original value ended with one.
+fn decode_edited(original: &str, bytes: Vec<u8>) -> Result<Option<String>, String> {
+    let mut edited = String::from_utf8(bytes)
+        .map_err(|_| "Edited value is not valid UTF-8; value not saved".to_string())?;
+    if !original.ends_with('\n') {
+        if edited.ends_with("\r\n") {
+            edited.truncate(edited.len() - 2);
+        } else if edited.ends_with('\n') {
+            edited.pop();
+        }
+    }
+    Ok((edited != original).then_some(edited))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_edited() {
+        // Trailing newline added by the editor is dropped
+        assert_eq!(
+            decode_edited("old", b"new\n".to_vec()),
+            Ok(Some("new".to_string()))
+        );
+        assert_eq!(decode_edited("same", b"same\r\n".to_vec()), Ok(None));
+
+        // Multi-line values keep their inner and (original) trailing newlines
+        let pem = "-----BEGIN CERTIFICATE-----\nMIIB\n-----END CERTIFICATE-----\n";
+        assert_eq!(decode_edited(pem, pem.as_bytes().to_vec()), Ok(None));
+        assert_eq!(
+            decode_edited("a", b"line 1\nline 2\n\n".to_vec()),
+            Ok(Some("line 1\nline 2\n".to_string()))
+        );
+
+        assert!(decode_edited("a", vec![0xff, 0xfe]).is_err());
+    }
+}
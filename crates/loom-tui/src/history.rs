@@ -0,0 +1,99 @@
+/// Most entries kept per tab; the oldest are dropped first.
+const MAX_HISTORY: usize = 200;
+
+/// Per-tab browser-style history of the entries shown in the detail panel.
+#[derive(Debug, Default)]
+pub struct NavHistory {
+    entries: Vec<String>,
+    /// Index of the entry on screen.
+    pos: usize,
+}
+
+impl NavHistory {
+    /// Record that `dn` is now shown. Showing the current entry again (a
+    /// refresh, or arriving after back/forward) changes nothing; otherwise
+    /// the forward entries are dropped, as in a browser.
+    pub fn visit(&mut self, dn: &str) {
+        if self
+            .current()
+            .is_some_and(|cur| cur.eq_ignore_ascii_case(dn))
+        {
+            return;
+        }
+        if !self.entries.is_empty() {
+            self.entries.truncate(self.pos + 1);
+        }
+        self.entries.push(dn.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+        self.pos = self.entries.len() - 1;
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.entries.get(self.pos).map(String::as_str)
+    }
+
+    /// Step back, returning the DN to show.
+    pub fn back(&mut self) -> Option<String> {
+        self.jump(self.pos.checked_sub(1)?)
+    }
+
+    /// Step forward, returning the DN to show.
+    pub fn forward(&mut self) -> Option<String> {
+        self.jump(self.pos + 1)
+    }
+
+    /// Move to the entry at `index`, keeping the rest of the history.
+    pub fn jump(&mut self, index: usize) -> Option<String> {
+        let dn = self.entries.get(index)?.clone();
+        self.pos = index;
+        Some(dn)
+    }
+
+    /// All visited DNs, oldest first, and the index of the current one.
+    pub fn entries(&self) -> (&[String], usize) {
+        (&self.entries, self.pos)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_back_forward_and_revisit() {
+        let mut history = NavHistory::default();
+        assert!(history.back().is_none());
+        history.visit("cn=a");
+        history.visit("cn=b");
+        history.visit("cn=c");
+
+        assert_eq!(history.back().as_deref(), Some("cn=b"));
+        // Loading the entry we stepped back to is not a new visit
+        history.visit("CN=b");
+        assert_eq!(history.back().as_deref(), Some("cn=a"));
+        assert!(history.back().is_none());
+        assert_eq!(history.forward().as_deref(), Some("cn=b"));
+
+        // A new visit from the middle drops the forward entries
+        history.visit("cn=d");
+        assert!(history.forward().is_none());
+        assert_eq!(history.entries().0, ["cn=a", "cn=b", "cn=d"]);
+    }
+
+    #[test]
+    fn test_jump_keeps_history() {
+        let mut history = NavHistory::default();
+        for dn in ["cn=a", "cn=b", "cn=c"] {
+            history.visit(dn);
+        }
+        assert_eq!(history.jump(0).as_deref(), Some("cn=a"));
+        assert_eq!(
+            history.entries(),
+            (&["cn=a", "cn=b", "cn=c"].map(String::from)[..], 0)
+        );
+        assert!(history.jump(5).is_none());
+        assert_eq!(history.current(), Some("cn=a"));
+    }
+}
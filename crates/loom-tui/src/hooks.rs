@@ -0,0 +1,263 @@
+use std::process::Stdio;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::mpsc::UnboundedSender;
+
+use crate::action::Action;
+
+/// A hook still running after this long is killed.
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Events a hook can run on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    PostExport,
+    PostImport,
+    PostBulkUpdate,
+    EntryAdded,
+    EntryModified,
+    EntryDeleted,
+}
+
+impl HookEvent {
+    /// The event's key in `[hooks]`, also passed to the command.
+    pub fn name(self) -> &'static str {
+        match self {
+            HookEvent::PostExport => "post_export",
+            HookEvent::PostImport => "post_import",
+            HookEvent::PostBulkUpdate => "post_bulk_update",
+            HookEvent::EntryAdded => "entry_added",
+            HookEvent::EntryModified => "entry_modified",
+            HookEvent::EntryDeleted => "entry_deleted",
+        }
+    }
+
+    /// Whether the event reports a change to the directory, which doesn't
+    /// happen while a tab's writes are held back by dry run or staging.
+    pub fn is_write(self) -> bool {
+        self != HookEvent::PostExport
+    }
+}
+
+/// `[hooks]`: shell commands run when events happen.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HooksConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_export: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_import: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub post_bulk_update: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_added: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_modified: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_deleted: Option<String>,
+}
+
+impl HooksConfig {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    pub fn command(&self, event: HookEvent) -> Option<&str> {
+        let command = match event {
+            HookEvent::PostExport => &self.post_export,
+            HookEvent::PostImport => &self.post_import,
+            HookEvent::PostBulkUpdate => &self.post_bulk_update,
+            HookEvent::EntryAdded => &self.entry_added,
+            HookEvent::EntryModified => &self.entry_modified,
+            HookEvent::EntryDeleted => &self.entry_deleted,
+        };
+        command.as_deref().filter(|c| !c.trim().is_empty())
+    }
+}
+
+/// What a hook is told about its event: as JSON on stdin, and as
+/// `LOOM_*` environment variables.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct HookContext {
+    pub event: HookEvent,
+    pub profile: String,
+    pub host: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dn: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl HookContext {
+    pub fn new(event: HookEvent, profile: &str, host: &str) -> Self {
+        Self {
+            event,
+            profile: profile.to_string(),
+            host: host.to_string(),
+            dn: None,
+            file: None,
+            message: None,
+        }
+    }
+
+    pub fn dn(mut self, dn: &str) -> Self {
+        self.dn = Some(dn.to_string());
+        self
+    }
+
+    pub fn file(mut self, file: &str) -> Self {
+        self.file = Some(file.to_string());
+        self
+    }
+
+    pub fn message(mut self, message: &str) -> Self {
+        self.message = Some(message.to_string());
+        self
+    }
+
+    fn env(&self) -> Vec<(&'static str, &str)> {
+        let mut env = vec![
+            ("LOOM_EVENT", self.event.name()),
+            ("LOOM_PROFILE", self.profile.as_str()),
+            ("LOOM_HOST", self.host.as_str()),
+        ];
+        let optional = [
+            ("LOOM_DN", &self.dn),
+            ("LOOM_FILE", &self.file),
+            ("LOOM_MESSAGE", &self.message),
+        ];
+        for (name, value) in optional {
+            if let Some(value) = value {
+                env.push((name, value.as_str()));
+            }
+        }
+        env
+    }
+}
+
+/// Runs the configured hooks in the background; a hook that fails is
+/// reported as an error message.
+#[derive(Clone)]
+pub struct Hooks {
+    config: Arc<HooksConfig>,
+    action_tx: UnboundedSender<Action>,
+}
+
+impl Hooks {
+    pub fn new(config: HooksConfig, action_tx: UnboundedSender<Action>) -> Self {
+        Self {
+            config: Arc::new(config),
+            action_tx,
+        }
+    }
+
+    pub fn is_set(&self, event: HookEvent) -> bool {
+        self.config.command(event).is_some()
+    }
+
+    /// Run the hook for the context's event, if one is configured.
+    pub fn run(&self, context: HookContext) {
+        let event = context.event;
+        let Some(command) = self.config.command(event).map(str::to_string) else {
+            return;
+        };
+        let tx = self.action_tx.clone();
+        tokio::spawn(async move {
+            if let Err(e) = run_hook(&command, &context).await {
+                let _ = tx.send(Action::ErrorMessage(format!(
+                    "The {} hook failed: {}",
+                    event.name(),
+                    e
+                )));
+            }
+        });
+    }
+}
+
+/// Run `command` through `sh -c` with the context, waiting for it to exit.
+async fn run_hook(command: &str, context: &HookContext) -> Result<(), String> {
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .envs(context.env())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to run '{}': {}", command, e))?;
+
+    let json = serde_json::to_string(context).map_err(|e| e.to_string())?;
+    if let Some(mut stdin) = child.stdin.take() {
+        // A hook that doesn't read its input is fine
+        let _ = stdin.write_all(json.as_bytes()).await;
+    }
+
+    let output = tokio::time::timeout(HOOK_TIMEOUT, child.wait_with_output())
+        .await
+        .map_err(|_| {
+            format!(
+                "'{}' didn't finish within {}s",
+                command,
+                HOOK_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| e.to_string())?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!(
+            "'{}' exited with {}: {}",
+            command,
+            output.status,
+            stderr.trim()
+        ));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_hook() {
+        let dir = tempfile::tempdir().unwrap();
+        let out = dir.path().join("out");
+        let command = format!(
+            "printf '%s|%s|%s\\n' \"$LOOM_EVENT\" \"$LOOM_DN\" \"${{LOOM_FILE-unset}}\" > {0}; cat >> {0}",
+            out.display()
+        );
+        let context =
+            HookContext::new(HookEvent::EntryModified, "Production", "ldap.x").dn("uid=jdoe,dc=x");
+        run_hook(&command, &context).await.unwrap();
+
+        let written = std::fs::read_to_string(&out).unwrap();
+        let (env, json) = written.split_once('\n').unwrap();
+        assert_eq!(env, "entry_modified|uid=jdoe,dc=x|unset");
+        assert_eq!(
+            json,
+            r#"{"event":"entry_modified","profile":"Production","host":"ldap.x","dn":"uid=jdoe,dc=x"}"#
+        );
+
+        let err = run_hook("echo nope >&2; exit 3", &context)
+            .await
+            .unwrap_err();
+        assert!(err.ends_with("exit status: 3: nope"), "{}", err);
+
+        // Blank commands are treated as unset
+        let config = HooksConfig {
+            post_export: Some(" ".to_string()),
+            entry_deleted: Some("true".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(config.command(HookEvent::PostExport), None);
+        assert_eq!(config.command(HookEvent::EntryDeleted), Some("true"));
+    }
+}
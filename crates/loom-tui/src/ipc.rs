@@ -0,0 +1,769 @@
+//! Remote control over a local Unix socket, so scripts, editor plugins and
+//! URL handlers (e.g. "open this user in loom" links) can drive a running
+//! instance.
+//!
+//! The protocol is one command per line, answered by one line:
+//!
+//! ```text
+//! goto cn=jdoe,ou=People,dc=example,dc=com
+//! search (&(objectClass=user)(department=IT))
+//! export ~/people.ldif (objectClass=person)
+//! ```
+//!
+//! Each is answered with `ok` once queued, or `error: <reason>`.
+//!
+//! With `rpc_enabled`, a line holding a JSON object is a JSON-RPC 2.0
+//! request instead, answered with a JSON-RPC response on one line. Every
+//! command then needs the session token, which is written next to the
+//! socket as `<socket>.token`: a client first calls `authenticate` with
+//! it, or sends `auth <token>` before plain commands:
+//!
+//! ```text
+//! {"jsonrpc":"2.0","id":1,"method":"authenticate","params":{"token":"..."}}
+//! {"jsonrpc":"2.0","id":2,"method":"get_entry","params":{"dn":"cn=jdoe,dc=example,dc=com"}}
+//! ```
+
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde::Serialize;
+use serde_json::{json, Value};
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::oneshot;
+
+use crate::action::Action;
+
+/// A request from a remote control client.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RemoteCommand {
+    /// Show an entry, switching to the tab whose base DN contains it.
+    Goto(String),
+    /// Run a search filter on the active tab.
+    Search(String),
+    /// Export the active tab's subtree, optionally filtered, to a file.
+    Export {
+        path: String,
+        filter: Option<String>,
+    },
+}
+
+impl RemoteCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (verb, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        match verb.to_ascii_lowercase().as_str() {
+            "goto" => {
+                loom_core::dn::validate_dn(rest)?;
+                Ok(RemoteCommand::Goto(rest.to_string()))
+            }
+            "search" => {
+                loom_core::filter::validate_filter(rest)?;
+                Ok(RemoteCommand::Search(rest.to_string()))
+            }
+            "export" => {
+                // A quoted path ends at the closing quote; otherwise the
+                // filter, if any, is everything from the first " ("
+                let (path, filter) = match rest.strip_prefix('"') {
+                    Some(quoted) => {
+                        let (path, after) = quoted
+                            .split_once('"')
+                            .ok_or_else(|| "export path has no closing quote".to_string())?;
+                        let after = after.trim();
+                        (path, (!after.is_empty()).then_some(after))
+                    }
+                    None => match rest.find(" (") {
+                        Some(i) => (rest[..i].trim(), Some(rest[i..].trim())),
+                        None => (rest, None),
+                    },
+                };
+                if path.is_empty() {
+                    return Err("export needs a file path".to_string());
+                }
+                if let Some(filter) = filter {
+                    loom_core::filter::validate_filter(filter)?;
+                }
+                Ok(RemoteCommand::Export {
+                    path: path.to_string(),
+                    filter: filter.map(str::to_string),
+                })
+            }
+            "" => Err("empty command".to_string()),
+            other => Err(format!(
+                "unknown command '{}' (expected goto, search or export)",
+                other
+            )),
+        }
+    }
+}
+
+impl fmt::Display for RemoteCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RemoteCommand::Goto(dn) => write!(f, "goto {}", dn),
+            RemoteCommand::Search(filter) => write!(f, "search {}", filter),
+            RemoteCommand::Export { path, filter } => {
+                if path.contains(" (") || path.starts_with('"') {
+                    write!(f, "export \"{}\"", path)?;
+                } else {
+                    write!(f, "export {}", path)?;
+                }
+                match filter {
+                    Some(filter) => write!(f, " {}", filter),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+}
+
+/// How long a JSON-RPC request may take before the client is told it
+/// timed out.
+const RPC_TIMEOUT: Duration = Duration::from_secs(300);
+
+/// JSON-RPC error codes: the protocol's own, then loom's.
+pub const PARSE_ERROR: i64 = -32700;
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+/// The request was valid but carrying it out failed.
+pub const REQUEST_FAILED: i64 = -32000;
+pub const NOT_AUTHENTICATED: i64 = -32001;
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+
+    pub fn invalid_params(message: impl Into<String>) -> Self {
+        Self::new(INVALID_PARAMS, message)
+    }
+
+    pub fn failed(message: impl Into<String>) -> Self {
+        Self::new(REQUEST_FAILED, message)
+    }
+}
+
+pub type RpcResult = Result<Value, RpcError>;
+
+/// A JSON-RPC request from an authenticated client, for the app to carry
+/// out. Clones share the reply, which only the first `respond` sends.
+#[derive(Clone)]
+pub struct RpcCall {
+    pub method: String,
+    pub params: Value,
+    reply: Arc<Mutex<Option<oneshot::Sender<RpcResult>>>>,
+}
+
+impl fmt::Debug for RpcCall {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RpcCall")
+            .field("method", &self.method)
+            .field("params", &self.params)
+            .finish_non_exhaustive()
+    }
+}
+
+impl RpcCall {
+    fn new(method: String, params: Value) -> (Self, oneshot::Receiver<RpcResult>) {
+        let (tx, rx) = oneshot::channel();
+        let call = Self {
+            method,
+            params,
+            reply: Arc::new(Mutex::new(Some(tx))),
+        };
+        (call, rx)
+    }
+
+    /// A string parameter, by name.
+    pub fn param(&self, name: &str) -> Option<&str> {
+        self.params.get(name).and_then(Value::as_str)
+    }
+
+    /// A string parameter the method can't do without.
+    pub fn required(&self, name: &str) -> Result<&str, RpcError> {
+        self.param(name)
+            .ok_or_else(|| RpcError::invalid_params(format!("missing string param '{}'", name)))
+    }
+
+    /// Send the result to the client.
+    pub fn respond(&self, result: RpcResult) {
+        let reply = self.reply.lock().unwrap_or_else(|e| e.into_inner()).take();
+        if let Some(reply) = reply {
+            let _ = reply.send(result);
+        }
+    }
+}
+
+/// A listening remote control socket. A socket file created by us is
+/// removed on drop; one handed over by the service manager is left alone.
+/// The session token file is always removed.
+pub struct RemoteControl {
+    owned_path: Option<PathBuf>,
+    token_path: Option<PathBuf>,
+}
+
+impl RemoteControl {
+    /// Where the session token was written, if JSON-RPC is served.
+    pub fn token_path(&self) -> Option<&Path> {
+        self.token_path.as_deref()
+    }
+}
+
+impl Drop for RemoteControl {
+    fn drop(&mut self) {
+        for path in [&self.owned_path, &self.token_path].into_iter().flatten() {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// The file the session token is written to: `<socket>.token`.
+pub fn token_path(socket: &Path) -> PathBuf {
+    let mut name = socket.as_os_str().to_owned();
+    name.push(".token");
+    PathBuf::from(name)
+}
+
+/// A fresh random session token.
+fn new_token() -> String {
+    use rand::RngCore;
+
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Start serving remote commands, sending them into the app's action queue.
+/// A socket passed in by systemd socket activation (`LISTEN_FDS`) is taken
+/// first; otherwise one is created at `path`. Returns `None` when neither
+/// is available. With `rpc`, JSON-RPC is served too, with a new session
+/// token written next to `path`.
+#[cfg(unix)]
+pub fn start(
+    path: Option<&Path>,
+    rpc: bool,
+    tx: UnboundedSender<Action>,
+) -> io::Result<Option<RemoteControl>> {
+    let (listener, owned_path) = match activated_listener()? {
+        Some(listener) => (listener, None),
+        None => match path {
+            Some(path) => (bind(path)?, Some(path.to_path_buf())),
+            None => return Ok(None),
+        },
+    };
+    let mut control = RemoteControl {
+        owned_path,
+        token_path: None,
+    };
+    let token = match path.filter(|_| rpc) {
+        Some(path) => {
+            let token = new_token();
+            let token_path = token_path(path);
+            write_private(&token_path, &token)?;
+            control.token_path = Some(token_path);
+            Some(Arc::<str>::from(token))
+        }
+        None if rpc => {
+            tracing::warn!("JSON-RPC needs ipc_socket set for its token file; not serving it");
+            None
+        }
+        None => None,
+    };
+    let listener = tokio::net::UnixListener::from_std(listener)?;
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, _)) => {
+                    tokio::spawn(serve_client(stream, token.clone(), tx.clone()));
+                }
+                Err(e) => {
+                    tracing::warn!("Remote control socket stopped accepting: {}", e);
+                    break;
+                }
+            }
+        }
+    });
+    Ok(Some(control))
+}
+
+#[cfg(not(unix))]
+pub fn start(
+    path: Option<&Path>,
+    _rpc: bool,
+    _tx: UnboundedSender<Action>,
+) -> io::Result<Option<RemoteControl>> {
+    match path {
+        Some(_) => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "remote control needs Unix domain sockets",
+        )),
+        None => Ok(None),
+    }
+}
+
+/// Set by [`claim_activation`] when the service manager passed this
+/// process a socket; cleared once the socket is taken.
+#[cfg(unix)]
+static ACTIVATED: std::sync::atomic::AtomicBool = std::sync::atomic::AtomicBool::new(false);
+
+/// Note a socket passed by systemd socket activation (`LISTEN_FDS`), if it
+/// was meant for this process, and clear the `LISTEN_*` variables so child
+/// processes (password commands, the external editor) don't think the
+/// sockets are theirs. Changing the environment isn't safe once other
+/// threads run, so call this first thing in `main`, before the runtime
+/// starts.
+#[cfg(unix)]
+pub fn claim_activation() {
+    let for_us = std::env::var("LISTEN_PID")
+        .ok()
+        .and_then(|pid| pid.parse::<u32>().ok())
+        .is_some_and(|pid| pid == std::process::id());
+    let count = std::env::var("LISTEN_FDS")
+        .ok()
+        .and_then(|n| n.parse::<i32>().ok())
+        .unwrap_or(0);
+    if !for_us || count < 1 {
+        return;
+    }
+    std::env::remove_var("LISTEN_PID");
+    std::env::remove_var("LISTEN_FDS");
+    std::env::remove_var("LISTEN_FDNAMES");
+    ACTIVATED.store(true, std::sync::atomic::Ordering::SeqCst);
+}
+
+#[cfg(not(unix))]
+pub fn claim_activation() {}
+
+/// The first socket passed by the service manager, if
+/// [`claim_activation`] found one and it hasn't been taken yet.
+#[cfg(unix)]
+fn activated_listener() -> io::Result<Option<std::os::unix::net::UnixListener>> {
+    use std::os::unix::io::FromRawFd;
+
+    const SD_LISTEN_FDS_START: i32 = 3;
+    if !ACTIVATED.swap(false, std::sync::atomic::Ordering::SeqCst) {
+        return Ok(None);
+    }
+
+    // SAFETY: with LISTEN_PID naming this process, the service manager
+    // guarantees fd 3 is an open socket handed to us and owned by nobody
+    // else in the process.
+    let listener = unsafe { std::os::unix::net::UnixListener::from_raw_fd(SD_LISTEN_FDS_START) };
+    listener.set_nonblocking(true)?;
+    Ok(Some(listener))
+}
+
+/// Write `contents` to a new file only the current user can read.
+#[cfg(unix)]
+fn write_private(path: &Path, contents: &str) -> io::Result<()> {
+    use std::io::Write;
+    use std::os::unix::fs::OpenOptionsExt;
+
+    let _ = std::fs::remove_file(path);
+    let mut file = std::fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    file.write_all(contents.as_bytes())
+}
+
+/// Bind a new socket at `path`, readable by the current user only. A stale
+/// socket file left by a crashed session is replaced; a live one is not.
+/// The socket is bound inside a new private directory, restricted, then
+/// moved into place, so it is never reachable with the umask's looser
+/// permissions.
+#[cfg(unix)]
+fn bind(path: &Path) -> io::Result<std::os::unix::net::UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, PermissionsExt};
+    use std::os::unix::net::{UnixListener, UnixStream};
+
+    if path.exists() {
+        if UnixStream::connect(path).is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("{} is in use by another instance", path.display()),
+            ));
+        }
+        std::fs::remove_file(path)?;
+    }
+    let mut private = path.as_os_str().to_owned();
+    private.push(format!(".{}", std::process::id()));
+    let private = PathBuf::from(private);
+    let _ = std::fs::remove_dir_all(&private);
+    std::fs::DirBuilder::new().mode(0o700).create(&private)?;
+    let staged = private.join("socket");
+    let bound = UnixListener::bind(&staged).and_then(|listener| {
+        std::fs::set_permissions(&staged, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&staged, path)?;
+        Ok(listener)
+    });
+    let _ = std::fs::remove_dir_all(&private);
+    let listener = bound?;
+    listener.set_nonblocking(true)?;
+    Ok(listener)
+}
+
+#[cfg(unix)]
+async fn serve_client(
+    stream: tokio::net::UnixStream,
+    token: Option<Arc<str>>,
+    tx: UnboundedSender<Action>,
+) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    let mut authenticated = false;
+    while let Ok(Some(line)) = lines.next_line().await {
+        if line.trim().is_empty() {
+            continue;
+        }
+        if let Some(given) = line.trim().strip_prefix("auth ") {
+            let reply = match token.as_deref() {
+                Some(token) if tokens_match(given.trim(), token) => {
+                    authenticated = true;
+                    "ok\n"
+                }
+                Some(_) => {
+                    authenticated = false;
+                    "error: invalid token\n"
+                }
+                None => "ok\n",
+            };
+            if writer.write_all(reply.as_bytes()).await.is_err() {
+                break;
+            }
+            continue;
+        }
+        if line.trim_start().starts_with('{') {
+            let reply = answer_rpc(&line, token.as_deref(), &mut authenticated, &tx).await;
+            if let Some(reply) = reply {
+                if writer
+                    .write_all(format!("{}\n", reply).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    break;
+                }
+            }
+            continue;
+        }
+        let reply = match RemoteCommand::parse(&line) {
+            Ok(_) if token.is_some() && !authenticated => {
+                "error: not authenticated (send auth with the session token first)\n".to_string()
+            }
+            Ok(cmd) => match tx.send(Action::RemoteCommand(cmd)) {
+                Ok(()) => "ok\n".to_string(),
+                Err(_) => "error: shutting down\n".to_string(),
+            },
+            Err(e) => format!("error: {}\n", e),
+        };
+        if writer.write_all(reply.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Answer one JSON-RPC request line. Notifications (requests without an
+/// `id`) are carried out but get no answer.
+async fn answer_rpc(
+    line: &str,
+    token: Option<&str>,
+    authenticated: &mut bool,
+    tx: &UnboundedSender<Action>,
+) -> Option<String> {
+    let request: Value = match serde_json::from_str(line) {
+        Ok(request) => request,
+        Err(e) => {
+            return Some(rpc_response(
+                &Value::Null,
+                Err(RpcError::new(PARSE_ERROR, e.to_string())),
+            ))
+        }
+    };
+    let id = request.get("id").cloned();
+    let result = match (
+        request.get("jsonrpc").and_then(Value::as_str),
+        request.get("method").and_then(Value::as_str),
+    ) {
+        (Some("2.0"), Some(method)) => {
+            let params = request.get("params").cloned().unwrap_or(Value::Null);
+            dispatch_rpc(method, params, token, authenticated, tx).await
+        }
+        _ => Err(RpcError::new(
+            INVALID_REQUEST,
+            "expected a JSON-RPC 2.0 request with a method",
+        )),
+    };
+    id.map(|id| rpc_response(&id, result))
+}
+
+async fn dispatch_rpc(
+    method: &str,
+    params: Value,
+    token: Option<&str>,
+    authenticated: &mut bool,
+    tx: &UnboundedSender<Action>,
+) -> RpcResult {
+    let Some(token) = token else {
+        return Err(RpcError::new(
+            METHOD_NOT_FOUND,
+            "JSON-RPC is disabled (set rpc_enabled under [general])",
+        ));
+    };
+    if method == "authenticate" {
+        let given = params.get("token").and_then(Value::as_str).unwrap_or("");
+        if !tokens_match(given, token) {
+            *authenticated = false;
+            return Err(RpcError::new(NOT_AUTHENTICATED, "invalid token"));
+        }
+        *authenticated = true;
+        return Ok(Value::Bool(true));
+    }
+    if !*authenticated {
+        return Err(RpcError::new(
+            NOT_AUTHENTICATED,
+            "call authenticate with the session token first",
+        ));
+    }
+    let (call, reply) = RpcCall::new(method.to_string(), params);
+    if tx.send(Action::RpcCall(call)).is_err() {
+        return Err(RpcError::failed("shutting down"));
+    }
+    match tokio::time::timeout(RPC_TIMEOUT, reply).await {
+        Ok(Ok(result)) => result,
+        Ok(Err(_)) => Err(RpcError::failed("the request was dropped")),
+        Err(_) => Err(RpcError::failed(format!(
+            "no answer within {}s",
+            RPC_TIMEOUT.as_secs()
+        ))),
+    }
+}
+
+/// Compare tokens in time independent of where they differ.
+fn tokens_match(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn rpc_response(id: &Value, result: RpcResult) -> String {
+    let response = match result {
+        Ok(result) => json!({"jsonrpc": "2.0", "id": id, "result": result}),
+        Err(error) => json!({"jsonrpc": "2.0", "id": id, "error": error}),
+    };
+    response.to_string()
+}
+
+/// Send one command line to a running instance and return its reply.
+/// When the instance serves JSON-RPC, the session token next to the
+/// socket is sent first.
+#[cfg(unix)]
+pub fn send_command(path: &Path, command: &str) -> io::Result<String> {
+    use std::io::{BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+
+    let token = std::fs::read_to_string(token_path(path)).ok();
+    let mut stream = UnixStream::connect(path)?;
+    if let Some(token) = &token {
+        stream.write_all(format!("auth {}\n", token.trim()).as_bytes())?;
+    }
+    stream.write_all(format!("{}\n", command.trim()).as_bytes())?;
+    stream.shutdown(std::net::Shutdown::Write)?;
+    let mut replies = BufReader::new(stream).lines();
+    if token.is_some() {
+        let auth = replies.next().transpose()?.unwrap_or_default();
+        if auth != "ok" {
+            return Ok(auth);
+        }
+    }
+    Ok(replies.next().transpose()?.unwrap_or_default())
+}
+
+#[cfg(not(unix))]
+pub fn send_command(_path: &Path, _command: &str) -> io::Result<String> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        "remote control needs Unix domain sockets",
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            RemoteCommand::parse("goto cn=jdoe,dc=example,dc=com"),
+            Ok(RemoteCommand::Goto("cn=jdoe,dc=example,dc=com".to_string()))
+        );
+        assert_eq!(
+            RemoteCommand::parse("SEARCH  (uid=jdoe) "),
+            Ok(RemoteCommand::Search("(uid=jdoe)".to_string()))
+        );
+        assert_eq!(
+            RemoteCommand::parse("export ~/My Exports/people.ldif (objectClass=person)"),
+            Ok(RemoteCommand::Export {
+                path: "~/My Exports/people.ldif".to_string(),
+                filter: Some("(objectClass=person)".to_string()),
+            })
+        );
+        let quoted = RemoteCommand::parse(r#"export "/tmp/People (old).ldif" (uid=j*)"#).unwrap();
+        assert_eq!(
+            quoted,
+            RemoteCommand::Export {
+                path: "/tmp/People (old).ldif".to_string(),
+                filter: Some("(uid=j*)".to_string()),
+            }
+        );
+        assert_eq!(RemoteCommand::parse(&quoted.to_string()), Ok(quoted));
+        assert!(RemoteCommand::parse(r#"export "/tmp/x.ldif"#).is_err());
+        assert!(RemoteCommand::parse("goto jdoe").is_err());
+        assert!(RemoteCommand::parse("search (uid=").is_err());
+        assert!(RemoteCommand::parse("export").is_err());
+        assert!(RemoteCommand::parse("delete cn=x").is_err());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_socket_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loom.sock");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let control = start(Some(&path), false, tx).unwrap().unwrap();
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o600);
+            // Only the socket is left behind, not the directory it was bound in
+            assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+        }
+
+        let client_path = path.clone();
+        let reply = tokio::task::spawn_blocking(move || {
+            send_command(&client_path, "goto cn=a,dc=example").unwrap()
+        })
+        .await
+        .unwrap();
+        assert_eq!(reply, "ok");
+        assert!(matches!(
+            rx.recv().await,
+            Some(Action::RemoteCommand(RemoteCommand::Goto(dn))) if dn == "cn=a,dc=example"
+        ));
+
+        drop(control);
+        assert!(!path.exists());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn test_plain_commands_need_the_token_with_rpc() {
+        use std::io::{BufRead, BufReader, Write};
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("loom.sock");
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let _control = start(Some(&path), true, tx).unwrap().unwrap();
+
+        let client_path = path.clone();
+        let (refused, accepted) = tokio::task::spawn_blocking(move || {
+            let mut stream = std::os::unix::net::UnixStream::connect(&client_path).unwrap();
+            stream.write_all(b"export /tmp/all.ldif\n").unwrap();
+            stream.shutdown(std::net::Shutdown::Write).unwrap();
+            let mut refused = String::new();
+            BufReader::new(stream).read_line(&mut refused).unwrap();
+            // send_command authenticates with the token file first
+            let accepted = send_command(&client_path, "goto cn=a,dc=example").unwrap();
+            (refused, accepted)
+        })
+        .await
+        .unwrap();
+        assert!(
+            refused.starts_with("error: not authenticated"),
+            "{}",
+            refused
+        );
+        assert_eq!(accepted, "ok");
+        assert!(matches!(
+            rx.recv().await,
+            Some(Action::RemoteCommand(RemoteCommand::Goto(dn))) if dn == "cn=a,dc=example"
+        ));
+    }
+
+    async fn ask(
+        line: &str,
+        token: Option<&str>,
+        authenticated: &mut bool,
+        tx: &UnboundedSender<Action>,
+    ) -> Option<Value> {
+        let reply = answer_rpc(line, token, authenticated, tx).await;
+        reply.map(|r| serde_json::from_str(&r).unwrap())
+    }
+
+    #[tokio::test]
+    async fn test_rpc_requests() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let auth = &mut false;
+
+        let search = r#"{"jsonrpc":"2.0","id":1,"method":"search"}"#;
+        let reply = ask(search, None, auth, &tx).await.unwrap();
+        assert_eq!(reply["error"]["code"], METHOD_NOT_FOUND);
+
+        let token = Some("s3cret");
+        let reply = ask(search, token, auth, &tx).await.unwrap();
+        assert_eq!(reply["error"]["code"], NOT_AUTHENTICATED);
+        let wrong =
+            r#"{"jsonrpc":"2.0","id":2,"method":"authenticate","params":{"token":"wrong!"}}"#;
+        let reply = ask(wrong, token, auth, &tx).await.unwrap();
+        assert_eq!(reply["error"]["code"], NOT_AUTHENTICATED);
+        let right =
+            r#"{"jsonrpc":"2.0","id":3,"method":"authenticate","params":{"token":"s3cret"}}"#;
+        let reply = ask(right, token, auth, &tx).await.unwrap();
+        assert_eq!(reply["result"], true);
+
+        // The app answers authenticated requests
+        let get =
+            r#"{"jsonrpc":"2.0","id":"four","method":"get_entry","params":{"dn":"cn=a,dc=x"}}"#;
+        let app = async {
+            match rx.recv().await {
+                Some(Action::RpcCall(call)) => {
+                    assert_eq!(call.method, "get_entry");
+                    assert_eq!(call.required("dn"), Ok("cn=a,dc=x"));
+                    assert!(call.required("filter").is_err());
+                    call.respond(Ok(json!({"dn": "cn=a,dc=x"})));
+                }
+                other => panic!("expected an RPC call, got {:?}", other),
+            }
+        };
+        let (reply, ()) = tokio::join!(ask(get, token, auth, &tx), app);
+        assert_eq!(
+            reply.unwrap(),
+            json!({"jsonrpc": "2.0", "id": "four", "result": {"dn": "cn=a,dc=x"}})
+        );
+
+        let reply = ask("{not json", token, auth, &tx).await.unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+        assert_eq!(reply["id"], Value::Null);
+        // Notifications get no answer
+        let notification = r#"{"jsonrpc":"2.0","method":"authenticate"}"#;
+        assert!(ask(notification, token, auth, &tx).await.is_none());
+    }
+}
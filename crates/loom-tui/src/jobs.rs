@@ -0,0 +1,457 @@
+use std::collections::{BTreeMap, HashMap};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use loom_core::bulk::{BulkMod, Throttle, ValueEdit};
+use loom_core::humanize;
+use loom_core::import::plan::ImportItem;
+use loom_core::orphans::Reference;
+use tokio::sync::mpsc::UnboundedSender;
+use tokio::task::AbortHandle;
+
+use crate::action::{Action, ConnectionId};
+
+pub type JobId = u64;
+
+/// Finished jobs kept for the jobs panel; older ones are dropped first.
+const MAX_FINISHED: usize = 50;
+
+/// What a job runs, kept so a failed or cancelled job can be started again.
+#[derive(Debug, Clone)]
+pub enum JobTask {
+    Export {
+        path: String,
+        base_dn: String,
+        filter: String,
+        attributes: Vec<String>,
+    },
+    ExportEntries {
+        dns: Vec<String>,
+        path: String,
+        attributes: Vec<String>,
+    },
+    BulkUpdate {
+        filter: String,
+        modifications: Vec<BulkMod>,
+        throttle: Throttle,
+    },
+    Import(Vec<ImportItem>),
+    MembershipImport {
+        path: String,
+        member_attr: String,
+    },
+    OrphanCleanup(Vec<Reference>),
+    BatchEdit {
+        attr: String,
+        edits: Vec<ValueEdit>,
+    },
+    AttributeStats {
+        base_dn: String,
+        sample: Option<usize>,
+    },
+    ApplyStaged {
+        stop_on_error: bool,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JobState {
+    Running,
+    /// Finished, with the summary it reported.
+    Done(String),
+    Failed(String),
+    Cancelled,
+}
+
+impl JobState {
+    /// The state a job's completion action reports: an error message means
+    /// the job failed.
+    fn from_completion(action: &Action) -> Self {
+        if let Action::ErrorMessage(msg) = action {
+            return JobState::Failed(msg.clone());
+        }
+        JobState::Done(summary(action).unwrap_or_default().to_string())
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            JobState::Running => "running",
+            JobState::Done(_) => "done",
+            JobState::Failed(_) => "failed",
+            JobState::Cancelled => "cancelled",
+        }
+    }
+}
+
+/// The summary carried by a job's successful completion action.
+fn summary(action: &Action) -> Option<&str> {
+    match action {
+        Action::ExportComplete(msg)
+        | Action::BulkUpdateComplete(msg)
+        | Action::ImportComplete(msg, _)
+        | Action::MembershipImportComplete(msg, _)
+        | Action::OrphanCleanupComplete(msg, _)
+        | Action::BatchEditComplete { summary: msg, .. }
+        | Action::AttributeStatsComplete(msg, _)
+        | Action::StagedChangesApplied(msg, _) => Some(msg),
+        _ => None,
+    }
+}
+
+/// [`summary`], to append to.
+fn summary_mut(action: &mut Action) -> Option<&mut String> {
+    match action {
+        Action::ExportComplete(msg)
+        | Action::BulkUpdateComplete(msg)
+        | Action::ImportComplete(msg, _)
+        | Action::MembershipImportComplete(msg, _)
+        | Action::OrphanCleanupComplete(msg, _)
+        | Action::BatchEditComplete { summary: msg, .. }
+        | Action::AttributeStatsComplete(msg, _)
+        | Action::StagedChangesApplied(msg, _) => Some(msg),
+        _ => None,
+    }
+}
+
+/// Append how long the work begun at `started` took: "... in 3m 05s".
+pub fn append_elapsed(summary: &mut String, started: Instant) {
+    summary.push_str(&format!(" in {}", humanize::duration(started.elapsed())));
+}
+
+/// A long-running operation started in the background.
+pub struct Job {
+    pub id: JobId,
+    pub conn_id: ConnectionId,
+    pub title: String,
+    pub task: JobTask,
+    pub state: JobState,
+    /// Items done so far, and the total when it is known up front.
+    pub progress: Option<(usize, Option<usize>)>,
+    started: Instant,
+    finished: Option<Instant>,
+    abort: Option<AbortHandle>,
+}
+
+impl Job {
+    pub fn is_running(&self) -> bool {
+        self.state == JobState::Running
+    }
+
+    /// Only jobs that didn't finish can be retried.
+    pub fn can_retry(&self) -> bool {
+        matches!(self.state, JobState::Failed(_) | JobState::Cancelled)
+    }
+
+    /// How long the job ran, or has been running.
+    pub fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(Instant::now) - self.started
+    }
+}
+
+/// Passed to a job's task so it can report progress.
+#[derive(Clone)]
+pub struct JobHandle {
+    id: JobId,
+    tx: UnboundedSender<Action>,
+}
+
+impl JobHandle {
+    pub fn progress(&self, done: usize, total: Option<usize>) {
+        let _ = self.tx.send(Action::JobProgress(self.id, done, total));
+    }
+}
+
+/// Short background operations running per connection.
+type ActivityCounts = Arc<Mutex<HashMap<ConnectionId, usize>>>;
+
+/// Held by a short background operation (a search, an entry or schema
+/// load) while it runs. These are too quick to list as jobs but still
+/// count towards the status bar's spinner; dropping the guard, when the
+/// task ends or is aborted, takes the operation off the count.
+pub struct Activity {
+    conn_id: ConnectionId,
+    counts: ActivityCounts,
+}
+
+impl Drop for Activity {
+    fn drop(&mut self) {
+        let mut counts = self.counts.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(count) = counts.get_mut(&self.conn_id) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(&self.conn_id);
+            }
+        }
+    }
+}
+
+/// Every job started this session, running ones and recently finished.
+pub struct JobRegistry {
+    jobs: Vec<Job>,
+    next_id: JobId,
+    tx: UnboundedSender<Action>,
+    activities: ActivityCounts,
+}
+
+impl JobRegistry {
+    pub fn new(tx: UnboundedSender<Action>) -> Self {
+        Self {
+            jobs: Vec::new(),
+            next_id: 1,
+            tx,
+            activities: ActivityCounts::default(),
+        }
+    }
+
+    pub fn jobs(&self) -> &[Job] {
+        &self.jobs
+    }
+
+    pub fn get(&self, id: JobId) -> Option<&Job> {
+        self.jobs.iter().find(|j| j.id == id)
+    }
+
+    pub fn running(&self) -> usize {
+        self.jobs.iter().filter(|j| j.is_running()).count()
+    }
+
+    /// Count a short operation on `conn_id` until the returned guard is
+    /// dropped. Move the guard into the operation's task.
+    pub fn track(&self, conn_id: ConnectionId) -> Activity {
+        let mut counts = self.activities.lock().unwrap_or_else(|e| e.into_inner());
+        *counts.entry(conn_id).or_insert(0) += 1;
+        Activity {
+            conn_id,
+            counts: self.activities.clone(),
+        }
+    }
+
+    /// Operations in flight per connection: running jobs and tracked
+    /// activities. Connections with nothing running are left out.
+    pub fn in_flight(&self) -> BTreeMap<ConnectionId, usize> {
+        let mut in_flight: BTreeMap<ConnectionId, usize> = self
+            .activities
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(&conn_id, &count)| (conn_id, count))
+            .collect();
+        for job in self.jobs.iter().filter(|j| j.is_running()) {
+            *in_flight.entry(job.conn_id).or_insert(0) += 1;
+        }
+        in_flight
+    }
+
+    /// Record a new running job.
+    pub fn register(&mut self, conn_id: ConnectionId, title: String, task: JobTask) -> JobId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.jobs.push(Job {
+            id,
+            conn_id,
+            title,
+            task,
+            state: JobState::Running,
+            progress: None,
+            started: Instant::now(),
+            finished: None,
+            abort: None,
+        });
+        id
+    }
+
+    /// Register a job and run `run` as its task. The task returns the action
+    /// it finishes with (its completion message or an error), which is sent
+    /// on to the app before the job is marked finished. A completion
+    /// message gets the time the job took added, "... in 2.4s".
+    pub fn spawn<F, Fut>(
+        &mut self,
+        conn_id: ConnectionId,
+        title: String,
+        task: JobTask,
+        run: F,
+    ) -> JobId
+    where
+        F: FnOnce(JobHandle) -> Fut,
+        Fut: Future<Output = Action> + Send + 'static,
+    {
+        let id = self.register(conn_id, title, task);
+        let tx = self.tx.clone();
+        let started = Instant::now();
+        let handle = tokio::spawn(run(JobHandle { id, tx: tx.clone() }));
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.abort = Some(handle.abort_handle());
+        }
+        tokio::spawn(async move {
+            let state = match handle.await {
+                Ok(mut action) => {
+                    if let Some(summary) = summary_mut(&mut action) {
+                        append_elapsed(summary, started);
+                    }
+                    let state = JobState::from_completion(&action);
+                    let _ = tx.send(action);
+                    state
+                }
+                Err(e) if e.is_cancelled() => JobState::Cancelled,
+                Err(e) => JobState::Failed(format!("Job failed: {}", e)),
+            };
+            let _ = tx.send(Action::JobFinished(id, state));
+        });
+        id
+    }
+
+    pub fn set_progress(&mut self, id: JobId, done: usize, total: Option<usize>) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id && j.is_running()) {
+            job.progress = Some((done, total));
+        }
+    }
+
+    pub fn finish(&mut self, id: JobId, state: JobState) {
+        if let Some(job) = self.jobs.iter_mut().find(|j| j.id == id) {
+            job.state = state;
+            job.finished = Some(Instant::now());
+            job.abort = None;
+        }
+        self.trim();
+    }
+
+    /// Stop a running job. Its state changes once the task has stopped.
+    pub fn cancel(&mut self, id: JobId) -> bool {
+        match self.jobs.iter().find(|j| j.id == id && j.is_running()) {
+            Some(Job {
+                abort: Some(abort), ..
+            }) => {
+                abort.abort();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Drop finished jobs from the list.
+    pub fn clear_finished(&mut self) {
+        self.jobs.retain(|j| j.is_running());
+    }
+
+    fn trim(&mut self) {
+        let finished = self.jobs.iter().filter(|j| !j.is_running()).count();
+        let mut excess = finished.saturating_sub(MAX_FINISHED);
+        self.jobs.retain(|j| {
+            if excess > 0 && !j.is_running() {
+                excess -= 1;
+                false
+            } else {
+                true
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn task() -> JobTask {
+        JobTask::MembershipImport {
+            path: "groups.csv".to_string(),
+            member_attr: "member".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_job_lifecycle() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        let a = jobs.register(0, "Import groups.csv".to_string(), task());
+        let b = jobs.register(0, "Import groups.csv".to_string(), task());
+        assert_eq!(jobs.running(), 2);
+
+        jobs.set_progress(a, 5, Some(10));
+        assert_eq!(jobs.get(a).unwrap().progress, Some((5, Some(10))));
+
+        jobs.finish(a, JobState::Done("ok".to_string()));
+        jobs.finish(b, JobState::Failed("Import failed".to_string()));
+        assert_eq!(jobs.running(), 0);
+        assert!(!jobs.get(a).unwrap().can_retry());
+        assert!(jobs.get(b).unwrap().can_retry());
+        // Progress arriving after the end is ignored
+        jobs.set_progress(a, 6, Some(10));
+        assert_eq!(jobs.get(a).unwrap().progress, Some((5, Some(10))));
+        assert!(!jobs.cancel(a));
+
+        jobs.clear_finished();
+        assert!(jobs.jobs().is_empty());
+    }
+
+    #[test]
+    fn test_finished_jobs_are_trimmed() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        let running = jobs.register(0, "Export".to_string(), task());
+        for _ in 0..MAX_FINISHED + 5 {
+            let id = jobs.register(0, "Export".to_string(), task());
+            jobs.finish(id, JobState::Cancelled);
+        }
+        assert_eq!(jobs.jobs().len(), MAX_FINISHED + 1);
+        assert!(jobs.get(running).unwrap().is_running());
+        // The oldest finished jobs are dropped first
+        assert!(jobs.get(2).is_none());
+        assert!(jobs.get(7).is_some());
+    }
+
+    #[test]
+    fn test_in_flight_counts() {
+        let (tx, _rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        let search = jobs.track(1);
+        let schema = jobs.track(1);
+        let export = jobs.register(2, "Export".to_string(), task());
+        assert_eq!(jobs.in_flight(), BTreeMap::from([(1, 2), (2, 1)]));
+
+        drop(search);
+        jobs.finish(export, JobState::Done(String::new()));
+        assert_eq!(jobs.in_flight(), BTreeMap::from([(1, 1)]));
+        drop(schema);
+        assert!(jobs.in_flight().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_cancel_running_job() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        let id = jobs.spawn(0, "Export".to_string(), task(), |_| async {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Action::None
+        });
+        assert!(jobs.cancel(id));
+        match rx.recv().await {
+            Some(Action::JobFinished(finished, JobState::Cancelled)) => assert_eq!(finished, id),
+            other => panic!("expected a cancelled job, got {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_completion_message_gets_elapsed_time() {
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let mut jobs = JobRegistry::new(tx);
+        jobs.spawn(0, "Export".to_string(), task(), |_| async {
+            Action::ExportComplete("Exported 3 entries to out.ldif".to_string())
+        });
+        match rx.recv().await {
+            Some(Action::ExportComplete(msg)) => {
+                assert!(
+                    msg.starts_with("Exported 3 entries to out.ldif in "),
+                    "{}",
+                    msg
+                );
+                assert!(msg.ends_with("ms"), "{}", msg);
+            }
+            other => panic!("expected the completion message, got {:?}", other),
+        }
+        match rx.recv().await {
+            Some(Action::JobFinished(_, JobState::Done(msg))) => assert!(msg.ends_with("ms")),
+            other => panic!("expected a finished job, got {:?}", other),
+        }
+    }
+}
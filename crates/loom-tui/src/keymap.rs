@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
 use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
 use tracing::warn;
@@ -105,10 +105,95 @@ pub fn display_key(modifiers: KeyModifiers, code: KeyCode) -> String {
     format!("{}{}", prefix, key_part)
 }
 
+/// Where a `[keybindings.<context>]` table applies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KeyContext {
+    /// Everywhere, like the named global bindings.
+    Global,
+    Tree,
+    Detail,
+    /// The search results list, while the filter isn't being edited.
+    SearchResults,
+    /// Any open popup or dialog.
+    Dialogs,
+}
+
+impl KeyContext {
+    /// Name of the config table, e.g. `tree` for `[keybindings.tree]`.
+    pub fn table(self) -> &'static str {
+        match self {
+            KeyContext::Global => "global",
+            KeyContext::Tree => "tree",
+            KeyContext::Detail => "detail",
+            KeyContext::SearchResults => "search_results",
+            KeyContext::Dialogs => "dialogs",
+        }
+    }
+
+    /// Whether the context has a selected entry to act on.
+    fn has_entry(self) -> bool {
+        matches!(
+            self,
+            KeyContext::Tree | KeyContext::Detail | KeyContext::SearchResults
+        )
+    }
+}
+
+/// Actions that can be bound by name in the `[keybindings.<context>]`
+/// tables, besides the names of the global bindings. Those acting on an
+/// entry take the DN selected in the context and return `None` without one.
+pub fn named_action(name: &str, dn: Option<&str>) -> Option<Action> {
+    let action = match name {
+        "show_about" => Action::ShowAbout,
+        "command_line" => Action::ShowCommandLine,
+        "focus_tree" => Action::FocusPanel(FocusTarget::TreePanel),
+        "focus_detail" => Action::FocusPanel(FocusTarget::DetailPanel),
+        "tree_up" => Action::TreeUp,
+        "tree_down" => Action::TreeDown,
+        "tree_toggle" => Action::TreeToggle,
+        "clear_search" => Action::SearchClear,
+        "show_import_dialog" => Action::ShowImportDialog,
+        "show_membership_import" => Action::ShowMembershipImportDialog,
+        "save_dry_run_script" => Action::ExportDryRunScript,
+        "clear_finished_jobs" => Action::ClearFinishedJobs,
+        "lock" => Action::LockSession,
+        "close_popup" => Action::ClosePopup,
+        "popup_confirm" => Action::PopupConfirm,
+        "popup_cancel" => Action::PopupCancel,
+        _ => {
+            let dn = dn?.to_string();
+            match name {
+                "copy_dn" => Action::CopyToClipboard(dn),
+                "create_child" => Action::ShowCreateEntryDialog(dn),
+                "add_attribute" => Action::ShowAddAttribute(dn),
+                "refresh_subtree" => Action::RefreshSubtree(dn),
+                "delete_entry" => Action::ShowConfirm(
+                    format!("Delete entry?\n{}", dn),
+                    Box::new(Action::DeleteEntry(dn)),
+                ),
+                "cut_entry" => Action::CutEntry { dn, subtree: false },
+                "cut_subtree" => Action::CutEntry { dn, subtree: true },
+                "paste_under" => Action::PasteEntry(dn),
+                "toggle_favorite" => Action::ToggleFavorite(dn),
+                "export_entry" => Action::ShowExportEntriesDialog(vec![dn]),
+                "entry_report" => Action::ShowReportDialog(vec![dn]),
+                "rename_user" => Action::ShowRenameUser(dn),
+                _ => return None,
+            }
+        }
+    };
+    Some(action)
+}
+
 /// Maps configured key bindings to actions and provides display hints.
 pub struct Keymap {
     global: HashMap<(KeyModifiers, KeyCode), Action>,
     hints: HashMap<&'static str, String>,
+    actions: HashMap<&'static str, Action>,
+    /// Context bindings to an action name, resolved when the key is pressed.
+    contexts: HashMap<(KeyContext, KeyModifiers, KeyCode), String>,
+    /// Invalid and conflicting bindings found while building the keymap.
+    warnings: Vec<String>,
 }
 
 impl Keymap {
@@ -118,6 +203,10 @@ impl Keymap {
         let defaults = KeybindingConfig::default();
         let mut global = HashMap::new();
         let mut hints = HashMap::new();
+        let mut actions = HashMap::new();
+        let mut warnings = Vec::new();
+        // Which binding owns each global key, to report overlaps
+        let mut owners: HashMap<(KeyModifiers, KeyCode), String> = HashMap::new();
 
         let bindings: Vec<(&str, &str, &str, Action)> = vec![
             ("quit", &config.quit, &defaults.quit, Action::Quit),
@@ -181,6 +270,42 @@ impl Keymap {
                 &defaults.toggle_log_panel,
                 Action::ToggleLogPanel,
             ),
+            (
+                "toggle_jobs_panel",
+                &config.toggle_jobs_panel,
+                &defaults.toggle_jobs_panel,
+                Action::ToggleJobsPanel,
+            ),
+            (
+                "toggle_trace_panel",
+                &config.toggle_trace_panel,
+                &defaults.toggle_trace_panel,
+                Action::ToggleTracePanel,
+            ),
+            (
+                "toggle_metrics_panel",
+                &config.toggle_metrics_panel,
+                &defaults.toggle_metrics_panel,
+                Action::ToggleMetricsPanel,
+            ),
+            (
+                "show_replication",
+                &config.show_replication,
+                &defaults.show_replication,
+                Action::ToggleReplicationPanel,
+            ),
+            (
+                "show_account_reports",
+                &config.show_account_reports,
+                &defaults.show_account_reports,
+                Action::ShowAccountReports,
+            ),
+            (
+                "force_refresh",
+                &config.force_refresh,
+                &defaults.force_refresh,
+                Action::EntryRefresh,
+            ),
             (
                 "save_connection",
                 &config.save_connection,
@@ -211,24 +336,218 @@ impl Keymap {
                 &defaults.close_tab,
                 Action::CloseCurrentTab,
             ),
+            (
+                "toggle_dry_run",
+                &config.toggle_dry_run,
+                &defaults.toggle_dry_run,
+                Action::ToggleDryRun,
+            ),
+            (
+                "toggle_staging",
+                &config.toggle_staging,
+                &defaults.toggle_staging,
+                Action::ToggleStaging,
+            ),
+            (
+                "toggle_changes_panel",
+                &config.toggle_changes_panel,
+                &defaults.toggle_changes_panel,
+                Action::ToggleChangesPanel,
+            ),
+            (
+                "show_recent",
+                &config.show_recent,
+                &defaults.show_recent,
+                Action::ShowRecentEntries,
+            ),
+            (
+                "toggle_split",
+                &config.toggle_split,
+                &defaults.toggle_split,
+                Action::ToggleSplitView,
+            ),
+            (
+                "swap_split_focus",
+                &config.swap_split_focus,
+                &defaults.swap_split_focus,
+                Action::SwapSplitFocus,
+            ),
+            (
+                "copy_to_other_side",
+                &config.copy_to_other_side,
+                &defaults.copy_to_other_side,
+                Action::CopyToOtherSide,
+            ),
+            (
+                "goto_dn",
+                &config.goto_dn,
+                &defaults.goto_dn,
+                Action::ShowGoToDn,
+            ),
+            (
+                "quick_open",
+                &config.quick_open,
+                &defaults.quick_open,
+                Action::ShowQuickOpen,
+            ),
+            (
+                "nav_back",
+                &config.nav_back,
+                &defaults.nav_back,
+                Action::NavigateBack,
+            ),
+            (
+                "nav_forward",
+                &config.nav_forward,
+                &defaults.nav_forward,
+                Action::NavigateForward,
+            ),
+            (
+                "show_history",
+                &config.show_history,
+                &defaults.show_history,
+                Action::ShowHistory,
+            ),
+            (
+                "show_favorites",
+                &config.show_favorites,
+                &defaults.show_favorites,
+                Action::ShowFavorites,
+            ),
+            (
+                "show_workspaces",
+                &config.show_workspaces,
+                &defaults.show_workspaces,
+                Action::ShowWorkspaces,
+            ),
+            (
+                "command_palette",
+                &config.command_palette,
+                &defaults.command_palette,
+                Action::ShowCommandPalette,
+            ),
         ];
 
         for (name, user_str, default_str, action) in bindings {
             let (mods, code) = match parse_key(user_str) {
                 Ok(parsed) => parsed,
                 Err(e) => {
-                    warn!(
+                    let msg = format!(
                         "Invalid keybinding for '{}': '{}' ({}), using default '{}'",
                         name, user_str, e, default_str
                     );
+                    warn!("{}", msg);
+                    warnings.push(msg);
                     parse_key(default_str).expect("default keybinding must parse")
                 }
             };
-            global.insert((mods, code), action);
+            if let Some(previous) = owners.insert((mods, code), name.to_string()) {
+                warnings.push(format!(
+                    "Keybinding conflict: {} is bound to both '{}' and '{}', using '{}'",
+                    user_str, previous, name, name
+                ));
+            }
+            global.insert((mods, code), action.clone());
             hints.insert(name, display_key(mods, code));
+            actions.insert(name, action);
+        }
+
+        let mut contexts = HashMap::new();
+        let tables: [(KeyContext, &BTreeMap<String, String>); 5] = [
+            (KeyContext::Global, &config.global),
+            (KeyContext::Tree, &config.tree),
+            (KeyContext::Detail, &config.detail),
+            (KeyContext::SearchResults, &config.search_results),
+            (KeyContext::Dialogs, &config.dialogs),
+        ];
+        for (context, table) in tables {
+            let section = format!("[keybindings.{}]", context.table());
+            for (key_str, name) in table {
+                let (mods, code) = match parse_key(key_str) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        warnings.push(format!("Invalid key '{}' in {}: {}", key_str, section, e));
+                        continue;
+                    }
+                };
+                let known =
+                    actions.contains_key(name.as_str()) || named_action(name, Some("")).is_some();
+                if !known {
+                    warnings.push(format!(
+                        "Unknown action '{}' for {} in {}",
+                        name, key_str, section
+                    ));
+                    continue;
+                }
+                let needs_entry =
+                    !actions.contains_key(name.as_str()) && named_action(name, None).is_none();
+                if needs_entry && !context.has_entry() {
+                    warnings.push(format!(
+                        "'{}' acts on the selected entry and can't be bound in {}",
+                        name, section
+                    ));
+                    continue;
+                }
+                let typed = mods.difference(KeyModifiers::SHIFT).is_empty()
+                    && matches!(code, KeyCode::Char(_));
+                if context == KeyContext::Dialogs && typed {
+                    warnings.push(format!(
+                        "{} in {} would block typing in dialogs; use a Ctrl, Alt or function key",
+                        key_str, section
+                    ));
+                    continue;
+                }
+                if let Some(previous) = contexts.insert((context, mods, code), name.clone()) {
+                    warnings.push(format!(
+                        "Keybinding conflict: {} is bound to both '{}' and '{}' in {}, using '{}'",
+                        key_str, previous, name, section, name
+                    ));
+                }
+                if let Some(owner) = owners.get(&(mods, code)) {
+                    warnings.push(format!(
+                        "Keybinding conflict: {} in {} overrides the global '{}'",
+                        key_str, section, owner
+                    ));
+                }
+                if context == KeyContext::Global {
+                    let action = actions
+                        .get(name.as_str())
+                        .cloned()
+                        .or_else(|| named_action(name, None))
+                        .expect("checked above");
+                    owners.insert((mods, code), name.clone());
+                    global.insert((mods, code), action);
+                }
+            }
         }
 
-        Self { global, hints }
+        Self {
+            global,
+            hints,
+            actions,
+            contexts,
+            warnings,
+        }
+    }
+
+    /// Invalid or conflicting bindings in the configuration, for the log.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// The action a `[keybindings.<context>]` table binds `key` to, with
+    /// entry actions applied to `dn`.
+    pub fn resolve_context(
+        &self,
+        context: KeyContext,
+        key: &KeyEvent,
+        dn: Option<&str>,
+    ) -> Option<Action> {
+        let name = self.contexts.get(&(context, key.modifiers, key.code))?;
+        self.actions
+            .get(name.as_str())
+            .cloned()
+            .or_else(|| named_action(name, dn))
     }
 
     /// Check only the configured global bindings, ignoring context-specific fallbacks.
@@ -265,6 +584,11 @@ impl Keymap {
         }
     }
 
+    /// The action bound to a name, e.g. `ShowSchemaViewer` for "show_schema_viewer".
+    pub fn action(&self, name: &str) -> Option<&Action> {
+        self.actions.get(name)
+    }
+
     /// Get the display string for a named action (for status bar hints).
     pub fn hint(&self, action: &str) -> &str {
         self.hints.get(action).map(|s| s.as_str()).unwrap_or("???")
@@ -541,10 +865,12 @@ mod tests {
 
     #[test]
     fn test_custom_keybinding() {
-        let mut config = KeybindingConfig::default();
-        config.quit = "Alt+q".to_string();
-        config.show_connect_dialog = "F5".to_string();
-        config.show_help = "F3".to_string(); // avoid collision with show_connect_dialog on F5
+        let config = KeybindingConfig {
+            quit: "Alt+q".to_string(),
+            show_connect_dialog: "F5".to_string(),
+            show_help: "F3".to_string(), // avoid collision with show_connect_dialog on F5
+            ..Default::default()
+        };
 
         let km = Keymap::from_config(&config);
 
@@ -566,8 +892,10 @@ mod tests {
 
     #[test]
     fn test_invalid_key_string_falls_back() {
-        let mut config = KeybindingConfig::default();
-        config.quit = "BADKEY!!!".to_string();
+        let config = KeybindingConfig {
+            quit: "BADKEY!!!".to_string(),
+            ..Default::default()
+        };
 
         let km = Keymap::from_config(&config);
 
@@ -619,6 +947,64 @@ mod tests {
         assert_eq!(km.hint("nonexistent"), "???");
     }
 
+    #[test]
+    fn test_default_f12_dry_run() {
+        let km = Keymap::default();
+        let action = km.resolve(key(KeyCode::F(12)), FocusTarget::DetailPanel);
+        assert!(matches!(action, Action::ToggleDryRun));
+        assert_eq!(km.hint("toggle_dry_run"), "F12");
+    }
+
+    #[test]
+    fn test_default_ctrl_o_recent() {
+        let km = Keymap::default();
+        let action = km.resolve(ctrl(KeyCode::Char('o')), FocusTarget::TreePanel);
+        assert!(matches!(action, Action::ShowRecentEntries));
+        assert_eq!(km.hint("show_recent"), "C-o");
+    }
+
+    #[test]
+    fn test_default_split_view_keys() {
+        let km = Keymap::default();
+        let action = km.resolve(ctrl(KeyCode::Char('s')), FocusTarget::TreePanel);
+        assert!(matches!(action, Action::ToggleSplitView));
+        let action = km.resolve(ctrl(KeyCode::Char('t')), FocusTarget::DetailPanel);
+        assert!(matches!(action, Action::SwapSplitFocus));
+        let action = km.resolve(ctrl(KeyCode::Char('y')), FocusTarget::DetailPanel);
+        assert!(matches!(action, Action::CopyToOtherSide));
+    }
+
+    #[test]
+    fn test_default_goto_dn_key() {
+        let km = Keymap::default();
+        let action = km.resolve(ctrl(KeyCode::Char('g')), FocusTarget::TreePanel);
+        assert!(matches!(action, Action::ShowGoToDn));
+        assert_eq!(km.hint("goto_dn"), "C-g");
+    }
+
+    #[test]
+    fn test_default_favorites_key() {
+        let km = Keymap::default();
+        let action = km.resolve(ctrl(KeyCode::Char('f')), FocusTarget::DetailPanel);
+        assert!(matches!(action, Action::ShowFavorites));
+        assert_eq!(km.hint("show_favorites"), "C-f");
+        assert_eq!(km.hint("show_workspaces"), "A-w");
+        assert_eq!(km.hint("command_palette"), "C-p");
+    }
+
+    #[test]
+    fn test_default_nav_history_keys() {
+        let km = Keymap::default();
+        let alt = |code| KeyEvent::new(code, KeyModifiers::ALT);
+        let action = km.resolve(alt(KeyCode::Left), FocusTarget::DetailPanel);
+        assert!(matches!(action, Action::NavigateBack));
+        let action = km.resolve(alt(KeyCode::Right), FocusTarget::TreePanel);
+        assert!(matches!(action, Action::NavigateForward));
+        assert_eq!(km.hint("nav_back"), "A-Left");
+        let action = km.resolve(alt(KeyCode::Char('h')), FocusTarget::TreePanel);
+        assert!(matches!(action, Action::ShowHistory));
+    }
+
     #[test]
     fn test_default_f5_help() {
         let km = Keymap::default();
@@ -626,6 +1012,62 @@ mod tests {
         assert!(matches!(action, Action::ShowHelp));
     }
 
+    #[test]
+    fn test_context_bindings() {
+        let table = |pairs: &[(&str, &str)]| {
+            pairs
+                .iter()
+                .map(|(k, v)| (k.to_string(), v.to_string()))
+                .collect::<BTreeMap<_, _>>()
+        };
+        let config = KeybindingConfig {
+            global: table(&[("Alt+a", "show_about")]),
+            tree: table(&[("y", "copy_dn"), ("Ctrl+s", "show_schema_viewer")]),
+            dialogs: table(&[("q", "close_popup"), ("Alt+x", "copy_dn")]),
+            detail: table(&[("z", "frobnicate")]),
+            ..Default::default()
+        };
+        let km = Keymap::from_config(&config);
+
+        let alt_a = KeyEvent::new(KeyCode::Char('a'), KeyModifiers::ALT);
+        assert!(matches!(
+            km.resolve(alt_a, FocusTarget::DetailPanel),
+            Action::ShowAbout
+        ));
+        let action = km.resolve_context(KeyContext::Tree, &key(KeyCode::Char('y')), Some("cn=a"));
+        assert!(matches!(action, Some(Action::CopyToClipboard(dn)) if dn == "cn=a"));
+        assert!(km
+            .resolve_context(KeyContext::Tree, &key(KeyCode::Char('y')), None)
+            .is_none());
+        assert!(km
+            .resolve_context(KeyContext::Detail, &key(KeyCode::Char('y')), Some("cn=a"))
+            .is_none());
+
+        // Shadowing Ctrl+s, a typed key and an entry action in dialogs, and
+        // an unknown name are all reported
+        let warnings = km.warnings();
+        assert_eq!(warnings.len(), 4, "{:?}", warnings);
+        assert!(warnings[0].contains("overrides the global 'toggle_split'"));
+        assert!(warnings
+            .iter()
+            .any(|w| w.contains("Unknown action 'frobnicate'")));
+        assert!(km
+            .resolve_context(KeyContext::Dialogs, &key(KeyCode::Char('q')), None)
+            .is_none());
+        assert!(Keymap::default().warnings().is_empty());
+    }
+
+    #[test]
+    fn test_global_conflict_reported() {
+        let config = KeybindingConfig {
+            show_recent: "Ctrl+s".to_string(),
+            ..Default::default()
+        };
+        let km = Keymap::from_config(&config);
+        assert_eq!(km.warnings().len(), 1);
+        assert!(km.warnings()[0].contains("'show_recent' and 'toggle_split'"));
+    }
+
     #[test]
     fn test_question_mark_fallback_help() {
         let km = Keymap::default();
@@ -1,11 +1,22 @@
 pub mod action;
 pub mod app;
+pub mod commands;
 pub mod component;
 pub mod components;
 pub mod config;
 pub mod event;
+pub mod exporter;
+pub mod external_editor;
 pub mod focus;
+pub mod history;
+pub mod hooks;
+pub mod ipc;
+pub mod jobs;
 pub mod keymap;
+pub mod log_capture;
+pub mod profile_import;
+pub mod session;
+pub mod stats;
 pub mod theme;
 pub mod tui;
 pub mod widgets;
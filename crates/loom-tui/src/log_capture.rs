@@ -0,0 +1,117 @@
+//! A tracing layer that copies log events from the loom crates into a
+//! buffer the app drains into its log panel, so `debug!` lines from
+//! loom-core show up next to the app's own messages.
+
+use std::fmt::Write;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::filter::{Filtered, Targets};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Events held until the app next drains them; older ones are dropped
+/// first if it falls behind.
+const MAX_PENDING: usize = 1000;
+
+static PENDING: Mutex<Vec<CapturedLog>> = Mutex::new(Vec::new());
+
+/// A log event recorded by [`CaptureLayer`].
+#[derive(Debug, Clone)]
+pub struct CapturedLog {
+    pub time: DateTime<Local>,
+    pub level: Level,
+    /// Module path the event came from, e.g. `loom_core::connection`.
+    pub target: String,
+    /// The event's message, followed by any other fields as `name=value`.
+    pub message: String,
+}
+
+/// Records every event it sees into the pending buffer.
+pub struct CaptureLayer;
+
+/// The capture layer, limited to debug and above from the loom crates.
+pub fn layer<S: Subscriber>() -> Filtered<CaptureLayer, Targets, S> {
+    let targets = Targets::new()
+        .with_target("loom_core", Level::DEBUG)
+        .with_target("loom_tui", Level::DEBUG)
+        .with_target("loom_ldapbrowser", Level::DEBUG);
+    CaptureLayer.with_filter(targets)
+}
+
+/// Take the events captured since the last call.
+pub fn drain() -> Vec<CapturedLog> {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    std::mem::take(&mut *pending)
+}
+
+fn push(log: CapturedLog) {
+    let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+    if pending.len() >= MAX_PENDING {
+        pending.remove(0);
+    }
+    pending.push(log);
+}
+
+impl<S: Subscriber> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let metadata = event.metadata();
+        push(CapturedLog {
+            time: Local::now(),
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.message + &visitor.fields,
+        });
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{:?}", value);
+        } else {
+            let _ = write!(self.fields, " {}={:?}", field.name(), value);
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message.push_str(value);
+        } else {
+            let _ = write!(self.fields, " {}={}", field.name(), value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn test_captures_loom_events() {
+        let subscriber = tracing_subscriber::registry().with(layer());
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::debug!(target: "loom_core::connection", dn = "dc=x", "bound");
+            tracing::info!(target: "other_crate", "not ours");
+            tracing::trace!(target: "loom_core", "too detailed");
+        });
+        let captured: Vec<CapturedLog> = drain()
+            .into_iter()
+            .filter(|log| log.target.starts_with("loom_core") || log.target == "other_crate")
+            .collect();
+        assert_eq!(captured.len(), 1);
+        assert_eq!(captured[0].level, Level::DEBUG);
+        assert_eq!(captured[0].target, "loom_core::connection");
+        assert_eq!(captured[0].message, "bound dn=dc=x");
+    }
+}
@@ -0,0 +1,513 @@
+use std::path::Path;
+
+use loom_core::connection::TlsMode;
+use loom_core::credentials::CredentialMethod;
+
+use crate::config::{AppConfig, ConnectionProfile};
+
+/// File formats accepted by the profile importer.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ProfileSource {
+    /// loom-ldapbrowser `[[connections]]` TOML.
+    Toml,
+    /// Apache Directory Studio `connections.xml` export.
+    DirectoryStudio,
+    /// OpenLDAP client `ldap.conf` / `.ldaprc`.
+    LdapConf,
+}
+
+impl ProfileSource {
+    /// Guess the format from the file name, then from the content.
+    pub fn detect(path: &str, content: &str) -> Self {
+        let p = Path::new(path);
+        let file_name = p
+            .file_name()
+            .map(|f| f.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let ext = p
+            .extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+
+        if ext == "xml" || content.trim_start().starts_with('<') {
+            ProfileSource::DirectoryStudio
+        } else if ext == "conf" || file_name == ".ldaprc" || file_name == "ldaprc" {
+            ProfileSource::LdapConf
+        } else {
+            ProfileSource::Toml
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            ProfileSource::Toml => "TOML",
+            ProfileSource::DirectoryStudio => "Apache Directory Studio",
+            ProfileSource::LdapConf => "ldap.conf",
+        }
+    }
+}
+
+/// Parse profiles from any supported format, returning the detected source.
+pub fn parse_profiles(
+    path: &str,
+    content: &str,
+) -> Result<(ProfileSource, Vec<ConnectionProfile>), String> {
+    let source = ProfileSource::detect(path, content);
+    let profiles = match source {
+        ProfileSource::Toml => AppConfig::import_profiles(content)?,
+        ProfileSource::DirectoryStudio => parse_directory_studio(content)?,
+        ProfileSource::LdapConf => parse_ldap_conf(content)?,
+    };
+    Ok((source, profiles))
+}
+
+/// Parse an Apache Directory Studio `connections.xml` file.
+///
+/// Reads the attributes of each `<connection>` element (name, host, port,
+/// encryptionMethod, authMethod, bindPrincipal, readOnly, timeout) and a
+/// base DN from any `extendedProperty` whose name ends in `baseDN`.
+/// Stored bind passwords are ignored.
+pub fn parse_directory_studio(content: &str) -> Result<Vec<ConnectionProfile>, String> {
+    let mut profiles = Vec::new();
+
+    for (attrs, body) in xml_elements(content, "connection") {
+        let get = |key: &str| {
+            attrs
+                .iter()
+                .find(|(k, _)| k.eq_ignore_ascii_case(key))
+                .map(|(_, v)| v.as_str())
+                .filter(|v| !v.is_empty())
+        };
+
+        let Some(host) = get("host") else {
+            continue;
+        };
+        let mut profile = imported_profile(get("name").unwrap_or(host), host);
+
+        if let Some(port) = get("port").and_then(|p| p.parse().ok()) {
+            profile.port = port;
+        }
+        profile.tls_mode = match get("encryptionMethod") {
+            Some("LDAPS") => TlsMode::Ldaps,
+            Some("START_TLS") => TlsMode::StartTls,
+            Some("NONE") => TlsMode::None,
+            _ => TlsMode::Auto,
+        };
+        if get("authMethod") != Some("NONE") {
+            profile.bind_dn = get("bindPrincipal").map(str::to_string);
+        }
+        profile.read_only = get("readOnly") == Some("true");
+        // Directory Studio stores the timeout in milliseconds
+        if let Some(ms) = get("timeout").and_then(|t| t.parse::<u64>().ok()) {
+            if ms > 0 {
+                profile.timeout_secs = ms.div_ceil(1000);
+            }
+        }
+
+        for (prop, _) in xml_elements(body, "extendedProperty") {
+            let name = prop.iter().find(|(k, _)| k == "name").map(|(_, v)| v);
+            let value = prop.iter().find(|(k, _)| k == "value").map(|(_, v)| v);
+            if let (Some(name), Some(value)) = (name, value) {
+                if name.to_lowercase().ends_with("basedn") && !value.is_empty() {
+                    profile.base_dn = Some(value.clone());
+                }
+            }
+        }
+
+        profile.neutralize_imported();
+        profiles.push(profile);
+    }
+
+    if profiles.is_empty() {
+        return Err("No <connection> entries found in file".to_string());
+    }
+    Ok(profiles)
+}
+
+/// Parse an OpenLDAP `ldap.conf` / `.ldaprc` file.
+///
+/// Each server listed in `URI` (or the legacy `HOST`/`PORT` pair) becomes a
+/// profile sharing the file's `BASE`, `BINDDN` and `NETWORK_TIMEOUT`.
+pub fn parse_ldap_conf(content: &str) -> Result<Vec<ConnectionProfile>, String> {
+    let mut uris: Vec<String> = Vec::new();
+    let mut hosts: Vec<String> = Vec::new();
+    let mut port: Option<u16> = None;
+    let mut base_dn = None;
+    let mut bind_dn = None;
+    let mut timeout = None;
+
+    for line in content.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let (key, value) = match line.split_once(char::is_whitespace) {
+            Some((k, v)) => (k.to_uppercase(), v.trim()),
+            None => continue,
+        };
+        match key.as_str() {
+            "URI" => uris.extend(value.split_whitespace().map(str::to_string)),
+            "HOST" => hosts.extend(value.split_whitespace().map(str::to_string)),
+            "PORT" => port = value.parse().ok(),
+            "BASE" => base_dn = Some(value.to_string()),
+            "BINDDN" => bind_dn = Some(value.to_string()),
+            "NETWORK_TIMEOUT" | "TIMEOUT" => timeout = value.parse::<u64>().ok(),
+            _ => {}
+        }
+    }
+
+    let mut servers: Vec<(String, Option<u16>, TlsMode)> = Vec::new();
+    for uri in &uris {
+        if let Some(server) = parse_ldap_uri(uri) {
+            servers.push(server);
+        }
+    }
+    for host in &hosts {
+        // HOST entries may carry their own ":port"
+        let (h, p) = split_host_port(host);
+        servers.push((h, p.or(port), TlsMode::Auto));
+    }
+
+    if servers.is_empty() {
+        return Err("No URI or HOST setting found in file".to_string());
+    }
+
+    Ok(servers
+        .into_iter()
+        .map(|(host, port, tls_mode)| {
+            let mut profile = imported_profile(&host, &host);
+            profile.tls_mode = tls_mode.clone();
+            profile.port = port.unwrap_or(match tls_mode {
+                TlsMode::Ldaps => 636,
+                _ => 389,
+            });
+            profile.base_dn = base_dn.clone();
+            profile.bind_dn = bind_dn.clone();
+            if let Some(secs) = timeout.filter(|s| *s > 0) {
+                profile.timeout_secs = secs;
+            }
+            profile.neutralize_imported();
+            profile
+        })
+        .collect())
+}
+
+/// Parse `ldap://host:port` / `ldaps://host` into (host, port, tls).
+/// `ldapi://` sockets are not supported and yield None.
+fn parse_ldap_uri(uri: &str) -> Option<(String, Option<u16>, TlsMode)> {
+    let (scheme, rest) = uri.split_once("://")?;
+    let tls_mode = match scheme.to_lowercase().as_str() {
+        "ldaps" => TlsMode::Ldaps,
+        "ldap" => TlsMode::Auto,
+        _ => return None,
+    };
+    let authority = rest.split('/').next().unwrap_or("");
+    if authority.is_empty() {
+        return None;
+    }
+    let (host, port) = split_host_port(authority);
+    Some((host, port, tls_mode))
+}
+
+/// Split "host:port" or "[v6addr]:port".
+fn split_host_port(s: &str) -> (String, Option<u16>) {
+    if let Some(rest) = s.strip_prefix('[') {
+        if let Some((host, tail)) = rest.split_once(']') {
+            let port = tail.strip_prefix(':').and_then(|p| p.parse().ok());
+            return (host.to_string(), port);
+        }
+    }
+    match s.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host.to_string(), port.parse().ok()),
+        _ => (s.to_string(), None),
+    }
+}
+
+/// A profile with loom's defaults, to be filled in by an importer.
+fn imported_profile(name: &str, host: &str) -> ConnectionProfile {
+    ConnectionProfile {
+        name: name.to_string(),
+        host: host.to_string(),
+        port: 389,
+        tls_mode: TlsMode::Auto,
+        bind_dn: None,
+        base_dn: None,
+        start_dn: None,
+        default_filter: None,
+        root_dse_attributes: Vec::new(),
+        favorites: Vec::new(),
+        saved_searches: Vec::new(),
+        edit_policy: None,
+        credential_method: CredentialMethod::Prompt,
+        password_command: None,
+        page_size: 500,
+        timeout_secs: 30,
+        pool_size: 3,
+        relax_rules: false,
+        sasl_mechanism: None,
+        authz_id: None,
+        proxy: None,
+        ssh_tunnel: None,
+        allow_commands: false,
+        dns_srv: false,
+        shuffle_hosts: false,
+        safety: None,
+        folder: None,
+        read_only: false,
+        offline: false,
+    }
+}
+
+/// Find every `<tag ...>` element and return its attributes and the text up
+/// to the matching `</tag>` (empty for self-closing elements).
+/// This is a minimal scanner for the flat files tools export, not a full XML parser.
+fn xml_elements<'a>(content: &'a str, tag: &str) -> Vec<(Vec<(String, String)>, &'a str)> {
+    let open = format!("<{}", tag);
+    let close = format!("</{}>", tag);
+    let mut out = Vec::new();
+    let mut pos = 0;
+
+    while let Some(found) = content[pos..].find(&open) {
+        let start = pos + found + open.len();
+        // Require a delimiter so "<connection" does not match "<connections"
+        match content[start..].chars().next() {
+            Some(c) if c.is_whitespace() || c == '>' || c == '/' => {}
+            _ => {
+                pos = start;
+                continue;
+            }
+        }
+
+        let Some(tag_len) = find_tag_end(&content[start..]) else {
+            break;
+        };
+        let tag_text = &content[start..start + tag_len];
+        let self_closing = tag_text.trim_end().ends_with('/');
+        let attrs = parse_xml_attrs(tag_text.trim_end_matches('/'));
+        pos = start + tag_len + 1;
+
+        let body = if self_closing {
+            ""
+        } else if let Some(end) = content[pos..].find(&close) {
+            let body = &content[pos..pos + end];
+            pos += end + close.len();
+            body
+        } else {
+            &content[pos..]
+        };
+        out.push((attrs, body));
+    }
+
+    out
+}
+
+/// Byte offset of the closing '>' of a tag, skipping quoted attribute values.
+fn find_tag_end(s: &str) -> Option<usize> {
+    let mut quote: Option<char> = None;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"') | (None, '\'') => quote = Some(c),
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_xml_attrs(s: &str) -> Vec<(String, String)> {
+    let mut attrs = Vec::new();
+    let mut rest = s;
+
+    while let Some(eq) = rest.find('=') {
+        let key = rest[..eq].trim().to_string();
+        let after = rest[eq + 1..].trim_start();
+        let Some(q) = after.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+            break;
+        };
+        let Some(end) = after[1..].find(q) else {
+            break;
+        };
+        attrs.push((key, xml_unescape(&after[1..1 + end])));
+        rest = &after[end + 2..];
+    }
+
+    attrs
+}
+
+fn xml_unescape(s: &str) -> String {
+    if !s.contains('&') {
+        return s.to_string();
+    }
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(amp) = rest.find('&') {
+        out.push_str(&rest[..amp]);
+        let tail = &rest[amp..];
+        let Some(semi) = tail.find(';') else {
+            out.push_str(tail);
+            return out;
+        };
+        let entity = &tail[1..semi];
+        let decoded = match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            e if e.starts_with("#x") => u32::from_str_radix(&e[2..], 16)
+                .ok()
+                .and_then(char::from_u32),
+            e if e.starts_with('#') => e[1..].parse().ok().and_then(char::from_u32),
+            _ => None,
+        };
+        match decoded {
+            Some(c) => out.push(c),
+            None => out.push_str(&tail[..=semi]),
+        }
+        rest = &tail[semi + 1..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STUDIO_XML: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<connections>
+  <connection id="a1" name="Corp &amp; Co" host="dc1.corp.example" port="636"
+      encryptionMethod="LDAPS" authMethod="SIMPLE"
+      bindPrincipal="CN=svc,OU=Service,DC=corp,DC=example" bindPassword="secret"
+      readOnly="true" timeout="15000">
+    <extendedProperties>
+      <extendedProperty name="ldapbrowser.baseDN" value="DC=corp,DC=example"/>
+    </extendedProperties>
+  </connection>
+  <connection id="b2" name="Anon" host="ldap.example.org" port="389"
+      encryptionMethod="START_TLS" authMethod="NONE" bindPrincipal="cn=ignored"/>
+</connections>
+"#;
+
+    #[test]
+    fn test_detect_source() {
+        assert_eq!(
+            ProfileSource::detect("connections.xml", ""),
+            ProfileSource::DirectoryStudio
+        );
+        assert_eq!(
+            ProfileSource::detect("/etc/openldap/ldap.conf", ""),
+            ProfileSource::LdapConf
+        );
+        assert_eq!(
+            ProfileSource::detect("~/.ldaprc", ""),
+            ProfileSource::LdapConf
+        );
+        assert_eq!(
+            ProfileSource::detect("profiles.toml", "[[connections]]"),
+            ProfileSource::Toml
+        );
+        assert_eq!(
+            ProfileSource::detect("export", "  <?xml version=\"1.0\"?>"),
+            ProfileSource::DirectoryStudio
+        );
+    }
+
+    #[test]
+    fn test_parse_directory_studio() {
+        let profiles = parse_directory_studio(STUDIO_XML).unwrap();
+        assert_eq!(profiles.len(), 2);
+
+        let corp = &profiles[0];
+        assert_eq!(corp.name, "Corp & Co");
+        assert_eq!(corp.host, "dc1.corp.example");
+        assert_eq!(corp.port, 636);
+        assert_eq!(corp.tls_mode, TlsMode::Ldaps);
+        assert_eq!(
+            corp.bind_dn.as_deref(),
+            Some("CN=svc,OU=Service,DC=corp,DC=example")
+        );
+        assert_eq!(corp.base_dn.as_deref(), Some("DC=corp,DC=example"));
+        assert!(corp.read_only);
+        assert_eq!(corp.timeout_secs, 15);
+
+        let anon = &profiles[1];
+        assert_eq!(anon.tls_mode, TlsMode::StartTls);
+        assert_eq!(anon.bind_dn, None);
+        assert_eq!(anon.base_dn, None);
+        assert!(!anon.read_only);
+    }
+
+    #[test]
+    fn test_parse_directory_studio_empty() {
+        assert!(parse_directory_studio("<connections/>").is_err());
+    }
+
+    #[test]
+    fn test_parse_ldap_conf() {
+        let conf = "\
+# site defaults
+BASE    dc=example,dc=com
+URI     ldaps://ldap1.example.com ldap://ldap2.example.com:3389
+BINDDN  cn=reader,dc=example,dc=com
+NETWORK_TIMEOUT 10
+TLS_REQCERT demand
+";
+        let profiles = parse_ldap_conf(conf).unwrap();
+        assert_eq!(profiles.len(), 2);
+        assert_eq!(profiles[0].host, "ldap1.example.com");
+        assert_eq!(profiles[0].port, 636);
+        assert_eq!(profiles[0].tls_mode, TlsMode::Ldaps);
+        assert_eq!(profiles[1].host, "ldap2.example.com");
+        assert_eq!(profiles[1].port, 3389);
+        assert_eq!(profiles[1].tls_mode, TlsMode::Auto);
+        for p in &profiles {
+            assert_eq!(p.base_dn.as_deref(), Some("dc=example,dc=com"));
+            assert_eq!(p.bind_dn.as_deref(), Some("cn=reader,dc=example,dc=com"));
+            assert_eq!(p.timeout_secs, 10);
+        }
+    }
+
+    #[test]
+    fn test_parse_ldap_conf_legacy_host() {
+        let profiles = parse_ldap_conf("HOST ldap.example.com\nPORT 10389\n").unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].host, "ldap.example.com");
+        assert_eq!(profiles[0].port, 10389);
+    }
+
+    #[test]
+    fn test_parse_ldap_conf_escapes_substitutions() {
+        let profiles = parse_ldap_conf("URI ldap://h\nBASE $(id)\nBINDDN ${USER}\n").unwrap();
+        assert_eq!(profiles[0].base_dn.as_deref(), Some("$$(id)"));
+        assert_eq!(profiles[0].bind_dn.as_deref(), Some("$${USER}"));
+    }
+
+    #[test]
+    fn test_parse_ldap_conf_without_server() {
+        assert!(parse_ldap_conf("BASE dc=example,dc=com\n").is_err());
+    }
+
+    #[test]
+    fn test_split_host_port_ipv6() {
+        assert_eq!(split_host_port("[::1]:389"), ("::1".to_string(), Some(389)));
+        assert_eq!(split_host_port("::1"), ("::1".to_string(), None));
+        assert_eq!(split_host_port("host"), ("host".to_string(), None));
+    }
+
+    #[test]
+    fn test_xml_unescape() {
+        assert_eq!(xml_unescape("a &lt;b&gt; &#65;&#x42;"), "a <b> AB");
+        assert_eq!(xml_unescape("broken &amp"), "broken &amp");
+    }
+
+    #[test]
+    fn test_parse_profiles_toml_passthrough() {
+        let toml = "[[connections]]\nname = \"T\"\nhost = \"h\"\n";
+        let (source, profiles) = parse_profiles("profiles.toml", toml).unwrap();
+        assert_eq!(source, ProfileSource::Toml);
+        assert_eq!(profiles[0].name, "T");
+    }
+}
@@ -0,0 +1,110 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// The open connection tabs, saved on quit so the next launch can offer to
+/// pick up where this one left off.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    pub tabs: Vec<SessionTab>,
+    /// Index into `tabs` of the focused tab.
+    #[serde(default)]
+    pub active: Option<usize>,
+    /// Index into `tabs` of the tab shown beside the focused one.
+    #[serde(default)]
+    pub split: Option<usize>,
+    /// Whether the split tab is drawn on the left.
+    #[serde(default)]
+    pub split_on_left: bool,
+    /// Whether the browser layout was showing, rather than the profiles.
+    #[serde(default)]
+    pub browser: bool,
+}
+
+/// One tab of a saved session.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SessionTab {
+    /// Name of the connection profile the tab was opened from.
+    pub profile: String,
+    /// DNs expanded in the tree. Only known for tabs that were on screen.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub expanded: Vec<String>,
+    /// The selected entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub selected: Option<String>,
+}
+
+impl Session {
+    /// Default location: `<config_dir>/loom-ldapbrowser/session.json`.
+    pub fn default_path() -> PathBuf {
+        dirs::config_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join("loom-ldapbrowser")
+            .join("session.json")
+    }
+
+    /// The session saved at `path`, if there is a readable one with tabs.
+    pub fn load(path: &Path) -> Option<Self> {
+        let text = std::fs::read_to_string(path).ok()?;
+        let session: Self = serde_json::from_str(&text).ok()?;
+        (!session.tabs.is_empty()).then_some(session)
+    }
+
+    /// Save to `path`, replacing the file atomically. A session without
+    /// tabs removes the file instead.
+    pub fn save(&self, path: &Path) -> Result<(), String> {
+        if self.tabs.is_empty() {
+            return match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                    Err(format!("Failed to remove {}: {}", path.display(), e))
+                }
+                _ => Ok(()),
+            };
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        let json = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize session: {}", e))?;
+        let tmp = path.with_extension("json.tmp");
+        std::fs::write(&tmp, json)
+            .and_then(|()| std::fs::rename(&tmp, path))
+            .map_err(|e| format!("Failed to write {}: {}", path.display(), e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session.json");
+        let session = Session {
+            tabs: vec![
+                SessionTab {
+                    profile: "prod".to_string(),
+                    expanded: vec!["ou=People,dc=x".to_string()],
+                    selected: Some("cn=a,ou=People,dc=x".to_string()),
+                },
+                SessionTab {
+                    profile: "dev".to_string(),
+                    ..SessionTab::default()
+                },
+            ],
+            active: Some(0),
+            split: Some(1),
+            split_on_left: false,
+            browser: true,
+        };
+        session.save(&path).unwrap();
+        assert_eq!(Session::load(&path), Some(session));
+
+        // Quitting with no tabs open forgets the session
+        Session::default().save(&path).unwrap();
+        assert!(!path.exists());
+        assert_eq!(Session::load(&path), None);
+    }
+}
@@ -0,0 +1,49 @@
+use std::path::Path;
+
+use serde::Serialize;
+
+/// Snapshot of session health, written periodically to the configured
+/// `stats_file` so long-running sessions can be monitored externally.
+#[derive(Debug, Serialize)]
+pub struct SessionStats {
+    /// When this snapshot was taken (RFC 3339, UTC).
+    pub timestamp: String,
+    pub pid: u32,
+    pub uptime_secs: u64,
+    /// Seconds since the last key press or mouse event.
+    pub idle_secs: u64,
+    pub open_connections: usize,
+    /// Actions waiting in the event queue when the snapshot was taken.
+    pub queued_actions: usize,
+    pub actions_processed: u64,
+    pub errors: u64,
+    pub recent_entries: usize,
+    pub locked: bool,
+    pub connections: Vec<ConnectionStats>,
+}
+
+/// Per-tab figures within `SessionStats`.
+#[derive(Debug, Serialize)]
+pub struct ConnectionStats {
+    pub label: String,
+    pub host: String,
+    pub server_type: String,
+    pub offline: bool,
+    pub read_only: bool,
+    pub dry_run: bool,
+    /// Tree nodes currently cached in memory.
+    pub tree_nodes: usize,
+    /// Attribute types in the cached schema (0 if not loaded).
+    pub schema_attribute_types: usize,
+}
+
+impl SessionStats {
+    /// Write the snapshot as pretty JSON. The file is replaced atomically so
+    /// a reader never sees a partial write.
+    pub fn write_to(&self, path: &Path) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        let tmp = path.with_extension("tmp");
+        std::fs::write(&tmp, json)?;
+        std::fs::rename(&tmp, path)
+    }
+}
@@ -1,7 +1,12 @@
+use std::path::PathBuf;
+
 use ratatui::style::{Color, Modifier, Style};
-use serde::Deserialize;
+use ratatui::text::Span;
+use serde::{Deserialize, Serialize};
 use tracing::warn;
 
+use crate::config::expand_home;
+
 /// Application theme with styles for every UI element.
 #[derive(Debug, Clone)]
 pub struct Theme {
@@ -24,6 +29,24 @@ pub struct Theme {
     pub popup_title: Style,
     pub command_prompt: Style,
     pub attr_operational: Style,
+    /// DN attribute types, e.g. `cn` in `cn=jdoe,dc=example`.
+    pub dn_attr: Style,
+    /// DN attribute values.
+    pub dn_value: Style,
+    /// The `=`, `,` and `+` between DN components.
+    pub dn_separator: Style,
+    /// Parentheses, `&`, `|`, `!`, comparison operators and `*` in filters.
+    pub filter_operator: Style,
+    /// Attribute names in filters.
+    pub filter_attr: Style,
+    /// Assertion values in filters.
+    pub filter_value: Style,
+    /// Numeric attribute values.
+    pub value_number: Style,
+    /// `TRUE` / `FALSE` attribute values.
+    pub value_boolean: Style,
+    /// Timestamps, raw or formatted.
+    pub value_date: Style,
 }
 
 impl Theme {
@@ -65,6 +88,15 @@ impl Theme {
             popup_title: Style::default().fg(mauve).add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(peach),
             attr_operational: Style::default().fg(overlay0),
+            dn_attr: Style::default().fg(lavender),
+            dn_value: Style::default().fg(text),
+            dn_separator: Style::default().fg(overlay0),
+            filter_operator: Style::default().fg(peach),
+            filter_attr: Style::default().fg(blue),
+            filter_value: Style::default().fg(green),
+            value_number: Style::default().fg(peach),
+            value_boolean: Style::default().fg(mauve),
+            value_date: Style::default().fg(yellow),
         }
     }
 
@@ -100,6 +132,15 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(Color::Rgb(0, 255, 0)),
             attr_operational: Style::default().fg(Color::Rgb(0, 120, 0)),
+            dn_attr: Style::default().fg(Color::Rgb(0, 255, 0)),
+            dn_value: Style::default().fg(Color::Rgb(0, 190, 0)),
+            dn_separator: Style::default().fg(Color::Rgb(0, 100, 0)),
+            filter_operator: Style::default().fg(Color::Rgb(0, 255, 0)),
+            filter_attr: Style::default().fg(Color::Rgb(120, 255, 120)),
+            filter_value: Style::default().fg(Color::Rgb(0, 190, 0)),
+            value_number: Style::default().fg(Color::Rgb(120, 255, 120)),
+            value_boolean: Style::default().fg(Color::Rgb(0, 255, 0)),
+            value_date: Style::default().fg(Color::Rgb(0, 220, 100)),
         }
     }
 
@@ -131,6 +172,15 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(Color::Blue),
             attr_operational: Style::default().fg(Color::Gray),
+            dn_attr: Style::default().fg(Color::Blue),
+            dn_value: Style::default().fg(Color::Black),
+            dn_separator: Style::default().fg(Color::DarkGray),
+            filter_operator: Style::default().fg(Color::Magenta),
+            filter_attr: Style::default().fg(Color::Blue),
+            filter_value: Style::default().fg(Color::Green),
+            value_number: Style::default().fg(Color::Magenta),
+            value_boolean: Style::default().fg(Color::Cyan),
+            value_date: Style::default().fg(Color::Green),
         }
     }
 
@@ -167,6 +217,15 @@ impl Theme {
             popup_title: Style::default().fg(yellow).add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(cyan),
             attr_operational: Style::default().fg(base01),
+            dn_attr: Style::default().fg(yellow),
+            dn_value: Style::default().fg(base0),
+            dn_separator: Style::default().fg(base01),
+            filter_operator: Style::default().fg(orange),
+            filter_attr: Style::default().fg(blue),
+            filter_value: Style::default().fg(green),
+            value_number: Style::default().fg(orange),
+            value_boolean: Style::default().fg(cyan),
+            value_date: Style::default().fg(green),
         }
     }
 
@@ -205,38 +264,173 @@ impl Theme {
                 .add_modifier(Modifier::BOLD),
             command_prompt: Style::default().fg(frost2),
             attr_operational: Style::default().fg(frost0),
+            dn_attr: Style::default().fg(frost2),
+            dn_value: Style::default().fg(snow0),
+            dn_separator: Style::default().fg(frost0),
+            filter_operator: Style::default().fg(aurora_orange),
+            filter_attr: Style::default().fg(frost2),
+            filter_value: Style::default().fg(aurora_green),
+            value_number: Style::default().fg(aurora_orange),
+            value_boolean: Style::default().fg(frost3),
+            value_date: Style::default().fg(aurora_green),
+        }
+    }
+
+    /// A built-in theme by name.
+    pub fn builtin(name: &str) -> Option<Self> {
+        match name.to_lowercase().as_str() {
+            "dark" => Some(Self::dark()),
+            "light" => Some(Self::light()),
+            "solarized" => Some(Self::solarized()),
+            "nord" => Some(Self::nord()),
+            "matrix" => Some(Self::matrix()),
+            _ => None,
         }
     }
 
     /// Load a theme by name. Supports built-in names and custom TOML paths.
     pub fn load(name: &str) -> Self {
-        match name.to_lowercase().as_str() {
-            "dark" => Self::dark(),
-            "light" => Self::light(),
-            "solarized" => Self::solarized(),
-            "nord" => Self::nord(),
-            "matrix" => Self::matrix(),
-            _ => {
-                // Try loading from config themes directory
-                if let Some(config_dir) = dirs::config_dir() {
-                    let theme_path = config_dir
-                        .join("loom-ldapbrowser")
-                        .join("themes")
-                        .join(format!("{}.toml", name));
-                    if theme_path.exists() {
-                        if let Ok(content) = std::fs::read_to_string(&theme_path) {
-                            if let Ok(theme_def) = toml::from_str::<ThemeDefinition>(&content) {
-                                return theme_def.to_theme();
-                            } else {
-                                warn!("Failed to parse theme file: {:?}", theme_path);
-                            }
-                        }
-                    }
+        Self::load_variant(name, ThemeVariant::default()).unwrap_or_else(|e| {
+            warn!("{}, using dark", e);
+            Self::dark()
+        })
+    }
+
+    /// Load a built-in theme, or a theme file: `<name>.toml` in the
+    /// themes directory, or a path ending in `.toml`. A file's `[light]`
+    /// or `[dark]` table is applied for the matching variant.
+    pub fn load_variant(name: &str, variant: ThemeVariant) -> Result<Self, String> {
+        if let Some(theme) = Self::builtin(name) {
+            return Ok(theme);
+        }
+        let path = if name.ends_with(".toml") {
+            expand_home(name)
+        } else {
+            themes_dir().join(format!("{}.toml", name))
+        };
+        if !path.exists() {
+            return Err(format!("Unknown theme '{}'", name));
+        }
+        let content = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Self::from_toml(&content, variant).map_err(|e| format!("{}: {}", path.display(), e))
+    }
+
+    /// Parse a theme file. Slots it leaves out keep the style of the theme
+    /// it `extends`, by default the built-in dark or light theme.
+    pub fn from_toml(content: &str, variant: ThemeVariant) -> Result<Self, String> {
+        let def: ThemeDefinition = toml::from_str(content).map_err(|e| e.to_string())?;
+        let base_name = def.extends.as_deref().unwrap_or(match variant {
+            ThemeVariant::Dark => "dark",
+            ThemeVariant::Light => "light",
+        });
+        let mut theme = Self::builtin(base_name)
+            .ok_or_else(|| format!("'{}' is not a built-in theme to extend", base_name))?;
+        def.colors.apply(&mut theme)?;
+        let overrides = match variant {
+            ThemeVariant::Dark => &def.dark,
+            ThemeVariant::Light => &def.light,
+        };
+        overrides.apply(&mut theme)?;
+        Ok(theme)
+    }
+
+    /// Spans for a DN with attribute types, values and separators styled
+    /// apart. Escaped separators (`\,`) stay part of the value.
+    pub fn dn_spans<'a>(&self, dn: &'a str) -> Vec<Span<'a>> {
+        let mut spans = Vec::new();
+        let mut start = 0;
+        let mut in_value = false;
+        let mut escaped = false;
+        for (i, c) in dn.char_indices() {
+            if escaped {
+                escaped = false;
+                continue;
+            }
+            let separator = match c {
+                '\\' => {
+                    escaped = true;
+                    false
                 }
-                warn!("Unknown theme '{}', using dark", name);
-                Self::dark()
+                '=' => !in_value,
+                ',' | '+' => in_value,
+                _ => false,
+            };
+            if separator {
+                let style = if in_value {
+                    self.dn_value
+                } else {
+                    self.dn_attr
+                };
+                spans.push(Span::styled(&dn[start..i], style));
+                spans.push(Span::styled(&dn[i..i + 1], self.dn_separator));
+                start = i + 1;
+                in_value = c == '=';
             }
         }
+        if start < dn.len() {
+            let style = if in_value {
+                self.dn_value
+            } else {
+                self.dn_attr
+            };
+            spans.push(Span::styled(&dn[start..], style));
+        }
+        spans
+    }
+
+    /// The style of each character of a (possibly partial) search filter:
+    /// operators, attribute names and assertion values.
+    pub fn filter_styles(&self, filter: &str) -> Vec<Style> {
+        let chars: Vec<char> = filter.chars().collect();
+        let mut styles = Vec::with_capacity(chars.len());
+        let mut in_value = false;
+        let mut i = 0;
+        while i < chars.len() {
+            let c = chars[i];
+            let style = match c {
+                '(' | ')' => {
+                    in_value = false;
+                    self.filter_operator
+                }
+                '&' | '|' | '!' if !in_value => self.filter_operator,
+                '~' | '>' | '<' if !in_value && chars.get(i + 1) == Some(&'=') => {
+                    styles.push(self.filter_operator);
+                    i += 1;
+                    in_value = true;
+                    self.filter_operator
+                }
+                '=' if !in_value => {
+                    in_value = true;
+                    self.filter_operator
+                }
+                '*' if in_value => self.filter_operator,
+                c if c.is_whitespace() => self.normal,
+                _ if in_value => self.filter_value,
+                _ => self.filter_attr,
+            };
+            styles.push(style);
+            i += 1;
+        }
+        styles
+    }
+
+    /// Spans for an attribute value: DNs by component, and numbers,
+    /// booleans and timestamps in their own styles. Anything else is
+    /// drawn in `style`.
+    pub fn value_spans<'a>(&self, value: &'a str, style: Style) -> Vec<Span<'a>> {
+        let kind_style = if is_number(value) {
+            self.value_number
+        } else if value.eq_ignore_ascii_case("true") || value.eq_ignore_ascii_case("false") {
+            self.value_boolean
+        } else if is_timestamp(value) {
+            self.value_date
+        } else if value.contains('=') && loom_core::dn::validate_dn(value).is_ok() {
+            return self.dn_spans(value);
+        } else {
+            style
+        };
+        vec![Span::styled(value, kind_style)]
     }
 }
 
@@ -246,62 +440,200 @@ impl Default for Theme {
     }
 }
 
-// --- TOML deserialization types ---
+fn is_number(value: &str) -> bool {
+    let digits = value.strip_prefix('-').unwrap_or(value);
+    !digits.is_empty() && digits.len() < 20 && digits.bytes().all(|b| b.is_ascii_digit())
+}
 
-#[derive(Debug, Deserialize)]
-struct ThemeDefinition {
-    colors: ThemeColors,
+/// A GeneralizedTime (`20240131120000Z`) or a formatted
+/// `2024-01-31 12:00:00` timestamp.
+fn is_timestamp(value: &str) -> bool {
+    let b = value.as_bytes();
+    let digits = |range: std::ops::Range<usize>| {
+        b.len() >= range.end && b[range].iter().all(u8::is_ascii_digit)
+    };
+    let generalized = digits(0..14) && matches!(b.get(14), Some(b'Z' | b'.' | b'+' | b'-'));
+    let formatted = digits(0..4)
+        && b.get(4) == Some(&b'-')
+        && digits(5..7)
+        && b.get(7) == Some(&b'-')
+        && digits(8..10);
+    generalized || formatted
+}
+
+/// Which table of a theme file applies on top of its `[colors]`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeVariant {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl ThemeVariant {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s.to_lowercase().as_str() {
+            "dark" => Some(ThemeVariant::Dark),
+            "light" => Some(ThemeVariant::Light),
+            _ => None,
+        }
+    }
 }
 
+/// Where user theme files live: `<config_dir>/loom-ldapbrowser/themes`.
+pub fn themes_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join("loom-ldapbrowser")
+        .join("themes")
+}
+
+/// Names of the built-in themes and the theme files in the themes directory.
+pub fn available_themes() -> Vec<String> {
+    let mut names: Vec<String> = ["dark", "light", "solarized", "nord", "matrix"]
+        .iter()
+        .map(|n| n.to_string())
+        .collect();
+    if let Ok(entries) = std::fs::read_dir(themes_dir()) {
+        let mut files: Vec<String> = entries
+            .flatten()
+            .filter_map(|e| {
+                let name = e.file_name().into_string().ok()?;
+                name.strip_suffix(".toml").map(str::to_string)
+            })
+            .collect();
+        files.sort();
+        names.extend(files);
+    }
+    names
+}
+
+// --- TOML deserialization types ---
+
 #[derive(Debug, Deserialize)]
-struct ThemeColors {
-    #[serde(default)]
-    border: StyleDef,
-    #[serde(default)]
-    border_focused: StyleDef,
-    #[serde(default)]
-    selected: StyleDef,
-    #[serde(default)]
-    header: StyleDef,
-    #[serde(default)]
-    normal: StyleDef,
-    #[serde(default)]
-    dimmed: StyleDef,
-    #[serde(default)]
-    error: StyleDef,
-    #[serde(default)]
-    warning: StyleDef,
-    #[serde(default)]
-    success: StyleDef,
-    #[serde(default = "default_white")]
-    status_bar_fg: String,
-    #[serde(default = "default_dark_gray")]
-    status_bar_bg: String,
-    #[serde(default)]
-    tab_active: StyleDef,
-    #[serde(default)]
-    tab_inactive: StyleDef,
-    #[serde(default)]
-    tree_node: StyleDef,
-    #[serde(default)]
-    tree_node_expanded: StyleDef,
-    #[serde(default)]
-    tree_node_selected: StyleDef,
+struct ThemeDefinition {
+    /// Built-in theme providing the slots the file leaves out.
     #[serde(default)]
-    popup_border: StyleDef,
+    extends: Option<String>,
     #[serde(default)]
-    popup_title: StyleDef,
+    colors: ThemeColors,
     #[serde(default)]
-    command_prompt: StyleDef,
+    dark: ThemeColors,
     #[serde(default)]
-    attr_operational: StyleDef,
+    light: ThemeColors,
 }
 
-fn default_white() -> String {
-    "white".to_string()
+#[derive(Debug, Default, Deserialize)]
+struct ThemeColors {
+    border: Option<StyleDef>,
+    border_focused: Option<StyleDef>,
+    selected: Option<StyleDef>,
+    header: Option<StyleDef>,
+    normal: Option<StyleDef>,
+    dimmed: Option<StyleDef>,
+    error: Option<StyleDef>,
+    warning: Option<StyleDef>,
+    success: Option<StyleDef>,
+    status_bar: Option<StyleDef>,
+    status_bar_fg: Option<String>,
+    status_bar_bg: Option<String>,
+    tab_active: Option<StyleDef>,
+    tab_inactive: Option<StyleDef>,
+    tree_node: Option<StyleDef>,
+    tree_node_expanded: Option<StyleDef>,
+    tree_node_selected: Option<StyleDef>,
+    popup_border: Option<StyleDef>,
+    popup_title: Option<StyleDef>,
+    command_prompt: Option<StyleDef>,
+    attr_operational: Option<StyleDef>,
+    dn_attr: Option<StyleDef>,
+    dn_value: Option<StyleDef>,
+    dn_separator: Option<StyleDef>,
+    filter_operator: Option<StyleDef>,
+    filter_attr: Option<StyleDef>,
+    filter_value: Option<StyleDef>,
+    value_number: Option<StyleDef>,
+    value_boolean: Option<StyleDef>,
+    value_date: Option<StyleDef>,
 }
-fn default_dark_gray() -> String {
-    "dark_gray".to_string()
+
+impl ThemeColors {
+    /// Override the slots this table sets.
+    fn apply(&self, theme: &mut Theme) -> Result<(), String> {
+        let slots = [
+            (&self.border, &mut theme.border, "border"),
+            (
+                &self.border_focused,
+                &mut theme.border_focused,
+                "border_focused",
+            ),
+            (&self.selected, &mut theme.selected, "selected"),
+            (&self.header, &mut theme.header, "header"),
+            (&self.normal, &mut theme.normal, "normal"),
+            (&self.dimmed, &mut theme.dimmed, "dimmed"),
+            (&self.error, &mut theme.error, "error"),
+            (&self.warning, &mut theme.warning, "warning"),
+            (&self.success, &mut theme.success, "success"),
+            (&self.status_bar, &mut theme.status_bar, "status_bar"),
+            (&self.tab_active, &mut theme.tab_active, "tab_active"),
+            (&self.tab_inactive, &mut theme.tab_inactive, "tab_inactive"),
+            (&self.tree_node, &mut theme.tree_node, "tree_node"),
+            (
+                &self.tree_node_expanded,
+                &mut theme.tree_node_expanded,
+                "tree_node_expanded",
+            ),
+            (
+                &self.tree_node_selected,
+                &mut theme.tree_node_selected,
+                "tree_node_selected",
+            ),
+            (&self.popup_border, &mut theme.popup_border, "popup_border"),
+            (&self.popup_title, &mut theme.popup_title, "popup_title"),
+            (
+                &self.command_prompt,
+                &mut theme.command_prompt,
+                "command_prompt",
+            ),
+            (
+                &self.attr_operational,
+                &mut theme.attr_operational,
+                "attr_operational",
+            ),
+            (&self.dn_attr, &mut theme.dn_attr, "dn_attr"),
+            (&self.dn_value, &mut theme.dn_value, "dn_value"),
+            (&self.dn_separator, &mut theme.dn_separator, "dn_separator"),
+            (
+                &self.filter_operator,
+                &mut theme.filter_operator,
+                "filter_operator",
+            ),
+            (&self.filter_attr, &mut theme.filter_attr, "filter_attr"),
+            (&self.filter_value, &mut theme.filter_value, "filter_value"),
+            (&self.value_number, &mut theme.value_number, "value_number"),
+            (
+                &self.value_boolean,
+                &mut theme.value_boolean,
+                "value_boolean",
+            ),
+            (&self.value_date, &mut theme.value_date, "value_date"),
+        ];
+        for (def, style, slot) in slots {
+            if let Some(def) = def {
+                *style = def
+                    .apply(*style)
+                    .map_err(|e| format!("{} in {}", e, slot))?;
+            }
+        }
+        // Older theme files set the status bar colors on their own
+        if let Some(ref fg) = self.status_bar_fg {
+            theme.status_bar = theme.status_bar.fg(try_parse_color(fg)?);
+        }
+        if let Some(ref bg) = self.status_bar_bg {
+            theme.status_bar = theme.status_bar.bg(try_parse_color(bg)?);
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Default, Deserialize)]
@@ -315,61 +647,38 @@ struct StyleDef {
 }
 
 impl StyleDef {
-    fn to_style(&self) -> Style {
-        let mut style = Style::default();
+    /// `base` with the colors this sets; modifiers, when given, replace
+    /// the base's.
+    fn apply(&self, mut style: Style) -> Result<Style, String> {
         if let Some(ref fg) = self.fg {
-            style = style.fg(parse_color(fg));
+            style = style.fg(try_parse_color(fg)?);
         }
         if let Some(ref bg) = self.bg {
-            style = style.bg(parse_color(bg));
+            style = style.bg(try_parse_color(bg)?);
         }
         if let Some(ref mods) = self.modifiers {
-            for m in mods.split('|') {
-                match m.trim().to_uppercase().as_str() {
-                    "BOLD" => style = style.add_modifier(Modifier::BOLD),
-                    "ITALIC" => style = style.add_modifier(Modifier::ITALIC),
-                    "UNDERLINED" => style = style.add_modifier(Modifier::UNDERLINED),
-                    "DIM" => style = style.add_modifier(Modifier::DIM),
-                    _ => {}
-                }
+            style = style.remove_modifier(Modifier::all());
+            for m in mods.split('|').filter(|m| !m.trim().is_empty()) {
+                style = match m.trim().to_uppercase().as_str() {
+                    "BOLD" => style.add_modifier(Modifier::BOLD),
+                    "ITALIC" => style.add_modifier(Modifier::ITALIC),
+                    "UNDERLINED" => style.add_modifier(Modifier::UNDERLINED),
+                    "DIM" => style.add_modifier(Modifier::DIM),
+                    "REVERSED" => style.add_modifier(Modifier::REVERSED),
+                    other => return Err(format!("unknown modifier '{}'", other)),
+                };
             }
         }
-        style
+        Ok(style)
     }
 }
 
-impl ThemeDefinition {
-    fn to_theme(&self) -> Theme {
-        let c = &self.colors;
-        Theme {
-            border: c.border.to_style(),
-            border_focused: c.border_focused.to_style(),
-            selected: c.selected.to_style(),
-            header: c.header.to_style(),
-            normal: c.normal.to_style(),
-            dimmed: c.dimmed.to_style(),
-            error: c.error.to_style(),
-            warning: c.warning.to_style(),
-            success: c.success.to_style(),
-            status_bar: Style::default()
-                .fg(parse_color(&c.status_bar_fg))
-                .bg(parse_color(&c.status_bar_bg)),
-            tab_active: c.tab_active.to_style(),
-            tab_inactive: c.tab_inactive.to_style(),
-            tree_node: c.tree_node.to_style(),
-            tree_node_expanded: c.tree_node_expanded.to_style(),
-            tree_node_selected: c.tree_node_selected.to_style(),
-            popup_border: c.popup_border.to_style(),
-            popup_title: c.popup_title.to_style(),
-            command_prompt: c.command_prompt.to_style(),
-            attr_operational: c.attr_operational.to_style(),
-        }
-    }
-}
-
-fn parse_color(s: &str) -> Color {
+/// Parse a color: a name, `#RRGGBB` truecolor, or a 256-color palette
+/// index as `42` or `color42`.
+fn try_parse_color(s: &str) -> Result<Color, String> {
     let s = s.trim().to_lowercase();
-    match s.as_str() {
+    let color = match s.as_str() {
+        "reset" | "default" => Color::Reset,
         "black" => Color::Black,
         "red" => Color::Red,
         "green" => Color::Green,
@@ -387,6 +696,12 @@ fn parse_color(s: &str) -> Color {
         "light_cyan" | "lightcyan" => Color::LightCyan,
         "white" => Color::White,
         _ => {
+            let index = s.strip_prefix("color").unwrap_or(&s);
+            if index.len() <= 3 {
+                if let Ok(n) = index.parse::<u8>() {
+                    return Ok(Color::Indexed(n));
+                }
+            }
             // Try #RRGGBB or RRGGBB
             let hex = s.strip_prefix('#').unwrap_or(&s);
             if hex.len() == 6 {
@@ -395,12 +710,13 @@ fn parse_color(s: &str) -> Color {
                     u8::from_str_radix(&hex[2..4], 16),
                     u8::from_str_radix(&hex[4..6], 16),
                 ) {
-                    return Color::Rgb(r, g, b);
+                    return Ok(Color::Rgb(r, g, b));
                 }
             }
-            Color::White
+            return Err(format!("unknown color '{}'", s));
         }
-    }
+    };
+    Ok(color)
 }
 
 #[cfg(test)]
@@ -409,15 +725,15 @@ mod tests {
 
     #[test]
     fn test_parse_color_names() {
-        assert_eq!(parse_color("red"), Color::Red);
-        assert_eq!(parse_color("dark_gray"), Color::DarkGray);
-        assert_eq!(parse_color("DarkGray"), Color::DarkGray);
+        assert_eq!(try_parse_color("red").unwrap(), Color::Red);
+        assert_eq!(try_parse_color("dark_gray").unwrap(), Color::DarkGray);
+        assert_eq!(try_parse_color("DarkGray").unwrap(), Color::DarkGray);
     }
 
     #[test]
     fn test_parse_color_hex() {
-        assert_eq!(parse_color("#FF0000"), Color::Rgb(255, 0, 0));
-        assert_eq!(parse_color("00FF00"), Color::Rgb(0, 255, 0));
+        assert_eq!(try_parse_color("#FF0000").unwrap(), Color::Rgb(255, 0, 0));
+        assert_eq!(try_parse_color("00FF00").unwrap(), Color::Rgb(0, 255, 0));
     }
 
     #[test]
@@ -428,4 +744,98 @@ mod tests {
         let _nord = Theme::load("nord");
         let _matrix = Theme::load("matrix");
     }
+
+    #[test]
+    fn test_parse_color_indexed() {
+        assert_eq!(try_parse_color("42").unwrap(), Color::Indexed(42));
+        assert_eq!(try_parse_color("color208").unwrap(), Color::Indexed(208));
+        assert_eq!(try_parse_color("000000").unwrap(), Color::Rgb(0, 0, 0));
+        assert!(try_parse_color("256").is_err());
+        assert!(try_parse_color("mauve").is_err());
+    }
+
+    #[test]
+    fn test_theme_file_variants() {
+        let content = r##"
+            [colors]
+            border = { fg = "color240" }
+            dn_attr = { fg = "#ff8800", modifiers = "BOLD" }
+
+            [light]
+            normal = { fg = "black" }
+        "##;
+        let dark = Theme::from_toml(content, ThemeVariant::Dark).unwrap();
+        assert_eq!(dark.border.fg, Some(Color::Indexed(240)));
+        assert_eq!(dark.dn_attr.fg, Some(Color::Rgb(255, 136, 0)));
+        assert!(dark.dn_attr.add_modifier.contains(Modifier::BOLD));
+        // Unset slots come from the built-in theme of the variant
+        assert_eq!(dark.normal, Theme::dark().normal);
+        assert_eq!(dark.selected, Theme::dark().selected);
+
+        let light = Theme::from_toml(content, ThemeVariant::Light).unwrap();
+        assert_eq!(light.normal.fg, Some(Color::Black));
+        assert_eq!(light.selected, Theme::light().selected);
+
+        let err = Theme::from_toml("[colors]\nerror = { fg = \"nope\" }", ThemeVariant::Dark);
+        assert_eq!(err.unwrap_err(), "unknown color 'nope' in error");
+        let err = Theme::from_toml("extends = \"ocean\"", ThemeVariant::Dark);
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn test_filter_styles() {
+        let theme = Theme::dark();
+        let filter = "(&(cn>=a*)(!(x=1)))";
+        let styles = theme.filter_styles(filter);
+        assert_eq!(styles.len(), filter.len());
+        let classes: String = styles
+            .iter()
+            .map(|s| match *s {
+                s if s == theme.filter_operator => 'o',
+                s if s == theme.filter_attr => 'a',
+                _ => 'v',
+            })
+            .collect();
+        assert_eq!(classes, "oooaaoovoooooaovooo");
+    }
+
+    #[test]
+    fn test_value_spans() {
+        let theme = Theme::dark();
+        let style_of = |v| theme.value_spans(v, theme.normal)[0].style;
+        assert_eq!(style_of("512"), theme.value_number);
+        assert_eq!(style_of("-1"), theme.value_number);
+        assert_eq!(style_of("TRUE"), theme.value_boolean);
+        assert_eq!(style_of("20240131120000.0Z"), theme.value_date);
+        assert_eq!(style_of("2024-01-31 12:00:00 UTC"), theme.value_date);
+        assert_eq!(style_of("hello"), theme.normal);
+        assert_eq!(style_of("1+1=2"), theme.normal);
+        assert_eq!(theme.value_spans("cn=a,dc=x", theme.normal).len(), 7);
+    }
+
+    #[test]
+    fn test_dn_spans() {
+        let theme = Theme::dark();
+        let spans = theme.dn_spans("cn=Doe\\, John+uid=jd,dc=x");
+        let parts: Vec<&str> = spans.iter().map(|s| s.content.as_ref()).collect();
+        assert_eq!(
+            parts,
+            [
+                "cn",
+                "=",
+                "Doe\\, John",
+                "+",
+                "uid",
+                "=",
+                "jd",
+                ",",
+                "dc",
+                "=",
+                "x"
+            ]
+        );
+        assert_eq!(spans[0].style, theme.dn_attr);
+        assert_eq!(spans[1].style, theme.dn_separator);
+        assert_eq!(spans[2].style, theme.dn_value);
+    }
 }
@@ -10,7 +10,18 @@ pub struct FuzzyMatch {
     pub score: u32,
 }
 
-/// A fuzzy matcher backed by nucleo.
+/// Bonus for a case-insensitive exact match.
+const EXACT_BONUS: u32 = 10_000;
+/// Bonus when the query is a prefix of the item (or of a DN component's value).
+const PREFIX_BONUS: u32 = 5_000;
+/// Bonus when the query matches the starts of camelCase words, e.g. `san`
+/// for `sAMAccountName`.
+const BOUNDARY_BONUS: u32 = 2_000;
+
+/// A fuzzy matcher backed by nucleo, with extra ranking for LDAP names:
+/// exact and prefix matches first, then camelCase word-start matches.
+/// DNs are matched per component, so `alice,people` finds
+/// `cn=Alice,ou=People,dc=example,dc=com`.
 pub struct FuzzyFilter {
     matcher: Matcher,
 }
@@ -23,7 +34,8 @@ impl FuzzyFilter {
     }
 
     /// Filter a list of strings by a query pattern.
-    /// Returns indices and scores, sorted by score (best first).
+    /// Returns indices and scores, sorted by score (best first); ties keep
+    /// the shorter item first, then the original order.
     pub fn filter(&mut self, query: &str, items: &[String]) -> Vec<FuzzyMatch> {
         if query.is_empty() {
             return items
@@ -33,27 +45,144 @@ impl FuzzyFilter {
                 .collect();
         }
 
-        let pattern = Pattern::new(
-            query,
-            CaseMatching::Ignore,
-            Normalization::Smart,
-            nucleo::pattern::AtomKind::Fuzzy,
-        );
-
-        let mut buf = Vec::new();
         let mut matches: Vec<FuzzyMatch> = items
             .iter()
             .enumerate()
             .filter_map(|(i, item)| {
-                let haystack = Utf32Str::new(item, &mut buf);
-                let score = pattern.score(haystack, &mut self.matcher)?;
+                let score = self.score(query, item)?;
                 Some(FuzzyMatch { index: i, score })
             })
             .collect();
 
-        matches.sort_by(|a, b| b.score.cmp(&a.score));
+        matches.sort_by_key(|m| {
+            (
+                std::cmp::Reverse(m.score),
+                items[m.index].chars().count(),
+                m.index,
+            )
+        });
         matches
     }
+
+    /// Score a single item against `query`, or `None` if it doesn't match.
+    pub fn score(&mut self, query: &str, item: &str) -> Option<u32> {
+        let query = query.trim();
+        if query.is_empty() {
+            return Some(0);
+        }
+        if query.contains(',') && looks_like_dn(item) {
+            return self.score_dn_components(query, item);
+        }
+
+        let base = self.fuzzy_score(query, item)?;
+        let bonus = if query.to_lowercase() == item.to_lowercase() {
+            EXACT_BONUS
+        } else if starts_with_ci(item, query) || dn_value_prefix(item, query) {
+            PREFIX_BONUS
+        } else if matches_word_starts(query, item) {
+            BOUNDARY_BONUS
+        } else {
+            0
+        };
+        Some(base.saturating_add(bonus))
+    }
+
+    /// Match each comma-separated part of the query against the DN's
+    /// components in order (each part must land in a later component than
+    /// the one before).
+    fn score_dn_components(&mut self, query: &str, dn: &str) -> Option<u32> {
+        let components: Vec<&str> = dn.split(',').map(str::trim).collect();
+        let mut next = 0;
+        let mut total: u32 = 0;
+        for part in query.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+            let (offset, score) = components[next..]
+                .iter()
+                .enumerate()
+                .find_map(|(i, component)| Some((i, self.score(part, component)?)))?;
+            total = total.saturating_add(score);
+            next += offset + 1;
+        }
+        Some(total)
+    }
+
+    fn fuzzy_score(&mut self, query: &str, item: &str) -> Option<u32> {
+        let pattern = Pattern::new(
+            query,
+            CaseMatching::Ignore,
+            Normalization::Smart,
+            nucleo::pattern::AtomKind::Fuzzy,
+        );
+        let mut buf = Vec::new();
+        pattern.score(Utf32Str::new(item, &mut buf), &mut self.matcher)
+    }
+}
+
+/// True if `item` has at least one `attr=value` RDN.
+fn looks_like_dn(item: &str) -> bool {
+    item.split(',').next().is_some_and(|rdn| rdn.contains('='))
+}
+
+fn starts_with_ci(item: &str, prefix: &str) -> bool {
+    let mut item_chars = item.chars().flat_map(char::to_lowercase);
+    prefix
+        .chars()
+        .flat_map(char::to_lowercase)
+        .all(|c| item_chars.next() == Some(c))
+}
+
+/// True if the query is a prefix of one of the DN's RDN values, so `ali`
+/// ranks `cn=Alice,...` as highly as a name starting with `ali`.
+fn dn_value_prefix(item: &str, query: &str) -> bool {
+    looks_like_dn(item)
+        && item.split(',').any(|rdn| {
+            rdn.split_once('=')
+                .is_some_and(|(_, value)| starts_with_ci(value.trim(), query))
+        })
+}
+
+/// Characters that begin a word: the first character, anything after a
+/// separator, a lower-to-upper case change (`accountName` → `N`), and the
+/// last capital of a run followed by lowercase (`sAMAccount` → `A` of
+/// `Account`).
+fn word_starts(item: &str) -> Vec<char> {
+    let chars: Vec<char> = item.chars().collect();
+    let mut starts = Vec::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            continue;
+        }
+        let is_start = match i.checked_sub(1).map(|p| chars[p]) {
+            None => true,
+            Some(prev) if !prev.is_alphanumeric() => true,
+            Some(prev) if prev.is_lowercase() && c.is_uppercase() => true,
+            Some(prev) if prev.is_alphabetic() != c.is_alphabetic() => true,
+            Some(prev) => {
+                prev.is_uppercase()
+                    && c.is_uppercase()
+                    && chars.get(i + 1).is_some_and(|n| n.is_lowercase())
+            }
+        };
+        if is_start {
+            starts.extend(c.to_lowercase());
+        }
+    }
+    starts
+}
+
+/// True if the query's letters are, in order, the starts of words in `item`
+/// (`san` → `sAMAccountName`, `gn` → `givenName`). Needs at least two
+/// characters so single letters don't all count as boundary hits.
+fn matches_word_starts(query: &str, item: &str) -> bool {
+    let query: Vec<char> = query
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect();
+    if query.len() < 2 {
+        return false;
+    }
+    let mut starts = word_starts(item).into_iter();
+    query.iter().all(|q| starts.any(|c| c == *q))
 }
 
 impl Default for FuzzyFilter {
@@ -86,4 +215,74 @@ mod tests {
         assert_eq!(matches.len(), 1);
         assert_eq!(matches[0].index, 0);
     }
+
+    #[test]
+    fn test_camel_case_exact_match_ranks_first() {
+        let mut filter = FuzzyFilter::new();
+        let items = vec![
+            "msDS-SupportedEncryptionTypes".to_string(),
+            "sAMAccountType".to_string(),
+            "sAMAccountName".to_string(),
+        ];
+        let matches = filter.filter("samaccountname", &items);
+        assert_eq!(matches[0].index, 2);
+    }
+
+    #[test]
+    fn test_word_start_matches() {
+        assert_eq!(word_starts("sAMAccountName"), vec!['s', 'a', 'a', 'n']);
+        assert!(matches_word_starts("san", "sAMAccountName"));
+        assert!(matches_word_starts("gn", "givenName"));
+        assert!(!matches_word_starts("gn", "objectClass"));
+
+        let mut filter = FuzzyFilter::new();
+        let items = vec!["displayName".to_string(), "givenName".to_string()];
+        let matches = filter.filter("gn", &items);
+        assert_eq!(matches[0].index, 1);
+    }
+
+    #[test]
+    fn test_prefix_beats_substring() {
+        let mut filter = FuzzyFilter::new();
+        let items = vec![
+            "homeMail".to_string(),
+            "mail".to_string(),
+            "mailHost".to_string(),
+        ];
+        let matches = filter.filter("mail", &items);
+        let order: Vec<usize> = matches.iter().map(|m| m.index).collect();
+        assert_eq!(order, vec![1, 2, 0]);
+    }
+
+    #[test]
+    fn test_dn_component_matching() {
+        let mut filter = FuzzyFilter::new();
+        let items = vec![
+            "cn=People Admins,ou=Groups,dc=example,dc=com".to_string(),
+            "cn=Alice,ou=People,dc=example,dc=com".to_string(),
+            "cn=Bob,ou=People,dc=example,dc=com".to_string(),
+        ];
+        let matches = filter.filter("alice,people", &items);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].index, 1);
+
+        // Query parts must follow the DN's component order
+        assert!(filter.filter("people,alice", &items[1..2]).is_empty());
+
+        // An RDN value prefix counts as a prefix match
+        let matches = filter.filter("bo", &items);
+        assert_eq!(matches[0].index, 2);
+    }
+
+    #[test]
+    fn test_non_ascii_items() {
+        let mut filter = FuzzyFilter::new();
+        let items = vec![
+            "cn=Zoë Ångström,dc=example".to_string(),
+            "cn=Zed".to_string(),
+        ];
+        let matches = filter.filter("zoë", &items);
+        assert_eq!(matches[0].index, 0);
+        assert!(matches_word_starts("zå", "Zoë Ångström"));
+    }
 }
@@ -1,3 +1,4 @@
 pub mod breadcrumb;
 pub mod fuzzy_input;
+pub mod scroll;
 pub mod spinner;
@@ -0,0 +1,53 @@
+/// Scroll a list's viewport `delta` rows (negative is up) without moving
+/// the selection unless it would leave the screen.
+///
+/// `shown(offset)` is how many rows fit on screen from `offset`. Returns
+/// the new offset, clamped so the view stops at the last row, and the
+/// selection, moved only when it scrolled out of view, to the nearest row
+/// still shown.
+pub fn scroll_view(
+    offset: usize,
+    selected: Option<usize>,
+    len: usize,
+    delta: isize,
+    shown: impl Fn(usize) -> usize,
+) -> (usize, Option<usize>) {
+    if len == 0 {
+        return (0, selected);
+    }
+    let fits = |offset: usize| shown(offset).max(1);
+    let max = (0..len).find(|&o| o + fits(o) >= len).unwrap_or(len - 1);
+    let offset = offset.saturating_add_signed(delta).min(max);
+    let last = (offset + fits(offset)).min(len) - 1;
+    (offset, selected.map(|s| s.clamp(offset, last)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scroll_view() {
+        // 20 rows, 5 on screen: scrolling down drags the selection along
+        // only once it reaches the top edge
+        assert_eq!(scroll_view(0, Some(2), 20, 3, |_| 5), (3, Some(3)));
+        assert_eq!(scroll_view(0, Some(4), 20, 3, |_| 5), (3, Some(4)));
+        // The view stops with the last row at the bottom
+        assert_eq!(scroll_view(10, Some(12), 20, 30, |_| 5), (15, Some(15)));
+        assert_eq!(scroll_view(3, Some(7), 20, -10, |_| 5), (0, Some(4)));
+        // Rows of different heights in a view 4 lines tall
+        let heights = [1, 3, 1, 1, 1, 1];
+        let shown = |o: usize| {
+            let mut lines = 0;
+            heights[o..]
+                .iter()
+                .take_while(|h| {
+                    lines += **h;
+                    lines <= 4
+                })
+                .count()
+        };
+        assert_eq!(scroll_view(0, Some(0), 6, 10, shown), (2, Some(2)));
+        assert_eq!(scroll_view(0, Some(0), 0, 3, |_| 5), (0, Some(0)));
+    }
+}